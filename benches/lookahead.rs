@@ -0,0 +1,95 @@
+//! Micro-benchmark for parser lookahead on pathological inputs.
+//!
+//! Run with: cargo bench --bench lookahead
+//!
+//! Parses function definitions with very long parameter lists, where every
+//! parameter triggers a lookahead check, and reports time per parameter.
+//! Linear scaling shows up as a flat per-parameter cost. The second table
+//! does the same for long expressions: a chain of `+` terms and a wide IN
+//! list, where the parser checks every token for a binary or postfix
+//! operator. The third compares draining a deep token buffer from the front
+//! with `Vec::remove(0)` (the previous buffer) against `VecDeque::pop_front`
+//! (the current one).
+
+use std::collections::VecDeque;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use vibesql::parser::MAX_LOOKAHEAD;
+use vibesql::Parser;
+
+fn time<F: FnMut()>(iterations: u32, mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    start.elapsed() / iterations
+}
+
+fn long_parameter_list(n: usize) -> String {
+    let params: Vec<String> = (0..n).map(|i| format!("p{} INT64", i)).collect();
+    format!(
+        "CREATE FUNCTION f({}) RETURNS INT64 AS (1)",
+        params.join(", ")
+    )
+}
+
+fn main() {
+    println!("parse long parameter lists");
+    for n in [1_000, 4_000, 16_000] {
+        let sql = long_parameter_list(n);
+        let mut depth = 0;
+        let elapsed = time(5, || {
+            let mut parser = Parser::new(&sql);
+            black_box(parser.parse().unwrap());
+            depth = parser.metrics().max_lookahead;
+        });
+        assert!(depth <= MAX_LOOKAHEAD);
+        println!(
+            "  {:>6} params: {:>10.3?} total, {:>6.1} ns/param, max lookahead {}",
+            n,
+            elapsed,
+            elapsed.as_nanos() as f64 / n as f64,
+            depth
+        );
+    }
+
+    println!("parse long expressions");
+    for n in [1_000, 4_000, 10_000] {
+        let terms: Vec<String> = (0..n).map(|i| format!("c{}", i)).collect();
+        let chain = format!("SELECT {}", terms.join(" + "));
+        let in_list = format!("SELECT * FROM t WHERE a IN ({})", terms.join(", "));
+        let per_term = |sql: &str| {
+            let elapsed = time(5, || {
+                black_box(Parser::new(sql).parse().unwrap());
+            });
+            elapsed.as_nanos() as f64 / n as f64
+        };
+        println!(
+            "  {:>6} terms: {:>6.1} ns/term chained +, {:>6.1} ns/term IN list",
+            n,
+            per_term(&chain),
+            per_term(&in_list)
+        );
+    }
+
+    println!("drain a buffer of n tokens from the front");
+    for n in [1_000, 4_000, 16_000] {
+        let vec = time(5, || {
+            let mut buffer: Vec<usize> = (0..n).collect();
+            while !buffer.is_empty() {
+                black_box(buffer.remove(0));
+            }
+        });
+        let deque = time(5, || {
+            let mut buffer: VecDeque<usize> = (0..n).collect();
+            while let Some(token) = buffer.pop_front() {
+                black_box(token);
+            }
+        });
+        println!(
+            "  {:>6} tokens: Vec::remove(0) {:>10.3?}, VecDeque::pop_front {:>10.3?}",
+            n, vec, deque
+        );
+    }
+}
@@ -5,13 +5,19 @@ use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 
+use vibesql::ast::visit::VisitorMut;
+use vibesql::catalog::{ApplyOutcome, StatsPack};
+use vibesql::eval::{evaluate, AggregateOptions, AggregateRegistry, Params};
+use vibesql::types::Value;
 use vibesql::{
-    AnalyzedQuery, Analyzer, Catalog, ColumnSchema, ExprKind, JoinCondition, JoinType,
-    MemoryCatalog, Parser, Query, QueryBody, Select, SelectItem, SqlType, StatementKind, TableRef,
+    AnalyzedQuery, Analyzer, Catalog, ColumnSchema, JoinCondition, JoinType, MemoryCatalog, Parser,
+    Query, QueryBody, Select, SelectItem, SqlType, StatementKind, TableRef, TableRefKind,
     TableSchemaBuilder,
 };
 
-use crate::execution::{ExecutionRow, TableInfo};
+use crate::execution::{
+    aggregate_name, cell_text, AggregateCall, ExecutionRow, ExtractAggregates, GroupRow, TableInfo,
+};
 use crate::result::{QueryResult, Row};
 
 /// A simple CSV database.
@@ -34,6 +40,9 @@ impl CsvDatabase {
             tables: HashMap::new(),
         };
         db.catalog.register_builtins();
+        db.catalog
+            .register_pack(&StatsPack::new())
+            .expect("stats pack conflicts with built-ins");
         db.discover_tables()?;
         db.register_information_schema();
         Ok(db)
@@ -51,24 +60,31 @@ impl CsvDatabase {
 
         // Register TABLES table
         let tables_schema = TableSchemaBuilder::new("tables")
-            .column(ColumnSchema::new("table_catalog", SqlType::Varchar))
-            .column(ColumnSchema::new("table_schema", SqlType::Varchar))
-            .column(ColumnSchema::new("table_name", SqlType::Varchar))
-            .column(ColumnSchema::new("table_type", SqlType::Varchar))
+            .column(ColumnSchema::new("table_catalog", SqlType::Varchar(None)))
+            .column(ColumnSchema::new("table_schema", SqlType::Varchar(None)))
+            .column(ColumnSchema::new("table_name", SqlType::Varchar(None)))
+            .column(ColumnSchema::new("table_type", SqlType::Varchar(None)))
             .build();
-        schema.tables.insert("tables".to_string(), tables_schema);
+        schema
+            .tables
+            .insert("tables".to_string(), tables_schema.into());
 
         // Register COLUMNS table
         let columns_schema = TableSchemaBuilder::new("columns")
-            .column(ColumnSchema::new("table_catalog", SqlType::Varchar))
-            .column(ColumnSchema::new("table_schema", SqlType::Varchar))
-            .column(ColumnSchema::new("table_name", SqlType::Varchar))
-            .column(ColumnSchema::new("column_name", SqlType::Varchar))
-            .column(ColumnSchema::new("ordinal_position", SqlType::Varchar))
-            .column(ColumnSchema::new("data_type", SqlType::Varchar))
-            .column(ColumnSchema::new("is_nullable", SqlType::Varchar))
+            .column(ColumnSchema::new("table_catalog", SqlType::Varchar(None)))
+            .column(ColumnSchema::new("table_schema", SqlType::Varchar(None)))
+            .column(ColumnSchema::new("table_name", SqlType::Varchar(None)))
+            .column(ColumnSchema::new("column_name", SqlType::Varchar(None)))
+            .column(ColumnSchema::new(
+                "ordinal_position",
+                SqlType::Varchar(None),
+            ))
+            .column(ColumnSchema::new("data_type", SqlType::Varchar(None)))
+            .column(ColumnSchema::new("is_nullable", SqlType::Varchar(None)))
             .build();
-        schema.tables.insert("columns".to_string(), columns_schema);
+        schema
+            .tables
+            .insert("columns".to_string(), columns_schema.into());
     }
 
     /// Generate information_schema.tables data dynamically.
@@ -96,7 +112,7 @@ impl CsvDatabase {
             if table_name.starts_with("information_schema.") {
                 continue;
             }
-            if let Ok(Some(schema)) = self.catalog.resolve_table(&[table_name.clone()]) {
+            if let Ok(Some(schema)) = self.catalog.resolve_table(std::slice::from_ref(table_name)) {
                 for (i, col) in schema.columns.iter().enumerate() {
                     rows.push(vec![
                         "csvdb".to_string(),
@@ -149,13 +165,6 @@ impl CsvDatabase {
 
         let columns: Vec<&str> = header.split(',').map(|s| s.trim()).collect();
 
-        let mut builder = TableSchemaBuilder::new(&table_name);
-        for col_name in &columns {
-            builder = builder.column(ColumnSchema::new(*col_name, SqlType::Varchar));
-        }
-        let schema = builder.build();
-        self.catalog.add_table(schema);
-
         let mut rows = Vec::new();
         for line in lines {
             let line = line?;
@@ -165,6 +174,14 @@ impl CsvDatabase {
             }
         }
 
+        let mut builder = TableSchemaBuilder::new(&table_name);
+        for (i, col_name) in columns.iter().enumerate() {
+            let data_type = infer_column_type(rows.iter().map(|row| row[i].as_str()));
+            builder = builder.column(ColumnSchema::new(*col_name, data_type));
+        }
+        let schema = builder.build();
+        self.catalog.add_table(schema);
+
         self.tables.insert(table_name, rows);
         Ok(())
     }
@@ -192,8 +209,10 @@ impl CsvDatabase {
 
                 if let vibesql::InsertSource::Values(rows) = &insert.source {
                     for row in rows {
-                        let values: Vec<String> =
-                            row.iter().map(|expr| self.eval_literal(expr)).collect();
+                        let values = row
+                            .iter()
+                            .map(|expr| self.eval_literal(expr))
+                            .collect::<Result<Vec<_>, _>>()?;
 
                         if let Some(table_data) = self.tables.get_mut(&table_name) {
                             table_data.push(values.clone());
@@ -216,21 +235,89 @@ impl CsvDatabase {
                     .map(|i| i.value.clone())
                     .unwrap_or_default();
 
-                let mut builder = TableSchemaBuilder::new(&table_name);
-                for col in &create.columns {
-                    builder = builder.column(ColumnSchema::new(&col.name.value, SqlType::Varchar));
+                let outcome = self.catalog.apply(stmt).map_err(|e| e.to_string())?;
+                let message = match &outcome {
+                    ApplyOutcome::Created => format!("Created table {}", table_name),
+                    ApplyOutcome::Replaced { old } => format!(
+                        "Replaced table {} (previously {} columns)",
+                        table_name,
+                        old.columns.len()
+                    ),
+                    ApplyOutcome::SkippedExists => {
+                        format!("Skipped: table {} already exists", table_name)
+                    }
+                    other => format!("{:?}", other),
+                };
+                if matches!(
+                    outcome,
+                    ApplyOutcome::Created | ApplyOutcome::Replaced { .. }
+                ) {
+                    self.tables.insert(table_name.clone(), Vec::new());
+                    self.save_table(&table_name)?;
                 }
-                let schema = builder.build();
-                self.catalog.add_table(schema);
 
-                self.tables.insert(table_name.clone(), Vec::new());
-                self.save_table(&table_name)?;
+                Ok(QueryResult::new(
+                    vec!["result".to_string()],
+                    vec![vec![message]],
+                ))
+            }
+            StatementKind::Drop(drop) => {
+                let outcome = self.catalog.apply(stmt).map_err(|e| e.to_string())?;
+                let names: Vec<String> = drop
+                    .names
+                    .iter()
+                    .filter_map(|n| n.parts.last().map(|i| i.value.clone()))
+                    .collect();
+                let message = match outcome {
+                    ApplyOutcome::Dropped => {
+                        for name in &names {
+                            if self.tables.remove(name).is_some() {
+                                let path = self.data_dir.join(format!("{}.csv", name));
+                                std::fs::remove_file(path).map_err(|e| e.to_string())?;
+                            }
+                        }
+                        format!("Dropped table {}", names.join(", "))
+                    }
+                    ApplyOutcome::SkippedMissing => {
+                        format!("Skipped: table {} does not exist", names.join(", "))
+                    }
+                    other => format!("{:?}", other),
+                };
 
                 Ok(QueryResult::new(
                     vec!["result".to_string()],
-                    vec![vec![format!("Created table {}", table_name)]],
+                    vec![vec![message]],
                 ))
             }
+            StatementKind::Show(show) => match (&show.object_type, &show.filter) {
+                (vibesql::ShowObjectType::Functions { .. }, None) => {
+                    let rows = self
+                        .catalog
+                        .list_functions()
+                        .map_err(|e| e.to_string())?
+                        .into_iter()
+                        .map(|f| {
+                            let kind = if f.signature.is_aggregate {
+                                "AGGREGATE"
+                            } else if f.signature.is_window {
+                                "WINDOW"
+                            } else {
+                                "SCALAR"
+                            };
+                            vec![
+                                f.signature.name,
+                                kind.to_string(),
+                                f.pack.unwrap_or_default(),
+                            ]
+                        })
+                        .collect();
+                    Ok(QueryResult::new(
+                        vec!["name".to_string(), "kind".to_string(), "pack".to_string()],
+                        rows,
+                    ))
+                }
+                _ => Err("Only SHOW FUNCTIONS without a filter is supported".to_string()),
+            },
             _ => Err("Unsupported statement type".to_string()),
         }
     }
@@ -264,7 +351,13 @@ impl CsvDatabase {
         let mut exec_rows = self.process_from_clause(&from.tables[0])?;
 
         if let Some(where_expr) = &select.where_clause {
-            exec_rows.retain(|row| self.eval_where(where_expr, row));
+            let mut kept = Vec::new();
+            for row in exec_rows {
+                if self.eval_where(where_expr, &row)? {
+                    kept.push(row);
+                }
+            }
+            exec_rows = kept;
         }
 
         let result_rows = if analyzed.has_aggregation {
@@ -275,7 +368,7 @@ impl CsvDatabase {
 
         let limited_rows = if let Some(limit) = &query.limit {
             if let Some(count) = &limit.count {
-                let n = self.eval_int(count) as usize;
+                let n = self.eval_int(count)? as usize;
                 result_rows.into_iter().take(n).collect()
             } else {
                 result_rows
@@ -297,46 +390,56 @@ impl CsvDatabase {
         exec_rows: &[ExecutionRow],
         analyzed: &AnalyzedQuery,
     ) -> Result<Vec<Row>, String> {
-        let groups = self.group_rows(select, exec_rows);
+        let registry = AggregateRegistry::with_builtins();
+        let mut extract = ExtractAggregates::new(&registry);
+        let projection: Vec<Option<Box<vibesql::Expr>>> = select
+            .projection
+            .iter()
+            .map(|item| match item {
+                SelectItem::Expr { expr, .. } => {
+                    let mut expr = expr.clone();
+                    extract.visit_expr(&mut expr);
+                    Some(expr)
+                }
+                _ => None,
+            })
+            .collect();
+        let calls = extract.calls;
 
-        let mut result = Vec::new();
+        // Without GROUP BY, all the rows are one group, even when there
+        // are none
+        let groups = self.group_rows(select, exec_rows)?;
 
+        let mut result = Vec::new();
         for (_group_key, group_rows) in groups {
-            let mut row = Vec::new();
+            let aggregates = calls
+                .iter()
+                .map(|call| self.compute_aggregate(&registry, call, &group_rows))
+                .collect::<Result<Vec<_>, _>>()?;
+            let group = GroupRow {
+                aggregates,
+                first: group_rows.first(),
+            };
 
-            for (i, item) in select.projection.iter().enumerate() {
-                match item {
-                    SelectItem::Expr { expr, .. } => {
-                        let val = self.eval_aggregate_expr(expr, &group_rows);
-                        row.push(val);
+            let mut row = Vec::new();
+            for (i, expr) in projection.iter().enumerate() {
+                match expr {
+                    Some(expr) => {
+                        let value = evaluate(expr, &group, &Params::new())
+                            .map_err(|e| format!("Execution error: {}", e))?;
+                        row.push(cell_text(value));
                     }
-                    _ => {
+                    None => {
                         if let Some(col) = analyzed.columns.get(i) {
-                            if let Some(first_row) = group_rows.first() {
-                                let val = first_row.get(&col.name).cloned().unwrap_or_default();
-                                row.push(val);
-                            } else {
-                                row.push(String::new());
-                            }
+                            let val = group
+                                .first
+                                .and_then(|first_row| first_row.get(&col.name).cloned())
+                                .unwrap_or_default();
+                            row.push(val);
                         }
                     }
                 }
             }
-
-            result.push(row);
-        }
-
-        if result.is_empty() && select.group_by.is_none() {
-            let mut row = Vec::new();
-            for item in &select.projection {
-                match item {
-                    SelectItem::Expr { expr, .. } => {
-                        let val = self.eval_aggregate_expr(expr, &[]);
-                        row.push(val);
-                    }
-                    _ => row.push(String::new()),
-                }
-            }
             result.push(row);
         }
 
@@ -348,7 +451,7 @@ impl CsvDatabase {
         &self,
         select: &Select,
         exec_rows: &[ExecutionRow],
-    ) -> Vec<(String, Vec<ExecutionRow>)> {
+    ) -> Result<Vec<(String, Vec<ExecutionRow>)>, String> {
         let group_by_exprs: Vec<&vibesql::Expr> = if let Some(group_by) = &select.group_by {
             group_by
                 .items
@@ -366,214 +469,74 @@ impl CsvDatabase {
         };
 
         if group_by_exprs.is_empty() {
-            return vec![("".to_string(), exec_rows.to_vec())];
+            return Ok(vec![("".to_string(), exec_rows.to_vec())]);
         }
 
         let mut groups: HashMap<String, Vec<ExecutionRow>> = HashMap::new();
 
         for row in exec_rows {
-            let key: Vec<String> = group_by_exprs
+            let key = group_by_exprs
                 .iter()
                 .map(|expr| self.eval_expr_row(expr, row))
-                .collect();
+                .collect::<Result<Vec<_>, _>>()?;
             let key_str = key.join("|");
 
-            groups
-                .entry(key_str)
-                .or_insert_with(Vec::new)
-                .push(row.clone());
+            groups.entry(key_str).or_default().push(row.clone());
         }
 
-        groups.into_iter().collect()
+        Ok(groups.into_iter().collect())
     }
 
-    /// Evaluate an expression that may contain aggregates.
-    fn eval_aggregate_expr(&self, expr: &vibesql::Expr, rows: &[ExecutionRow]) -> String {
-        match &expr.kind {
-            ExprKind::Aggregate(agg) => {
-                let func_name = agg
-                    .function
-                    .name
-                    .parts
-                    .last()
-                    .map(|i| i.value.to_uppercase())
-                    .unwrap_or_default();
-
-                let arg_values: Vec<String> = if agg.function.args.is_empty() {
-                    vec![]
-                } else {
-                    rows.iter()
-                        .filter_map(|row| {
-                            if let Some(vibesql::FunctionArg::Unnamed(arg_expr)) =
-                                agg.function.args.first()
-                            {
-                                Some(self.eval_expr_row(arg_expr, row))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect()
-                };
-
-                self.compute_aggregate(&func_name, &arg_values, rows.len())
-            }
-            ExprKind::Function(func) => {
-                let func_name = func
-                    .name
-                    .parts
-                    .last()
-                    .map(|i| i.value.to_uppercase())
-                    .unwrap_or_default();
-
-                if matches!(func_name.as_str(), "COUNT" | "SUM" | "AVG" | "MIN" | "MAX") {
-                    let arg_values: Vec<String> = rows
-                        .iter()
-                        .filter_map(|row| {
-                            if let Some(vibesql::FunctionArg::Unnamed(arg_expr)) = func.args.first()
-                            {
-                                Some(self.eval_expr_row(arg_expr, row))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-
-                    self.compute_aggregate(&func_name, &arg_values, rows.len())
-                } else {
-                    rows.first()
-                        .map(|row| self.eval_expr_row(expr, row))
-                        .unwrap_or_default()
+    /// Compute an aggregate call over the rows of a group.
+    fn compute_aggregate(
+        &self,
+        registry: &AggregateRegistry,
+        call: &AggregateCall,
+        rows: &[ExecutionRow],
+    ) -> Result<Value, String> {
+        let function = &call.function;
+        let name = aggregate_name(function);
+        let options = AggregateOptions::from_call(function).map_err(|e| e.to_string())?;
+        let mut aggregator = registry
+            .create(&name, &options)
+            .ok_or_else(|| format!("Unsupported aggregate: {}", name))?;
+
+        for row in rows {
+            if let Some(filter) = &call.filter {
+                if !self.eval_where(filter, row)? {
+                    continue;
                 }
             }
-            ExprKind::Identifier(_) | ExprKind::CompoundIdentifier(_) => rows
-                .first()
-                .map(|row| self.eval_expr_row(expr, row))
-                .unwrap_or_default(),
-            ExprKind::BinaryOp { op, left, right } => {
-                let left_val = self.eval_aggregate_expr(left, rows);
-                let right_val = self.eval_aggregate_expr(right, rows);
-
-                // Try numeric arithmetic
-                if let (Ok(l), Ok(r)) = (left_val.parse::<f64>(), right_val.parse::<f64>()) {
-                    let result = match op {
-                        vibesql::BinaryOp::Plus => l + r,
-                        vibesql::BinaryOp::Minus => l - r,
-                        vibesql::BinaryOp::Multiply => l * r,
-                        vibesql::BinaryOp::Divide => {
-                            if r != 0.0 {
-                                l / r
-                            } else {
-                                return "NULL".to_string();
-                            }
-                        }
-                        vibesql::BinaryOp::Modulo => {
-                            if r != 0.0 {
-                                l % r
-                            } else {
-                                return "NULL".to_string();
-                            }
-                        }
-                        _ => return format!("{} {} {}", left_val, op, right_val),
-                    };
-                    if result.fract() == 0.0 {
-                        (result as i64).to_string()
-                    } else {
-                        format!("{:.2}", result)
-                    }
-                } else {
-                    // Non-numeric, return concatenated for debugging
-                    format!("{}{}{}", left_val, right_val, "")
-                }
+            // The arguments, then the ORDER BY keys; COUNT(*) has neither
+            let mut args = Vec::new();
+            let exprs = function
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    vibesql::FunctionArg::Unnamed(expr) => Some(expr.as_ref()),
+                    vibesql::FunctionArg::Named { value, .. } => Some(value.as_ref()),
+                    vibesql::FunctionArg::Star => None,
+                })
+                .chain(function.order_by.iter().map(|item| item.expr.as_ref()));
+            for expr in exprs {
+                let value = evaluate(expr, row, &Params::new())
+                    .map_err(|e| format!("Execution error: {}", e))?;
+                args.push(value);
             }
-            ExprKind::Integer(n) => n.to_string(),
-            ExprKind::Float(f) => f.to_string(),
-            _ => rows
-                .first()
-                .map(|row| self.eval_expr_row(expr, row))
-                .unwrap_or_default(),
+            aggregator
+                .update(&args)
+                .map_err(|e| format!("Execution error: {}", e))?;
         }
-    }
 
-    /// Compute an aggregate function.
-    fn compute_aggregate(&self, func_name: &str, values: &[String], row_count: usize) -> String {
-        match func_name {
-            "COUNT" => {
-                if values.is_empty() {
-                    row_count.to_string()
-                } else {
-                    values.iter().filter(|v| !v.is_empty()).count().to_string()
-                }
-            }
-            "SUM" => {
-                let sum: f64 = values.iter().filter_map(|v| v.parse::<f64>().ok()).sum();
-                if sum.fract() == 0.0 {
-                    (sum as i64).to_string()
-                } else {
-                    format!("{:.2}", sum)
-                }
-            }
-            "AVG" => {
-                let nums: Vec<f64> = values
-                    .iter()
-                    .filter_map(|v| v.parse::<f64>().ok())
-                    .collect();
-                if nums.is_empty() {
-                    "NULL".to_string()
-                } else {
-                    let avg = nums.iter().sum::<f64>() / nums.len() as f64;
-                    format!("{:.2}", avg)
-                }
-            }
-            "MIN" => {
-                let nums: Vec<f64> = values
-                    .iter()
-                    .filter_map(|v| v.parse::<f64>().ok())
-                    .collect();
-                if !nums.is_empty() {
-                    let min = nums.iter().cloned().fold(f64::INFINITY, f64::min);
-                    if min.fract() == 0.0 {
-                        (min as i64).to_string()
-                    } else {
-                        format!("{:.2}", min)
-                    }
-                } else {
-                    values
-                        .iter()
-                        .filter(|v| !v.is_empty())
-                        .min()
-                        .cloned()
-                        .unwrap_or_else(|| "NULL".to_string())
-                }
-            }
-            "MAX" => {
-                let nums: Vec<f64> = values
-                    .iter()
-                    .filter_map(|v| v.parse::<f64>().ok())
-                    .collect();
-                if !nums.is_empty() {
-                    let max = nums.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-                    if max.fract() == 0.0 {
-                        (max as i64).to_string()
-                    } else {
-                        format!("{:.2}", max)
-                    }
-                } else {
-                    values
-                        .iter()
-                        .filter(|v| !v.is_empty())
-                        .max()
-                        .cloned()
-                        .unwrap_or_else(|| "NULL".to_string())
-                }
-            }
-            _ => "NULL".to_string(),
-        }
+        aggregator
+            .finalize()
+            .map_err(|e| format!("Execution error: {}", e))
     }
 
     /// Process FROM clause and return execution rows.
     fn process_from_clause(&self, table_ref: &TableRef) -> Result<Vec<ExecutionRow>, String> {
-        match table_ref {
-            TableRef::Table { name, alias, .. } => {
+        match &table_ref.kind {
+            TableRefKind::Table { name, alias, .. } => {
                 let table_name = name
                     .parts
                     .iter()
@@ -597,12 +560,17 @@ impl CsvDatabase {
                 let mut exec_rows = Vec::new();
                 for row in &table_info.rows {
                     let mut exec_row = ExecutionRow::new();
-                    exec_row.add_table(&table_info.alias, &table_info.columns, row);
+                    exec_row.add_table(
+                        &table_info.alias,
+                        &table_info.columns,
+                        &table_info.types,
+                        row,
+                    );
                     exec_rows.push(exec_row);
                 }
                 Ok(exec_rows)
             }
-            TableRef::Join {
+            TableRefKind::Join {
                 left,
                 right,
                 join_type,
@@ -630,6 +598,7 @@ impl CsvDatabase {
             ];
             return Ok(TableInfo {
                 alias: alias.to_string(),
+                types: vec![SqlType::Varchar(None); columns.len()],
                 columns,
                 rows: self.get_information_schema_tables(),
             });
@@ -645,6 +614,7 @@ impl CsvDatabase {
             ];
             return Ok(TableInfo {
                 alias: alias.to_string(),
+                types: vec![SqlType::Varchar(None); columns.len()],
                 columns,
                 rows: self.get_information_schema_columns(),
             });
@@ -663,10 +633,12 @@ impl CsvDatabase {
             .ok_or_else(|| format!("Data for '{}' not found", table_name))?;
 
         let columns: Vec<String> = schema.columns.iter().map(|c| c.name.clone()).collect();
+        let types: Vec<SqlType> = schema.columns.iter().map(|c| c.data_type.clone()).collect();
 
         Ok(TableInfo {
             alias: alias.to_string(),
             columns,
+            types,
             rows,
         })
     }
@@ -686,7 +658,7 @@ impl CsvDatabase {
                 for left in left_rows {
                     for right in right_rows {
                         let combined = self.combine_rows(left, right);
-                        if self.check_join_condition(&combined, condition) {
+                        if self.check_join_condition(&combined, condition)? {
                             result.push(combined);
                         }
                     }
@@ -697,7 +669,7 @@ impl CsvDatabase {
                     let mut found_match = false;
                     for right in right_rows {
                         let combined = self.combine_rows(left, right);
-                        if self.check_join_condition(&combined, condition) {
+                        if self.check_join_condition(&combined, condition)? {
                             result.push(combined);
                             found_match = true;
                         }
@@ -713,7 +685,7 @@ impl CsvDatabase {
                     let mut found_match = false;
                     for left in left_rows {
                         let combined = self.combine_rows(left, right);
-                        if self.check_join_condition(&combined, condition) {
+                        if self.check_join_condition(&combined, condition)? {
                             result.push(combined);
                             found_match = true;
                         }
@@ -731,7 +703,7 @@ impl CsvDatabase {
                     let mut found_match = false;
                     for (i, right) in right_rows.iter().enumerate() {
                         let combined = self.combine_rows(left, right);
-                        if self.check_join_condition(&combined, condition) {
+                        if self.check_join_condition(&combined, condition)? {
                             result.push(combined);
                             found_match = true;
                             right_matched[i] = true;
@@ -766,6 +738,7 @@ impl CsvDatabase {
     fn combine_rows(&self, left: &ExecutionRow, right: &ExecutionRow) -> ExecutionRow {
         let mut combined = ExecutionRow::new();
         combined.values = left.values.clone();
+        combined.types = left.types.clone();
         combined.col_map = left.col_map.clone();
 
         let offset = combined.values.len();
@@ -773,6 +746,7 @@ impl CsvDatabase {
             combined.col_map.insert(key.clone(), idx + offset);
         }
         combined.values.extend(right.values.iter().cloned());
+        combined.types.extend(right.types.iter().cloned());
 
         combined
     }
@@ -785,6 +759,7 @@ impl CsvDatabase {
     ) -> ExecutionRow {
         let mut combined = ExecutionRow::new();
         combined.values = left.values.clone();
+        combined.types = left.types.clone();
         combined.col_map = left.col_map.clone();
 
         if let Some(right) = right_template {
@@ -794,7 +769,8 @@ impl CsvDatabase {
             }
             combined
                 .values
-                .extend(std::iter::repeat(String::new()).take(right.values.len()));
+                .extend(std::iter::repeat_n(String::new(), right.values.len()));
+            combined.types.extend(right.types.iter().cloned());
         }
 
         combined
@@ -810,9 +786,10 @@ impl CsvDatabase {
 
         if let Some(left) = left_template {
             combined.col_map = left.col_map.clone();
+            combined.types = left.types.clone();
             combined
                 .values
-                .extend(std::iter::repeat(String::new()).take(left.values.len()));
+                .extend(std::iter::repeat_n(String::new(), left.values.len()));
         }
 
         let offset = combined.values.len();
@@ -820,16 +797,21 @@ impl CsvDatabase {
             combined.col_map.insert(key.clone(), idx + offset);
         }
         combined.values.extend(right.values.iter().cloned());
+        combined.types.extend(right.types.iter().cloned());
 
         combined
     }
 
     /// Check if a join condition is satisfied.
-    fn check_join_condition(&self, row: &ExecutionRow, condition: &Option<JoinCondition>) -> bool {
+    fn check_join_condition(
+        &self,
+        row: &ExecutionRow,
+        condition: &Option<JoinCondition>,
+    ) -> Result<bool, String> {
         match condition {
             Some(JoinCondition::On(expr)) => self.eval_where(expr, row),
-            Some(JoinCondition::Using(_)) => true,
-            None => true,
+            Some(JoinCondition::Using(_)) => Ok(true),
+            None => Ok(true),
         }
     }
 
@@ -866,7 +848,7 @@ impl CsvDatabase {
                         }
                     }
                     SelectItem::Expr { expr, .. } => {
-                        let val = self.eval_expr_row(expr, exec_row);
+                        let val = self.eval_expr_row(expr, exec_row)?;
                         row.push(val);
                     }
                     _ => {
@@ -884,97 +866,35 @@ impl CsvDatabase {
         Ok(result)
     }
 
-    /// Evaluate a WHERE clause expression.
-    fn eval_where(&self, expr: &vibesql::Expr, row: &ExecutionRow) -> bool {
-        match &expr.kind {
-            ExprKind::BinaryOp { op, left, right } => match op {
-                vibesql::BinaryOp::And => self.eval_where(left, row) && self.eval_where(right, row),
-                vibesql::BinaryOp::Or => self.eval_where(left, row) || self.eval_where(right, row),
-                _ => {
-                    let left_val = self.eval_expr_row(left, row);
-                    let right_val = self.eval_expr_row(right, row);
-
-                    match op {
-                        vibesql::BinaryOp::Eq => left_val == right_val,
-                        vibesql::BinaryOp::NotEq => left_val != right_val,
-                        vibesql::BinaryOp::Lt => {
-                            self.compare_values(&left_val, &right_val) == std::cmp::Ordering::Less
-                        }
-                        vibesql::BinaryOp::LtEq => {
-                            self.compare_values(&left_val, &right_val)
-                                != std::cmp::Ordering::Greater
-                        }
-                        vibesql::BinaryOp::Gt => {
-                            self.compare_values(&left_val, &right_val)
-                                == std::cmp::Ordering::Greater
-                        }
-                        vibesql::BinaryOp::GtEq => {
-                            self.compare_values(&left_val, &right_val) != std::cmp::Ordering::Less
-                        }
-                        _ => false,
-                    }
-                }
-            },
-            ExprKind::Boolean(b) => *b,
-            _ => true,
+    /// Evaluate a WHERE clause expression. Rows where it is NULL are
+    /// filtered out, as rows where it is FALSE are.
+    fn eval_where(&self, expr: &vibesql::Expr, row: &ExecutionRow) -> Result<bool, String> {
+        match evaluate(expr, row, &Params::new()) {
+            Ok(value) => Ok(value == Value::Boolean(true)),
+            Err(e) => Err(format!("Execution error: {}", e)),
         }
     }
 
     /// Evaluate an expression against an execution row.
-    fn eval_expr_row(&self, expr: &vibesql::Expr, row: &ExecutionRow) -> String {
-        match &expr.kind {
-            ExprKind::Identifier(ident) => row.get(&ident.value).cloned().unwrap_or_default(),
-            ExprKind::CompoundIdentifier(parts) => {
-                if parts.len() >= 2 {
-                    let key = format!(
-                        "{}.{}",
-                        parts[parts.len() - 2].value,
-                        parts[parts.len() - 1].value
-                    );
-                    row.get(&key).cloned().unwrap_or_default()
-                } else if parts.len() == 1 {
-                    row.get(&parts[0].value).cloned().unwrap_or_default()
-                } else {
-                    String::new()
-                }
-            }
-            ExprKind::String(s) => s.clone(),
-            ExprKind::Integer(n) => n.to_string(),
-            ExprKind::Float(f) => f.to_string(),
-            ExprKind::Boolean(b) => b.to_string(),
-            ExprKind::Null => String::new(),
-            _ => String::new(),
-        }
+    fn eval_expr_row(&self, expr: &vibesql::Expr, row: &ExecutionRow) -> Result<String, String> {
+        evaluate(expr, row, &Params::new())
+            .map(cell_text)
+            .map_err(|e| format!("Execution error: {}", e))
     }
 
-    /// Compare two string values (try numeric comparison first).
-    fn compare_values(&self, a: &str, b: &str) -> std::cmp::Ordering {
-        if let (Ok(a_num), Ok(b_num)) = (a.parse::<f64>(), b.parse::<f64>()) {
-            a_num
-                .partial_cmp(&b_num)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        } else {
-            a.cmp(b)
-        }
-    }
-
-    /// Evaluate a literal expression.
-    fn eval_literal(&self, expr: &vibesql::Expr) -> String {
-        match &expr.kind {
-            ExprKind::String(s) => s.clone(),
-            ExprKind::Integer(n) => n.to_string(),
-            ExprKind::Float(f) => f.to_string(),
-            ExprKind::Boolean(b) => b.to_string(),
-            ExprKind::Null => String::new(),
-            _ => String::new(),
-        }
+    /// Evaluate a constant expression.
+    fn eval_literal(&self, expr: &vibesql::Expr) -> Result<String, String> {
+        evaluate(expr, &(), &Params::new())
+            .map(cell_text)
+            .map_err(|e| format!("Execution error: {}", e))
     }
 
     /// Evaluate an integer expression.
-    fn eval_int(&self, expr: &vibesql::Expr) -> i64 {
-        match &expr.kind {
-            ExprKind::Integer(n) => *n,
-            _ => 0,
+    fn eval_int(&self, expr: &vibesql::Expr) -> Result<i64, String> {
+        match evaluate(expr, &(), &Params::new()) {
+            Ok(Value::Int64(n)) => Ok(n),
+            Ok(other) => Err(format!("Expected an integer, found {}", other)),
+            Err(e) => Err(format!("Execution error: {}", e)),
         }
     }
 
@@ -1001,3 +921,21 @@ impl CsvDatabase {
         Ok(())
     }
 }
+
+/// The type of a CSV column: the first of BIGINT, DOUBLE PRECISION and DATE
+/// that every non-empty cell converts to, or VARCHAR.
+fn infer_column_type<'a>(cells: impl Iterator<Item = &'a str> + Clone) -> SqlType {
+    let candidates = [SqlType::Int64, SqlType::Float64, SqlType::Date];
+    let mut cells = cells.filter(|cell| !cell.is_empty()).peekable();
+    if cells.peek().is_none() {
+        return SqlType::Varchar(None);
+    }
+    candidates
+        .into_iter()
+        .find(|data_type| {
+            cells
+                .clone()
+                .all(|cell| Value::String(cell.to_string()).cast_to(data_type).is_ok())
+        })
+        .unwrap_or(SqlType::Varchar(None))
+}
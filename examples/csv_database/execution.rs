@@ -2,6 +2,11 @@
 
 use std::collections::HashMap;
 
+use vibesql::ast::visit::{walk_expr_mut, VisitorMut};
+use vibesql::eval::{AggregateRegistry, RowAccessor};
+use vibesql::types::Value;
+use vibesql::{Expr, ExprKind, FunctionCall, Ident, SqlType};
+
 use crate::result::Row;
 
 /// A combined row from potentially multiple tables with column metadata.
@@ -9,6 +14,8 @@ use crate::result::Row;
 pub struct ExecutionRow {
     /// Values in order.
     pub values: Vec<String>,
+    /// Column types, parallel to `values`.
+    pub types: Vec<SqlType>,
     /// Map from "table.column" -> index and "column" -> index.
     pub col_map: HashMap<String, usize>,
 }
@@ -17,11 +24,18 @@ impl ExecutionRow {
     pub fn new() -> Self {
         Self {
             values: Vec::new(),
+            types: Vec::new(),
             col_map: HashMap::new(),
         }
     }
 
-    pub fn add_table(&mut self, alias: &str, columns: &[String], values: &[String]) {
+    pub fn add_table(
+        &mut self,
+        alias: &str,
+        columns: &[String],
+        types: &[SqlType],
+        values: &[String],
+    ) {
         let start_idx = self.values.len();
         for (i, col) in columns.iter().enumerate() {
             let qualified = format!("{}.{}", alias.to_lowercase(), col.to_lowercase());
@@ -31,6 +45,7 @@ impl ExecutionRow {
             self.col_map.entry(unqualified).or_insert(start_idx + i);
         }
         self.values.extend(values.iter().cloned());
+        self.types.extend(types.iter().cloned());
     }
 
     pub fn get(&self, key: &str) -> Option<&String> {
@@ -40,9 +55,116 @@ impl ExecutionRow {
     }
 }
 
+/// Cells are read as values of their column's type; an empty cell is NULL.
+impl RowAccessor for ExecutionRow {
+    fn get(&self, table: Option<&str>, column: &str) -> Option<Value> {
+        let key = match table {
+            Some(table) => format!("{}.{}", table, column),
+            None => column.to_string(),
+        };
+        let &i = self.col_map.get(&key.to_lowercase())?;
+        let cell = self.values.get(i)?;
+        if cell.is_empty() {
+            return Some(Value::Null);
+        }
+        let text = Value::String(cell.clone());
+        Some(text.cast_to(&self.types[i]).unwrap_or(text))
+    }
+}
+
+/// The CSV cell text of a value.
+pub fn cell_text(value: Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s,
+        value => match value.cast_to(&SqlType::Varchar(None)) {
+            Ok(Value::String(s)) => s,
+            _ => value.to_string(),
+        },
+    }
+}
+
+/// Replaces the aggregate calls in expressions by references to
+/// `#aggregateN` columns, collecting the calls in order.
+pub struct ExtractAggregates<'a> {
+    registry: &'a AggregateRegistry,
+    pub calls: Vec<AggregateCall>,
+}
+
+impl<'a> ExtractAggregates<'a> {
+    pub fn new(registry: &'a AggregateRegistry) -> Self {
+        Self {
+            registry,
+            calls: Vec::new(),
+        }
+    }
+}
+
+impl VisitorMut for ExtractAggregates<'_> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        let call = match &expr.kind {
+            ExprKind::Aggregate(agg) => AggregateCall {
+                function: agg.function.clone(),
+                filter: agg.filter.as_deref().cloned(),
+            },
+            ExprKind::Function(function) if self.registry.contains(&aggregate_name(function)) => {
+                AggregateCall {
+                    function: function.clone(),
+                    filter: None,
+                }
+            }
+            _ => return walk_expr_mut(self, expr),
+        };
+        let column = format!("#aggregate{}", self.calls.len());
+        self.calls.push(call);
+        expr.kind = ExprKind::Identifier(Ident::new(column, expr.span));
+    }
+}
+
+/// An aggregate call and its FILTER condition.
+pub struct AggregateCall {
+    pub function: FunctionCall,
+    pub filter: Option<Expr>,
+}
+
+/// The unqualified name of a function.
+pub fn aggregate_name(function: &FunctionCall) -> String {
+    function
+        .name
+        .parts
+        .last()
+        .map(|i| i.value.to_uppercase())
+        .unwrap_or_default()
+}
+
+/// A group of rows, for evaluating a projection with its aggregate calls
+/// replaced by [`ExtractAggregates`]. Other columns are read from the
+/// group's first row.
+pub struct GroupRow<'a> {
+    /// The value of each `#aggregateN` column.
+    pub aggregates: Vec<Value>,
+    pub first: Option<&'a ExecutionRow>,
+}
+
+impl RowAccessor for GroupRow<'_> {
+    fn get(&self, table: Option<&str>, column: &str) -> Option<Value> {
+        let aggregate = column
+            .strip_prefix("#aggregate")
+            .and_then(|n| n.parse::<usize>().ok());
+        match (table, aggregate) {
+            (None, Some(i)) => self.aggregates.get(i).cloned(),
+            _ => match self.first {
+                Some(row) => RowAccessor::get(row, table, column),
+                None => Some(Value::Null),
+            },
+        }
+    }
+}
+
 /// Table info for execution.
 pub struct TableInfo {
     pub alias: String,
     pub columns: Vec<String>,
+    pub types: Vec<SqlType>,
     pub rows: Vec<Row>,
 }
@@ -0,0 +1,32 @@
+//! Parsing arbitrary input must return an error, never panic.
+//!
+//! Run with: cargo +nightly fuzz run parse
+//!
+//! Inputs that crash go in `tests/fuzz_regressions.rs` once fixed.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vibesql::parser::split_statements;
+use vibesql::{is_complete_statement, Parser};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(sql) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    match Parser::new(sql).parse() {
+        Ok(statements) => {
+            for statement in &statements {
+                let _ = statement.to_string();
+            }
+        }
+        // Rendering slices the source at the error's span.
+        Err(err) => {
+            let _ = err.display_with_source(sql);
+        }
+    }
+    let _ = Parser::new(sql).parse_with_recovery();
+    let _ = split_statements(sql);
+    let _ = is_complete_statement(sql);
+});
@@ -0,0 +1,729 @@
+//! Best-effort query equivalence checking.
+//!
+//! Full query equivalence is undecidable, so [`compare`] checks a fixed set
+//! of [`Dimension`]s after light normalization (span erasure, ASCII case
+//! folding of unquoted identifiers, flattening of AND chains, and ordering of
+//! commutative equality operands) and reports each separately:
+//!
+//! - [`Outcome::Equal`]: the dimension matches after normalization.
+//! - [`Outcome::Different`]: the dimension differs in a way normalization
+//!   cannot reconcile. This is a strong signal, not a proof, that the
+//!   statements are not equivalent.
+//! - [`Outcome::Unknown`]: the dimension could not be checked.
+//!
+//! The overall outcome is only [`Outcome::Equal`] when the two statements are
+//! identical after normalization; matching dimensions alone leave it at
+//! [`Outcome::Unknown`], since other parts of the statements may still differ.
+
+use crate::analyzer::{Analyzer, OutputColumn};
+use crate::ast::visit::{walk_binary_chain, Visitor};
+use crate::ast::*;
+use crate::catalog::Catalog;
+use std::fmt;
+
+/// Result of comparing one dimension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The dimension matches.
+    Equal,
+    /// The dimension differs.
+    Different,
+    /// The dimension could not be checked.
+    Unknown,
+}
+
+/// An aspect of two statements that is compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    /// Statement type (query, insert, ...).
+    StatementKind,
+    /// Output column names, types, and nullability.
+    OutputSchema,
+    /// Referenced base tables (CTE names excluded).
+    Tables,
+    /// Join types, in textual order.
+    Joins,
+    /// DISTINCT on each SELECT.
+    Distinct,
+    /// WHERE, ON, USING, HAVING, and QUALIFY predicates.
+    Filters,
+    /// Grouping keys and aggregate calls.
+    Aggregation,
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Dimension::StatementKind => "statement kind",
+            Dimension::OutputSchema => "output schema",
+            Dimension::Tables => "tables",
+            Dimension::Joins => "joins",
+            Dimension::Distinct => "distinct",
+            Dimension::Filters => "filters",
+            Dimension::Aggregation => "aggregation",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Outcome of one dimension, with human-readable details.
+#[derive(Debug, Clone)]
+pub struct DimensionResult {
+    /// The compared dimension.
+    pub dimension: Dimension,
+    /// The outcome.
+    pub outcome: Outcome,
+    /// Explanations of differences or of why the check was not possible.
+    pub details: Vec<String>,
+}
+
+/// Report produced by [`compare`].
+#[derive(Debug, Clone)]
+pub struct ComparisonReport {
+    /// Per-dimension results.
+    pub dimensions: Vec<DimensionResult>,
+    /// Overall outcome.
+    pub overall: Outcome,
+}
+
+impl ComparisonReport {
+    /// Get the result for a dimension, if it was checked.
+    pub fn get(&self, dimension: Dimension) -> Option<&DimensionResult> {
+        self.dimensions.iter().find(|d| d.dimension == dimension)
+    }
+
+    /// Get the outcome for a dimension ([`Outcome::Unknown`] if not checked).
+    pub fn outcome(&self, dimension: Dimension) -> Outcome {
+        self.get(dimension)
+            .map(|d| d.outcome)
+            .unwrap_or(Outcome::Unknown)
+    }
+}
+
+/// Options for [`compare_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct CompareOptions {
+    /// Compare output columns as a set of (name, type, nullability) rather
+    /// than positionally.
+    pub order_insensitive_columns: bool,
+}
+
+/// Compare two statements with default options.
+pub fn compare<C: Catalog>(old: &Statement, new: &Statement, catalog: &C) -> ComparisonReport {
+    compare_with_options(old, new, catalog, &CompareOptions::default())
+}
+
+/// Compare two statements.
+pub fn compare_with_options<C: Catalog>(
+    old: &Statement,
+    new: &Statement,
+    catalog: &C,
+    options: &CompareOptions,
+) -> ComparisonReport {
+    let mut dimensions = Vec::new();
+
+    if std::mem::discriminant(&old.kind) == std::mem::discriminant(&new.kind) {
+        dimensions.push(DimensionResult::equal(Dimension::StatementKind));
+    } else {
+        dimensions.push(DimensionResult {
+            dimension: Dimension::StatementKind,
+            outcome: Outcome::Different,
+            details: vec!["statements are of different kinds".to_string()],
+        });
+    }
+
+    if let (StatementKind::Query(old_query), StatementKind::Query(new_query)) =
+        (&old.kind, &new.kind)
+    {
+        dimensions.push(compare_output_schema(
+            old_query, new_query, catalog, options,
+        ));
+
+        let old_shape = QueryShape::collect(old_query, catalog);
+        let new_shape = QueryShape::collect(new_query, catalog);
+        dimensions.push(compare_tables(&old_shape.tables, &new_shape.tables));
+        dimensions.push(compare_lists(
+            Dimension::Joins,
+            &old_shape.joins,
+            &new_shape.joins,
+        ));
+        dimensions.push(compare_lists(
+            Dimension::Distinct,
+            &old_shape.distinct,
+            &new_shape.distinct,
+        ));
+        dimensions.push(compare_lists(
+            Dimension::Filters,
+            &old_shape.filters,
+            &new_shape.filters,
+        ));
+        dimensions.push(compare_lists(
+            Dimension::Aggregation,
+            &old_shape.aggregation,
+            &new_shape.aggregation,
+        ));
+    }
+
+    let overall = if dimensions.iter().any(|d| d.outcome == Outcome::Different) {
+        Outcome::Different
+    } else if canonical(old) == canonical(new) {
+        Outcome::Equal
+    } else {
+        Outcome::Unknown
+    };
+
+    ComparisonReport {
+        dimensions,
+        overall,
+    }
+}
+
+impl DimensionResult {
+    fn equal(dimension: Dimension) -> Self {
+        Self {
+            dimension,
+            outcome: Outcome::Equal,
+            details: Vec::new(),
+        }
+    }
+}
+
+/// Compare the analyzed output columns of two queries.
+fn compare_output_schema<C: Catalog>(
+    old: &Query,
+    new: &Query,
+    catalog: &C,
+    options: &CompareOptions,
+) -> DimensionResult {
+    let analyze = |query: &Query| Analyzer::with_catalog(catalog).analyze_query_result(query);
+    let (old_result, new_result) = match (analyze(old), analyze(new)) {
+        (Ok(old_result), Ok(new_result)) => (old_result, new_result),
+        (Err(e), _) | (_, Err(e)) => {
+            return DimensionResult {
+                dimension: Dimension::OutputSchema,
+                outcome: Outcome::Unknown,
+                details: vec![format!("analysis failed: {}", e)],
+            };
+        }
+    };
+
+    let mut old_columns: Vec<String> = old_result.columns.iter().map(describe_column).collect();
+    let mut new_columns: Vec<String> = new_result.columns.iter().map(describe_column).collect();
+    if options.order_insensitive_columns {
+        old_columns.sort();
+        new_columns.sort();
+    }
+
+    let mut details = Vec::new();
+    if old_columns.len() != new_columns.len() {
+        details.push(format!(
+            "column count differs: {} vs {}",
+            old_columns.len(),
+            new_columns.len()
+        ));
+    }
+    for (i, (old_col, new_col)) in old_columns.iter().zip(&new_columns).enumerate() {
+        if old_col != new_col {
+            details.push(format!("column {}: {} vs {}", i, old_col, new_col));
+        }
+    }
+
+    DimensionResult {
+        dimension: Dimension::OutputSchema,
+        outcome: if details.is_empty() {
+            Outcome::Equal
+        } else {
+            Outcome::Different
+        },
+        details,
+    }
+}
+
+fn describe_column(col: &OutputColumn) -> String {
+    format!(
+        "{} {}{}",
+        col.name.to_ascii_lowercase(),
+        col.data_type,
+        if col.nullable { "" } else { " NOT NULL" }
+    )
+}
+
+fn compare_tables(old: &[String], new: &[String]) -> DimensionResult {
+    let mut details = Vec::new();
+    for table in old.iter().filter(|t| !new.contains(t)) {
+        details.push(format!("only in old: {}", table));
+    }
+    for table in new.iter().filter(|t| !old.contains(t)) {
+        details.push(format!("only in new: {}", table));
+    }
+    DimensionResult {
+        dimension: Dimension::Tables,
+        outcome: if details.is_empty() {
+            Outcome::Equal
+        } else {
+            Outcome::Different
+        },
+        details,
+    }
+}
+
+fn compare_lists<T: PartialEq + fmt::Debug>(
+    dimension: Dimension,
+    old: &[T],
+    new: &[T],
+) -> DimensionResult {
+    if old == new {
+        DimensionResult::equal(dimension)
+    } else {
+        DimensionResult {
+            dimension,
+            outcome: Outcome::Different,
+            details: vec![format!("old: {:?}", old), format!("new: {:?}", new)],
+        }
+    }
+}
+
+/// Normalized structural facts about a query.
+#[derive(Debug, Default)]
+struct QueryShape {
+    /// Referenced base tables, sorted and de-duplicated.
+    tables: Vec<String>,
+    /// Join types in textual order.
+    joins: Vec<String>,
+    /// Whether each SELECT is DISTINCT.
+    distinct: Vec<bool>,
+    /// Sorted predicate keys for each SELECT.
+    filters: Vec<Vec<String>>,
+    /// Sorted grouping keys and aggregate call keys for each SELECT.
+    aggregation: Vec<(Vec<String>, Vec<String>)>,
+}
+
+struct ShapeCollector<'a, C: Catalog> {
+    catalog: &'a C,
+    cte_names: Vec<String>,
+    shape: QueryShape,
+}
+
+impl QueryShape {
+    fn collect<C: Catalog>(query: &Query, catalog: &C) -> Self {
+        let mut collector = ShapeCollector {
+            catalog,
+            cte_names: Vec::new(),
+            shape: QueryShape::default(),
+        };
+        collector.query(query);
+        let mut shape = collector.shape;
+        shape.tables.sort();
+        shape.tables.dedup();
+        shape
+    }
+}
+
+impl<C: Catalog> ShapeCollector<'_, C> {
+    fn query(&mut self, query: &Query) {
+        if let Some(with) = &query.with {
+            for cte in &with.ctes {
+                self.cte_names.push(cte.name.value.to_ascii_lowercase());
+                self.query(&cte.query);
+            }
+        }
+        self.body(&query.body);
+        for item in &query.order_by {
+            self.scan(&item.expr);
+        }
+        if let Some(limit) = &query.limit {
+            for expr in limit.count.iter().chain(&limit.offset) {
+                self.scan(expr);
+            }
+        }
+    }
+
+    fn body(&mut self, body: &QueryBody) {
+        match body {
+            QueryBody::Select(select) => self.select(select),
+            QueryBody::SetOperation { left, right, .. } => {
+                self.body(left);
+                self.body(right);
+            }
+            QueryBody::Parenthesized(query) => self.query(query),
+        }
+    }
+
+    fn select(&mut self, select: &Select) {
+        self.shape.distinct.push(matches!(
+            select.distinct,
+            Some(Distinct::Distinct | Distinct::On(_))
+        ));
+
+        let mut filters = Vec::new();
+        if let Some(from) = &select.from {
+            for table_ref in &from.tables {
+                self.table_ref(table_ref, &mut filters);
+            }
+        }
+
+        let mut aggregates = Vec::new();
+        for item in &select.projection {
+            match item {
+                SelectItem::Expr { expr, .. } => self.scan_aggregates(expr, &mut aggregates),
+                SelectItem::WildcardReplace { replace, .. } => {
+                    for (expr, _) in replace {
+                        self.scan_aggregates(expr, &mut aggregates);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (prefix, clause) in [
+            ("WHERE", &select.where_clause),
+            ("HAVING", &select.having),
+            ("QUALIFY", &select.qualify),
+        ] {
+            if let Some(expr) = clause {
+                conjuncts(expr, prefix, &mut filters);
+                self.scan_aggregates(expr, &mut aggregates);
+            }
+        }
+
+        let mut group_keys: Vec<String> = select
+            .group_by
+            .iter()
+            .flat_map(|g| g.items.iter().map(canonical))
+            .collect();
+
+        filters.sort();
+        group_keys.sort();
+        aggregates.sort();
+        self.shape.filters.push(filters);
+        self.shape.aggregation.push((group_keys, aggregates));
+    }
+
+    fn table_ref(&mut self, table_ref: &TableRef, filters: &mut Vec<String>) {
+        match &table_ref.kind {
+            TableRefKind::Table { name, .. } => {
+                let mut parts: Vec<String> = name.parts.iter().map(ident_key).collect();
+                if parts.len() == 1 && self.cte_names.contains(&parts[0]) {
+                    return;
+                }
+                if parts.len() == 2 && parts[0] == self.catalog.default_schema() {
+                    parts.remove(0);
+                }
+                self.shape.tables.push(parts.join("."));
+            }
+            TableRefKind::Subquery { query, .. } => self.query(query),
+            TableRefKind::Unnest { expr, .. } => self.scan(expr),
+            TableRefKind::Join {
+                left,
+                right,
+                join_type,
+                condition,
+            } => {
+                self.table_ref(left, filters);
+                self.table_ref(right, filters);
+                self.shape.joins.push(format!("{:?}", join_type));
+                match condition {
+                    Some(JoinCondition::On(expr)) => {
+                        conjuncts(expr, "ON", filters);
+                        self.scan(expr);
+                    }
+                    Some(JoinCondition::Using(columns)) => {
+                        let mut names: Vec<String> = columns.iter().map(ident_key).collect();
+                        names.sort();
+                        filters.push(format!("USING {}", names.join(", ")));
+                    }
+                    None => {}
+                }
+            }
+            TableRefKind::Parenthesized(inner) => self.table_ref(inner, filters),
+            TableRefKind::TableFunction { name, args, .. } => {
+                let parts: Vec<String> = name.parts.iter().map(ident_key).collect();
+                self.shape.tables.push(format!("{}()", parts.join(".")));
+                for arg in args {
+                    if let FunctionArg::Unnamed(expr) | FunctionArg::Named { value: expr, .. } = arg
+                    {
+                        self.scan(expr);
+                    }
+                }
+            }
+            TableRefKind::Pivot {
+                input,
+                aggregates,
+                for_column,
+                values,
+                ..
+            } => {
+                self.table_ref(input, filters);
+                for (expr, _) in aggregates {
+                    self.scan(expr);
+                }
+                let key = canonical(&(aggregates, for_column, values));
+                self.shape.joins.push(format!("PIVOT {}", key));
+            }
+            TableRefKind::Unpivot {
+                input,
+                include_nulls,
+                value_columns,
+                name_column,
+                columns,
+                ..
+            } => {
+                self.table_ref(input, filters);
+                let key = canonical(&(include_nulls, value_columns, name_column, columns));
+                self.shape.joins.push(format!("UNPIVOT {}", key));
+            }
+        }
+    }
+
+    /// Visit the subqueries nested in an expression.
+    fn scan(&mut self, expr: &Expr) {
+        for query in ExprScan::new(expr).queries {
+            self.query(query);
+        }
+    }
+
+    /// Visit nested subqueries and record the aggregate calls of an
+    /// expression.
+    fn scan_aggregates(&mut self, expr: &Expr, aggregates: &mut Vec<String>) {
+        let ExprScan { calls, queries } = ExprScan::new(expr);
+        for (call, is_aggregate) in calls {
+            let is_aggregate = is_aggregate || {
+                let name: Vec<String> = call.name.parts.iter().map(|p| p.value.clone()).collect();
+                matches!(self.catalog.resolve_function(&name), Ok(Some(sig)) if sig.is_aggregate)
+            };
+            if is_aggregate {
+                aggregates.push(canonical(call));
+            }
+        }
+        for query in queries {
+            self.query(query);
+        }
+    }
+}
+
+/// Identifier key with ASCII case folded unless quoted.
+fn ident_key(ident: &Ident) -> String {
+    ident.normalized(IdentifierMatching::AsciiCaseInsensitive)
+}
+
+/// Split an expression into normalized AND-conjunct keys.
+fn conjuncts(expr: &Expr, prefix: &str, out: &mut Vec<String>) {
+    match &expr.kind {
+        ExprKind::BinaryOp {
+            op: BinaryOp::And,
+            left,
+            right,
+        } => {
+            conjuncts(left, prefix, out);
+            conjuncts(right, prefix, out);
+        }
+        ExprKind::Parenthesized(inner) => conjuncts(inner, prefix, out),
+        ExprKind::BinaryOp {
+            op: op @ (BinaryOp::Eq | BinaryOp::NotEq),
+            left,
+            right,
+        } => {
+            let mut operands = [canonical(left), canonical(right)];
+            operands.sort();
+            out.push(format!(
+                "{} {:?}({}, {})",
+                prefix, op, operands[0], operands[1]
+            ));
+        }
+        _ => out.push(format!("{} {}", prefix, canonical(expr))),
+    }
+}
+
+/// The function calls (with whether they are known aggregates) and
+/// subqueries directly contained in an expression. Subqueries are not
+/// descended into.
+#[derive(Default)]
+struct ExprScan<'a> {
+    calls: Vec<(&'a FunctionCall, bool)>,
+    queries: Vec<&'a Query>,
+}
+
+impl<'a> ExprScan<'a> {
+    fn new(expr: &'a Expr) -> Self {
+        let mut scan = Self::default();
+        scan.visit_expr(expr);
+        scan
+    }
+}
+
+impl<'a> Visitor<'a> for ExprScan<'a> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Function(call) => self.calls.push((call, false)),
+            ExprKind::Aggregate(agg) => self.calls.push((&agg.function, true)),
+            _ => {}
+        }
+        walk_binary_chain(self, expr, |_, _| {});
+    }
+
+    fn visit_query(&mut self, query: &'a Query) {
+        self.queries.push(query);
+    }
+}
+
+/// Canonical text of an AST node: its `Debug` form with spans removed and
+/// unquoted identifiers folded to ASCII lower case.
+fn canonical<T: fmt::Debug>(node: &T) -> String {
+    const SPAN: &str = ", span: Span { ";
+    const NODE_ID: &str = ", id: NodeId(";
+    const IDENT: &str = "Ident { value: \"";
+
+    let raw = format!("{:?}", node);
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw.as_str();
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix(SPAN) {
+            let end = after.find(" }").map_or(after.len(), |i| i + 2);
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix(NODE_ID) {
+            let end = after.find(')').map_or(after.len(), |i| i + 1);
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix(IDENT) {
+            let end = string_literal_end(after);
+            let (value, tail) = after.split_at(end);
+            out.push_str(IDENT);
+            if tail.starts_with("\", quoted: false") {
+                out.push_str(&value.to_ascii_lowercase());
+            } else {
+                out.push_str(value);
+            }
+            // Emit the closing quote so it is not read as an opening one.
+            out.push('"');
+            rest = tail.get(1..).unwrap_or_default();
+        } else if c == '"' {
+            // Copy other string literals verbatim so their contents are
+            // never mistaken for structure.
+            let end = (2 + string_literal_end(&rest[1..])).min(rest.len());
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+/// Byte offset of the closing quote of a `Debug`-escaped string whose
+/// opening quote has already been consumed.
+fn string_literal_end(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{ColumnSchema, MemoryCatalog, TableSchemaBuilder};
+    use crate::parser::Parser;
+    use crate::types::SqlType;
+
+    fn catalog() -> MemoryCatalog {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        catalog.set_identifier_matching(IdentifierMatching::AsciiCaseInsensitive);
+        catalog.add_table(
+            TableSchemaBuilder::new("users")
+                .column(ColumnSchema::new("id", SqlType::Int64).not_null())
+                .column(ColumnSchema::new("name", SqlType::Varchar(None)))
+                .build(),
+        );
+        catalog.add_table(
+            TableSchemaBuilder::new("orders")
+                .column(ColumnSchema::new("id", SqlType::Int64).not_null())
+                .column(ColumnSchema::new("user_id", SqlType::Int64))
+                .column(ColumnSchema::new("amount", SqlType::Float64))
+                .build(),
+        );
+        catalog
+    }
+
+    fn report(old: &str, new: &str) -> ComparisonReport {
+        let old = Parser::new(old).parse().unwrap().remove(0);
+        let new = Parser::new(new).parse().unwrap().remove(0);
+        compare(&old, &new, &catalog())
+    }
+
+    #[test]
+    fn test_identical_after_normalization() {
+        let r = report(
+            "SELECT u.name FROM users u WHERE u.id = 1 AND u.name = 'x'",
+            "select U.NAME from USERS u where 'x' = u.name and u.id = 1",
+        );
+        for d in &r.dimensions {
+            assert_eq!(
+                d.outcome,
+                Outcome::Equal,
+                "{}: {:?}",
+                d.dimension,
+                d.details
+            );
+        }
+        // Conjuncts were reordered, so the statements are not identical.
+        assert_eq!(r.overall, Outcome::Unknown);
+
+        let r = report("SELECT id FROM users", "select ID  from Users");
+        assert_eq!(r.overall, Outcome::Equal);
+    }
+
+    #[test]
+    fn test_join_type_change_is_different() {
+        let r = report(
+            "SELECT u.name, o.amount FROM users u JOIN orders o ON u.id = o.user_id",
+            "SELECT u.name, o.amount FROM users u LEFT JOIN orders o ON o.user_id = u.id",
+        );
+        assert_eq!(r.outcome(Dimension::Joins), Outcome::Different);
+        assert_eq!(r.outcome(Dimension::Tables), Outcome::Equal);
+        assert_eq!(r.outcome(Dimension::Filters), Outcome::Equal);
+        assert_eq!(r.overall, Outcome::Different);
+    }
+
+    #[test]
+    fn test_added_distinct_is_different() {
+        let r = report("SELECT name FROM users", "SELECT DISTINCT name FROM users");
+        assert_eq!(r.outcome(Dimension::Distinct), Outcome::Different);
+        assert_eq!(r.outcome(Dimension::OutputSchema), Outcome::Equal);
+        assert_eq!(r.overall, Outcome::Different);
+    }
+
+    #[test]
+    fn test_schema_tables_and_aggregation() {
+        let r = report("SELECT id, name FROM users", "SELECT name, id FROM users");
+        assert_eq!(r.outcome(Dimension::OutputSchema), Outcome::Different);
+        let old = Parser::new("SELECT id, name FROM users").parse().unwrap();
+        let new = Parser::new("SELECT name, id FROM users").parse().unwrap();
+        let options = CompareOptions {
+            order_insensitive_columns: true,
+        };
+        let r = compare_with_options(&old[0], &new[0], &catalog(), &options);
+        assert_eq!(r.outcome(Dimension::OutputSchema), Outcome::Equal);
+
+        let r = report(
+            "WITH users AS (SELECT id FROM orders) SELECT id FROM users",
+            "SELECT id FROM users",
+        );
+        assert_eq!(r.outcome(Dimension::Tables), Outcome::Different);
+
+        let r = report(
+            "SELECT user_id, SUM(amount) FROM orders GROUP BY user_id",
+            "SELECT user_id, AVG(amount) FROM orders GROUP BY user_id",
+        );
+        assert_eq!(r.outcome(Dimension::Aggregation), Outcome::Different);
+
+        let r = report("SELECT nope FROM users", "SELECT nope FROM users");
+        assert_eq!(r.outcome(Dimension::OutputSchema), Outcome::Unknown);
+    }
+}
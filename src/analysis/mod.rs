@@ -0,0 +1,14 @@
+//! Tooling built on top of the parser and analyzer.
+//!
+//! This module hosts higher-level utilities that inspect whole statements,
+//! such as [`compare`] for best-effort equivalence checking and
+//! [`references`] for the tables and functions a statement uses.
+
+mod compare;
+mod references;
+
+pub use compare::{
+    compare, compare_with_options, CompareOptions, ComparisonReport, Dimension, DimensionResult,
+    Outcome,
+};
+pub use references::{references, AccessMode, StatementReferences, TableReference};
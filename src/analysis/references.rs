@@ -0,0 +1,346 @@
+//! The tables and functions a statement uses.
+//!
+//! [`references`] is purely syntactic: it needs no catalog, so it works on
+//! statements against unknown schemas. Names are reported as written, and
+//! two references to the same object are merged when their parts match
+//! under [`IdentifierMatching::default`].
+
+use crate::ast::visit::{walk_query, walk_statement, walk_table_ref, Visitor};
+use crate::ast::*;
+use std::collections::BTreeSet;
+
+/// How a statement uses a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccessMode {
+    /// Rows are read: FROM, JOIN, subqueries, and the source of a MERGE.
+    Read,
+    /// Rows are inserted.
+    Insert,
+    /// Rows are updated.
+    Update,
+    /// Rows are deleted, including by TRUNCATE.
+    Delete,
+    /// The table or view is created.
+    Create,
+    /// The table or view is dropped.
+    Drop,
+    /// The table's or view's definition is changed, or an index is created
+    /// on it.
+    Alter,
+}
+
+/// A table or view a statement uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableReference {
+    /// The name as first written in the statement.
+    pub name: ObjectName,
+    /// Every way the statement uses the table.
+    pub modes: BTreeSet<AccessMode>,
+}
+
+/// The tables and functions a statement uses, returned by [`references`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatementReferences {
+    /// The tables and views, in order of first reference. CTE names are
+    /// not included.
+    pub tables: Vec<TableReference>,
+    /// The functions called, including table functions, in order of first
+    /// call.
+    pub functions: Vec<ObjectName>,
+}
+
+impl StatementReferences {
+    /// The table referenced by `name`, which is matched as a single
+    /// unquoted part, if any.
+    pub fn table(&self, name: &str) -> Option<&TableReference> {
+        self.tables.iter().find(|t| {
+            t.name.parts.len() == 1 && t.name.parts[0].matches_with(name, Default::default())
+        })
+    }
+
+    /// The tables the statement reads.
+    pub fn reads(&self) -> impl Iterator<Item = &TableReference> {
+        self.tables
+            .iter()
+            .filter(|t| t.modes.contains(&AccessMode::Read))
+    }
+
+    /// The tables the statement modifies, creates, drops, or alters.
+    pub fn writes(&self) -> impl Iterator<Item = &TableReference> {
+        self.tables
+            .iter()
+            .filter(|t| t.modes.iter().any(|m| *m != AccessMode::Read))
+    }
+}
+
+/// Collect the tables `stmt` references, with how each is used, and the
+/// functions it calls.
+///
+/// CTE names are resolved by scope, so a CTE that shadows a table hides it
+/// only where the CTE is visible.
+///
+/// ```
+/// use vibesql::analysis::{references, AccessMode};
+/// use vibesql::parser::Parser;
+///
+/// let stmt = Parser::new("INSERT INTO a SELECT COUNT(*) FROM b")
+///     .parse_statement()
+///     .unwrap();
+/// let refs = references(&stmt);
+/// assert!(refs.table("a").unwrap().modes.contains(&AccessMode::Insert));
+/// assert!(refs.table("b").unwrap().modes.contains(&AccessMode::Read));
+/// assert_eq!(refs.functions[0].to_string(), "COUNT");
+/// ```
+pub fn references(stmt: &Statement) -> StatementReferences {
+    let mut collector = Collector::default();
+    collector.visit_statement(stmt);
+    collector.refs
+}
+
+#[derive(Default)]
+struct Collector<'ast> {
+    refs: StatementReferences,
+    /// The CTE names in scope, innermost last.
+    ctes: Vec<&'ast Ident>,
+    /// A table reference that is recorded by its statement rather than as
+    /// a read.
+    target: Option<&'ast TableRef>,
+}
+
+impl<'ast> Collector<'ast> {
+    fn record(&mut self, name: &ObjectName, mode: AccessMode) {
+        let key = name.normalized(IdentifierMatching::default());
+        let existing = self
+            .refs
+            .tables
+            .iter_mut()
+            .find(|t| t.name.normalized(IdentifierMatching::default()) == key);
+        match existing {
+            Some(table) => {
+                table.modes.insert(mode);
+            }
+            None => self.refs.tables.push(TableReference {
+                name: name.clone(),
+                modes: BTreeSet::from([mode]),
+            }),
+        }
+    }
+
+    fn record_function(&mut self, name: &ObjectName) {
+        let key = name.normalized(IdentifierMatching::default());
+        if !self
+            .refs
+            .functions
+            .iter()
+            .any(|f| f.normalized(IdentifierMatching::default()) == key)
+        {
+            self.refs.functions.push(name.clone());
+        }
+    }
+
+    fn is_cte(&self, name: &ObjectName) -> bool {
+        match name.parts.as_slice() {
+            [part] => self.ctes.iter().any(|cte| {
+                cte.normalized(IdentifierMatching::default())
+                    == part.normalized(IdentifierMatching::default())
+            }),
+            _ => false,
+        }
+    }
+
+    /// Record the table named by a statement's target reference, marking
+    /// it so that it is not also recorded as a read.
+    fn record_target(&mut self, table: &'ast TableRef, mode: AccessMode) {
+        if let TableRefKind::Table { name, .. } = &table.kind {
+            self.record(name, mode);
+        }
+        self.target = Some(table);
+    }
+}
+
+impl<'ast> Visitor<'ast> for Collector<'ast> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::Insert(insert) => self.record(&insert.table, AccessMode::Insert),
+            StatementKind::Update(update) => self.record_target(&update.table, AccessMode::Update),
+            StatementKind::Delete(delete) => self.record(&delete.table, AccessMode::Delete),
+            StatementKind::Truncate(truncate) => self.record(&truncate.table, AccessMode::Delete),
+            StatementKind::Merge(merge) => {
+                // The target is also read, to match rows against the source.
+                if let TableRefKind::Table { name, .. } = &merge.target.kind {
+                    for clause in &merge.clauses {
+                        let mode = match clause {
+                            MergeClause::Matched { action, .. }
+                            | MergeClause::NotMatchedBySource { action, .. } => match action {
+                                MergeMatchedAction::Update { .. } => AccessMode::Update,
+                                MergeMatchedAction::Delete => AccessMode::Delete,
+                            },
+                            MergeClause::NotMatched { .. } => AccessMode::Insert,
+                        };
+                        self.record(name, mode);
+                    }
+                }
+            }
+            StatementKind::CreateTable(create) => {
+                self.record(&create.name, AccessMode::Create);
+                for source in create.like.iter().chain(&create.clone) {
+                    self.record(source, AccessMode::Read);
+                }
+            }
+            StatementKind::CreateView(create) => self.record(&create.name, AccessMode::Create),
+            StatementKind::CreateIndex(create) => self.record(&create.table, AccessMode::Alter),
+            StatementKind::AlterTable(alter) => self.record(&alter.name, AccessMode::Alter),
+            StatementKind::AlterView(alter) => self.record(&alter.name, AccessMode::Alter),
+            StatementKind::Drop(drop) => {
+                if matches!(
+                    drop.object_type,
+                    ObjectType::Table | ObjectType::View | ObjectType::MaterializedView
+                ) {
+                    for name in &drop.names {
+                        self.record(name, AccessMode::Drop);
+                    }
+                }
+            }
+            StatementKind::Describe(describe) => self.record(&describe.object, AccessMode::Read),
+            _ => {}
+        }
+        walk_statement(self, stmt);
+    }
+
+    fn visit_query(&mut self, query: &'ast Query) {
+        let depth = self.ctes.len();
+        if let Some(with) = &query.with {
+            // A recursive CTE is visible in its own body and in those of
+            // the CTEs before it.
+            if with.recursive {
+                self.ctes.extend(with.ctes.iter().map(|cte| &cte.name));
+            }
+        }
+        walk_query(self, query);
+        self.ctes.truncate(depth);
+    }
+
+    fn visit_cte(&mut self, cte: &'ast Cte) {
+        self.visit_query(&cte.query);
+        self.ctes.push(&cte.name);
+    }
+
+    fn visit_table_ref(&mut self, table: &'ast TableRef) {
+        let is_target = self.target.is_some_and(|t| std::ptr::eq(t, table));
+        match &table.kind {
+            TableRefKind::Table { name, .. } if !is_target && !self.is_cte(name) => {
+                self.record(name, AccessMode::Read);
+            }
+            TableRefKind::TableFunction { name, .. } => self.record_function(name),
+            _ => {}
+        }
+        walk_table_ref(self, table);
+    }
+
+    fn visit_function_call(&mut self, call: &'ast FunctionCall) {
+        self.record_function(&call.name);
+        crate::ast::visit::walk_function_call(self, call);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn refs(sql: &str) -> StatementReferences {
+        references(&Parser::new(sql).parse_statement().unwrap())
+    }
+
+    fn modes(refs: &StatementReferences, name: &str) -> Vec<AccessMode> {
+        refs.table(name)
+            .unwrap_or_else(|| panic!("{} is not referenced", name))
+            .modes
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_dml_modes() {
+        use AccessMode::*;
+
+        let r = refs("INSERT INTO a SELECT * FROM b JOIN c USING (id)");
+        assert_eq!(modes(&r, "a"), [Insert]);
+        assert_eq!(modes(&r, "b"), [Read]);
+        assert_eq!(modes(&r, "c"), [Read]);
+
+        let r = refs("UPDATE t SET x = 1 FROM s WHERE t.id = s.id AND s.id IN (SELECT id FROM u)");
+        assert_eq!(modes(&r, "t"), [Update]);
+        assert_eq!(modes(&r, "s"), [Read]);
+        assert_eq!(modes(&r, "u"), [Read]);
+
+        let r = refs("DELETE FROM t WHERE id IN (SELECT id FROM t WHERE x > 0)");
+        assert_eq!(modes(&r, "t"), [Read, Delete]);
+
+        let r = refs(
+            "MERGE INTO t USING s ON t.id = s.id \
+             WHEN MATCHED THEN UPDATE SET x = s.x \
+             WHEN NOT MATCHED THEN INSERT (id, x) VALUES (s.id, s.x)",
+        );
+        assert_eq!(modes(&r, "t"), [Read, Insert, Update]);
+        assert_eq!(modes(&r, "s"), [Read]);
+        assert_eq!(r.writes().count(), 1);
+    }
+
+    #[test]
+    fn test_ddl_modes() {
+        use AccessMode::*;
+
+        let r = refs("CREATE VIEW v AS SELECT * FROM a WHERE x IN (SELECT x FROM b)");
+        assert_eq!(modes(&r, "v"), [Create]);
+        assert_eq!(modes(&r, "a"), [Read]);
+        assert_eq!(modes(&r, "b"), [Read]);
+
+        let r = refs("CREATE TABLE t AS SELECT * FROM a");
+        assert_eq!(modes(&r, "t"), [Create]);
+        assert_eq!(modes(&r, "a"), [Read]);
+
+        let r = refs("DROP TABLE a, b");
+        assert_eq!(modes(&r, "a"), [Drop]);
+        assert_eq!(modes(&r, "b"), [Drop]);
+
+        assert!(refs("DROP FUNCTION f").tables.is_empty());
+        assert_eq!(
+            modes(&refs("ALTER TABLE a ADD COLUMN y INT64"), "a"),
+            [Alter]
+        );
+        assert_eq!(modes(&refs("TRUNCATE TABLE a"), "a"), [Delete]);
+    }
+
+    #[test]
+    fn test_ctes_and_deduplication() {
+        // The CTE `a` shadows the table only after its definition.
+        let r = refs(
+            "WITH a AS (SELECT * FROM a), b AS (SELECT * FROM a) \
+             SELECT * FROM b JOIN c ON TRUE JOIN C ON TRUE",
+        );
+        let tables: Vec<String> = r.tables.iter().map(|t| t.name.to_string()).collect();
+        assert_eq!(tables, ["a", "c"]);
+
+        let r = refs(
+            "WITH RECURSIVE n AS (SELECT 1 AS x UNION ALL SELECT x + 1 FROM n WHERE x < 3) \
+             SELECT * FROM n, (SELECT * FROM n) AS m",
+        );
+        assert!(r.tables.is_empty());
+
+        // Outside the query that defines it, a CTE name is a table again.
+        let r = refs("SELECT (SELECT 1 FROM (WITH n AS (SELECT 1) SELECT * FROM n)) FROM n");
+        assert_eq!(r.tables.len(), 1);
+
+        let r = refs(
+            "SELECT upper(name), UPPER(name), COUNT(*), ROW_NUMBER() OVER (ORDER BY id) \
+             FROM UNNEST([1]) AS x, generate_series(1, 3)",
+        );
+        let functions: Vec<String> = r.functions.iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            functions,
+            ["upper", "COUNT", "ROW_NUMBER", "generate_series"]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Evaluation of constant expressions.
+//!
+//! These functions fold operators applied to literal values, with the
+//! semantics [`eval`](crate::eval) gives them at runtime. They return
+//! `None` when the result is not known at analysis time, including on
+//! integer overflow and division by zero, so the expression is left to be
+//! evaluated (and fail) at runtime.
+
+use crate::ast::{BinaryOp, UnaryOp};
+use crate::eval;
+use crate::types::Value;
+
+/// Fold a unary operator applied to a constant.
+pub(crate) fn fold_unary(op: UnaryOp, value: &Value) -> Option<Value> {
+    eval::unary_op(op, value).ok()
+}
+
+/// Fold a binary operator applied to two constants.
+///
+/// AND and OR follow three-valued logic, so `NULL AND FALSE` is FALSE and
+/// `NULL OR TRUE` is TRUE; other operators return NULL when either operand
+/// is NULL.
+pub(crate) fn fold_binary(op: BinaryOp, left: &Value, right: &Value) -> Option<Value> {
+    eval::binary_op(op, left, right).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::StatementKind;
+    use crate::catalog::MemoryCatalog;
+    use crate::testgen::Rng;
+    use crate::types::Numeric;
+    use crate::{Analyzer, Parser};
+
+    #[test]
+    fn test_fold_binary() {
+        use Value::{Boolean, Float64, Int64, Null};
+
+        let cases = [
+            (BinaryOp::Plus, Int64(1), Int64(1), Some(Int64(2))),
+            (BinaryOp::Plus, Int64(i64::MAX), Int64(1), None),
+            (BinaryOp::Multiply, Int64(i64::MAX), Int64(2), None),
+            (BinaryOp::Divide, Int64(7), Int64(2), Some(Int64(3))),
+            (BinaryOp::Divide, Int64(1), Int64(0), None),
+            (BinaryOp::Modulo, Int64(1), Int64(0), None),
+            (BinaryOp::Divide, Int64(i64::MIN), Int64(-1), None),
+            (BinaryOp::Divide, Float64(1.0), Int64(0), None),
+            (BinaryOp::Plus, Int64(1), Float64(0.5), Some(Float64(1.5))),
+            (BinaryOp::Minus, Int64(1), Null, Some(Null)),
+            (BinaryOp::And, Null, Boolean(false), Some(Boolean(false))),
+            (BinaryOp::And, Null, Boolean(true), Some(Null)),
+            (BinaryOp::Or, Null, Boolean(true), Some(Boolean(true))),
+            (BinaryOp::Or, Boolean(false), Null, Some(Null)),
+            (
+                BinaryOp::Or,
+                Boolean(false),
+                Boolean(true),
+                Some(Boolean(true)),
+            ),
+            (BinaryOp::Lt, Int64(1), Float64(1.5), Some(Boolean(true))),
+            (BinaryOp::Eq, Int64(1), Int64(1), Some(Boolean(true))),
+            (BinaryOp::Eq, Int64(1), Null, Some(Null)),
+            (BinaryOp::Eq, Int64(1), Value::from("1"), None),
+            (
+                BinaryOp::Concat,
+                Value::from("a"),
+                Value::from("b"),
+                Some(Value::from("ab")),
+            ),
+        ];
+        for (op, left, right, expected) in cases {
+            assert_eq!(
+                fold_binary(op, &left, &right),
+                expected,
+                "{:?} {:?} {:?}",
+                left,
+                op,
+                right
+            );
+        }
+
+        assert_eq!(fold_unary(UnaryOp::Minus, &Int64(i64::MIN)), None);
+        assert_eq!(fold_unary(UnaryOp::Not, &Null), Some(Null));
+        assert_eq!(
+            fold_unary(UnaryOp::Not, &Boolean(true)),
+            Some(Boolean(false))
+        );
+    }
+
+    /// The constant value of the only column of `SELECT <expr>`, or `None`
+    /// when the analyzer rejects it.
+    fn folded(expr: &str) -> Option<Option<Value>> {
+        let sql = format!("SELECT {}", expr);
+        let stmts = Parser::new(&sql).parse().unwrap();
+        let StatementKind::Query(query) = &stmts[0].kind else {
+            unreachable!("a SELECT is a query");
+        };
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        let result = Analyzer::with_catalog(&catalog).analyze_query_result(query);
+        result
+            .ok()
+            .map(|query| query.columns[0].constant_value.clone())
+    }
+
+    fn sample_values() -> Vec<Value> {
+        let numeric = |s: &str| Value::Numeric(Numeric::parse(s).unwrap());
+        vec![
+            Value::Null,
+            Value::Boolean(true),
+            Value::Int64(0),
+            Value::Int64(3),
+            Value::Int64(-7),
+            Value::Int64(i64::MAX),
+            Value::Int64(i64::MIN),
+            Value::Float64(0.5),
+            Value::Float64(-2.25),
+            Value::Float64(1e300),
+            numeric("1.5"),
+            numeric("-0.125"),
+            numeric("99999999999999999999999999999"),
+            Value::from("a'b"),
+            Value::from(vec![0u8, 255]),
+            Value::Date(19_000),
+            Value::Timestamp(-1),
+        ]
+    }
+
+    #[test]
+    fn test_folding_matches_value_operations() {
+        let values = sample_values();
+        let ops = [
+            BinaryOp::Plus,
+            BinaryOp::Minus,
+            BinaryOp::Multiply,
+            BinaryOp::Divide,
+            BinaryOp::Modulo,
+            BinaryOp::Eq,
+            BinaryOp::NotEq,
+            BinaryOp::Lt,
+            BinaryOp::GtEq,
+        ];
+        let mut rng = Rng::new(2322);
+        for _ in 0..1000 {
+            let (left, right, op) = (rng.pick(&values), rng.pick(&values), *rng.pick(&ops));
+            let Some(folded) = folded(&format!("{} {} ({})", left, op, right)) else {
+                continue;
+            };
+            let ordering = left.partial_cmp(right);
+            let expected = match op {
+                BinaryOp::Plus => left.add(right).ok(),
+                BinaryOp::Minus => left.sub(right).ok(),
+                BinaryOp::Multiply => left.mul(right).ok(),
+                BinaryOp::Divide => left.div(right).ok(),
+                BinaryOp::Modulo => left.rem(right).ok(),
+                _ if left.is_null() || right.is_null() => Some(Value::Null),
+                BinaryOp::Eq => ordering.map(|o| Value::Boolean(o.is_eq())),
+                BinaryOp::NotEq => ordering.map(|o| Value::Boolean(o.is_ne())),
+                BinaryOp::Lt => ordering.map(|o| Value::Boolean(o.is_lt())),
+                _ => ordering.map(|o| Value::Boolean(o.is_ge())),
+            };
+            assert_eq!(folded, expected, "{} {} {}", left, op, right);
+        }
+    }
+
+    #[test]
+    fn test_display_folds_to_value() {
+        for value in sample_values() {
+            assert_eq!(folded(&value.to_string()), Some(Some(value.clone())));
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Analyzer-specific error types.
 
+use super::suggest;
 use crate::error::Span;
 use crate::types::SqlType;
 use std::fmt;
@@ -11,10 +12,37 @@ pub enum AnalyzerErrorKind {
     TableNotFound { name: String },
     /// Column not found.
     ColumnNotFound { name: String, table: Option<String> },
+    /// A struct has no field with the name accessed.
+    FieldNotFound {
+        name: String,
+        /// The struct-typed column, field, or expression accessed.
+        parent: String,
+        /// The names of the struct's fields.
+        available: Vec<String>,
+    },
     /// Ambiguous column reference.
-    AmbiguousColumn { name: String, tables: Vec<String> },
+    AmbiguousColumn {
+        name: String,
+        candidates: Vec<String>,
+    },
+    /// Table name that matches several catalog tables, none exactly.
+    AmbiguousTable {
+        name: String,
+        candidates: Vec<String>,
+    },
     /// Function not found.
-    FunctionNotFound { name: String },
+    FunctionNotFound {
+        name: String,
+        /// The schemas searched for the function, in order, when the
+        /// catalog reports them.
+        searched: Vec<String>,
+    },
+    /// Named data type not found.
+    TypeNotFound { name: String },
+    /// System variable not defined by the session.
+    VariableNotFound { name: String },
+    /// Function body references an undeclared parameter.
+    UndefinedParameter { function: String, name: String },
     /// Wrong number of arguments to function.
     WrongArgumentCount {
         function: String,
@@ -28,41 +56,114 @@ pub enum AnalyzerErrorKind {
         actual: SqlType,
         context: String,
     },
+    /// Value cannot be assigned to a column.
+    InvalidAssignment {
+        column: String,
+        column_type: SqlType,
+        value_type: SqlType,
+    },
+    /// NULL assigned to a NOT NULL column.
+    NullAssignment {
+        column: String,
+        column_type: SqlType,
+    },
+    /// INSERT supplies a different number of values than target columns.
+    InsertColumnCountMismatch { expected: usize, found: usize },
+    /// INSERT omits a NOT NULL column that has no default.
+    MissingInsertColumn { column: String },
     /// Types are not comparable.
     TypesNotComparable { left: SqlType, right: SqlType },
     /// Invalid use of aggregate function.
     InvalidAggregateUse { function: String, reason: String },
     /// Invalid use of window function.
     InvalidWindowUse { function: String, reason: String },
+    /// Invalid window specification or frame.
+    InvalidWindowSpec { reason: String },
+    /// Invalid TABLESAMPLE size or seed.
+    InvalidTableSample { reason: String },
+    /// Invalid PIVOT or UNPIVOT operator.
+    InvalidPivot { operator: String, reason: String },
     /// Duplicate alias.
     DuplicateAlias { name: String },
     /// Duplicate column in GROUP BY.
     DuplicateGroupByColumn { name: String },
+    /// Column named twice in a CTE or alias column list.
+    DuplicateColumn { name: String },
+    /// CTE or alias column list does not fit the relation's columns.
+    ColumnListMismatch {
+        relation: String,
+        expected: usize,
+        found: usize,
+    },
     /// Non-aggregated column in SELECT with GROUP BY.
     NonAggregatedColumn { column: String },
+    /// GROUP BY or ORDER BY ordinal outside the select list.
+    PositionOutOfRange {
+        clause: String,
+        position: i64,
+        count: usize,
+    },
     /// ORDER BY column not in SELECT (when DISTINCT is used).
     OrderByNotInSelect { column: String },
+    /// ORDER BY starts with an expression outside `DISTINCT ON`.
+    DistinctOnOrderMismatch { expr: String },
     /// Invalid HAVING clause (no GROUP BY).
     HavingWithoutGroupBy,
+    /// `FETCH ... WITH TIES` on a query without ORDER BY.
+    WithTiesWithoutOrderBy,
     /// Invalid subquery.
     InvalidSubquery { reason: String },
+    /// A subquery used as a value, or compared with a row, returns other
+    /// than the expected number of columns.
+    SubqueryColumnCount { expected: usize, found: usize },
     /// Division by zero (constant folding).
     DivisionByZero,
     /// Invalid CAST.
     InvalidCast { from: SqlType, to: SqlType },
-    /// Invalid date/time literal.
-    InvalidDateTimeLiteral {
+    /// A typed literal whose string is not a value of its type, such as
+    /// `DATE '2024-13-45'`.
+    InvalidLiteral {
         value: String,
         expected_type: String,
+        reason: String,
     },
     /// CTE name conflict.
     DuplicateCte { name: String },
+    /// Too many CTE definitions in one statement.
+    TooManyCtes { limit: usize },
+    /// An expression nested deeper than
+    /// [`AnalyzerOptions::max_expr_depth`](super::AnalyzerOptions::max_expr_depth).
+    ExpressionTooDeep { limit: usize },
+    /// Comma-separated FROM items when
+    /// [`AnalyzerOptions::allow_comma_joins`](super::AnalyzerOptions::allow_comma_joins)
+    /// is off.
+    ImplicitCrossJoin,
+    /// A join other than CROSS or NATURAL without an ON or USING clause.
+    MissingJoinCondition,
+    /// A CROSS JOIN with an ON or USING clause.
+    CrossJoinCondition,
+    /// MERGE whose WHEN clauses conflict or read what they cannot.
+    InvalidMerge { reason: String },
     /// Recursive CTE without UNION ALL.
     InvalidRecursiveCte { reason: String },
+    /// ORDER BY on a type without an ordering.
+    NotOrderable { data_type: SqlType },
+    /// UPDATE/DELETE/MERGE that can affect every row, rejected by
+    /// [`WriteGuard::Error`](super::WriteGuard::Error).
+    UnguardedWrite { statement: String },
+    /// `NATURAL JOIN` inputs share no column names, reported by
+    /// [`DisjointNaturalJoin::Error`](super::DisjointNaturalJoin::Error).
+    NoNaturalJoinColumns,
     /// Star (*) not allowed in this context.
     StarNotAllowed { context: String },
     /// EXCEPT/INTERSECT column count mismatch.
     SetOperationColumnMismatch { left: usize, right: usize },
+    /// Set operation inputs with no common type for a column.
+    SetOperationTypeMismatch {
+        position: usize,
+        left: SqlType,
+        right: SqlType,
+    },
     /// Unknown error.
     Other { message: String },
 }
@@ -74,12 +175,19 @@ pub struct AnalyzerError {
     pub kind: AnalyzerErrorKind,
     /// The source span where the error occurred.
     pub span: Option<Span>,
+    /// A name that was probably meant, shown as "did you mean". Boxed to
+    /// keep `AnalyzerError` small.
+    pub suggestion: Option<Box<str>>,
 }
 
 impl AnalyzerError {
     /// Create a new analyzer error.
     pub fn new(kind: AnalyzerErrorKind) -> Self {
-        Self { kind, span: None }
+        Self {
+            kind,
+            span: None,
+            suggestion: None,
+        }
     }
 
     /// Create a new analyzer error with a span.
@@ -87,9 +195,31 @@ impl AnalyzerError {
         Self {
             kind,
             span: Some(span),
+            suggestion: None,
         }
     }
 
+    /// Suggest the name that was probably meant.
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into().into_boxed_str());
+        self
+    }
+
+    /// Suggest the candidate closest to the unresolved table, column,
+    /// function, or variable name, if one is close enough.
+    pub(crate) fn suggesting<'a>(mut self, candidates: impl IntoIterator<Item = &'a str>) -> Self {
+        let name = match &self.kind {
+            AnalyzerErrorKind::TableNotFound { name }
+            | AnalyzerErrorKind::ColumnNotFound { name, .. }
+            | AnalyzerErrorKind::FieldNotFound { name, .. }
+            | AnalyzerErrorKind::FunctionNotFound { name, .. }
+            | AnalyzerErrorKind::VariableNotFound { name } => name,
+            _ => return self,
+        };
+        self.suggestion = suggest::closest(name, candidates).map(String::into_boxed_str);
+        self
+    }
+
     /// Table not found.
     pub fn table_not_found(name: impl Into<String>) -> Self {
         Self::new(AnalyzerErrorKind::TableNotFound { name: name.into() })
@@ -103,17 +233,25 @@ impl AnalyzerError {
         })
     }
 
-    /// Ambiguous column.
-    pub fn ambiguous_column(name: impl Into<String>, tables: Vec<String>) -> Self {
+    /// Ambiguous column, with its candidates qualified by their tables.
+    pub fn ambiguous_column(name: impl Into<String>, candidates: Vec<String>) -> Self {
         Self::new(AnalyzerErrorKind::AmbiguousColumn {
             name: name.into(),
-            tables,
+            candidates,
         })
     }
 
     /// Function not found.
     pub fn function_not_found(name: impl Into<String>) -> Self {
-        Self::new(AnalyzerErrorKind::FunctionNotFound { name: name.into() })
+        Self::function_not_found_in(name, Vec::new())
+    }
+
+    /// Function not found in any of the searched schemas.
+    pub fn function_not_found_in(name: impl Into<String>, searched: Vec<String>) -> Self {
+        Self::new(AnalyzerErrorKind::FunctionNotFound {
+            name: name.into(),
+            searched,
+        })
     }
 
     /// Wrong argument count.
@@ -179,16 +317,54 @@ impl fmt::Display for AnalyzerError {
                     write!(f, "column '{}' not found", name)
                 }
             }
-            AnalyzerErrorKind::AmbiguousColumn { name, tables } => {
+            AnalyzerErrorKind::FieldNotFound {
+                name,
+                parent,
+                available,
+            } => {
+                write!(
+                    f,
+                    "field '{}' not found in '{}' (fields: {})",
+                    name,
+                    parent,
+                    available.join(", ")
+                )
+            }
+            AnalyzerErrorKind::AmbiguousColumn { name, candidates } => {
+                write!(
+                    f,
+                    "ambiguous column '{}' could be any of: {}",
+                    name,
+                    candidates.join(", ")
+                )
+            }
+            AnalyzerErrorKind::AmbiguousTable { name, candidates } => {
                 write!(
                     f,
-                    "ambiguous column '{}' found in tables: {}",
+                    "ambiguous table '{}' could be any of: {}",
                     name,
-                    tables.join(", ")
+                    candidates.join(", ")
                 )
             }
-            AnalyzerErrorKind::FunctionNotFound { name } => {
-                write!(f, "function '{}' not found", name)
+            AnalyzerErrorKind::FunctionNotFound { name, searched } => {
+                write!(f, "function '{}' not found", name)?;
+                if !searched.is_empty() {
+                    write!(f, " (searched {})", searched.join(", "))?;
+                }
+                Ok(())
+            }
+            AnalyzerErrorKind::TypeNotFound { name } => {
+                write!(f, "type '{}' not found", name)
+            }
+            AnalyzerErrorKind::VariableNotFound { name } => {
+                write!(f, "system variable '@@{}' not found", name)
+            }
+            AnalyzerErrorKind::UndefinedParameter { function, name } => {
+                write!(
+                    f,
+                    "function '{}' has no parameter named '{}'",
+                    function, name
+                )
             }
             AnalyzerErrorKind::WrongArgumentCount {
                 function,
@@ -229,6 +405,41 @@ impl fmt::Display for AnalyzerError {
                     context, expected, actual
                 )
             }
+            AnalyzerErrorKind::InvalidAssignment {
+                column,
+                column_type,
+                value_type,
+            } => {
+                write!(
+                    f,
+                    "cannot assign {} to column '{}' of type {}",
+                    value_type, column, column_type
+                )
+            }
+            AnalyzerErrorKind::NullAssignment {
+                column,
+                column_type,
+            } => {
+                write!(
+                    f,
+                    "cannot assign NULL to NOT NULL column '{}' of type {}",
+                    column, column_type
+                )
+            }
+            AnalyzerErrorKind::InsertColumnCountMismatch { expected, found } => {
+                write!(
+                    f,
+                    "INSERT has {} values but {} target columns",
+                    found, expected
+                )
+            }
+            AnalyzerErrorKind::MissingInsertColumn { column } => {
+                write!(
+                    f,
+                    "INSERT must supply NOT NULL column '{}', which has no default",
+                    column
+                )
+            }
             AnalyzerErrorKind::TypesNotComparable { left, right } => {
                 write!(f, "cannot compare {} with {}", left, right)
             }
@@ -246,12 +457,35 @@ impl fmt::Display for AnalyzerError {
                     function, reason
                 )
             }
+            AnalyzerErrorKind::InvalidWindowSpec { reason } => {
+                write!(f, "invalid window specification: {}", reason)
+            }
+            AnalyzerErrorKind::InvalidTableSample { reason } => {
+                write!(f, "invalid TABLESAMPLE: {}", reason)
+            }
+            AnalyzerErrorKind::InvalidPivot { operator, reason } => {
+                write!(f, "invalid {}: {}", operator, reason)
+            }
             AnalyzerErrorKind::DuplicateAlias { name } => {
                 write!(f, "duplicate alias '{}'", name)
             }
             AnalyzerErrorKind::DuplicateGroupByColumn { name } => {
                 write!(f, "duplicate column '{}' in GROUP BY", name)
             }
+            AnalyzerErrorKind::DuplicateColumn { name } => {
+                write!(f, "column '{}' is named more than once", name)
+            }
+            AnalyzerErrorKind::ColumnListMismatch {
+                relation,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "'{}' has {} columns but its column list names {}",
+                    relation, expected, found
+                )
+            }
             AnalyzerErrorKind::NonAggregatedColumn { column } => {
                 write!(
                     f,
@@ -259,6 +493,17 @@ impl fmt::Display for AnalyzerError {
                     column
                 )
             }
+            AnalyzerErrorKind::PositionOutOfRange {
+                clause,
+                position,
+                count,
+            } => {
+                write!(
+                    f,
+                    "{} position {} is not in select list of {} items",
+                    clause, position, count
+                )
+            }
             AnalyzerErrorKind::OrderByNotInSelect { column } => {
                 write!(
                     f,
@@ -266,30 +511,90 @@ impl fmt::Display for AnalyzerError {
                     column
                 )
             }
+            AnalyzerErrorKind::DistinctOnOrderMismatch { expr } => {
+                write!(
+                    f,
+                    "DISTINCT ON expressions must match the leading ORDER BY expressions, \
+                     but ORDER BY has '{}'",
+                    expr
+                )
+            }
+            AnalyzerErrorKind::WithTiesWithoutOrderBy => {
+                write!(f, "FETCH WITH TIES requires ORDER BY")
+            }
             AnalyzerErrorKind::HavingWithoutGroupBy => {
                 write!(f, "HAVING clause requires GROUP BY clause")
             }
             AnalyzerErrorKind::InvalidSubquery { reason } => {
                 write!(f, "invalid subquery: {}", reason)
             }
+            AnalyzerErrorKind::SubqueryColumnCount { expected: 1, found } => {
+                write!(
+                    f,
+                    "subquery must return exactly one column, but returns {}",
+                    found
+                )
+            }
+            AnalyzerErrorKind::SubqueryColumnCount { expected, found } => {
+                write!(
+                    f,
+                    "subquery must return exactly {} columns, but returns {}",
+                    expected, found
+                )
+            }
             AnalyzerErrorKind::DivisionByZero => {
                 write!(f, "division by zero")
             }
             AnalyzerErrorKind::InvalidCast { from, to } => {
                 write!(f, "cannot cast {} to {}", from, to)
             }
-            AnalyzerErrorKind::InvalidDateTimeLiteral {
+            AnalyzerErrorKind::InvalidLiteral {
                 value,
                 expected_type,
+                reason,
             } => {
-                write!(f, "invalid {} literal: '{}'", expected_type, value)
+                write!(
+                    f,
+                    "invalid {} literal '{}': {}",
+                    expected_type, value, reason
+                )
             }
             AnalyzerErrorKind::DuplicateCte { name } => {
                 write!(f, "duplicate CTE name '{}'", name)
             }
+            AnalyzerErrorKind::TooManyCtes { limit } => {
+                write!(f, "statement defines more than {} CTEs", limit)
+            }
+            AnalyzerErrorKind::ExpressionTooDeep { limit } => {
+                write!(f, "expression is nested more than {} levels deep", limit)
+            }
+            AnalyzerErrorKind::ImplicitCrossJoin => {
+                write!(
+                    f,
+                    "comma-separated FROM items are not allowed; use CROSS JOIN"
+                )
+            }
+            AnalyzerErrorKind::MissingJoinCondition => {
+                write!(f, "JOIN requires an ON or USING clause")
+            }
+            AnalyzerErrorKind::CrossJoinCondition => {
+                write!(f, "CROSS JOIN cannot have an ON or USING clause")
+            }
+            AnalyzerErrorKind::InvalidMerge { reason } => {
+                write!(f, "invalid MERGE: {}", reason)
+            }
             AnalyzerErrorKind::InvalidRecursiveCte { reason } => {
                 write!(f, "invalid recursive CTE: {}", reason)
             }
+            AnalyzerErrorKind::NotOrderable { data_type } => {
+                write!(f, "cannot ORDER BY a value of type {}", data_type)
+            }
+            AnalyzerErrorKind::UnguardedWrite { statement } => {
+                write!(f, "{}", unguarded_write_message(statement))
+            }
+            AnalyzerErrorKind::NoNaturalJoinColumns => {
+                write!(f, "{}", NO_NATURAL_JOIN_COLUMNS)
+            }
             AnalyzerErrorKind::StarNotAllowed { context } => {
                 write!(f, "* not allowed in {}", context)
             }
@@ -300,11 +605,39 @@ impl fmt::Display for AnalyzerError {
                     left, right
                 )
             }
+            AnalyzerErrorKind::SetOperationTypeMismatch {
+                position,
+                left,
+                right,
+            } => {
+                write!(
+                    f,
+                    "set operation column {} has incompatible types {} and {}",
+                    position, left, right
+                )
+            }
             AnalyzerErrorKind::Other { message } => {
                 write!(f, "{}", message)
             }
+        }?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "; did you mean '{}'?", suggestion)?;
         }
+        Ok(())
     }
 }
 
 impl std::error::Error for AnalyzerError {}
+
+/// Message shared by the disjoint NATURAL JOIN error and warning.
+pub(crate) const NO_NATURAL_JOIN_COLUMNS: &str =
+    "NATURAL JOIN inputs share no column names, so it is a cross join";
+
+/// Message shared by the unguarded write error and warning.
+pub(crate) fn unguarded_write_message(statement: &str) -> String {
+    format!(
+        "{} can affect every row: add a WHERE clause that references a column, \
+         or the ALLOW_FULL_SCAN table hint",
+        statement
+    )
+}
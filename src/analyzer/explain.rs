@@ -0,0 +1,318 @@
+//! The resolved query tree reported by [`Analyzer::explain`](super::Analyzer::explain).
+
+use super::{AnalyzedQuery, ColumnOrigin, ParameterType, ScopeColumn};
+use crate::ast::Parameter;
+use crate::types::SqlType;
+use std::cell::RefCell;
+use std::fmt;
+
+/// The analysis of a statement, as a tree.
+///
+/// Its [`Display`](fmt::Display) renders the tree with two spaces of
+/// indentation per level:
+///
+/// ```text
+/// query
+///   from u: table users
+///     id BIGINT NOT NULL
+///     name VARCHAR
+///   column name VARCHAR (u.name)
+///   column n BIGINT, aggregate
+/// parameter ?1: BIGINT
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExplainOutput {
+    query: ExplainQuery,
+    parameters: Vec<ParameterType>,
+}
+
+impl ExplainOutput {
+    pub(crate) fn new(query: ExplainQuery, parameters: Vec<ParameterType>) -> Self {
+        Self { query, parameters }
+    }
+
+    /// The statement as a query. A statement other than a query has no
+    /// output columns, but has the FROM items and subqueries it reads,
+    /// such as the target of an UPDATE and the subqueries of its WHERE.
+    pub fn query(&self) -> &ExplainQuery {
+        &self.query
+    }
+
+    /// The types inferred for the statement's parameter placeholders, as
+    /// returned by [`Analyzer::infer_parameter_types`](super::Analyzer::infer_parameter_types).
+    pub fn parameters(&self) -> &[ParameterType] {
+        &self.parameters
+    }
+}
+
+/// A query with the names it resolved.
+#[derive(Debug, Clone, Default)]
+pub struct ExplainQuery {
+    /// The CTEs the query defines, in definition order.
+    pub ctes: Vec<ExplainCte>,
+    /// The FROM items of the query's SELECTs, in textual order, with the
+    /// items of a join flattened.
+    pub tables: Vec<ExplainTable>,
+    /// The output columns.
+    pub columns: Vec<ExplainColumn>,
+    /// Subqueries in the query's expressions, in the order they were
+    /// analyzed.
+    pub subqueries: Vec<ExplainQuery>,
+}
+
+/// A CTE and the query it names.
+#[derive(Debug, Clone)]
+pub struct ExplainCte {
+    pub name: String,
+    pub query: ExplainQuery,
+}
+
+/// A FROM item and what it bound to.
+#[derive(Debug, Clone)]
+pub struct ExplainTable {
+    /// The name the item is referenced by: its alias, or its table name.
+    pub alias: String,
+    pub source: ExplainSource,
+    /// The columns the item provides.
+    pub columns: Vec<ScopeColumn>,
+}
+
+/// What a FROM item reads.
+#[derive(Debug, Clone)]
+pub enum ExplainSource {
+    /// A catalog table, by its catalog name.
+    Table(String),
+    /// A CTE of this query or an enclosing one.
+    Cte(String),
+    /// A derived table.
+    Subquery(Box<ExplainQuery>),
+    /// `UNNEST` of an array.
+    Unnest,
+    /// A table function, by name.
+    Function(String),
+    /// A PIVOT of the FROM items it contains.
+    Pivot(Vec<ExplainTable>),
+    /// An UNPIVOT of the FROM items it contains.
+    Unpivot(Vec<ExplainTable>),
+}
+
+/// An output column of a query.
+#[derive(Debug, Clone)]
+pub struct ExplainColumn {
+    pub name: String,
+    pub data_type: SqlType,
+    pub nullable: bool,
+    /// The table column the output column reads, if it is a plain column
+    /// reference.
+    pub source: Option<ColumnOrigin>,
+    /// Whether the column's expression calls an aggregate function.
+    pub contains_aggregate: bool,
+    /// Whether the column's expression calls a window function.
+    pub contains_window: bool,
+}
+
+/// The parts of a query found so far while it is analyzed.
+#[derive(Debug, Default)]
+pub(crate) struct OpenQuery {
+    pub(crate) ctes: Vec<ExplainCte>,
+    pub(crate) tables: Vec<ExplainTable>,
+    pub(crate) subqueries: Vec<ExplainQuery>,
+    /// Whether each output position contains an aggregate or a window
+    /// function in any of the query's SELECTs.
+    flags: Vec<(bool, bool)>,
+}
+
+impl OpenQuery {
+    pub(crate) fn add_table(
+        &mut self,
+        alias: &str,
+        source: ExplainSource,
+        columns: &[ScopeColumn],
+    ) {
+        self.tables.push(ExplainTable {
+            alias: alias.to_string(),
+            source,
+            columns: columns.to_vec(),
+        });
+    }
+
+    /// Record the aggregates and window functions of the select item at
+    /// `position`.
+    pub(crate) fn flag_column(&mut self, position: usize, aggregate: bool, window: bool) {
+        if self.flags.len() <= position {
+            self.flags.resize(position + 1, (false, false));
+        }
+        self.flags[position].0 |= aggregate;
+        self.flags[position].1 |= window;
+    }
+
+    fn finish(self, analyzed: Option<&AnalyzedQuery>) -> ExplainQuery {
+        let columns = analyzed
+            .map_or(&[][..], |analyzed| &analyzed.columns)
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let (contains_aggregate, contains_window) =
+                    self.flags.get(i).copied().unwrap_or_default();
+                ExplainColumn {
+                    name: column.name.clone(),
+                    data_type: column.data_type.clone(),
+                    nullable: column.nullable,
+                    source: column.source.clone(),
+                    contains_aggregate,
+                    contains_window,
+                }
+            })
+            .collect();
+        ExplainQuery {
+            ctes: self.ctes,
+            tables: self.tables,
+            columns,
+            subqueries: self.subqueries,
+        }
+    }
+}
+
+/// The queries an explaining analyzer has open, innermost last.
+#[derive(Debug, Default)]
+pub(crate) struct ExplainRecorder {
+    open: RefCell<Vec<OpenQuery>>,
+}
+
+impl ExplainRecorder {
+    pub(crate) fn open(&self) {
+        self.open.borrow_mut().push(OpenQuery::default());
+    }
+
+    /// Close the innermost open query, which analyzed to `analyzed`, or
+    /// has no output columns when `None`.
+    pub(crate) fn close(&self, analyzed: Option<&AnalyzedQuery>) -> Option<ExplainQuery> {
+        let open = self.open.borrow_mut().pop()?;
+        Some(open.finish(analyzed))
+    }
+
+    /// Update the innermost open query, if there is one.
+    pub(crate) fn record(&self, update: impl FnOnce(&mut OpenQuery)) {
+        if let Some(open) = self.open.borrow_mut().last_mut() {
+            update(open);
+        }
+    }
+}
+
+impl fmt::Display for ExplainOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.query.write_tree(f, 0)?;
+        for parameter in &self.parameters {
+            // Number `?` placeholders, which otherwise all print alike.
+            match &parameter.parameter {
+                Parameter::Positional(n) => write!(f, "parameter ?{}", n)?,
+                other => write!(f, "parameter {}", other)?,
+            }
+            writeln!(f, ": {}", parameter.data_type)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ExplainQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
+
+impl ExplainQuery {
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        writeln!(f, "{:indent$}query", "", indent = depth * 2)?;
+        for cte in &self.ctes {
+            writeln!(f, "{:indent$}cte {}", "", cte.name, indent = depth * 2 + 2)?;
+            cte.query.write_tree(f, depth + 2)?;
+        }
+        for table in &self.tables {
+            table.write_tree(f, depth + 1)?;
+        }
+        for column in &self.columns {
+            write!(
+                f,
+                "{:indent$}column {} {}",
+                "",
+                column.name,
+                column.data_type,
+                indent = depth * 2 + 2
+            )?;
+            if !column.nullable {
+                write!(f, " NOT NULL")?;
+            }
+            if let Some(source) = &column.source {
+                write!(f, " ({}.{})", source.table, source.column)?;
+            }
+            if column.contains_aggregate {
+                write!(f, ", aggregate")?;
+            }
+            if column.contains_window {
+                write!(f, ", window")?;
+            }
+            writeln!(f)?;
+        }
+        for subquery in &self.subqueries {
+            writeln!(f, "{:indent$}subquery", "", indent = depth * 2 + 2)?;
+            subquery.write_tree(f, depth + 2)?;
+        }
+        Ok(())
+    }
+}
+
+impl ExplainTable {
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = depth * 2;
+        write!(f, "{:indent$}from {}: ", "", self.alias)?;
+        let inputs: &[ExplainTable] = match &self.source {
+            ExplainSource::Table(name) => {
+                writeln!(f, "table {}", name)?;
+                &[]
+            }
+            ExplainSource::Cte(name) => {
+                writeln!(f, "cte {}", name)?;
+                &[]
+            }
+            // The subquery's output columns are the item's columns.
+            ExplainSource::Subquery(query) => {
+                writeln!(f, "subquery")?;
+                return query.write_tree(f, depth + 1);
+            }
+            ExplainSource::Unnest => {
+                writeln!(f, "unnest")?;
+                &[]
+            }
+            ExplainSource::Function(name) => {
+                writeln!(f, "function {}", name)?;
+                &[]
+            }
+            ExplainSource::Pivot(inputs) => {
+                writeln!(f, "pivot")?;
+                inputs
+            }
+            ExplainSource::Unpivot(inputs) => {
+                writeln!(f, "unpivot")?;
+                inputs
+            }
+        };
+        for column in &self.columns {
+            write!(
+                f,
+                "{:indent$}{} {}",
+                "",
+                column.name,
+                column.data_type,
+                indent = indent + 2
+            )?;
+            if !column.nullable {
+                write!(f, " NOT NULL")?;
+            }
+            writeln!(f)?;
+        }
+        for input in inputs {
+            input.write_tree(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+//! Analysis of SQL-language function definitions.
+
+use super::error::{AnalyzerError, AnalyzerErrorKind};
+use super::scope::{Scope, ScopeColumn, ScopeTable};
+use super::type_checker::{sql_type_from_spec, TypeChecker};
+use crate::ast::*;
+use crate::catalog::{Catalog, FunctionParameter, FunctionSignature};
+use crate::types::SqlType;
+
+/// Check a CREATE FUNCTION statement and build the signature it defines.
+///
+/// An expression body is type checked in a scope holding the declared
+/// parameters, which can also be qualified with the function name. The
+/// body must not contain aggregates, window functions, or subqueries, and
+/// its type must be assignable to RETURNS; when RETURNS is omitted, the
+/// body type is used. Other bodies are not analyzed and default to an
+/// Unknown return type.
+pub(crate) fn check_create_function<C: Catalog>(
+    catalog: &C,
+    create: &CreateFunctionStatement,
+    matching: IdentifierMatching,
+) -> Result<FunctionSignature, AnalyzerError> {
+    let name = create
+        .name
+        .parts
+        .last()
+        .map(|i| i.value.clone())
+        .unwrap_or_default();
+    let params: Vec<(Option<String>, SqlType)> = create
+        .params
+        .iter()
+        .map(|p| {
+            Ok((
+                p.name.as_ref().map(|n| n.value.clone()),
+                sql_type_from_spec(catalog, &p.data_type)?,
+            ))
+        })
+        .collect::<Result<_, AnalyzerError>>()?;
+    let declared = create
+        .returns
+        .as_ref()
+        .map(|dt| sql_type_from_spec(catalog, dt))
+        .transpose()?;
+
+    let return_type = match &create.body {
+        FunctionBody::Expr(body) => {
+            check_body(catalog, &name, &params, body, matching, declared.as_ref())?
+        }
+        FunctionBody::Statements(_) | FunctionBody::External(_) => {
+            declared.unwrap_or(SqlType::Unknown)
+        }
+    };
+
+    let mut sig = FunctionSignature::scalar(&name, return_type);
+    for ((param_name, data_type), param) in params.into_iter().zip(&create.params) {
+        let mut parameter = match param_name {
+            Some(n) => FunctionParameter::new(n, data_type),
+            None => FunctionParameter::unnamed(data_type),
+        };
+        if param.default.is_some() {
+            parameter = parameter.optional();
+        }
+        sig = sig.with_param(parameter);
+    }
+    let required = create.params.iter().filter(|p| p.default.is_none()).count();
+    sig = sig
+        .with_min_args(required)
+        .with_max_args(create.params.len());
+    if let FunctionBody::Expr(body) = &create.body {
+        sig = sig.with_body((**body).clone());
+    }
+    Ok(sig)
+}
+
+/// Type check an expression body and return the function's return type.
+fn check_body<C: Catalog>(
+    catalog: &C,
+    name: &str,
+    params: &[(Option<String>, SqlType)],
+    body: &Expr,
+    matching: IdentifierMatching,
+    declared: Option<&SqlType>,
+) -> Result<SqlType, AnalyzerError> {
+    let mut subquery = None;
+    let mut window = None;
+    walk_expr(body, &mut |node| match node {
+        NodeRef::Query(q) => {
+            subquery.get_or_insert(q.span);
+        }
+        NodeRef::Expr(Expr {
+            kind: ExprKind::WindowFunction(call),
+            span,
+            ..
+        }) => {
+            window.get_or_insert((&call.function.name, *span));
+        }
+        _ => {}
+    });
+    if let Some(span) = subquery {
+        return Err(AnalyzerError::with_span(
+            AnalyzerErrorKind::InvalidSubquery {
+                reason: format!(
+                    "subqueries are not allowed in the body of function '{}'",
+                    name
+                ),
+            },
+            span,
+        ));
+    }
+    if let Some((function, span)) = window {
+        let function = function
+            .parts
+            .last()
+            .map(|i| i.value.to_uppercase())
+            .unwrap_or_default();
+        return Err(AnalyzerError::with_span(
+            AnalyzerErrorKind::InvalidWindowUse {
+                function,
+                reason: "window functions are not allowed in a scalar function body".to_string(),
+            },
+            span,
+        ));
+    }
+
+    let columns = params
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (param, data_type))| {
+            param
+                .as_ref()
+                .map(|p| ScopeColumn::new(p.clone(), data_type.clone(), true, name.to_string(), i))
+        })
+        .collect();
+    let mut scope = Scope::with_matching(matching);
+    scope.add_table(ScopeTable::new(
+        name.to_string(),
+        vec![name.to_string()],
+        columns,
+    ))?;
+
+    let typed = TypeChecker::new(catalog)
+        .with_aggregates_forbidden("a scalar function body")
+        .check_expr(body, &scope)
+        .map_err(|e| match e.kind {
+            AnalyzerErrorKind::ColumnNotFound { name: param, .. } => AnalyzerError {
+                kind: AnalyzerErrorKind::UndefinedParameter {
+                    function: name.to_string(),
+                    name: param,
+                },
+                span: e.span,
+                suggestion: None,
+            },
+            _ => e,
+        })?;
+
+    match declared {
+        Some(returns) if !typed.data_type.can_coerce_to(returns) => Err(AnalyzerError::with_span(
+            AnalyzerError::type_mismatch(
+                returns.clone(),
+                typed.data_type,
+                format!("body of function '{}'", name),
+            )
+            .kind,
+            body.span,
+        )),
+        Some(returns) => Ok(returns.clone()),
+        None => Ok(typed.data_type),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::Analyzer;
+    use crate::ast::StatementKind;
+    use crate::catalog::{Catalog, ColumnSchema, MemoryCatalog, TableSchemaBuilder};
+    use crate::parser::Parser;
+    use crate::types::SqlType;
+
+    fn catalog() -> MemoryCatalog {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        catalog.add_table(
+            TableSchemaBuilder::new("orders")
+                .column(ColumnSchema::new("amount", SqlType::Float64))
+                .column(ColumnSchema::new("note", SqlType::Varchar(None)))
+                .build(),
+        );
+        catalog
+    }
+
+    /// Analyze each statement, applying CREATE FUNCTION to the catalog.
+    fn run_script(catalog: &mut MemoryCatalog, sql: &str) -> crate::error::Result<()> {
+        for stmt in Parser::new(sql).parse()? {
+            Analyzer::with_catalog(&*catalog).analyze(&stmt)?;
+            if matches!(stmt.kind, StatementKind::CreateFunction(_)) {
+                catalog.apply(&stmt)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_udf_script() {
+        let mut catalog = catalog();
+        run_script(
+            &mut catalog,
+            "CREATE FUNCTION add_tax(price FLOAT64) RETURNS FLOAT64 AS (price * 1.1);
+             CREATE FUNCTION double_tax(price FLOAT64) AS (add_tax(add_tax(price)));
+             SELECT double_tax(amount) AS total FROM orders",
+        )
+        .unwrap();
+
+        let sig = catalog
+            .resolve_function(&["double_tax".to_string()])
+            .unwrap()
+            .unwrap();
+        assert_eq!(sig.return_type, SqlType::Float64);
+        assert_eq!(sig.parameters[0].name.as_deref(), Some("price"));
+        assert!(sig.body.is_some());
+
+        let stmt = Parser::new("SELECT add_tax(amount) AS total FROM orders")
+            .parse()
+            .unwrap()
+            .remove(0);
+        let StatementKind::Query(query) = stmt.kind else {
+            unreachable!()
+        };
+        let result = Analyzer::with_catalog(&catalog)
+            .analyze_query_result(&query)
+            .unwrap();
+        assert_eq!(result.columns[0].data_type, SqlType::Float64);
+
+        let err = run_script(&mut catalog, "SELECT add_tax(note) FROM orders").unwrap_err();
+        assert!(err.to_string().contains("argument 1 of ADD_TAX"), "{}", err);
+        let err = run_script(&mut catalog, "SELECT add_tax(amount, 2) FROM orders").unwrap_err();
+        assert!(err.to_string().contains("expects 1 arguments"), "{}", err);
+    }
+
+    #[test]
+    fn test_udf_unknown_parameter() {
+        let err = run_script(
+            &mut catalog(),
+            "CREATE FUNCTION add_tax(price FLOAT64) RETURNS FLOAT64 AS (cost * 1.1)",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("function 'add_tax' has no parameter named 'cost'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_udf_body_restrictions() {
+        let cases = [
+            ("RETURNS BOOL AS (x + 1)", "type mismatch", Some("x + 1")),
+            (
+                "AS (x + SUM(x))",
+                "aggregate function 'SUM': aggregate functions are not allowed",
+                Some("SUM(x)"),
+            ),
+            (
+                "AS (1 + ROW_NUMBER() OVER (ORDER BY x))",
+                "window function 'ROW_NUMBER': window functions are not allowed",
+                Some("ROW_NUMBER() OVER (ORDER BY x)"),
+            ),
+            ("AS ((SELECT 1))", "subqueries are not allowed", None),
+        ];
+        for (rest, expected, text) in cases {
+            let sql = format!("CREATE FUNCTION f(x INT64) {}", rest);
+            let err = run_script(&mut catalog(), &sql).unwrap_err();
+            assert!(err.to_string().contains(expected), "{}: {}", sql, err);
+            let span = err.span().expect("error has a span");
+            if let Some(text) = text {
+                assert_eq!(&sql[span.start..span.end], text, "{}", sql);
+            }
+        }
+    }
+}
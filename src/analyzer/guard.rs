@@ -0,0 +1,179 @@
+//! Guard rails against UPDATE and DELETE statements that touch every row.
+//!
+//! With [`WriteGuard::Warn`](super::WriteGuard::Warn) or
+//! [`WriteGuard::Error`](super::WriteGuard::Error), the analyzer flags:
+//!
+//! - `UPDATE` and `DELETE` without a WHERE clause, or whose WHERE clause
+//!   references no columns (`WHERE TRUE`, `WHERE 1 = 1`);
+//! - `MERGE` with an unconditional `THEN DELETE` clause that is either
+//!   `WHEN NOT MATCHED BY SOURCE` or paired with an ON condition that
+//!   references no columns.
+//!
+//! A statement opts out with the `ALLOW_FULL_SCAN` table hint on its
+//! target table:
+//!
+//! ```sql
+//! DELETE FROM sessions @{ALLOW_FULL_SCAN = TRUE};
+//! UPDATE users @{ALLOW_FULL_SCAN = TRUE} SET active = FALSE;
+//! ```
+//!
+//! Errors carry the [`UNGUARDED_WRITE`] code so gateways can block by code
+//! rather than by message.
+
+use crate::ast::*;
+
+/// Error and warning code for a write without a filtering condition.
+pub const UNGUARDED_WRITE: &str = "unguarded_write";
+
+/// Table hint that opts a statement out of the guard.
+const ALLOW_FULL_SCAN: &str = "ALLOW_FULL_SCAN";
+
+/// Whether a WHERE clause restricts a write to some rows.
+///
+/// A condition counts as filtering when it references a column or contains
+/// a subquery; constant conditions do not.
+pub(crate) fn is_filtering(condition: Option<&Expr>) -> bool {
+    let Some(condition) = condition else {
+        return false;
+    };
+    let mut filtering = false;
+    walk_expr(condition, &mut |node| match node {
+        NodeRef::Expr(e) => {
+            if matches!(
+                e.kind,
+                ExprKind::Identifier(_) | ExprKind::CompoundIdentifier(_)
+            ) {
+                filtering = true;
+            }
+        }
+        NodeRef::Query(_) => filtering = true,
+        _ => {}
+    });
+    filtering
+}
+
+/// Whether table hints include `ALLOW_FULL_SCAN = TRUE`.
+pub(crate) fn allows_full_scan(hints: &[SqlOption]) -> bool {
+    hints.iter().any(|h| {
+        h.name.value.eq_ignore_ascii_case(ALLOW_FULL_SCAN)
+            && matches!(h.value.kind, ExprKind::Boolean(true))
+    })
+}
+
+/// The hints on a target table reference.
+pub(crate) fn target_hints(table: &TableRef) -> &[SqlOption] {
+    match &table.kind {
+        TableRefKind::Table { hints, .. } => hints,
+        _ => &[],
+    }
+}
+
+/// Whether a MERGE has a DELETE clause that can remove every target row.
+pub(crate) fn merge_deletes_all(merge: &MergeStatement) -> bool {
+    let on_filters = is_filtering(Some(&merge.on));
+    merge.clauses.iter().any(|clause| match clause {
+        MergeClause::Matched {
+            condition: None,
+            action: MergeMatchedAction::Delete,
+        } => !on_filters,
+        MergeClause::NotMatchedBySource {
+            condition: None,
+            action: MergeMatchedAction::Delete,
+        } => true,
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{Analyzer, AnalyzerOptions, WriteGuard};
+    use crate::catalog::{ColumnSchema, MemoryCatalog, TableSchemaBuilder};
+    use crate::error::Result;
+    use crate::parser::Parser;
+    use crate::types::SqlType;
+
+    fn analyze(sql: &str, guard: WriteGuard) -> (Result<()>, Vec<String>) {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        for name in ["users", "staged"] {
+            catalog.add_table(
+                TableSchemaBuilder::new(name)
+                    .column(ColumnSchema::new("id", SqlType::Int64))
+                    .column(ColumnSchema::new("age", SqlType::Int64))
+                    .build(),
+            );
+        }
+        let stmt = Parser::new(sql).parse().unwrap().remove(0);
+        let options = AnalyzerOptions::new().with_unguarded_writes(guard);
+        let mut analyzer = Analyzer::with_catalog_and_options(catalog, options);
+        let result = analyzer.analyze(&stmt);
+        let codes = analyzer
+            .warnings()
+            .iter()
+            .filter_map(|w| w.code().map(str::to_string))
+            .collect();
+        (result, codes)
+    }
+
+    #[test]
+    fn test_unguarded_writes_rejected() {
+        let cases = [
+            "DELETE FROM users",
+            "DELETE FROM users WHERE TRUE",
+            "DELETE FROM users WHERE 1 = 1",
+            "UPDATE users SET age = 1",
+            "UPDATE users SET age = 1 WHERE NOT FALSE",
+            "MERGE INTO users AS t USING staged AS s ON TRUE WHEN MATCHED THEN DELETE",
+            "MERGE INTO users AS t USING staged AS s ON t.id = s.id \
+             WHEN NOT MATCHED BY SOURCE THEN DELETE",
+        ];
+        for sql in cases {
+            let err = analyze(sql, WriteGuard::Error).0.unwrap_err();
+            assert_eq!(err.code(), Some(UNGUARDED_WRITE), "{}: {}", sql, err);
+            assert!(err.to_string().contains("can affect every row"), "{}", err);
+
+            assert!(analyze(sql, WriteGuard::Off).0.is_ok(), "{}", sql);
+            let (result, codes) = analyze(sql, WriteGuard::Warn);
+            assert!(result.is_ok(), "{}", sql);
+            assert_eq!(codes, [UNGUARDED_WRITE], "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_filtered_writes_allowed() {
+        let cases = [
+            "DELETE FROM users WHERE id = 1",
+            "DELETE FROM users WHERE age IS NULL",
+            "UPDATE users SET age = 1 WHERE users.id > 10",
+            "MERGE INTO users AS t USING staged AS s ON t.id = s.id WHEN MATCHED THEN DELETE",
+        ];
+        for sql in cases {
+            let (result, codes) = analyze(sql, WriteGuard::Error);
+            assert!(result.is_ok(), "{}: {:?}", sql, result);
+            assert!(codes.is_empty(), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_allow_full_scan_hint() {
+        let cases = [
+            "DELETE FROM users @{ALLOW_FULL_SCAN = TRUE}",
+            "DELETE FROM users @{allow_full_scan = TRUE} WHERE TRUE",
+            "UPDATE users @{ALLOW_FULL_SCAN = TRUE} SET age = 1",
+        ];
+        for sql in cases {
+            let (result, codes) = analyze(sql, WriteGuard::Error);
+            assert!(result.is_ok(), "{}: {:?}", sql, result);
+            assert!(codes.is_empty(), "{}", sql);
+        }
+
+        let err = analyze(
+            "DELETE FROM users @{ALLOW_FULL_SCAN = FALSE}",
+            WriteGuard::Error,
+        )
+        .0
+        .unwrap_err();
+        assert_eq!(err.code(), Some(UNGUARDED_WRITE));
+    }
+}
@@ -3,18 +3,41 @@
 //! This module provides semantic analysis for parsed SQL AST,
 //! including type checking, name resolution, and validation.
 
+pub(crate) mod constant;
 mod error;
+mod explain;
+mod function;
+mod guard;
+mod options;
 mod scope;
+mod suggest;
 mod type_checker;
+mod warning;
 
 pub use error::{AnalyzerError, AnalyzerErrorKind};
-pub use scope::{ColumnLookupResult, CteRef, Scope, ScopeColumn, ScopeTable};
-pub use type_checker::{TypeChecker, TypedExpr};
+pub use explain::{
+    ExplainColumn, ExplainCte, ExplainOutput, ExplainQuery, ExplainSource, ExplainTable,
+};
+pub use guard::UNGUARDED_WRITE;
+pub use options::{
+    AnalyzerOptions, DisjointNaturalJoin, GroupByMode, IdentifierCase, NullOrdering,
+    NumericFloatArithmetic, UsingWildcardMode, WriteGuard,
+};
+pub use scope::{ColumnLookupResult, CteRef, ExprRef, Scope, ScopeColumn, ScopeTable};
+pub use type_checker::{SortKey, SortTarget, TypeChecker, TypedExpr, TypedWindow};
+pub use warning::{AnalyzerWarning, AnalyzerWarningKind};
 
+use crate::ast::visit::Visitor;
 use crate::ast::*;
-use crate::catalog::{Catalog, ColumnSchema, MemoryCatalog, TableSchema};
-use crate::error::{Error, Result};
-use crate::types::SqlType;
+use crate::catalog::{
+    Catalog, ColumnSchema, FunctionSignature, MemoryCatalog, SessionCatalog, TableSchema,
+};
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::types::{CastKind, SqlType, Value};
+use explain::ExplainRecorder;
+use std::cell::{Cell, RefCell};
+use std::sync::Arc;
+use type_checker::{struct_field, SubqueryContext};
 
 /// Semantic analyzer for SQL statements.
 pub struct Analyzer<C: Catalog = MemoryCatalog> {
@@ -24,6 +47,26 @@ pub struct Analyzer<C: Catalog = MemoryCatalog> {
     scopes: Vec<Scope>,
     /// Accumulated errors (for error recovery).
     errors: Vec<AnalyzerError>,
+    /// Non-fatal findings from the last analysis.
+    warnings: Vec<AnalyzerWarning>,
+    /// Types inferred for parameter placeholders in the last analysis.
+    parameter_types: RefCell<Vec<ParameterType>>,
+    /// Warnings recorded by type checkers, moved into `warnings` when an
+    /// analysis finishes.
+    checker_warnings: RefCell<Vec<AnalyzerWarning>>,
+    /// Analysis options.
+    options: AnalyzerOptions,
+    /// Number of CTE definitions analyzed for the current statement.
+    cte_count: usize,
+    /// The scope of the enclosing query, when analyzing a subquery of an
+    /// expression. Every scope this analyzer pushes chains to it.
+    outer: Option<Arc<Scope>>,
+    /// How many query levels up the furthest correlated column reference
+    /// reaches, or 0 if there is none.
+    outer_depth: Cell<usize>,
+    /// Where the queries being analyzed are recorded, during
+    /// [`explain`](Self::explain).
+    explain: Option<ExplainRecorder>,
 }
 
 /// Analysis result for a query.
@@ -35,6 +78,16 @@ pub struct AnalyzedQuery {
     pub has_aggregation: bool,
     /// Whether the query uses window functions.
     pub has_window_functions: bool,
+    /// Window function calls in the SELECT list, in source order.
+    pub windows: Vec<TypedWindow>,
+    /// The query's ORDER BY, resolved.
+    pub sort_keys: Vec<SortKey>,
+    /// Spans of the FROM items that follow a comma, each an implicit cross
+    /// join with the items before it, in this query's SELECTs but not its
+    /// subqueries. Always empty when
+    /// [`AnalyzerOptions::allow_comma_joins`] is off, since they are
+    /// rejected.
+    pub implicit_cross_joins: Vec<Span>,
 }
 
 /// An output column from a query.
@@ -46,15 +99,152 @@ pub struct OutputColumn {
     pub data_type: SqlType,
     /// Whether the column is nullable.
     pub nullable: bool,
+    /// The table column this output column reads, when it is a plain
+    /// column reference or comes from a wildcard.
+    pub source: Option<ColumnOrigin>,
+    /// The column's value, when it is a constant expression the analyzer
+    /// can evaluate.
+    pub constant_value: Option<Value>,
+}
+
+impl OutputColumn {
+    /// An output column read straight from a column in scope.
+    fn from_scope(column: &ScopeColumn) -> Self {
+        Self {
+            name: column.name.clone(),
+            data_type: column.data_type.clone(),
+            nullable: column.nullable,
+            source: Some(ColumnOrigin::of(column)),
+            constant_value: None,
+        }
+    }
+}
+
+/// The table column an output column comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnOrigin {
+    /// Alias of the FROM clause table reference, or its name when it has
+    /// no alias.
+    pub table: String,
+    /// Column name within that table reference.
+    pub column: String,
+    /// Zero-based position of the column within that table reference.
+    pub column_index: usize,
+}
+
+impl ColumnOrigin {
+    fn of(column: &ScopeColumn) -> Self {
+        Self {
+            table: column.table_alias.clone(),
+            column: column.name.clone(),
+            column_index: column.column_index,
+        }
+    }
+}
+
+/// The type inferred for a parameter placeholder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterType {
+    /// The placeholder.
+    pub parameter: Parameter,
+    /// Type taken from the placeholder's context, or `Unknown` when the
+    /// context does not determine one.
+    pub data_type: SqlType,
+}
+
+/// Analysis result for an UPDATE statement.
+#[derive(Debug, Clone)]
+pub struct AnalyzedUpdate {
+    /// Name of the updated table as stored in the catalog.
+    pub table: String,
+    /// Implicit casts the analyzer accepted for SET assignments, in
+    /// assignment order.
+    pub coercions: Vec<AssignmentCoercion>,
+}
+
+/// An implicit cast applied to an assignment value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssignmentCoercion {
+    /// Target column; struct field paths are joined with `.`.
+    pub column: String,
+    /// Type of the value expression.
+    pub from: SqlType,
+    /// Declared type of the target column or field.
+    pub to: SqlType,
+}
+
+/// The table a DML statement writes to.
+#[derive(Debug, Clone)]
+pub struct ResolvedTable {
+    /// The name as written in the statement.
+    pub name: Vec<String>,
+    /// The alias the statement gives the table, or its name when it has
+    /// none.
+    pub alias: String,
+    /// The table's schema, as the catalog resolved it.
+    pub schema: Arc<TableSchema>,
+}
+
+/// The kind of a DML statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmlKind {
+    /// `INSERT`.
+    Insert,
+    /// `UPDATE`.
+    Update,
+    /// `DELETE`.
+    Delete,
+    /// `MERGE`.
+    Merge,
+}
+
+/// The result of analyzing one statement, from
+/// [`Analyzer::analyze_statement_result`] or [`Analyzer::analyze_all`].
+#[derive(Debug, Clone)]
+pub enum StatementAnalysis {
+    /// A query, with its output columns.
+    Query(AnalyzedQuery),
+    /// An `INSERT`, `UPDATE`, `DELETE`, or `MERGE`.
+    Dml {
+        /// The table the statement writes to.
+        table: ResolvedTable,
+        /// The output columns of the `RETURNING` clause, typed like a
+        /// SELECT list over the target table, if the statement has one.
+        returning: Option<Vec<OutputColumn>>,
+        /// Which statement it is.
+        kind: DmlKind,
+    },
+    /// A `CREATE TABLE` or `CREATE VIEW`.
+    Ddl {
+        /// The schema of the table or view the statement created, which
+        /// later statements of the script can use. A query's output columns
+        /// give the schema of `CREATE TABLE ... AS SELECT` and of views.
+        table: Option<TableSchema>,
+    },
+    /// Any other statement.
+    Other,
 }
 
 impl<C: Catalog> Analyzer<C> {
     /// Create a new analyzer with the given catalog.
     pub fn with_catalog(catalog: C) -> Self {
+        Self::with_catalog_and_options(catalog, AnalyzerOptions::default())
+    }
+
+    /// Create a new analyzer with the given catalog and options.
+    pub fn with_catalog_and_options(catalog: C, options: AnalyzerOptions) -> Self {
         Self {
             catalog,
-            scopes: vec![Scope::new()],
+            scopes: vec![Scope::with_matching(options.identifier_matching)],
             errors: Vec::new(),
+            warnings: Vec::new(),
+            parameter_types: RefCell::new(Vec::new()),
+            checker_warnings: RefCell::new(Vec::new()),
+            options,
+            cte_count: 0,
+            outer: None,
+            outer_depth: Cell::new(0),
+            explain: None,
         }
     }
 
@@ -63,40 +253,298 @@ impl<C: Catalog> Analyzer<C> {
         &self.catalog
     }
 
+    /// Get the analysis options.
+    pub fn options(&self) -> &AnalyzerOptions {
+        &self.options
+    }
+
     /// Analyze a statement.
     pub fn analyze(&mut self, stmt: &Statement) -> Result<()> {
         self.errors.clear();
-        self.analyze_statement(stmt)
-            .map_err(|e| Error::analyzer(e.to_string()))
+        self.warnings.clear();
+        self.parameter_types.borrow_mut().clear();
+        self.cte_count = 0;
+        let result = self.analyze_statement(stmt).map(|_| ());
+        self.finish(result)
+    }
+
+    /// Analyze a statement and return what it resolved to: a query's
+    /// output columns, the target table and `RETURNING` columns of a DML
+    /// statement, or the schema a `CREATE TABLE` or `CREATE VIEW` defines.
+    ///
+    /// ```
+    /// use vibesql::analyzer::{Analyzer, DmlKind, StatementAnalysis};
+    /// use vibesql::catalog::CatalogBuilder;
+    /// use vibesql::types::SqlType;
+    /// use vibesql::Parser;
+    ///
+    /// let catalog = CatalogBuilder::new()
+    ///     .add_table("users", |t| {
+    ///         t.primary_key("id", SqlType::Int64)
+    ///             .column("name", SqlType::Varchar(None))
+    ///     })
+    ///     .build();
+    /// let stmt = Parser::new("DELETE FROM users WHERE id = 1 RETURNING id, name AS deleted")
+    ///     .parse_statement()
+    ///     .unwrap();
+    ///
+    /// let analysis = Analyzer::with_catalog(&catalog)
+    ///     .analyze_statement_result(&stmt)
+    ///     .unwrap();
+    /// let StatementAnalysis::Dml { table, returning: Some(columns), kind } = analysis else {
+    ///     panic!("expected a DML statement");
+    /// };
+    /// assert_eq!((table.schema.name.as_str(), kind), ("users", DmlKind::Delete));
+    /// assert_eq!(columns[1].name, "deleted");
+    /// assert!(!columns[0].nullable);
+    /// ```
+    pub fn analyze_statement_result(&mut self, stmt: &Statement) -> Result<StatementAnalysis> {
+        self.errors.clear();
+        self.warnings.clear();
+        self.parameter_types.borrow_mut().clear();
+        self.cte_count = 0;
+        let result = self.analyze_statement(stmt);
+        self.finish(result)
+    }
+
+    /// Analyze statements in order, as a script, returning what each one
+    /// defines.
+    ///
+    /// Tables and views created by `CREATE TABLE`, including `CREATE TABLE
+    /// ... AS SELECT`, and `CREATE VIEW` are visible to the statements
+    /// after them, layered over the catalog in a [`SessionCatalog`] for
+    /// the length of the call; the catalog itself is not changed. Analysis
+    /// stops at the first statement that fails, and [`Analyzer::errors`]
+    /// then holds its errors. [`Analyzer::warnings`] collects the warnings
+    /// of every statement analyzed.
+    ///
+    /// ```
+    /// use vibesql::analyzer::{Analyzer, StatementAnalysis};
+    /// use vibesql::catalog::CatalogBuilder;
+    /// use vibesql::types::SqlType;
+    /// use vibesql::Parser;
+    ///
+    /// let catalog = CatalogBuilder::new()
+    ///     .add_table("users", |t| {
+    ///         t.primary_key("id", SqlType::Int64)
+    ///             .column("name", SqlType::Varchar(None))
+    ///     })
+    ///     .build();
+    /// let stmts = Parser::new("CREATE TABLE t AS SELECT id, name FROM users; SELECT * FROM t")
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let analyses = Analyzer::with_catalog(&catalog).analyze_all(&stmts).unwrap();
+    /// let StatementAnalysis::Query(query) = &analyses[1] else {
+    ///     panic!("expected a query");
+    /// };
+    /// assert_eq!(query.columns[1].name, "name");
+    /// ```
+    pub fn analyze_all(&mut self, stmts: &[Statement]) -> Result<Vec<StatementAnalysis>> {
+        let mut session = SessionCatalog::new(&self.catalog);
+        session.set_identifier_matching(self.options.identifier_matching);
+        let mut script = Analyzer::with_catalog_and_options(session, self.options.clone());
+        self.errors.clear();
+        self.warnings.clear();
+        let mut analyses = Vec::with_capacity(stmts.len());
+        for stmt in stmts {
+            let result = script.analyze_in_session(stmt);
+            self.warnings.append(&mut script.warnings);
+            match result {
+                Ok(analysis) => analyses.push(analysis),
+                Err(err) => {
+                    self.errors = std::mem::take(&mut script.errors);
+                    return Err(err);
+                }
+            }
+        }
+        Ok(analyses)
     }
 
     /// Analyze a query and return column information.
     pub fn analyze_query_result(&mut self, query: &Query) -> Result<AnalyzedQuery> {
         self.errors.clear();
-        self.analyze_query_internal(query)
-            .map_err(|e| Error::analyzer(e.to_string()))
+        self.warnings.clear();
+        self.parameter_types.borrow_mut().clear();
+        self.cte_count = 0;
+        let result = self.analyze_query_internal(query);
+        self.finish(result)
+    }
+
+    /// Analyze an UPDATE statement and return the coercions applied to its
+    /// assignments.
+    pub fn analyze_update_result(&mut self, update: &UpdateStatement) -> Result<AnalyzedUpdate> {
+        self.errors.clear();
+        self.warnings.clear();
+        self.parameter_types.borrow_mut().clear();
+        self.cte_count = 0;
+        let result = self.analyze_update(update).map(|(update, _)| update);
+        self.finish(result)
+    }
+
+    /// Analyze a statement and return the types of its parameter
+    /// placeholders, one entry per distinct placeholder in source order.
+    ///
+    /// `SELECT * FROM users WHERE id = ? AND name = ?` gives the first
+    /// placeholder the type of `id` and the second the type of `name`.
+    /// Placeholders whose context does not determine a type, as in
+    /// `SELECT ?`, are reported as [`SqlType::Unknown`].
+    pub fn infer_parameter_types(&mut self, stmt: &Statement) -> Result<Vec<ParameterType>> {
+        self.analyze(stmt)?;
+        Ok(self.take_parameter_types(stmt))
+    }
+
+    /// The parameter types the last analysis inferred, one entry per
+    /// distinct placeholder of `stmt` in source order.
+    fn take_parameter_types(&mut self, stmt: &Statement) -> Vec<ParameterType> {
+        let inferred = self.parameter_types.take();
+        let mut types: Vec<ParameterType> = Vec::new();
+        walk_statement(stmt, &mut |node| {
+            if let NodeRef::Expr(Expr {
+                kind: ExprKind::Parameter(parameter),
+                ..
+            }) = node
+            {
+                if types.iter().all(|t| &t.parameter != parameter) {
+                    let data_type = inferred
+                        .iter()
+                        .find(|t| &t.parameter == parameter)
+                        .map_or(SqlType::Unknown, |t| t.data_type.clone());
+                    types.push(ParameterType {
+                        parameter: parameter.clone(),
+                        data_type,
+                    });
+                }
+            }
+        });
+        types
+    }
+
+    /// Analyze a statement and describe what it resolved to: the FROM
+    /// items of its query and what each bound to, its output columns, and
+    /// its parameter types. `EXPLAIN stmt` explains `stmt`.
+    ///
+    /// ```
+    /// use vibesql::analyzer::Analyzer;
+    /// use vibesql::catalog::CatalogBuilder;
+    /// use vibesql::Parser;
+    ///
+    /// let catalog = CatalogBuilder::new()
+    ///     .with_builtins()
+    ///     .add_table("users", |t| t.column("id", vibesql::types::SqlType::Int64))
+    ///     .build();
+    /// let stmt = Parser::new("EXPLAIN SELECT COUNT(*) AS n FROM users u")
+    ///     .parse_statement()
+    ///     .unwrap();
+    /// let explained = Analyzer::with_catalog(catalog).explain(&stmt).unwrap();
+    /// let query = explained.query();
+    /// assert_eq!(query.tables[0].alias, "u");
+    /// assert!(query.columns[0].contains_aggregate);
+    /// ```
+    pub fn explain(&mut self, stmt: &Statement) -> Result<ExplainOutput> {
+        let stmt = match &stmt.kind {
+            StatementKind::Explain(explain) => &explain.statement,
+            _ => stmt,
+        };
+        let recorder = ExplainRecorder::default();
+        recorder.open();
+        self.explain = Some(recorder);
+        let result = match &stmt.kind {
+            StatementKind::Query(query) => self.analyze_query_result(query).map(Some),
+            _ => self.analyze(stmt).map(|()| None),
+        };
+        let recorder = self.explain.take();
+        let analyzed = result?;
+        let query = recorder
+            .and_then(|recorder| recorder.close(analyzed.as_ref()))
+            .unwrap_or_default();
+        Ok(ExplainOutput::new(query, self.take_parameter_types(stmt)))
     }
 
-    /// Get any accumulated errors.
+    /// Every error from the last analysis, in the order they were found.
+    ///
+    /// Name resolution errors in select items and in `WHERE` and `HAVING`
+    /// conditions do not stop analysis: the offending item is typed as
+    /// [`SqlType::Unknown`] and analysis carries on, so a statement with
+    /// several unknown columns reports all of them here. The analysis itself
+    /// still fails with the first error.
     pub fn errors(&self) -> &[AnalyzerError] {
         &self.errors
     }
 
+    /// Record a recoverable error and carry on, or propagate any other.
+    fn recover<T>(
+        &mut self,
+        result: std::result::Result<T, AnalyzerError>,
+    ) -> std::result::Result<Option<T>, AnalyzerError> {
+        match result {
+            Err(err) if is_recoverable(&err) => {
+                self.errors.push(err);
+                Ok(None)
+            }
+            result => result.map(Some),
+        }
+    }
+
+    /// Finish an analysis, failing with the first error recorded.
+    fn finish<T>(&mut self, result: std::result::Result<T, AnalyzerError>) -> Result<T> {
+        self.take_checker_warnings();
+        if let Err(err) = &result {
+            self.errors.push(err.clone());
+        }
+        match self.errors.first() {
+            Some(first) => Err(to_error(first.clone())),
+            None => result.map_err(to_error),
+        }
+    }
+
+    /// Warnings from the last analysis, such as a CTE shadowing a table.
+    pub fn warnings(&self) -> &[AnalyzerWarning] {
+        &self.warnings
+    }
+
+    /// Move the warnings type checkers recorded into `warnings`.
+    fn take_checker_warnings(&mut self) {
+        let recorded = self.checker_warnings.take();
+        self.warnings.extend(recorded);
+    }
+
     /// Analyze a statement.
-    fn analyze_statement(&mut self, stmt: &Statement) -> std::result::Result<(), AnalyzerError> {
+    fn analyze_statement(
+        &mut self,
+        stmt: &Statement,
+    ) -> std::result::Result<StatementAnalysis, AnalyzerError> {
         match &stmt.kind {
             StatementKind::Query(query) => {
-                self.analyze_query_internal(query)?;
-                Ok(())
+                return Ok(StatementAnalysis::Query(
+                    self.analyze_query_internal(query)?,
+                ));
+            }
+            StatementKind::Insert(insert) => return self.analyze_insert(insert),
+            StatementKind::Update(update) => return Ok(self.analyze_update(update)?.1),
+            StatementKind::Delete(delete) => return self.analyze_delete(delete),
+            StatementKind::Merge(merge) => return self.analyze_merge(merge),
+            StatementKind::CreateTable(create) => {
+                let table = self.analyze_create_table(create)?;
+                return Ok(StatementAnalysis::Ddl { table });
             }
-            StatementKind::Insert(insert) => self.analyze_insert(insert),
-            StatementKind::Update(update) => self.analyze_update(update),
-            StatementKind::Delete(delete) => self.analyze_delete(delete),
-            StatementKind::Merge(merge) => self.analyze_merge(merge),
-            StatementKind::CreateTable(create) => self.analyze_create_table(create),
-            StatementKind::CreateView(create) => self.analyze_create_view(create),
-            _ => Ok(()), // Other statements don't need deep analysis
+            StatementKind::CreateView(create) => {
+                let table = Some(self.analyze_create_view(create)?);
+                return Ok(StatementAnalysis::Ddl { table });
+            }
+            StatementKind::CreateFunction(create) => {
+                function::check_create_function(
+                    &self.catalog,
+                    create,
+                    self.options.identifier_matching,
+                )?;
+            }
+            StatementKind::Explain(explain) => {
+                self.analyze_statement(&explain.statement)?;
+            }
+            _ => {} // Other statements don't need deep analysis
         }
+        Ok(StatementAnalysis::Other)
     }
 
     /// Analyze a query.
@@ -104,22 +552,80 @@ impl<C: Catalog> Analyzer<C> {
         &mut self,
         query: &Query,
     ) -> std::result::Result<AnalyzedQuery, AnalyzerError> {
-        // Process WITH clause first (CTEs)
+        // Process WITH clause first (CTEs). Its CTEs get their own scope so
+        // they are visible to this query and its subqueries only.
         if let Some(with) = &query.with {
+            self.push_scope();
             self.analyze_with_clause(with)?;
         }
 
-        // Analyze the main query body
-        let result = self.analyze_query_body(&query.body)?;
+        // Analyze the main query body. A plain SELECT keeps its scope open
+        // so ORDER BY can sort by FROM clause columns that are not selected.
+        let mut result = match &query.body {
+            QueryBody::Select(select) => {
+                self.push_scope();
+                self.analyze_select_in_scope(select)?
+            }
+            body => self.analyze_query_body(body)?,
+        };
 
-        // Analyze ORDER BY
-        for order_item in &query.order_by {
-            self.analyze_expr(&order_item.expr)?;
+        // Resolve ORDER BY. Output columns, by position or name, take
+        // precedence over source columns: they are the first layer of the
+        // scope. Bodies other than a plain SELECT get a scope of their own.
+        if !matches!(query.body, QueryBody::Select(_)) {
+            self.push_scope();
+        }
+        for (ordinal, column) in result.columns.iter().enumerate() {
+            self.current_scope_mut().add_named_expr(ExprRef {
+                name: column.name.clone(),
+                data_type: column.data_type.clone(),
+                nullable: column.nullable,
+                ordinal,
+                source: column.source.clone(),
+            });
+        }
+        let checker = self.type_checker();
+        result.sort_keys = Vec::with_capacity(query.order_by.len());
+        for item in &query.order_by {
+            if let ExprKind::Integer(n) = item.expr.kind {
+                if n < 1 || n as usize > result.columns.len() {
+                    return Err(AnalyzerError::with_span(
+                        AnalyzerErrorKind::PositionOutOfRange {
+                            clause: "ORDER BY".to_string(),
+                            position: n,
+                            count: result.columns.len(),
+                        },
+                        item.expr.span,
+                    ));
+                }
+            }
+            let key = match self.output_position(&item.expr, &result.columns)? {
+                Some(index) => checker.sort_key(
+                    item,
+                    SortTarget::Output(index),
+                    result.columns[index].data_type.clone(),
+                )?,
+                None => {
+                    let typed = checker.check_expr(&item.expr, self.current_scope())?;
+                    checker.sort_key(item, SortTarget::Expr(item.expr.clone()), typed.data_type)?
+                }
+            };
+            result.sort_keys.push(key);
+        }
+        if let QueryBody::Select(select) = &query.body {
+            self.check_distinct_order_by(select, &query.order_by, &result.sort_keys)?;
         }
+        self.pop_scope();
 
         // Analyze LIMIT/OFFSET
         if let Some(limit) = &query.limit {
             if let Some(count) = &limit.count {
+                if limit.with_ties && query.order_by.is_empty() {
+                    return Err(AnalyzerError::with_span(
+                        AnalyzerErrorKind::WithTiesWithoutOrderBy,
+                        count.span,
+                    ));
+                }
                 self.analyze_expr_expect_int(count)?;
             }
             if let Some(offset) = &limit.offset {
@@ -127,47 +633,284 @@ impl<C: Catalog> Analyzer<C> {
             }
         }
 
+        if query.with.is_some() {
+            self.pop_scope();
+        }
+
         Ok(result)
     }
 
     /// Analyze a WITH clause.
+    ///
+    /// Each CTE body is analyzed exactly once, here; references to the CTE
+    /// reuse the cached result stored on its [`CteRef`].
     fn analyze_with_clause(&mut self, with: &WithClause) -> std::result::Result<(), AnalyzerError> {
         for cte in &with.ctes {
+            self.cte_count += 1;
+            if self.cte_count > self.options.max_ctes {
+                return Err(AnalyzerError::new(AnalyzerErrorKind::TooManyCtes {
+                    limit: self.options.max_ctes,
+                }));
+            }
+
             // Check for duplicate CTE names
             if self.current_scope().has_cte(&cte.name.value) {
                 return Err(AnalyzerError::new(AnalyzerErrorKind::DuplicateCte {
                     name: cte.name.value.clone(),
                 }));
             }
+            self.warn_cte_shadowing(cte);
 
             // Analyze the CTE query
-            let cte_result = self.analyze_query_internal(&cte.query)?;
+            let self_reference = if with.recursive && cte.query.with.is_none() {
+                self.table_reference_span(&cte.query.body, &cte.name.value, RecursiveTermCheck::Any)
+            } else {
+                None
+            };
+            let (cte_result, explained) = self.explained(|analyzer| match self_reference {
+                Some(span) => analyzer.analyze_recursive_cte(cte, span),
+                None => analyzer.analyze_query_internal(&cte.query),
+            })?;
+            if let Some(query) = explained {
+                let name = cte.name.value.clone();
+                self.record(|open| open.ctes.push(ExplainCte { name, query }));
+            }
+            self.add_cte_to_scope(cte, with.recursive, cte_result)?;
+        }
+        Ok(())
+    }
 
-            // Add CTE to scope
-            let columns: Vec<ScopeColumn> = cte_result
-                .columns
+    /// Add an analyzed CTE to the current scope, renaming its columns after
+    /// the CTE's column list.
+    fn add_cte_to_scope(
+        &mut self,
+        cte: &Cte,
+        is_recursive: bool,
+        mut analyzed: AnalyzedQuery,
+    ) -> std::result::Result<(), AnalyzerError> {
+        if !cte.columns.is_empty() {
+            self.check_column_list(
+                &cte.name.value,
+                &cte.columns,
+                analyzed.columns.len(),
+                true,
+                cte.name.span,
+            )?;
+            for (column, name) in analyzed.columns.iter_mut().zip(&cte.columns) {
+                column.name = name.value.clone();
+            }
+        }
+
+        let columns: Vec<ScopeColumn> = analyzed
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                ScopeColumn::new(
+                    col.name.clone(),
+                    col.data_type.clone(),
+                    col.nullable,
+                    cte.name.value.clone(),
+                    i,
+                )
+            })
+            .collect();
+
+        self.current_scope_mut().add_cte(CteRef {
+            name: cte.name.value.clone(),
+            columns,
+            is_recursive,
+            span: cte.name.span,
+            analyzed: Some(Arc::new(analyzed)),
+        });
+        Ok(())
+    }
+
+    /// Check a CTE or alias column list against the `count` columns of
+    /// `relation`.
+    ///
+    /// Names must be distinct. An alias list (`exact == false`) may rename
+    /// only the leading columns; a CTE list must name them all. Errors point
+    /// at the offending name, or at `span` when a CTE list is too short.
+    fn check_column_list(
+        &self,
+        relation: &str,
+        names: &[Ident],
+        count: usize,
+        exact: bool,
+        span: Span,
+    ) -> std::result::Result<(), AnalyzerError> {
+        let matching = self.options.identifier_matching;
+        for (i, name) in names.iter().enumerate() {
+            if names[..i]
                 .iter()
-                .enumerate()
-                .map(|(i, col)| {
-                    ScopeColumn::new(
-                        col.name.clone(),
-                        col.data_type.clone(),
-                        col.nullable,
-                        cte.name.value.clone(),
-                        i,
-                    )
-                })
-                .collect();
+                .any(|n| matching.matches(&n.value, &name.value))
+            {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::DuplicateColumn {
+                        name: name.value.clone(),
+                    },
+                    name.span,
+                ));
+            }
+        }
+        if names.len() > count || (exact && names.len() < count) {
+            let span = names.get(count).map_or(span, |n| n.span);
+            return Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::ColumnListMismatch {
+                    relation: relation.to_string(),
+                    expected: count,
+                    found: names.len(),
+                },
+                span,
+            ));
+        }
+        Ok(())
+    }
 
-            self.current_scope_mut().add_cte(CteRef {
-                name: cte.name.value.clone(),
-                columns,
-                is_recursive: with.recursive,
-            });
+    /// Rename scope columns after an alias column list, if any.
+    fn apply_alias_columns(
+        &self,
+        alias: Option<&Alias>,
+        columns: &mut [ScopeColumn],
+    ) -> std::result::Result<(), AnalyzerError> {
+        let Some(alias) = alias.filter(|a| !a.columns.is_empty()) else {
+            return Ok(());
+        };
+        self.check_column_list(
+            &alias.name.value,
+            &alias.columns,
+            columns.len(),
+            false,
+            alias.name.span,
+        )?;
+        for (column, name) in columns.iter_mut().zip(&alias.columns) {
+            column.name = name.value.clone();
         }
         Ok(())
     }
 
+    /// Analyze a CTE that references itself.
+    ///
+    /// The body must be a UNION of a non-recursive anchor term and a
+    /// recursive term. The anchor is analyzed first and its columns give the
+    /// CTE's shape while the recursive term is analyzed; the recursive
+    /// term's columns must then coerce to the anchor's.
+    fn analyze_recursive_cte(
+        &mut self,
+        cte: &Cte,
+        reference: Span,
+    ) -> std::result::Result<AnalyzedQuery, AnalyzerError> {
+        let name = &cte.name.value;
+        let invalid = |reason: String, span: Span| {
+            AnalyzerError::with_span(AnalyzerErrorKind::InvalidRecursiveCte { reason }, span)
+        };
+
+        let QueryBody::SetOperation {
+            op: SetOperator::Union,
+            left,
+            right,
+            ..
+        } = &cte.query.body
+        else {
+            return Err(invalid(
+                format!(
+                    "'{}' must be a UNION of a non-recursive term and a recursive term",
+                    name
+                ),
+                reference,
+            ));
+        };
+        if let Some(span) = self.table_reference_span(left, name, RecursiveTermCheck::Any) {
+            return Err(invalid(
+                format!("'{}' is referenced in its non-recursive term", name),
+                span,
+            ));
+        }
+        if let Some(span) = self.table_reference_span(right, name, RecursiveTermCheck::ExceptRight)
+        {
+            return Err(invalid(
+                format!("'{}' is referenced on the right side of EXCEPT", name),
+                span,
+            ));
+        }
+
+        let anchor = self.analyze_query_body(left)?;
+        self.add_cte_to_scope(cte, true, anchor.clone())?;
+        let recursive = self.analyze_query_body(right)?;
+
+        if recursive.has_aggregation {
+            return Err(invalid(
+                format!(
+                    "aggregate functions are not allowed in the recursive term of '{}'",
+                    name
+                ),
+                reference,
+            ));
+        }
+        if anchor.columns.len() != recursive.columns.len() {
+            return Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::SetOperationColumnMismatch {
+                    left: anchor.columns.len(),
+                    right: recursive.columns.len(),
+                },
+                cte.name.span,
+            ));
+        }
+
+        let mut result = anchor;
+        for (column, step) in result.columns.iter_mut().zip(&recursive.columns) {
+            if !matches!(step.data_type, SqlType::Unknown | SqlType::Any)
+                && step.data_type.can_cast_to(&column.data_type) != CastKind::Implicit
+            {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::TypeMismatch {
+                        expected: column.data_type.clone(),
+                        actual: step.data_type.clone(),
+                        context: format!(
+                            "column '{}' of the recursive term of '{}'",
+                            column.name, name
+                        ),
+                    },
+                    cte.name.span,
+                ));
+            }
+            column.nullable |= step.nullable;
+            column.source = None;
+            column.constant_value = None;
+        }
+        Ok(result)
+    }
+
+    /// Find the first reference to table `name` in a query body.
+    ///
+    /// With [`RecursiveTermCheck::ExceptRight`], only references on the right
+    /// side of an EXCEPT are reported.
+    fn table_reference_span(
+        &self,
+        body: &QueryBody,
+        name: &str,
+        check: RecursiveTermCheck,
+    ) -> Option<Span> {
+        match body {
+            QueryBody::SetOperation {
+                op, left, right, ..
+            } => {
+                let right_check = match op {
+                    SetOperator::Except => RecursiveTermCheck::Any,
+                    _ => check,
+                };
+                self.table_reference_span(left, name, check)
+                    .or_else(|| self.table_reference_span(right, name, right_check))
+            }
+            QueryBody::Parenthesized(query) if check == RecursiveTermCheck::ExceptRight => {
+                self.table_reference_span(&query.body, name, check)
+            }
+            _ if check == RecursiveTermCheck::ExceptRight => None,
+            _ => first_table_reference(body, name, self.options.identifier_matching),
+        }
+    }
+
     /// Analyze a query body (SELECT, UNION, etc.).
     fn analyze_query_body(
         &mut self,
@@ -187,34 +930,110 @@ impl<C: Catalog> Analyzer<C> {
                     ));
                 }
 
-                // Result uses left side column names
-                Ok(left_result)
+                // Result uses left side column names, typed as the common
+                // supertype of each pair of columns. Chained operations fold
+                // left to right, so `A UNION B UNION C` merges C into the
+                // merge of A and B.
+                let right_exprs = body_select_list_exprs(right);
+                let mut result = left_result;
+                for (i, (column, other)) in result
+                    .columns
+                    .iter_mut()
+                    .zip(&right_result.columns)
+                    .enumerate()
+                {
+                    let Some(merged) = column.data_type.common_supertype(&other.data_type) else {
+                        let kind = AnalyzerErrorKind::SetOperationTypeMismatch {
+                            position: i + 1,
+                            left: column.data_type.clone(),
+                            right: other.data_type.clone(),
+                        };
+                        return Err(match right_exprs.as_ref().and_then(|e| e.get(i)) {
+                            Some(expr) => AnalyzerError::with_span(kind, expr.span),
+                            None => AnalyzerError::new(kind),
+                        });
+                    };
+                    column.data_type = merged;
+                    column.nullable |= other.nullable;
+                    column.source = None;
+                    column.constant_value = None;
+                }
+                result
+                    .implicit_cross_joins
+                    .extend(right_result.implicit_cross_joins);
+                Ok(result)
             }
             QueryBody::Parenthesized(query) => self.analyze_query_internal(query),
         }
     }
 
     /// Analyze a SELECT statement.
+    ///
+    /// `SELECT *` expands to the FROM clause columns in the left-to-right
+    /// textual order of its table references, whether they are separated
+    /// by commas or JOINs; a parenthesized join expands in place in its own
+    /// left-to-right order. Each table contributes its columns in
+    /// definition order, and `USING`/`NATURAL` joins order their output as
+    /// described on [`UsingWildcardMode`]. Results can therefore be bound
+    /// positionally.
     fn analyze_select(
         &mut self,
         select: &Select,
     ) -> std::result::Result<AnalyzedQuery, AnalyzerError> {
         self.push_scope();
+        let result = self.analyze_select_in_scope(select)?;
+        self.pop_scope();
+        Ok(result)
+    }
 
-        // First, analyze FROM clause to populate scope with tables
+    /// Analyze a SELECT statement in the current scope, which is left
+    /// holding its FROM clause tables.
+    fn analyze_select_in_scope(
+        &mut self,
+        select: &Select,
+    ) -> std::result::Result<AnalyzedQuery, AnalyzerError> {
+        // First, analyze FROM clause to populate scope with tables. The
+        // collected columns are the FROM clause output in textual order and
+        // drive unqualified wildcard expansion.
+        let mut from_columns = Vec::new();
         if let Some(from) = &select.from {
+            if let (false, Some(second)) = (self.options.allow_comma_joins, from.tables.get(1)) {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::ImplicitCrossJoin,
+                    second.span,
+                ));
+            }
             for table_ref in &from.tables {
-                self.analyze_table_ref(table_ref)?;
+                from_columns.extend(self.analyze_table_ref(table_ref)?);
             }
         }
+        let implicit_cross_joins = select
+            .from
+            .iter()
+            .flat_map(|from| from.tables.iter().skip(1).map(|table_ref| table_ref.span));
+        let implicit_cross_joins = implicit_cross_joins.collect();
 
         // Check for GROUP BY
         let has_group_by = select.group_by.is_some();
         self.current_scope_mut().has_group_by = has_group_by;
 
+        let mut group_keys = Vec::new();
         if let Some(group_by) = &select.group_by {
             for item in &group_by.items {
-                if let GroupByItem::Expr(expr) = item {
+                let exprs: Vec<&Expr> = match item {
+                    GroupByItem::Expr(expr) => vec![expr],
+                    GroupByItem::Rollup(exprs) | GroupByItem::Cube(exprs) => {
+                        exprs.iter().map(|e| &**e).collect()
+                    }
+                    GroupByItem::GroupingSets(sets) => {
+                        sets.iter().flatten().map(|e| &**e).collect()
+                    }
+                };
+                for expr in exprs {
+                    let expr = self.resolve_group_by_expr(expr, &select.projection)?;
+                    self.row_checker("GROUP BY")
+                        .check_expr(expr, self.current_scope())?;
+                    group_keys.push(self.group_key(expr));
                     if let ExprKind::Identifier(ident) = &expr.kind {
                         self.current_scope_mut()
                             .group_by_columns
@@ -226,24 +1045,107 @@ impl<C: Catalog> Analyzer<C> {
 
         // Analyze WHERE clause
         if let Some(where_clause) = &select.where_clause {
-            self.analyze_expr_expect_bool(where_clause)?;
+            let result = self.analyze_filter(where_clause, "WHERE");
+            self.recover(result)?;
+        }
+
+        // Register named windows before the SELECT items that use them. A
+        // definition can refine one defined before it.
+        for def in &select.window {
+            let checker = self.type_checker();
+            let spec = checker.resolve_window_spec(&def.spec, self.current_scope())?;
+            checker.check_window_spec(&spec, self.current_scope())?;
+            self.current_scope_mut().add_window(&def.name.value, spec);
         }
 
         // Analyze SELECT items
+        let Projection {
+            columns,
+            has_aggregation,
+            has_window_functions,
+            windows,
+        } = self.analyze_projection(&select.projection, &from_columns, None)?;
+
+        if let Some(Distinct::On(exprs)) = &select.distinct {
+            for expr in exprs {
+                self.analyze_expr(expr)?;
+            }
+        }
+
+        // Analyze HAVING clause
+        if let Some(having) = &select.having {
+            if !has_group_by && !has_aggregation {
+                return Err(AnalyzerError::new(AnalyzerErrorKind::HavingWithoutGroupBy));
+            }
+            let checker = self.type_checker().with_windows_forbidden("HAVING");
+            let result = self.expect_bool(checker, having);
+            self.recover(result)?;
+        }
+
+        if self.options.group_by == GroupByMode::Strict && (has_group_by || has_aggregation) {
+            self.check_grouped_select(select, &from_columns, &group_keys)?;
+        }
+
+        Ok(AnalyzedQuery {
+            columns,
+            has_aggregation,
+            has_window_functions,
+            windows,
+            sort_keys: Vec::new(),
+            implicit_cross_joins,
+        })
+    }
+
+    /// Analyze a select list against the tables in the current scope.
+    /// `from_columns` are the columns an unqualified wildcard expands to.
+    /// When `forbidden_in` names a clause, such as `"RETURNING"`, items
+    /// may not call aggregate or window functions.
+    fn analyze_projection(
+        &mut self,
+        items: &[SelectItem],
+        from_columns: &[ScopeColumn],
+        forbidden_in: Option<&'static str>,
+    ) -> std::result::Result<Projection, AnalyzerError> {
         let mut columns = Vec::new();
         let mut has_aggregation = false;
         let mut has_window_functions = false;
+        let mut windows = Vec::new();
 
-        for item in &select.projection {
+        for item in items {
             match item {
                 SelectItem::Expr { expr, alias } => {
-                    let typed = self.analyze_expr(expr)?;
+                    let mut checker = self.type_checker();
+                    if let Some(clause) = forbidden_in {
+                        checker = checker.with_aggregates_forbidden(clause);
+                    }
+                    let result = checker.check_expr(expr, self.current_scope());
+                    let found = checker.take_windows();
+                    if let (Some(clause), Some(window)) = (forbidden_in, found.first()) {
+                        return Err(AnalyzerError::with_span(
+                            AnalyzerErrorKind::InvalidWindowUse {
+                                function: window.function.clone(),
+                                reason: format!("window functions are not allowed in {}", clause),
+                            },
+                            expr.span,
+                        ));
+                    }
+                    windows.extend(found);
+                    let typed = self
+                        .recover(result)?
+                        .unwrap_or_else(|| TypedExpr::nullable(SqlType::Unknown));
                     has_aggregation = has_aggregation || typed.contains_aggregate;
                     has_window_functions = has_window_functions || typed.contains_window;
+                    self.record(|open| {
+                        open.flag_column(
+                            columns.len(),
+                            typed.contains_aggregate,
+                            typed.contains_window,
+                        )
+                    });
 
                     let name = alias
                         .as_ref()
-                        .map(|a| a.value.clone())
+                        .map(|a| self.options.identifier_case.fold(a))
                         .or_else(|| self.expr_to_name(expr))
                         .unwrap_or_else(|| format!("_col{}", columns.len()));
 
@@ -251,18 +1153,14 @@ impl<C: Catalog> Analyzer<C> {
                         name,
                         data_type: typed.data_type,
                         nullable: typed.nullable,
+                        source: self.column_origin(expr),
+                        constant_value: typed.constant_value,
                     });
                 }
                 SelectItem::Wildcard => {
-                    // Expand * to all columns from all tables in scope
-                    for table in self.current_scope().all_tables() {
-                        for col in &table.columns {
-                            columns.push(OutputColumn {
-                                name: col.name.clone(),
-                                data_type: col.data_type.clone(),
-                                nullable: col.nullable,
-                            });
-                        }
+                    // Expand * to the output columns of the FROM clause
+                    for col in from_columns {
+                        columns.push(OutputColumn::from_scope(col));
                     }
                 }
                 SelectItem::QualifiedWildcard { qualifier } => {
@@ -275,168 +1173,300 @@ impl<C: Catalog> Analyzer<C> {
 
                     if let Some(table) = self.current_scope().lookup_table(&table_name) {
                         for col in &table.columns {
-                            columns.push(OutputColumn {
-                                name: col.name.clone(),
-                                data_type: col.data_type.clone(),
-                                nullable: col.nullable,
-                            });
+                            columns.push(OutputColumn::from_scope(col));
                         }
                     } else {
-                        return Err(AnalyzerError::table_not_found(&table_name));
+                        return Err(AnalyzerError::table_not_found(&table_name)
+                            .suggesting(self.current_scope().table_names()));
                     }
                 }
                 SelectItem::WildcardExcept { qualifier, except } => {
-                    let table_iter: Vec<_> = if let Some(q) = qualifier {
+                    let source_columns: Vec<ScopeColumn> = if let Some(q) = qualifier {
                         let table_name =
                             q.parts.last().map(|i| i.value.clone()).unwrap_or_default();
                         if let Some(table) = self.current_scope().lookup_table(&table_name) {
-                            vec![table.clone()]
+                            table.columns.clone()
                         } else {
-                            return Err(AnalyzerError::table_not_found(&table_name));
+                            return Err(AnalyzerError::table_not_found(&table_name)
+                                .suggesting(self.current_scope().table_names()));
                         }
                     } else {
-                        self.current_scope().all_tables().cloned().collect()
+                        from_columns.to_vec()
                     };
 
-                    let except_names: Vec<String> =
-                        except.iter().map(|i| i.value.to_lowercase()).collect();
-                    for table in table_iter {
-                        for col in &table.columns {
-                            if !except_names.contains(&col.name.to_lowercase()) {
-                                columns.push(OutputColumn {
-                                    name: col.name.clone(),
-                                    data_type: col.data_type.clone(),
-                                    nullable: col.nullable,
-                                });
-                            }
+                    let matching = self.options.identifier_matching;
+                    let except_names: Vec<String> = except
+                        .iter()
+                        .map(|i| matching.normalize(&i.value))
+                        .collect();
+                    for col in &source_columns {
+                        if !except_names.contains(&matching.normalize(&col.name)) {
+                            columns.push(OutputColumn::from_scope(col));
                         }
                     }
                 }
                 SelectItem::WildcardReplace { qualifier, replace } => {
-                    let table_iter: Vec<_> = if let Some(q) = qualifier {
+                    let source_columns: Vec<ScopeColumn> = if let Some(q) = qualifier {
                         let table_name =
                             q.parts.last().map(|i| i.value.clone()).unwrap_or_default();
                         if let Some(table) = self.current_scope().lookup_table(&table_name) {
-                            vec![table.clone()]
+                            table.columns.clone()
                         } else {
-                            return Err(AnalyzerError::table_not_found(&table_name));
+                            return Err(AnalyzerError::table_not_found(&table_name)
+                                .suggesting(self.current_scope().table_names()));
                         }
                     } else {
-                        self.current_scope().all_tables().cloned().collect()
+                        from_columns.to_vec()
                     };
 
+                    let matching = self.options.identifier_matching;
                     let replace_map: std::collections::HashMap<String, &Expr> = replace
                         .iter()
-                        .map(|(expr, ident)| (ident.value.to_lowercase(), expr.as_ref()))
+                        .map(|(expr, ident)| (matching.normalize(&ident.value), expr.as_ref()))
                         .collect();
 
-                    for table in table_iter {
-                        for col in &table.columns {
-                            let col_lower = col.name.to_lowercase();
-                            if let Some(replace_expr) = replace_map.get(&col_lower) {
-                                let typed = self.analyze_expr(replace_expr)?;
-                                columns.push(OutputColumn {
-                                    name: col.name.clone(),
-                                    data_type: typed.data_type,
-                                    nullable: typed.nullable,
-                                });
-                            } else {
-                                columns.push(OutputColumn {
-                                    name: col.name.clone(),
-                                    data_type: col.data_type.clone(),
-                                    nullable: col.nullable,
-                                });
-                            }
+                    for col in &source_columns {
+                        if let Some(replace_expr) = replace_map.get(&matching.normalize(&col.name))
+                        {
+                            let typed = self.analyze_expr(replace_expr)?;
+                            columns.push(OutputColumn {
+                                name: col.name.clone(),
+                                data_type: typed.data_type,
+                                nullable: typed.nullable,
+                                source: self.column_origin(replace_expr),
+                                constant_value: typed.constant_value,
+                            });
+                        } else {
+                            columns.push(OutputColumn::from_scope(col));
                         }
                     }
                 }
             }
         }
 
-        // Analyze HAVING clause
-        if let Some(having) = &select.having {
-            if !has_group_by && !has_aggregation {
-                return Err(AnalyzerError::new(AnalyzerErrorKind::HavingWithoutGroupBy));
-            }
-            self.analyze_expr_expect_bool(having)?;
-        }
-
-        self.pop_scope();
-
-        Ok(AnalyzedQuery {
+        Ok(Projection {
             columns,
             has_aggregation,
             has_window_functions,
+            windows,
         })
     }
 
-    /// Analyze a table reference in FROM clause.
-    fn analyze_table_ref(
-        &mut self,
-        table_ref: &TableRef,
+    /// Resolve a GROUP BY expression that names a select item.
+    ///
+    /// An integer literal is a 1-based position in the select list. An
+    /// identifier that is not a FROM clause column but matches a select
+    /// list alias stands for the aliased expression; source columns take
+    /// precedence, as in the standard. Anything else is returned as is.
+    fn resolve_group_by_expr<'e>(
+        &self,
+        expr: &'e Expr,
+        projection: &'e [SelectItem],
+    ) -> std::result::Result<&'e Expr, AnalyzerError> {
+        match &expr.kind {
+            ExprKind::Integer(n) => {
+                let item = usize::try_from(*n)
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|i| projection.get(i))
+                    .ok_or_else(|| {
+                        AnalyzerError::with_span(
+                            AnalyzerErrorKind::PositionOutOfRange {
+                                clause: "GROUP BY".to_string(),
+                                position: *n,
+                                count: projection.len(),
+                            },
+                            expr.span,
+                        )
+                    })?;
+                match item {
+                    SelectItem::Expr { expr, .. } => Ok(expr),
+                    _ => Err(AnalyzerError::with_span(
+                        AnalyzerErrorKind::StarNotAllowed {
+                            context: format!("GROUP BY position {}", n),
+                        },
+                        expr.span,
+                    )),
+                }
+            }
+            ExprKind::Identifier(ident)
+                if matches!(
+                    self.current_scope().lookup_column(&ident.value),
+                    ColumnLookupResult::NotFound
+                ) =>
+            {
+                let matching = self.options.identifier_matching;
+                let aliased = projection.iter().find_map(|item| match item {
+                    SelectItem::Expr {
+                        expr,
+                        alias: Some(alias),
+                    } if matching.matches(&alias.value, &ident.value) => Some(&**expr),
+                    _ => None,
+                });
+                Ok(aliased.unwrap_or(expr))
+            }
+            _ => Ok(expr),
+        }
+    }
+
+    /// Check a query's ORDER BY against the DISTINCT form of its SELECT.
+    ///
+    /// With `SELECT DISTINCT`, every ORDER BY item must be an output column
+    /// or repeat a select list expression. With `DISTINCT ON`, the leading
+    /// ORDER BY items, as many as there are ON expressions, must each be one
+    /// of the ON expressions. Expressions are compared by their SQL text;
+    /// an item that names an output column by position or alias stands for
+    /// that select list expression.
+    fn check_distinct_order_by(
+        &self,
+        select: &Select,
+        order_by: &[OrderByExpr],
+        sort_keys: &[SortKey],
     ) -> std::result::Result<(), AnalyzerError> {
-        match table_ref {
-            TableRef::Table { name, alias, .. } => {
-                let name_parts: Vec<String> = name.parts.iter().map(|i| i.value.clone()).collect();
+        let projected = |i: usize| {
+            let items = select.projection.get(..=i)?;
+            if !items
+                .iter()
+                .all(|item| matches!(item, SelectItem::Expr { .. }))
+            {
+                return None;
+            }
+            match &items[i] {
+                SelectItem::Expr { expr, .. } => Some(expr.to_string()),
+                _ => None,
+            }
+        };
+        let spellings = |item: &OrderByExpr, key: &SortKey| {
+            let mut texts = vec![item.expr.to_string()];
+            if let SortTarget::Output(i) = key.target {
+                texts.extend(projected(i));
+            }
+            texts
+        };
 
-                // First check if it's a CTE (search all parent scopes)
-                let cte_name = name_parts.last().cloned().unwrap_or_default();
-                if let Some(cte) = self.lookup_cte(&cte_name) {
-                    let table_alias = alias
-                        .as_ref()
-                        .map(|a| a.name.value.clone())
-                        .unwrap_or_else(|| cte_name.clone());
+        match &select.distinct {
+            Some(Distinct::Distinct) => {
+                for (item, key) in order_by.iter().zip(sort_keys) {
+                    let text = item.expr.to_string();
+                    let selected = select.projection.iter().any(
+                        |p| matches!(p, SelectItem::Expr { expr, .. } if expr.to_string() == text),
+                    );
+                    if !matches!(key.target, SortTarget::Output(_)) && !selected {
+                        return Err(AnalyzerError::with_span(
+                            AnalyzerErrorKind::OrderByNotInSelect { column: text },
+                            item.expr.span,
+                        ));
+                    }
+                }
+            }
+            Some(Distinct::On(exprs)) => {
+                let on: Vec<String> = exprs.iter().map(|e| e.to_string()).collect();
+                for (item, key) in order_by.iter().zip(sort_keys).take(on.len()) {
+                    if !spellings(item, key).iter().any(|t| on.contains(t)) {
+                        return Err(AnalyzerError::with_span(
+                            AnalyzerErrorKind::DistinctOnOrderMismatch {
+                                expr: item.expr.to_string(),
+                            },
+                            item.expr.span,
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
 
-                    let columns: Vec<ScopeColumn> = cte
-                        .columns
-                        .iter()
-                        .map(|c| {
-                            ScopeColumn::new(
-                                c.name.clone(),
-                                c.data_type.clone(),
-                                c.nullable,
-                                table_alias.clone(),
-                                c.column_index,
-                            )
-                        })
-                        .collect();
+    /// Analyze a table reference in FROM clause.
+    ///
+    /// Returns the columns the table reference contributes to `SELECT *`, in
+    /// output order.
+    fn analyze_table_ref(
+        &mut self,
+        table_ref: &TableRef,
+    ) -> std::result::Result<Vec<ScopeColumn>, AnalyzerError> {
+        match &table_ref.kind {
+            TableRefKind::Table {
+                name,
+                alias,
+                sample,
+                ..
+            } => {
+                if let Some(sample) = sample {
+                    self.check_table_sample(sample)?;
+                }
+                let name_parts: Vec<String> = name.parts.iter().map(|i| i.value.clone()).collect();
 
-                    self.current_scope_mut().add_table(ScopeTable::new(
-                        table_alias,
-                        name_parts,
-                        columns,
-                    ));
-                    return Ok(());
+                // Unqualified names resolve to the innermost visible CTE,
+                // then to CTEs of enclosing queries, then to the catalog.
+                // Qualified names such as `default.users` always go to the
+                // catalog, so a shadowed table stays reachable.
+                let cte_name = name_parts.last().cloned().unwrap_or_default();
+                let table_alias = alias
+                    .as_ref()
+                    .map(|a| a.name.value.clone())
+                    .unwrap_or_else(|| cte_name.clone());
+                let cte_columns = if name_parts.len() == 1 {
+                    self.cte_columns(&cte_name, &table_alias)
+                } else {
+                    None
+                };
+                let alias_span = alias.as_ref().map_or(table_ref.span, |a| a.name.span);
+                if let Some(mut columns) = cte_columns {
+                    self.apply_alias_columns(alias.as_ref(), &mut columns)?;
+                    self.record(|open| {
+                        open.add_table(&table_alias, ExplainSource::Cte(cte_name.clone()), &columns)
+                    });
+                    let table = ScopeTable::new(table_alias, name_parts, columns.clone());
+                    self.add_scope_table(table, alias_span)?;
+                    return Ok(columns);
                 }
 
                 // Look up table in catalog
                 let table_schema = self
                     .catalog
-                    .resolve_table(&name_parts)
-                    .map_err(|_| AnalyzerError::table_not_found(&cte_name))?
-                    .ok_or_else(|| AnalyzerError::table_not_found(&cte_name))?;
+                    .resolve_table_ident(&name.parts)
+                    .map_err(|err| table_lookup_error(err, &cte_name))?
+                    .ok_or_else(|| self.table_not_found(&cte_name))?;
 
                 let table_alias = alias
                     .as_ref()
                     .map(|a| a.name.value.clone())
                     .unwrap_or_else(|| table_schema.name.clone());
-
-                let columns = self.table_schema_to_columns(&table_schema, &table_alias);
-                self.current_scope_mut().add_table(ScopeTable::new(
-                    table_alias,
-                    name_parts,
-                    columns,
-                ));
+                let mut columns = self.table_schema_to_columns(&table_schema, &table_alias);
+                self.apply_alias_columns(alias.as_ref(), &mut columns)?;
+                self.record(|open| {
+                    let source = ExplainSource::Table(table_schema.name.clone());
+                    open.add_table(&table_alias, source, &columns)
+                });
+                let table = ScopeTable::new(table_alias, name_parts, columns.clone());
+                self.add_scope_table(table, alias_span)?;
+                Ok(columns)
             }
-            TableRef::Subquery { query, alias } => {
-                let result = self.analyze_query_internal(query)?;
+            TableRefKind::Subquery {
+                query,
+                alias,
+                sample,
+                lateral,
+            } => {
+                if let Some(sample) = sample {
+                    self.check_table_sample(sample)?;
+                }
+                let (result, explained) = self.explained(|analyzer| {
+                    if *lateral {
+                        analyzer
+                            .with_lateral_scope(|analyzer| analyzer.analyze_query_internal(query))
+                    } else {
+                        analyzer.analyze_query_internal(query)
+                    }
+                })?;
 
                 let alias_name = alias
                     .as_ref()
                     .map(|a| a.name.value.clone())
                     .unwrap_or_else(|| "_subquery".to_string());
 
-                let columns: Vec<ScopeColumn> = result
+                let mut columns: Vec<ScopeColumn> = result
                     .columns
                     .iter()
                     .enumerate()
@@ -450,27 +1480,109 @@ impl<C: Catalog> Analyzer<C> {
                         )
                     })
                     .collect();
+                self.apply_alias_columns(alias.as_ref(), &mut columns)?;
+                if let Some(query) = explained {
+                    let source = ExplainSource::Subquery(Box::new(query));
+                    self.record(|open| open.add_table(&alias_name, source, &columns));
+                }
 
-                self.current_scope_mut().add_table(ScopeTable::new(
-                    alias_name,
-                    vec!["_subquery".to_string()],
-                    columns,
-                ));
+                let table =
+                    ScopeTable::new(alias_name, vec!["_subquery".to_string()], columns.clone());
+                self.add_aliased_table(table, alias.as_ref())?;
+                Ok(columns)
             }
-            TableRef::Join {
+            TableRefKind::Join {
                 left,
                 right,
+                join_type,
                 condition,
-                ..
             } => {
-                self.analyze_table_ref(left)?;
-                self.analyze_table_ref(right)?;
+                let start = self.scope_marks();
+                let mut left_columns = self.analyze_table_ref(left)?;
+                let middle = self.scope_marks();
+                let mut right_columns = self.analyze_table_ref(right)?;
+                let end = self.scope_marks();
+
+                match (join_type, condition) {
+                    (JoinType::Cross, Some(condition)) => {
+                        let span = match condition {
+                            JoinCondition::On(expr) => expr.span,
+                            JoinCondition::Using(idents) => {
+                                idents.first().map_or(table_ref.span, |ident| ident.span)
+                            }
+                        };
+                        return Err(AnalyzerError::with_span(
+                            AnalyzerErrorKind::CrossJoinCondition,
+                            span,
+                        ));
+                    }
+                    (JoinType::Cross | JoinType::Natural, None) | (_, Some(_)) => {}
+                    (_, None) => {
+                        return Err(AnalyzerError::with_span(
+                            AnalyzerErrorKind::MissingJoinCondition,
+                            table_ref.span,
+                        ));
+                    }
+                }
 
-                if let Some(JoinCondition::On(expr)) = condition {
-                    self.analyze_expr_expect_bool(expr)?;
+                let using_names: Vec<(String, Span)> = match condition {
+                    Some(JoinCondition::On(expr)) => {
+                        self.analyze_filter(expr, "JOIN ON")?;
+                        Vec::new()
+                    }
+                    Some(JoinCondition::Using(idents)) => {
+                        idents.iter().map(|i| (i.value.clone(), i.span)).collect()
+                    }
+                    None if *join_type == JoinType::Natural => left_columns
+                        .iter()
+                        .filter(|l| {
+                            right_columns
+                                .iter()
+                                .any(|r| self.options.identifier_matching.matches(&r.name, &l.name))
+                        })
+                        .map(|c| (c.name.clone(), table_ref.span))
+                        .collect(),
+                    None => Vec::new(),
+                };
+
+                if using_names.is_empty() && *join_type == JoinType::Natural {
+                    self.check_disjoint_natural_join(table_ref.span)?;
+                }
+                // Coalesced columns take their nullability from the
+                // original inputs, so they are computed before the
+                // null-extended side is marked.
+                let coalesced = if using_names.is_empty() {
+                    Vec::new()
+                } else {
+                    self.coalesce_join_columns(
+                        *join_type,
+                        &using_names,
+                        &left_columns,
+                        &right_columns,
+                    )?
+                };
+
+                let (left_nullable, right_nullable) = null_extended_sides(*join_type);
+                let scope = self.current_scope_mut();
+                if left_nullable {
+                    scope.null_extend(start.0..middle.0, start.1..middle.1);
+                    left_columns.iter_mut().for_each(|c| c.nullable = true);
+                }
+                if right_nullable {
+                    scope.null_extend(middle.0..end.0, middle.1..end.1);
+                    right_columns.iter_mut().for_each(|c| c.nullable = true);
+                }
+
+                if using_names.is_empty() {
+                    let mut columns = left_columns;
+                    columns.extend(right_columns);
+                    return Ok(columns);
                 }
+                Ok(self.join_output_columns(&using_names, coalesced, left_columns, right_columns))
             }
-            TableRef::Unnest { expr, alias, .. } => {
+            TableRefKind::Unnest { expr, alias, .. } => {
+                // UNNEST is implicitly lateral: the array may be a column of
+                // a FROM item before it, as in `FROM t, UNNEST(t.tags)`.
                 let typed = self.analyze_expr(expr)?;
 
                 let elem_type = match &typed.data_type {
@@ -483,131 +1595,937 @@ impl<C: Catalog> Analyzer<C> {
                     .map(|a| a.name.value.clone())
                     .unwrap_or_else(|| "_unnest".to_string());
 
-                let columns = vec![ScopeColumn::new(
-                    "value".to_string(),
+                // As in BigQuery, `UNNEST(tags) AS tag` names the element
+                // column `tag`.
+                let column_name = alias
+                    .as_ref()
+                    .map_or_else(|| "value".to_string(), |a| a.name.value.clone());
+                let mut columns = vec![ScopeColumn::new(
+                    column_name,
                     elem_type,
                     true,
                     alias_name.clone(),
                     0,
                 )];
+                self.apply_alias_columns(alias.as_ref(), &mut columns)?;
+                self.record(|open| open.add_table(&alias_name, ExplainSource::Unnest, &columns));
 
-                self.current_scope_mut().add_table(ScopeTable::new(
-                    alias_name,
-                    vec!["_unnest".to_string()],
+                let table =
+                    ScopeTable::new(alias_name, vec!["_unnest".to_string()], columns.clone());
+                self.add_aliased_table(table, alias.as_ref())?;
+                Ok(columns)
+            }
+            TableRefKind::Parenthesized(inner) => self.analyze_table_ref(inner),
+            TableRefKind::TableFunction { name, alias, .. } => {
+                // Table functions would need special handling
+                self.record(|open| {
+                    let source = ExplainSource::Function(name.to_string());
+                    let alias = alias
+                        .as_ref()
+                        .map_or_else(|| name.to_string(), |a| a.name.value.clone());
+                    open.add_table(&alias, source, &[])
+                });
+                Ok(Vec::new())
+            }
+            TableRefKind::Pivot {
+                input,
+                aggregates,
+                for_column,
+                values,
+                alias,
+            } => {
+                // The input's columns are only visible inside the operator.
+                let recorded = self.recorded_table_count();
+                self.push_scope();
+                let columns = self.pivot_columns(input, aggregates, for_column, values);
+                self.pop_scope();
+                let inputs = self.take_recorded_tables(recorded);
+                let columns = self.add_derived_table("_pivot", alias.as_ref(), columns?)?;
+                let alias = alias.as_ref().map_or("_pivot", |a| a.name.value.as_str());
+                self.record(|open| open.add_table(alias, ExplainSource::Pivot(inputs), &columns));
+                Ok(columns)
+            }
+            TableRefKind::Unpivot {
+                input,
+                include_nulls,
+                value_columns,
+                name_column,
+                columns,
+                alias,
+            } => {
+                let recorded = self.recorded_table_count();
+                self.push_scope();
+                let columns = self.unpivot_columns(
+                    input,
+                    *include_nulls,
+                    value_columns,
+                    name_column,
                     columns,
-                ));
+                );
+                self.pop_scope();
+                let inputs = self.take_recorded_tables(recorded);
+                let columns = self.add_derived_table("_unpivot", alias.as_ref(), columns?)?;
+                let alias = alias.as_ref().map_or("_unpivot", |a| a.name.value.as_str());
+                self.record(|open| open.add_table(alias, ExplainSource::Unpivot(inputs), &columns));
+                Ok(columns)
             }
-            TableRef::Parenthesized(inner) => {
-                self.analyze_table_ref(inner)?;
+        }
+    }
+
+    /// Check a TABLESAMPLE clause: a percentage must be a numeric constant
+    /// from 0 to 100, and a row count and the seed must be integers.
+    fn check_table_sample(&self, sample: &TableSample) -> std::result::Result<(), AnalyzerError> {
+        let invalid = |reason: &str, span: Span| {
+            AnalyzerError::with_span(
+                AnalyzerErrorKind::InvalidTableSample {
+                    reason: reason.to_string(),
+                },
+                span,
+            )
+        };
+        let is_integer = |typed: &TypedExpr| {
+            typed.data_type.is_integer()
+                || matches!(typed.data_type, SqlType::Unknown | SqlType::Any)
+        };
+
+        match &sample.size {
+            SampleSize::Percent(expr) => {
+                let percent = match self.analyze_expr(expr)?.constant_value {
+                    Some(Value::Int64(n)) => n as f64,
+                    Some(Value::Float64(f)) => f,
+                    _ => {
+                        return Err(invalid(
+                            "the percentage must be a numeric literal",
+                            expr.span,
+                        ))
+                    }
+                };
+                if !(0.0..=100.0).contains(&percent) {
+                    return Err(invalid(
+                        "the percentage must be between 0 and 100",
+                        expr.span,
+                    ));
+                }
             }
-            TableRef::TableFunction { .. } => {
-                // Table functions would need special handling
+            SampleSize::Rows(expr) => {
+                let typed = self.analyze_expr(expr)?;
+                if !is_integer(&typed) {
+                    return Err(invalid("the row count must be an integer", expr.span));
+                }
+                if matches!(typed.constant_value, Some(Value::Int64(n)) if n < 0) {
+                    return Err(invalid("the row count cannot be negative", expr.span));
+                }
+            }
+        }
+        if let Some(seed) = &sample.seed {
+            if !is_integer(&self.analyze_expr(seed)?) {
+                return Err(invalid("the seed must be an integer", seed.span));
             }
         }
         Ok(())
     }
 
+    /// Register the columns computed by a FROM clause operator as a table
+    /// named by `alias`, or `default_name` without one.
+    fn add_derived_table(
+        &mut self,
+        default_name: &str,
+        alias: Option<&Alias>,
+        mut columns: Vec<ScopeColumn>,
+    ) -> std::result::Result<Vec<ScopeColumn>, AnalyzerError> {
+        let alias_name = alias
+            .map(|a| a.name.value.clone())
+            .unwrap_or_else(|| default_name.to_string());
+        for (i, column) in columns.iter_mut().enumerate() {
+            column.table_alias = alias_name.clone();
+            column.column_index = i;
+        }
+        self.apply_alias_columns(alias, &mut columns)?;
+        let table = ScopeTable::new(alias_name, vec![default_name.to_string()], columns.clone());
+        self.add_aliased_table(table, alias)?;
+        Ok(columns)
+    }
+
+    /// The output columns of a PIVOT.
+    ///
+    /// Input columns that the aggregates and the FOR column do not read
+    /// group the rows and come first. Then, for each IN value and each
+    /// aggregate, a column holds the aggregate over the rows where the FOR
+    /// column equals the value. It is named by the value's alias, or the
+    /// value itself (a string's contents, or the SQL text of other
+    /// constants), prefixed by `alias_` when the aggregate has an alias.
+    fn pivot_columns(
+        &mut self,
+        input: &TableRef,
+        aggregates: &[(Box<Expr>, Option<Ident>)],
+        for_column: &Expr,
+        values: &[(Box<Expr>, Option<Ident>)],
+    ) -> std::result::Result<Vec<ScopeColumn>, AnalyzerError> {
+        let invalid = |reason: &str, span: Span| {
+            AnalyzerError::with_span(
+                AnalyzerErrorKind::InvalidPivot {
+                    operator: "PIVOT".to_string(),
+                    reason: reason.to_string(),
+                },
+                span,
+            )
+        };
+
+        let input_columns = self.analyze_table_ref(input)?;
+        let for_type = self
+            .type_checker()
+            .with_aggregates_forbidden("PIVOT FOR")
+            .check_expr(for_column, self.current_scope())?
+            .data_type;
+        let mut aggregate_types = Vec::with_capacity(aggregates.len());
+        for (expr, _) in aggregates {
+            let typed = self.analyze_expr(expr)?;
+            if !typed.contains_aggregate {
+                return Err(invalid("expected an aggregate function call", expr.span));
+            }
+            aggregate_types.push(typed);
+        }
+
+        let read = column_names(
+            aggregates
+                .iter()
+                .map(|(expr, _)| &**expr)
+                .chain([for_column]),
+        );
+        let matching = self.options.identifier_matching;
+        let mut columns: Vec<ScopeColumn> = input_columns
+            .into_iter()
+            .filter(|c| !read.iter().any(|name| matching.matches(name, &c.name)))
+            .collect();
+
+        for (value, value_alias) in values {
+            let typed = self.analyze_expr(value)?;
+            if !typed.is_constant {
+                return Err(invalid("IN values must be constants", value.span));
+            }
+            type_checker::check_comparable(
+                (for_column, &for_type),
+                (value, &typed.data_type),
+                value.span,
+            )?;
+            let value_name = match (value_alias, &value.kind) {
+                (Some(alias), _) => alias.value.clone(),
+                (None, ExprKind::String(s)) => s.clone(),
+                (None, _) => value.to_string(),
+            };
+            for ((_, aggregate_alias), typed) in aggregates.iter().zip(&aggregate_types) {
+                let name = match aggregate_alias {
+                    Some(alias) => format!("{}_{}", alias.value, value_name),
+                    None => value_name.clone(),
+                };
+                if columns.iter().any(|c| matching.matches(&c.name, &name)) {
+                    return Err(AnalyzerError::with_span(
+                        AnalyzerErrorKind::DuplicateColumn { name },
+                        value.span,
+                    ));
+                }
+                columns.push(ScopeColumn::new(
+                    name,
+                    typed.data_type.clone(),
+                    typed.nullable,
+                    String::new(),
+                    0,
+                ));
+            }
+        }
+        Ok(columns)
+    }
+
+    /// The output columns of an UNPIVOT.
+    ///
+    /// Input columns that are not unpivoted come first, then the value
+    /// columns, typed as the common supertype of the columns unpivoted into
+    /// each, and finally the name column. The name column holds each
+    /// group's label, or its column names joined by `_`; it is INT64 when
+    /// every label is an integer and a string otherwise. With the default
+    /// EXCLUDE NULLS, rows whose values are all NULL are dropped, so a
+    /// single value column is NOT NULL.
+    fn unpivot_columns(
+        &mut self,
+        input: &TableRef,
+        include_nulls: bool,
+        value_columns: &[Ident],
+        name_column: &Ident,
+        groups: &[(Vec<Ident>, Option<Box<Expr>>)],
+    ) -> std::result::Result<Vec<ScopeColumn>, AnalyzerError> {
+        let invalid = |reason: String, span: Span| {
+            AnalyzerError::with_span(
+                AnalyzerErrorKind::InvalidPivot {
+                    operator: "UNPIVOT".to_string(),
+                    reason,
+                },
+                span,
+            )
+        };
+
+        let input_columns = self.analyze_table_ref(input)?;
+        let matching = self.options.identifier_matching;
+        let find = |ident: &Ident| {
+            input_columns
+                .iter()
+                .find(|c| matching.matches(&c.name, &ident.value))
+                .ok_or_else(|| {
+                    AnalyzerError::with_span(
+                        AnalyzerErrorKind::ColumnNotFound {
+                            name: ident.value.clone(),
+                            table: None,
+                        },
+                        ident.span,
+                    )
+                    .suggesting(input_columns.iter().map(|c| c.name.as_str()))
+                })
+        };
+
+        let mut value_types: Vec<Option<(SqlType, bool)>> = vec![None; value_columns.len()];
+        for (group, _) in groups {
+            if group.len() != value_columns.len() {
+                let span = group[0].span.merge(group[group.len() - 1].span);
+                return Err(invalid(
+                    format!(
+                        "expected {} columns in each IN group, found {}",
+                        value_columns.len(),
+                        group.len()
+                    ),
+                    span,
+                ));
+            }
+            for (slot, ident) in value_types.iter_mut().zip(group) {
+                let column = find(ident)?;
+                *slot = Some(match slot.take() {
+                    None => (column.data_type.clone(), column.nullable),
+                    Some((data_type, nullable)) => {
+                        let merged =
+                            data_type
+                                .common_supertype(&column.data_type)
+                                .ok_or_else(|| {
+                                    invalid(
+                                        format!(
+                                            "column '{}' has type {}, incompatible with {}",
+                                            ident.value, column.data_type, data_type
+                                        ),
+                                        ident.span,
+                                    )
+                                })?;
+                        (merged, nullable || column.nullable)
+                    }
+                });
+            }
+        }
+
+        let labels: Vec<&ExprKind> = groups
+            .iter()
+            .filter_map(|(_, label)| label.as_ref().map(|l| &l.kind))
+            .collect();
+        let integer_labels = labels.iter().filter(|l| matches!(l, ExprKind::Integer(_)));
+        let name_type = match integer_labels.count() {
+            0 => SqlType::Varchar(None),
+            n if n == groups.len() => SqlType::Int64,
+            _ => {
+                let span = groups
+                    .iter()
+                    .find_map(|(group, label)| match label {
+                        Some(label) if matches!(label.kind, ExprKind::Integer(_)) => None,
+                        Some(label) => Some(label.span),
+                        None => Some(group[0].span),
+                    })
+                    .unwrap_or(name_column.span);
+                return Err(invalid(
+                    "labels must all be strings or all be integers".to_string(),
+                    span,
+                ));
+            }
+        };
+
+        let unpivoted = |c: &ScopeColumn| {
+            groups
+                .iter()
+                .flat_map(|(group, _)| group)
+                .any(|ident| matching.matches(&ident.value, &c.name))
+        };
+        let mut columns: Vec<ScopeColumn> = input_columns
+            .iter()
+            .filter(|c| !unpivoted(c))
+            .cloned()
+            .collect();
+        let single_value = value_columns.len() == 1;
+        let new_columns = value_columns
+            .iter()
+            .zip(value_types)
+            .map(|(ident, slot)| {
+                let (data_type, nullable) = slot.unwrap_or((SqlType::Unknown, true));
+                (
+                    ident,
+                    data_type,
+                    nullable && (include_nulls || !single_value),
+                )
+            })
+            .chain([(name_column, name_type, false)]);
+        for (ident, data_type, nullable) in new_columns {
+            if columns
+                .iter()
+                .any(|c| matching.matches(&c.name, &ident.value))
+            {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::DuplicateColumn {
+                        name: ident.value.clone(),
+                    },
+                    ident.span,
+                ));
+            }
+            columns.push(ScopeColumn::new(
+                ident.value.clone(),
+                data_type,
+                nullable,
+                String::new(),
+                0,
+            ));
+        }
+        Ok(columns)
+    }
+
+    /// Merge the `USING`/`NATURAL` join columns of two join inputs.
+    ///
+    /// Each named column must appear exactly once in each input, and only
+    /// once in the name list, and the two sides must have a common
+    /// supertype; errors point at `span` paired with the offending name.
+    /// Each named column is registered in scope as a single coalesced column
+    /// typed as the common supertype of both sides. It is NOT NULL for inner
+    /// joins (the equality rejects NULLs), follows the preserved side for
+    /// LEFT/RIGHT joins, and is nullable for FULL joins if either side is.
+    /// Returns the coalesced columns in name order.
+    fn coalesce_join_columns(
+        &mut self,
+        join_type: JoinType,
+        names: &[(String, Span)],
+        left_columns: &[ScopeColumn],
+        right_columns: &[ScopeColumn],
+    ) -> std::result::Result<Vec<ScopeColumn>, AnalyzerError> {
+        let matching = self.options.identifier_matching;
+        let mut coalesced: Vec<ScopeColumn> = Vec::with_capacity(names.len());
+        for (name, span) in names {
+            let at = |e: AnalyzerError| AnalyzerError {
+                span: Some(*span),
+                ..e
+            };
+            if coalesced.iter().any(|c| matching.matches(&c.name, name)) {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::Other {
+                        message: format!(
+                            "column '{}' appears more than once in USING clause",
+                            name
+                        ),
+                    },
+                    *span,
+                ));
+            }
+            let left = self
+                .find_join_input_column(left_columns, name)
+                .map_err(at)?;
+            let right = self
+                .find_join_input_column(right_columns, name)
+                .map_err(at)?;
+
+            let data_type = left
+                .data_type
+                .common_supertype(&right.data_type)
+                .ok_or_else(|| {
+                    at(AnalyzerError::type_mismatch(
+                        left.data_type.clone(),
+                        right.data_type.clone(),
+                        format!("join column '{}'", name),
+                    ))
+                })?;
+            let nullable = match join_type {
+                JoinType::Left => left.nullable,
+                JoinType::Right => right.nullable,
+                JoinType::Full => left.nullable || right.nullable,
+                _ => false,
+            };
+
+            coalesced.push(ScopeColumn::new(
+                left.name.clone(),
+                data_type,
+                nullable,
+                String::new(),
+                coalesced.len(),
+            ));
+        }
+
+        for col in &coalesced {
+            self.current_scope_mut().add_join_column(col.clone());
+        }
+        Ok(coalesced)
+    }
+
+    /// The `SELECT *` columns of a `USING`/`NATURAL` join.
+    ///
+    /// In the default [`UsingWildcardMode::Coalesced`] mode these are the
+    /// coalesced columns, then the remaining left columns, then the
+    /// remaining right columns. In [`UsingWildcardMode::BothSides`] both
+    /// originals are returned instead.
+    fn join_output_columns(
+        &self,
+        names: &[(String, Span)],
+        coalesced: Vec<ScopeColumn>,
+        left_columns: Vec<ScopeColumn>,
+        right_columns: Vec<ScopeColumn>,
+    ) -> Vec<ScopeColumn> {
+        match self.options.using_wildcard {
+            UsingWildcardMode::BothSides => {
+                let mut columns = left_columns;
+                columns.extend(right_columns);
+                columns
+            }
+            UsingWildcardMode::Coalesced => {
+                let matching = self.options.identifier_matching;
+                let is_join_column =
+                    |col: &ScopeColumn| names.iter().any(|(n, _)| matching.matches(n, &col.name));
+                let mut columns = coalesced;
+                columns.extend(left_columns.into_iter().filter(|c| !is_join_column(c)));
+                columns.extend(right_columns.into_iter().filter(|c| !is_join_column(c)));
+                columns
+            }
+        }
+    }
+
+    /// Apply the [`DisjointNaturalJoin`] policy to a `NATURAL JOIN` whose
+    /// inputs share no column names.
+    fn check_disjoint_natural_join(
+        &mut self,
+        span: Span,
+    ) -> std::result::Result<(), AnalyzerError> {
+        match self.options.disjoint_natural_join {
+            DisjointNaturalJoin::Off => Ok(()),
+            DisjointNaturalJoin::Warn => {
+                self.warnings.push(AnalyzerWarning::with_span(
+                    AnalyzerWarningKind::NoNaturalJoinColumns,
+                    span,
+                ));
+                Ok(())
+            }
+            DisjointNaturalJoin::Error => Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::NoNaturalJoinColumns,
+                span,
+            )),
+        }
+    }
+
+    /// Find a join column in the output of one join input.
+    fn find_join_input_column<'c>(
+        &self,
+        columns: &'c [ScopeColumn],
+        name: &str,
+    ) -> std::result::Result<&'c ScopeColumn, AnalyzerError> {
+        let matching = self.options.identifier_matching;
+        let matches: Vec<&ScopeColumn> = columns
+            .iter()
+            .filter(|c| matching.matches(&c.name, name))
+            .collect();
+        match matches.as_slice() {
+            [] => Err(AnalyzerError::column_not_found(name, None)
+                .suggesting(columns.iter().map(|c| c.name.as_str()))),
+            [col] => Ok(col),
+            _ => Err(AnalyzerError::ambiguous_column(
+                name,
+                matches
+                    .iter()
+                    .map(|c| format!("{}.{}", c.table_alias, c.name))
+                    .collect(),
+            )),
+        }
+    }
+
     /// Analyze an INSERT statement.
     fn analyze_insert(
         &mut self,
         insert: &InsertStatement,
-    ) -> std::result::Result<(), AnalyzerError> {
+    ) -> std::result::Result<StatementAnalysis, AnalyzerError> {
         let name_parts: Vec<String> = insert.table.parts.iter().map(|i| i.value.clone()).collect();
         let table_name = name_parts.last().cloned().unwrap_or_default();
 
         // Verify table exists
         let table_schema = self
             .catalog
-            .resolve_table(&name_parts)
-            .map_err(|_| AnalyzerError::table_not_found(&table_name))?
-            .ok_or_else(|| AnalyzerError::table_not_found(&table_name))?;
-
-        // Verify columns if specified
-        for col in &insert.columns {
-            if table_schema.get_column(&col.value).is_none() {
-                return Err(AnalyzerError::column_not_found(
-                    &col.value,
-                    Some(table_name.clone()),
-                ));
-            }
-        }
+            .resolve_table_ident(&insert.table.parts)
+            .map_err(|err| table_lookup_error(err, &table_name))?
+            .ok_or_else(|| self.table_not_found(&table_name))?;
+
+        let targets = insert_targets(&table_schema, &table_name, &insert.columns)?;
+        let given = match insert.source {
+            InsertSource::DefaultValues => &[][..],
+            _ => &targets[..],
+        };
+        check_missing_insert_columns(&table_schema, given, insert.table.span)?;
 
         // Analyze the source
         match &insert.source {
             InsertSource::Values(rows) => {
                 for row in rows {
-                    for expr in row {
-                        self.analyze_expr(expr)?;
-                    }
+                    self.check_insert_row(row, &targets, insert.table.span)?;
                 }
             }
             InsertSource::Query(query) => {
-                self.analyze_query_internal(query)?;
+                let analyzed = self.analyze_query_internal(query)?;
+                let items = select_list_exprs(query);
+                if analyzed.columns.len() != targets.len() {
+                    // Point at the first extra item, or at a short select
+                    // list
+                    let span = items
+                        .as_ref()
+                        .and_then(|items| match items.get(targets.len()) {
+                            Some(extra) => Some(extra.span),
+                            None => items
+                                .first()
+                                .zip(items.last())
+                                .map(|(first, last)| Span::new(first.span.start, last.span.end)),
+                        })
+                        .unwrap_or(query.span);
+                    return Err(AnalyzerError::with_span(
+                        AnalyzerErrorKind::InsertColumnCountMismatch {
+                            expected: targets.len(),
+                            found: analyzed.columns.len(),
+                        },
+                        span,
+                    ));
+                }
+                for (i, (output, column)) in analyzed.columns.iter().zip(&targets).enumerate() {
+                    let typed = TypedExpr {
+                        nullable: output.nullable,
+                        ..TypedExpr::non_null(output.data_type.clone())
+                    };
+                    match items.as_ref().and_then(|items| items.get(i)) {
+                        Some(expr) => {
+                            self.check_assignment(
+                                column.name.clone(),
+                                column.data_type.clone(),
+                                column.nullable,
+                                expr,
+                                &typed,
+                            )?;
+                        }
+                        None => {
+                            let from = &output.data_type;
+                            if !matches!(from, SqlType::Unknown | SqlType::Any)
+                                && from.can_cast_to(&column.data_type) != CastKind::Implicit
+                            {
+                                return Err(AnalyzerError::with_span(
+                                    AnalyzerErrorKind::InvalidAssignment {
+                                        column: column.name.clone(),
+                                        column_type: column.data_type.clone(),
+                                        value_type: from.clone(),
+                                    },
+                                    query.span,
+                                ));
+                            }
+                        }
+                    }
+                }
             }
             InsertSource::DefaultValues => {}
         }
 
+        let table = ResolvedTable {
+            alias: table_name,
+            name: name_parts,
+            schema: table_schema,
+        };
+        let returning = match &insert.returning {
+            Some(returning) => {
+                self.push_scope();
+                let columns = self.table_schema_to_columns(&table.schema, &table.alias);
+                let scope_table =
+                    ScopeTable::new(table.alias.clone(), table.name.clone(), columns.clone());
+                self.add_scope_table(scope_table, insert.table.span)?;
+                let returning = self.analyze_returning(returning, &columns)?;
+                self.pop_scope();
+                Some(returning)
+            }
+            None => None,
+        };
+        Ok(StatementAnalysis::Dml {
+            table,
+            returning,
+            kind: DmlKind::Insert,
+        })
+    }
+
+    /// Check a row of INSERT values against the target columns, in number
+    /// and type. `span` locates an empty row.
+    fn check_insert_row(
+        &mut self,
+        row: &[Box<Expr>],
+        targets: &[&ColumnSchema],
+        span: Span,
+    ) -> std::result::Result<(), AnalyzerError> {
+        if row.len() != targets.len() {
+            // Point at the first extra value, or at the short row
+            let span = match row.get(targets.len()) {
+                Some(extra) => extra.span,
+                None => row
+                    .first()
+                    .zip(row.last())
+                    .map(|(first, last)| Span::new(first.span.start, last.span.end))
+                    .unwrap_or(span),
+            };
+            return Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::InsertColumnCountMismatch {
+                    expected: targets.len(),
+                    found: row.len(),
+                },
+                span,
+            ));
+        }
+        for (expr, column) in row.iter().zip(targets) {
+            let typed = self.analyze_expr_as(expr, &column.data_type)?;
+            self.check_assignment(
+                column.name.clone(),
+                column.data_type.clone(),
+                column.nullable,
+                expr,
+                &typed,
+            )?;
+        }
         Ok(())
     }
 
+    /// Analyze a `RETURNING` clause as a select list over the tables in
+    /// the current scope, whose columns in order are `columns`. Aggregate
+    /// and window functions are rejected, since the clause reads one row
+    /// at a time.
+    fn analyze_returning(
+        &mut self,
+        returning: &ReturningClause,
+        columns: &[ScopeColumn],
+    ) -> std::result::Result<Vec<OutputColumn>, AnalyzerError> {
+        Ok(self
+            .analyze_projection(&returning.items, columns, Some("RETURNING"))?
+            .columns)
+    }
+
     /// Analyze an UPDATE statement.
     fn analyze_update(
         &mut self,
         update: &UpdateStatement,
-    ) -> std::result::Result<(), AnalyzerError> {
+    ) -> std::result::Result<(AnalyzedUpdate, StatementAnalysis), AnalyzerError> {
+        if !guard::allows_full_scan(guard::target_hints(&update.table))
+            && !guard::is_filtering(update.where_clause.as_deref())
+        {
+            self.guard_write("UPDATE")?;
+        }
+
         self.push_scope();
 
         // Add target table to scope - need to extract name from TableRef
-        let (name_parts, table_name, alias_opt) = self.extract_table_info(&update.table)?;
+        let (name, table_name, alias_opt) = self.extract_table_info(&update.table)?;
+        let name_parts: Vec<String> = name.iter().map(|i| i.value.clone()).collect();
 
         let table_schema = self
             .catalog
-            .resolve_table(&name_parts)
-            .map_err(|_| AnalyzerError::table_not_found(&table_name))?
-            .ok_or_else(|| AnalyzerError::table_not_found(&table_name))?;
+            .resolve_table_ident(name)
+            .map_err(|err| table_lookup_error(err, &table_name))?
+            .ok_or_else(|| self.table_not_found(&table_name))?;
 
         let alias = alias_opt.unwrap_or_else(|| table_name.clone());
 
         let columns = self.table_schema_to_columns(&table_schema, &alias);
-        self.current_scope_mut()
-            .add_table(ScopeTable::new(alias.clone(), name_parts, columns));
+        self.record(|open| {
+            open.add_table(
+                &alias,
+                ExplainSource::Table(table_schema.name.clone()),
+                &columns,
+            )
+        });
+        let table = ScopeTable::new(alias.clone(), name_parts.clone(), columns.clone());
+        self.add_scope_table(table, update.table.span)?;
+
+        // FROM tables are visible to the assignments, WHERE, and RETURNING
+        let mut from_columns = columns;
+        if let Some(from) = &update.from {
+            for table_ref in &from.tables {
+                from_columns.extend(self.analyze_table_ref(table_ref)?);
+            }
+        }
 
         // Analyze assignments
+        let mut coercions = Vec::new();
         for assignment in &update.assignments {
-            match &assignment.target {
-                AssignmentTarget::Column(col) => {
-                    if table_schema.get_column(&col.value).is_none() {
-                        return Err(AnalyzerError::column_not_found(
-                            &col.value,
-                            Some(table_name.clone()),
-                        ));
-                    }
-                }
-                AssignmentTarget::Path(_) => {}
+            let (label, target_type, target_nullable) =
+                self.resolve_assignment_target(&assignment.target, &table_schema, &alias)?;
+            let typed = self.analyze_expr_as(&assignment.value, &target_type)?;
+            if let Some(coercion) = self.check_assignment(
+                label,
+                target_type,
+                target_nullable,
+                &assignment.value,
+                &typed,
+            )? {
+                coercions.push(coercion);
             }
-            self.analyze_expr(&assignment.value)?;
         }
 
         // Analyze WHERE clause
         if let Some(where_clause) = &update.where_clause {
-            self.analyze_expr_expect_bool(where_clause)?;
+            self.analyze_filter(where_clause, "WHERE")?;
         }
 
+        let returning = match &update.returning {
+            Some(returning) => Some(self.analyze_returning(returning, &from_columns)?),
+            None => None,
+        };
+
         self.pop_scope();
-        Ok(())
+        let analyzed = AnalyzedUpdate {
+            table: table_schema.name.clone(),
+            coercions,
+        };
+        let table = ResolvedTable {
+            name: name_parts,
+            alias,
+            schema: table_schema,
+        };
+        Ok((
+            analyzed,
+            StatementAnalysis::Dml {
+                table,
+                returning,
+                kind: DmlKind::Update,
+            },
+        ))
+    }
+
+    /// Resolve an assignment target to its label, declared type and
+    /// nullability.
+    ///
+    /// Path targets may start with the table name or alias; the remaining
+    /// segments name a column followed by struct fields.
+    fn resolve_assignment_target(
+        &self,
+        target: &AssignmentTarget,
+        table: &TableSchema,
+        alias: &str,
+    ) -> std::result::Result<(String, SqlType, bool), AnalyzerError> {
+        let matching = self.options.identifier_matching;
+        let parts: &[Ident] = match target {
+            AssignmentTarget::Column(col) => std::slice::from_ref(col),
+            AssignmentTarget::Path(parts) => {
+                let qualified = parts.len() > 1
+                    && (matching.matches(&parts[0].value, alias)
+                        || matching.matches(&parts[0].value, &table.name))
+                    && table.get_column(&parts[0].value).is_none();
+                if qualified {
+                    &parts[1..]
+                } else {
+                    parts
+                }
+            }
+        };
+
+        let column = table.get_column(&parts[0].value).ok_or_else(|| {
+            AnalyzerError::with_span(
+                AnalyzerErrorKind::ColumnNotFound {
+                    name: parts[0].value.clone(),
+                    table: Some(table.name.clone()),
+                },
+                parts[0].span,
+            )
+            .suggesting(table.column_names())
+        })?;
+
+        let mut label = column.name.clone();
+        let mut data_type = column.data_type.clone();
+        let mut nullable = column.nullable;
+        for field_ident in &parts[1..] {
+            let field = struct_field(&data_type, &label, field_ident, matching)?;
+            label = format!("{}.{}", label, field_ident.value);
+            data_type = field.data_type.clone();
+            // Struct fields carry no nullability of their own.
+            nullable = true;
+        }
+
+        Ok((label, data_type, nullable))
+    }
+
+    /// Check that a value can be assigned to a column of the given type.
+    ///
+    /// Returns the implicit cast the assignment requires, if any.
+    fn check_assignment(
+        &mut self,
+        column: String,
+        column_type: SqlType,
+        nullable: bool,
+        value: &Expr,
+        typed: &TypedExpr,
+    ) -> std::result::Result<Option<AssignmentCoercion>, AnalyzerError> {
+        if matches!(value.kind, ExprKind::Null) {
+            if !nullable {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::NullAssignment {
+                        column,
+                        column_type,
+                    },
+                    value.span,
+                ));
+            }
+            return Ok(None);
+        }
+        if let Some(kind) = literal_overflow(&column, &column_type, value) {
+            self.warnings
+                .push(AnalyzerWarning::with_span(kind, value.span));
+        }
+
+        let from = typed.data_type.clone();
+        if from == column_type || matches!(from, SqlType::Unknown | SqlType::Any) {
+            return Ok(None);
+        }
+
+        let literal = literal_kind(value);
+        let allowed = from.can_cast_to(&column_type) == CastKind::Implicit
+            // Numeric literals adopt the target's numeric type.
+            || (matches!(literal, Some(ExprKind::Integer(_))) && column_type.is_numeric())
+            || (matches!(literal, Some(ExprKind::Float(_)))
+                && (column_type.is_floating_point()
+                    || matches!(column_type, SqlType::Numeric { .. })))
+            || (matches!(literal, Some(ExprKind::String(_)))
+                && self.options.lenient_string_assignment
+                && is_string_castable(&column_type));
+
+        if !allowed {
+            return Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::InvalidAssignment {
+                    column,
+                    column_type,
+                    value_type: from,
+                },
+                value.span,
+            ));
+        }
+
+        Ok(Some(AssignmentCoercion {
+            column,
+            from,
+            to: column_type,
+        }))
     }
 
     /// Extract table name information from a TableRef.
-    fn extract_table_info(
+    fn extract_table_info<'t>(
         &self,
-        table_ref: &TableRef,
-    ) -> std::result::Result<(Vec<String>, String, Option<String>), AnalyzerError> {
-        match table_ref {
-            TableRef::Table { name, alias, .. } => {
-                let name_parts: Vec<String> = name.parts.iter().map(|i| i.value.clone()).collect();
-                let table_name = name_parts.last().cloned().unwrap_or_default();
+        table_ref: &'t TableRef,
+    ) -> std::result::Result<(&'t [Ident], String, Option<String>), AnalyzerError> {
+        match &table_ref.kind {
+            TableRefKind::Table { name, alias, .. } => {
+                let table_name = name
+                    .parts
+                    .last()
+                    .map(|i| i.value.clone())
+                    .unwrap_or_default();
                 let alias_name = alias.as_ref().map(|a| a.name.value.clone());
-                Ok((name_parts, table_name, alias_name))
+                Ok((&name.parts, table_name, alias_name))
             }
             _ => Err(AnalyzerError::new(AnalyzerErrorKind::Other {
                 message: "Expected table reference".to_string(),
@@ -619,7 +2537,13 @@ impl<C: Catalog> Analyzer<C> {
     fn analyze_delete(
         &mut self,
         delete: &DeleteStatement,
-    ) -> std::result::Result<(), AnalyzerError> {
+    ) -> std::result::Result<StatementAnalysis, AnalyzerError> {
+        if !guard::allows_full_scan(&delete.hints)
+            && !guard::is_filtering(delete.where_clause.as_deref())
+        {
+            self.guard_write("DELETE")?;
+        }
+
         self.push_scope();
 
         let name_parts: Vec<String> = delete.table.parts.iter().map(|i| i.value.clone()).collect();
@@ -627,9 +2551,9 @@ impl<C: Catalog> Analyzer<C> {
 
         let table_schema = self
             .catalog
-            .resolve_table(&name_parts)
-            .map_err(|_| AnalyzerError::table_not_found(&table_name))?
-            .ok_or_else(|| AnalyzerError::table_not_found(&table_name))?;
+            .resolve_table_ident(&delete.table.parts)
+            .map_err(|err| table_lookup_error(err, &table_name))?
+            .ok_or_else(|| self.table_not_found(&table_name))?;
 
         let alias = delete
             .alias
@@ -638,30 +2562,104 @@ impl<C: Catalog> Analyzer<C> {
             .unwrap_or_else(|| table_name.clone());
 
         let columns = self.table_schema_to_columns(&table_schema, &alias);
-        self.current_scope_mut()
-            .add_table(ScopeTable::new(alias, name_parts, columns));
+        self.record(|open| {
+            open.add_table(
+                &alias,
+                ExplainSource::Table(table_schema.name.clone()),
+                &columns,
+            )
+        });
+        let table = ScopeTable::new(alias.clone(), name_parts.clone(), columns.clone());
+        self.add_scope_table(table, delete.table.span)?;
 
         // Analyze WHERE clause
         if let Some(where_clause) = &delete.where_clause {
-            self.analyze_expr_expect_bool(where_clause)?;
+            self.analyze_filter(where_clause, "WHERE")?;
         }
 
+        let returning = match &delete.returning {
+            Some(returning) => Some(self.analyze_returning(returning, &columns)?),
+            None => None,
+        };
+
         self.pop_scope();
-        Ok(())
+        Ok(StatementAnalysis::Dml {
+            table: ResolvedTable {
+                name: name_parts,
+                alias,
+                schema: table_schema,
+            },
+            returning,
+            kind: DmlKind::Delete,
+        })
     }
 
     /// Analyze a MERGE statement.
-    fn analyze_merge(&mut self, merge: &MergeStatement) -> std::result::Result<(), AnalyzerError> {
+    ///
+    /// The target and source are resolved separately. `WHEN MATCHED` and
+    /// `WHEN NOT MATCHED` clauses see both, while `WHEN NOT MATCHED BY
+    /// SOURCE` clauses, which have no source row, see only the target.
+    /// SET and INSERT columns must be columns of the target, and their
+    /// values are checked against the columns' types.
+    fn analyze_merge(
+        &mut self,
+        merge: &MergeStatement,
+    ) -> std::result::Result<StatementAnalysis, AnalyzerError> {
+        if !guard::allows_full_scan(guard::target_hints(&merge.target))
+            && guard::merge_deletes_all(merge)
+        {
+            self.guard_write("MERGE")?;
+        }
+
+        let (name, table_name, alias) = self.extract_table_info(&merge.target)?;
+        let table = ResolvedTable {
+            name: name.iter().map(|i| i.value.clone()).collect(),
+            alias: alias.unwrap_or_else(|| table_name.clone()),
+            schema: self
+                .catalog
+                .resolve_table_ident(name)
+                .map_err(|err| table_lookup_error(err, &table_name))?
+                .ok_or_else(|| self.table_not_found(&table_name))?,
+        };
+
+        // Only the last WHEN MATCHED clause may omit its condition
+        let unconditional = merge.clauses.iter().filter(|clause| {
+            matches!(
+                clause,
+                MergeClause::Matched {
+                    condition: None,
+                    ..
+                }
+            )
+        });
+        if unconditional.count() > 1 {
+            return Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::InvalidMerge {
+                    reason: "only one WHEN MATCHED clause may omit its condition".to_string(),
+                },
+                merge.target.span,
+            ));
+        }
+
         self.push_scope();
 
         // Analyze target table
-        self.analyze_table_ref(&merge.target)?;
+        let columns = self.table_schema_to_columns(&table.schema, &table.alias);
+        self.record(|open| {
+            open.add_table(
+                &table.alias,
+                ExplainSource::Table(table.schema.name.clone()),
+                &columns,
+            )
+        });
+        let target = ScopeTable::new(table.alias.clone(), table.name.clone(), columns);
+        self.add_scope_table(target.clone(), merge.target.span)?;
 
         // Analyze source table
-        self.analyze_table_ref(&merge.source)?;
+        let source_columns = self.analyze_table_ref(&merge.source)?;
 
         // Analyze ON condition
-        self.analyze_expr_expect_bool(&merge.on)?;
+        self.analyze_filter(&merge.on, "MERGE ON")?;
 
         // Analyze WHEN clauses
         for clause in &merge.clauses {
@@ -670,53 +2668,150 @@ impl<C: Catalog> Analyzer<C> {
                     if let Some(cond) = condition {
                         self.analyze_expr_expect_bool(cond)?;
                     }
-                    match action {
-                        MergeMatchedAction::Update { assignments } => {
-                            for assignment in assignments {
-                                self.analyze_expr(&assignment.value)?;
-                            }
-                        }
-                        MergeMatchedAction::Delete => {}
-                    }
+                    self.analyze_merge_action(action, &table, &source_columns)?;
                 }
                 MergeClause::NotMatched { condition, action } => {
                     if let Some(cond) = condition {
                         self.analyze_expr_expect_bool(cond)?;
                     }
-                    for expr in &action.values {
-                        self.analyze_expr(expr)?;
-                    }
+                    let targets = insert_targets(&table.schema, &table_name, &action.columns)?;
+                    check_missing_insert_columns(&table.schema, &targets, merge.target.span)?;
+                    self.check_insert_row(&action.values, &targets, merge.target.span)?;
                 }
                 MergeClause::NotMatchedBySource { condition, action } => {
-                    if let Some(cond) = condition {
-                        self.analyze_expr_expect_bool(cond)?;
-                    }
-                    match action {
-                        MergeMatchedAction::Update { assignments } => {
-                            for assignment in assignments {
-                                self.analyze_expr(&assignment.value)?;
-                            }
-                        }
-                        MergeMatchedAction::Delete => {}
-                    }
+                    self.push_scope();
+                    let result = self
+                        .add_scope_table(target.clone(), merge.target.span)
+                        .and_then(|()| match condition {
+                            Some(cond) => self.analyze_expr_expect_bool(cond),
+                            None => Ok(()),
+                        })
+                        .and_then(|()| self.analyze_merge_action(action, &table, &source_columns));
+                    self.pop_scope();
+                    let clause_span = merge_clause_span(condition.as_deref(), action)
+                        .unwrap_or(merge.target.span);
+                    result
+                        .map_err(|err| self.source_reference(err, &source_columns, clause_span))?;
                 }
             }
         }
 
         self.pop_scope();
+        Ok(StatementAnalysis::Dml {
+            table,
+            returning: None,
+            kind: DmlKind::Merge,
+        })
+    }
+
+    /// Analyze the UPDATE or DELETE of a MERGE clause. SET targets must be
+    /// columns of the target table, never of the source.
+    fn analyze_merge_action(
+        &mut self,
+        action: &MergeMatchedAction,
+        table: &ResolvedTable,
+        source_columns: &[ScopeColumn],
+    ) -> std::result::Result<(), AnalyzerError> {
+        let MergeMatchedAction::Update { assignments } = action else {
+            return Ok(());
+        };
+        let matching = self.options.identifier_matching;
+        for assignment in assignments {
+            if let AssignmentTarget::Path(parts) = &assignment.target {
+                let qualifier = &parts[0];
+                if parts.len() > 1
+                    && table.schema.get_column(&qualifier.value).is_none()
+                    && source_columns
+                        .iter()
+                        .any(|c| matching.matches(&qualifier.value, &c.table_alias))
+                {
+                    return Err(AnalyzerError::with_span(
+                        AnalyzerErrorKind::InvalidMerge {
+                            reason: format!(
+                                "cannot assign to '{}' of the source; SET assigns columns of '{}'",
+                                parts
+                                    .iter()
+                                    .map(|part| part.value.as_str())
+                                    .collect::<Vec<_>>()
+                                    .join("."),
+                                table.alias
+                            ),
+                        },
+                        qualifier.span,
+                    ));
+                }
+            }
+            let (label, target_type, target_nullable) =
+                self.resolve_assignment_target(&assignment.target, &table.schema, &table.alias)?;
+            let typed = self.analyze_expr_as(&assignment.value, &target_type)?;
+            self.check_assignment(
+                label,
+                target_type,
+                target_nullable,
+                &assignment.value,
+                &typed,
+            )?;
+        }
         Ok(())
     }
 
+    /// Report a reference to a MERGE source column from a `WHEN NOT
+    /// MATCHED BY SOURCE` clause, where the column is always NULL, rather
+    /// than as an unknown column. The error points at the reference, or at
+    /// `clause_span` when the lookup did not say where it was.
+    fn source_reference(
+        &self,
+        err: AnalyzerError,
+        source_columns: &[ScopeColumn],
+        clause_span: Span,
+    ) -> AnalyzerError {
+        let matching = self.options.identifier_matching;
+        let is_source = |table: &str| {
+            source_columns
+                .iter()
+                .any(|c| matching.matches(table, &c.table_alias))
+        };
+        let reference = match &err.kind {
+            AnalyzerErrorKind::ColumnNotFound {
+                name,
+                table: Some(table),
+            } if is_source(table) => format!("column '{}.{}'", table, name),
+            AnalyzerErrorKind::ColumnNotFound { name, table: None }
+                if source_columns
+                    .iter()
+                    .any(|c| matching.matches(name, &c.name)) =>
+            {
+                format!("column '{}'", name)
+            }
+            AnalyzerErrorKind::TableNotFound { name } if is_source(name) => {
+                format!("table '{}'", name)
+            }
+            _ => return err,
+        };
+        AnalyzerError {
+            kind: AnalyzerErrorKind::InvalidMerge {
+                reason: format!(
+                    "WHEN NOT MATCHED BY SOURCE cannot reference source {}",
+                    reference
+                ),
+            },
+            span: Some(err.span.unwrap_or(clause_span)),
+            suggestion: None,
+        }
+    }
+
     /// Analyze a CREATE TABLE statement.
+    ///
+    /// A table defined by a query gets its schema from the query's output
+    /// columns, which is returned.
     fn analyze_create_table(
         &mut self,
         create: &CreateTableStatement,
-    ) -> std::result::Result<(), AnalyzerError> {
-        // Check that the table doesn't already exist (unless IF NOT EXISTS)
-        if !create.if_not_exists {
-            let name_parts: Vec<String> =
-                create.name.parts.iter().map(|i| i.value.clone()).collect();
-            if let Ok(Some(_)) = self.catalog.resolve_table(&name_parts) {
+    ) -> std::result::Result<Option<TableSchema>, AnalyzerError> {
+        // Check that the table doesn't already exist (unless IF NOT EXISTS
+        // or OR REPLACE). A temporary table may shadow a permanent one.
+        if !create.if_not_exists && !create.or_replace && !create.temporary {
+            if let Ok(Some(_)) = self.catalog.resolve_table_ident(&create.name.parts) {
                 return Err(AnalyzerError::new(AnalyzerErrorKind::Other {
                     message: format!("table '{}' already exists", create.name),
                 }));
@@ -736,32 +2831,180 @@ impl<C: Catalog> Analyzer<C> {
                     name: col.name.value.clone(),
                 }));
             }
+            if let Some(data_type) = &col.data_type {
+                type_checker::sql_type_from_spec(&self.catalog, data_type)?;
+            }
         }
 
-        Ok(())
+        let Some(query) = &create.as_query else {
+            return Ok(None);
+        };
+        let names: Vec<Ident> = create.columns.iter().map(|c| c.name.clone()).collect();
+        let columns = self.derived_columns(&create.name, query, &names)?;
+        Ok(Some(TableSchema::new(create.name.to_string(), columns)))
     }
 
-    /// Analyze a CREATE VIEW statement.
+    /// Analyze a CREATE VIEW statement, returning the view's schema.
     fn analyze_create_view(
         &mut self,
         create: &CreateViewStatement,
-    ) -> std::result::Result<(), AnalyzerError> {
-        // Analyze the view query
-        self.analyze_query_internal(&create.query)?;
-        Ok(())
+    ) -> std::result::Result<TableSchema, AnalyzerError> {
+        let columns = self.derived_columns(&create.name, &create.query, &create.columns)?;
+        Ok(TableSchema::new(create.name.to_string(), columns))
+    }
+
+    /// The columns of a table or view defined by a query: the query's
+    /// output columns, renamed after the explicit column list if there is
+    /// one.
+    fn derived_columns(
+        &mut self,
+        relation: &ObjectName,
+        query: &Query,
+        names: &[Ident],
+    ) -> std::result::Result<Vec<ColumnSchema>, AnalyzerError> {
+        let result = self.analyze_query_internal(query)?;
+        if !names.is_empty() {
+            let relation_name = relation.to_string();
+            let count = result.columns.len();
+            self.check_column_list(&relation_name, names, count, true, relation.span)?;
+        }
+        let mut columns: Vec<ColumnSchema> =
+            result.columns.into_iter().map(column_schema).collect();
+        for (column, name) in columns.iter_mut().zip(names) {
+            column.name = name.value.clone();
+        }
+        let matching = self.options.identifier_matching;
+        for (i, column) in columns.iter().enumerate() {
+            if columns[..i]
+                .iter()
+                .any(|c| matching.matches(&c.name, &column.name))
+            {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::DuplicateColumn {
+                        name: column.name.clone(),
+                    },
+                    relation.span,
+                ));
+            }
+        }
+        Ok(columns)
     }
 
     // === Helper methods ===
 
+    /// A type checker configured from the analyzer options.
+    /// A table-not-found error suggesting a table in scope or the catalog.
+    fn table_not_found(&self, name: &str) -> AnalyzerError {
+        let tables = self.catalog.list_tables(None).unwrap_or_default();
+        let scopes = self.scopes.iter().rev().flat_map(|s| s.table_names());
+        AnalyzerError::table_not_found(name)
+            .suggesting(scopes.chain(tables.iter().map(String::as_str)))
+    }
+
+    fn type_checker(&self) -> TypeChecker<'_, C> {
+        TypeChecker::new(&self.catalog)
+            .with_options(&self.options)
+            .with_parameter_types(&self.parameter_types)
+            .with_warnings(&self.checker_warnings)
+            .with_subqueries(SubqueryContext {
+                options: &self.options,
+                scopes: &self.scopes,
+                outer_depth: &self.outer_depth,
+                explain: self.explain.as_ref(),
+            })
+    }
+
+    /// The scope column a select item reads, if it is a plain column
+    /// reference.
+    fn column_origin(&self, expr: &Expr) -> Option<ColumnOrigin> {
+        let scope = self.current_scope();
+        match &expr.kind {
+            ExprKind::Parenthesized(inner) => self.column_origin(inner),
+            ExprKind::Identifier(ident) => match scope.lookup_column(&ident.value) {
+                ColumnLookupResult::Found(_, column) => Some(ColumnOrigin::of(&column)),
+                _ => None,
+            },
+            ExprKind::CompoundIdentifier(parts) => match parts.as_slice() {
+                [table, column] => scope
+                    .lookup_qualified_column(&table.value, &column.value)
+                    .map(|column| ColumnOrigin::of(&column)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     /// Analyze an expression and return its typed result.
     fn analyze_expr(&self, expr: &Expr) -> std::result::Result<TypedExpr, AnalyzerError> {
-        let checker = TypeChecker::new(&self.catalog);
-        checker.check_expr(expr, self.current_scope())
+        self.type_checker().check_expr(expr, self.current_scope())
+    }
+
+    /// Analyze an expression whose context expects type `expected`.
+    fn analyze_expr_as(
+        &self,
+        expr: &Expr,
+        expected: &SqlType,
+    ) -> std::result::Result<TypedExpr, AnalyzerError> {
+        self.type_checker()
+            .check_expr_as(expr, expected, self.current_scope())
+    }
+
+    /// The output column an ORDER BY expression refers to: a 1-based
+    /// position, or a name the current scope resolves to an output column.
+    /// A name shared by different output columns is ambiguous.
+    fn output_position(
+        &self,
+        expr: &Expr,
+        columns: &[OutputColumn],
+    ) -> std::result::Result<Option<usize>, AnalyzerError> {
+        match &expr.kind {
+            ExprKind::Integer(n) if *n >= 1 && (*n as usize) <= columns.len() => {
+                Ok(Some(*n as usize - 1))
+            }
+            ExprKind::Identifier(ident) => {
+                match self.current_scope().resolve_column(&ident.value) {
+                    ColumnLookupResult::Alias(alias) => Ok(Some(alias.ordinal)),
+                    ColumnLookupResult::Ambiguous(candidates) => {
+                        let err = AnalyzerError::ambiguous_column(&ident.value, candidates);
+                        Err(AnalyzerError::with_span(err.kind, expr.span))
+                    }
+                    _ => Ok(None),
+                }
+            }
+            _ => Ok(None),
+        }
     }
 
     /// Analyze an expression and expect a boolean result.
     fn analyze_expr_expect_bool(&self, expr: &Expr) -> std::result::Result<(), AnalyzerError> {
-        let typed = self.analyze_expr(expr)?;
+        self.expect_bool(self.type_checker(), expr)
+    }
+
+    /// Analyze a row filter, such as a WHERE or ON condition, which must be
+    /// boolean and cannot contain aggregate or window function calls.
+    fn analyze_filter(
+        &self,
+        expr: &Expr,
+        clause: &'static str,
+    ) -> std::result::Result<(), AnalyzerError> {
+        self.expect_bool(self.row_checker(clause), expr)
+    }
+
+    /// A type checker for `clause`, which is evaluated per input row and so
+    /// cannot call aggregate or window functions.
+    fn row_checker(&self, clause: &'static str) -> TypeChecker<'_, C> {
+        self.type_checker()
+            .with_aggregates_forbidden(clause)
+            .with_windows_forbidden(clause)
+    }
+
+    /// Check an expression with `checker` and expect a boolean result.
+    fn expect_bool(
+        &self,
+        checker: TypeChecker<'_, C>,
+        expr: &Expr,
+    ) -> std::result::Result<(), AnalyzerError> {
+        let typed = checker.check_expr_as(expr, &SqlType::Bool, self.current_scope())?;
         if typed.data_type != SqlType::Bool
             && typed.data_type != SqlType::Unknown
             && typed.data_type != SqlType::Any
@@ -778,7 +3021,7 @@ impl<C: Catalog> Analyzer<C> {
 
     /// Analyze an expression and expect an integer result.
     fn analyze_expr_expect_int(&self, expr: &Expr) -> std::result::Result<(), AnalyzerError> {
-        let typed = self.analyze_expr(expr)?;
+        let typed = self.analyze_expr_as(expr, &SqlType::Int64)?;
         if !typed.data_type.is_integer()
             && typed.data_type != SqlType::Unknown
             && typed.data_type != SqlType::Any
@@ -818,19 +3061,168 @@ impl<C: Catalog> Analyzer<C> {
 
     /// Try to derive a name from an expression.
     fn expr_to_name(&self, expr: &Expr) -> Option<String> {
-        match &expr.kind {
-            ExprKind::Identifier(ident) => Some(ident.value.clone()),
-            ExprKind::CompoundIdentifier(parts) => parts.last().map(|i| i.value.clone()),
-            ExprKind::Function(func) => func.name.parts.last().map(|i| i.value.clone()),
-            ExprKind::Aggregate(agg) => agg.function.name.parts.last().map(|i| i.value.clone()),
-            ExprKind::WindowFunction(wf) => wf.function.name.parts.last().map(|i| i.value.clone()),
-            _ => None,
+        let ident = match &expr.kind {
+            ExprKind::Identifier(ident) => ident,
+            ExprKind::CompoundIdentifier(parts) => parts.last()?,
+            ExprKind::Function(func) => func.name.parts.last()?,
+            ExprKind::Aggregate(agg) => agg.function.name.parts.last()?,
+            ExprKind::WindowFunction(wf) => wf.function.name.parts.last()?,
+            _ => return None,
+        };
+        Some(self.options.identifier_case.fold(ident))
+    }
+
+    /// The key a GROUP BY expression is matched by: its SQL text,
+    /// normalized by the identifier matching policy.
+    fn group_key(&self, expr: &Expr) -> String {
+        self.options
+            .identifier_matching
+            .normalize(&expr.to_string())
+    }
+
+    /// Under [`GroupByMode::Strict`], check that the select list and HAVING
+    /// clause of a grouped query read columns only through GROUP BY
+    /// expressions or aggregate calls.
+    fn check_grouped_select(
+        &self,
+        select: &Select,
+        from_columns: &[ScopeColumn],
+        group_keys: &[String],
+    ) -> std::result::Result<(), AnalyzerError> {
+        let matching = self.options.identifier_matching;
+        let grouped = |table: Option<&str>, column: &str| {
+            let column = matching.normalize(column);
+            group_keys.iter().any(|key| {
+                *key == column
+                    || key.rsplit_once('.').is_some_and(|(qualifier, name)| {
+                        name == column && table.is_none_or(|t| matching.normalize(t) == qualifier)
+                    })
+            })
+        };
+        let is_aggregate = |call: &FunctionCall| {
+            let name: Vec<String> = call.name.parts.iter().map(|p| p.value.clone()).collect();
+            matches!(self.catalog.resolve_function(&name), Ok(Some(sig)) if sig.is_aggregate)
+        };
+        let ungrouped = |expr: &Expr| {
+            ungrouped_column(
+                expr,
+                &|e| group_keys.contains(&self.group_key(e)),
+                &grouped,
+                &is_aggregate,
+            )
+        };
+
+        let mut exprs: Vec<&Expr> = Vec::new();
+        for item in &select.projection {
+            match item {
+                SelectItem::Expr { expr, .. } => exprs.push(expr),
+                SelectItem::Wildcard => {
+                    if let Some(column) = from_columns
+                        .iter()
+                        .find(|c| !grouped(Some(&c.table_alias), &c.name))
+                    {
+                        return Err(AnalyzerError::non_aggregated_column(&column.name));
+                    }
+                }
+                _ => {}
+            }
+        }
+        exprs.extend(select.having.as_deref());
+        for expr in exprs {
+            if let Some((column, span)) = ungrouped(expr) {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::NonAggregatedColumn { column },
+                    span,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a table to the current scope, failing at `span` if its alias is
+    /// already in scope.
+    fn add_scope_table(
+        &mut self,
+        table: ScopeTable,
+        span: Span,
+    ) -> std::result::Result<(), AnalyzerError> {
+        self.current_scope_mut()
+            .add_table(table)
+            .map_err(|err| AnalyzerError::with_span(err.kind, span))
+    }
+
+    /// Add a derived table to the current scope. Without an alias it has a
+    /// made-up name, which never conflicts.
+    fn add_aliased_table(
+        &mut self,
+        table: ScopeTable,
+        alias: Option<&Alias>,
+    ) -> std::result::Result<(), AnalyzerError> {
+        match alias {
+            Some(alias) => self.add_scope_table(table, alias.name.span),
+            None => {
+                self.current_scope_mut().add_unnamed_table(table);
+                Ok(())
+            }
+        }
+    }
+
+    /// Run `analyze` with the current scope enclosing the scopes it pushes,
+    /// so a lateral FROM item can reference the FROM items before it.
+    /// Those references do not make the query correlated.
+    fn with_lateral_scope<T>(&mut self, analyze: impl FnOnce(&mut Self) -> T) -> T {
+        let preceding = Arc::new(self.current_scope().clone());
+        let outer = self.outer.replace(preceding);
+        let outer_depth = self.outer_depth.replace(0);
+        let result = analyze(self);
+        self.outer = outer;
+        let depth = self.outer_depth.get().saturating_sub(1);
+        self.outer_depth.set(outer_depth.max(depth));
+        result
+    }
+
+    /// Run `analyze` and, during [`explain`](Self::explain), return what it
+    /// recorded as a query of its own.
+    fn explained(
+        &mut self,
+        analyze: impl FnOnce(&mut Self) -> std::result::Result<AnalyzedQuery, AnalyzerError>,
+    ) -> std::result::Result<(AnalyzedQuery, Option<ExplainQuery>), AnalyzerError> {
+        if let Some(recorder) = &self.explain {
+            recorder.open();
+        }
+        let result = analyze(self);
+        let recorder = self.explain.as_ref();
+        let explained = recorder.and_then(|recorder| recorder.close(result.as_ref().ok()));
+        Ok((result?, explained))
+    }
+
+    /// Record part of the query being explained.
+    fn record(&self, update: impl FnOnce(&mut explain::OpenQuery)) {
+        if let Some(recorder) = &self.explain {
+            recorder.record(update);
         }
     }
 
+    /// How many FROM items the query being explained has recorded.
+    fn recorded_table_count(&self) -> usize {
+        let mut count = 0;
+        self.record(|open| count = open.tables.len());
+        count
+    }
+
+    /// Remove the FROM items recorded after the first `count`, which an
+    /// operator such as PIVOT reads.
+    fn take_recorded_tables(&self, count: usize) -> Vec<ExplainTable> {
+        let mut tables = Vec::new();
+        self.record(|open| tables = open.tables.split_off(count));
+        tables
+    }
+
     /// Push a new scope.
     fn push_scope(&mut self) {
-        self.scopes.push(Scope::new());
+        let scope =
+            Scope::with_matching(self.options.identifier_matching).with_outer(self.outer.clone());
+        self.scopes.push(scope);
     }
 
     /// Pop the current scope.
@@ -843,20 +3235,512 @@ impl<C: Catalog> Analyzer<C> {
         self.scopes.last().expect("No scope available")
     }
 
+    /// The current scope's table and join column counts, for marking the
+    /// tables a join input adds.
+    fn scope_marks(&self) -> (usize, usize) {
+        let scope = self.current_scope();
+        (scope.table_count(), scope.join_column_count())
+    }
+
     /// Get the current scope mutably.
     fn current_scope_mut(&mut self) -> &mut Scope {
         self.scopes.last_mut().expect("No scope available")
     }
 
-    /// Look up a CTE in all scopes (current and parents).
-    fn lookup_cte(&self, name: &str) -> Option<CteRef> {
-        for scope in self.scopes.iter().rev() {
-            if let Some(cte) = scope.lookup_cte(name) {
-                return Some(cte.clone());
+    /// Apply the [`WriteGuard`] policy to a write that can affect every row.
+    fn guard_write(&mut self, statement: &str) -> std::result::Result<(), AnalyzerError> {
+        let statement = statement.to_string();
+        match self.options.unguarded_writes {
+            WriteGuard::Off => Ok(()),
+            WriteGuard::Warn => {
+                self.warnings
+                    .push(AnalyzerWarning::new(AnalyzerWarningKind::UnguardedWrite {
+                        statement,
+                    }));
+                Ok(())
+            }
+            WriteGuard::Error => Err(AnalyzerError::new(AnalyzerErrorKind::UnguardedWrite {
+                statement,
+            })),
+        }
+    }
+
+    /// Warn if a new CTE hides an outer CTE or a catalog table.
+    fn warn_cte_shadowing(&mut self, cte: &Cte) {
+        let name = &cte.name.value;
+        let kind = if let Some(outer) = self.lookup_cte(name) {
+            AnalyzerWarningKind::CteShadowsCte {
+                name: name.clone(),
+                outer: outer.span,
+            }
+        } else if let Ok(Some(_)) = self
+            .catalog
+            .resolve_table_ident(std::slice::from_ref(&cte.name))
+        {
+            AnalyzerWarningKind::CteShadowsTable { name: name.clone() }
+        } else {
+            return;
+        };
+        self.warnings
+            .push(AnalyzerWarning::with_span(kind, cte.name.span));
+    }
+
+    /// Look up a CTE in all scopes, innermost first.
+    fn lookup_cte(&self, name: &str) -> Option<&CteRef> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.lookup_cte(name))
+    }
+
+    /// Resolve a CTE reference to its cached columns, re-qualified with the
+    /// reference's alias. Returns `None` if no CTE with this name is visible.
+    fn cte_columns(&self, name: &str, alias: &str) -> Option<Vec<ScopeColumn>> {
+        let cte = self.lookup_cte(name)?;
+        let columns = cte
+            .columns
+            .iter()
+            .map(|c| {
+                ScopeColumn::new(
+                    c.name.clone(),
+                    c.data_type.clone(),
+                    c.nullable,
+                    alias.to_string(),
+                    c.column_index,
+                )
+            })
+            .collect();
+        Some(columns)
+    }
+}
+
+impl<C: Catalog> Analyzer<SessionCatalog<C>> {
+    /// Analyze a statement, then apply the tables and views it creates or
+    /// drops to the session.
+    fn analyze_in_session(&mut self, stmt: &Statement) -> Result<StatementAnalysis> {
+        self.errors.clear();
+        self.warnings.clear();
+        self.parameter_types.borrow_mut().clear();
+        self.cte_count = 0;
+        let result = self.analyze_statement(stmt);
+        let mut analysis = self.finish(result)?;
+        match (&stmt.kind, &mut analysis) {
+            (_, StatementAnalysis::Ddl { table: Some(table) }) => {
+                self.catalog.create_derived(stmt, table.clone())?;
+            }
+            (StatementKind::CreateTable(create), StatementAnalysis::Ddl { table }) => {
+                self.catalog.apply(stmt)?;
+                *table = self
+                    .catalog
+                    .session_table(&create.name)
+                    .map(Arc::unwrap_or_clone);
+            }
+            (StatementKind::Drop(drop), _)
+                if matches!(drop.object_type, ObjectType::Table | ObjectType::View)
+                    && drop
+                        .names
+                        .iter()
+                        .all(|name| self.catalog.session_table(name).is_some()) =>
+            {
+                self.catalog.apply(stmt)?;
+            }
+            _ => {}
+        }
+        Ok(analysis)
+    }
+
+    /// Analyze a script, statement by statement, stopping at the first
+    /// statement that fails.
+    ///
+    /// Each `CREATE TABLE` and `CREATE VIEW`, including `CREATE TEMP TABLE`
+    /// and `CREATE TABLE ... AS SELECT`, is applied to the session once it
+    /// has been analyzed, so later statements can use what earlier ones
+    /// created. `DROP TABLE` and `DROP VIEW` drop session tables; dropping
+    /// a base table is analyzed but leaves the base catalog unchanged.
+    pub fn analyze_script(&mut self, stmts: &[Statement]) -> Result<()> {
+        for stmt in stmts {
+            self.analyze_in_session(stmt)?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert an analyzer error into a crate error, keeping its span.
+/// Build the signature defined by a CREATE FUNCTION statement, analyzing
+/// its body against `catalog`.
+pub(crate) fn create_function_signature<C: Catalog>(
+    catalog: &C,
+    create: &CreateFunctionStatement,
+    matching: IdentifierMatching,
+) -> Result<FunctionSignature> {
+    function::check_create_function(catalog, create, matching).map_err(to_error)
+}
+
+/// The output columns of a query as table columns, for tables and views
+/// created from a query.
+pub(crate) fn query_columns<C: Catalog>(
+    catalog: &C,
+    query: &Query,
+    matching: IdentifierMatching,
+) -> Result<Vec<ColumnSchema>> {
+    let options = AnalyzerOptions {
+        identifier_matching: matching,
+        ..AnalyzerOptions::default()
+    };
+    let result =
+        Analyzer::with_catalog_and_options(catalog, options).analyze_query_result(query)?;
+    Ok(result.columns.into_iter().map(column_schema).collect())
+}
+
+/// The table column an output column becomes in a table or view defined
+/// by a query.
+fn column_schema(column: OutputColumn) -> ColumnSchema {
+    let schema = ColumnSchema::new(column.name, column.data_type);
+    if column.nullable {
+        schema
+    } else {
+        schema.not_null()
+    }
+}
+
+/// Which inputs of a join are null-extended, as `(left, right)`.
+fn null_extended_sides(join_type: JoinType) -> (bool, bool) {
+    match join_type {
+        JoinType::Left => (false, true),
+        JoinType::Right => (true, false),
+        JoinType::Full => (true, true),
+        _ => (false, false),
+    }
+}
+
+/// Whether analysis can carry on past an error, as it can past names that
+/// do not resolve.
+fn is_recoverable(err: &AnalyzerError) -> bool {
+    matches!(
+        err.kind,
+        AnalyzerErrorKind::ColumnNotFound { .. }
+            | AnalyzerErrorKind::AmbiguousColumn { .. }
+            | AnalyzerErrorKind::FunctionNotFound { .. }
+            | AnalyzerErrorKind::VariableNotFound { .. }
+    )
+}
+
+/// The span covering a MERGE clause's condition and the values its action
+/// assigns, or `None` for an unconditional DELETE.
+fn merge_clause_span(condition: Option<&Expr>, action: &MergeMatchedAction) -> Option<Span> {
+    let values = match action {
+        MergeMatchedAction::Update { assignments } => assignments.as_slice(),
+        MergeMatchedAction::Delete => &[],
+    };
+    condition
+        .map(|cond| cond.span)
+        .into_iter()
+        .chain(values.iter().map(|a| a.value.span))
+        .reduce(Span::merge)
+}
+
+/// The error for a table name the catalog failed to resolve: ambiguous
+/// when it matched several tables, and otherwise not found.
+fn table_lookup_error(err: Error, name: &str) -> AnalyzerError {
+    let ErrorKind::AmbiguousName { candidates, .. } = err.kind() else {
+        return AnalyzerError::table_not_found(name);
+    };
+    let kind = AnalyzerErrorKind::AmbiguousTable {
+        name: name.to_string(),
+        candidates: candidates.clone(),
+    };
+    match err.span() {
+        Some(span) => AnalyzerError::with_span(kind, span),
+        None => AnalyzerError::new(kind),
+    }
+}
+
+fn to_error(e: AnalyzerError) -> Error {
+    if let AnalyzerErrorKind::UnguardedWrite { .. } = &e.kind {
+        let kind = ErrorKind::PolicyViolation {
+            code: UNGUARDED_WRITE,
+            message: e.to_string(),
+        };
+        return match e.span {
+            Some(span) => Error::with_span(kind, span),
+            None => Error::new(kind),
+        };
+    }
+    match e.span {
+        Some(span) => Error::with_span(ErrorKind::Internal(e.to_string()), span),
+        None => Error::analyzer(e.to_string()),
+    }
+}
+
+/// The literal an expression consists of, looking through unary signs.
+fn literal_kind(expr: &Expr) -> Option<&ExprKind> {
+    match &expr.kind {
+        ExprKind::UnaryOp {
+            op: UnaryOp::Plus | UnaryOp::Minus,
+            expr,
+        } => literal_kind(expr),
+        kind @ (ExprKind::Integer(_) | ExprKind::Float(_) | ExprKind::String(_)) => Some(kind),
+        _ => None,
+    }
+}
+
+/// The warning for a literal that cannot fit a column of type `target`: a
+/// string or bytes longer than its length, or a number with more integer
+/// digits than its precision and scale allow.
+fn literal_overflow(column: &str, target: &SqlType, value: &Expr) -> Option<AnalyzerWarningKind> {
+    let length = match (&value.kind, target) {
+        (ExprKind::String(s), SqlType::Varchar(Some(max))) => Some((s.chars().count(), *max)),
+        (ExprKind::Bytes(b), SqlType::Varbinary(Some(max))) => Some((b.len(), *max)),
+        _ => None,
+    };
+    if let Some((length, max)) = length {
+        return (length > max as usize).then(|| AnalyzerWarningKind::ValueTooLong {
+            column: column.to_string(),
+            column_type: target.clone(),
+            length,
+        });
+    }
+
+    let SqlType::Numeric {
+        precision: Some(precision),
+        scale,
+    } = target
+    else {
+        return None;
+    };
+    let digits = match literal_kind(value)? {
+        ExprKind::Integer(0) => 0,
+        ExprKind::Integer(n) => n.unsigned_abs().ilog10() as usize + 1,
+        ExprKind::Float(f) if f.is_finite() && f.abs() >= 1.0 => {
+            format!("{:.0}", f.abs().trunc()).len()
+        }
+        _ => 0,
+    };
+    let allowed = precision - scale.unwrap_or(0).min(*precision);
+    (digits > usize::from(allowed)).then(|| AnalyzerWarningKind::NumericOutOfRange {
+        column: column.to_string(),
+        column_type: target.clone(),
+    })
+}
+
+/// Resolve the target columns of an INSERT, all of the table's when none
+/// are listed.
+fn insert_targets<'t>(
+    table: &'t TableSchema,
+    table_name: &str,
+    columns: &[Ident],
+) -> std::result::Result<Vec<&'t ColumnSchema>, AnalyzerError> {
+    if columns.is_empty() {
+        return Ok(table.columns.iter().collect());
+    }
+    columns
+        .iter()
+        .map(|col| {
+            table.get_column(&col.value).ok_or_else(|| {
+                AnalyzerError::with_span(
+                    AnalyzerErrorKind::ColumnNotFound {
+                        name: col.value.clone(),
+                        table: Some(table_name.to_string()),
+                    },
+                    col.span,
+                )
+                .suggesting(table.column_names())
+            })
+        })
+        .collect()
+}
+
+/// Check that an INSERT gives a value for every NOT NULL column without a
+/// default; omitted columns take NULL unless they have one.
+fn check_missing_insert_columns(
+    table: &TableSchema,
+    given: &[&ColumnSchema],
+    span: Span,
+) -> std::result::Result<(), AnalyzerError> {
+    match table.columns.iter().find(|col| {
+        !col.nullable
+            && col.default_value.is_none()
+            && !given.iter().any(|target| target.name == col.name)
+    }) {
+        Some(missing) => Err(AnalyzerError::with_span(
+            AnalyzerErrorKind::MissingInsertColumn {
+                column: missing.name.clone(),
+            },
+            span,
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The analyzed items of a select list.
+struct Projection {
+    columns: Vec<OutputColumn>,
+    has_aggregation: bool,
+    has_window_functions: bool,
+    windows: Vec<TypedWindow>,
+}
+
+/// Which references [`Analyzer::table_reference_span`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecursiveTermCheck {
+    /// Any reference.
+    Any,
+    /// References on the right side of an EXCEPT.
+    ExceptRight,
+}
+
+/// The names of the columns an expression reads, by their last name part.
+fn column_names<'a>(exprs: impl IntoIterator<Item = &'a Expr>) -> Vec<String> {
+    struct Names(Vec<String>);
+
+    impl<'ast> Visitor<'ast> for Names {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            match &expr.kind {
+                ExprKind::Identifier(ident) => self.0.push(ident.value.clone()),
+                ExprKind::CompoundIdentifier(parts) => {
+                    self.0.extend(parts.last().map(|p| p.value.clone()))
+                }
+                _ => visit::walk_binary_chain(self, expr, |_, _| {}),
+            }
+        }
+    }
+
+    let mut names = Names(Vec::new());
+    for expr in exprs {
+        names.visit_expr(expr);
+    }
+    names.0
+}
+
+/// The first column reference in `expr` that is outside every aggregate call
+/// and every grouped subexpression, with its span. Subqueries are not
+/// searched.
+fn ungrouped_column(
+    expr: &Expr,
+    is_group_key: &dyn Fn(&Expr) -> bool,
+    grouped: &dyn Fn(Option<&str>, &str) -> bool,
+    is_aggregate: &dyn Fn(&FunctionCall) -> bool,
+) -> Option<(String, Span)> {
+    struct Finder<'f> {
+        is_group_key: &'f dyn Fn(&Expr) -> bool,
+        grouped: &'f dyn Fn(Option<&str>, &str) -> bool,
+        is_aggregate: &'f dyn Fn(&FunctionCall) -> bool,
+        found: Option<(String, Span)>,
+    }
+
+    impl<'ast> Visitor<'ast> for Finder<'_> {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if self.found.is_some() || (self.is_group_key)(expr) {
+                return;
+            }
+            match &expr.kind {
+                ExprKind::Aggregate(_) => {}
+                ExprKind::Function(call) if (self.is_aggregate)(call) => {}
+                ExprKind::Identifier(ident) => {
+                    if !(self.grouped)(None, &ident.value) {
+                        self.found = Some((ident.value.clone(), expr.span));
+                    }
+                }
+                ExprKind::CompoundIdentifier(parts) => {
+                    let (column, qualifier) = match parts.as_slice() {
+                        [.., table, column] => (column, Some(table.value.as_str())),
+                        [column] => (column, None),
+                        [] => return,
+                    };
+                    if !(self.grouped)(qualifier, &column.value) {
+                        let name: Vec<&str> = parts.iter().map(|p| p.value.as_str()).collect();
+                        self.found = Some((name.join("."), expr.span));
+                    }
+                }
+                _ => visit::walk_expr(self, expr),
+            }
+        }
+
+        fn visit_query(&mut self, _query: &'ast Query) {}
+    }
+
+    let mut finder = Finder {
+        is_group_key,
+        grouped,
+        is_aggregate,
+        found: None,
+    };
+    finder.visit_expr(expr);
+    finder.found
+}
+
+/// Find the first FROM-clause reference to a table by unqualified name in
+/// a SELECT or parenthesized query.
+fn first_table_reference(
+    body: &QueryBody,
+    name: &str,
+    matching: IdentifierMatching,
+) -> Option<Span> {
+    struct Finder<'n> {
+        name: &'n str,
+        matching: IdentifierMatching,
+        found: Option<Span>,
+    }
+
+    impl<'ast> Visitor<'ast> for Finder<'_> {
+        fn visit_table_ref(&mut self, table: &'ast TableRef) {
+            if self.found.is_some() {
+                return;
+            }
+            if let TableRefKind::Table { name, .. } = &table.kind {
+                if let [part] = name.parts.as_slice() {
+                    if self.matching.matches(&part.value, self.name) {
+                        self.found = Some(name.span);
+                        return;
+                    }
+                }
             }
+            visit::walk_table_ref(self, table);
         }
-        None
     }
+
+    let mut finder = Finder {
+        name,
+        matching,
+        found: None,
+    };
+    match body {
+        QueryBody::Select(select) => finder.visit_select(select),
+        QueryBody::Parenthesized(query) => finder.visit_query(query),
+        QueryBody::SetOperation { .. } => {}
+    }
+    finder.found
+}
+
+/// The select-list expressions of a plain SELECT without wildcards, in
+/// output order.
+fn select_list_exprs(query: &Query) -> Option<Vec<&Expr>> {
+    body_select_list_exprs(&query.body)
+}
+
+/// [`select_list_exprs`] for a query body.
+fn body_select_list_exprs(body: &QueryBody) -> Option<Vec<&Expr>> {
+    let QueryBody::Select(select) = body else {
+        return None;
+    };
+    select
+        .projection
+        .iter()
+        .map(|item| match item {
+            SelectItem::Expr { expr, .. } => Some(&**expr),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether a string literal can be cast to this type by a lenient assignment.
+fn is_string_castable(target: &SqlType) -> bool {
+    target.is_numeric()
+        || target.is_datetime()
+        || matches!(
+            target,
+            SqlType::Bool | SqlType::Json | SqlType::Uuid | SqlType::Interval
+        )
 }
 
 impl Default for Analyzer<MemoryCatalog> {
@@ -874,11 +3758,26 @@ impl Analyzer<MemoryCatalog> {
     }
 }
 
+impl<'c> Analyzer<&'c dyn Catalog> {
+    /// An analyzer for a subquery of an expression checked in `scope`. It
+    /// sees the CTEs of the enclosing analyzer's scopes, and its scopes
+    /// chain to `scope` so the subquery can reference its columns.
+    fn for_subquery(catalog: &'c dyn Catalog, context: SubqueryContext<'_>, scope: &Scope) -> Self {
+        let mut analyzer = Self::with_catalog_and_options(catalog, context.options.clone());
+        analyzer.scopes = context.scopes.to_vec();
+        analyzer.outer = Some(Arc::new(scope.clone()));
+        analyzer.explain = context.explain.map(|_| ExplainRecorder::default());
+        analyzer
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::catalog::TableSchemaBuilder;
+    use crate::catalog::{FunctionSignature, TableSchema, TableSchemaBuilder};
+    use crate::error::Span;
     use crate::parser::Parser;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     fn setup_test_catalog() -> MemoryCatalog {
         let mut catalog = MemoryCatalog::new();
@@ -888,9 +3787,9 @@ mod tests {
         catalog.add_table(
             TableSchemaBuilder::new("users")
                 .column(ColumnSchema::new("id", SqlType::Int64).not_null())
-                .column(ColumnSchema::new("name", SqlType::Varchar))
+                .column(ColumnSchema::new("name", SqlType::Varchar(None)))
                 .column(ColumnSchema::new("age", SqlType::Int64))
-                .column(ColumnSchema::new("email", SqlType::Varchar))
+                .column(ColumnSchema::new("email", SqlType::Varchar(None)))
                 .build(),
         );
 
@@ -922,21 +3821,62 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_simple_select() {
-        let catalog = setup_test_catalog();
-        let result = parse_and_analyze("SELECT id, name FROM users", catalog).unwrap();
-
-        assert_eq!(result.columns.len(), 2);
-        assert_eq!(result.columns[0].name, "id");
-        assert_eq!(result.columns[0].data_type, SqlType::Int64);
-        assert_eq!(result.columns[1].name, "name");
-        assert_eq!(result.columns[1].data_type, SqlType::Varchar);
-    }
-
-    #[test]
-    fn test_select_star() {
-        let catalog = setup_test_catalog();
+    fn setup_using_catalog() -> MemoryCatalog {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        catalog.add_table(
+            TableSchemaBuilder::new("l")
+                .column(ColumnSchema::new("id", SqlType::Int32).not_null())
+                .column(ColumnSchema::new("a", SqlType::Varchar(None)))
+                .build(),
+        );
+        catalog.add_table(
+            TableSchemaBuilder::new("r")
+                .column(ColumnSchema::new("b", SqlType::Bool))
+                .column(ColumnSchema::new("id", SqlType::Int64))
+                .build(),
+        );
+        catalog.add_table(
+            TableSchemaBuilder::new("c")
+                .column(ColumnSchema::new("k", SqlType::Float64))
+                .build(),
+        );
+        catalog
+    }
+
+    fn analyze_with_options(sql: &str, options: AnalyzerOptions) -> Result<AnalyzedQuery> {
+        let stmt = Parser::new(sql).parse()?.into_iter().next().unwrap();
+        if let StatementKind::Query(query) = stmt.kind {
+            let mut analyzer = Analyzer::with_catalog_and_options(setup_using_catalog(), options);
+            analyzer.analyze_query_result(&query)
+        } else {
+            panic!("Expected a query statement");
+        }
+    }
+
+    fn column_summary(result: &AnalyzedQuery) -> Vec<(String, SqlType, bool)> {
+        result
+            .columns
+            .iter()
+            .map(|c| (c.name.clone(), c.data_type.clone(), c.nullable))
+            .collect()
+    }
+
+    #[test]
+    fn test_simple_select() {
+        let catalog = setup_test_catalog();
+        let result = parse_and_analyze("SELECT id, name FROM users", catalog).unwrap();
+
+        assert_eq!(result.columns.len(), 2);
+        assert_eq!(result.columns[0].name, "id");
+        assert_eq!(result.columns[0].data_type, SqlType::Int64);
+        assert_eq!(result.columns[1].name, "name");
+        assert_eq!(result.columns[1].data_type, SqlType::Varchar(None));
+    }
+
+    #[test]
+    fn test_select_star() {
+        let catalog = setup_test_catalog();
         let result = parse_and_analyze("SELECT * FROM users", catalog).unwrap();
 
         assert_eq!(result.columns.len(), 4);
@@ -989,6 +3929,42 @@ mod tests {
         assert!(err.to_string().contains("not found"));
     }
 
+    #[test]
+    fn test_undefined_qualifier() {
+        let analyze = |sql: &str| parse_and_analyze(sql, setup_test_catalog());
+
+        // A qualifier no query in scope defines is an undefined table.
+        for (sql, qualifier) in [
+            ("SELECT x.a FROM users", "x"),
+            ("SELECT x.name FROM users u", "x"),
+            ("SELECT (SELECT x.id FROM orders o) FROM users", "x"),
+        ] {
+            let err = analyze(sql).unwrap_err();
+            let message = format!("table '{}' not found", qualifier);
+            assert!(err.to_string().contains(&message), "{}: {}", sql, err);
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some(qualifier));
+        }
+        let err = analyze("SELECT usr.id FROM users usr2").unwrap_err();
+        assert!(err.to_string().contains("did you mean 'usr2'?"), "{}", err);
+
+        // A defined qualifier with a missing column is a missing column, in
+        // this query or an enclosing one.
+        for sql in [
+            "SELECT u.nope FROM users u",
+            "SELECT (SELECT u.nope FROM orders o) FROM users u",
+        ] {
+            let err = analyze(sql).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("column 'nope' not found in table 'u'"),
+                "{}: {}",
+                sql,
+                err
+            );
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("u.nope"));
+        }
+    }
+
     #[test]
     fn test_ambiguous_column() {
         let catalog = setup_test_catalog();
@@ -1026,4 +4002,3475 @@ mod tests {
 
         assert_eq!(result.columns.len(), 2);
     }
+
+    #[test]
+    fn test_using_join_coalesced_order() {
+        let cases = [
+            ("l JOIN r USING (id)", false),
+            ("l LEFT JOIN r USING (id)", false),
+            ("l FULL JOIN r USING (id)", true),
+        ];
+        for (from, id_nullable) in cases {
+            let sql = format!("SELECT * FROM {}", from);
+            let result = analyze_with_options(&sql, AnalyzerOptions::default()).unwrap();
+            assert_eq!(
+                column_summary(&result),
+                vec![
+                    ("id".to_string(), SqlType::Int64, id_nullable),
+                    ("a".to_string(), SqlType::Varchar(None), true),
+                    ("b".to_string(), SqlType::Bool, true),
+                ],
+                "{}",
+                from
+            );
+        }
+    }
+
+    #[test]
+    fn test_using_join_both_sides_order() {
+        let options = AnalyzerOptions::new().with_using_wildcard(UsingWildcardMode::BothSides);
+        for (from, left_id_nullable) in [
+            ("l JOIN r USING (id)", false),
+            ("l LEFT JOIN r USING (id)", false),
+            ("l FULL JOIN r USING (id)", true),
+        ] {
+            let sql = format!("SELECT * FROM {}", from);
+            let result = analyze_with_options(&sql, options.clone()).unwrap();
+            assert_eq!(
+                column_summary(&result),
+                vec![
+                    ("id".to_string(), SqlType::Int32, left_id_nullable),
+                    ("a".to_string(), SqlType::Varchar(None), true),
+                    ("b".to_string(), SqlType::Bool, true),
+                    ("id".to_string(), SqlType::Int64, true),
+                ],
+                "{}",
+                from
+            );
+        }
+    }
+
+    #[test]
+    fn test_using_join_column_references() {
+        for options in [
+            AnalyzerOptions::default(),
+            AnalyzerOptions::new().with_using_wildcard(UsingWildcardMode::BothSides),
+        ] {
+            let result = analyze_with_options(
+                "SELECT id, l.id, r.id FROM l FULL JOIN r USING (id)",
+                options,
+            )
+            .unwrap();
+            assert_eq!(
+                column_summary(&result),
+                vec![
+                    ("id".to_string(), SqlType::Int64, true),
+                    ("id".to_string(), SqlType::Int32, true),
+                    ("id".to_string(), SqlType::Int64, true),
+                ]
+            );
+        }
+
+        let err = analyze_with_options("SELECT * FROM l JOIN r USING (a)", AnalyzerOptions::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+
+    #[test]
+    fn test_using_join_validation() {
+        let result =
+            analyze_with_options("SELECT id FROM l JOIN r USING (id)", AnalyzerOptions::new())
+                .unwrap();
+        assert_eq!(
+            column_summary(&result),
+            vec![("id".to_string(), SqlType::Int64, false)]
+        );
+
+        let sql = "SELECT * FROM l JOIN r USING (id, ID)";
+        let err = analyze_with_options(sql, AnalyzerOptions::new()).unwrap_err();
+        assert!(err.to_string().contains("more than once"), "{}", err);
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("ID"));
+
+        let sql = "SELECT * FROM l JOIN r USING (b)";
+        let err = analyze_with_options(sql, AnalyzerOptions::new()).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("b"));
+
+        let err = analyze_with_options(
+            "SELECT * FROM (SELECT 'x' AS id) s JOIN r USING (id)",
+            AnalyzerOptions::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("join column 'id'"), "{}", err);
+
+        // A name matching two columns of one input is ambiguous
+        let sql = "SELECT * FROM (l JOIN r ON TRUE) JOIN c USING (id)";
+        let err = analyze_with_options(sql, AnalyzerOptions::new()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("ambiguous column 'id' could be any of: l.id, r.id"),
+            "{}",
+            err
+        );
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("id"));
+
+        // A NATURAL join has no name list, so its errors cover the join
+        let sql = "SELECT * FROM (SELECT 'x' AS id) s NATURAL JOIN r";
+        let err = analyze_with_options(sql, AnalyzerOptions::new()).unwrap_err();
+        assert!(err.to_string().contains("join column 'id'"), "{}", err);
+        assert!(err.span().is_some());
+    }
+
+    #[test]
+    fn test_outer_join_nullability() {
+        let cases = [
+            ("users u JOIN orders o ON TRUE", [false, false]),
+            ("users u LEFT JOIN orders o ON TRUE", [false, true]),
+            ("users u RIGHT JOIN orders o ON TRUE", [true, false]),
+            ("users u FULL JOIN orders o ON TRUE", [true, true]),
+            ("users u, orders o", [false, false]),
+        ];
+        for (from, expected) in cases {
+            let sql = format!("SELECT u.id, o.id FROM {}", from);
+            let result = parse_and_analyze(&sql, setup_test_catalog()).unwrap();
+            let nullable: Vec<_> = result.columns.iter().map(|c| c.nullable).collect();
+            assert_eq!(nullable, expected, "{}", from);
+        }
+
+        let nested = [
+            (
+                "users u LEFT JOIN (orders o JOIN users v ON TRUE) ON TRUE",
+                [false, true, true],
+            ),
+            (
+                "users u LEFT JOIN orders o ON TRUE RIGHT JOIN users v ON TRUE",
+                [true, true, false],
+            ),
+            (
+                "(users u RIGHT JOIN orders o ON TRUE) JOIN users v ON TRUE",
+                [true, false, false],
+            ),
+        ];
+        for (from, expected) in nested {
+            let sql = format!("SELECT u.id, o.id, v.id FROM {}", from);
+            let result = parse_and_analyze(&sql, setup_test_catalog()).unwrap();
+            let nullable: Vec<_> = result.columns.iter().map(|c| c.nullable).collect();
+            assert_eq!(nullable, expected, "{}", from);
+        }
+
+        let result = analyze_with_options(
+            "SELECT * FROM c LEFT JOIN (l JOIN r USING (id)) ON TRUE",
+            AnalyzerOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            column_summary(&result),
+            vec![
+                ("k".to_string(), SqlType::Float64, true),
+                ("id".to_string(), SqlType::Int64, true),
+                ("a".to_string(), SqlType::Varchar(None), true),
+                ("b".to_string(), SqlType::Bool, true),
+            ]
+        );
+        let result = analyze_with_options(
+            "SELECT id FROM c LEFT JOIN (l JOIN r USING (id)) ON TRUE",
+            AnalyzerOptions::new(),
+        )
+        .unwrap();
+        assert!(result.columns[0].nullable);
+
+        // Null extension stays within the join's own inputs
+        for (from, expected) in [
+            (
+                "users v, users u LEFT JOIN orders o ON TRUE",
+                [false, false, true],
+            ),
+            (
+                "users v CROSS JOIN users u JOIN orders o ON TRUE",
+                [false, false, false],
+            ),
+        ] {
+            let sql = format!("SELECT v.id, u.id, o.id FROM {}", from);
+            let result = parse_and_analyze(&sql, setup_test_catalog()).unwrap();
+            let nullable: Vec<_> = result.columns.iter().map(|c| c.nullable).collect();
+            assert_eq!(nullable, expected, "{}", from);
+        }
+        let err = parse_and_analyze(
+            "SELECT u.id FROM users u LEFT JOIN orders o ON o.nope = u.id",
+            setup_test_catalog(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("'nope'"), "{}", err);
+    }
+
+    #[test]
+    fn test_natural_join_coalesced_order() {
+        let result =
+            analyze_with_options("SELECT * FROM r NATURAL JOIN l", AnalyzerOptions::new()).unwrap();
+        let names: Vec<_> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "b", "a"]);
+
+        let result = parse_and_analyze(
+            "SELECT * FROM users NATURAL JOIN orders",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        let names: Vec<_> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "id",
+                "name",
+                "age",
+                "email",
+                "user_id",
+                "amount",
+                "created_at"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_natural_join_without_common_columns() {
+        let sql = "SELECT * FROM l NATURAL JOIN c";
+        let err = analyze_with_options(sql, AnalyzerOptions::new()).unwrap_err();
+        assert!(err.to_string().contains("share no column names"), "{}", err);
+        assert_eq!(
+            err.span().map(|s| &sql[s.start..s.end]),
+            Some("l NATURAL JOIN c")
+        );
+
+        for (policy, warnings) in [
+            (DisjointNaturalJoin::Warn, 1),
+            (DisjointNaturalJoin::Off, 0),
+        ] {
+            let stmt = Parser::new(sql).parse().unwrap().remove(0);
+            let StatementKind::Query(query) = stmt.kind else {
+                panic!("Expected a query statement");
+            };
+            let options = AnalyzerOptions::new().with_disjoint_natural_join(policy);
+            let mut analyzer = Analyzer::with_catalog_and_options(setup_using_catalog(), options);
+            let result = analyzer.analyze_query_result(&query).unwrap();
+            assert_eq!(result.columns.len(), 3);
+            assert_eq!(analyzer.warnings().len(), warnings, "{:?}", policy);
+            if let [warning] = analyzer.warnings() {
+                assert!(matches!(
+                    warning.kind,
+                    AnalyzerWarningKind::NoNaturalJoinColumns
+                ));
+            }
+        }
+
+        // Only NATURAL joins need common columns
+        for sql in [
+            "SELECT * FROM l CROSS JOIN c",
+            "SELECT * FROM l JOIN c ON TRUE",
+            "SELECT * FROM l, c",
+            "SELECT * FROM l NATURAL JOIN r",
+        ] {
+            analyze_with_options(sql, AnalyzerOptions::new())
+                .unwrap_or_else(|e| panic!("{}: {}", sql, e));
+        }
+
+        let err = analyze_with_options(
+            "SELECT * FROM (SELECT 'x' AS id) s NATURAL JOIN r",
+            AnalyzerOptions::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("join column 'id'"), "{}", err);
+    }
+
+    #[test]
+    fn test_wildcard_follows_from_order() {
+        let cases = [
+            ("l, r, c", vec!["id", "a", "b", "id", "k"]),
+            ("c, r, l", vec!["k", "b", "id", "id", "a"]),
+            ("c, (r JOIN l ON TRUE)", vec!["k", "b", "id", "id", "a"]),
+            (
+                "(r JOIN l ON TRUE) JOIN c ON TRUE",
+                vec!["b", "id", "id", "a", "k"],
+            ),
+            (
+                "l JOIN (c JOIN r ON TRUE) ON TRUE",
+                vec!["id", "a", "k", "b", "id"],
+            ),
+            ("r, l JOIN c ON TRUE", vec!["b", "id", "id", "a", "k"]),
+            (
+                "(l JOIN r USING (id)) JOIN c ON TRUE",
+                vec!["id", "a", "b", "k"],
+            ),
+            (
+                "c JOIN (r NATURAL JOIN l) ON TRUE",
+                vec!["k", "id", "b", "a"],
+            ),
+        ];
+        for (from, expected) in cases {
+            let sql = format!("SELECT * FROM {}", from);
+            let result = analyze_with_options(&sql, AnalyzerOptions::new()).unwrap();
+            let names: Vec<_> = result.columns.iter().map(|c| c.name.as_str()).collect();
+            assert_eq!(names, expected, "{}", from);
+        }
+
+        // Scope tables are visited in the same order.
+        let sql = "SELECT id FROM c, (r JOIN l ON TRUE)";
+        let err = analyze_with_options(sql, AnalyzerOptions::new()).unwrap_err();
+        assert!(
+            err.to_string().contains("could be any of: r.id, l.id"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_cte_chain_analyzed_once() {
+        let mut sql = String::from("WITH c0 AS (SELECT id AS x FROM users)");
+        for i in 1..200 {
+            sql.push_str(&format!(", c{} AS (SELECT x + 1 AS x FROM c{})", i, i - 1));
+        }
+        sql.push_str(" SELECT x FROM c199");
+
+        // Counts the lookups of `users`, which only the body of `c0` reads.
+        struct CountingCatalog {
+            inner: MemoryCatalog,
+            users_lookups: AtomicUsize,
+        }
+        impl Catalog for CountingCatalog {
+            fn resolve_table(
+                &self,
+                name: &[String],
+            ) -> crate::error::Result<Option<Arc<TableSchema>>> {
+                if name.last().is_some_and(|n| n == "users") {
+                    self.users_lookups.fetch_add(1, Ordering::Relaxed);
+                }
+                self.inner.resolve_table(name)
+            }
+
+            fn resolve_function(
+                &self,
+                name: &[String],
+            ) -> crate::error::Result<Option<FunctionSignature>> {
+                self.inner.resolve_function(name)
+            }
+
+            fn resolve_function_overloads(
+                &self,
+                name: &[String],
+            ) -> crate::error::Result<Vec<FunctionSignature>> {
+                self.inner.resolve_function_overloads(name)
+            }
+
+            fn list_tables(&self, schema: Option<&str>) -> crate::error::Result<Vec<String>> {
+                self.inner.list_tables(schema)
+            }
+
+            fn list_schemas(&self) -> crate::error::Result<Vec<String>> {
+                self.inner.list_schemas()
+            }
+        }
+
+        let catalog = CountingCatalog {
+            inner: setup_test_catalog(),
+            users_lookups: AtomicUsize::new(0),
+        };
+        let stmt = Parser::new(&sql).parse_statement().unwrap();
+        let StatementKind::Query(query) = stmt.kind else {
+            panic!("expected a query");
+        };
+        let mut analyzer = Analyzer::with_catalog(catalog);
+        let result = analyzer.analyze_query_result(&query).unwrap();
+        // Each CTE body is analyzed once, however deep the chain of
+        // references to it.
+        assert_eq!(analyzer.catalog().users_lookups.load(Ordering::Relaxed), 1);
+
+        assert_eq!(result.columns.len(), 1);
+        assert_eq!(result.columns[0].name, "x");
+        assert_eq!(result.columns[0].data_type, SqlType::Int64);
+        assert!(!result.columns[0].nullable);
+    }
+
+    #[test]
+    fn test_group_by_ordinals_and_aliases() {
+        for sql in [
+            "SELECT name, age, COUNT(*) FROM users GROUP BY 1, 2",
+            "SELECT name AS n, COUNT(*) FROM users GROUP BY n",
+            "SELECT UPPER(name) AS n, COUNT(*) FROM users GROUP BY ROLLUP (n, 1)",
+            "SELECT age AS name, COUNT(*) FROM users GROUP BY name",
+        ] {
+            let result = parse_and_analyze(sql, setup_test_catalog());
+            assert!(result.is_ok(), "{}: {:?}", sql, result.err());
+        }
+
+        for (sql, position) in [
+            ("SELECT name, COUNT(*) FROM users GROUP BY 3", "3"),
+            ("SELECT name FROM users GROUP BY 0", "0"),
+            (
+                "SELECT name, COUNT(*) FROM users GROUP BY ROLLUP (1, 4)",
+                "4",
+            ),
+            (
+                "SELECT name, age FROM users GROUP BY GROUPING SETS ((1), (2, 7))",
+                "7",
+            ),
+        ] {
+            let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+            assert!(err.to_string().contains("GROUP BY position"), "{}", err);
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some(position));
+        }
+
+        let err =
+            parse_and_analyze("SELECT * FROM users GROUP BY 1", setup_test_catalog()).unwrap_err();
+        assert!(err.to_string().contains("* not allowed"), "{}", err);
+        let err = parse_and_analyze("SELECT name FROM users GROUP BY nope", setup_test_catalog())
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+    }
+
+    #[test]
+    fn test_order_by_ordinals() {
+        let result = parse_and_analyze(
+            "SELECT id, name, age FROM users ORDER BY 3 DESC",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        assert_eq!(result.sort_keys[0].target, SortTarget::Output(2));
+        assert_eq!(result.sort_keys[0].order, SortOrder::Desc);
+
+        let sql = "SELECT id, name FROM users ORDER BY 1, 3";
+        let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("ORDER BY position 3 is not in select list of 2 items"),
+            "{}",
+            err
+        );
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("3"));
+
+        let sql = "SELECT id FROM users ORDER BY 0";
+        let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+        assert!(err.to_string().contains("ORDER BY position 0"), "{}", err);
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("0"));
+    }
+
+    #[test]
+    fn test_distinct_order_by() {
+        for sql in [
+            "SELECT DISTINCT name, age FROM users ORDER BY age, 1",
+            "SELECT DISTINCT name AS n FROM users ORDER BY n",
+            "SELECT DISTINCT u.name FROM users u ORDER BY u.name",
+            "SELECT DISTINCT UPPER(name) FROM users ORDER BY UPPER(name) DESC",
+            "SELECT DISTINCT * FROM users ORDER BY age",
+        ] {
+            let result = parse_and_analyze(sql, setup_test_catalog());
+            assert!(result.is_ok(), "{}: {:?}", sql, result.err());
+        }
+
+        let sql = "SELECT DISTINCT name FROM users ORDER BY age";
+        let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+        assert!(
+            err.to_string().contains("'age' must appear in SELECT list"),
+            "{}",
+            err
+        );
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("age"));
+    }
+
+    #[test]
+    fn test_distinct_on() {
+        for sql in [
+            "SELECT DISTINCT ON (name) name, age FROM users ORDER BY name, age DESC",
+            "SELECT DISTINCT ON (name, age) id FROM users ORDER BY age, name, id",
+            "SELECT DISTINCT ON (name, age) id FROM users ORDER BY name",
+            "SELECT DISTINCT ON (name) name AS n, id FROM users ORDER BY 1, id",
+            "SELECT DISTINCT ON (UPPER(name)) id FROM users",
+        ] {
+            let result = parse_and_analyze(sql, setup_test_catalog());
+            assert!(result.is_ok(), "{}: {:?}", sql, result.err());
+        }
+
+        let result = parse_and_analyze(
+            "SELECT DISTINCT ON (name) name, age FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        assert_eq!(
+            column_summary(&result),
+            vec![
+                ("name".to_string(), SqlType::Varchar(None), true),
+                ("age".to_string(), SqlType::Int64, true),
+            ]
+        );
+
+        let sql = "SELECT DISTINCT ON (name) name, age FROM users ORDER BY age, name";
+        let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+        assert!(
+            err.to_string().contains("but ORDER BY has 'age'"),
+            "{}",
+            err
+        );
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("age"));
+
+        let err = parse_and_analyze(
+            "SELECT DISTINCT ON (nope) name FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+    }
+
+    #[test]
+    fn test_order_by_scope() {
+        let cases = [
+            ("SELECT id AS x FROM users ORDER BY x", Some(0)),
+            ("SELECT id AS age FROM users ORDER BY age", Some(0)),
+            ("SELECT id FROM users ORDER BY age", None),
+            ("SELECT u.id FROM users u ORDER BY u.age + 1", None),
+            (
+                "SELECT name FROM users GROUP BY name ORDER BY COUNT(*)",
+                None,
+            ),
+        ];
+        for (sql, output) in cases {
+            let result = parse_and_analyze(sql, setup_test_catalog()).unwrap();
+            match (&result.sort_keys[0].target, output) {
+                (SortTarget::Output(index), Some(expected)) => assert_eq!(*index, expected),
+                (SortTarget::Expr(_), None) => {}
+                (target, _) => panic!("{}: unexpected sort target {:?}", sql, target),
+            }
+        }
+
+        for sql in [
+            "SELECT id FROM users ORDER BY some_garbage",
+            "SELECT id FROM users UNION ALL SELECT id FROM users ORDER BY age",
+            "SELECT id FROM users LIMIT age",
+            // The scope ORDER BY reads closes with its query
+            "WITH a AS (SELECT id FROM users ORDER BY age) SELECT age FROM a",
+            "SELECT id FROM (SELECT id FROM users ORDER BY age) s ORDER BY age",
+        ] {
+            let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+            assert!(err.to_string().contains("not found"), "{}: {}", sql, err);
+        }
+
+        let sql = "SELECT id AS x, name AS x FROM users ORDER BY x";
+        let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+        assert!(err.to_string().contains("ambiguous column 'x'"), "{}", err);
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("x"));
+    }
+
+    #[test]
+    fn test_duplicate_aliases_and_order_by_priority() {
+        let analyze = |sql: &str| parse_and_analyze(sql, setup_test_catalog());
+
+        let sql = "SELECT * FROM users a JOIN orders a ON a.id = a.user_id";
+        let err = analyze(sql).unwrap_err();
+        assert!(err.to_string().contains("'a'"), "{}", err);
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("a"));
+        assert_eq!(err.span().unwrap().start, 34);
+        assert!(analyze("SELECT 1 FROM users JOIN users ON TRUE").is_err());
+        assert!(analyze("SELECT 1 FROM users, users u").is_ok());
+        assert!(analyze("SELECT 1 FROM (SELECT 1 AS x), (SELECT 2 AS y)").is_ok());
+
+        // ORDER BY prefers an output alias to a table column of that name.
+        let result = analyze("SELECT age AS name FROM users ORDER BY name").unwrap();
+        assert!(matches!(result.sort_keys[0].target, SortTarget::Output(0)));
+        assert_eq!(result.sort_keys[0].data_type, SqlType::Int64);
+        let result = analyze("SELECT age AS name FROM users ORDER BY users.name").unwrap();
+        assert_eq!(result.sort_keys[0].data_type, SqlType::Varchar(None));
+
+        // A name shared by different output columns is ambiguous; the same
+        // column selected twice is not.
+        let err =
+            analyze("SELECT u.id, o.id FROM users u JOIN orders o ON u.id = o.user_id ORDER BY id")
+                .unwrap_err();
+        assert!(
+            err.to_string().contains("could be any of: u.id, o.id"),
+            "{}",
+            err
+        );
+        assert!(analyze("SELECT id, * FROM users ORDER BY id").is_ok());
+
+        // Table columns are ambiguous across tables, listed with qualifiers.
+        let sql = "SELECT id FROM users u JOIN orders o ON TRUE";
+        let err = analyze(sql).unwrap_err();
+        assert!(
+            err.to_string().contains("could be any of: u.id, o.id"),
+            "{}",
+            err
+        );
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("id"));
+        let sql = "SELECT 1 FROM users x, (SELECT 1 AS y) x";
+        let err = analyze(sql).unwrap_err();
+        assert!(err.to_string().contains("'x'"), "{}", err);
+        assert_eq!(err.span().unwrap().start, sql.len() - 1);
+    }
+
+    #[test]
+    fn test_cte_limit() {
+        let sql = "WITH a AS (SELECT 1 AS x), b AS (SELECT x FROM a), c AS (SELECT x FROM b) \
+                   SELECT * FROM c";
+        let stmt = Parser::new(sql).parse().unwrap().remove(0);
+        let StatementKind::Query(query) = stmt.kind else {
+            panic!("Expected a query statement");
+        };
+
+        let options = AnalyzerOptions::new().with_max_ctes(2);
+        let mut analyzer = Analyzer::with_catalog_and_options(setup_test_catalog(), options);
+        let err = analyzer.analyze_query_result(&query).unwrap_err();
+        assert!(err.to_string().contains("more than 2 CTEs"));
+
+        let mut analyzer = Analyzer::with_catalog(setup_test_catalog());
+        assert!(analyzer.analyze_query_result(&query).is_ok());
+    }
+
+    #[test]
+    fn test_identifier_matching_policy() {
+        let mut catalog = setup_test_catalog();
+        catalog.add_table(
+            TableSchemaBuilder::new("caf\u{e9}")
+                .column(ColumnSchema::new("\u{e9}t\u{e9}", SqlType::Int64))
+                .build(),
+        );
+        catalog.set_identifier_matching(IdentifierMatching::UnicodeCaseFold);
+        // The lexer only accepts ASCII in unquoted identifiers, so quote the
+        // NFD spellings.
+        let sql = "SELECT c.`e\u{301}te\u{301}` FROM `cafe\u{301}` AS c";
+        let stmt = Parser::new(sql).parse().unwrap().remove(0);
+        let StatementKind::Query(query) = stmt.kind else {
+            panic!("Expected a query statement");
+        };
+
+        // ASCII folding does not unify the normalization forms in scope
+        let mut analyzer = Analyzer::with_catalog(catalog.clone());
+        assert!(analyzer.analyze_query_result(&query).is_err());
+
+        let options =
+            AnalyzerOptions::new().with_identifier_matching(IdentifierMatching::UnicodeCaseFold);
+        let mut analyzer = Analyzer::with_catalog_and_options(catalog, options);
+        let result = analyzer.analyze_query_result(&query).unwrap();
+        assert_eq!(result.columns[0].data_type, SqlType::Int64);
+    }
+
+    #[test]
+    fn test_insert_checks() {
+        let mut catalog = setup_test_catalog();
+        catalog.add_table(
+            TableSchemaBuilder::new("events")
+                .column(ColumnSchema::new("id", SqlType::Int64).not_null())
+                .column(
+                    ColumnSchema::new("kind", SqlType::Varchar(None))
+                        .not_null()
+                        .with_default("'x'"),
+                )
+                .column(ColumnSchema::new("seen_at", SqlType::Timestamp))
+                .build(),
+        );
+        let analyze = |sql: &str| {
+            let stmt = Parser::new(sql).parse()?.into_iter().next().unwrap();
+            Analyzer::with_catalog(catalog.clone()).analyze(&stmt)
+        };
+
+        for sql in [
+            "INSERT INTO users VALUES (1, 'a', 30, 'a@x'), (2, NULL, NULL, NULL)",
+            "INSERT INTO events (id) VALUES (1)",
+            "INSERT INTO events (seen_at, id) SELECT created_at, user_id FROM orders",
+            "INSERT INTO events SELECT * FROM events",
+        ] {
+            let result = analyze(sql);
+            assert!(result.is_ok(), "{}: {:?}", sql, result.err());
+        }
+
+        let cases = [
+            (
+                "INSERT INTO users (id, name) VALUES (1)",
+                "INSERT has 1 values but 2 target columns",
+                "1",
+            ),
+            (
+                "INSERT INTO users VALUES ('abc', 2, 3, 4, 5)",
+                "INSERT has 5 values but 4 target columns",
+                "5",
+            ),
+            (
+                "INSERT INTO users (id, name) VALUES (1, 'a'), ('b', 'c')",
+                "cannot assign VARCHAR to column 'id' of type BIGINT",
+                "'b'",
+            ),
+            (
+                "INSERT INTO users (id, name) VALUES (NULL, 'a')",
+                "cannot assign NULL to NOT NULL column 'id'",
+                "NULL",
+            ),
+            (
+                "INSERT INTO users (name) VALUES ('a')",
+                "INSERT must supply NOT NULL column 'id'",
+                "users",
+            ),
+            (
+                "INSERT INTO events DEFAULT VALUES",
+                "NOT NULL column 'id'",
+                "events",
+            ),
+            (
+                "INSERT INTO events (id, seen_at) SELECT id, name FROM users",
+                "cannot assign VARCHAR to column 'seen_at' of type TIMESTAMP",
+                "name",
+            ),
+            (
+                "INSERT INTO events (id) SELECT id, name FROM users",
+                "INSERT has 2 values but 1 target columns",
+                "name",
+            ),
+            (
+                "INSERT INTO events (id, nope) VALUES (1, 2)",
+                "nope",
+                "nope",
+            ),
+            (
+                "INSERT INTO events (id, kind) SELECT id FROM users",
+                "INSERT has 1 values but 2 target columns",
+                "id",
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let err = analyze(sql).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some(snippet));
+        }
+    }
+
+    fn analyze_update_sql(sql: &str, options: AnalyzerOptions) -> Result<AnalyzedUpdate> {
+        let mut catalog = setup_test_catalog();
+        catalog.add_table(
+            TableSchemaBuilder::new("people")
+                .column(ColumnSchema::new("id", SqlType::Int32).not_null())
+                .column(ColumnSchema::new(
+                    "address",
+                    SqlType::Struct(vec![crate::types::StructField {
+                        name: Some("zip".to_string()),
+                        data_type: SqlType::Int32,
+                    }]),
+                ))
+                .build(),
+        );
+        let stmt = Parser::new(sql).parse()?.into_iter().next().unwrap();
+        let StatementKind::Update(update) = stmt.kind else {
+            panic!("Expected an UPDATE statement");
+        };
+        Analyzer::with_catalog_and_options(catalog, options).analyze_update_result(&update)
+    }
+
+    #[test]
+    fn test_update_from() {
+        let ok = analyze_update_sql(
+            "UPDATE users SET age = o.user_id, name = p.name FROM orders o \
+             JOIN users p ON p.id = o.user_id WHERE o.user_id = users.id",
+            AnalyzerOptions::default(),
+        );
+        assert!(ok.is_ok(), "{:?}", ok.err());
+
+        let err = analyze_update_sql(
+            "UPDATE users SET age = o.created_at FROM orders o WHERE o.user_id = users.id",
+            AnalyzerOptions::default(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("cannot assign TIMESTAMP to column 'age' of type BIGINT"));
+
+        let err = analyze_update_sql(
+            "UPDATE users SET age = o.user_id WHERE id = 1",
+            AnalyzerOptions::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("table 'o' not found"), "{}", err);
+
+        for (sql, message) in [
+            (
+                "UPDATE users SET age = 1 FROM nosuch n WHERE n.id = users.id",
+                "table 'nosuch' not found",
+            ),
+            ("UPDATE users SET age = 1 FROM orders users", "duplicate"),
+        ] {
+            let err = analyze_update_sql(sql, AnalyzerOptions::default()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+        }
+    }
+
+    #[test]
+    fn test_update_assignment_types() {
+        let ok = analyze_update_sql(
+            "UPDATE users SET age = age + 1, name = 'x' WHERE id = 1",
+            AnalyzerOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(ok.table, "users");
+        assert!(ok.coercions.is_empty());
+
+        let sql = "UPDATE users SET name = 42";
+        let err = analyze_update_sql(sql, AnalyzerOptions::default()).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("'name'"), "{}", msg);
+        assert!(msg.contains("VARCHAR") && msg.contains("BIGINT"), "{}", msg);
+        let start = sql.find("42").unwrap();
+        assert_eq!(err.span(), Some(Span::new(start, start + 2)));
+
+        // Integer literals adopt the column's numeric type.
+        let narrowed =
+            analyze_update_sql("UPDATE people SET id = 7", AnalyzerOptions::default()).unwrap();
+        assert_eq!(
+            narrowed.coercions,
+            vec![AssignmentCoercion {
+                column: "id".to_string(),
+                from: SqlType::Int64,
+                to: SqlType::Int32,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_update_assignment_null() {
+        assert!(
+            analyze_update_sql("UPDATE users SET name = NULL", AnalyzerOptions::default()).is_ok()
+        );
+        let err = analyze_update_sql("UPDATE users SET id = NULL", AnalyzerOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("NOT NULL column 'id'"));
+    }
+
+    #[test]
+    fn test_update_assignment_struct_path() {
+        let ok = analyze_update_sql(
+            "UPDATE people p SET p.address.zip = 12345",
+            AnalyzerOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(ok.coercions[0].column, "address.zip");
+
+        let err = analyze_update_sql(
+            "UPDATE people SET address.zip = 'abc'",
+            AnalyzerOptions::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("'address.zip'"));
+
+        let err = analyze_update_sql(
+            "UPDATE people SET address.street = 'x'",
+            AnalyzerOptions::default(),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("field 'street' not found in 'address' (fields: zip)"),
+            "{}",
+            err
+        );
+        assert!(
+            analyze_update_sql("UPDATE people SET id.x = 1", AnalyzerOptions::default()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_struct_field_access() {
+        let catalog = crate::catalog::CatalogBuilder::new()
+            .with_builtins()
+            .add_table("people", |t| {
+                t.column("id", SqlType::Int64).struct_column(
+                    "address",
+                    [
+                        ("city", SqlType::Varchar(None)),
+                        (
+                            "geo",
+                            SqlType::struct_of([
+                                ("lat", SqlType::Float64),
+                                ("lng", SqlType::Float64),
+                            ]),
+                        ),
+                    ],
+                )
+            })
+            .build();
+        let analyze = |sql: &str| parse_and_analyze(sql, catalog.clone());
+
+        let result = analyze(
+            "SELECT address.city, p.address.geo.lat, (address).geo, \
+             STRUCT(1 AS n, 'x' AS s).s FROM people p",
+        )
+        .unwrap();
+        let types: Vec<String> = result
+            .columns
+            .iter()
+            .map(|c| c.data_type.to_string())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "VARCHAR",
+                "DOUBLE PRECISION",
+                "STRUCT<lat DOUBLE PRECISION, lng DOUBLE PRECISION>",
+                "VARCHAR"
+            ]
+        );
+        let result = analyze("SELECT STRUCT(id AS key, address.city AS city) FROM people").unwrap();
+        assert_eq!(
+            result.columns[0].data_type.to_string(),
+            "STRUCT<key BIGINT, city VARCHAR>"
+        );
+
+        for (sql, message, snippet) in [
+            (
+                "SELECT address.cty FROM people",
+                "field 'cty' not found in 'address' (fields: city, geo); did you mean 'city'?",
+                "cty",
+            ),
+            (
+                "SELECT people.address.geo.alt FROM people",
+                "field 'alt' not found in 'address.geo' (fields: lat, lng)",
+                "alt",
+            ),
+            (
+                "SELECT address.city.x FROM people",
+                "'address.city' is not a struct",
+                "x",
+            ),
+            (
+                "SELECT (address).zip FROM people",
+                "field 'zip' not found",
+                "zip",
+            ),
+            ("SELECT (id).x FROM people", "is not a struct", "x"),
+            (
+                "SELECT STRUCT(1 AS n).m FROM people",
+                "field 'm' not found",
+                "m",
+            ),
+        ] {
+            let err = analyze(sql).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some(snippet));
+        }
+
+        // Structs compare field by field.
+        assert!(analyze("SELECT * FROM people WHERE address.geo = STRUCT(1.5, 2)").is_ok());
+        assert!(analyze("SELECT * FROM people WHERE address.geo = STRUCT(1.5, 'x')").is_err());
+        assert!(analyze("SELECT * FROM people WHERE address.geo = STRUCT(1.5)").is_err());
+    }
+
+    #[test]
+    fn test_correlated_subqueries() {
+        let analyze = |sql: &str| parse_and_analyze(sql, setup_test_catalog());
+
+        let result = analyze(
+            "SELECT name FROM users u WHERE EXISTS (SELECT 1 FROM orders o WHERE o.user_id = u.id)",
+        );
+        assert!(result.is_ok(), "{:?}", result.err());
+
+        // A scalar subquery has the type of its column and is nullable.
+        let result = analyze(
+            "SELECT (SELECT MAX(amount) FROM orders WHERE user_id = users.id) AS top FROM users",
+        )
+        .unwrap();
+        assert_eq!(result.columns[0].data_type, SqlType::Float64);
+        assert!(result.columns[0].nullable);
+
+        // Inner tables shadow outer ones, and references reach any level.
+        assert!(analyze(
+            "SELECT id FROM users WHERE age IN (SELECT id FROM orders \
+             WHERE EXISTS (SELECT 1 FROM orders o2 WHERE o2.amount > users.age))"
+        )
+        .is_ok());
+        let err = analyze("SELECT (SELECT o.missing FROM orders o) FROM users").unwrap_err();
+        assert!(err.to_string().contains("missing"), "{}", err);
+
+        let err = analyze("SELECT (SELECT id, user_id FROM orders) FROM users").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("subquery must return exactly one column, but returns 2"),
+            "{}",
+            err
+        );
+        assert!(analyze("SELECT id FROM users WHERE id IN (SELECT * FROM orders)").is_err());
+        let err = analyze("SELECT id FROM users WHERE id IN (SELECT created_at FROM orders)")
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot compare"), "{}", err);
+        for (sql, message) in [
+            (
+                "SELECT id FROM users WHERE id = ANY (SELECT id, user_id FROM orders)",
+                "subquery must return exactly one column, but returns 2",
+            ),
+            (
+                "SELECT id FROM users WHERE (id, age) IN (SELECT id FROM orders)",
+                "subquery must return exactly 2 columns, but returns 1",
+            ),
+            (
+                "SELECT id FROM users WHERE (id, name) IN (SELECT id, created_at FROM orders)",
+                "cannot compare",
+            ),
+            (
+                "SELECT id FROM users u WHERE EXISTS (SELECT 1 FROM orders WHERE u.nope = 1)",
+                "nope",
+            ),
+        ] {
+            let err = analyze(sql).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+        }
+
+        assert!(analyze(
+            "SELECT id FROM users WHERE (id, age) IN (SELECT user_id, amount FROM orders)"
+        )
+        .is_ok());
+
+        // The checker marks subqueries that reference an enclosing query.
+        let mut analyzer = Analyzer::with_catalog(setup_test_catalog());
+        analyzer.push_scope();
+        let column = ScopeColumn::new("id".into(), SqlType::Int64, false, "u".into(), 0);
+        let table = ScopeTable::new("u".into(), vec!["users".into()], vec![column]);
+        analyzer.current_scope_mut().add_table(table).unwrap();
+        let correlated = |sql: &str| {
+            let expr = Parser::new(sql).parse_expression().unwrap();
+            analyzer.analyze_expr(&expr).unwrap().correlated
+        };
+        assert!(correlated(
+            "1 + (SELECT MAX(id) FROM orders WHERE user_id = u.id)"
+        ));
+        // `id` is the inner table's own column.
+        assert!(!correlated(
+            "NOT EXISTS (SELECT 1 FROM orders WHERE user_id = id)"
+        ));
+        assert!(correlated(
+            "u.id IN (SELECT user_id FROM orders WHERE id > u.id)"
+        ));
+        assert!(!correlated("u.id IN (SELECT user_id FROM orders)"));
+    }
+
+    #[test]
+    fn test_lateral_from_items() {
+        let analyze = |sql: &str| parse_and_analyze(sql, setup_test_catalog());
+
+        let result = analyze(
+            "SELECT * FROM users u, LATERAL (SELECT * FROM orders o WHERE o.user_id = u.id) x",
+        )
+        .unwrap();
+        assert_eq!(result.columns.len(), 8);
+        assert!(analyze(
+            "SELECT u.name, x.amount FROM users u \
+             LEFT JOIN LATERAL (SELECT amount FROM orders WHERE user_id = u.id LIMIT 1) x ON TRUE"
+        )
+        .is_ok());
+
+        // Without LATERAL, a derived table cannot see the FROM items before it.
+        let err =
+            analyze("SELECT * FROM users u, (SELECT * FROM orders o WHERE o.user_id = u.id) x")
+                .unwrap_err();
+        assert!(err.to_string().contains("table 'u' not found"), "{}", err);
+        // A lateral item sees only the items before it.
+        let err = analyze("SELECT * FROM LATERAL (SELECT u.id) x, users u").unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+
+        // UNNEST is implicitly lateral, and its alias names the element column.
+        let catalog = crate::catalog::CatalogBuilder::new()
+            .with_builtins()
+            .add_table("posts", |t| {
+                t.column("id", SqlType::Int64)
+                    .column("tags", SqlType::Array(Box::new(SqlType::Varchar(None))))
+            })
+            .build();
+        let result = parse_and_analyze(
+            "SELECT p.id, tag FROM posts p, UNNEST(p.tags) AS tag",
+            catalog,
+        )
+        .unwrap();
+        assert_eq!(result.columns[1].name, "tag");
+        assert_eq!(result.columns[1].data_type, SqlType::Varchar(None));
+    }
+
+    #[test]
+    fn test_explain() {
+        let explain = |sql: &str| {
+            let stmt = Parser::new(sql).parse_statement().unwrap();
+            Analyzer::with_catalog(setup_test_catalog())
+                .explain(&stmt)
+                .unwrap()
+        };
+
+        let explained = explain(
+            "EXPLAIN WITH recent AS (SELECT user_id, amount FROM orders) \
+             SELECT u.name, r.amount, \
+                    (SELECT MAX(o.id) FROM orders o WHERE o.user_id = u.id) AS last_order, \
+                    ROW_NUMBER() OVER (ORDER BY u.id) AS rn \
+             FROM users u JOIN recent r ON r.user_id = u.id, (SELECT COUNT(*) AS n FROM orders) s \
+             WHERE u.age > ?",
+        );
+        let query = explained.query();
+        assert_eq!(query.ctes.len(), 1);
+        assert_eq!(query.ctes[0].name, "recent");
+        assert!(matches!(
+            &query.ctes[0].query.tables[0].source,
+            ExplainSource::Table(name) if name == "orders"
+        ));
+
+        let aliases: Vec<&str> = query.tables.iter().map(|t| t.alias.as_str()).collect();
+        assert_eq!(aliases, ["u", "r", "s"]);
+        assert!(matches!(&query.tables[0].source, ExplainSource::Table(name) if name == "users"));
+        assert_eq!(query.tables[0].columns.len(), 4);
+        assert!(matches!(&query.tables[1].source, ExplainSource::Cte(name) if name == "recent"));
+        let ExplainSource::Subquery(derived) = &query.tables[2].source else {
+            panic!("expected a subquery, got {:?}", query.tables[2].source);
+        };
+        assert!(derived.columns[0].contains_aggregate);
+
+        let columns: Vec<&str> = query.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(columns, ["name", "amount", "last_order", "rn"]);
+        let origin = query.columns[0].source.as_ref().unwrap();
+        assert_eq!(
+            (origin.table.as_str(), origin.column.as_str()),
+            ("u", "name")
+        );
+        assert!(!query.columns[2].contains_aggregate);
+        assert!(query.columns[3].contains_window);
+
+        assert_eq!(query.subqueries.len(), 1);
+        assert_eq!(query.subqueries[0].tables[0].alias, "o");
+        assert!(query.subqueries[0].columns[0].contains_aggregate);
+        assert_eq!(explained.parameters().len(), 1);
+        assert_eq!(explained.parameters()[0].data_type, SqlType::Int64);
+
+        let explained = explain("SELECT COUNT(*) AS n, name FROM users GROUP BY name");
+        assert_eq!(
+            explained.to_string(),
+            "query\n\
+             \x20 from users: table users\n\
+             \x20   id BIGINT NOT NULL\n\
+             \x20   name VARCHAR\n\
+             \x20   age BIGINT\n\
+             \x20   email VARCHAR\n\
+             \x20 column n BIGINT NOT NULL, aggregate\n\
+             \x20 column name VARCHAR (users.name)\n"
+        );
+
+        // Other statements have the tables and subqueries they read.
+        let explained =
+            explain("UPDATE users SET age = ? WHERE id IN (SELECT user_id FROM orders)");
+        let query = explained.query();
+        assert_eq!(query.tables[0].alias, "users");
+        assert!(query.columns.is_empty());
+        assert_eq!(query.subqueries.len(), 1);
+        assert!(explained.to_string().ends_with("parameter ?1: BIGINT\n"));
+
+        // A statement that fails analysis fails to explain, and leaves the
+        // analyzer ready for the next one.
+        let mut analyzer = Analyzer::with_catalog(setup_test_catalog());
+        let stmt = Parser::new("EXPLAIN SELECT nope FROM users")
+            .parse_statement()
+            .unwrap();
+        let err = analyzer.explain(&stmt).unwrap_err();
+        assert!(err.to_string().contains("nope"), "{}", err);
+        let stmt = Parser::new("SELECT id FROM users")
+            .parse_statement()
+            .unwrap();
+        let explained = analyzer.explain(&stmt).unwrap();
+        assert_eq!(explained.query().columns.len(), 1);
+        assert!(analyzer.explain.is_none());
+    }
+
+    #[test]
+    fn test_system_variables() {
+        use crate::session::Session;
+
+        let sql = "SELECT @@max_rows AS m, @@global.time_zone AS tz, @@nope AS x";
+        let stmt = Parser::new(sql).parse_statement().unwrap();
+        let StatementKind::Query(query) = &stmt.kind else {
+            panic!("expected a query");
+        };
+
+        // Without a session, every system variable has an unknown type.
+        let result = Analyzer::with_catalog(setup_test_catalog())
+            .analyze_query_result(query)
+            .unwrap();
+        assert!(result
+            .columns
+            .iter()
+            .all(|c| c.data_type == SqlType::Unknown && c.nullable));
+
+        let options = AnalyzerOptions::default().with_session(Session::new().permissive(true));
+        let result = Analyzer::with_catalog_and_options(setup_test_catalog(), options)
+            .analyze_query_result(query)
+            .unwrap();
+        let types: Vec<&SqlType> = result.columns.iter().map(|c| &c.data_type).collect();
+        assert_eq!(
+            types,
+            [&SqlType::Int64, &SqlType::Varchar(None), &SqlType::Unknown]
+        );
+
+        let options = AnalyzerOptions::default().with_session(Session::new());
+        let err = Analyzer::with_catalog_and_options(setup_test_catalog(), options)
+            .analyze_query_result(query)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("system variable '@@nope' not found"));
+        assert_eq!(err.span(), Some(Span::new(50, 56)));
+
+        let options = AnalyzerOptions::default().with_session(Session::new());
+        let result = Analyzer::with_catalog_and_options(setup_test_catalog(), options)
+            .analyze(&Parser::new("SELECT @@max_row").parse_statement().unwrap());
+        let err = result.unwrap_err();
+        assert!(
+            err.to_string().contains("did you mean 'max_rows'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_array_types() {
+        let catalog = crate::catalog::CatalogBuilder::new()
+            .with_builtins()
+            .add_table("t", |t| {
+                t.column("tags", SqlType::Array(Box::new(SqlType::Varchar(None))))
+                    .column("n", SqlType::Int64)
+                    .column("doc", SqlType::Json)
+            })
+            .build();
+        let analyze = |sql: &str| parse_and_analyze(sql, catalog.clone());
+
+        let result = analyze(
+            "SELECT tags[0], tags[SAFE_OFFSET(n)], [1, 2.5], [], [NULL, 1], [[1], []], \
+             ARRAY<INT32>[1, 2], doc[0] FROM t",
+        )
+        .unwrap();
+        let types: Vec<String> = result
+            .columns
+            .iter()
+            .map(|c| c.data_type.to_string())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "VARCHAR",
+                "VARCHAR",
+                "ARRAY<DOUBLE PRECISION>",
+                "ARRAY<UNKNOWN>",
+                "ARRAY<BIGINT>",
+                "ARRAY<ARRAY<BIGINT>>",
+                "ARRAY<INTEGER>",
+                "JSON"
+            ]
+        );
+        assert!(result.columns[1].nullable);
+        assert!(analyze("SELECT * FROM t WHERE 'a' IN UNNEST(tags)").is_ok());
+        assert!(analyze("SELECT * FROM t WHERE n NOT IN UNNEST([1, 2])").is_ok());
+
+        for (sql, message, snippet) in [
+            (
+                "SELECT n[0] FROM t",
+                "type mismatch in array subscript: expected ARRAY<ANY>, got BIGINT",
+                "n",
+            ),
+            (
+                "SELECT tags['a'] FROM t",
+                "type mismatch in array subscript: expected BIGINT, got VARCHAR",
+                "'a'",
+            ),
+            (
+                "SELECT [1, 'a']",
+                "type mismatch in array literal: expected BIGINT, got VARCHAR",
+                "'a'",
+            ),
+            (
+                "SELECT ARRAY<INT64>[1, 'a']",
+                "type mismatch in array literal: expected BIGINT, got VARCHAR",
+                "'a'",
+            ),
+            (
+                "SELECT * FROM t WHERE n IN UNNEST(tags)",
+                "cannot compare BIGINT with VARCHAR",
+                "n",
+            ),
+            (
+                "SELECT * FROM t WHERE n IN UNNEST(n)",
+                "type mismatch in UNNEST: expected ARRAY<ANY>, got BIGINT",
+                "n",
+            ),
+        ] {
+            let err = analyze(sql).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            let span = err.span().unwrap();
+            assert_eq!(&sql[span.start..span.end], snippet, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_update_lenient_string_coercion() {
+        let sql = "UPDATE users SET age = '30'";
+        assert!(analyze_update_sql(sql, AnalyzerOptions::default()).is_err());
+
+        let result = analyze_update_sql(
+            sql,
+            AnalyzerOptions::default().with_lenient_string_assignment(true),
+        )
+        .unwrap();
+        assert_eq!(
+            result.coercions,
+            vec![AssignmentCoercion {
+                column: "age".to_string(),
+                from: SqlType::Varchar(None),
+                to: SqlType::Int64,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_misplaced_aggregates() {
+        let cases = [
+            (
+                "SELECT * FROM users WHERE SUM(age) > 100",
+                "'SUM': aggregate functions are not allowed in WHERE",
+                "SUM(age)",
+            ),
+            (
+                "SELECT * FROM users u JOIN orders o ON COUNT(*) > 1",
+                "'COUNT': aggregate functions are not allowed in JOIN ON",
+                "COUNT(*)",
+            ),
+            (
+                "SELECT SUM(COUNT(*)) FROM users",
+                "'SUM': aggregate function calls cannot be nested",
+                "SUM(COUNT(*))",
+            ),
+            (
+                "SELECT MAX(ROW_NUMBER() OVER (ORDER BY id)) FROM users",
+                "'MAX': window functions are not allowed in aggregate arguments",
+                "MAX(ROW_NUMBER() OVER (ORDER BY id))",
+            ),
+            (
+                "SELECT * FROM users WHERE ABS(MAX(age)) > 1",
+                "'MAX': aggregate functions are not allowed in WHERE",
+                "MAX(age)",
+            ),
+            (
+                "UPDATE users SET age = 1 WHERE age > AVG(age)",
+                "'AVG': aggregate functions are not allowed in WHERE",
+                "AVG(age)",
+            ),
+            (
+                "DELETE FROM users WHERE COUNT(*) > 1",
+                "'COUNT': aggregate functions are not allowed in WHERE",
+                "COUNT(*)",
+            ),
+            (
+                "MERGE INTO users t USING orders s ON MIN(s.user_id) = t.id \
+                 WHEN MATCHED THEN DELETE",
+                "'MIN': aggregate functions are not allowed in MERGE ON",
+                "MIN(s.user_id)",
+            ),
+            (
+                "SELECT COUNT(*) FROM users GROUP BY SUM(age)",
+                "'SUM': aggregate functions are not allowed in GROUP BY",
+                "SUM(age)",
+            ),
+            (
+                "SELECT name, COUNT(*) AS n FROM users GROUP BY name, n",
+                "'COUNT': aggregate functions are not allowed in GROUP BY",
+                "COUNT(*)",
+            ),
+            (
+                "SELECT * FROM users WHERE ROW_NUMBER() OVER (ORDER BY id) = 1",
+                "'ROW_NUMBER': window functions are not allowed in WHERE",
+                "ROW_NUMBER() OVER (ORDER BY id)",
+            ),
+            (
+                "SELECT COUNT(*) FROM users GROUP BY RANK() OVER (ORDER BY age)",
+                "'RANK': window functions are not allowed in GROUP BY",
+                "RANK() OVER (ORDER BY age)",
+            ),
+            (
+                "SELECT name FROM users GROUP BY name \
+                 HAVING COUNT(*) OVER () > 1",
+                "'COUNT': window functions are not allowed in HAVING",
+                "COUNT(*) OVER ()",
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let stmt = Parser::new(sql).parse_statement().unwrap();
+            let err = Analyzer::with_catalog(setup_test_catalog())
+                .analyze(&stmt)
+                .unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some(snippet));
+        }
+
+        for sql in [
+            "SELECT name FROM users GROUP BY name HAVING SUM(age) > 100",
+            "SELECT name, SUM(SUM(age)) OVER () FROM users GROUP BY name",
+            "SELECT * FROM users WHERE age > (SELECT AVG(age) FROM users)",
+        ] {
+            let result = parse_and_analyze(sql, setup_test_catalog());
+            assert!(result.is_ok(), "{}: {:?}", sql, result.err());
+        }
+    }
+
+    #[test]
+    fn test_counts_are_not_null() {
+        let result = parse_and_analyze(
+            "SELECT COUNT(*), COUNT(age), COUNTIF(age > 1), SUM(age), \
+             COUNT(*) OVER () FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        let nullable: Vec<_> = result.columns.iter().map(|c| c.nullable).collect();
+        assert_eq!(nullable, [false, false, false, true, false]);
+    }
+
+    #[test]
+    fn test_function_overloads() {
+        let result = parse_and_analyze(
+            "SELECT ABS(age), ABS(amount), SUBSTR(name, 2), ROUND(1) FROM users, orders",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        let types: Vec<_> = result.columns.iter().map(|c| c.data_type.clone()).collect();
+        assert_eq!(
+            types,
+            [
+                SqlType::Int64,
+                SqlType::Float64,
+                SqlType::Varchar(None),
+                SqlType::Float64
+            ]
+        );
+
+        let cases = [
+            (
+                "SELECT SUBSTR(name) FROM users",
+                "function 'SUBSTR' expects 2-3 arguments, got 1",
+                "SUBSTR(name)",
+            ),
+            (
+                "SELECT ABS(age, 1) FROM users",
+                "function 'ABS' expects 1 arguments, got 2",
+                "ABS(age, 1)",
+            ),
+            (
+                "SELECT SUBSTR(name, 'x') FROM users",
+                "argument 2 of SUBSTR",
+                "'x'",
+            ),
+            ("SELECT ABS(name) FROM users", "argument 1 of ABS", "name"),
+            (
+                "SELECT CONCAT() FROM users",
+                "function 'CONCAT' expects at least 1 arguments, got 0",
+                "CONCAT()",
+            ),
+            (
+                "SELECT ROUND(name, 1) FROM users",
+                "argument 1 of ROUND",
+                "name",
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some(snippet));
+        }
+    }
+
+    #[test]
+    fn test_polymorphic_return_types() {
+        let result = parse_and_analyze(
+            "SELECT MAX(u.age), ARRAY_AGG(u.id), COALESCE(u.age, o.amount), \
+             ANY_VALUE(u.name) FROM users u JOIN orders o ON u.id = o.user_id",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        let types: Vec<_> = result.columns.iter().map(|c| c.data_type.clone()).collect();
+        assert_eq!(
+            types,
+            [
+                SqlType::Int64,
+                SqlType::Array(Box::new(SqlType::Int64)),
+                SqlType::Float64,
+                SqlType::Varchar(None)
+            ]
+        );
+
+        let result = parse_and_analyze(
+            "SELECT LAG(name) OVER (ORDER BY id), NULLIF(age, 0), COALESCE(NULL, name) FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        let types: Vec<_> = result.columns.iter().map(|c| c.data_type.clone()).collect();
+        assert_eq!(
+            types,
+            [
+                SqlType::Varchar(None),
+                SqlType::Int64,
+                SqlType::Varchar(None)
+            ]
+        );
+        assert_eq!(result.windows[0].data_type, SqlType::Varchar(None));
+
+        // Arguments without a common supertype keep the declared type.
+        let result = parse_and_analyze(
+            "SELECT COALESCE(age, name) FROM users",
+            setup_test_catalog(),
+        );
+        assert_eq!(result.unwrap().columns[0].data_type, SqlType::Any);
+    }
+
+    #[test]
+    fn test_cast_validity() {
+        let result = parse_and_analyze(
+            "SELECT CAST(id AS VARCHAR), SAFE_CAST(id AS VARCHAR), SAFE_CAST(name AS INT64), \
+             CAST(COUNT(*) AS FLOAT64) FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        let nullable: Vec<_> = result.columns.iter().map(|c| c.nullable).collect();
+        assert_eq!(nullable, [false, false, true, false]);
+
+        let cases = [
+            (
+                "SELECT CAST(ARRAY[1, 2] AS BOOLEAN) FROM users",
+                "cannot cast ARRAY<BIGINT> to BOOLEAN",
+                "CAST(ARRAY[1, 2] AS BOOLEAN)",
+            ),
+            (
+                "SELECT SAFE_CAST(created_at AS TIME) FROM orders WHERE CAST(id AS DATE) < DATE '2024-01-01'",
+                "cannot cast BIGINT to DATE",
+                "CAST(id AS DATE)",
+            ),
+            (
+                "SELECT * FROM users WHERE id = name",
+                "cannot compare BIGINT with VARCHAR",
+                "id = name",
+            ),
+            (
+                "SELECT * FROM users WHERE age IN (1, TRUE)",
+                "cannot compare BIGINT with BOOLEAN",
+                "TRUE",
+            ),
+            (
+                "SELECT SAFE_CAST(ARRAY[1] AS DATE) FROM users",
+                "cannot cast ARRAY<BIGINT> to DATE",
+                "SAFE_CAST(ARRAY[1] AS DATE)",
+            ),
+            (
+                "SELECT * FROM users WHERE (id, age) = (1, TRUE)",
+                "cannot compare",
+                "(id, age) = (1, TRUE)",
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some(snippet));
+        }
+
+        for sql in [
+            "SELECT * FROM orders WHERE created_at > '2024-01-01' AND amount > id",
+            "SELECT * FROM users WHERE (id, age) = (1, 2.5)",
+            "SELECT * FROM users WHERE NOT id = 1 AND NOT age > 2",
+        ] {
+            let result = parse_and_analyze(sql, setup_test_catalog());
+            assert!(result.is_ok(), "{}: {:?}", sql, result.err());
+        }
+    }
+
+    #[test]
+    fn test_recursive_cte() {
+        let result = parse_and_analyze(
+            "WITH RECURSIVE t AS (SELECT 1 AS n UNION ALL SELECT n + 1 FROM t WHERE n < 10) \
+             SELECT * FROM t",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        assert_eq!(result.columns.len(), 1);
+        assert_eq!(result.columns[0].name, "n");
+        assert_eq!(result.columns[0].data_type, SqlType::Int64);
+
+        // Recursive references through joins, and CTEs that do not recurse
+        for sql in [
+            "WITH RECURSIVE chain AS (SELECT id, age FROM users WHERE id = 1 \
+             UNION SELECT u.id, u.age FROM users u JOIN chain c ON u.age = c.id) \
+             SELECT id FROM chain",
+            "WITH RECURSIVE one AS (SELECT 1 AS x) SELECT x FROM one",
+        ] {
+            let result = parse_and_analyze(sql, setup_test_catalog());
+            assert!(result.is_ok(), "{}: {:?}", sql, result.err());
+        }
+
+        let cases = [
+            (
+                "WITH RECURSIVE t AS (SELECT n FROM t) SELECT * FROM t",
+                "'t' must be a UNION of a non-recursive term and a recursive term",
+                "t",
+            ),
+            (
+                "WITH RECURSIVE t AS (SELECT 1 AS n UNION ALL SELECT COUNT(*) FROM t) SELECT * FROM t",
+                "aggregate functions are not allowed in the recursive term of 't'",
+                "t",
+            ),
+            (
+                "WITH RECURSIVE t AS (SELECT 1 AS n UNION ALL \
+                 (SELECT id FROM users EXCEPT SELECT n FROM t)) SELECT * FROM t",
+                "'t' is referenced on the right side of EXCEPT",
+                "t",
+            ),
+            (
+                "WITH RECURSIVE t AS (SELECT 1 AS n UNION ALL SELECT 'x' FROM t) SELECT * FROM t",
+                "column 'n' of the recursive term of 't'",
+                "t",
+            ),
+            (
+                "WITH RECURSIVE t AS (SELECT 1 AS n UNION ALL SELECT n, n FROM t) SELECT * FROM t",
+                "set operations require the same number of columns (1 vs 2)",
+                "t",
+            ),
+            (
+                "WITH RECURSIVE t AS (SELECT n FROM t UNION ALL SELECT 1) SELECT * FROM t",
+                "'t' is referenced in its non-recursive term",
+                "t",
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some(snippet));
+        }
+    }
+
+    #[test]
+    fn test_column_lists() {
+        let catalog = setup_test_catalog();
+
+        let result = parse_and_analyze(
+            "WITH t (a, b) AS (SELECT id, name FROM users) SELECT a, t.b FROM t",
+            catalog.clone(),
+        )
+        .unwrap();
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert!(!result.columns[0].nullable);
+
+        let result = parse_and_analyze(
+            "SELECT c1, x.c2 FROM (SELECT 1, 'a') AS x(c1, c2)",
+            catalog.clone(),
+        )
+        .unwrap();
+        assert_eq!(result.columns[1].data_type, SqlType::Varchar(None));
+
+        // An alias list may rename only the leading columns.
+        let result =
+            parse_and_analyze("SELECT * FROM users AS u(uid, uname)", catalog.clone()).unwrap();
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["uid", "uname", "age", "email"]);
+
+        parse_and_analyze("SELECT n FROM UNNEST([1, 2]) AS v(n)", catalog.clone()).unwrap();
+        parse_and_analyze(
+            "WITH RECURSIVE t (n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM t WHERE n < 3) \
+             SELECT n FROM t",
+            catalog.clone(),
+        )
+        .unwrap();
+
+        let cases = [
+            (
+                "WITH t (a, b) AS (SELECT id, name FROM users) SELECT id FROM t",
+                "column 'id' not found",
+                None,
+            ),
+            (
+                "WITH t (a) AS (SELECT id, name FROM users) SELECT a FROM t",
+                "'t' has 2 columns but its column list names 1",
+                Some("t"),
+            ),
+            (
+                "WITH t (a, b, c) AS (SELECT id, name FROM users) SELECT a FROM t",
+                "'t' has 2 columns but its column list names 3",
+                Some("c"),
+            ),
+            (
+                "WITH t (a, A) AS (SELECT id, name FROM users) SELECT a FROM t",
+                "column 'A' is named more than once",
+                Some("A"),
+            ),
+            (
+                "SELECT 1 FROM (SELECT 1, 2) AS x(c1, c1)",
+                "column 'c1' is named more than once",
+                Some("c1"),
+            ),
+            (
+                "SELECT 1 FROM orders AS o(a, b, c, d, e)",
+                "'o' has 4 columns but its column list names 5",
+                Some("e"),
+            ),
+            (
+                "SELECT 1 FROM UNNEST([1]) AS v(a, b)",
+                "'v' has 1 columns but its column list names 2",
+                Some("b"),
+            ),
+            (
+                "SELECT 1 FROM (SELECT 1) AS x(a, b)",
+                "'x' has 1 columns but its column list names 2",
+                Some("b"),
+            ),
+            (
+                "SELECT u.id FROM users AS u(uid)",
+                "column 'id' not found",
+                None,
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let err = parse_and_analyze(sql, catalog.clone()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            if let Some(snippet) = snippet {
+                assert_eq!(
+                    err.span().map(|s| &sql[s.start..s.end]),
+                    Some(snippet),
+                    "{}",
+                    sql
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_operation_types() {
+        let catalog = setup_test_catalog();
+
+        let result = parse_and_analyze(
+            "SELECT id FROM users UNION SELECT amount FROM orders",
+            catalog.clone(),
+        )
+        .unwrap();
+        assert_eq!(result.columns[0].name, "id");
+        assert_eq!(result.columns[0].data_type, SqlType::Float64);
+        assert!(result.columns[0].nullable);
+
+        let result = parse_and_analyze(
+            "SELECT id FROM users INTERSECT SELECT id FROM orders",
+            catalog.clone(),
+        )
+        .unwrap();
+        assert_eq!(result.columns[0].data_type, SqlType::Int64);
+        assert!(!result.columns[0].nullable);
+
+        // Chains fold left to right, and NULL takes the other side's type.
+        let result = parse_and_analyze(
+            "SELECT id FROM users UNION SELECT NULL UNION ALL SELECT amount FROM orders",
+            catalog.clone(),
+        )
+        .unwrap();
+        assert_eq!(result.columns[0].data_type, SqlType::Float64);
+        assert!(result.columns[0].nullable);
+
+        let cases = [
+            (
+                "SELECT id FROM users UNION SELECT name FROM users",
+                "set operation column 1 has incompatible types BIGINT and VARCHAR",
+                Some("name"),
+            ),
+            (
+                "SELECT id, name FROM users EXCEPT SELECT user_id, created_at FROM orders",
+                "set operation column 2 has incompatible types VARCHAR and TIMESTAMP",
+                Some("created_at"),
+            ),
+            (
+                "SELECT id FROM users UNION SELECT amount FROM orders UNION SELECT name FROM users",
+                "column 1 has incompatible types DOUBLE PRECISION and VARCHAR",
+                Some("name"),
+            ),
+            (
+                "SELECT id FROM users UNION (SELECT name FROM users)",
+                "column 1 has incompatible types BIGINT and VARCHAR",
+                None,
+            ),
+            (
+                "SELECT name FROM users INTERSECT SELECT created_at FROM orders",
+                "set operation column 1 has incompatible types VARCHAR and TIMESTAMP",
+                Some("created_at"),
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let err = parse_and_analyze(sql, catalog.clone()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            if let Some(snippet) = snippet {
+                assert_eq!(
+                    err.span().map(|s| &sql[s.start..s.end]),
+                    Some(snippet),
+                    "{}",
+                    sql
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_window_validation() {
+        let catalog = setup_test_catalog();
+
+        // Refined windows inherit PARTITION BY and ORDER BY.
+        let result = parse_and_analyze(
+            "SELECT SUM(age) OVER w2, RANK() OVER (w1 ORDER BY age) FROM users \
+             WINDOW w1 AS (PARTITION BY name), \
+                    w2 AS (w1 ORDER BY id ROWS BETWEEN 2 PRECEDING AND 1 FOLLOWING)",
+            catalog.clone(),
+        )
+        .unwrap();
+        let [sum, rank] = result.windows.as_slice() else {
+            panic!("expected two windows, got {:?}", result.windows);
+        };
+        assert_eq!(sum.spec.partition_by.len(), 1);
+        assert_eq!(sum.spec.order_by.len(), 1);
+        assert!(sum.spec.base.is_none());
+        assert!(sum.spec.frame.is_some());
+        assert_eq!(rank.spec.partition_by.len(), 1);
+        assert_eq!(rank.sort_keys[0].data_type, SqlType::Int64);
+
+        for sql in [
+            "SELECT SUM(amount) OVER (ORDER BY amount RANGE BETWEEN 1.5 PRECEDING AND CURRENT ROW) \
+             FROM orders",
+            "SELECT COUNT(*) OVER (ORDER BY created_at RANGE INTERVAL 1 DAY PRECEDING) FROM orders",
+            "SELECT SUM(amount) OVER (ORDER BY id ROWS BETWEEN CURRENT ROW AND UNBOUNDED FOLLOWING) \
+             FROM orders",
+            "SELECT SUM(amount) OVER (ORDER BY id, user_id RANGE UNBOUNDED PRECEDING) FROM orders",
+        ] {
+            parse_and_analyze(sql, catalog.clone()).unwrap();
+        }
+
+        let cases = [
+            (
+                "SELECT SUM(age) OVER (w ORDER BY id) FROM users",
+                "window 'w' is not defined",
+                "w",
+            ),
+            (
+                "SELECT SUM(age) OVER w2 FROM users \
+                 WINDOW w1 AS (PARTITION BY name), w2 AS (w1 PARTITION BY age)",
+                "cannot override PARTITION BY of window 'w1'",
+                "age",
+            ),
+            (
+                "SELECT SUM(age) OVER (w ORDER BY age) FROM users WINDOW w AS (ORDER BY id)",
+                "cannot override ORDER BY of window 'w'",
+                "age",
+            ),
+            (
+                "SELECT SUM(age) OVER (w) FROM users WINDOW w AS (ORDER BY id ROWS CURRENT ROW)",
+                "cannot refine window 'w', which has a frame",
+                "w",
+            ),
+            (
+                "SELECT SUM(age) OVER (ORDER BY id ROWS BETWEEN 3 FOLLOWING AND 1 PRECEDING) \
+                 FROM users",
+                "frame starts after it ends",
+                "ROWS BETWEEN 3 FOLLOWING AND 1 PRECEDING",
+            ),
+            (
+                "SELECT SUM(age) OVER (ORDER BY id ROWS UNBOUNDED FOLLOWING) FROM users",
+                "frame cannot start at UNBOUNDED FOLLOWING",
+                "ROWS UNBOUNDED FOLLOWING",
+            ),
+            (
+                "SELECT SUM(age) OVER (ORDER BY id ROWS BETWEEN -1 PRECEDING AND CURRENT ROW) \
+                 FROM users",
+                "frame offsets must not be negative",
+                "-1",
+            ),
+            (
+                "SELECT SUM(age) OVER (ORDER BY id ROWS BETWEEN age PRECEDING AND CURRENT ROW) \
+                 FROM users",
+                "frame offsets must be constants",
+                "age",
+            ),
+            (
+                "SELECT SUM(age) OVER (ORDER BY id ROWS 1.5 PRECEDING) FROM users",
+                "ROWS frame offsets must be integers",
+                "1.5",
+            ),
+            (
+                "SELECT SUM(age) OVER (ORDER BY id, age RANGE 1 PRECEDING) FROM users",
+                "RANGE frame with an offset requires exactly one ORDER BY key",
+                "RANGE 1 PRECEDING",
+            ),
+            (
+                "SELECT SUM(age) OVER (ORDER BY name RANGE 1 PRECEDING) FROM users",
+                "requires a numeric or date/time ORDER BY key, not VARCHAR",
+                "name",
+            ),
+            (
+                "SELECT SUM(age) OVER w FROM users WINDOW w AS (RANGE 1 PRECEDING)",
+                "RANGE frame with an offset requires exactly one ORDER BY key",
+                "RANGE 1 PRECEDING",
+            ),
+            (
+                "SELECT ROW_NUMBER() OVER (PARTITION BY name) FROM users",
+                "ranking functions require ORDER BY in the window",
+                "(PARTITION BY name)",
+            ),
+            (
+                "SELECT RANK() OVER (ORDER BY id ROWS UNBOUNDED PRECEDING) FROM users",
+                "ranking functions do not accept a window frame",
+                "ROWS UNBOUNDED PRECEDING",
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let err = parse_and_analyze(sql, catalog.clone()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            assert_eq!(
+                err.span().map(|s| &sql[s.start..s.end]),
+                Some(snippet),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn test_constants_and_column_origins() {
+        let catalog = setup_test_catalog();
+
+        let result = parse_and_analyze(
+            "SELECT 1 + 1 AS two, name, u.id, (age), age + 1, * EXCEPT (email) FROM users AS u",
+            catalog.clone(),
+        )
+        .unwrap();
+        let two = &result.columns[0];
+        assert_eq!(two.data_type, SqlType::Int64);
+        assert_eq!(two.constant_value, Some(Value::Int64(2)));
+        assert!(two.source.is_none());
+        let origin = |table: &str, column: &str, column_index| {
+            Some(ColumnOrigin {
+                table: table.to_string(),
+                column: column.to_string(),
+                column_index,
+            })
+        };
+        assert_eq!(result.columns[1].source, origin("u", "name", 1));
+        assert_eq!(result.columns[2].source, origin("u", "id", 0));
+        assert_eq!(result.columns[3].source, origin("u", "age", 2));
+        assert_eq!(result.columns[4].source, None);
+        assert_eq!(result.columns[4].constant_value, None);
+        let wildcard: Vec<_> = result.columns[5..]
+            .iter()
+            .map(|c| c.source.clone())
+            .collect();
+        assert_eq!(
+            wildcard,
+            [
+                origin("u", "id", 0),
+                origin("u", "name", 1),
+                origin("u", "age", 2)
+            ]
+        );
+
+        let values = |sql: &str| -> Vec<(Option<Value>, bool)> {
+            parse_and_analyze(sql, catalog.clone())
+                .unwrap()
+                .columns
+                .into_iter()
+                .map(|c| (c.constant_value, c.nullable))
+                .collect()
+        };
+        assert_eq!(
+            values("SELECT 2 + 3 * 4, 'a' || 'b', -(1.5), NOT TRUE, 7 / 2"),
+            [
+                (Some(Value::Int64(14)), false),
+                (Some(Value::from("ab")), false),
+                (Some(Value::Float64(-1.5)), false),
+                (Some(Value::Boolean(false)), false),
+                (Some(Value::Int64(3)), false),
+            ]
+        );
+        // Three-valued logic decides nullability.
+        assert_eq!(
+            values("SELECT NULL AND FALSE, NULL AND TRUE, NULL OR TRUE, 1 + NULL, CAST(NULL AS BIGINT)"),
+            [
+                (Some(Value::Boolean(false)), false),
+                (Some(Value::Null), true),
+                (Some(Value::Boolean(true)), false),
+                (Some(Value::Null), true),
+                (Some(Value::Null), true),
+            ]
+        );
+        // Overflow and division by zero are left to runtime.
+        assert_eq!(
+            values("SELECT 9223372036854775807 + 1, 1 / 0, CAST(1 AS FLOAT64)"),
+            [(None, false), (None, false), (None, false)]
+        );
+
+        // A UNION column has no single origin or value.
+        let result =
+            parse_and_analyze("SELECT name FROM users UNION SELECT 'x'", catalog.clone()).unwrap();
+        assert_eq!(result.columns[0].source, None);
+        assert_eq!(result.columns[0].constant_value, None);
+
+        let checker_result = {
+            let checker = TypeChecker::new(&catalog);
+            checker.check_expr(
+                &Parser::new("1 + 2").parse_expression().unwrap(),
+                &Scope::new(),
+            )
+        }
+        .unwrap();
+        assert!(checker_result.is_constant);
+        assert_eq!(checker_result.constant_value, Some(Value::Int64(3)));
+    }
+
+    #[test]
+    fn test_pivot_and_unpivot() {
+        let catalog = setup_test_catalog();
+        let columns = |sql: &str| -> Vec<(String, SqlType, bool)> {
+            parse_and_analyze(sql, catalog.clone())
+                .unwrap_or_else(|e| panic!("{}: {}", sql, e))
+                .columns
+                .into_iter()
+                .map(|c| (c.name, c.data_type, c.nullable))
+                .collect()
+        };
+        let names = |sql: &str| -> Vec<String> {
+            columns(sql).into_iter().map(|(name, ..)| name).collect()
+        };
+
+        assert_eq!(
+            names("SELECT * FROM orders PIVOT(SUM(amount) FOR user_id IN (1, 2 AS two))"),
+            ["id", "created_at", "1", "two"]
+        );
+        assert_eq!(
+            columns(
+                "SELECT p.total_1, n_1 FROM orders \
+                 PIVOT(SUM(amount) AS total, COUNT(*) AS n FOR user_id IN (1)) AS p"
+            ),
+            [
+                ("total_1".to_string(), SqlType::Float64, true),
+                ("n_1".to_string(), SqlType::Int64, false),
+            ]
+        );
+        assert_eq!(
+            names("SELECT * FROM users PIVOT(MAX(age) FOR name IN ('a', 'b'))"),
+            ["id", "email", "a", "b"]
+        );
+
+        assert_eq!(
+            columns("SELECT * FROM users UNPIVOT(val FOR col IN (name, email))"),
+            [
+                ("id".to_string(), SqlType::Int64, false),
+                ("age".to_string(), SqlType::Int64, true),
+                ("val".to_string(), SqlType::Varchar(None), false),
+                ("col".to_string(), SqlType::Varchar(None), false),
+            ]
+        );
+        let result = columns(
+            "SELECT val, col FROM users UNPIVOT INCLUDE NULLS (val FOR col IN (name AS 1, email 2))",
+        );
+        assert_eq!(
+            result,
+            [
+                ("val".to_string(), SqlType::Varchar(None), true),
+                ("col".to_string(), SqlType::Int64, false),
+            ]
+        );
+        assert_eq!(
+            columns(
+                "SELECT * FROM orders \
+                 UNPIVOT((a, b) FOR k IN ((user_id, amount) AS 'x', (id, amount) AS 'y'))"
+            ),
+            [
+                ("created_at".to_string(), SqlType::Timestamp, true),
+                ("a".to_string(), SqlType::Int64, true),
+                ("b".to_string(), SqlType::Float64, true),
+                ("k".to_string(), SqlType::Varchar(None), false),
+            ]
+        );
+        assert_eq!(
+            names("SELECT x FROM users UNPIVOT(v FOR k IN (name, email)) AS u(x)"),
+            ["x"]
+        );
+
+        let cases = [
+            (
+                "SELECT amount FROM orders PIVOT(SUM(amount) FOR user_id IN (1))",
+                "column 'amount' not found",
+                None,
+            ),
+            (
+                "SELECT * FROM orders PIVOT(amount FOR user_id IN (1))",
+                "invalid PIVOT: expected an aggregate function call",
+                Some("amount"),
+            ),
+            (
+                "SELECT * FROM orders PIVOT(SUM(amount) FOR user_id IN (id))",
+                "invalid PIVOT: IN values must be constants",
+                Some("id"),
+            ),
+            (
+                "SELECT * FROM orders PIVOT(SUM(amount) FOR user_id IN (TRUE))",
+                "cannot compare",
+                Some("TRUE"),
+            ),
+            (
+                "SELECT * FROM orders PIVOT(SUM(amount), COUNT(*) FOR user_id IN (7))",
+                "column '7' is named more than once",
+                Some("7"),
+            ),
+            (
+                "SELECT * FROM orders PIVOT(SUM(amount) FOR SUM(user_id) IN (1))",
+                "PIVOT FOR",
+                None,
+            ),
+            (
+                "SELECT * FROM users UNPIVOT(v FOR k IN (name, nope))",
+                "column 'nope' not found",
+                Some("nope"),
+            ),
+            (
+                "SELECT * FROM users UNPIVOT(v FOR k IN (name, age))",
+                "invalid UNPIVOT: column 'age' has type",
+                Some("age"),
+            ),
+            (
+                "SELECT * FROM orders UNPIVOT((a, b) FOR k IN ((user_id, amount), (id)))",
+                "expected 2 columns in each IN group, found 1",
+                Some("id"),
+            ),
+            (
+                "SELECT * FROM users UNPIVOT(v FOR k IN (name AS 1, email))",
+                "labels must all be strings or all be integers",
+                Some("email"),
+            ),
+            (
+                "SELECT * FROM users UNPIVOT(id FOR k IN (name, email))",
+                "column 'id' is named more than once",
+                Some("id"),
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let err = parse_and_analyze(sql, catalog.clone()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            if let Some(snippet) = snippet {
+                assert_eq!(
+                    err.span().map(|s| &sql[s.start..s.end]),
+                    Some(snippet),
+                    "{}",
+                    sql
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_table_sample() {
+        let catalog = setup_test_catalog();
+        for sql in [
+            "SELECT * FROM users TABLESAMPLE SYSTEM (10 PERCENT)",
+            "SELECT * FROM users AS u TABLESAMPLE BERNOULLI (0.5) REPEATABLE (42)",
+            "SELECT * FROM users TABLESAMPLE BERNOULLI (100 PERCENT)",
+            "SELECT * FROM users TABLESAMPLE RESERVOIR (10 ROWS) REPEATABLE (?)",
+            "SELECT * FROM (SELECT id FROM users) AS d TABLESAMPLE SYSTEM (0 PERCENT)",
+        ] {
+            parse_and_analyze(sql, catalog.clone()).unwrap_or_else(|e| panic!("{}: {}", sql, e));
+        }
+
+        let cases = [
+            (
+                "SELECT * FROM users TABLESAMPLE SYSTEM (101 PERCENT)",
+                "invalid TABLESAMPLE: the percentage must be between 0 and 100",
+                "101",
+            ),
+            (
+                "SELECT * FROM users TABLESAMPLE SYSTEM (-1 PERCENT)",
+                "the percentage must be between 0 and 100",
+                "-1",
+            ),
+            (
+                "SELECT * FROM users TABLESAMPLE SYSTEM ('10' PERCENT)",
+                "the percentage must be a numeric literal",
+                "'10'",
+            ),
+            (
+                "SELECT * FROM users TABLESAMPLE SYSTEM (?)",
+                "the percentage must be a numeric literal",
+                "?",
+            ),
+            (
+                "SELECT * FROM users TABLESAMPLE BERNOULLI (5) REPEATABLE (1.5)",
+                "the seed must be an integer",
+                "1.5",
+            ),
+            (
+                "SELECT * FROM users TABLESAMPLE RESERVOIR (2.5 ROWS)",
+                "the row count must be an integer",
+                "2.5",
+            ),
+            (
+                "SELECT * FROM (SELECT 1) AS d TABLESAMPLE RESERVOIR (-3 ROWS)",
+                "the row count cannot be negative",
+                "-3",
+            ),
+        ];
+        for (sql, message, snippet) in cases {
+            let err = parse_and_analyze(sql, catalog.clone()).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", sql, err);
+            assert_eq!(
+                err.span().map(|s| &sql[s.start..s.end]),
+                Some(snippet),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn test_fetch_with_ties_requires_order_by() {
+        let catalog = setup_test_catalog();
+        parse_and_analyze(
+            "SELECT id FROM users ORDER BY age FETCH FIRST 3 ROWS WITH TIES",
+            catalog.clone(),
+        )
+        .unwrap();
+        parse_and_analyze("SELECT id FROM users LIMIT 5, 10", catalog.clone()).unwrap();
+
+        let sql = "SELECT id FROM users OFFSET 1 ROWS FETCH FIRST 3 ROWS WITH TIES";
+        let err = parse_and_analyze(sql, catalog.clone()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("FETCH WITH TIES requires ORDER BY"));
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("3"));
+
+        let err = parse_and_analyze("SELECT id FROM users LIMIT 'a', 10", catalog).unwrap_err();
+        assert!(err.to_string().contains("LIMIT/OFFSET"), "{}", err);
+    }
+
+    #[test]
+    fn test_table_names_match_by_quoting() {
+        let mut catalog = MemoryCatalog::new();
+        catalog.add_table(TableSchema::new(
+            "Users",
+            vec![ColumnSchema::new("Id", SqlType::Int64)],
+        ));
+
+        for sql in [
+            "SELECT id FROM users",
+            "SELECT USERS.ID FROM USERS",
+            "SELECT `Id` FROM default.`Users`",
+        ] {
+            assert!(parse_and_analyze(sql, catalog.clone()).is_ok(), "{}", sql);
+        }
+        for sql in ["SELECT * FROM `users`", "SELECT * FROM `default`.`USERS`"] {
+            assert!(parse_and_analyze(sql, catalog.clone()).is_err(), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_unknown_named_types() {
+        let catalog = setup_test_catalog();
+        let result = parse_and_analyze(
+            "SELECT CAST(NULL AS STRUCT<INT64, x NUMERIC(10, 2)>)",
+            catalog.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            result.columns[0].data_type.to_string(),
+            "STRUCT<BIGINT, x NUMERIC(10, 2)>"
+        );
+
+        for sql in [
+            "SELECT CAST(NULL AS STRUCT<money>)",
+            "CREATE TABLE t (a STRUCT<money>)",
+        ] {
+            let stmt = Parser::new(sql).parse().unwrap().remove(0);
+            let err = Analyzer::with_catalog(catalog.clone())
+                .analyze(&stmt)
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("type 'money' not found"),
+                "{}",
+                err
+            );
+            let span = err.span().unwrap();
+            assert_eq!(&sql[span.start..span.end], "money");
+        }
+    }
+
+    #[test]
+    fn test_custom_types_resolve_through_catalog() {
+        let mut catalog = crate::catalog::CatalogBuilder::new()
+            .add_type_alias("EMAIL", SqlType::Varchar(None))
+            .add_type_alias("GEOGRAPHY", SqlType::Varbinary(None))
+            .add_table("users", |t| t.column("contact", SqlType::Varchar(None)))
+            .build();
+
+        let result = parse_and_analyze(
+            "SELECT CAST(contact AS email), CAST(NULL AS ARRAY<GEOGRAPHY(4326)>) FROM users",
+            catalog.clone(),
+        )
+        .unwrap();
+        assert_eq!(result.columns[0].data_type, SqlType::Varchar(None));
+        assert_eq!(
+            result.columns[1].data_type,
+            SqlType::Array(Box::new(SqlType::Varbinary(None)))
+        );
+        let stmt = Parser::new("CREATE TABLE contacts (e EMAIL NOT NULL, g GEOGRAPHY(4326))")
+            .parse()
+            .unwrap()
+            .remove(0);
+        assert!(Analyzer::with_catalog(catalog.clone())
+            .analyze(&stmt)
+            .is_ok());
+
+        // Unknown names parse, and fail only when analyzed.
+        let sql = "SELECT CAST(contact AS PHONE) FROM users";
+        let stmt = Parser::new(sql).parse().unwrap().remove(0);
+        let err = Analyzer::with_catalog(catalog.clone())
+            .analyze(&stmt)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("type 'PHONE' not found"),
+            "{}",
+            err
+        );
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("PHONE"));
+
+        // Applied DDL uses the catalog's aliases too.
+        let stmt = Parser::new("CREATE TABLE contacts (e EMAIL)")
+            .parse()
+            .unwrap()
+            .remove(0);
+        catalog.apply(&stmt).unwrap();
+        let table = catalog
+            .resolve_table(&["contacts".to_string()])
+            .unwrap()
+            .unwrap();
+        assert_eq!(table.columns[0].data_type, SqlType::Varchar(None));
+    }
+
+    #[test]
+    fn test_assigned_literal_lengths() {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        let ddl = "CREATE TABLE notes (code VARCHAR(3), body TEXT, digest VARBINARY(2), \
+                   price NUMERIC(5, 2))";
+        let stmt = Parser::new(ddl).parse().unwrap().remove(0);
+        catalog.apply(&stmt).unwrap();
+
+        let warnings = |sql: &str| {
+            let stmt = Parser::new(sql).parse().unwrap().remove(0);
+            let mut analyzer = Analyzer::with_catalog(catalog.clone());
+            analyzer.analyze(&stmt).unwrap();
+            analyzer
+                .warnings()
+                .iter()
+                .map(|w| {
+                    (
+                        w.to_string(),
+                        w.span.map(|s| sql[s.start..s.end].to_string()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let found = warnings("INSERT INTO notes VALUES ('abcd', 'long text', b'abc', 1234.5)");
+        assert_eq!(
+            found,
+            [
+                (
+                    "value of length 4 is too long for column 'code' of type VARCHAR(3)"
+                        .to_string(),
+                    Some("'abcd'".to_string())
+                ),
+                (
+                    "value of length 3 is too long for column 'digest' of type VARBINARY(2)"
+                        .to_string(),
+                    Some("b'abc'".to_string())
+                ),
+                (
+                    "value is out of range for column 'price' of type NUMERIC(5, 2)".to_string(),
+                    Some("1234.5".to_string())
+                ),
+            ]
+        );
+        // Lengths count characters, and scale digits may round away.
+        assert!(warnings("INSERT INTO notes (code, price) VALUES ('äbc', -999.999)").is_empty());
+        // Values that exactly fill the column, or an unbounded one, fit.
+        assert!(warnings(
+            "INSERT INTO notes VALUES ('abc', 'a very long body of text', b'ab', 999.99)"
+        )
+        .is_empty());
+        assert_eq!(
+            warnings("UPDATE notes SET code = 'abcd', price = 0 WHERE body = 'x'").len(),
+            1
+        );
+
+        let stmt = Parser::new("SELECT code, code || body FROM notes")
+            .parse()
+            .unwrap()
+            .remove(0);
+        let StatementKind::Query(query) = stmt.kind else {
+            panic!("Expected a query statement");
+        };
+        let result = Analyzer::with_catalog(catalog.clone())
+            .analyze_query_result(&query)
+            .unwrap();
+        assert_eq!(result.columns[0].data_type, SqlType::Varchar(Some(3)));
+        assert_eq!(result.columns[1].data_type, SqlType::Varchar(None));
+    }
+
+    #[test]
+    fn test_numeric_arithmetic_types() {
+        let numeric = |p, s| SqlType::Numeric {
+            precision: Some(p),
+            scale: Some(s),
+        };
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        catalog.add_table(TableSchema::new(
+            "items",
+            vec![
+                ColumnSchema::new("price", numeric(10, 2)),
+                ColumnSchema::new("rate", numeric(8, 4)),
+                ColumnSchema::new("qty", SqlType::Int32),
+                ColumnSchema::new("weight", SqlType::Float64),
+                ColumnSchema::new("huge", numeric(38, 10)),
+            ],
+        ));
+        let analyze = |sql: &str, options: AnalyzerOptions| {
+            let stmt = Parser::new(sql).parse().unwrap().remove(0);
+            let StatementKind::Query(query) = stmt.kind else {
+                panic!("Expected a query statement");
+            };
+            let mut analyzer = Analyzer::with_catalog_and_options(catalog.clone(), options);
+            let result = analyzer.analyze_query_result(&query);
+            (result, analyzer.warnings().to_vec())
+        };
+
+        let (result, warnings) = analyze(
+            "SELECT price + rate, price * qty, price / rate, SUM(price), AVG(price), \
+             SUM(qty), price + weight FROM items WHERE price > rate",
+            AnalyzerOptions::default(),
+        );
+        let types: Vec<SqlType> = result
+            .unwrap()
+            .columns
+            .into_iter()
+            .map(|c| c.data_type)
+            .collect();
+        assert_eq!(
+            types,
+            [
+                numeric(13, 4),
+                numeric(21, 2),
+                numeric(23, 11),
+                numeric(38, 2),
+                numeric(38, 6),
+                SqlType::Float64,
+                SqlType::Float64,
+            ]
+        );
+        assert!(warnings.is_empty());
+
+        let options =
+            AnalyzerOptions::default().with_numeric_float_arithmetic(NumericFloatArithmetic::Error);
+        let sql = "SELECT price + weight FROM items";
+        let err = analyze(sql, options).0.unwrap_err();
+        assert!(err.to_string().contains("NUMERIC arithmetic"), "{}", err);
+        assert_eq!(
+            err.span().map(|s| &sql[s.start..s.end]),
+            Some("price + weight")
+        );
+
+        let sql = "SELECT huge + huge FROM items";
+        let (result, warnings) = analyze(sql, AnalyzerOptions::default());
+        assert_eq!(result.unwrap().columns[0].data_type, numeric(38, 9));
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(
+            warnings[0].kind,
+            AnalyzerWarningKind::NumericPrecisionCapped { precision: 39, .. }
+        ));
+        assert_eq!(
+            warnings[0].span.map(|s| &sql[s.start..s.end]),
+            Some("huge + huge")
+        );
+    }
+
+    #[test]
+    fn test_dialect_options() {
+        let analyze = |sql: &str, options: AnalyzerOptions| {
+            let stmt = Parser::new(sql).parse().unwrap().remove(0);
+            let StatementKind::Query(query) = stmt.kind else {
+                panic!("Expected a query statement");
+            };
+            Analyzer::with_catalog_and_options(setup_test_catalog(), options)
+                .analyze_query_result(&query)
+        };
+        let error_text = |sql: &str, options: AnalyzerOptions| {
+            let err = analyze(sql, options).unwrap_err();
+            let span = err.span().unwrap();
+            (err.to_string(), sql[span.start..span.end].to_string())
+        };
+
+        // GROUP BY is permissive by default.
+        let ungrouped = "SELECT name, age FROM users GROUP BY name";
+        assert!(analyze(ungrouped, AnalyzerOptions::default()).is_ok());
+        let strict = || AnalyzerOptions::default().with_group_by(GroupByMode::Strict);
+        for sql in [
+            "SELECT name, COUNT(*) FROM users GROUP BY name",
+            "SELECT u.name, SUM(age) + 1 FROM users u GROUP BY name",
+            "SELECT UPPER(name) AS n, COUNT(*) FROM users GROUP BY n",
+            "SELECT name FROM users GROUP BY users.name HAVING MAX(age) > 1",
+            "SELECT name, email FROM users GROUP BY 1, 2",
+            "SELECT * FROM users GROUP BY id, name, age, email",
+        ] {
+            assert!(analyze(sql, strict()).is_ok(), "{}", sql);
+        }
+        for (sql, column) in [
+            (ungrouped, "age"),
+            ("SELECT name, COUNT(*) FROM users", "name"),
+            (
+                "SELECT UPPER(email) FROM users GROUP BY UPPER(name)",
+                "email",
+            ),
+            ("SELECT name FROM users GROUP BY name HAVING age > 1", "age"),
+            (
+                "SELECT o.id FROM users u JOIN orders o ON u.id = o.user_id GROUP BY u.id",
+                "o.id",
+            ),
+        ] {
+            let (message, span) = error_text(sql, strict());
+            assert!(message.contains("must appear in GROUP BY"), "{}", message);
+            assert_eq!(span, column, "{}", sql);
+        }
+        assert!(analyze("SELECT * FROM users GROUP BY id", strict()).is_err());
+
+        // Output column names fold unquoted identifiers.
+        let sql = "SELECT Name, `Age`, id AS ID, COUNT(*) FROM users GROUP BY 1, 2, 3";
+        let names = |case: IdentifierCase| -> Vec<String> {
+            let options = AnalyzerOptions::default().with_identifier_case(case);
+            let result = analyze(sql, options).unwrap();
+            result.columns.into_iter().map(|c| c.name).collect()
+        };
+        assert_eq!(
+            names(IdentifierCase::Preserve),
+            ["Name", "Age", "ID", "COUNT"]
+        );
+        assert_eq!(names(IdentifierCase::Lower), ["name", "Age", "id", "count"]);
+        assert_eq!(names(IdentifierCase::Upper), ["NAME", "Age", "ID", "COUNT"]);
+
+        // `||` is concatenation unless configured as logical OR.
+        let sql = "SELECT name || 'x', TRUE || FALSE FROM users";
+        assert!(analyze(sql, AnalyzerOptions::default()).is_ok());
+        let pipes_as_or = AnalyzerOptions::default().with_pipes_as_concat(false);
+        let result = analyze("SELECT (age > 1) || FALSE FROM users", pipes_as_or).unwrap();
+        assert_eq!(result.columns[0].data_type, SqlType::Bool);
+
+        let sql = "SELECT * FROM users, orders";
+        assert!(analyze(sql, AnalyzerOptions::default()).is_ok());
+        let (message, span) = error_text(sql, AnalyzerOptions::default().with_comma_joins(false));
+        assert!(message.contains("CROSS JOIN"), "{}", message);
+        assert_eq!(span, "orders");
+        let sql = "SELECT * FROM users CROSS JOIN orders";
+        assert!(analyze(sql, AnalyzerOptions::default().with_comma_joins(false)).is_ok());
+
+        let depth = |limit| AnalyzerOptions::default().with_max_expr_depth(limit);
+        assert!(analyze("SELECT 1 + 2", depth(2)).is_ok());
+        let (message, span) = error_text("SELECT (1 + 2)", depth(2));
+        assert!(message.contains("more than 2 levels"), "{}", message);
+        assert_eq!(span, "1");
+    }
+
+    #[test]
+    fn test_join_conditions() {
+        let analyze = |sql: &str| parse_and_analyze(sql, setup_test_catalog());
+        let error_text = |sql: &str| {
+            let err = analyze(sql).unwrap_err();
+            let span = err.span().unwrap();
+            (err.to_string(), sql[span.start..span.end].to_string())
+        };
+        for sql in [
+            "SELECT * FROM users JOIN orders",
+            "SELECT * FROM users LEFT JOIN orders",
+            "SELECT * FROM users u FULL OUTER JOIN orders o",
+        ] {
+            let (message, span) = error_text(sql);
+            assert!(
+                message.contains("JOIN requires an ON or USING clause"),
+                "{}",
+                message
+            );
+            assert_eq!(span, &sql[14..], "{}", sql);
+        }
+        assert!(analyze("SELECT * FROM users CROSS JOIN orders").is_ok());
+        assert!(analyze("SELECT * FROM users NATURAL JOIN orders").is_ok());
+
+        let (message, span) =
+            error_text("SELECT * FROM users u CROSS JOIN orders o ON u.id = o.user_id");
+        assert!(message.contains("CROSS JOIN cannot have"), "{}", message);
+        assert_eq!(span, "u.id = o.user_id");
+        let (message, span) = error_text("SELECT * FROM users CROSS JOIN orders USING (id)");
+        assert!(message.contains("CROSS JOIN cannot have"), "{}", message);
+        assert_eq!(span, "id");
+        let sql = "SELECT * FROM users RIGHT JOIN orders ON TRUE JOIN users u2";
+        let (message, span) = error_text(sql);
+        assert!(message.contains("JOIN requires"), "{}", message);
+        assert_eq!(span, &sql[14..]);
+
+        // Comma joins are listed for callers to warn on, through set
+        // operations but not subqueries.
+        let sql = "SELECT 1 FROM users, orders o \
+                   UNION ALL SELECT 1 FROM (SELECT 1 FROM users, orders) AS d, users";
+        let spans = analyze(sql).unwrap().implicit_cross_joins;
+        let texts: Vec<&str> = spans.iter().map(|s| &sql[s.start..s.end]).collect();
+        assert_eq!(texts, ["orders o", "users"]);
+        assert!(analyze("SELECT * FROM users CROSS JOIN orders")
+            .unwrap()
+            .implicit_cross_joins
+            .is_empty());
+    }
+
+    #[test]
+    fn test_double_quoted_identifiers_resolve() {
+        let sql = r#"SELECT "name" FROM "users" WHERE "id" = 1"#;
+        let options = crate::parser::ParserOptions::new().with_double_quote_identifiers(true);
+        let stmt = Parser::new(sql)
+            .with_options(options)
+            .parse_statement()
+            .unwrap();
+        let StatementKind::Query(query) = stmt.kind else {
+            panic!("Expected a query statement");
+        };
+        let mut analyzer = Analyzer::with_catalog(setup_test_catalog());
+        let result = analyzer.analyze_query_result(&query).unwrap();
+        assert_eq!(result.columns[0].name, "name");
+        assert_eq!(result.columns[0].data_type, SqlType::Varchar(None));
+    }
+
+    #[test]
+    fn test_default_expr_depth_matches_parser() {
+        let mut analyzer = Analyzer::with_catalog(setup_test_catalog());
+
+        // An operator chain is one level however long it is.
+        let terms: Vec<String> = (0..10_000).map(|i| format!("id = {}", i)).collect();
+        let sql = format!("SELECT * FROM users WHERE {}", terms.join(" OR "));
+        let stmt = Parser::new(&sql).parse_statement().unwrap();
+        analyzer.analyze(&stmt).unwrap();
+
+        // Whatever the parser accepts is within the limit; a tree built
+        // deeper is not.
+        let limit = crate::parser::DEFAULT_MAX_DEPTH;
+        let nested = |n: usize| format!("SELECT {}1{}", "(".repeat(n), ")".repeat(n));
+        let stmt = Parser::new(&nested(limit - 3)).parse_statement().unwrap();
+        analyzer.analyze(&stmt).unwrap();
+        let stmt = Parser::new(&nested(limit))
+            .with_max_depth(limit + 3)
+            .parse_statement()
+            .unwrap();
+        assert!(analyzer.analyze(&stmt).is_err());
+        let kind = &analyzer.errors()[0].kind;
+        assert!(
+            matches!(kind, AnalyzerErrorKind::ExpressionTooDeep { limit: l } if *l == limit),
+            "{:?}",
+            kind
+        );
+    }
+
+    #[test]
+    fn test_analysis_reports_every_unknown_name() {
+        let sql = "SELECT nme, age + 1, agee FROM users WHERE emial = 'x' AND id > 0";
+        let stmt = Parser::new(sql).parse().unwrap().remove(0);
+        let mut analyzer = Analyzer::with_catalog(setup_test_catalog());
+        let err = analyzer.analyze(&stmt).unwrap_err();
+        assert!(err.to_string().contains("emial"), "{}", err);
+        let names: Vec<String> = analyzer
+            .errors()
+            .iter()
+            .map(|e| match &e.kind {
+                AnalyzerErrorKind::ColumnNotFound { name, .. } => name.clone(),
+                other => panic!("unexpected error {:?}", other),
+            })
+            .collect();
+        assert_eq!(names, ["emial", "nme", "agee"]);
+
+        // Errors that are not about names still stop analysis.
+        let stmt = Parser::new("SELECT nme FROM users WHERE name + 1")
+            .parse()
+            .unwrap()
+            .remove(0);
+        assert!(analyzer.analyze(&stmt).is_err());
+        assert_eq!(analyzer.errors().len(), 1);
+
+        assert!(analyzer
+            .analyze(&Parser::new("SELECT name FROM users").parse().unwrap()[0])
+            .is_ok());
+        assert!(analyzer.errors().is_empty());
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let suggestion = |sql: &str| {
+            let stmt = Parser::new(sql).parse().unwrap().remove(0);
+            let mut analyzer = Analyzer::with_catalog(setup_test_catalog());
+            let err = analyzer.analyze(&stmt).unwrap_err();
+            let first = &analyzer.errors()[0];
+            if let Some(s) = &first.suggestion {
+                assert!(err.to_string().contains(&format!("did you mean '{}'?", s)));
+            }
+            first.suggestion.clone()
+        };
+        let cases = [
+            ("SELECT nmae FROM users", Some("name")),
+            ("SELECT u.emial FROM users u", Some("email")),
+            ("SELECT ag FROM users", Some("age")),
+            ("SELECT address FROM users", None),
+            ("SELECT * FROM user", Some("users")),
+            ("SELECT * FROM ordrs", Some("orders")),
+            (
+                "WITH recent AS (SELECT 1 AS n) SELECT * FROM recnet",
+                Some("recent"),
+            ),
+            ("SELECT UPPR(name) FROM users", Some("UPPER")),
+            ("SELECT * FROM users JOIN orders USING (ids)", Some("id")),
+            ("INSERT INTO users (id, nme) VALUES (1, 'a')", Some("name")),
+            ("UPDATE users SET emal = 'x'", Some("email")),
+            // `id` and `ix` are equally close; the first column wins.
+            ("SELECT i FROM (SELECT 1 AS id, 2 AS ix) t", Some("id")),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(suggestion(sql).as_deref(), expected, "{}", sql);
+        }
+
+        let options =
+            AnalyzerOptions::default().with_identifier_matching(IdentifierMatching::Exact);
+        let stmt = Parser::new("SELECT Name FROM users")
+            .parse()
+            .unwrap()
+            .remove(0);
+        let mut analyzer = Analyzer::with_catalog_and_options(setup_test_catalog(), options);
+        let err = analyzer.analyze(&stmt).unwrap_err();
+        assert!(err.to_string().contains("did you mean 'name'?"), "{}", err);
+    }
+
+    #[test]
+    fn test_groups_frame_requires_order_by() {
+        let err = parse_and_analyze(
+            "SELECT SUM(age) OVER (PARTITION BY name GROUPS UNBOUNDED PRECEDING) FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("GROUPS frame requires ORDER BY"));
+
+        let err = parse_and_analyze(
+            "SELECT SUM(age) OVER w FROM users WINDOW w AS (GROUPS CURRENT ROW)",
+            setup_test_catalog(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("GROUPS frame requires ORDER BY"));
+    }
+
+    #[test]
+    fn test_window_frame_carried_to_analysis() {
+        let result = parse_and_analyze(
+            "SELECT SUM(age) OVER w FROM users \
+             WINDOW w AS (ORDER BY id GROUPS BETWEEN 1 PRECEDING AND CURRENT ROW EXCLUDE GROUP)",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        assert_eq!(result.windows.len(), 1);
+        let window = &result.windows[0];
+        assert_eq!(window.function, "SUM");
+        let frame = window.spec.frame.as_ref().unwrap();
+        assert_eq!(frame.unit, WindowFrameUnit::Groups);
+        assert_eq!(frame.exclusion, Some(FrameExclusion::Group));
+
+        let err = parse_and_analyze(
+            "SELECT SUM(age) OVER missing FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("window 'missing' is not defined"));
+    }
+
+    fn analyze_with_warnings(sql: &str) -> (AnalyzedQuery, Vec<AnalyzerWarning>) {
+        let stmt = Parser::new(sql).parse().unwrap().remove(0);
+        let StatementKind::Query(query) = stmt.kind else {
+            panic!("Expected a query statement");
+        };
+        let mut analyzer = Analyzer::with_catalog(setup_test_catalog());
+        let result = analyzer.analyze_query_result(&query).unwrap();
+        (result, analyzer.warnings().to_vec())
+    }
+
+    #[test]
+    fn test_typed_literal_contents() {
+        let result = parse_and_analyze(
+            "SELECT DATE '2024-02-29' AS d, TIMESTAMP '2024-01-01 00:00:00+05:30' AS ts, \
+             NUMERIC '-12.50' AS n, JSON '{\"a\": [1, null]}' AS j",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        assert_eq!(result.columns[0].data_type, SqlType::Date);
+
+        for (sql, message, span) in [
+            (
+                "SELECT DATE '2024-13-45'",
+                "invalid DATE literal '2024-13-45': month 13 is out of range",
+                (7, 24),
+            ),
+            (
+                "SELECT 1 + TIMESTAMP 'not a timestamp'",
+                "invalid TIMESTAMP literal 'not a timestamp': expected 4 digits for the year",
+                (11, 38),
+            ),
+            (
+                "SELECT JSON '{invalid'",
+                "unexpected 'i' at offset 1 where an object key was expected",
+                (7, 22),
+            ),
+            (
+                "SELECT NUMERIC '1.0000000001'",
+                "more than 9 digits after the decimal point",
+                (7, 29),
+            ),
+        ] {
+            let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+            assert!(
+                err.to_string().contains(message),
+                "{}: unexpected error: {}",
+                sql,
+                err
+            );
+            assert_eq!(err.span(), Some(Span::new(span.0, span.1)), "{}", sql);
+        }
+        let max = format!("SELECT NUMERIC '{}.{}'", "9".repeat(29), "9".repeat(9));
+        assert!(parse_and_analyze(&max, setup_test_catalog()).is_ok());
+    }
+
+    #[test]
+    fn test_quantified_comparison() {
+        let result = parse_and_analyze(
+            "SELECT age > ALL (SELECT age FROM users WHERE name = 'x') AS a, \
+             id = ANY ([1, 2, 3]) AS b, name <> SOME ('a', email) AS c FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        assert!(result.columns.iter().all(|c| c.data_type == SqlType::Bool));
+
+        for (sql, message) in [
+            (
+                "SELECT * FROM users WHERE age > ALL (SELECT id, age FROM users)",
+                "subquery must return exactly one column, but returns 2",
+            ),
+            (
+                "SELECT * FROM users WHERE age = ANY (SELECT name FROM users)",
+                "cannot compare BIGINT with VARCHAR",
+            ),
+            (
+                "SELECT * FROM users WHERE age = ANY (['a'])",
+                "cannot compare BIGINT with VARCHAR",
+            ),
+        ] {
+            let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+            assert!(
+                err.to_string().contains(message),
+                "{}: unexpected error: {}",
+                sql,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_pattern_match_operands() {
+        let result = parse_and_analyze(
+            "SELECT name ILIKE 'a%' AS a, email NOT LIKE ANY ('%.com', @p) AS b, \
+             name SIMILAR TO '(x|y)%' ESCAPE '!' AS c FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        assert!(result.columns.iter().all(|c| c.data_type == SqlType::Bool));
+
+        for sql in [
+            "SELECT * FROM users WHERE age ILIKE 'a%'",
+            "SELECT * FROM users WHERE name LIKE ALL ('a%', id)",
+            "SELECT * FROM users WHERE name SIMILAR TO 'a' ESCAPE 1",
+        ] {
+            let err = parse_and_analyze(sql, setup_test_catalog()).unwrap_err();
+            assert!(
+                err.to_string().contains("expected VARCHAR, got BIGINT"),
+                "{}: unexpected error: {}",
+                sql,
+                err
+            );
+        }
+        let err = parse_and_analyze(
+            "SELECT * FROM users WHERE age ILIKE 'a%'",
+            setup_test_catalog(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("in ILIKE"), "{}", err);
+    }
+
+    #[test]
+    fn test_is_boolean_tests() {
+        let result = parse_and_analyze(
+            "SELECT (age > 1) IS UNKNOWN AS a, NULL IS NOT TRUE AS b, name IS NULL AS c FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        assert!(result
+            .columns
+            .iter()
+            .all(|c| c.data_type == SqlType::Bool && !c.nullable));
+
+        let err = parse_and_analyze(
+            "SELECT * FROM users WHERE name IS TRUE",
+            setup_test_catalog(),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("expected BOOLEAN"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_null_literals_and_distinct_from() {
+        let (result, warnings) = analyze_with_warnings(
+            "SELECT COALESCE(NULL, 1) AS a, id IS DISTINCT FROM NULL AS b, \
+             age <=> @p AS c FROM users WHERE name = NULL OR NULL <> email",
+        );
+        let columns: Vec<(&str, &SqlType, bool)> = result
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), &c.data_type, c.nullable))
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                ("a", &SqlType::Int64, true),
+                ("b", &SqlType::Bool, false),
+                ("c", &SqlType::Bool, false),
+            ]
+        );
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            [
+                "'=' with a NULL operand is always NULL; use IS NULL or IS NOT DISTINCT FROM",
+                "'!=' with a NULL operand is always NULL; use IS NOT NULL or IS DISTINCT FROM",
+            ]
+        );
+
+        let err = parse_and_analyze(
+            "SELECT * FROM users WHERE id IS NOT DISTINCT FROM name",
+            setup_test_catalog(),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("cannot compare BIGINT with VARCHAR"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_cte_shadows_table() {
+        let (result, warnings) =
+            analyze_with_warnings("WITH users AS (SELECT 1 AS x) SELECT * FROM users");
+        assert_eq!(result.columns.len(), 1);
+        assert_eq!(result.columns[0].name, "x");
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(
+            &warnings[0].kind,
+            AnalyzerWarningKind::CteShadowsTable { name } if name == "users"
+        ));
+        assert_eq!(warnings[0].span.map(|s| s.start), Some(5));
+    }
+
+    #[test]
+    fn test_qualified_name_bypasses_cte() {
+        let (result, _) =
+            analyze_with_warnings("WITH users AS (SELECT 1 AS x) SELECT * FROM default.users");
+        assert_eq!(result.columns.len(), 4);
+        assert_eq!(result.columns[0].name, "id");
+    }
+
+    #[test]
+    fn test_cte_shadows_outer_cte() {
+        let sql = "WITH c AS (SELECT id FROM users) \
+                   SELECT * FROM (WITH c AS (SELECT name FROM users) SELECT * FROM c) AS t";
+        let (result, warnings) = analyze_with_warnings(sql);
+        assert_eq!(result.columns[0].name, "name");
+        assert_eq!(warnings.len(), 1);
+        match &warnings[0].kind {
+            AnalyzerWarningKind::CteShadowsCte { name, outer } => {
+                assert_eq!(name, "c");
+                assert_eq!(outer.start, 5);
+            }
+            other => panic!("unexpected warning: {:?}", other),
+        }
+
+        // The inner WITH does not leak into the outer query.
+        let (result, _) = analyze_with_warnings(
+            "SELECT * FROM (WITH c AS (SELECT name FROM users) SELECT * FROM c) AS t, users",
+        );
+        assert_eq!(result.columns.len(), 5);
+    }
+
+    #[test]
+    fn test_order_by_effective_nulls() {
+        let sql = "SELECT id, name FROM users ORDER BY 1, name DESC, 2 ASC NULLS FIRST";
+        let stmt = Parser::new(sql).parse().unwrap().remove(0);
+        let StatementKind::Query(query) = stmt.kind else {
+            panic!("Expected a query statement");
+        };
+        let cases = [
+            (
+                NullOrdering::NullsHigh,
+                [NullsOrder::Last, NullsOrder::First, NullsOrder::First],
+            ),
+            (
+                NullOrdering::NullsLow,
+                [NullsOrder::First, NullsOrder::Last, NullsOrder::First],
+            ),
+        ];
+        for (ordering, expected) in cases {
+            let options = AnalyzerOptions::new().with_null_ordering(ordering);
+            let mut analyzer = Analyzer::with_catalog_and_options(setup_test_catalog(), options);
+            let result = analyzer.analyze_query_result(&query).unwrap();
+            let keys: Vec<_> = result
+                .sort_keys
+                .iter()
+                .map(|k| (k.target.clone(), k.order, k.nulls))
+                .collect();
+            assert_eq!(
+                keys,
+                [
+                    (SortTarget::Output(0), SortOrder::Asc, expected[0]),
+                    (SortTarget::Output(1), SortOrder::Desc, expected[1]),
+                    (SortTarget::Output(1), SortOrder::Asc, expected[2]),
+                ],
+                "{:?}",
+                ordering
+            );
+        }
+    }
+
+    #[test]
+    fn test_order_by_array_column() {
+        let mut catalog = setup_test_catalog();
+        catalog.add_table(
+            TableSchemaBuilder::new("posts")
+                .column(ColumnSchema::new("id", SqlType::Int64))
+                .column(ColumnSchema::new(
+                    "tags",
+                    SqlType::Array(Box::new(SqlType::Varchar(None))),
+                ))
+                .build(),
+        );
+
+        let err = parse_and_analyze("SELECT id, tags FROM posts ORDER BY tags", catalog.clone())
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("cannot ORDER BY a value of type ARRAY<VARCHAR>"),
+            "{}",
+            err
+        );
+
+        let err = parse_and_analyze(
+            "SELECT ROW_NUMBER() OVER (ORDER BY tags) AS n FROM posts",
+            catalog,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("cannot ORDER BY"), "{}", err);
+    }
+
+    #[test]
+    fn test_window_sort_keys() {
+        let result = parse_and_analyze(
+            "SELECT RANK() OVER (ORDER BY age DESC) AS r FROM users",
+            setup_test_catalog(),
+        )
+        .unwrap();
+        let key = &result.windows[0].sort_keys[0];
+        assert_eq!(key.data_type, SqlType::Int64);
+        assert_eq!(key.order, SortOrder::Desc);
+        assert_eq!(key.nulls, NullsOrder::First);
+        assert!(matches!(key.target, SortTarget::Expr(_)));
+    }
+
+    fn parameter_types(sql: &str) -> Vec<(String, SqlType)> {
+        let stmt = Parser::new(sql).parse().unwrap().remove(0);
+        Analyzer::with_catalog(setup_test_catalog())
+            .infer_parameter_types(&stmt)
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.parameter.to_string(), p.data_type))
+            .collect()
+    }
+
+    #[test]
+    fn test_infer_parameter_types() {
+        assert_eq!(
+            parameter_types("SELECT * FROM users WHERE id = ? AND name = ?"),
+            [
+                ("?".to_string(), SqlType::Int64),
+                ("?".to_string(), SqlType::Varchar(None))
+            ]
+        );
+
+        let cases = [
+            ("SELECT * FROM users WHERE ? < age", SqlType::Int64),
+            (
+                "SELECT * FROM users WHERE age BETWEEN 1 AND ?",
+                SqlType::Int64,
+            ),
+            (
+                "SELECT * FROM users WHERE name IN ('a', ?)",
+                SqlType::Varchar(None),
+            ),
+            (
+                "SELECT * FROM users WHERE email LIKE ?",
+                SqlType::Varchar(None),
+            ),
+            ("SELECT * FROM users WHERE ?", SqlType::Bool),
+            ("SELECT * FROM users LIMIT ?", SqlType::Int64),
+            ("SELECT CAST(? AS DATE)", SqlType::Date),
+            ("SELECT age + (?) FROM users", SqlType::Int64),
+            (
+                "UPDATE users SET name = ? WHERE id = 1",
+                SqlType::Varchar(None),
+            ),
+            ("SELECT ?", SqlType::Unknown),
+            ("SELECT COUNT(?) FROM users", SqlType::Unknown),
+        ];
+        for (sql, expected) in cases {
+            let types = parameter_types(sql);
+            assert_eq!(types.len(), 1, "{}", sql);
+            assert_eq!(types[0].1, expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_infer_repeated_parameter_types() {
+        assert_eq!(
+            parameter_types("SELECT * FROM users WHERE id = $2 OR age > $1 OR $2 = 0"),
+            [
+                ("$2".to_string(), SqlType::Int64),
+                ("$1".to_string(), SqlType::Int64)
+            ]
+        );
+        assert_eq!(
+            parameter_types("SELECT @v FROM users WHERE name = @v"),
+            [("@v".to_string(), SqlType::Varchar(None))]
+        );
+    }
+
+    #[test]
+    fn test_analyze_statement_result() {
+        let catalog = setup_test_catalog();
+        let mut analyzer = Analyzer::with_catalog(&catalog);
+        let mut analyze = |sql: &str| {
+            let stmt = Parser::new(sql).parse_statement().unwrap();
+            analyzer.analyze_statement_result(&stmt)
+        };
+
+        let StatementAnalysis::Dml {
+            table,
+            returning: Some(returning),
+            kind: DmlKind::Insert,
+        } = analyze("INSERT INTO users (id, name) VALUES (1, 'a') RETURNING *").unwrap()
+        else {
+            panic!("expected an INSERT");
+        };
+        assert_eq!(table.name, ["users"]);
+        let names: Vec<&str> = returning.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["id", "name", "age", "email"]);
+
+        let StatementAnalysis::Dml {
+            table,
+            returning: Some(returning),
+            kind: DmlKind::Update,
+        } = analyze(
+            "UPDATE orders o SET amount = 0 FROM users u WHERE u.id = o.user_id \
+             RETURNING o.id, amount * 2 AS doubled, u.name",
+        )
+        .unwrap()
+        else {
+            panic!("expected an UPDATE");
+        };
+        assert_eq!(table.alias, "o");
+        assert_eq!(returning[1].name, "doubled");
+        assert_eq!(returning[1].data_type, SqlType::Float64);
+        assert_eq!(returning[2].data_type, SqlType::Varchar(None));
+        assert_eq!(returning[0].source.as_ref().unwrap().table, "o");
+
+        assert!(matches!(
+            analyze("DELETE FROM users WHERE id = 1").unwrap(),
+            StatementAnalysis::Dml {
+                returning: None,
+                kind: DmlKind::Delete,
+                ..
+            }
+        ));
+        assert!(matches!(
+            analyze(
+                "MERGE INTO orders t USING users s ON t.user_id = s.id \
+                 WHEN MATCHED THEN DELETE"
+            )
+            .unwrap(),
+            StatementAnalysis::Dml { table, kind: DmlKind::Merge, .. } if table.alias == "t"
+        ));
+        assert!(matches!(
+            analyze("SELECT 1").unwrap(),
+            StatementAnalysis::Query(_)
+        ));
+
+        // RETURNING only sees the target table
+        let err = analyze("DELETE FROM users WHERE id = 1 RETURNING amount").unwrap_err();
+        assert!(err.to_string().contains("amount"), "{}", err);
+        let err = analyze("INSERT INTO users (id) VALUES (1) RETURNING nope").unwrap_err();
+        assert!(err.to_string().contains("nope"), "{}", err);
+        let err = analyze("UPDATE orders o SET amount = 0 RETURNING u.name").unwrap_err();
+        assert!(err.to_string().contains("'u'"), "{}", err);
+    }
+
+    #[test]
+    fn test_returning_clause() {
+        let catalog = setup_test_catalog();
+        let mut analyzer = Analyzer::with_catalog(&catalog);
+        let mut returning = |sql: &str| -> Result<Vec<OutputColumn>> {
+            let stmt = Parser::new(sql).parse_statement().unwrap();
+            match analyzer.analyze_statement_result(&stmt)? {
+                StatementAnalysis::Dml { returning, .. } => Ok(returning.unwrap()),
+                other => panic!("expected a DML statement: {:?}", other),
+            }
+        };
+
+        let columns = returning(
+            "INSERT INTO users (id, name) VALUES (1, 'a') \
+             RETURNING id AS user_id, UPPER(name), age + 1, users.email",
+        )
+        .unwrap();
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["user_id", "UPPER", "_col2", "email"]);
+        assert_eq!(columns[2].data_type, SqlType::Int64);
+        let columns =
+            returning("DELETE FROM orders WHERE id = 1 RETURNING * EXCEPT (amount)").unwrap();
+        assert_eq!(columns.len(), 3);
+
+        let err = returning("DELETE FROM users WHERE id = 1 RETURNING COUNT(*)").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'COUNT': aggregate functions are not allowed in RETURNING"),
+            "{}",
+            err
+        );
+        let err = returning(
+            "UPDATE users SET age = 1 WHERE id = 1 RETURNING ROW_NUMBER() OVER (ORDER BY id)",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("window functions are not allowed in RETURNING"),
+            "{}",
+            err
+        );
+        assert!(returning("DELETE FROM orders WHERE id = 1 RETURNING UPPER(created_at)").is_err());
+        let sql = "UPDATE users SET age = 1 WHERE id = 1 RETURNING id, o.id";
+        let err = returning(sql).unwrap_err();
+        assert!(err.to_string().contains("'o'"), "{}", err);
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("o"));
+        let sql = "DELETE FROM users WHERE id = 1 RETURNING id + nosuch";
+        let err = returning(sql).unwrap_err();
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("nosuch"));
+
+        // Aggregates are rejected inside expressions too.
+        let err =
+            returning("INSERT INTO users (id) VALUES (1) RETURNING id + SUM(age)").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'SUM': aggregate functions are not allowed in RETURNING"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_merge_validation() {
+        let catalog = setup_test_catalog();
+        let mut analyzer = Analyzer::with_catalog(&catalog);
+        let mut analyze = |sql: &str| {
+            let stmt = Parser::new(sql).parse_statement().unwrap();
+            analyzer.analyze(&stmt)
+        };
+        let merge = |clauses: &str| {
+            format!(
+                "MERGE INTO users t USING orders s ON t.id = s.user_id {}",
+                clauses
+            )
+        };
+
+        analyze(&merge(
+            "WHEN MATCHED AND s.amount > 10 THEN UPDATE SET age = t.age + 1 \
+             WHEN MATCHED THEN DELETE \
+             WHEN NOT MATCHED THEN INSERT (id, name) VALUES (s.user_id, 'new') \
+             WHEN NOT MATCHED BY SOURCE AND t.age > 90 THEN UPDATE SET t.email = NULL",
+        ))
+        .unwrap();
+
+        let mut error = |clauses: &str| analyze(&merge(clauses)).unwrap_err().to_string();
+        let err = error("WHEN MATCHED THEN UPDATE SET amount = 0");
+        assert!(
+            err.contains("column 'amount' not found in table 'users'"),
+            "{}",
+            err
+        );
+        let err = error("WHEN MATCHED THEN UPDATE SET s.amount = 0");
+        assert!(
+            err.contains("cannot assign to 's.amount' of the source"),
+            "{}",
+            err
+        );
+        let err = error("WHEN MATCHED THEN UPDATE SET age = s.created_at");
+        assert!(err.contains("age"), "{}", err);
+        let err = error("WHEN NOT MATCHED THEN INSERT (id, name) VALUES (s.user_id)");
+        assert!(err.contains("1 values but 2 target columns"), "{}", err);
+        let err = error("WHEN NOT MATCHED THEN INSERT (id, nope) VALUES (1, 'a')");
+        assert!(err.contains("'nope'"), "{}", err);
+        let err = error("WHEN NOT MATCHED THEN INSERT (name) VALUES ('a')");
+        assert!(err.contains("'id'"), "{}", err);
+        let err = error("WHEN NOT MATCHED THEN INSERT (id, age) VALUES (1, s.created_at)");
+        assert!(err.contains("age"), "{}", err);
+        let err = error("WHEN NOT MATCHED BY SOURCE AND s.amount > 0 THEN DELETE");
+        assert!(
+            err.contains("WHEN NOT MATCHED BY SOURCE cannot reference source table 's'"),
+            "{}",
+            err
+        );
+        let err = error("WHEN NOT MATCHED BY SOURCE THEN UPDATE SET age = user_id");
+        assert!(err.contains("source column 'user_id'"), "{}", err);
+        let err = error("WHEN MATCHED THEN DELETE WHEN MATCHED THEN UPDATE SET age = 1");
+        assert!(
+            err.contains("only one WHEN MATCHED clause may omit its condition"),
+            "{}",
+            err
+        );
+        let err = error("WHEN NOT MATCHED THEN INSERT (id) VALUES (1, 2)");
+        assert!(err.contains("2 values but 1 target columns"), "{}", err);
+        assert!(analyze(&merge(
+            "WHEN MATCHED AND s.amount > 0 THEN DELETE WHEN MATCHED THEN UPDATE SET age = 1"
+        ))
+        .is_ok());
+
+        for (clauses, snippet) in [
+            (
+                "WHEN NOT MATCHED BY SOURCE AND s.amount > 0 THEN DELETE",
+                "s",
+            ),
+            (
+                "WHEN NOT MATCHED BY SOURCE THEN UPDATE SET age = user_id",
+                "user_id",
+            ),
+            ("WHEN NOT MATCHED THEN INSERT (id) VALUES (1, 2)", "2"),
+            (
+                "WHEN NOT MATCHED THEN INSERT (id, age) VALUES (1, s.created_at)",
+                "s.created_at",
+            ),
+        ] {
+            let sql = merge(clauses);
+            let err = analyze(&sql).unwrap_err();
+            assert_eq!(
+                err.span().map(|s| &sql[s.start..s.end]),
+                Some(snippet),
+                "{}",
+                clauses
+            );
+        }
+    }
+
+    #[test]
+    fn test_analyze_all() {
+        let catalog = setup_test_catalog();
+        let stmts = Parser::new(
+            "CREATE TABLE adults (user_id, user_name) AS SELECT id, name FROM users WHERE age > 17; \
+             CREATE VIEW big_orders AS SELECT user_id, amount FROM orders WHERE amount > 100; \
+             SELECT a.user_name, b.amount FROM adults a JOIN big_orders b USING (user_id); \
+             INSERT INTO adults SELECT id, email FROM users",
+        )
+        .parse()
+        .unwrap();
+        let mut analyzer = Analyzer::with_catalog(&catalog);
+        let analyses = analyzer.analyze_all(&stmts).unwrap();
+        assert_eq!(analyses.len(), 4);
+
+        let StatementAnalysis::Ddl {
+            table: Some(adults),
+        } = &analyses[0]
+        else {
+            panic!("expected a table: {:?}", analyses[0]);
+        };
+        assert_eq!(adults.column_names(), vec!["user_id", "user_name"]);
+        assert!(!adults.columns[0].nullable);
+        assert!(matches!(
+            &analyses[1],
+            StatementAnalysis::Ddl { table: Some(view) } if view.columns[1].data_type == SqlType::Float64
+        ));
+        let StatementAnalysis::Query(query) = &analyses[2] else {
+            panic!("expected a query: {:?}", analyses[2]);
+        };
+        assert_eq!(query.columns[0].data_type, SqlType::Varchar(None));
+        assert!(matches!(
+            &analyses[3],
+            StatementAnalysis::Dml { table, returning: None, kind: DmlKind::Insert }
+                if table.schema.name == "adults"
+        ));
+        assert!(!catalog.table_exists(&["adults".to_string()]).unwrap());
+
+        // An explicit column list must name every column
+        let stmts =
+            Parser::new("CREATE TABLE t (a) AS SELECT id, name FROM users; SELECT a FROM t")
+                .parse()
+                .unwrap();
+        let err = analyzer.analyze_all(&stmts).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'t' has 2 columns but its column list names 1"),
+            "{}",
+            err
+        );
+        let stmts = Parser::new("CREATE TABLE t (a, b, c) AS SELECT id, name FROM users")
+            .parse()
+            .unwrap();
+        let err = analyzer.analyze_all(&stmts).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'t' has 2 columns but its column list names 3"),
+            "{}",
+            err
+        );
+        let stmts = Parser::new("CREATE VIEW v AS SELECT id, id FROM users")
+            .parse()
+            .unwrap();
+        let err = analyzer.analyze_all(&stmts).unwrap_err();
+        assert!(err.to_string().contains("'id'"), "{}", err);
+
+        // A failing statement stops the script and its errors are kept
+        let stmts = Parser::new(
+            "CREATE TABLE t AS SELECT id FROM users; SELECT nope FROM t; SELECT id FROM t",
+        )
+        .parse()
+        .unwrap();
+        let err = analyzer.analyze_all(&stmts).unwrap_err();
+        assert!(err.to_string().contains("nope"), "{}", err);
+        assert_eq!(analyzer.errors().len(), 1);
+
+        // Without analyze_all, later statements do not see earlier ones
+        let stmts = Parser::new("CREATE TABLE t AS SELECT id FROM users; SELECT id FROM t")
+            .parse()
+            .unwrap();
+        analyzer.analyze(&stmts[0]).unwrap();
+        assert!(analyzer.analyze(&stmts[1]).is_err());
+        assert!(analyzer.analyze_all(&stmts).is_ok());
+    }
 }
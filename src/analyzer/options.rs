@@ -0,0 +1,279 @@
+//! Configuration options for semantic analysis.
+
+use crate::ast::{Ident, IdentifierMatching, NullsOrder, SortOrder};
+use crate::session::Session;
+use std::sync::Arc;
+
+/// How `SELECT *` expands over `USING` and `NATURAL` joins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UsingWildcardMode {
+    /// SQL standard behavior: each join column appears once, coalesced from
+    /// both inputs, followed by the remaining left columns and then the
+    /// remaining right columns.
+    #[default]
+    Coalesced,
+    /// Legacy behavior: both qualified originals are kept, in left-then-right
+    /// order, and no coalesced column is emitted.
+    BothSides,
+}
+
+/// What the analyzer does with UPDATE, DELETE, and MERGE statements that
+/// can affect every row of their target. See the `guard` module docs for the
+/// exact triggers and the `ALLOW_FULL_SCAN` escape hatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteGuard {
+    /// Accept such statements silently.
+    #[default]
+    Off,
+    /// Accept them and record an [`AnalyzerWarning`](super::AnalyzerWarning).
+    Warn,
+    /// Reject them with an error.
+    Error,
+}
+
+/// What the analyzer does with a `NATURAL JOIN` whose inputs share no
+/// column names. The standard treats such a join as a cross join, which is
+/// rarely what the author meant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisjointNaturalJoin {
+    /// Accept the join silently as a cross join.
+    Off,
+    /// Accept it and record an [`AnalyzerWarning`](super::AnalyzerWarning).
+    Warn,
+    /// Reject it with an error.
+    #[default]
+    Error,
+}
+
+/// What arithmetic mixing `NUMERIC` and floating-point operands produces.
+/// Exact results are usually intended where NUMERIC is used, so the
+/// implicit conversion to an approximate type can be rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumericFloatArithmetic {
+    /// The result is `DOUBLE PRECISION`.
+    #[default]
+    Float64,
+    /// Reject the operation with a type mismatch; one operand must be cast.
+    Error,
+}
+
+/// How the select list and HAVING clause of a grouped query are checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupByMode {
+    /// Accept columns that are neither grouped nor aggregated, as MySQL
+    /// without `ONLY_FULL_GROUP_BY` and SQLite do.
+    #[default]
+    Permissive,
+    /// Reject such columns with
+    /// [`AnalyzerErrorKind::NonAggregatedColumn`](super::AnalyzerErrorKind::NonAggregatedColumn),
+    /// as the SQL standard requires.
+    Strict,
+}
+
+/// How unquoted identifiers are folded when they name output columns.
+/// Quoted identifiers always keep their case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdentifierCase {
+    /// Keep the identifier as written.
+    #[default]
+    Preserve,
+    /// Fold to lower case, as PostgreSQL does.
+    Lower,
+    /// Fold to upper case, as the SQL standard, Oracle, and Snowflake do.
+    Upper,
+}
+
+impl IdentifierCase {
+    /// The name an identifier gives an output column.
+    pub fn fold(self, ident: &Ident) -> String {
+        match self {
+            _ if ident.quoted => ident.value.clone(),
+            IdentifierCase::Preserve => ident.value.clone(),
+            IdentifierCase::Lower => ident.value.to_lowercase(),
+            IdentifierCase::Upper => ident.value.to_uppercase(),
+        }
+    }
+}
+
+/// Where NULLs sort when an ORDER BY item omits `NULLS FIRST`/`NULLS LAST`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullOrdering {
+    /// NULLs sort above every other value: last for ASC, first for DESC.
+    /// This is the PostgreSQL and Oracle behavior.
+    #[default]
+    NullsHigh,
+    /// NULLs sort below every other value: first for ASC, last for DESC.
+    /// This is the MySQL, SQLite, and SQL Server behavior.
+    NullsLow,
+}
+
+impl NullOrdering {
+    /// The effective null placement for a sort direction.
+    pub fn resolve(self, order: SortOrder) -> NullsOrder {
+        match (self, order) {
+            (NullOrdering::NullsHigh, SortOrder::Asc) => NullsOrder::Last,
+            (NullOrdering::NullsHigh, SortOrder::Desc) => NullsOrder::First,
+            (NullOrdering::NullsLow, SortOrder::Asc) => NullsOrder::First,
+            (NullOrdering::NullsLow, SortOrder::Desc) => NullsOrder::Last,
+        }
+    }
+}
+
+/// Options controlling analyzer behavior.
+#[derive(Debug, Clone)]
+pub struct AnalyzerOptions {
+    /// Wildcard expansion over `USING`/`NATURAL` joins.
+    pub using_wildcard: UsingWildcardMode,
+    /// Maximum number of CTE definitions analyzed for a single statement,
+    /// counting nested WITH clauses. Guards against pathological generated
+    /// queries.
+    pub max_ctes: usize,
+    /// How table aliases, CTE names, and column names are matched in scope.
+    pub identifier_matching: IdentifierMatching,
+    /// Allow string literals to be assigned to non-string columns (for
+    /// example `SET age = '30'`). The implicit cast is recorded on the
+    /// analysis result instead of being rejected.
+    pub lenient_string_assignment: bool,
+    /// Default null placement for ORDER BY items without `NULLS FIRST` or
+    /// `NULLS LAST`.
+    pub null_ordering: NullOrdering,
+    /// Policy for writes without a filtering WHERE clause.
+    pub unguarded_writes: WriteGuard,
+    /// Policy for `NATURAL JOIN`s whose inputs share no column names.
+    pub disjoint_natural_join: DisjointNaturalJoin,
+    /// Result of arithmetic between `NUMERIC` and floating-point operands.
+    pub numeric_float_arithmetic: NumericFloatArithmetic,
+    /// How columns that are neither grouped nor aggregated are treated.
+    pub group_by: GroupByMode,
+    /// How unquoted identifiers are folded in output column names.
+    pub identifier_case: IdentifierCase,
+    /// Whether `||` concatenates strings. When false it is logical OR, as
+    /// in MySQL, though it still parses with concatenation precedence.
+    pub pipes_as_concat: bool,
+    /// Whether comma-separated FROM items, which form an implicit cross
+    /// join, are allowed.
+    pub allow_comma_joins: bool,
+    /// Maximum nesting depth of a single expression, if limited. Defaults
+    /// to the parser's [`DEFAULT_MAX_DEPTH`](crate::parser::DEFAULT_MAX_DEPTH),
+    /// so that checking a tree built by hand cannot overflow the stack.
+    /// The links of an operator chain such as `a + b + c` count as one
+    /// level, since checking walks them in a loop.
+    pub max_expr_depth: Option<usize>,
+    /// The session whose variables `@@name` references read. Without one,
+    /// a system variable has an unknown type.
+    pub session: Option<Arc<Session>>,
+}
+
+impl Default for AnalyzerOptions {
+    fn default() -> Self {
+        Self {
+            using_wildcard: UsingWildcardMode::default(),
+            max_ctes: 1024,
+            identifier_matching: IdentifierMatching::default(),
+            lenient_string_assignment: false,
+            null_ordering: NullOrdering::default(),
+            unguarded_writes: WriteGuard::default(),
+            disjoint_natural_join: DisjointNaturalJoin::default(),
+            numeric_float_arithmetic: NumericFloatArithmetic::default(),
+            group_by: GroupByMode::default(),
+            identifier_case: IdentifierCase::default(),
+            pipes_as_concat: true,
+            allow_comma_joins: true,
+            max_expr_depth: Some(crate::parser::DEFAULT_MAX_DEPTH),
+            session: None,
+        }
+    }
+}
+
+impl AnalyzerOptions {
+    /// Create options with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the wildcard expansion mode for `USING`/`NATURAL` joins.
+    pub fn with_using_wildcard(mut self, mode: UsingWildcardMode) -> Self {
+        self.using_wildcard = mode;
+        self
+    }
+
+    /// Set the maximum number of CTE definitions per statement.
+    pub fn with_max_ctes(mut self, max_ctes: usize) -> Self {
+        self.max_ctes = max_ctes;
+        self
+    }
+
+    /// Set the identifier matching policy used for scope lookups.
+    pub fn with_identifier_matching(mut self, matching: IdentifierMatching) -> Self {
+        self.identifier_matching = matching;
+        self
+    }
+
+    /// Allow string literals to be implicitly cast in assignments.
+    pub fn with_lenient_string_assignment(mut self, lenient: bool) -> Self {
+        self.lenient_string_assignment = lenient;
+        self
+    }
+
+    /// Set the policy for writes without a filtering WHERE clause.
+    pub fn with_unguarded_writes(mut self, guard: WriteGuard) -> Self {
+        self.unguarded_writes = guard;
+        self
+    }
+
+    /// Set the policy for `NATURAL JOIN`s without common columns.
+    pub fn with_disjoint_natural_join(mut self, policy: DisjointNaturalJoin) -> Self {
+        self.disjoint_natural_join = policy;
+        self
+    }
+
+    /// Set the default null placement for ORDER BY.
+    pub fn with_null_ordering(mut self, ordering: NullOrdering) -> Self {
+        self.null_ordering = ordering;
+        self
+    }
+
+    /// Set the result of arithmetic between `NUMERIC` and floating point.
+    pub fn with_numeric_float_arithmetic(mut self, policy: NumericFloatArithmetic) -> Self {
+        self.numeric_float_arithmetic = policy;
+        self
+    }
+
+    /// Set how columns that are neither grouped nor aggregated are treated.
+    pub fn with_group_by(mut self, mode: GroupByMode) -> Self {
+        self.group_by = mode;
+        self
+    }
+
+    /// Set how unquoted identifiers are folded in output column names.
+    pub fn with_identifier_case(mut self, case: IdentifierCase) -> Self {
+        self.identifier_case = case;
+        self
+    }
+
+    /// Set whether `||` concatenates strings or is logical OR.
+    pub fn with_pipes_as_concat(mut self, pipes_as_concat: bool) -> Self {
+        self.pipes_as_concat = pipes_as_concat;
+        self
+    }
+
+    /// Allow or reject comma-separated FROM items.
+    pub fn with_comma_joins(mut self, allow: bool) -> Self {
+        self.allow_comma_joins = allow;
+        self
+    }
+
+    /// Limit the nesting depth of expressions.
+    pub fn with_max_expr_depth(mut self, depth: usize) -> Self {
+        self.max_expr_depth = Some(depth);
+        self
+    }
+
+    /// Type `@@name` references by the variables of `session`. A variable
+    /// the session does not define is an error, unless the session is
+    /// permissive.
+    pub fn with_session(mut self, session: impl Into<Arc<Session>>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+}
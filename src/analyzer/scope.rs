@@ -1,17 +1,37 @@
 //! Scope management for semantic analysis.
 
+use super::error::{AnalyzerError, AnalyzerErrorKind};
+use super::{AnalyzedQuery, ColumnOrigin};
+use crate::ast::{IdentifierMatching, WindowSpec};
+use crate::error::Span;
 use crate::types::SqlType;
 use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
 
 /// A scope for name resolution.
+///
+/// An unqualified column name resolves through three layers, in priority
+/// order (see [`resolve_column`](Self::resolve_column)):
+///
+/// 1. output column aliases, registered for clauses such as ORDER BY that
+///    can see them;
+/// 2. the columns of this scope's tables, with coalesced join columns
+///    first;
+/// 3. the scopes of enclosing queries, nearest first.
 #[derive(Debug, Clone)]
 pub struct Scope {
-    /// Tables available in this scope (keyed by alias or name).
-    tables: HashMap<String, ScopeTable>,
+    /// Tables available in this scope, in the order they were added.
+    tables: Vec<ScopeTable>,
     /// CTEs available in this scope.
     ctes: HashMap<String, CteRef>,
-    /// Named expressions (for SELECT aliases that can be referenced).
-    named_exprs: HashMap<String, ExprRef>,
+    /// Named expressions (for SELECT aliases that can be referenced), in
+    /// select list order.
+    named_exprs: Vec<ExprRef>,
+    /// Coalesced columns produced by USING/NATURAL joins.
+    join_columns: Vec<ScopeColumn>,
+    /// Named windows from the WINDOW clause.
+    windows: HashMap<String, WindowSpec>,
     /// Whether this scope allows aggregates.
     pub allows_aggregates: bool,
     /// Whether we're inside an aggregate function.
@@ -22,6 +42,10 @@ pub struct Scope {
     pub group_by_columns: Vec<String>,
     /// Whether GROUP BY is present.
     pub has_group_by: bool,
+    /// How names are compared in this scope.
+    matching: IdentifierMatching,
+    /// The scope of the enclosing query, for a subquery's scope.
+    outer: Option<Arc<Scope>>,
 }
 
 /// Reference to a table in scope (renamed to avoid collision with ast::TableRef).
@@ -59,6 +83,11 @@ pub struct CteRef {
     pub columns: Vec<ScopeColumn>,
     /// Whether this is a recursive CTE.
     pub is_recursive: bool,
+    /// Where the CTE is defined.
+    pub span: Span,
+    /// The analysis of the CTE body, computed once when the CTE is added to
+    /// scope and shared by every reference to it.
+    pub analyzed: Option<Arc<AnalyzedQuery>>,
 }
 
 /// Reference to a named expression (SELECT alias).
@@ -72,66 +101,244 @@ pub struct ExprRef {
     pub nullable: bool,
     /// Position in select list.
     pub ordinal: usize,
+    /// The table column the expression reads, when it is a plain column
+    /// reference.
+    pub source: Option<ColumnOrigin>,
 }
 
 impl Scope {
     /// Create a new empty scope.
     pub fn new() -> Self {
+        Self::with_matching(IdentifierMatching::default())
+    }
+
+    /// Create a new empty scope using the given identifier matching policy.
+    pub fn with_matching(matching: IdentifierMatching) -> Self {
         Self {
-            tables: HashMap::new(),
+            tables: Vec::new(),
             ctes: HashMap::new(),
-            named_exprs: HashMap::new(),
+            named_exprs: Vec::new(),
+            join_columns: Vec::new(),
+            windows: HashMap::new(),
             allows_aggregates: true,
             in_aggregate: false,
             in_window: false,
             group_by_columns: Vec::new(),
             has_group_by: false,
+            matching,
+            outer: None,
         }
     }
 
+    /// Chain this scope to the scope of an enclosing query, whose columns
+    /// expressions in this scope may reference as correlated columns.
+    pub fn with_outer(mut self, outer: Option<Arc<Scope>>) -> Self {
+        self.outer = outer;
+        self
+    }
+
+    /// The scope of the enclosing query, if this is a subquery's scope.
+    pub fn outer(&self) -> Option<&Scope> {
+        self.outer.as_deref()
+    }
+
+    /// The identifier matching policy of this scope.
+    pub fn matching(&self) -> IdentifierMatching {
+        self.matching
+    }
+
     /// Add a table to this scope.
-    pub fn add_table(&mut self, table: ScopeTable) {
-        let key = table.alias.to_lowercase();
-        self.tables.insert(key, table);
+    ///
+    /// Fails with [`AnalyzerErrorKind::DuplicateAlias`] if a table with the
+    /// same alias is already in scope, as in `FROM users a JOIN orders a`.
+    pub fn add_table(&mut self, table: ScopeTable) -> Result<(), AnalyzerError> {
+        if self.table_index(&table.alias).is_some() {
+            return Err(AnalyzerError::new(AnalyzerErrorKind::DuplicateAlias {
+                name: table.alias,
+            }));
+        }
+        self.tables.push(table);
+        Ok(())
+    }
+
+    /// Add a table whose alias the analyzer made up, such as `_subquery`
+    /// for a derived table without one. It replaces an earlier table with
+    /// the same alias in its original position.
+    pub(crate) fn add_unnamed_table(&mut self, table: ScopeTable) {
+        match self.table_index(&table.alias) {
+            Some(i) => self.tables[i] = table,
+            None => self.tables.push(table),
+        }
+    }
+
+    /// Position of the table with the given alias.
+    fn table_index(&self, name: &str) -> Option<usize> {
+        let key = self.matching.normalize(name);
+        self.tables
+            .iter()
+            .position(|t| self.matching.normalize(&t.alias) == key)
     }
 
     /// Add a CTE to this scope.
     pub fn add_cte(&mut self, cte: CteRef) {
-        let key = cte.name.to_lowercase();
+        let key = self.matching.normalize(&cte.name);
         self.ctes.insert(key, cte);
     }
 
     /// Add a named expression (SELECT alias).
+    ///
+    /// A name may be added several times, as in `SELECT a AS x, b AS x`,
+    /// which makes it ambiguous. Another expression with the same name
+    /// that reads the same table column is not added.
     pub fn add_named_expr(&mut self, expr: ExprRef) {
-        let key = expr.name.to_lowercase();
-        self.named_exprs.insert(key, expr);
+        let duplicate = self.named_exprs.iter().any(|e| {
+            self.matching.matches(&e.name, &expr.name)
+                && e.source.is_some()
+                && e.source == expr.source
+        });
+        if !duplicate {
+            self.named_exprs.push(expr);
+        }
+    }
+
+    /// Add a coalesced join column (from `USING` or `NATURAL`).
+    ///
+    /// Unqualified references to the column name resolve to the most
+    /// recently added join column instead of being ambiguous between the
+    /// two join inputs. Qualified references still reach the originals.
+    pub fn add_join_column(&mut self, column: ScopeColumn) {
+        self.join_columns.push(column);
+    }
+
+    /// Number of tables in scope.
+    pub(crate) fn table_count(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Number of coalesced join columns in scope.
+    pub(crate) fn join_column_count(&self) -> usize {
+        self.join_columns.len()
     }
 
-    /// Look up a table by name (case-insensitive).
+    /// Mark every column of the given tables and coalesced join columns
+    /// nullable, for the null-extended side of an outer join. The ranges
+    /// are positions as counted by [`table_count`](Self::table_count) and
+    /// [`join_column_count`](Self::join_column_count).
+    pub(crate) fn null_extend(&mut self, tables: Range<usize>, join_columns: Range<usize>) {
+        for table in &mut self.tables[tables] {
+            for col in &mut table.columns {
+                col.nullable = true;
+            }
+        }
+        for col in &mut self.join_columns[join_columns] {
+            col.nullable = true;
+        }
+    }
+
+    /// Add a named window from the WINDOW clause.
+    pub fn add_window(&mut self, name: &str, spec: WindowSpec) {
+        let key = self.matching.normalize(name);
+        self.windows.insert(key, spec);
+    }
+
+    /// Look up a named window by name.
+    pub fn lookup_window(&self, name: &str) -> Option<&WindowSpec> {
+        self.windows.get(&self.matching.normalize(name))
+    }
+
+    /// Look up a coalesced join column by name.
+    pub fn lookup_join_column(&self, name: &str) -> Option<&ScopeColumn> {
+        let key = self.matching.normalize(name);
+        self.join_columns
+            .iter()
+            .rev()
+            .find(|c| self.matching.normalize(&c.name) == key)
+    }
+
+    /// Look up a table by name.
     pub fn lookup_table(&self, name: &str) -> Option<&ScopeTable> {
-        self.tables.get(&name.to_lowercase())
+        self.table_index(name).map(|i| &self.tables[i])
     }
 
-    /// Look up a CTE by name (case-insensitive).
+    /// Look up a CTE by name.
     pub fn lookup_cte(&self, name: &str) -> Option<&CteRef> {
-        self.ctes.get(&name.to_lowercase())
+        self.ctes.get(&self.matching.normalize(name))
     }
 
-    /// Look up a named expression by name (case-insensitive).
+    /// Look up a named expression by name, the first if there are several.
     pub fn lookup_named_expr(&self, name: &str) -> Option<&ExprRef> {
-        self.named_exprs.get(&name.to_lowercase())
+        self.named_exprs
+            .iter()
+            .find(|e| self.matching.matches(&e.name, name))
+    }
+
+    /// Resolve an unqualified column name through every layer of this
+    /// scope, in priority order: output aliases, then this scope's table
+    /// and join columns, then enclosing scopes. A match in one layer hides
+    /// the later ones, and a name that matches several candidates in the
+    /// first layer it appears in is ambiguous.
+    pub fn resolve_column(&self, name: &str) -> ColumnLookupResult {
+        let aliases: Vec<&ExprRef> = self
+            .named_exprs
+            .iter()
+            .filter(|e| self.matching.matches(&e.name, name))
+            .collect();
+        match aliases.as_slice() {
+            [] => {}
+            [alias] => return ColumnLookupResult::Alias((*alias).clone()),
+            _ => {
+                let candidates = aliases
+                    .iter()
+                    .map(|e| match &e.source {
+                        Some(source) => format!("{}.{}", source.table, source.column),
+                        None => e.name.clone(),
+                    })
+                    .collect();
+                return ColumnLookupResult::Ambiguous(candidates);
+            }
+        }
+
+        match self.lookup_column(name) {
+            ColumnLookupResult::NotFound => {}
+            result => return result,
+        }
+
+        let mut outer = self.outer();
+        let mut depth = 1;
+        while let Some(scope) = outer {
+            match scope.lookup_column(name) {
+                ColumnLookupResult::NotFound => {}
+                ColumnLookupResult::Found(table, column) => {
+                    return ColumnLookupResult::Outer {
+                        table,
+                        column,
+                        depth,
+                    };
+                }
+                result => return result,
+            }
+            outer = scope.outer();
+            depth += 1;
+        }
+        ColumnLookupResult::NotFound
     }
 
-    /// Look up a column by name across all tables.
-    /// Returns (table_ref, column_ref) or None.
-    /// Returns an error indicator if ambiguous.
+    /// Look up a column by name across the tables of this scope, the
+    /// second layer of [`resolve_column`](Self::resolve_column). A name in
+    /// several tables is ambiguous, with the candidates qualified by their
+    /// tables.
     pub fn lookup_column(&self, name: &str) -> ColumnLookupResult {
-        let name_lower = name.to_lowercase();
+        if let Some(col) = self.lookup_join_column(name) {
+            let table = ScopeTable::new(col.table_alias.clone(), Vec::new(), vec![col.clone()]);
+            return ColumnLookupResult::Found(table, col.clone());
+        }
+
+        let key = self.matching.normalize(name);
         let mut found: Vec<(&ScopeTable, &ScopeColumn)> = Vec::new();
 
-        for table in self.tables.values() {
+        for table in &self.tables {
             for col in &table.columns {
-                if col.name.to_lowercase() == name_lower {
+                if self.matching.normalize(&col.name) == key {
                     found.push((table, col));
                 }
             }
@@ -141,8 +348,11 @@ impl Scope {
             0 => ColumnLookupResult::NotFound,
             1 => ColumnLookupResult::Found(found[0].0.clone(), found[0].1.clone()),
             _ => {
-                let tables: Vec<String> = found.iter().map(|(t, _)| t.alias.clone()).collect();
-                ColumnLookupResult::Ambiguous(tables)
+                let candidates = found
+                    .iter()
+                    .map(|(t, c)| format!("{}.{}", t.alias, c.name))
+                    .collect();
+                ColumnLookupResult::Ambiguous(candidates)
             }
         }
     }
@@ -154,35 +364,65 @@ impl Scope {
         column_name: &str,
     ) -> Option<ScopeColumn> {
         let table = self.lookup_table(table_name)?;
-        let col_lower = column_name.to_lowercase();
+        let key = self.matching.normalize(column_name);
         table
             .columns
             .iter()
-            .find(|c| c.name.to_lowercase() == col_lower)
+            .find(|c| self.matching.normalize(&c.name) == key)
             .cloned()
     }
 
-    /// Get all tables in scope.
+    /// Get all tables in scope, in the order they were added.
+    ///
+    /// The analyzer adds FROM clause tables left to right as they appear in
+    /// the query text, descending into parenthesized joins in place.
     pub fn all_tables(&self) -> impl Iterator<Item = &ScopeTable> {
-        self.tables.values()
+        self.tables.iter()
     }
 
-    /// Get all columns across all tables.
+    /// Get all columns across all tables, in table order and then column
+    /// order.
     pub fn all_columns(&self) -> Vec<&ScopeColumn> {
+        self.tables.iter().flat_map(|t| t.columns.iter()).collect()
+    }
+
+    /// Names of the columns `table`, or any table when `None`, provides,
+    /// as candidates for a misspelled column.
+    pub(crate) fn column_names(&self, table: Option<&str>) -> Vec<&str> {
+        match table {
+            Some(table) => self
+                .lookup_table(table)
+                .map(|t| t.columns.iter().map(|c| c.name.as_str()).collect())
+                .unwrap_or_default(),
+            None => self
+                .join_columns
+                .iter()
+                .chain(self.tables.iter().flat_map(|t| &t.columns))
+                .map(|c| c.name.as_str())
+                .collect(),
+        }
+    }
+
+    /// Names of the tables and CTEs in scope, as candidates for a
+    /// misspelled table.
+    pub(crate) fn table_names(&self) -> Vec<&str> {
+        let mut ctes: Vec<&str> = self.ctes.values().map(|c| c.name.as_str()).collect();
+        ctes.sort_unstable();
         self.tables
-            .values()
-            .flat_map(|t| t.columns.iter())
+            .iter()
+            .map(|t| t.alias.as_str())
+            .chain(ctes)
             .collect()
     }
 
     /// Check if a table name exists in scope.
     pub fn has_table(&self, name: &str) -> bool {
-        self.tables.contains_key(&name.to_lowercase())
+        self.table_index(name).is_some()
     }
 
     /// Check if a CTE name exists in scope.
     pub fn has_cte(&self, name: &str) -> bool {
-        self.ctes.contains_key(&name.to_lowercase())
+        self.ctes.contains_key(&self.matching.normalize(name))
     }
 }
 
@@ -195,11 +435,19 @@ impl Default for Scope {
 /// Result of looking up a column.
 #[derive(Debug, Clone)]
 pub enum ColumnLookupResult {
-    /// Column found uniquely.
+    /// Column found uniquely among the tables of the scope.
     Found(ScopeTable, ScopeColumn),
+    /// Name found as an output column alias.
+    Alias(ExprRef),
+    /// Column found in the scope of an enclosing query, `depth` levels up.
+    Outer {
+        table: ScopeTable,
+        column: ScopeColumn,
+        depth: usize,
+    },
     /// Column not found.
     NotFound,
-    /// Column found in multiple tables (ambiguous).
+    /// Name with several candidates, such as `t1.id` and `t2.id`.
     Ambiguous(Vec<String>),
 }
 
@@ -262,14 +510,14 @@ mod tests {
                 ),
                 ScopeColumn::new(
                     "name".to_string(),
-                    SqlType::Varchar,
+                    SqlType::Varchar(None),
                     true,
                     "users".to_string(),
                     1,
                 ),
             ],
         );
-        scope.add_table(table);
+        scope.add_table(table).unwrap();
 
         assert!(scope.has_table("users"));
         assert!(scope.has_table("USERS")); // case-insensitive
@@ -284,34 +532,136 @@ mod tests {
     fn test_scope_ambiguous_column() {
         let mut scope = Scope::new();
 
-        scope.add_table(ScopeTable::new(
-            "t1".to_string(),
-            vec!["table1".to_string()],
-            vec![ScopeColumn::new(
-                "id".to_string(),
-                SqlType::Int64,
-                false,
+        scope
+            .add_table(ScopeTable::new(
                 "t1".to_string(),
-                0,
-            )],
-        ));
-        scope.add_table(ScopeTable::new(
-            "t2".to_string(),
-            vec!["table2".to_string()],
-            vec![ScopeColumn::new(
-                "id".to_string(),
-                SqlType::Int64,
-                false,
+                vec!["table1".to_string()],
+                vec![ScopeColumn::new(
+                    "id".to_string(),
+                    SqlType::Int64,
+                    false,
+                    "t1".to_string(),
+                    0,
+                )],
+            ))
+            .unwrap();
+        scope
+            .add_table(ScopeTable::new(
                 "t2".to_string(),
-                0,
-            )],
-        ));
+                vec!["table2".to_string()],
+                vec![ScopeColumn::new(
+                    "id".to_string(),
+                    SqlType::Int64,
+                    false,
+                    "t2".to_string(),
+                    0,
+                )],
+            ))
+            .unwrap();
 
         match scope.lookup_column("id") {
             ColumnLookupResult::Ambiguous(tables) => {
-                assert_eq!(tables.len(), 2);
+                assert_eq!(tables, ["t1.id", "t2.id"]);
             }
             _ => panic!("Expected ambiguous result"),
         }
     }
+
+    #[test]
+    fn test_scope_tables_in_insertion_order() {
+        let table = |alias: &str, column: &str| {
+            ScopeTable::new(
+                alias.to_string(),
+                vec![alias.to_string()],
+                vec![ScopeColumn::new(
+                    column.to_string(),
+                    SqlType::Int64,
+                    false,
+                    alias.to_string(),
+                    0,
+                )],
+            )
+        };
+        let mut scope = Scope::new();
+        for (alias, column) in [("z", "c1"), ("a", "c2"), ("_subquery", "c3")] {
+            scope.add_table(table(alias, column)).unwrap();
+        }
+        scope.add_unnamed_table(table("_subquery", "c4"));
+        scope.add_table(table("m", "c5")).unwrap();
+
+        let aliases: Vec<&str> = scope.all_tables().map(|t| t.alias.as_str()).collect();
+        assert_eq!(aliases, ["z", "a", "_subquery", "m"]);
+        let columns: Vec<&str> = scope
+            .all_columns()
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(columns, ["c1", "c2", "c4", "c5"]);
+
+        assert!(scope.lookup_table("missing").is_none());
+        assert!(scope.lookup_qualified_column("missing", "c1").is_none());
+        assert!(scope.lookup_qualified_column("a", "c1").is_none());
+    }
+
+    #[test]
+    fn test_scope_resolution_layers() {
+        let table = |alias: &str, column: &str| {
+            ScopeTable::new(
+                alias.to_string(),
+                vec![alias.to_string()],
+                vec![ScopeColumn::new(
+                    column.to_string(),
+                    SqlType::Int64,
+                    false,
+                    alias.to_string(),
+                    0,
+                )],
+            )
+        };
+        let alias = |name: &str, ordinal: usize| ExprRef {
+            name: name.to_string(),
+            data_type: SqlType::Bool,
+            nullable: true,
+            ordinal,
+            source: None,
+        };
+
+        let mut outer = Scope::new();
+        outer.add_table(table("o", "x")).unwrap();
+        let mut scope = Scope::new().with_outer(Some(Arc::new(outer)));
+        scope.add_table(table("t", "id")).unwrap();
+        let err = scope.add_table(table("T", "other")).unwrap_err();
+        assert!(matches!(err.kind, AnalyzerErrorKind::DuplicateAlias { .. }));
+
+        assert!(matches!(
+            scope.resolve_column("id"),
+            ColumnLookupResult::Found(..)
+        ));
+        assert!(matches!(
+            scope.resolve_column("x"),
+            ColumnLookupResult::Outer { depth: 1, .. }
+        ));
+        assert!(matches!(
+            scope.resolve_column("y"),
+            ColumnLookupResult::NotFound
+        ));
+
+        // Output aliases hide table columns; `lookup_column` reads only the
+        // table layer.
+        scope.add_named_expr(alias("id", 0));
+        match scope.resolve_column("id") {
+            ColumnLookupResult::Alias(expr) => assert_eq!(expr.ordinal, 0),
+            other => panic!("expected alias, got {:?}", other),
+        }
+        assert!(matches!(
+            scope.lookup_column("id"),
+            ColumnLookupResult::Found(..)
+        ));
+
+        scope.add_named_expr(alias("id", 1));
+        match scope.resolve_column("id") {
+            ColumnLookupResult::Ambiguous(candidates) => assert_eq!(candidates, ["id", "id"]),
+            other => panic!("expected ambiguous, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,77 @@
+//! "Did you mean" suggestions for names that do not resolve.
+
+/// The candidate most likely meant by a misspelled `name`.
+///
+/// Names are compared case-insensitively, so a candidate that differs only
+/// in case is always suggested. Other candidates qualify within an edit
+/// distance of a third of the name's length, rounded up, where swapping
+/// two adjacent characters counts as one edit. The closest candidate wins
+/// and ties go to the one listed first.
+pub(crate) fn closest<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let target: Vec<char> = name.to_lowercase().chars().collect();
+    let limit = target.len().div_ceil(3).max(1);
+    let mut best: Option<(usize, &str)> = None;
+    for candidate in candidates {
+        if candidate == name {
+            continue;
+        }
+        let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+        let distance = edit_distance(&target, &chars);
+        if distance <= limit && best.is_none_or(|(closest, _)| distance < closest) {
+            best = Some((distance, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate.to_string())
+}
+
+/// Optimal string alignment distance: insertions, deletions,
+/// substitutions, and transpositions of adjacent characters.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest() {
+        let columns = ["id", "name", "age", "email"];
+        assert_eq!(closest("nmae", columns).as_deref(), Some("name"));
+        assert_eq!(closest("NAME", columns).as_deref(), Some("name"));
+        assert_eq!(closest("emial", columns).as_deref(), Some("email"));
+        assert_eq!(closest("ag", columns).as_deref(), Some("age"));
+        assert_eq!(closest("address", columns), None);
+
+        // The closest candidate beats an earlier, farther one; equally
+        // close candidates go to the first listed.
+        assert_eq!(
+            closest("user_ids", ["users", "user_id"]).as_deref(),
+            Some("user_id")
+        );
+        assert_eq!(
+            closest("cat", ["bat", "cab", "car"]).as_deref(),
+            Some("bat")
+        );
+    }
+}
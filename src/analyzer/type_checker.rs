@@ -1,14 +1,100 @@
 //! Type checking for SQL expressions.
 
-use super::error::AnalyzerError;
+use super::constant;
+use super::error::{AnalyzerError, AnalyzerErrorKind};
+use super::explain::ExplainRecorder;
+use super::options::{AnalyzerOptions, NullOrdering, NumericFloatArithmetic};
 use super::scope::{ColumnLookupResult, Scope};
+use super::warning::{AnalyzerWarning, AnalyzerWarningKind};
+use super::{AnalyzedQuery, Analyzer, OutputColumn, ParameterType};
 use crate::ast::*;
-use crate::catalog::Catalog;
-use crate::types::SqlType;
+use crate::catalog::{Catalog, FunctionSignature};
+use crate::error::{ErrorKind, Span};
+use crate::session::Session;
+use crate::types::{ArithmeticOp, CastKind, SqlType, Value};
+use std::cell::{Cell, RefCell};
+use std::sync::Arc;
 
 /// Type checker for SQL expressions.
 pub struct TypeChecker<'a, C: Catalog> {
     catalog: &'a C,
+    /// Window function calls seen while checking, in source order.
+    windows: RefCell<Vec<TypedWindow>>,
+    /// Default null placement for ORDER BY items.
+    null_ordering: NullOrdering,
+    /// Result of arithmetic between NUMERIC and floating point.
+    numeric_float_arithmetic: NumericFloatArithmetic,
+    /// Whether `||` concatenates, rather than being logical OR.
+    pipes_as_concat: bool,
+    /// Maximum expression nesting depth, if limited.
+    max_expr_depth: Option<usize>,
+    /// The session that types system variables, if any.
+    session: Option<Arc<Session>>,
+    /// Nesting depth of the expression being checked.
+    depth: Cell<usize>,
+    /// Where inferred parameter types are recorded, if anywhere.
+    parameters: Option<&'a RefCell<Vec<ParameterType>>>,
+    /// Where warnings are recorded, if anywhere.
+    warnings: Option<&'a RefCell<Vec<AnalyzerWarning>>>,
+    /// The clause being checked, if it rejects aggregate calls.
+    aggregates_forbidden_in: Option<&'static str>,
+    /// The clause being checked, if it rejects window function calls.
+    windows_forbidden_in: Option<&'static str>,
+    /// How to analyze subqueries, if they are analyzed.
+    subqueries: Option<SubqueryContext<'a>>,
+}
+
+/// What a type checker needs from its analyzer to analyze subqueries.
+#[derive(Clone, Copy)]
+pub(crate) struct SubqueryContext<'a> {
+    /// The analyzer's options, which subqueries are analyzed with.
+    pub(crate) options: &'a AnalyzerOptions,
+    /// The analyzer's scope stack, for the CTEs it defines.
+    pub(crate) scopes: &'a [Scope],
+    /// Where the analyzer tracks how many query levels up its furthest
+    /// correlated column reference reaches.
+    pub(crate) outer_depth: &'a Cell<usize>,
+    /// Where the analyzer records the query it explains, if it is
+    /// explaining one.
+    pub(crate) explain: Option<&'a ExplainRecorder>,
+}
+
+/// A window function call with its resolved window specification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedWindow {
+    /// Function name (uppercase).
+    pub function: String,
+    /// Result type of the call.
+    pub data_type: SqlType,
+    /// The window specification, with named window references resolved.
+    /// The frame, including any exclusion, is carried through unchanged.
+    pub spec: WindowSpec,
+    /// The window's ORDER BY, resolved.
+    pub sort_keys: Vec<SortKey>,
+}
+
+/// A resolved ORDER BY item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    /// What is sorted.
+    pub target: SortTarget,
+    /// Type of the sorted value.
+    pub data_type: SqlType,
+    /// Sort direction, ASC when omitted.
+    pub order: SortOrder,
+    /// Null placement, as written or from the [`NullOrdering`] default.
+    pub nulls: NullsOrder,
+    /// Collation name. Always `None` until COLLATE is supported.
+    pub collation: Option<String>,
+}
+
+/// The value a [`SortKey`] orders by.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortTarget {
+    /// An output column of the query, by zero-based index.
+    Output(usize),
+    /// An expression evaluated per input row.
+    Expr(Box<Expr>),
 }
 
 /// Result of type checking an expression.
@@ -22,6 +108,15 @@ pub struct TypedExpr {
     pub contains_aggregate: bool,
     /// Whether the expression contains a window function.
     pub contains_window: bool,
+    /// Whether the expression is built from literals only, with operators
+    /// and casts, so it has the same value for every row.
+    pub is_constant: bool,
+    /// The value of a constant expression, when the analyzer can compute
+    /// it. Integer overflow and division by zero leave it unknown.
+    pub constant_value: Option<Value>,
+    /// Whether the expression contains a subquery that references columns
+    /// of an enclosing query, so it must be evaluated for each row.
+    pub correlated: bool,
 }
 
 impl TypedExpr {
@@ -32,16 +127,28 @@ impl TypedExpr {
             nullable: false,
             contains_aggregate: false,
             contains_window: false,
+            is_constant: false,
+            constant_value: None,
+            correlated: false,
         }
     }
 
     /// Create a nullable typed expression.
     pub fn nullable(data_type: SqlType) -> Self {
         Self {
-            data_type,
             nullable: true,
-            contains_aggregate: false,
-            contains_window: false,
+            ..Self::non_null(data_type)
+        }
+    }
+
+    /// Create a constant expression, with its value if known. It is
+    /// nullable only if the value is known to be NULL.
+    pub fn constant(data_type: SqlType, value: Option<Value>) -> Self {
+        Self {
+            nullable: value.as_ref().is_some_and(Value::is_null),
+            is_constant: true,
+            constant_value: value,
+            ..Self::non_null(data_type)
         }
     }
 }
@@ -49,77 +156,334 @@ impl TypedExpr {
 impl<'a, C: Catalog> TypeChecker<'a, C> {
     /// Create a new type checker.
     pub fn new(catalog: &'a C) -> Self {
-        Self { catalog }
+        Self {
+            catalog,
+            windows: RefCell::new(Vec::new()),
+            null_ordering: NullOrdering::default(),
+            numeric_float_arithmetic: NumericFloatArithmetic::default(),
+            pipes_as_concat: true,
+            max_expr_depth: Some(crate::parser::DEFAULT_MAX_DEPTH),
+            session: None,
+            depth: Cell::new(0),
+            parameters: None,
+            warnings: None,
+            aggregates_forbidden_in: None,
+            windows_forbidden_in: None,
+            subqueries: None,
+        }
+    }
+
+    /// Record the types inferred for parameter placeholders in `store`.
+    ///
+    /// A parameter takes the type its context expects: the other operand
+    /// of a comparison or arithmetic operator, the tested value of IN and
+    /// BETWEEN, the declared parameter type of a function argument, or the
+    /// target of a CAST. Parameters in other positions are recorded as
+    /// [`SqlType::Unknown`]. A parameter used several times keeps the
+    /// common supertype of its known types.
+    pub fn with_parameter_types(mut self, store: &'a RefCell<Vec<ParameterType>>) -> Self {
+        self.parameters = Some(store);
+        self
+    }
+
+    /// Reject aggregate calls, reporting `clause` (such as `"WHERE"`) as
+    /// the place they were misused.
+    pub fn with_aggregates_forbidden(mut self, clause: &'static str) -> Self {
+        self.aggregates_forbidden_in = Some(clause);
+        self
+    }
+
+    /// Reject window function calls, reporting `clause` (such as
+    /// `"HAVING"`) as the place they were misused.
+    pub fn with_windows_forbidden(mut self, clause: &'static str) -> Self {
+        self.windows_forbidden_in = Some(clause);
+        self
+    }
+
+    /// Set the default null placement for ORDER BY items.
+    pub fn with_null_ordering(mut self, ordering: NullOrdering) -> Self {
+        self.null_ordering = ordering;
+        self
+    }
+
+    /// Apply the expression-level analyzer options: null ordering, NUMERIC
+    /// and floating-point arithmetic, the meaning of `||`, the expression
+    /// depth limit, and the session.
+    pub fn with_options(mut self, options: &AnalyzerOptions) -> Self {
+        self.null_ordering = options.null_ordering;
+        self.numeric_float_arithmetic = options.numeric_float_arithmetic;
+        self.pipes_as_concat = options.pipes_as_concat;
+        self.max_expr_depth = options.max_expr_depth;
+        self.session = options.session.clone();
+        self
+    }
+
+    /// Set the result of arithmetic between NUMERIC and floating point.
+    pub fn with_numeric_float_arithmetic(mut self, policy: NumericFloatArithmetic) -> Self {
+        self.numeric_float_arithmetic = policy;
+        self
+    }
+
+    /// Record warnings, such as NUMERIC precision overflow, in `store`.
+    /// A warning for the same span is recorded once however often the
+    /// expression is checked.
+    pub fn with_warnings(mut self, store: &'a RefCell<Vec<AnalyzerWarning>>) -> Self {
+        self.warnings = Some(store);
+        self
+    }
+
+    /// Analyze the subqueries of checked expressions. Without this, scalar
+    /// subqueries have an unknown type and subqueries are not checked.
+    pub(crate) fn with_subqueries(mut self, context: SubqueryContext<'a>) -> Self {
+        self.subqueries = Some(context);
+        self
+    }
+
+    /// Resolve ORDER BY items that sort by expression, as in window
+    /// specifications and aggregate calls.
+    pub fn check_order_by(
+        &self,
+        items: &[OrderByExpr],
+        scope: &Scope,
+    ) -> Result<Vec<SortKey>, AnalyzerError> {
+        items
+            .iter()
+            .map(|item| {
+                let typed = self.check_expr(&item.expr, scope)?;
+                self.sort_key(item, SortTarget::Expr(item.expr.clone()), typed.data_type)
+            })
+            .collect()
+    }
+
+    /// Build a sort key, rejecting types without an ordering.
+    pub(crate) fn sort_key(
+        &self,
+        item: &OrderByExpr,
+        target: SortTarget,
+        data_type: SqlType,
+    ) -> Result<SortKey, AnalyzerError> {
+        if !data_type.is_orderable() {
+            return Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::NotOrderable { data_type },
+                item.expr.span,
+            ));
+        }
+        let order = item.order.unwrap_or(SortOrder::Asc);
+        Ok(SortKey {
+            target,
+            data_type,
+            order,
+            nulls: item
+                .nulls
+                .unwrap_or_else(|| self.null_ordering.resolve(order)),
+            collation: None,
+        })
+    }
+
+    /// Take the window function calls recorded by previous checks.
+    pub fn take_windows(&self) -> Vec<TypedWindow> {
+        self.windows.take()
+    }
+
+    /// Check an expression that its context expects to have type
+    /// `expected`. A bare parameter is given that type; anything else is
+    /// checked as usual.
+    pub fn check_expr_as(
+        &self,
+        expr: &Expr,
+        expected: &SqlType,
+        scope: &Scope,
+    ) -> Result<TypedExpr, AnalyzerError> {
+        match parameter_of(expr) {
+            Some(parameter) => {
+                self.record_parameter(parameter, expected);
+                Ok(TypedExpr::nullable(expected.clone()))
+            }
+            None => self.check_expr(expr, scope),
+        }
+    }
+
+    /// Record the type inferred for a parameter.
+    fn record_parameter(&self, parameter: &Parameter, data_type: &SqlType) {
+        let Some(store) = self.parameters else {
+            return;
+        };
+        let mut store = store.borrow_mut();
+        match store.iter_mut().find(|p| &p.parameter == parameter) {
+            Some(existing) if existing.data_type == SqlType::Unknown => {
+                existing.data_type = data_type.clone();
+            }
+            Some(existing) => {
+                if *data_type != SqlType::Unknown {
+                    if let Some(common) = existing.data_type.common_supertype(data_type) {
+                        existing.data_type = common;
+                    }
+                }
+            }
+            None => store.push(ParameterType {
+                parameter: parameter.clone(),
+                data_type: data_type.clone(),
+            }),
+        }
     }
 
     /// Check the type of an expression.
     pub fn check_expr(&self, expr: &Expr, scope: &Scope) -> Result<TypedExpr, AnalyzerError> {
+        let depth = self.depth.get() + 1;
+        if let Some(limit) = self.max_expr_depth.filter(|limit| depth > *limit) {
+            return Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::ExpressionTooDeep { limit },
+                expr.span,
+            ));
+        }
+        self.depth.set(depth);
+        let result = self.check_expr_kind(expr, scope);
+        self.depth.set(depth - 1);
+        result
+    }
+
+    fn check_expr_kind(&self, expr: &Expr, scope: &Scope) -> Result<TypedExpr, AnalyzerError> {
         match &expr.kind {
             // Literals
-            ExprKind::Null => Ok(TypedExpr::nullable(SqlType::Unknown)),
-            ExprKind::Boolean(_) => Ok(TypedExpr::non_null(SqlType::Bool)),
-            ExprKind::Integer(_) => Ok(TypedExpr::non_null(SqlType::Int64)),
-            ExprKind::Float(_) => Ok(TypedExpr::non_null(SqlType::Float64)),
-            ExprKind::String(_) => Ok(TypedExpr::non_null(SqlType::Varchar)),
-            ExprKind::Bytes(_) => Ok(TypedExpr::non_null(SqlType::Varbinary)),
+            ExprKind::Null => Ok(TypedExpr::constant(SqlType::Unknown, Some(Value::Null))),
+            ExprKind::Boolean(_) => Ok(self.literal(SqlType::Bool, expr)),
+            ExprKind::Integer(_) => Ok(self.literal(SqlType::Int64, expr)),
+            ExprKind::Float(_) => Ok(self.literal(SqlType::Float64, expr)),
+            ExprKind::String(_) => Ok(self.literal(SqlType::Varchar(None), expr)),
+            ExprKind::Bytes(_) => Ok(self.literal(SqlType::Varbinary(None), expr)),
 
             // Identifiers
-            ExprKind::Identifier(ident) => self.check_column(&ident.value, None, scope),
-            ExprKind::CompoundIdentifier(parts) => {
-                if parts.len() == 2 {
-                    self.check_column(&parts[1].value, Some(&parts[0].value), scope)
-                } else if parts.len() == 1 {
-                    self.check_column(&parts[0].value, None, scope)
-                } else {
-                    // For longer paths, use the last as column, second-to-last as table
-                    let col = &parts[parts.len() - 1].value;
-                    let table = &parts[parts.len() - 2].value;
-                    self.check_column(col, Some(table), scope)
-                }
-            }
+            ExprKind::Identifier(ident) => self
+                .resolve_in_scopes(scope, |s| self.check_column(&ident.value, None, s))
+                .map_err(|err| at_reference(err, expr.span)),
+            ExprKind::CompoundIdentifier(parts) => self
+                .resolve_in_scopes(scope, |s| self.check_compound_identifier(parts, s))
+                .map_err(|err| at_qualifier(err, parts, scope, expr.span)),
 
             // Operators
-            ExprKind::BinaryOp { op, left, right } => self.check_binary_op(*op, left, right, scope),
+            ExprKind::BinaryOp { .. } => self.check_binary_chain(expr, scope),
             ExprKind::UnaryOp { op, expr } => self.check_unary_op(*op, expr, scope),
 
             // Comparisons
             ExprKind::Between {
                 expr, low, high, ..
             } => {
-                self.check_expr(expr, scope)?;
-                self.check_expr(low, scope)?;
-                self.check_expr(high, scope)?;
+                self.check_operands(&[expr, low, high], scope)?;
                 Ok(TypedExpr::non_null(SqlType::Bool))
             }
             ExprKind::In { expr, list, .. } => {
-                self.check_expr(expr, scope)?;
+                let mut correlated = false;
                 match list {
                     InList::Values(values) => {
-                        for v in values {
-                            self.check_expr(v, scope)?;
-                        }
+                        let mut operands: Vec<&Expr> = vec![expr];
+                        operands.extend(values.iter().map(|v| &**v));
+                        self.check_operands(&operands, scope)?;
+                    }
+                    InList::Subquery(query) => {
+                        correlated = self.check_in_subquery(expr, query, scope)?;
                     }
-                    InList::Subquery(_) => {}
+                    InList::Unnest(array) => self.check_in_unnest(expr, array, scope)?,
                 }
+                Ok(TypedExpr {
+                    correlated,
+                    ..TypedExpr::non_null(SqlType::Bool)
+                })
+            }
+            ExprKind::Like {
+                expr,
+                pattern,
+                escape,
+                case_insensitive,
+                ..
+            } => {
+                let context = if *case_insensitive { "ILIKE" } else { "LIKE" };
+                self.check_strings(context, [expr, pattern], scope)?;
+                self.check_strings(context, escape, scope)?;
                 Ok(TypedExpr::non_null(SqlType::Bool))
             }
-            ExprKind::Like { expr, pattern, .. } => {
-                self.check_expr(expr, scope)?;
-                self.check_expr(pattern, scope)?;
+            ExprKind::QuantifiedLike {
+                expr,
+                patterns,
+                escape,
+                case_insensitive,
+                ..
+            } => {
+                let context = if *case_insensitive { "ILIKE" } else { "LIKE" };
+                self.check_strings(context, [expr], scope)?;
+                self.check_strings(context, patterns, scope)?;
+                self.check_strings(context, escape, scope)?;
+                Ok(TypedExpr::non_null(SqlType::Bool))
+            }
+            ExprKind::SimilarTo {
+                expr,
+                pattern,
+                escape,
+                ..
+            } => {
+                self.check_strings("SIMILAR TO", [expr, pattern], scope)?;
+                self.check_strings("SIMILAR TO", escape, scope)?;
+                Ok(TypedExpr::non_null(SqlType::Bool))
+            }
+            ExprKind::IsExpr { expr, test, .. } => {
+                if *test == IsTest::Null {
+                    self.check_expr(expr, scope)?;
+                    return Ok(TypedExpr::non_null(SqlType::Bool));
+                }
+                // IS TRUE, IS FALSE and IS UNKNOWN test a boolean.
+                let operand = self.check_expr_as(expr, &SqlType::Bool, scope)?.data_type;
+                if !matches!(operand, SqlType::Bool | SqlType::Unknown | SqlType::Any) {
+                    let err = AnalyzerError::type_mismatch(SqlType::Bool, operand, "IS test");
+                    return Err(AnalyzerError::with_span(err.kind, expr.span));
+                }
+                Ok(TypedExpr::non_null(SqlType::Bool))
+            }
+            ExprKind::IsDistinct { left, right, .. } => {
+                self.check_operands(&[left, right], scope)?;
                 Ok(TypedExpr::non_null(SqlType::Bool))
             }
-            ExprKind::IsExpr { .. } => Ok(TypedExpr::non_null(SqlType::Bool)),
-            ExprKind::IsDistinct { .. } => Ok(TypedExpr::non_null(SqlType::Bool)),
 
             // Functions
-            ExprKind::Function(func) => self.check_function(func, scope),
-            ExprKind::Aggregate(agg) => self.check_aggregate(agg, scope),
-            ExprKind::WindowFunction(wf) => self.check_window_function(wf, scope),
+            ExprKind::Function(func) => self.check_function(func, expr.span, scope),
+            ExprKind::Aggregate(agg) => self.check_aggregate(agg, expr.span, scope),
+            ExprKind::WindowFunction(wf) => self.check_window_function(wf, expr.span, scope),
 
             // Type operations
-            ExprKind::Cast { data_type, .. } => {
-                let sql_type = self.data_type_to_sql_type(data_type);
-                Ok(TypedExpr::nullable(sql_type))
+            ExprKind::Cast {
+                expr: inner,
+                data_type,
+                safe,
+            } => {
+                let sql_type = sql_type_from_spec(self.catalog, data_type)?;
+                if let Some(parameter) = parameter_of(inner) {
+                    self.record_parameter(parameter, &sql_type);
+                    return Ok(TypedExpr::nullable(sql_type));
+                }
+                let typed = self.check_expr(inner, scope)?;
+                if typed.data_type.can_cast_to(&sql_type) == CastKind::Invalid {
+                    return Err(AnalyzerError::with_span(
+                        AnalyzerErrorKind::InvalidCast {
+                            from: typed.data_type,
+                            to: sql_type,
+                        },
+                        expr.span,
+                    ));
+                }
+                // SAFE_CAST returns NULL for values that fail to convert
+                let nullable =
+                    typed.nullable || (*safe && typed.data_type.cast_may_fail(&sql_type));
+                // Only NULL and same-type casts keep a known value
+                let constant_value = typed
+                    .constant_value
+                    .filter(|v| v.is_null() || typed.data_type == sql_type);
+                Ok(TypedExpr {
+                    data_type: sql_type,
+                    nullable,
+                    contains_aggregate: typed.contains_aggregate,
+                    contains_window: typed.contains_window,
+                    is_constant: typed.is_constant,
+                    constant_value,
+                    correlated: typed.correlated,
+                })
             }
             ExprKind::Extract { .. } => Ok(TypedExpr::nullable(SqlType::Int64)),
 
@@ -185,12 +549,13 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
                 elements,
                 element_type,
             } => {
-                let elem_type = if let Some(et) = element_type {
-                    self.data_type_to_sql_type(et)
-                } else if let Some(first) = elements.first() {
-                    self.check_expr(first, scope)?.data_type
-                } else {
-                    SqlType::Unknown
+                let elem_type = match element_type {
+                    Some(et) => {
+                        let declared = sql_type_from_spec(self.catalog, et)?;
+                        self.check_array_elements_as(elements, &declared, scope)?;
+                        declared
+                    }
+                    None => self.check_array_elements(elements, scope)?,
                 };
                 Ok(TypedExpr::non_null(SqlType::Array(Box::new(elem_type))))
             }
@@ -207,69 +572,69 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
             }
 
             // Subqueries
-            ExprKind::Subquery(_) => Ok(TypedExpr::nullable(SqlType::Unknown)),
-            ExprKind::Exists { .. } => Ok(TypedExpr::non_null(SqlType::Bool)),
-            ExprKind::InSubquery { .. } => Ok(TypedExpr::non_null(SqlType::Bool)),
-            ExprKind::SubqueryOp { .. } => Ok(TypedExpr::non_null(SqlType::Bool)),
+            ExprKind::Subquery(query) => self.check_scalar_subquery(query, scope),
+            ExprKind::Exists { subquery, .. } => {
+                let correlated = self
+                    .check_subquery(subquery, scope)?
+                    .is_some_and(|(_, correlated)| correlated);
+                Ok(TypedExpr {
+                    correlated,
+                    ..TypedExpr::non_null(SqlType::Bool)
+                })
+            }
+            ExprKind::InSubquery { expr, subquery, .. } => {
+                let correlated = self.check_in_subquery(expr, subquery, scope)?;
+                Ok(TypedExpr {
+                    correlated,
+                    ..TypedExpr::non_null(SqlType::Bool)
+                })
+            }
+            ExprKind::QuantifiedComparison { expr, list, .. } => {
+                let correlated = self.check_quantified_comparison(expr, list, scope)?;
+                Ok(TypedExpr {
+                    correlated,
+                    ..TypedExpr::non_null(SqlType::Bool)
+                })
+            }
 
             // Other
-            ExprKind::Parameter(_) => Ok(TypedExpr::nullable(SqlType::Unknown)),
-            ExprKind::ArraySubscript { array, .. } => {
-                let typed = self.check_expr(array, scope)?;
-                let elem_type = match &typed.data_type {
-                    SqlType::Array(elem) => (**elem).clone(),
-                    _ => SqlType::Unknown,
-                };
-                Ok(TypedExpr::nullable(elem_type))
+            ExprKind::Parameter(parameter) => {
+                self.record_parameter(parameter, &SqlType::Unknown);
+                Ok(TypedExpr::nullable(SqlType::Unknown))
             }
-            ExprKind::SafeArraySubscript { array, .. } => {
-                let typed = self.check_expr(array, scope)?;
-                let elem_type = match &typed.data_type {
-                    SqlType::Array(elem) => (**elem).clone(),
-                    _ => SqlType::Unknown,
+            ExprKind::SystemVariable { name, .. } => self.check_system_variable(name, expr.span),
+            ExprKind::ArraySubscript { array, index } => {
+                let index = match index {
+                    ArraySubscriptKind::Index(index)
+                    | ArraySubscriptKind::Offset(index)
+                    | ArraySubscriptKind::Ordinal(index)
+                    | ArraySubscriptKind::SafeOffset(index)
+                    | ArraySubscriptKind::SafeOrdinal(index) => index,
                 };
-                Ok(TypedExpr::nullable(elem_type))
+                self.check_subscript(array, index, scope)
             }
-            ExprKind::FieldAccess { expr, field } => {
-                let typed = self.check_expr(expr, scope)?;
+            ExprKind::SafeArraySubscript { array, index, .. } => {
+                self.check_subscript(array, index, scope)
+            }
+            ExprKind::FieldAccess { expr: inner, field } => {
+                let typed = self.check_expr(inner, scope)?;
                 match &typed.data_type {
-                    SqlType::Struct(fields) => {
-                        for f in fields {
-                            if let Some(name) = &f.name {
-                                if name.eq_ignore_ascii_case(&field.value) {
-                                    return Ok(TypedExpr::nullable(f.data_type.clone()));
-                                }
-                            }
-                        }
-                        Err(AnalyzerError::column_not_found(&field.value, None))
-                    }
                     SqlType::Json => Ok(TypedExpr::nullable(SqlType::Json)),
-                    _ => Err(AnalyzerError::column_not_found(&field.value, None)),
+                    SqlType::Unknown | SqlType::Any => Ok(TypedExpr::nullable(SqlType::Unknown)),
+                    data_type => {
+                        let parent = inner.to_string();
+                        let field = struct_field(data_type, &parent, field, scope.matching())?;
+                        Ok(TypedExpr::nullable(field.data_type.clone()))
+                    }
                 }
             }
             ExprKind::JsonSubscript { expr, .. } => {
                 self.check_expr(expr, scope)?;
                 Ok(TypedExpr::nullable(SqlType::Json))
             }
-            ExprKind::Interval { .. } => Ok(TypedExpr::non_null(SqlType::Interval)),
-            ExprKind::TypedLiteral { data_type, .. } => {
-                let sql_type = match data_type {
-                    TypedLiteralType::Date => SqlType::Date,
-                    TypedLiteralType::Time => SqlType::Time,
-                    TypedLiteralType::Timestamp => SqlType::Timestamp,
-                    TypedLiteralType::Datetime => SqlType::Datetime,
-                    TypedLiteralType::Json => SqlType::Json,
-                    TypedLiteralType::Numeric => SqlType::Numeric {
-                        precision: None,
-                        scale: None,
-                    },
-                    TypedLiteralType::Bignumeric => SqlType::Numeric {
-                        precision: None,
-                        scale: None,
-                    },
-                    TypedLiteralType::Range => SqlType::Unknown,
-                };
-                Ok(TypedExpr::non_null(sql_type))
+            ExprKind::Interval { .. } => Ok(TypedExpr::constant(SqlType::Interval, None)),
+            ExprKind::TypedLiteral { data_type, value } => {
+                check_typed_literal(*data_type, value, expr.span)
             }
             ExprKind::Parenthesized(inner) => self.check_expr(inner, scope),
             ExprKind::Row(exprs) => {
@@ -286,6 +651,54 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
         }
     }
 
+    /// Type a literal, with its value.
+    fn literal(&self, data_type: SqlType, expr: &Expr) -> TypedExpr {
+        TypedExpr::constant(data_type, Value::from_literal(&expr.kind))
+    }
+
+    /// A function-not-found error listing the schemas searched for the
+    /// function and suggesting a catalog function.
+    fn function_not_found(&self, name: &str, name_parts: &[String]) -> AnalyzerError {
+        let searched = self
+            .catalog
+            .function_search_schemas(name_parts)
+            .unwrap_or_default();
+        let functions = self.catalog.list_functions().unwrap_or_default();
+        AnalyzerError::function_not_found_in(name, searched)
+            .suggesting(functions.iter().map(|f| f.signature.name.as_str()))
+    }
+
+    /// Resolve a column reference in `scope`, or failing that in the scopes
+    /// of enclosing queries as a correlated reference.
+    fn resolve_in_scopes(
+        &self,
+        scope: &Scope,
+        resolve: impl Fn(&Scope) -> Result<TypedExpr, AnalyzerError>,
+    ) -> Result<TypedExpr, AnalyzerError> {
+        let err = match resolve(scope) {
+            Err(err) if matches!(err.kind, AnalyzerErrorKind::ColumnNotFound { .. }) => err,
+            result => return result,
+        };
+        let mut outer = scope.outer();
+        let mut depth = 1;
+        while let Some(outer_scope) = outer {
+            match resolve(outer_scope) {
+                Err(e) if matches!(e.kind, AnalyzerErrorKind::ColumnNotFound { .. }) => {}
+                result => {
+                    if let (Ok(_), Some(context)) = (&result, self.subqueries) {
+                        context
+                            .outer_depth
+                            .set(context.outer_depth.get().max(depth));
+                    }
+                    return result;
+                }
+            }
+            outer = outer_scope.outer();
+            depth += 1;
+        }
+        Err(err)
+    }
+
     /// Check a column reference.
     fn check_column(
         &self,
@@ -300,12 +713,15 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
                     nullable: col.nullable,
                     contains_aggregate: false,
                     contains_window: false,
+                    is_constant: false,
+                    constant_value: None,
+                    correlated: false,
                 })
             } else {
-                Err(AnalyzerError::column_not_found(
-                    col_name,
-                    Some(table.to_string()),
-                ))
+                Err(
+                    AnalyzerError::column_not_found(col_name, Some(table.to_string()))
+                        .suggesting(scope.column_names(Some(table))),
+                )
             }
         } else {
             match scope.lookup_column(col_name) {
@@ -314,27 +730,402 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
                     nullable: col.nullable,
                     contains_aggregate: false,
                     contains_window: false,
+                    is_constant: false,
+                    constant_value: None,
+                    correlated: false,
                 }),
-                ColumnLookupResult::NotFound => {
-                    Err(AnalyzerError::column_not_found(col_name, None))
+                ColumnLookupResult::Ambiguous(candidates) => {
+                    Err(AnalyzerError::ambiguous_column(col_name, candidates))
+                }
+                // Only this scope's tables are searched; enclosing scopes
+                // are tried by `resolve_in_scopes`.
+                ColumnLookupResult::NotFound
+                | ColumnLookupResult::Alias(_)
+                | ColumnLookupResult::Outer { .. } => {
+                    Err(AnalyzerError::column_not_found(col_name, None)
+                        .suggesting(scope.column_names(None)))
+                }
+            }
+        }
+    }
+
+    /// Analyze a subquery of an expression checked in `scope`, whose columns
+    /// the subquery may reference. Returns the subquery's result and whether
+    /// it is correlated, or `None` if this checker does not analyze
+    /// subqueries.
+    fn check_subquery(
+        &self,
+        query: &Query,
+        scope: &Scope,
+    ) -> Result<Option<(AnalyzedQuery, bool)>, AnalyzerError> {
+        let Some(context) = self.subqueries else {
+            return Ok(None);
+        };
+        let catalog: &dyn Catalog = self.catalog;
+        let mut analyzer = Analyzer::for_subquery(catalog, context, scope);
+        let result = analyzer.explained(|analyzer| analyzer.analyze_query_internal(query));
+
+        analyzer.take_checker_warnings();
+        if let Some(store) = self.warnings {
+            let mut warnings = store.borrow_mut();
+            for warning in analyzer.warnings.drain(..) {
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+        for parameter in analyzer.parameter_types.take() {
+            self.record_parameter(&parameter.parameter, &parameter.data_type);
+        }
+        if let Some(err) = analyzer.errors.into_iter().next() {
+            return Err(err);
+        }
+        let (analyzed, explained) = result?;
+        if let (Some(recorder), Some(query)) = (context.explain, explained) {
+            recorder.record(|open| open.subqueries.push(query));
+        }
+
+        // A reference past this query's scope also correlates this query.
+        let depth = analyzer.outer_depth.get();
+        if depth > 1 {
+            context
+                .outer_depth
+                .set(context.outer_depth.get().max(depth - 1));
+        }
+        Ok(Some((analyzed, depth > 0)))
+    }
+
+    /// Check a scalar subquery, which has the type of its only column. It
+    /// is NULL when the subquery returns no rows, so it is nullable.
+    fn check_scalar_subquery(
+        &self,
+        query: &Query,
+        scope: &Scope,
+    ) -> Result<TypedExpr, AnalyzerError> {
+        let Some((analyzed, correlated)) = self.check_subquery(query, scope)? else {
+            return Ok(TypedExpr::nullable(SqlType::Unknown));
+        };
+        let column = single_column(&analyzed, query)?;
+        Ok(TypedExpr {
+            correlated,
+            ..TypedExpr::nullable(column.data_type.clone())
+        })
+    }
+
+    /// Check `expr IN (subquery)` or a quantified comparison such as
+    /// `expr = ANY (subquery)`. The subquery must return one column that
+    /// `expr` can be compared with, or when `expr` is a row, one column for
+    /// each of its fields. Returns whether the subquery is correlated.
+    fn check_in_subquery(
+        &self,
+        expr: &Expr,
+        query: &Query,
+        scope: &Scope,
+    ) -> Result<bool, AnalyzerError> {
+        let Some((analyzed, correlated)) = self.check_subquery(query, scope)? else {
+            self.check_expr(expr, scope)?;
+            return Ok(false);
+        };
+        let width = match &expr.kind {
+            ExprKind::Row(exprs) => Some(exprs.len()),
+            ExprKind::Struct { fields } => Some(fields.len()),
+            _ => None,
+        };
+        if let Some(width) = width {
+            if analyzed.columns.len() != width {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::SubqueryColumnCount {
+                        expected: width,
+                        found: analyzed.columns.len(),
+                    },
+                    query.span,
+                ));
+            }
+            let typed = self.check_expr(expr, scope)?;
+            let row_type = SqlType::Struct(
+                analyzed
+                    .columns
+                    .iter()
+                    .map(|column| crate::types::StructField {
+                        name: None,
+                        data_type: column.data_type.clone(),
+                    })
+                    .collect(),
+            );
+            check_comparable((expr, &typed.data_type), (expr, &row_type), expr.span)?;
+            return Ok(correlated);
+        }
+        let column = single_column(&analyzed, query)?;
+        let typed = self.check_expr_as(expr, &column.data_type, scope)?;
+        // The column is not a literal, so `expr` stands in for it.
+        check_comparable(
+            (expr, &typed.data_type),
+            (expr, &column.data_type),
+            expr.span,
+        )?;
+        Ok(correlated)
+    }
+
+    /// Check `expr op ANY|SOME|ALL (...)`, returning whether a subquery
+    /// on the right is correlated. A list of one array compares `expr`
+    /// with the array's elements.
+    fn check_quantified_comparison(
+        &self,
+        expr: &Expr,
+        list: &InList,
+        scope: &Scope,
+    ) -> Result<bool, AnalyzerError> {
+        match list {
+            InList::Subquery(query) => return self.check_in_subquery(expr, query, scope),
+            InList::Unnest(array) => self.check_in_unnest(expr, array, scope)?,
+            InList::Values(values) => match values.as_slice() {
+                [value] if parameter_of(value).is_none() => {
+                    let right = match self.check_expr(value, scope)?.data_type {
+                        SqlType::Array(element) => *element,
+                        other => other,
+                    };
+                    let left = self.check_expr_as(expr, &right, scope)?;
+                    check_comparable((expr, &left.data_type), (value, &right), expr.span)?;
                 }
-                ColumnLookupResult::Ambiguous(tables) => {
-                    Err(AnalyzerError::ambiguous_column(col_name, tables))
+                _ => {
+                    let mut operands: Vec<&Expr> = vec![expr];
+                    operands.extend(values.iter().map(|v| &**v));
+                    self.check_operands(&operands, scope)?;
                 }
+            },
+        }
+        Ok(false)
+    }
+
+    /// Check `@@name`, which has the type the session defines for it. The
+    /// scope is not checked, and the value may change before the statement
+    /// runs, so the result is nullable and never constant.
+    fn check_system_variable(&self, name: &Ident, span: Span) -> Result<TypedExpr, AnalyzerError> {
+        let Some(session) = &self.session else {
+            return Ok(TypedExpr::nullable(SqlType::Unknown));
+        };
+        match session.definition(&name.value) {
+            Some(definition) => Ok(TypedExpr::nullable(definition.data_type.clone())),
+            None if session.is_permissive() => Ok(TypedExpr::nullable(SqlType::Unknown)),
+            None => Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::VariableNotFound {
+                    name: name.value.clone(),
+                },
+                span,
+            )
+            .suggesting(session.variables().map(|(d, _)| d.name.as_str()))),
+        }
+    }
+
+    /// Check `array[index]`, which has the array's element type.
+    ///
+    /// The index must be an integer. Elements may be NULL, and SAFE_OFFSET
+    /// and SAFE_ORDINAL give NULL out of range, so the result is nullable.
+    /// Subscripts of JSON are JSON, and subscripting any other type that is
+    /// not an array is an error.
+    fn check_subscript(
+        &self,
+        array: &Expr,
+        index: &Expr,
+        scope: &Scope,
+    ) -> Result<TypedExpr, AnalyzerError> {
+        let typed = self.check_expr(array, scope)?;
+        let element = match typed.data_type {
+            SqlType::Json => {
+                self.check_expr(index, scope)?;
+                return Ok(TypedExpr::nullable(SqlType::Json));
+            }
+            SqlType::Array(element) => *element,
+            SqlType::Unknown | SqlType::Any => SqlType::Unknown,
+            other => {
+                let err = AnalyzerError::type_mismatch(
+                    SqlType::Array(Box::new(SqlType::Any)),
+                    other,
+                    "array subscript",
+                );
+                return Err(AnalyzerError::with_span(err.kind, array.span));
             }
+        };
+        let index_type = self.check_expr_as(index, &SqlType::Int64, scope)?.data_type;
+        if !index_type.is_integer() && !matches!(index_type, SqlType::Unknown | SqlType::Any) {
+            let err = AnalyzerError::type_mismatch(SqlType::Int64, index_type, "array subscript");
+            return Err(AnalyzerError::with_span(err.kind, index.span));
+        }
+        Ok(TypedExpr::nullable(element))
+    }
+
+    /// The element type of an array literal: the common supertype of its
+    /// elements, or `Unknown` when it is empty or all NULL so it unifies
+    /// with whatever it meets.
+    fn check_array_elements(
+        &self,
+        elements: &[Box<Expr>],
+        scope: &Scope,
+    ) -> Result<SqlType, AnalyzerError> {
+        let mut common = SqlType::Unknown;
+        for element in elements.iter().filter(|e| parameter_of(e).is_none()) {
+            let typed = self.check_expr(element, scope)?;
+            common = common.common_supertype(&typed.data_type).ok_or_else(|| {
+                let err = AnalyzerError::type_mismatch(
+                    common.clone(),
+                    typed.data_type.clone(),
+                    "array literal",
+                );
+                AnalyzerError::with_span(err.kind, element.span)
+            })?;
         }
+        for element in elements.iter().filter(|e| parameter_of(e).is_some()) {
+            self.check_expr_as(element, &common, scope)?;
+        }
+        Ok(common)
     }
 
-    /// Check a binary operation.
+    /// Check the elements of an array literal with a declared element type,
+    /// as in `ARRAY<INT64>[1, 2]`. Numeric literals adopt a numeric element
+    /// type.
+    fn check_array_elements_as(
+        &self,
+        elements: &[Box<Expr>],
+        declared: &SqlType,
+        scope: &Scope,
+    ) -> Result<(), AnalyzerError> {
+        for element in elements {
+            let typed = self.check_expr_as(element, declared, scope)?;
+            let numeric_literal = matches!(element.kind, ExprKind::Integer(_) | ExprKind::Float(_))
+                && declared.is_numeric();
+            if !typed.data_type.can_coerce_to(declared) && !numeric_literal {
+                let err = AnalyzerError::type_mismatch(
+                    declared.clone(),
+                    typed.data_type,
+                    "array literal",
+                );
+                return Err(AnalyzerError::with_span(err.kind, element.span));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check `expr IN UNNEST(array)`, where `expr` must be comparable with
+    /// the array's elements.
+    fn check_in_unnest(
+        &self,
+        expr: &Expr,
+        array: &Expr,
+        scope: &Scope,
+    ) -> Result<(), AnalyzerError> {
+        if parameter_of(array).is_some() {
+            let typed = self.check_expr(expr, scope)?;
+            self.check_expr_as(array, &SqlType::Array(Box::new(typed.data_type)), scope)?;
+            return Ok(());
+        }
+        let element = match self.check_expr(array, scope)?.data_type {
+            SqlType::Array(element) => *element,
+            SqlType::Unknown | SqlType::Any => SqlType::Unknown,
+            other => {
+                let err = AnalyzerError::type_mismatch(
+                    SqlType::Array(Box::new(SqlType::Any)),
+                    other,
+                    "UNNEST",
+                );
+                return Err(AnalyzerError::with_span(err.kind, array.span));
+            }
+        };
+        let typed = self.check_expr_as(expr, &element, scope)?;
+        check_comparable((expr, &typed.data_type), (array, &element), expr.span)
+    }
+
+    /// Check a dotted name.
+    ///
+    /// `a.b.c...` is column `b` of table `a` when `a` is a table with that
+    /// column, otherwise column `a` when there is one, and otherwise column
+    /// `c` of table `b` in schema `a`. Names after the column are struct
+    /// fields.
+    fn check_compound_identifier(
+        &self,
+        parts: &[Ident],
+        scope: &Scope,
+    ) -> Result<TypedExpr, AnalyzerError> {
+        let (mut typed, mut label, fields) = match parts {
+            [column] => return self.check_column(&column.value, None, scope),
+            [table, column, fields @ ..]
+                if scope
+                    .lookup_qualified_column(&table.value, &column.value)
+                    .is_some() =>
+            {
+                let typed = self.check_column(&column.value, Some(&table.value), scope)?;
+                (typed, column.value.clone(), fields)
+            }
+            [column, fields @ ..]
+                if !matches!(
+                    scope.lookup_column(&column.value),
+                    ColumnLookupResult::NotFound
+                ) =>
+            {
+                let typed = self.check_column(&column.value, None, scope)?;
+                (typed, column.value.clone(), fields)
+            }
+            [.., table, column] => {
+                return self.check_column(&column.value, Some(&table.value), scope);
+            }
+            [] => return Ok(TypedExpr::nullable(SqlType::Unknown)),
+        };
+        for name in fields {
+            let field = struct_field(&typed.data_type, &label, name, scope.matching())?;
+            typed = TypedExpr::nullable(field.data_type.clone());
+            label = format!("{}.{}", label, name.value);
+        }
+        Ok(typed)
+    }
+
+    /// Check a binary operation, and the binary operations among its left
+    /// operands from the inside out. A chain such as `a + b + c` is checked
+    /// in a loop, so it is one level of nesting however long it is.
+    fn check_binary_chain(&self, expr: &Expr, scope: &Scope) -> Result<TypedExpr, AnalyzerError> {
+        let mut links = Vec::new();
+        let mut link = expr;
+        while let ExprKind::BinaryOp { op, left, right } = &link.kind {
+            links.push((*op, &**left, &**right));
+            link = left;
+        }
+        let mut left_typed = None;
+        while let Some((op, left, right)) = links.pop() {
+            left_typed = Some(self.check_binary_op(op, left, left_typed, right, scope)?);
+        }
+        Ok(left_typed.expect("expr is a binary operation"))
+    }
+
+    /// Check a binary operation, whose left operand is already typed when
+    /// `left_typed` is given.
     fn check_binary_op(
         &self,
         op: BinaryOp,
         left: &Expr,
+        left_typed: Option<TypedExpr>,
         right: &Expr,
         scope: &Scope,
     ) -> Result<TypedExpr, AnalyzerError> {
-        let left_typed = self.check_expr(left, scope)?;
-        let right_typed = self.check_expr(right, scope)?;
+        let op = match op {
+            BinaryOp::Concat if !self.pipes_as_concat => BinaryOp::Or,
+            op => op,
+        };
+        // Type the non-parameter side first so a parameter can take its type.
+        let (left_typed, right_typed) = match left_typed {
+            Some(left_typed) => {
+                let hint = operand_hint(op, &left_typed.data_type);
+                (left_typed, self.check_expr_as(right, &hint, scope)?)
+            }
+            None if parameter_of(left).is_some() && parameter_of(right).is_none() => {
+                let right_typed = self.check_expr(right, scope)?;
+                let hint = operand_hint(op, &right_typed.data_type);
+                (self.check_expr_as(left, &hint, scope)?, right_typed)
+            }
+            None => {
+                let left_typed =
+                    self.check_expr_as(left, &operand_hint(op, &SqlType::Unknown), scope)?;
+                let hint = operand_hint(op, &left_typed.data_type);
+                (left_typed, self.check_expr_as(right, &hint, scope)?)
+            }
+        };
 
         let result_type = match op {
             // Comparison operators return Bool
@@ -343,7 +1134,18 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
             | BinaryOp::Lt
             | BinaryOp::LtEq
             | BinaryOp::Gt
-            | BinaryOp::GtEq => SqlType::Bool,
+            | BinaryOp::GtEq => {
+                let span = Span::new(left.span.start, right.span.end);
+                check_comparable(
+                    (left, &left_typed.data_type),
+                    (right, &right_typed.data_type),
+                    span,
+                )?;
+                if matches!(left.kind, ExprKind::Null) || matches!(right.kind, ExprKind::Null) {
+                    self.warn(AnalyzerWarningKind::ComparisonWithNull { op }, span);
+                }
+                SqlType::Bool
+            }
 
             // Logical operators
             BinaryOp::And | BinaryOp::Or => SqlType::Bool,
@@ -353,13 +1155,15 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
             | BinaryOp::Minus
             | BinaryOp::Multiply
             | BinaryOp::Divide
-            | BinaryOp::Modulo => left_typed
-                .data_type
-                .common_supertype(&right_typed.data_type)
-                .unwrap_or(SqlType::Float64),
+            | BinaryOp::Modulo => self.arithmetic_type(
+                op,
+                &left_typed.data_type,
+                &right_typed.data_type,
+                Span::new(left.span.start, right.span.end),
+            )?,
 
             // String concatenation
-            BinaryOp::Concat => SqlType::Varchar,
+            BinaryOp::Concat => SqlType::Varchar(None),
 
             // Bitwise operators
             BinaryOp::BitwiseAnd
@@ -369,14 +1173,134 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
             | BinaryOp::RightShift => SqlType::Int64,
         };
 
+        let is_constant = left_typed.is_constant && right_typed.is_constant;
+        let constant_value = match (&left_typed.constant_value, &right_typed.constant_value) {
+            (Some(l), Some(r)) => constant::fold_binary(op, l, r),
+            _ => None,
+        };
         Ok(TypedExpr {
             data_type: result_type,
-            nullable: left_typed.nullable || right_typed.nullable,
+            nullable: constant_value
+                .as_ref()
+                .map_or(left_typed.nullable || right_typed.nullable, Value::is_null),
             contains_aggregate: left_typed.contains_aggregate || right_typed.contains_aggregate,
             contains_window: left_typed.contains_window || right_typed.contains_window,
+            is_constant,
+            constant_value,
+            correlated: left_typed.correlated || right_typed.correlated,
         })
     }
 
+    /// The result type of an arithmetic operator. NUMERIC operands with
+    /// NUMERIC or integer operands give a NUMERIC with the derived
+    /// precision and scale; with floating-point operands the
+    /// [`NumericFloatArithmetic`] policy applies.
+    fn arithmetic_type(
+        &self,
+        op: BinaryOp,
+        left: &SqlType,
+        right: &SqlType,
+        span: Span,
+    ) -> Result<SqlType, AnalyzerError> {
+        let numeric_op = match op {
+            BinaryOp::Plus => ArithmeticOp::Add,
+            BinaryOp::Minus => ArithmeticOp::Subtract,
+            BinaryOp::Multiply => ArithmeticOp::Multiply,
+            BinaryOp::Divide => ArithmeticOp::Divide,
+            _ => ArithmeticOp::Modulo,
+        };
+        if let Some(numeric) = left.numeric_arithmetic(numeric_op, right) {
+            if let Some(precision) = numeric.capped_precision {
+                self.warn(
+                    AnalyzerWarningKind::NumericPrecisionCapped {
+                        precision,
+                        result: numeric.result.clone(),
+                    },
+                    span,
+                );
+            }
+            return Ok(numeric.result);
+        }
+
+        let mixed = match (left, right) {
+            (numeric @ SqlType::Numeric { .. }, float)
+            | (float, numeric @ SqlType::Numeric { .. })
+                if float.is_floating_point() =>
+            {
+                Some((numeric, float))
+            }
+            _ => None,
+        };
+        match (mixed, self.numeric_float_arithmetic) {
+            (Some(_), NumericFloatArithmetic::Float64) => Ok(SqlType::Float64),
+            (Some((numeric, float)), NumericFloatArithmetic::Error) => {
+                let kind = AnalyzerErrorKind::TypeMismatch {
+                    expected: numeric.clone(),
+                    actual: float.clone(),
+                    context: "NUMERIC arithmetic".to_string(),
+                };
+                Err(AnalyzerError::with_span(kind, span))
+            }
+            (None, _) => Ok(left.common_supertype(right).unwrap_or(SqlType::Float64)),
+        }
+    }
+
+    /// Record a warning, unless it was already recorded.
+    fn warn(&self, kind: AnalyzerWarningKind, span: Span) {
+        if let Some(store) = self.warnings {
+            let warning = AnalyzerWarning::with_span(kind, span);
+            let mut warnings = store.borrow_mut();
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+
+    /// Check the operands of a pattern match, which must be strings.
+    fn check_strings<'e>(
+        &self,
+        context: &'static str,
+        operands: impl IntoIterator<Item = &'e Box<Expr>>,
+        scope: &Scope,
+    ) -> Result<(), AnalyzerError> {
+        for operand in operands {
+            let data_type = self
+                .check_expr_as(operand, &SqlType::Varchar(None), scope)?
+                .data_type;
+            if !data_type.is_string() && !matches!(data_type, SqlType::Unknown | SqlType::Any) {
+                let err = AnalyzerError::type_mismatch(SqlType::Varchar(None), data_type, context);
+                return Err(AnalyzerError::with_span(err.kind, operand.span));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check operands that are compared with each other, as in BETWEEN
+    /// and IN. Parameters take the common type of the other operands.
+    fn check_operands(&self, operands: &[&Expr], scope: &Scope) -> Result<(), AnalyzerError> {
+        let mut common = SqlType::Unknown;
+        let mut first: Option<(&Expr, SqlType)> = None;
+        for operand in operands.iter().filter(|e| parameter_of(e).is_none()) {
+            let typed = self.check_expr(operand, scope)?;
+            match &first {
+                Some((first_expr, first_type)) => check_comparable(
+                    (first_expr, first_type),
+                    (operand, &typed.data_type),
+                    operand.span,
+                )?,
+                None => first = Some((operand, typed.data_type.clone())),
+            }
+            common = match common {
+                SqlType::Unknown => typed.data_type,
+                known => known.common_supertype(&typed.data_type).unwrap_or(known),
+            };
+        }
+        for operand in operands.iter().filter(|e| parameter_of(e).is_some()) {
+            self.check_expr_as(operand, &common, scope)?;
+        }
+        Ok(())
+    }
+
     /// Check a unary operation.
     fn check_unary_op(
         &self,
@@ -384,17 +1308,29 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
         expr: &Expr,
         scope: &Scope,
     ) -> Result<TypedExpr, AnalyzerError> {
-        let typed = self.check_expr(expr, scope)?;
+        let typed = match op {
+            UnaryOp::Not => self.check_expr_as(expr, &SqlType::Bool, scope)?,
+            _ => self.check_expr(expr, scope)?,
+        };
         let result_type = match op {
             UnaryOp::Not => SqlType::Bool,
             UnaryOp::Plus | UnaryOp::Minus => typed.data_type.clone(),
             UnaryOp::BitwiseNot => SqlType::Int64,
         };
+        let constant_value = typed
+            .constant_value
+            .as_ref()
+            .and_then(|v| constant::fold_unary(op, v));
         Ok(TypedExpr {
             data_type: result_type,
-            nullable: typed.nullable,
+            nullable: constant_value
+                .as_ref()
+                .map_or(typed.nullable, Value::is_null),
             contains_aggregate: typed.contains_aggregate,
             contains_window: typed.contains_window,
+            is_constant: typed.is_constant,
+            constant_value,
+            correlated: typed.correlated,
         })
     }
 
@@ -402,6 +1338,7 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
     fn check_function(
         &self,
         func: &FunctionCall,
+        span: Span,
         scope: &Scope,
     ) -> Result<TypedExpr, AnalyzerError> {
         let func_name = func
@@ -412,36 +1349,88 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
             .unwrap_or_default();
         let name_parts: Vec<String> = func.name.parts.iter().map(|i| i.value.clone()).collect();
 
-        // Look up function in catalog
-        let sig = self
+        // Look up the function's overloads in the catalog
+        let overloads = self
             .catalog
-            .resolve_function(&name_parts)
-            .map_err(|_| AnalyzerError::function_not_found(&func_name))?
-            .ok_or_else(|| AnalyzerError::function_not_found(&func_name))?;
+            .resolve_function_overloads(&name_parts)
+            .map_err(|_| AnalyzerError::function_not_found(&func_name))?;
+        if overloads.is_empty() {
+            return Err(self.function_not_found(&func_name, &name_parts));
+        }
 
         // Check argument count
         let arg_count = func.args.len();
-        if !sig.accepts_arg_count(arg_count) {
-            return Err(AnalyzerError::wrong_argument_count(
-                &func_name,
-                sig.min_args,
-                sig.max_args,
-                arg_count,
+        let candidates: Vec<&FunctionSignature> = overloads
+            .iter()
+            .filter(|sig| sig.accepts_arg_count(arg_count))
+            .collect();
+        if candidates.is_empty() {
+            let min = overloads.iter().map(|sig| sig.min_args).min().unwrap_or(0);
+            let max = overloads
+                .iter()
+                .map(|sig| sig.max_args)
+                .try_fold(0, |max, sig_max| sig_max.map(|m| m.max(max)));
+            return Err(AnalyzerError::with_span(
+                AnalyzerError::wrong_argument_count(&func_name, min, max, arg_count).kind,
+                span,
             ));
         }
 
-        // Type check arguments
-        for arg in &func.args {
+        // Type check arguments. A parameter's declared type is passed down
+        // only when every candidate overload agrees on it.
+        let mut args = Vec::with_capacity(arg_count);
+        for (i, arg) in func.args.iter().enumerate() {
             if let FunctionArg::Unnamed(expr) = arg {
-                self.check_expr(expr, scope)?;
+                let mut declared = candidates
+                    .iter()
+                    .map(|sig| sig.parameter(i).and_then(|p| p.data_type.as_ref()));
+                let first = declared.next().flatten();
+                let expected = match first {
+                    Some(ty) if declared.all(|d| d == first) => ty,
+                    _ => &SqlType::Unknown,
+                };
+                args.push((i, expr.span, self.check_expr_as(expr, expected, scope)?));
             }
         }
 
+        // Pick the first overload that accepts every argument, preferring
+        // the one with the most exact type matches.
+        let mut best: Option<(&FunctionSignature, usize)> = None;
+        for sig in &candidates {
+            if argument_mismatch(sig, &args).is_some() {
+                continue;
+            }
+            let exact = exact_argument_matches(sig, &args);
+            if best.is_none_or(|(_, most)| exact > most) {
+                best = Some((sig, exact));
+            }
+        }
+        let Some((sig, _)) = best else {
+            let (i, arg_span, expected, actual) = argument_mismatch(candidates[0], &args)
+                .ok_or_else(|| AnalyzerError::function_not_found(&func_name))?;
+            return Err(AnalyzerError::with_span(
+                AnalyzerError::type_mismatch(
+                    expected.clone(),
+                    actual.clone(),
+                    format!("argument {} of {}", i + 1, func_name),
+                )
+                .kind,
+                arg_span,
+            ));
+        };
+        let typed_args: Vec<TypedExpr> = args.into_iter().map(|(_, _, typed)| typed).collect();
+        if sig.is_aggregate {
+            self.check_aggregate_use(&func_name, &typed_args, span)?;
+        }
+
         Ok(TypedExpr {
-            data_type: sig.return_type.clone(),
-            nullable: true,
+            data_type: sig.resolve_return_type(&argument_types(&typed_args)),
+            nullable: !(sig.is_aggregate && NON_NULL_AGGREGATES.contains(&func_name.as_str())),
             contains_aggregate: sig.is_aggregate,
             contains_window: sig.is_window,
+            is_constant: false,
+            constant_value: None,
+            correlated: false,
         })
     }
 
@@ -449,6 +1438,7 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
     fn check_aggregate(
         &self,
         agg: &AggregateCall,
+        span: Span,
         scope: &Scope,
     ) -> Result<TypedExpr, AnalyzerError> {
         let func_name = agg
@@ -470,27 +1460,60 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
             .catalog
             .resolve_function(&name_parts)
             .map_err(|_| AnalyzerError::function_not_found(&func_name))?
-            .ok_or_else(|| AnalyzerError::function_not_found(&func_name))?;
+            .ok_or_else(|| self.function_not_found(&func_name, &name_parts))?;
 
         // Type check arguments
+        let mut typed_args = Vec::with_capacity(agg.function.args.len());
         for arg in &agg.function.args {
             if let FunctionArg::Unnamed(expr) = arg {
-                self.check_expr(expr, scope)?;
+                typed_args.push(self.check_expr(expr, scope)?);
             }
         }
+        self.check_aggregate_use(&func_name, &typed_args, span)?;
+        self.check_order_by(&agg.function.order_by, scope)?;
 
         Ok(TypedExpr {
-            data_type: sig.return_type.clone(),
-            nullable: true,
+            data_type: sig.resolve_return_type(&argument_types(&typed_args)),
+            nullable: !NON_NULL_AGGREGATES.contains(&func_name.as_str()),
             contains_aggregate: true,
             contains_window: false,
+            is_constant: false,
+            constant_value: None,
+            correlated: false,
         })
     }
 
+    /// Reject an aggregate call in a clause that forbids aggregates, and
+    /// aggregate or window calls nested in its arguments.
+    fn check_aggregate_use(
+        &self,
+        func_name: &str,
+        args: &[TypedExpr],
+        span: Span,
+    ) -> Result<(), AnalyzerError> {
+        let reason = if let Some(clause) = self.aggregates_forbidden_in {
+            format!("aggregate functions are not allowed in {}", clause)
+        } else if args.iter().any(|a| a.contains_aggregate) {
+            "aggregate function calls cannot be nested".to_string()
+        } else if args.iter().any(|a| a.contains_window) {
+            "window functions are not allowed in aggregate arguments".to_string()
+        } else {
+            return Ok(());
+        };
+        Err(AnalyzerError::with_span(
+            AnalyzerErrorKind::InvalidAggregateUse {
+                function: func_name.to_string(),
+                reason,
+            },
+            span,
+        ))
+    }
+
     /// Check a window function call.
     fn check_window_function(
         &self,
         wf: &WindowFunctionCall,
+        span: Span,
         scope: &Scope,
     ) -> Result<TypedExpr, AnalyzerError> {
         let func_name = wf
@@ -500,6 +1523,15 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
             .last()
             .map(|i| i.value.to_uppercase())
             .unwrap_or_default();
+        if let Some(clause) = self.windows_forbidden_in {
+            return Err(AnalyzerError::with_span(
+                AnalyzerErrorKind::InvalidWindowUse {
+                    function: func_name,
+                    reason: format!("window functions are not allowed in {}", clause),
+                },
+                span,
+            ));
+        }
         let name_parts: Vec<String> = wf
             .function
             .name
@@ -512,66 +1544,504 @@ impl<'a, C: Catalog> TypeChecker<'a, C> {
             .catalog
             .resolve_function(&name_parts)
             .map_err(|_| AnalyzerError::function_not_found(&func_name))?
-            .ok_or_else(|| AnalyzerError::function_not_found(&func_name))?;
+            .ok_or_else(|| self.function_not_found(&func_name, &name_parts))?;
 
         // Type check arguments
+        let mut arg_types = Vec::with_capacity(wf.function.args.len());
         for arg in &wf.function.args {
             if let FunctionArg::Unnamed(expr) = arg {
-                self.check_expr(expr, scope)?;
+                arg_types.push(self.check_expr(expr, scope)?.data_type);
             }
         }
+        let data_type = sig.resolve_return_type(&arg_types);
+
+        let spec = match &wf.window {
+            WindowSpecOrRef::Spec(spec) => self.resolve_window_spec(spec, scope)?,
+            WindowSpecOrRef::Ref(name) => {
+                scope.lookup_window(&name.value).cloned().ok_or_else(|| {
+                    AnalyzerError::with_span(
+                        AnalyzerErrorKind::InvalidWindowUse {
+                            function: func_name.clone(),
+                            reason: format!("window '{}' is not defined", name.value),
+                        },
+                        name.span,
+                    )
+                })?
+            }
+        };
+        let sort_keys = self.check_window_spec(&spec, scope)?;
+
+        if RANKING_FUNCTIONS.contains(&func_name.as_str()) {
+            let reason = if let Some(frame) = &spec.frame {
+                Some(("ranking functions do not accept a window frame", frame.span))
+            } else if spec.order_by.is_empty() {
+                Some((
+                    "ranking functions require ORDER BY in the window",
+                    spec.span,
+                ))
+            } else {
+                None
+            };
+            if let Some((reason, span)) = reason {
+                return Err(AnalyzerError::with_span(
+                    AnalyzerErrorKind::InvalidWindowUse {
+                        function: func_name,
+                        reason: reason.to_string(),
+                    },
+                    span,
+                ));
+            }
+        }
+
+        let nullable = !NON_NULL_AGGREGATES.contains(&func_name.as_str());
+        self.windows.borrow_mut().push(TypedWindow {
+            function: func_name,
+            data_type: data_type.clone(),
+            spec,
+            sort_keys,
+        });
 
         Ok(TypedExpr {
-            data_type: sig.return_type.clone(),
-            nullable: true,
+            data_type,
+            nullable,
             contains_aggregate: false,
             contains_window: true,
+            is_constant: false,
+            constant_value: None,
+            correlated: false,
         })
     }
 
-    /// Convert AST data type to SqlType.
-    fn data_type_to_sql_type(&self, dt: &DataTypeSpec) -> SqlType {
-        Self::convert_data_type(dt)
-    }
-
-    /// Convert AST data type to SqlType (static helper).
-    fn convert_data_type(dt: &DataTypeSpec) -> SqlType {
-        match &dt.kind {
-            DataTypeKind::Bool => SqlType::Bool,
-            DataTypeKind::Int32 => SqlType::Int32,
-            DataTypeKind::Int64 => SqlType::Int64,
-            DataTypeKind::Uint32 => SqlType::Uint32,
-            DataTypeKind::Uint64 => SqlType::Uint64,
-            DataTypeKind::Float32 => SqlType::Float32,
-            DataTypeKind::Float64 => SqlType::Float64,
-            DataTypeKind::Numeric { .. } => SqlType::Numeric {
-                precision: None,
-                scale: None,
+    /// Resolve the base window a specification refines, if it names one.
+    ///
+    /// The result inherits the base window's PARTITION BY and ORDER BY. The
+    /// refining specification cannot repartition, can add ORDER BY only when
+    /// the base has none, and cannot refine a base that has a frame.
+    pub(crate) fn resolve_window_spec(
+        &self,
+        spec: &WindowSpec,
+        scope: &Scope,
+    ) -> Result<WindowSpec, AnalyzerError> {
+        let Some(base_name) = &spec.base else {
+            return Ok(spec.clone());
+        };
+        let invalid = |reason: String, span: Span| {
+            AnalyzerError::with_span(AnalyzerErrorKind::InvalidWindowSpec { reason }, span)
+        };
+        let name = &base_name.value;
+        let base = scope
+            .lookup_window(name)
+            .ok_or_else(|| invalid(format!("window '{}' is not defined", name), base_name.span))?;
+        if let Some(expr) = spec.partition_by.first() {
+            return Err(invalid(
+                format!("cannot override PARTITION BY of window '{}'", name),
+                expr.span,
+            ));
+        }
+        if let (Some(item), false) = (spec.order_by.first(), base.order_by.is_empty()) {
+            return Err(invalid(
+                format!("cannot override ORDER BY of window '{}'", name),
+                item.expr.span,
+            ));
+        }
+        if base.frame.is_some() {
+            return Err(invalid(
+                format!("cannot refine window '{}', which has a frame", name),
+                base_name.span,
+            ));
+        }
+        Ok(WindowSpec {
+            base: None,
+            partition_by: base.partition_by.clone(),
+            order_by: if spec.order_by.is_empty() {
+                base.order_by.clone()
+            } else {
+                spec.order_by.clone()
             },
-            DataTypeKind::Varchar { .. } => SqlType::Varchar,
-            DataTypeKind::Varbinary { .. } => SqlType::Varbinary,
-            DataTypeKind::Date => SqlType::Date,
-            DataTypeKind::Time => SqlType::Time,
-            DataTypeKind::Datetime => SqlType::Datetime,
-            DataTypeKind::Timestamp => SqlType::Timestamp,
-            DataTypeKind::Interval => SqlType::Interval,
-            DataTypeKind::Json => SqlType::Json,
-            DataTypeKind::Uuid => SqlType::Uuid,
-            DataTypeKind::Array(elem) => SqlType::Array(Box::new(Self::convert_data_type(elem))),
-            DataTypeKind::Struct(fields) => {
-                let sql_fields: Vec<crate::types::StructField> = fields
-                    .iter()
-                    .map(|f| crate::types::StructField {
-                        name: f.name.as_ref().map(|i| i.value.clone()),
-                        data_type: Self::convert_data_type(&f.data_type),
-                    })
-                    .collect();
-                SqlType::Struct(sql_fields)
+            frame: spec.frame.clone(),
+            span: spec.span,
+        })
+    }
+
+    /// Check a resolved window specification and return its ORDER BY keys.
+    ///
+    /// Frame offsets must be non-negative constants, integers for ROWS and
+    /// GROUPS. A frame cannot start after it ends. GROUPS frames need an
+    /// ORDER BY, and RANGE frames with an offset need exactly one numeric
+    /// or date/time ORDER BY key.
+    pub(crate) fn check_window_spec(
+        &self,
+        spec: &WindowSpec,
+        scope: &Scope,
+    ) -> Result<Vec<SortKey>, AnalyzerError> {
+        let sort_keys = self.check_order_by(&spec.order_by, scope)?;
+        let Some(frame) = &spec.frame else {
+            return Ok(sort_keys);
+        };
+        let invalid = |reason: String, span: Span| {
+            AnalyzerError::with_span(AnalyzerErrorKind::InvalidWindowSpec { reason }, span)
+        };
+
+        if frame.unit == WindowFrameUnit::Groups && spec.order_by.is_empty() {
+            return Err(invalid(
+                "GROUPS frame requires ORDER BY in the window specification".to_string(),
+                frame.span,
+            ));
+        }
+
+        let end = frame.end.as_ref().unwrap_or(&WindowFrameBound::CurrentRow);
+        let mut has_offset = false;
+        for bound in [&frame.start, end] {
+            if let WindowFrameBound::Preceding(Some(offset))
+            | WindowFrameBound::Following(Some(offset)) = bound
+            {
+                check_frame_offset(frame.unit, offset).map_err(|r| invalid(r, offset.span))?;
+                has_offset = true;
+            }
+        }
+
+        if matches!(frame.start, WindowFrameBound::Following(None)) {
+            return Err(invalid(
+                "frame cannot start at UNBOUNDED FOLLOWING".to_string(),
+                frame.span,
+            ));
+        }
+        if matches!(end, WindowFrameBound::Preceding(None)) {
+            return Err(invalid(
+                "frame cannot end at UNBOUNDED PRECEDING".to_string(),
+                frame.span,
+            ));
+        }
+        if frame_bound_rank(&frame.start) > frame_bound_rank(end) {
+            return Err(invalid(
+                "frame starts after it ends".to_string(),
+                frame.span,
+            ));
+        }
+
+        if frame.unit == WindowFrameUnit::Range && has_offset {
+            let [key] = sort_keys.as_slice() else {
+                return Err(invalid(
+                    "RANGE frame with an offset requires exactly one ORDER BY key".to_string(),
+                    frame.span,
+                ));
+            };
+            let key_type = &key.data_type;
+            if !(key_type.is_numeric()
+                || key_type.is_datetime()
+                || matches!(key_type, SqlType::Unknown | SqlType::Any))
+            {
+                return Err(invalid(
+                    format!(
+                        "RANGE frame with an offset requires a numeric or date/time \
+                         ORDER BY key, not {}",
+                        key_type
+                    ),
+                    spec.order_by[0].expr.span,
+                ));
             }
-            DataTypeKind::Range(elem) => SqlType::Range(Box::new(Self::convert_data_type(elem))),
-            DataTypeKind::Named(_) => SqlType::Unknown,
         }
+        Ok(sort_keys)
+    }
+}
+
+/// Window functions that rank rows by the window's ORDER BY.
+const RANKING_FUNCTIONS: &[&str] = &[
+    "ROW_NUMBER",
+    "RANK",
+    "DENSE_RANK",
+    "PERCENT_RANK",
+    "CUME_DIST",
+    "NTILE",
+];
+
+/// Aggregate functions that count rows, and so are never NULL, even over
+/// no rows.
+const NON_NULL_AGGREGATES: &[&str] = &["COUNT", "COUNTIF"];
+
+/// Check a frame offset, returning the reason it is invalid.
+/// Point a failed column lookup that has no span at the reference.
+fn at_reference(mut err: AnalyzerError, span: Span) -> AnalyzerError {
+    if err.span.is_none()
+        && matches!(
+            err.kind,
+            AnalyzerErrorKind::ColumnNotFound { .. } | AnalyzerErrorKind::AmbiguousColumn { .. }
+        )
+    {
+        err.span = Some(span);
     }
+    err
+}
+
+/// Point a failed qualified column lookup at its qualifier, as an
+/// undefined table, when no enclosing query defines that table, and
+/// otherwise at the reference.
+fn at_qualifier(err: AnalyzerError, parts: &[Ident], scope: &Scope, span: Span) -> AnalyzerError {
+    if let (AnalyzerErrorKind::ColumnNotFound { table: Some(_), .. }, [.., table, _]) =
+        (&err.kind, parts)
+    {
+        let mut scopes = std::iter::successors(Some(scope), |s| s.outer());
+        if err.span.is_none() && !scopes.any(|s| s.lookup_table(&table.value).is_some()) {
+            let kind = AnalyzerErrorKind::TableNotFound {
+                name: table.value.clone(),
+            };
+            let tables = scope.all_tables().map(|t| t.alias.as_str());
+            return AnalyzerError::with_span(kind, table.span).suggesting(tables);
+        }
+    }
+    at_reference(err, span)
+}
+
+fn check_frame_offset(unit: WindowFrameUnit, offset: &Expr) -> Result<(), String> {
+    let integral = unit != WindowFrameUnit::Range;
+    match &offset.kind {
+        ExprKind::Integer(_) | ExprKind::Parameter(_) => Ok(()),
+        ExprKind::Float(_) | ExprKind::Interval { .. } if !integral => Ok(()),
+        ExprKind::Float(_) | ExprKind::Interval { .. } => Err(format!(
+            "{} frame offsets must be integers",
+            if unit == WindowFrameUnit::Rows {
+                "ROWS"
+            } else {
+                "GROUPS"
+            }
+        )),
+        ExprKind::UnaryOp {
+            op: UnaryOp::Minus,
+            expr,
+        } if matches!(expr.kind, ExprKind::Integer(_) | ExprKind::Float(_)) => {
+            Err("frame offsets must not be negative".to_string())
+        }
+        _ => Err("frame offsets must be constants".to_string()),
+    }
+}
+
+/// The position of a frame bound, from UNBOUNDED PRECEDING to UNBOUNDED
+/// FOLLOWING.
+fn frame_bound_rank(bound: &WindowFrameBound) -> u8 {
+    match bound {
+        WindowFrameBound::Preceding(None) => 0,
+        WindowFrameBound::Preceding(Some(_)) => 1,
+        WindowFrameBound::CurrentRow => 2,
+        WindowFrameBound::Following(Some(_)) => 3,
+        WindowFrameBound::Following(None) => 4,
+    }
+}
+
+/// Check that two compared values have comparable types.
+///
+/// Types are comparable when one coerces to the other or both are numbers
+/// or date/time values. A string literal is compared after conversion to
+/// the other operand's type.
+pub(crate) fn check_comparable(
+    (left, left_type): (&Expr, &SqlType),
+    (right, right_type): (&Expr, &SqlType),
+    span: Span,
+) -> Result<(), AnalyzerError> {
+    let string_literal_for = |expr: &Expr, other: &SqlType| {
+        matches!(expr.kind, ExprKind::String(_))
+            && SqlType::Varchar(None).can_cast_to(other) != CastKind::Invalid
+    };
+    if comparable_types(left_type, right_type)
+        || string_literal_for(left, right_type)
+        || string_literal_for(right, left_type)
+    {
+        return Ok(());
+    }
+    Err(AnalyzerError::with_span(
+        AnalyzerErrorKind::TypesNotComparable {
+            left: left_type.clone(),
+            right: right_type.clone(),
+        },
+        span,
+    ))
+}
+
+/// Check the string of a typed literal such as `DATE '2024-01-31'`, which
+/// is a constant of the literal's type when the string is a value of it.
+fn check_typed_literal(
+    data_type: TypedLiteralType,
+    text: &str,
+    span: Span,
+) -> Result<TypedExpr, AnalyzerError> {
+    let sql_type = match data_type {
+        TypedLiteralType::Date => SqlType::Date,
+        TypedLiteralType::Time => SqlType::Time,
+        TypedLiteralType::Datetime => SqlType::Datetime,
+        TypedLiteralType::Timestamp => SqlType::Timestamp,
+        TypedLiteralType::Json => SqlType::Json,
+        TypedLiteralType::Numeric | TypedLiteralType::Bignumeric => SqlType::Numeric {
+            precision: None,
+            scale: None,
+        },
+        TypedLiteralType::Range => SqlType::Unknown,
+    };
+    let value = Value::parse_typed_literal(data_type, text);
+    match value {
+        Ok(value) => Ok(TypedExpr::constant(sql_type, value)),
+        Err(reason) => Err(AnalyzerError::with_span(
+            AnalyzerErrorKind::InvalidLiteral {
+                value: text.to_string(),
+                expected_type: data_type.to_string(),
+                reason,
+            },
+            span,
+        )),
+    }
+}
+
+/// The only output column of a subquery used as a value.
+fn single_column<'q>(
+    analyzed: &'q AnalyzedQuery,
+    query: &Query,
+) -> Result<&'q OutputColumn, AnalyzerError> {
+    match analyzed.columns.as_slice() {
+        [column] => Ok(column),
+        columns => Err(AnalyzerError::with_span(
+            AnalyzerErrorKind::SubqueryColumnCount {
+                expected: 1,
+                found: columns.len(),
+            },
+            query.span,
+        )),
+    }
+}
+
+/// Find field `name` of struct type `data_type`, where `parent` names the
+/// struct in errors.
+///
+/// A type that is not a struct is an error at the field name, as is a
+/// name that matches no field, which lists the struct's fields.
+pub(crate) fn struct_field<'t>(
+    data_type: &'t SqlType,
+    parent: &str,
+    name: &Ident,
+    matching: IdentifierMatching,
+) -> Result<&'t crate::types::StructField, AnalyzerError> {
+    let fields = data_type.struct_fields().ok_or_else(|| {
+        AnalyzerError::with_span(
+            AnalyzerErrorKind::Other {
+                message: format!("'{}' is not a struct", parent),
+            },
+            name.span,
+        )
+    })?;
+    fields
+        .iter()
+        .find(|f| {
+            f.name
+                .as_deref()
+                .is_some_and(|n| matching.matches(n, &name.value))
+        })
+        .ok_or_else(|| {
+            let available: Vec<&str> = fields.iter().filter_map(|f| f.name.as_deref()).collect();
+            AnalyzerError::with_span(
+                AnalyzerErrorKind::FieldNotFound {
+                    name: name.value.clone(),
+                    parent: parent.to_string(),
+                    available: available.iter().map(|n| n.to_string()).collect(),
+                },
+                name.span,
+            )
+            .suggesting(available)
+        })
+}
+
+/// Check whether values of two types can be compared, field by field for
+/// structs such as row values.
+fn comparable_types(left: &SqlType, right: &SqlType) -> bool {
+    match (left, right) {
+        (SqlType::Struct(a), SqlType::Struct(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|(a, b)| comparable_types(&a.data_type, &b.data_type))
+        }
+        (SqlType::Array(a), SqlType::Array(b)) => comparable_types(a, b),
+        _ => {
+            left.is_comparable_with(right)
+                || left.can_cast_to(right) == CastKind::Implicit
+                || right.can_cast_to(left) == CastKind::Implicit
+        }
+    }
+}
+
+/// The types of checked arguments, for deriving a call's result type.
+fn argument_types(args: &[TypedExpr]) -> Vec<SqlType> {
+    args.iter().map(|a| a.data_type.clone()).collect()
+}
+
+/// A checked function argument: its position, span, and type.
+type CheckedArg = (usize, Span, TypedExpr);
+
+/// Find the first argument a signature cannot accept, with the declared
+/// and actual types.
+fn argument_mismatch<'s>(
+    sig: &'s FunctionSignature,
+    args: &'s [CheckedArg],
+) -> Option<(usize, Span, &'s SqlType, &'s SqlType)> {
+    args.iter().find_map(|(i, span, typed)| {
+        let expected = sig.parameter(*i)?.data_type.as_ref()?;
+        (!typed.data_type.can_coerce_to(expected)).then_some((
+            *i,
+            *span,
+            expected,
+            &typed.data_type,
+        ))
+    })
+}
+
+/// Count the arguments whose type is exactly the declared parameter type.
+fn exact_argument_matches(sig: &FunctionSignature, args: &[CheckedArg]) -> usize {
+    args.iter()
+        .filter(|(i, _, typed)| {
+            sig.parameter(*i)
+                .and_then(|p| p.data_type.as_ref())
+                .is_some_and(|expected| *expected == typed.data_type)
+        })
+        .count()
+}
+
+/// The parameter an expression consists of, looking through parentheses.
+fn parameter_of(expr: &Expr) -> Option<&Parameter> {
+    match &expr.kind {
+        ExprKind::Parameter(parameter) => Some(parameter),
+        ExprKind::Parenthesized(inner) => parameter_of(inner),
+        _ => None,
+    }
+}
+
+/// The type a parameter operand of `op` takes when the other operand has
+/// type `other`.
+fn operand_hint(op: BinaryOp, other: &SqlType) -> SqlType {
+    match op {
+        BinaryOp::And | BinaryOp::Or => SqlType::Bool,
+        BinaryOp::Concat => SqlType::Varchar(None),
+        BinaryOp::BitwiseAnd
+        | BinaryOp::BitwiseOr
+        | BinaryOp::BitwiseXor
+        | BinaryOp::LeftShift
+        | BinaryOp::RightShift => SqlType::Int64,
+        _ => other.clone(),
+    }
+}
+
+/// Convert an AST data type to a SqlType, resolving custom types through
+/// the catalog and reporting unknown ones as analyzer errors.
+pub(crate) fn sql_type_from_spec<C: Catalog + ?Sized>(
+    catalog: &C,
+    dt: &DataTypeSpec,
+) -> Result<SqlType, AnalyzerError> {
+    dt.to_sql_type_with(&mut |name| catalog.resolve_type(name))
+        .map_err(|e| {
+            let name = match e.kind() {
+                ErrorKind::UndefinedType(name) => name.clone(),
+                _ => dt.kind.to_string(),
+            };
+            AnalyzerError::with_span(
+                AnalyzerErrorKind::TypeNotFound { name },
+                e.span().unwrap_or(dt.span),
+            )
+        })
 }
 
 #[cfg(test)]
@@ -601,7 +2071,7 @@ mod tests {
         // String literal
         let expr = Expr::new(ExprKind::String("hello".to_string()), Span::default());
         let typed = checker.check_expr(&expr, &scope).unwrap();
-        assert_eq!(typed.data_type, SqlType::Varchar);
+        assert_eq!(typed.data_type, SqlType::Varchar(None));
 
         // Null literal
         let expr = Expr::new(ExprKind::Null, Span::default());
@@ -0,0 +1,151 @@
+//! Non-fatal findings reported by the analyzer.
+
+use super::error::{unguarded_write_message, NO_NATURAL_JOIN_COLUMNS};
+use super::guard::UNGUARDED_WRITE;
+use crate::ast::BinaryOp;
+use crate::error::Span;
+use crate::types::{SqlType, MAX_NUMERIC_PRECISION};
+use std::fmt;
+
+/// Analyzer warning kinds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalyzerWarningKind {
+    /// A CTE has the same name as a catalog table, so unqualified
+    /// references resolve to the CTE.
+    CteShadowsTable { name: String },
+    /// A CTE has the same name as a CTE from an enclosing query.
+    CteShadowsCte {
+        name: String,
+        /// Where the outer CTE is defined.
+        outer: Span,
+    },
+    /// UPDATE/DELETE/MERGE that can affect every row, reported by
+    /// [`WriteGuard::Warn`](super::WriteGuard::Warn).
+    UnguardedWrite { statement: String },
+    /// `NATURAL JOIN` inputs share no column names, reported by
+    /// [`DisjointNaturalJoin::Warn`](super::DisjointNaturalJoin::Warn).
+    NoNaturalJoinColumns,
+    /// NUMERIC arithmetic needed more than the maximum precision of 38, so
+    /// the result's scale was reduced.
+    NumericPrecisionCapped {
+        /// The precision the result needed.
+        precision: u32,
+        /// The capped result type.
+        result: SqlType,
+    },
+    /// A string or bytes literal assigned to a column is longer than the
+    /// column's VARCHAR or VARBINARY length.
+    ValueTooLong {
+        column: String,
+        column_type: SqlType,
+        /// The literal's length, in characters for strings and bytes for
+        /// bytes.
+        length: usize,
+    },
+    /// A numeric literal assigned to a NUMERIC column has more integer
+    /// digits than the column's precision and scale allow.
+    NumericOutOfRange {
+        column: String,
+        column_type: SqlType,
+    },
+    /// A comparison operator has a NULL literal operand, so it is always
+    /// NULL.
+    ComparisonWithNull { op: BinaryOp },
+}
+
+/// A warning with location information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyzerWarning {
+    /// The kind of warning.
+    pub kind: AnalyzerWarningKind,
+    /// The source span the warning applies to.
+    pub span: Option<Span>,
+}
+
+impl AnalyzerWarning {
+    /// Create a new warning.
+    pub fn new(kind: AnalyzerWarningKind) -> Self {
+        Self { kind, span: None }
+    }
+
+    /// A stable code for warnings that policies act on, such as
+    /// [`UNGUARDED_WRITE`].
+    pub fn code(&self) -> Option<&'static str> {
+        match self.kind {
+            AnalyzerWarningKind::UnguardedWrite { .. } => Some(UNGUARDED_WRITE),
+            _ => None,
+        }
+    }
+
+    /// Create a new warning with a span.
+    pub fn with_span(kind: AnalyzerWarningKind, span: Span) -> Self {
+        Self {
+            kind,
+            span: Some(span),
+        }
+    }
+}
+
+impl fmt::Display for AnalyzerWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            AnalyzerWarningKind::CteShadowsTable { name } => {
+                write!(
+                    f,
+                    "CTE '{}' shadows table '{}'; qualify the table name to reference it",
+                    name, name
+                )
+            }
+            AnalyzerWarningKind::CteShadowsCte { name, outer } => {
+                write!(
+                    f,
+                    "CTE '{}' shadows an outer CTE defined at position {}",
+                    name, outer.start
+                )
+            }
+            AnalyzerWarningKind::UnguardedWrite { statement } => {
+                write!(f, "{}", unguarded_write_message(statement))
+            }
+            AnalyzerWarningKind::NoNaturalJoinColumns => {
+                write!(f, "{}", NO_NATURAL_JOIN_COLUMNS)
+            }
+            AnalyzerWarningKind::NumericPrecisionCapped { precision, result } => {
+                write!(
+                    f,
+                    "NUMERIC result needs precision {}, more than the maximum of {}; \
+                     it is {}",
+                    precision, MAX_NUMERIC_PRECISION, result
+                )
+            }
+            AnalyzerWarningKind::ValueTooLong {
+                column,
+                column_type,
+                length,
+            } => {
+                write!(
+                    f,
+                    "value of length {} is too long for column '{}' of type {}",
+                    length, column, column_type
+                )
+            }
+            AnalyzerWarningKind::NumericOutOfRange {
+                column,
+                column_type,
+            } => {
+                write!(
+                    f,
+                    "value is out of range for column '{}' of type {}",
+                    column, column_type
+                )
+            }
+            AnalyzerWarningKind::ComparisonWithNull { op } => {
+                let instead = match op {
+                    BinaryOp::Eq => "; use IS NULL or IS NOT DISTINCT FROM",
+                    BinaryOp::NotEq => "; use IS NOT NULL or IS DISTINCT FROM",
+                    _ => "",
+                };
+                write!(f, "'{}' with a NULL operand is always NULL{}", op, instead)
+            }
+        }
+    }
+}
@@ -0,0 +1,654 @@
+//! Public API surface checks.
+//!
+//! `docs/api-manifest.txt` lists every public item of the crate, one per
+//! line: types with their derives, enum variants with their shapes, public
+//! fields, inherent methods and free functions with their arities (a
+//! receiver counts as one argument), trait methods, trait impls, and
+//! re-exports. The manifest is generated by scanning `src/`, which relies
+//! on the code being `rustfmt`-formatted; `test_api_manifest` regenerates it
+//! and fails when it differs from the committed copy, so every change to the
+//! public surface is deliberate. To accept a change, run
+//!
+//! ```text
+//! VIBESQL_UPDATE_API=1 cargo test api_manifest
+//! ```
+//!
+//! and describe it in `CHANGELOG.md`. Removed lines and changed arities or
+//! variant shapes are breaking; added lines are not, except for new enum
+//! variants and new fields on structs whose fields are all public.
+//!
+//! Modules listed in [`UNSTABLE`] are left out of the manifest: they are
+//! public for advanced use but may change in any release.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Committed manifest, relative to the crate root.
+const MANIFEST_PATH: &str = "docs/api-manifest.txt";
+
+/// Module paths whose items are not covered by semver.
+const UNSTABLE: &[&str] = &["analyzer::scope", "analyzer::type_checker", "testgen"];
+
+const HEADER: &str = "\
+# Public API of vibesql, generated by `cargo test api_manifest`.
+# Do not edit by hand; see src/api_manifest.rs.
+";
+
+/// Build the manifest for the sources under `src`.
+fn generate(src: &Path) -> String {
+    let mut files = Vec::new();
+    collect_sources(src, &mut files);
+
+    let mut entries = Vec::new();
+    for file in files {
+        let relative = file.strip_prefix(src).unwrap();
+        if relative == Path::new("main.rs") {
+            continue;
+        }
+        let module = module_path(relative);
+        if is_unstable(&module) {
+            continue;
+        }
+        let source = fs::read_to_string(&file).unwrap();
+        scan_file(&module, &source, &mut entries);
+    }
+    entries.sort();
+    entries.dedup();
+
+    let mut manifest = HEADER.to_string();
+    for entry in entries {
+        manifest.push_str(&entry);
+        manifest.push('\n');
+    }
+    manifest
+}
+
+fn collect_sources(dir: &Path, out: &mut Vec<PathBuf>) {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_sources(&path, out);
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            out.push(path);
+        }
+    }
+}
+
+/// `analyzer/error.rs` -> `analyzer::error`; `lib.rs` -> `crate`.
+fn module_path(relative: &Path) -> String {
+    let mut parts: Vec<String> = relative
+        .iter()
+        .map(|p| p.to_string_lossy().trim_end_matches(".rs").to_string())
+        .collect();
+    if matches!(parts.last().map(String::as_str), Some("mod" | "lib")) {
+        parts.pop();
+    }
+    if parts.is_empty() {
+        "crate".to_string()
+    } else {
+        parts.join("::")
+    }
+}
+
+fn is_unstable(module: &str) -> bool {
+    UNSTABLE.iter().any(|u| {
+        module == *u
+            || module
+                .strip_prefix(u)
+                .is_some_and(|rest| rest.starts_with("::"))
+    })
+}
+
+/// Record the public items of one file. Scanning stops at the file's
+/// `#[cfg(test)] mod tests` block.
+fn scan_file(module: &str, source: &str, out: &mut Vec<String>) {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut derives: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if line == "#[cfg(test)]" && lines.get(i + 1) == Some(&"mod tests {") {
+            break;
+        }
+        if line.starts_with("#[derive(") {
+            let (text, end) = join_until(&lines, i, ")]");
+            let list = text.trim_start_matches("#[derive(").trim_end_matches(")]");
+            derives.extend(
+                list.split(',')
+                    .map(|d| d.trim().to_string())
+                    .filter(|d| !d.is_empty()),
+            );
+            i = end + 1;
+            continue;
+        }
+        if line.starts_with("#[") || line.starts_with("//") || line.is_empty() {
+            i += 1;
+            continue;
+        }
+
+        i = if let Some(rest) = line.strip_prefix("pub ") {
+            scan_item(module, &lines, i, rest, &derives, out)
+        } else if line.starts_with("impl") {
+            scan_impl(module, &lines, i, out)
+        } else {
+            i
+        } + 1;
+        derives.clear();
+    }
+}
+
+/// Record a top-level `pub` item and return its last line.
+fn scan_item(
+    module: &str,
+    lines: &[&str],
+    start: usize,
+    rest: &str,
+    derives: &[String],
+    out: &mut Vec<String>,
+) -> usize {
+    let (kind, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    let (kind, rest) = match kind {
+        "const" if rest.starts_with("fn ") => ("fn", &rest[3..]),
+        _ => (kind, rest),
+    };
+    if kind == "use" {
+        let (text, end) = join_until(lines, start, ";");
+        let path = text.trim_start_matches("pub use ").trim_end_matches(';');
+        out.push(format!(
+            "{} use {}",
+            module,
+            path.replace("{ ", "{")
+                .replace(" }", "}")
+                .replace(",}", "}")
+        ));
+        return end;
+    }
+
+    let name = ident(rest);
+    let path = format!("{}::{}", module, name);
+    let derive = if derives.is_empty() {
+        String::new()
+    } else {
+        let mut sorted = derives.to_vec();
+        sorted.sort();
+        format!(" derive({})", sorted.join(", "))
+    };
+    match kind {
+        "struct" => {
+            out.push(format!("{} struct{}", path, derive));
+            if let Some(fields) = rest.find('(').map(|p| &rest[p..]) {
+                for (n, field) in split_top_level(strip_parens(fields)).iter().enumerate() {
+                    if field.starts_with("pub ") {
+                        out.push(format!("{}.{} field", path, n));
+                    }
+                }
+            }
+            if !lines[start].ends_with('{') {
+                return start;
+            }
+            let end = block_end(lines, start);
+            for line in &lines[start + 1..end] {
+                if let Some(field) = line.strip_prefix("    pub ") {
+                    out.push(format!("{}.{} field", path, ident(field)));
+                }
+            }
+            end
+        }
+        "enum" => {
+            out.push(format!("{} enum{}", path, derive));
+            let end = block_end(lines, start);
+            let mut i = start + 1;
+            while i < end {
+                let line = lines[i];
+                let Some(variant) = line.strip_prefix("    ") else {
+                    i += 1;
+                    continue;
+                };
+                if !variant.starts_with(|c: char| c.is_ascii_uppercase()) {
+                    i += 1;
+                    continue;
+                }
+                let name = ident(variant);
+                let tail = &variant[name.len()..];
+                let shape = if tail.starts_with('(') {
+                    let (text, last) = join_balanced(lines, i, line.find('(').unwrap());
+                    i = last;
+                    format!("({})", split_top_level(strip_parens(&text)).len())
+                } else if tail.starts_with(" {") {
+                    let mut fields = Vec::new();
+                    if tail.ends_with('{') {
+                        i += 1;
+                        while !lines[i].starts_with("    }") {
+                            if let Some(field) = lines[i].strip_prefix("        ") {
+                                if field.starts_with(|c: char| c.is_ascii_lowercase()) {
+                                    fields.push(ident(field));
+                                }
+                            }
+                            i += 1;
+                        }
+                    } else {
+                        let inner = tail.trim_start_matches(" {").trim_end_matches(',');
+                        let inner = inner.trim_end_matches('}');
+                        fields = split_top_level(inner).iter().map(|f| ident(f)).collect();
+                    }
+                    format!(" {{ {} }}", fields.join(", "))
+                } else {
+                    String::new()
+                };
+                out.push(format!("{}::{}{} variant", path, name, shape));
+                i += 1;
+            }
+            end
+        }
+        "trait" => {
+            out.push(format!("{} trait", path));
+            let end = block_end(lines, start);
+            let mut i = start + 1;
+            while i < end {
+                if let Some(sig) = lines[i].strip_prefix("    fn ") {
+                    let (text, last) = join_balanced(lines, i, lines[i].find('(').unwrap());
+                    let (tail, _) = join_until(lines, last, "");
+                    let provided = if tail.trim_end().ends_with(';') {
+                        ""
+                    } else {
+                        " provided"
+                    };
+                    out.push(format!(
+                        "{}::{} trait fn({}){}",
+                        path,
+                        ident(sig),
+                        arity(&text),
+                        provided
+                    ));
+                    i = last;
+                }
+                i += 1;
+            }
+            end
+        }
+        "fn" => {
+            let (text, last) = join_balanced(lines, start, open_paren(lines[start]));
+            out.push(format!("{} fn({})", path, arity(&text)));
+            if lines[last].ends_with(';') {
+                last
+            } else {
+                block_end(lines, start)
+            }
+        }
+        "mod" => {
+            out.push(format!("{} mod", path));
+            start
+        }
+        "const" | "static" | "type" => {
+            out.push(format!("{} {}", path, kind));
+            join_until(lines, start, ";").1
+        }
+        _ => start,
+    }
+}
+
+/// Record an impl block and return its last line.
+fn scan_impl(module: &str, lines: &[&str], start: usize, out: &mut Vec<String>) -> usize {
+    let (header, end) = match lines[start].strip_suffix(" {}") {
+        Some(header) => (header.to_string(), start),
+        None => (join_until(lines, start, "{").0, block_end(lines, start)),
+    };
+    let header = header
+        .trim_start_matches("impl")
+        .trim_end_matches('{')
+        .trim();
+    let header = if header.starts_with('<') {
+        header[matching_close(header, 0) + 1..].trim()
+    } else {
+        header
+    };
+    let header = header.split(" where").next().unwrap_or(header);
+
+    if let Some((trait_name, target)) = header.split_once(" for ") {
+        let trait_name = trait_name.rsplit("::").next().unwrap_or(trait_name);
+        out.push(format!(
+            "{}::{} impl {}",
+            module,
+            type_name(target),
+            trait_name
+        ));
+        return end;
+    }
+
+    let path = format!("{}::{}", module, type_name(header));
+    let mut i = start + 1;
+    while i < end {
+        let line = lines[i];
+        let sig = line
+            .strip_prefix("    pub fn ")
+            .or_else(|| line.strip_prefix("    pub const fn "));
+        if let Some(sig) = sig {
+            let (text, last) = join_balanced(lines, i, open_paren(line));
+            out.push(format!("{}::{} fn({})", path, ident(sig), arity(&text)));
+            i = last;
+        } else if let Some(constant) = line.strip_prefix("    pub const ") {
+            out.push(format!("{}::{} const", path, ident(constant)));
+        }
+        i += 1;
+    }
+    end
+}
+
+/// The leading identifier of `s`.
+fn ident(s: &str) -> String {
+    s.chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect()
+}
+
+/// The type name of an impl target, without generic arguments.
+fn type_name(target: &str) -> String {
+    let target = target.trim();
+    match target.find('<') {
+        Some(p) if !target.starts_with('&') => target[..p].to_string(),
+        _ => target.to_string(),
+    }
+}
+
+/// Byte offset of the parameter list of the `fn` on `line`, after any
+/// generic parameters.
+fn open_paren(line: &str) -> usize {
+    let name_start = line.find("fn ").unwrap() + 3;
+    let after_name = name_start + ident(&line[name_start..]).len();
+    if line[after_name..].starts_with('<') {
+        matching_close(line, after_name) + 1
+    } else {
+        after_name
+    }
+}
+
+/// End of the item starting at `start`: the next line that closes a block
+/// at the same indentation.
+fn block_end(lines: &[&str], start: usize) -> usize {
+    let indent = lines[start].len() - lines[start].trim_start().len();
+    let closing = format!("{}}}", " ".repeat(indent));
+    (start..lines.len())
+        .find(|&i| lines[i].starts_with(&closing) && lines[i].trim_start().starts_with('}'))
+        .unwrap_or(lines.len() - 1)
+}
+
+/// Join lines from `start` through the first one ending with `terminator`.
+fn join_until(lines: &[&str], start: usize, terminator: &str) -> (String, usize) {
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        if !text.is_empty() && !text.ends_with('(') && !text.ends_with('<') {
+            text.push(' ');
+        }
+        text.push_str(line.trim());
+        if line.trim_end().ends_with(terminator) {
+            return (text, i);
+        }
+    }
+    (text, lines.len() - 1)
+}
+
+/// Join text from the delimiter at byte `open` of `lines[start]` through
+/// its matching close, returning the text and the line it ends on.
+fn join_balanced(lines: &[&str], start: usize, open: usize) -> (String, usize) {
+    let mut text = String::new();
+    let mut depth = 0i32;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        let line = if i == start {
+            &line[open..]
+        } else {
+            line.trim()
+        };
+        let mut prev = ' ';
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '<' => depth += 1,
+                '>' if prev == '-' => {}
+                ')' | ']' | '>' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+            prev = c;
+            if depth == 0 {
+                return (text, i);
+            }
+        }
+        text.push(' ');
+    }
+    (text, lines.len() - 1)
+}
+
+/// Offset of the delimiter closing the one at byte `open` of `s`.
+fn matching_close(s: &str, open: usize) -> usize {
+    let mut depth = 0;
+    let mut prev = ' ';
+    for (i, c) in s[open..].char_indices() {
+        match c {
+            '(' | '[' | '<' => depth += 1,
+            '>' if prev == '-' => {}
+            ')' | ']' | '>' => depth -= 1,
+            _ => {}
+        }
+        prev = c;
+        if depth == 0 {
+            return open + i;
+        }
+    }
+    s.len() - 1
+}
+
+/// `(a, b)` -> `a, b`.
+fn strip_parens(s: &str) -> &str {
+    let s = s.trim();
+    let s = s.strip_prefix('(').unwrap_or(s);
+    let end = matching_close(&format!("({}", s), 0);
+    &s[..end.saturating_sub(1).min(s.len())]
+}
+
+/// Split on commas outside of any delimiters, dropping empty parts.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut prev = ' ';
+    let mut part_start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' | '<' | '{' => depth += 1,
+            '>' if prev == '-' => {}
+            ')' | ']' | '>' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(s[part_start..i].trim());
+                part_start = i + 1;
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    parts.push(s[part_start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+/// Number of parameters in a `(...)` parameter list.
+fn arity(params: &str) -> usize {
+    split_top_level(strip_parens(params)).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{AnalyzerOptions, UsingWildcardMode};
+    use crate::catalog::TypeRegistry;
+    use crate::{
+        Analyzer, Catalog, CatalogBuilder, ColumnSchema, FunctionSignature, Lexer, MemoryCatalog,
+        Parser, SqlType, StatementKind, TableSchemaBuilder, TokenKind, Value,
+    };
+
+    #[test]
+    fn test_api_manifest() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let generated = generate(&root.join("src"));
+        let path = root.join(MANIFEST_PATH);
+        if std::env::var_os("VIBESQL_UPDATE_API").is_some() {
+            fs::write(&path, &generated).unwrap();
+            return;
+        }
+
+        let committed = fs::read_to_string(&path).unwrap_or_default();
+        if committed == generated {
+            return;
+        }
+        let old: Vec<&str> = committed.lines().collect();
+        let new: Vec<&str> = generated.lines().collect();
+        let mut diff = String::new();
+        for line in old.iter().filter(|l| !new.contains(l)) {
+            diff.push_str(&format!("- {}\n", line));
+        }
+        for line in new.iter().filter(|l| !old.contains(l)) {
+            diff.push_str(&format!("+ {}\n", line));
+        }
+        panic!(
+            "the public API changed:\n{}\n\
+             If this is intentional, regenerate {} with\n\n    \
+             VIBESQL_UPDATE_API=1 cargo test api_manifest\n\n\
+             and describe the change in CHANGELOG.md. Removed lines and changed \
+             arities or variant shapes are breaking changes.",
+            diff, MANIFEST_PATH
+        );
+    }
+
+    #[test]
+    fn test_scanner() {
+        let source = "\
+/// Doc.
+#[derive(Debug, Clone)]
+pub enum Shape<T> {
+    /// Unit.
+    Empty,
+    Pair(Vec<T>, fn(u8) -> u8),
+    Named { width: u32, height: u32 },
+    Long {
+        /// Field doc.
+        a: Option<Box<T>>,
+        b: u8,
+    },
+}
+
+pub struct Point(pub u32, u32);
+
+impl<T: Clone> Shape<T> {
+    pub fn new() -> Self {
+        Shape::Empty
+    }
+
+    pub fn with<F: Fn(u8) -> u8>(
+        self,
+        f: F,
+        other: (u8, u8),
+    ) -> Self {
+        self
+    }
+
+    fn private(&self) {}
+}
+
+impl<T> std::fmt::Display for Shape<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    pub fn ignored() {}
+}
+";
+        let mut out = Vec::new();
+        scan_file("m", source, &mut out);
+        assert_eq!(
+            out,
+            [
+                "m::Shape enum derive(Clone, Debug)",
+                "m::Shape::Empty variant",
+                "m::Shape::Pair(2) variant",
+                "m::Shape::Named { width, height } variant",
+                "m::Shape::Long { a, b } variant",
+                "m::Point struct",
+                "m::Point.0 field",
+                "m::Shape::new fn(0)",
+                "m::Shape::with fn(3)",
+                "m::Shape impl Display",
+            ]
+        );
+        assert!(is_unstable("analyzer::scope"));
+        assert!(!is_unstable("analyzer::scoped"));
+    }
+
+    /// Construct every type re-exported from the crate root and call its
+    /// main entry points, so signature changes break here first.
+    #[test]
+    fn test_reexport_exemplars() {
+        let tokens = Lexer::new("SELECT 1").tokenize().unwrap();
+        assert!(matches!(tokens[0].kind, TokenKind::Keyword(_)));
+
+        let mut parser = Parser::new("SELECT id, name FROM users WHERE id = 1");
+        let stmt = parser.parse_statement().unwrap();
+        assert!(parser.diagnostics().is_empty());
+        let StatementKind::Query(query) = &stmt.kind else {
+            panic!("expected a query");
+        };
+
+        let catalog: MemoryCatalog = CatalogBuilder::new()
+            .with_builtins()
+            .add_scalar_function("MY_HASH", SqlType::Int64)
+            .add_table("users", |t| {
+                t.primary_key("id", SqlType::Int64)
+                    .column("name", SqlType::Varchar(None))
+            })
+            .build();
+        assert!(catalog
+            .resolve_table(&["users".to_string()])
+            .unwrap()
+            .is_some());
+        assert!(catalog
+            .resolve_function(&["my_hash".to_string()])
+            .unwrap()
+            .is_some());
+
+        let mut other = MemoryCatalog::new();
+        other.add_table(
+            TableSchemaBuilder::new("users")
+                .column(ColumnSchema::new("id", SqlType::Int64).not_null())
+                .column(ColumnSchema::new("name", SqlType::Varchar(None)))
+                .build(),
+        );
+        other.add_function(FunctionSignature::scalar("MY_HASH", SqlType::Int64));
+        let registry = TypeRegistry::new();
+        assert!(registry.resolve("NO_SUCH_TYPE").is_none());
+
+        let options = AnalyzerOptions::new().with_using_wildcard(UsingWildcardMode::Coalesced);
+        let mut analyzer = Analyzer::with_catalog_and_options(catalog, options);
+        let analyzed = analyzer.analyze_query_result(query).unwrap();
+        assert_eq!(analyzed.columns.len(), 2);
+        assert_eq!(analyzed.columns[0].data_type, SqlType::Int64);
+        assert!(!analyzed.columns[0].nullable);
+        analyzer.analyze(&stmt).unwrap();
+
+        let err = Analyzer::with_catalog(other)
+            .analyze(
+                &Parser::new("SELECT x FROM users")
+                    .parse_statement()
+                    .unwrap(),
+            )
+            .unwrap_err();
+        assert!(!err.to_string().is_empty());
+        let result: crate::Result<()> = Err(err);
+        assert!(result.is_err());
+
+        assert_eq!(Value::Int64(1), Value::Int64(1));
+        assert_eq!(SqlType::Int64.to_string(), "BIGINT");
+    }
+}
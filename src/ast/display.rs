@@ -0,0 +1,3003 @@
+//! SQL serialization of the AST.
+//!
+//! Statements, queries, expressions, and the nodes inside them implement
+//! [`Display`] by writing SQL that parses back to an equivalent tree: the
+//! same nodes, with different spans. Output is a single line with
+//! upper-case keywords. Quoted identifiers keep their backticks, string and
+//! bytes literals are re-escaped, and parentheses are added wherever a
+//! child binds more loosely than its position requires. Parentheses that
+//! were in the source are [`ExprKind::Parenthesized`] nodes and print as
+//! written, so parsed trees never gain extra ones.
+//!
+//! Each node first describes its SQL as a layout [`Doc`], which
+//! [`crate::format`] also renders as indented, multi-line SQL.
+//!
+//! Printing recurses once per level of nesting, which the parser's limit,
+//! [`Parser::with_max_depth`](crate::Parser::with_max_depth), bounds. A
+//! flat chain such as `a OR b OR c` is a tree as deep as it is long, so
+//! its operators are printed in a loop instead.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::*;
+use crate::format::doc::{bracketed, clause, docs, group, kw, list, nest, Doc};
+
+/// Binding strength of postfix forms: `IS`, `IN`, `BETWEEN`, `LIKE`, field
+/// access, and subscripts. Binary operators bind more loosely, at their
+/// [`BinaryOp::precedence`].
+const POSTFIX: u8 = 11;
+
+/// Binding strength of `NOT`, whose operand extends to the next `AND` or
+/// `OR`. See [`trailing_operand`].
+const NOT: u8 = 3;
+
+/// Binding strength of the other prefix operators, whose operand is parsed
+/// without any postfix or binary continuation.
+const UNARY: u8 = 12;
+
+/// Binding strength of self-delimiting expressions.
+const PRIMARY: u8 = 13;
+
+/// How tightly the outermost syntax of an expression binds.
+fn binding(expr: &Expr) -> u8 {
+    match &expr.kind {
+        ExprKind::BinaryOp { op, .. } | ExprKind::QuantifiedComparison { op, .. } => {
+            op.precedence()
+        }
+        ExprKind::UnaryOp {
+            op: UnaryOp::Not, ..
+        } => NOT,
+        ExprKind::UnaryOp { .. } => UNARY,
+        ExprKind::Between { .. }
+        | ExprKind::In { .. }
+        | ExprKind::InSubquery { .. }
+        | ExprKind::Like { .. }
+        | ExprKind::QuantifiedLike { .. }
+        | ExprKind::SimilarTo { .. }
+        | ExprKind::IsExpr { .. }
+        | ExprKind::IsDistinct { .. }
+        | ExprKind::ArraySubscript { .. }
+        | ExprKind::SafeArraySubscript { .. }
+        | ExprKind::FieldAccess { .. }
+        | ExprKind::JsonSubscript { .. } => POSTFIX,
+        _ => PRIMARY,
+    }
+}
+
+/// An expression in a position that requires binding strength `min`,
+/// parenthesized when it binds more loosely.
+fn operand(expr: &Expr, min: u8) -> Doc {
+    if binding(expr) < min {
+        docs!["(", expr.doc(), ")"]
+    } else {
+        expr.doc()
+    }
+}
+
+/// An operand that the parser reads down to a minimum precedence, `min`,
+/// such as the right operand of a binary operator.
+///
+/// The parser gives a `NOT` there an operand that binds at least as
+/// tightly as `min` as well (`a = NOT b = c` is `(a = NOT b) = c`), so
+/// the `NOT` itself needs no parentheses.
+fn trailing_operand(expr: &Expr, min: u8) -> Doc {
+    match &expr.kind {
+        ExprKind::UnaryOp {
+            op: UnaryOp::Not,
+            expr,
+        } => docs![kw("NOT"), " ", trailing_operand(expr, min.max(NOT))],
+        _ => operand(expr, min),
+    }
+}
+
+/// The left operand of a postfix form.
+///
+/// `BETWEEN`, `LIKE` and an `ESCAPE` clause end in an operand that would
+/// absorb a following postfix form (`a LIKE b IS NULL` is
+/// `a LIKE (b IS NULL)`), so they are parenthesized here even though they
+/// bind as tightly as postfix forms.
+fn postfix_operand(expr: &Expr) -> Doc {
+    match expr.kind {
+        ExprKind::Between { .. }
+        | ExprKind::Like { .. }
+        | ExprKind::SimilarTo { .. }
+        | ExprKind::QuantifiedLike {
+            escape: Some(_), ..
+        } => operand(expr, PRIMARY),
+        _ => operand(expr, POSTFIX),
+    }
+}
+
+/// Text for a node with a single-line `Display`, such as an identifier.
+fn shown(node: &impl Display) -> Doc {
+    Doc::from(node.to_string())
+}
+
+/// Dot-separated identifiers.
+fn path(parts: &[Ident]) -> Doc {
+    let parts: Vec<String> = parts.iter().map(Ident::to_string).collect();
+    Doc::from(parts.join("."))
+}
+
+/// A parenthesized query, on its own indented lines unless it fits.
+fn subquery(query: &Query) -> Doc {
+    bracketed("(", query.doc(), ")")
+}
+
+/// A parenthesized comma-separated list.
+fn parenthesized(items: impl IntoIterator<Item = Doc>) -> Doc {
+    bracketed("(", list(items), ")")
+}
+
+/// The values of IN or a quantified comparison.
+fn in_list(list: &InList) -> Doc {
+    match list {
+        InList::Values(values) => parenthesized(values.iter().map(|v| v.doc())),
+        InList::Subquery(query) => subquery(query),
+        InList::Unnest(array) => docs![kw("UNNEST"), parenthesized([array.doc()])],
+    }
+}
+
+/// Write `s` between `quote` characters, escaping it for the lexer.
+pub(crate) fn write_quoted(out: &mut impl fmt::Write, s: &str, quote: char) -> fmt::Result {
+    out.write_char(quote)?;
+    for c in s.chars() {
+        match c {
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            c if c == quote => write!(out, "\\{}", c)?,
+            c if c.is_control() && (c as u32) <= 0xff => write!(out, "\\x{:02x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char(quote)
+}
+
+/// A single-quoted string literal.
+fn string(s: &str) -> Doc {
+    let mut out = String::with_capacity(s.len() + 2);
+    // Writing to a String cannot fail.
+    let _ = write_quoted(&mut out, s, '\'');
+    Doc::from(out)
+}
+
+/// `ANY`, `SOME` or `ALL`.
+fn quantifier(quantifier: Quantifier) -> Doc {
+    kw(match quantifier {
+        Quantifier::Any => "ANY",
+        Quantifier::Some => "SOME",
+        Quantifier::All => "ALL",
+    })
+}
+
+/// ` ESCAPE escape` when a pattern match has an escape character.
+fn escape(escape: &Option<Box<Expr>>) -> Doc {
+    match escape {
+        Some(escape) => docs![" ", kw("ESCAPE"), " ", trailing_operand(escape, POSTFIX)],
+        None => docs![],
+    }
+}
+
+/// `"NOT "` for negated forms.
+fn not(negated: bool) -> Doc {
+    if negated {
+        docs![kw("NOT"), " "]
+    } else {
+        docs![]
+    }
+}
+
+/// ` OPTIONS(...)` when there are options.
+fn options(options: &[SqlOption]) -> Doc {
+    if options.is_empty() {
+        docs![]
+    } else {
+        docs![" ", kw("OPTIONS"), options_list(options)]
+    }
+}
+
+/// `(name = value, ...)`.
+fn options_list(options: &[SqlOption]) -> Doc {
+    parenthesized(options.iter().map(SqlOption::doc))
+}
+
+/// ` (a, b)` when there are identifiers.
+fn column_list(columns: &[Ident]) -> Doc {
+    if columns.is_empty() {
+        docs![]
+    } else {
+        docs![" ", parenthesized(columns.iter().map(shown))]
+    }
+}
+
+/// A WHERE clause on its own line, when there is a condition.
+fn where_clause(condition: Option<&Expr>) -> Doc {
+    match condition {
+        Some(condition) => docs![Doc::HardLine, clause(kw("WHERE"), condition.doc())],
+        None => docs![],
+    }
+}
+
+/// ` AS a (c1, c2)` when there is an alias.
+fn alias(alias: Option<&Alias>) -> Doc {
+    match alias {
+        Some(alias) => docs![" ", alias.doc()],
+        None => docs![],
+    }
+}
+
+/// ` @{hint = value, ...}` when there are hints.
+fn hints(hints: &[SqlOption]) -> Doc {
+    if hints.is_empty() {
+        docs![]
+    } else {
+        docs![
+            " @",
+            bracketed("{", list(hints.iter().map(SqlOption::doc)), "}")
+        ]
+    }
+}
+
+/// ` /*+ hint ... */` when there are optimizer hints.
+fn optimizer_hints(hints: &[Hint]) -> Doc {
+    if hints.is_empty() {
+        docs![]
+    } else {
+        let mut parts = vec![Doc::from(" /*+")];
+        for hint in hints {
+            parts.push(docs![" ", hint.doc()]);
+        }
+        parts.push(Doc::from(" */"));
+        Doc::Concat(parts)
+    }
+}
+
+/// ` TABLESAMPLE ...` when there is a sample clause.
+fn table_sample(sample: Option<&TableSample>) -> Doc {
+    match sample {
+        Some(sample) => docs![" ", sample.doc()],
+        None => docs![],
+    }
+}
+
+/// `LATERAL ` for a lateral subquery or table function.
+fn lateral_keyword(lateral: bool) -> Doc {
+    if lateral {
+        docs![kw("LATERAL"), " "]
+    } else {
+        docs![]
+    }
+}
+
+/// A RETURNING clause on its own line, when there is one.
+fn returning(returning: Option<&ReturningClause>) -> Doc {
+    match returning {
+        Some(returning) => {
+            let items = list(returning.items.iter().map(SelectItem::doc));
+            let with_action = if returning.with_action {
+                docs![" ", kw("WITH ACTION")]
+            } else {
+                docs![]
+            };
+            docs![Doc::HardLine, clause(kw("RETURNING"), items), with_action]
+        }
+        None => docs![],
+    }
+}
+
+/// `IF NOT EXISTS ` or `IF EXISTS ` when the flag is set.
+fn if_exists(set: bool, keyword: &'static str) -> Doc {
+    if set {
+        docs![kw(keyword), " "]
+    } else {
+        docs![]
+    }
+}
+
+/// Statements of a BEGIN ... END body, each on its own indented line.
+fn block(statements: &[Statement]) -> Doc {
+    let body: Vec<Doc> = statements
+        .iter()
+        .map(|s| docs![Doc::HardLine, s.doc(), ";"])
+        .collect();
+    docs![
+        kw("BEGIN"),
+        nest(Doc::Concat(body)),
+        Doc::HardLine,
+        kw("END")
+    ]
+}
+
+/// Close a parameterized type whose last parameter is `inner`, keeping
+/// `>>` apart because it lexes as a shift operator.
+fn close_type(inner: &Doc) -> Doc {
+    if inner.flat().ends_with('>') {
+        Doc::from(" >")
+    } else {
+        Doc::from(">")
+    }
+}
+
+// ============================================================================
+// Expressions
+// ============================================================================
+
+/// A float literal as SQL that evaluates to it.
+///
+/// Infinity prints as a literal too large to represent, which reads back as
+/// the same literal. Negative infinity and NaN have no literal form, since
+/// a minus sign is a separate operator, so they print as casts.
+fn float(n: f64) -> String {
+    if n.is_nan() {
+        "CAST('NaN' AS FLOAT64)".to_string()
+    } else if n == f64::INFINITY {
+        "1e999".to_string()
+    } else if n == f64::NEG_INFINITY {
+        "CAST('-inf' AS FLOAT64)".to_string()
+    } else {
+        format!("{:?}", n)
+    }
+}
+
+impl Expr {
+    /// The expression as a layout document.
+    pub(crate) fn doc(&self) -> Doc {
+        match &self.kind {
+            ExprKind::Null => kw("NULL"),
+            ExprKind::Boolean(true) => kw("TRUE"),
+            ExprKind::Boolean(false) => kw("FALSE"),
+            ExprKind::Integer(n) => Doc::from(n.to_string()),
+            ExprKind::Float(n) => Doc::from(float(*n)),
+            ExprKind::String(s) => string(s),
+            ExprKind::Bytes(bytes) => {
+                let mut out = String::from("b'");
+                for &b in bytes {
+                    match b {
+                        b'\\' => out.push_str("\\\\"),
+                        b'\'' => out.push_str("\\'"),
+                        0x20..=0x7e => out.push(b as char),
+                        _ => out.push_str(&format!("\\x{:02x}", b)),
+                    }
+                }
+                out.push('\'');
+                Doc::from(out)
+            }
+            ExprKind::Array {
+                element_type,
+                elements,
+            } => {
+                let prefix = match element_type {
+                    Some(element_type) => {
+                        let inner = element_type.doc();
+                        let close = close_type(&inner);
+                        docs![kw("ARRAY"), "<", inner, close]
+                    }
+                    None => docs![],
+                };
+                docs![
+                    prefix,
+                    bracketed("[", list(elements.iter().map(|e| e.doc())), "]")
+                ]
+            }
+            ExprKind::Struct { fields } => docs![
+                kw("STRUCT"),
+                parenthesized(fields.iter().map(StructField::doc))
+            ],
+            ExprKind::Identifier(ident) => shown(ident),
+            ExprKind::CompoundIdentifier(parts) => path(parts),
+            ExprKind::Parameter(parameter) => shown(parameter),
+            ExprKind::SystemVariable { scope, name } => match scope {
+                Some(scope) => docs!["@@", shown(scope), ".", shown(name)],
+                None => docs!["@@", shown(name)],
+            },
+            ExprKind::UnaryOp { op, expr } => {
+                let operand = match op {
+                    UnaryOp::Not => trailing_operand(expr, NOT),
+                    _ => trailing_operand(expr, UNARY),
+                };
+                match op {
+                    UnaryOp::Not => docs![kw("NOT"), " ", operand],
+                    // `--` would start a comment.
+                    UnaryOp::Minus if operand.starts_with('-') => docs!["- ", operand],
+                    _ => docs![op.to_string(), operand],
+                }
+            }
+            ExprKind::BinaryOp {
+                op: op @ (BinaryOp::And | BinaryOp::Or),
+                ..
+            } => {
+                // Keep a chain of the same operator in one group, breaking
+                // before each operator.
+                let mut operands = Vec::new();
+                self.logical_chain(*op, &mut operands);
+                let keyword = if *op == BinaryOp::And { "AND" } else { "OR" };
+                let mut parts = Vec::with_capacity(operands.len() * 4);
+                for (i, operand) in operands.into_iter().enumerate() {
+                    if i > 0 {
+                        parts.extend([Doc::Line, kw(keyword), Doc::from(" ")]);
+                    }
+                    parts.push(operand);
+                }
+                group(Doc::Concat(parts))
+            }
+            ExprKind::BinaryOp { op, left, right } => {
+                // A left operand that needs no parentheses is spliced into
+                // the same parts, so a chain such as `a + b + c` is printed
+                // in a loop rather than recursing once per operator.
+                let mut links = vec![(*op, right)];
+                let mut first = left;
+                let mut precedence = op.precedence();
+                while let ExprKind::BinaryOp { op, left, right } = &first.kind {
+                    if op.precedence() < precedence {
+                        break;
+                    }
+                    links.push((*op, right));
+                    first = left;
+                    precedence = op.precedence();
+                }
+                let mut parts = vec![operand(first, precedence)];
+                for (op, right) in links.into_iter().rev() {
+                    parts.extend([
+                        Doc::from(" "),
+                        binary_op(op),
+                        Doc::from(" "),
+                        trailing_operand(right, op.precedence() + 1),
+                    ]);
+                }
+                Doc::Concat(parts)
+            }
+            ExprKind::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => docs![
+                postfix_operand(expr),
+                " ",
+                not(*negated),
+                kw("BETWEEN"),
+                " ",
+                trailing_operand(low, POSTFIX),
+                " ",
+                kw("AND"),
+                " ",
+                trailing_operand(high, POSTFIX)
+            ],
+            ExprKind::In {
+                expr,
+                list: values,
+                negated,
+            } => docs![
+                postfix_operand(expr),
+                " ",
+                not(*negated),
+                kw("IN"),
+                " ",
+                in_list(values)
+            ],
+            ExprKind::InSubquery {
+                expr,
+                subquery: query,
+                negated,
+            } => docs![
+                postfix_operand(expr),
+                " ",
+                not(*negated),
+                kw("IN"),
+                " ",
+                subquery(query)
+            ],
+            ExprKind::Like {
+                expr,
+                pattern,
+                escape: escape_char,
+                negated,
+                case_insensitive,
+            } => docs![
+                postfix_operand(expr),
+                " ",
+                not(*negated),
+                kw(if *case_insensitive { "ILIKE" } else { "LIKE" }),
+                " ",
+                trailing_operand(pattern, POSTFIX),
+                escape(escape_char)
+            ],
+            ExprKind::QuantifiedLike {
+                expr,
+                quantifier: which,
+                patterns,
+                escape: escape_char,
+                negated,
+                case_insensitive,
+            } => docs![
+                postfix_operand(expr),
+                " ",
+                not(*negated),
+                kw(if *case_insensitive { "ILIKE" } else { "LIKE" }),
+                " ",
+                quantifier(*which),
+                " ",
+                parenthesized(patterns.iter().map(|p| p.doc())),
+                escape(escape_char)
+            ],
+            ExprKind::SimilarTo {
+                expr,
+                pattern,
+                escape: escape_char,
+                negated,
+            } => docs![
+                postfix_operand(expr),
+                " ",
+                not(*negated),
+                kw("SIMILAR TO"),
+                " ",
+                trailing_operand(pattern, POSTFIX),
+                escape(escape_char)
+            ],
+            ExprKind::IsExpr {
+                expr,
+                test,
+                negated,
+            } => {
+                let test = match test {
+                    IsTest::Null => "NULL",
+                    IsTest::True => "TRUE",
+                    IsTest::False => "FALSE",
+                    IsTest::Unknown => "UNKNOWN",
+                };
+                docs![
+                    postfix_operand(expr),
+                    " ",
+                    kw("IS"),
+                    " ",
+                    not(*negated),
+                    kw(test)
+                ]
+            }
+            ExprKind::IsDistinct {
+                left,
+                right,
+                negated,
+            } => docs![
+                postfix_operand(left),
+                " ",
+                kw("IS"),
+                " ",
+                not(*negated),
+                kw("DISTINCT FROM"),
+                " ",
+                trailing_operand(right, POSTFIX)
+            ],
+            ExprKind::Function(call) => call.doc(),
+            ExprKind::Aggregate(call) => {
+                let filter = match &call.filter {
+                    Some(filter) => docs![
+                        " ",
+                        kw("FILTER"),
+                        " ",
+                        bracketed("(", docs![kw("WHERE"), " ", filter.doc()], ")")
+                    ],
+                    None => docs![],
+                };
+                docs![call.function.doc(), filter]
+            }
+            ExprKind::WindowFunction(call) => {
+                let window = match &call.window {
+                    WindowSpecOrRef::Spec(spec) => bracketed("(", spec.doc(), ")"),
+                    WindowSpecOrRef::Ref(name) => shown(name),
+                };
+                docs![call.function.doc(), " ", kw("OVER"), " ", window]
+            }
+            ExprKind::Cast {
+                expr,
+                data_type,
+                safe,
+            } => {
+                let name = if *safe { "SAFE_CAST" } else { "CAST" };
+                let contents = docs![expr.doc(), " ", kw("AS"), " ", data_type.doc()];
+                docs![kw(name), bracketed("(", contents, ")")]
+            }
+            ExprKind::Extract { field, from } => {
+                let contents = docs![kw(field.name()), " ", kw("FROM"), " ", from.doc()];
+                docs![kw("EXTRACT"), bracketed("(", contents, ")")]
+            }
+            ExprKind::Case {
+                operand,
+                conditions,
+                else_result,
+            } => {
+                let operand = match operand {
+                    Some(operand) => docs![" ", operand.doc()],
+                    None => docs![],
+                };
+                let mut arms = Vec::new();
+                for (condition, result) in conditions {
+                    arms.push(docs![
+                        Doc::HardLine,
+                        kw("WHEN"),
+                        " ",
+                        condition.doc(),
+                        " ",
+                        kw("THEN"),
+                        " ",
+                        result.doc()
+                    ]);
+                }
+                if let Some(else_result) = else_result {
+                    arms.push(docs![Doc::HardLine, kw("ELSE"), " ", else_result.doc()]);
+                }
+                docs![
+                    kw("CASE"),
+                    operand,
+                    nest(Doc::Concat(arms)),
+                    Doc::HardLine,
+                    kw("END")
+                ]
+            }
+            ExprKind::If {
+                condition,
+                then_expr,
+                else_expr,
+            } => docs![
+                kw("IF"),
+                parenthesized([condition.doc(), then_expr.doc(), else_expr.doc()])
+            ],
+            ExprKind::Coalesce(args) => {
+                docs![kw("COALESCE"), parenthesized(args.iter().map(|a| a.doc()))]
+            }
+            ExprKind::Nullif { left, right } => {
+                docs![kw("NULLIF"), parenthesized([left.doc(), right.doc()])]
+            }
+            ExprKind::IfNull {
+                expr,
+                null_replacement,
+            } => docs![
+                kw("IFNULL"),
+                parenthesized([expr.doc(), null_replacement.doc()])
+            ],
+            ExprKind::Subquery(query) => subquery(query),
+            ExprKind::Exists {
+                subquery: query,
+                negated,
+            } => docs![not(*negated), kw("EXISTS"), " ", subquery(query)],
+            ExprKind::QuantifiedComparison {
+                expr,
+                op,
+                quantifier: which,
+                list,
+            } => docs![
+                operand(expr, op.precedence()),
+                " ",
+                binary_op(*op),
+                " ",
+                quantifier(*which),
+                " ",
+                in_list(list)
+            ],
+            ExprKind::ArraySubscript { array, index } => {
+                let index = match index {
+                    ArraySubscriptKind::Index(i) => i.doc(),
+                    ArraySubscriptKind::Offset(i) => docs![kw("OFFSET"), "(", i.doc(), ")"],
+                    ArraySubscriptKind::Ordinal(i) => docs![kw("ORDINAL"), "(", i.doc(), ")"],
+                    ArraySubscriptKind::SafeOffset(i) => {
+                        docs![kw("SAFE_OFFSET"), "(", i.doc(), ")"]
+                    }
+                    ArraySubscriptKind::SafeOrdinal(i) => {
+                        docs![kw("SAFE_ORDINAL"), "(", i.doc(), ")"]
+                    }
+                };
+                docs![postfix_operand(array), "[", index, "]"]
+            }
+            ExprKind::SafeArraySubscript {
+                array,
+                index,
+                offset_type,
+            } => {
+                let function = match offset_type {
+                    ArrayOffsetType::Offset => "SAFE_OFFSET",
+                    ArrayOffsetType::Ordinal => "SAFE_ORDINAL",
+                };
+                docs![
+                    postfix_operand(array),
+                    "[",
+                    kw(function),
+                    "(",
+                    index.doc(),
+                    ")]"
+                ]
+            }
+            ExprKind::FieldAccess { expr, field } => {
+                docs![postfix_operand(expr), ".", shown(field)]
+            }
+            ExprKind::JsonSubscript { expr, key } => {
+                let key = match key {
+                    JsonKey::String(key) => string(key),
+                    JsonKey::Index(index) => Doc::from(index.to_string()),
+                };
+                docs![postfix_operand(expr), "[", key, "]"]
+            }
+            ExprKind::Interval { value, unit } => docs![
+                kw("INTERVAL"),
+                " ",
+                trailing_operand(value, POSTFIX),
+                " ",
+                kw(unit.name())
+            ],
+            ExprKind::TypedLiteral { data_type, value } => {
+                docs![kw(data_type.name()), " ", string(value)]
+            }
+            ExprKind::Parenthesized(expr) => docs!["(", expr.doc(), ")"],
+            ExprKind::Row(values) => {
+                docs![kw("ROW"), parenthesized(values.iter().map(|v| v.doc()))]
+            }
+        }
+    }
+
+    /// Collect the operands of a chain of `op` that needs no parentheses.
+    fn logical_chain(&self, op: BinaryOp, operands: &mut Vec<Doc>) {
+        let mut rights = Vec::new();
+        let mut first = self;
+        while let ExprKind::BinaryOp {
+            op: inner,
+            left,
+            right,
+        } = &first.kind
+        {
+            if *inner != op {
+                break;
+            }
+            rights.push(right);
+            first = left;
+        }
+        operands.push(operand(first, op.precedence()));
+        for right in rights.into_iter().rev() {
+            operands.push(trailing_operand(right, op.precedence() + 1));
+        }
+    }
+}
+
+/// A binary operator, as a keyword when it is one.
+fn binary_op(op: BinaryOp) -> Doc {
+    let text = op.to_string();
+    if text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        Doc::Keyword(text.into())
+    } else {
+        Doc::from(text)
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl StructField {
+    fn doc(&self) -> Doc {
+        match &self.name {
+            Some(name) => docs![self.value.doc(), " ", kw("AS"), " ", shown(name)],
+            None => self.value.doc(),
+        }
+    }
+}
+
+impl Display for StructField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl FunctionCall {
+    fn doc(&self) -> Doc {
+        let mut contents = Vec::new();
+        if self.distinct {
+            contents.push(docs![kw("DISTINCT"), " "]);
+        }
+        contents.push(list(self.args.iter().map(FunctionArg::doc)));
+        match self.null_treatment {
+            Some(NullTreatment::IgnoreNulls) => contents.push(docs![" ", kw("IGNORE NULLS")]),
+            Some(NullTreatment::RespectNulls) => contents.push(docs![" ", kw("RESPECT NULLS")]),
+            None => {}
+        }
+        if !self.order_by.is_empty() {
+            contents.push(docs![
+                " ",
+                kw("ORDER BY"),
+                " ",
+                group(list(self.order_by.iter().map(OrderByExpr::doc)))
+            ]);
+        }
+        if let Some(limit) = &self.limit {
+            contents.push(docs![" ", kw("LIMIT"), " ", limit.doc()]);
+        }
+        docs![
+            shown(&self.name),
+            bracketed("(", Doc::Concat(contents), ")")
+        ]
+    }
+}
+
+impl Display for FunctionCall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl FunctionArg {
+    fn doc(&self) -> Doc {
+        match self {
+            FunctionArg::Unnamed(expr) => expr.doc(),
+            FunctionArg::Named { name, value } => docs![shown(name), " => ", value.doc()],
+            FunctionArg::Star => Doc::from("*"),
+        }
+    }
+}
+
+impl Display for FunctionArg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl WindowSpec {
+    fn doc(&self) -> Doc {
+        let mut parts = Vec::new();
+        if let Some(base) = &self.base {
+            parts.push(shown(base));
+        }
+        if !self.partition_by.is_empty() {
+            parts.push(group(docs![
+                kw("PARTITION BY"),
+                " ",
+                list(self.partition_by.iter().map(|e| e.doc()))
+            ]));
+        }
+        if !self.order_by.is_empty() {
+            parts.push(group(docs![
+                kw("ORDER BY"),
+                " ",
+                list(self.order_by.iter().map(OrderByExpr::doc))
+            ]));
+        }
+        if let Some(frame) = &self.frame {
+            parts.push(frame.doc());
+        }
+        let mut out = Vec::with_capacity(parts.len() * 2);
+        for (i, part) in parts.into_iter().enumerate() {
+            if i > 0 {
+                out.push(Doc::Line);
+            }
+            out.push(part);
+        }
+        Doc::Concat(out)
+    }
+}
+
+impl Display for WindowSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl WindowFrame {
+    fn doc(&self) -> Doc {
+        let unit = match self.unit {
+            WindowFrameUnit::Rows => "ROWS",
+            WindowFrameUnit::Range => "RANGE",
+            WindowFrameUnit::Groups => "GROUPS",
+        };
+        let extent = match &self.end {
+            Some(end) => docs![
+                kw("BETWEEN"),
+                " ",
+                self.start.doc(),
+                " ",
+                kw("AND"),
+                " ",
+                end.doc()
+            ],
+            None => self.start.doc(),
+        };
+        let exclusion = match &self.exclusion {
+            Some(exclusion) => {
+                let excluded = match exclusion {
+                    FrameExclusion::CurrentRow => "CURRENT ROW",
+                    FrameExclusion::Group => "GROUP",
+                    FrameExclusion::Ties => "TIES",
+                    FrameExclusion::NoOthers => "NO OTHERS",
+                };
+                docs![" ", kw("EXCLUDE"), " ", kw(excluded)]
+            }
+            None => docs![],
+        };
+        docs![kw(unit), " ", extent, exclusion]
+    }
+}
+
+impl Display for WindowFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl WindowFrameBound {
+    fn doc(&self) -> Doc {
+        match self {
+            WindowFrameBound::CurrentRow => kw("CURRENT ROW"),
+            WindowFrameBound::Preceding(None) => kw("UNBOUNDED PRECEDING"),
+            WindowFrameBound::Preceding(Some(n)) => docs![n.doc(), " ", kw("PRECEDING")],
+            WindowFrameBound::Following(None) => kw("UNBOUNDED FOLLOWING"),
+            WindowFrameBound::Following(Some(n)) => docs![n.doc(), " ", kw("FOLLOWING")],
+        }
+    }
+}
+
+impl Display for WindowFrameBound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl DateTimePart {
+    /// The keyword naming this part.
+    fn name(&self) -> &'static str {
+        match self {
+            DateTimePart::Year => "YEAR",
+            DateTimePart::Month => "MONTH",
+            DateTimePart::Day => "DAY",
+            DateTimePart::Hour => "HOUR",
+            DateTimePart::Minute => "MINUTE",
+            DateTimePart::Second => "SECOND",
+            DateTimePart::Millisecond => "MILLISECOND",
+            DateTimePart::Microsecond => "MICROSECOND",
+            DateTimePart::Nanosecond => "NANOSECOND",
+            DateTimePart::Dayofweek => "DAYOFWEEK",
+            DateTimePart::Dayofyear => "DAYOFYEAR",
+            DateTimePart::Week => "WEEK",
+            DateTimePart::Quarter => "QUARTER",
+            DateTimePart::Date => "DATE",
+            DateTimePart::Time => "TIME",
+            DateTimePart::Datetime => "DATETIME",
+            DateTimePart::Isoweek => "ISOWEEK",
+            DateTimePart::Isoyear => "ISOYEAR",
+        }
+    }
+}
+
+impl Display for DateTimePart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl IntervalUnit {
+    /// The keyword naming this unit.
+    fn name(&self) -> &'static str {
+        match self {
+            IntervalUnit::Year => "YEAR",
+            IntervalUnit::Month => "MONTH",
+            IntervalUnit::Day => "DAY",
+            IntervalUnit::Hour => "HOUR",
+            IntervalUnit::Minute => "MINUTE",
+            IntervalUnit::Second => "SECOND",
+            IntervalUnit::Millisecond => "MILLISECOND",
+            IntervalUnit::Microsecond => "MICROSECOND",
+            IntervalUnit::Nanosecond => "NANOSECOND",
+            IntervalUnit::Week => "WEEK",
+            IntervalUnit::Quarter => "QUARTER",
+        }
+    }
+}
+
+impl Display for IntervalUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl TypedLiteralType {
+    /// The keyword naming this type.
+    fn name(&self) -> &'static str {
+        match self {
+            TypedLiteralType::Date => "DATE",
+            TypedLiteralType::Time => "TIME",
+            TypedLiteralType::Timestamp => "TIMESTAMP",
+            TypedLiteralType::Datetime => "DATETIME",
+            TypedLiteralType::Json => "JSON",
+            TypedLiteralType::Numeric => "NUMERIC",
+            TypedLiteralType::Bignumeric => "BIGNUMERIC",
+            TypedLiteralType::Range => "RANGE",
+        }
+    }
+}
+
+impl Display for TypedLiteralType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl DataTypeSpec {
+    /// The type in a form the parser accepts.
+    ///
+    /// This differs from the [`DataTypeKind`] display, which uses ISO names
+    /// such as `DOUBLE PRECISION`, and closes nested parameterized types
+    /// with `> >`.
+    fn doc(&self) -> Doc {
+        match &self.kind {
+            DataTypeKind::Float64 => kw("FLOAT64"),
+            DataTypeKind::Array(element) | DataTypeKind::Range(element) => {
+                let name = if matches!(self.kind, DataTypeKind::Array(_)) {
+                    "ARRAY"
+                } else {
+                    "RANGE"
+                };
+                let inner = element.doc();
+                let close = close_type(&inner);
+                docs![kw(name), "<", inner, close]
+            }
+            DataTypeKind::Struct(fields) => {
+                let inner = group(list(fields.iter().map(|field| match &field.name {
+                    Some(name) => docs![shown(name), " ", field.data_type.doc()],
+                    None => field.data_type.doc(),
+                })));
+                let close = close_type(&inner);
+                docs![kw("STRUCT"), "<", inner, close]
+            }
+            DataTypeKind::Custom(name, args) if args.is_empty() => path(&name.parts),
+            DataTypeKind::Custom(name, args) => docs![
+                path(&name.parts),
+                parenthesized(args.iter().map(|arg| arg.doc()))
+            ],
+            // The remaining names are keywords, possibly with numeric
+            // parameters.
+            kind => Doc::Keyword(kind.to_string().into()),
+        }
+    }
+}
+
+impl Display for DataTypeSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+impl Query {
+    /// The query as a layout document, with each clause on its own line.
+    pub(crate) fn doc(&self) -> Doc {
+        let mut parts = Vec::new();
+        if let Some(with) = &self.with {
+            parts.push(docs![with.doc(), Doc::HardLine]);
+        }
+        parts.push(self.body.doc());
+        if !self.order_by.is_empty() {
+            parts.push(docs![
+                Doc::HardLine,
+                clause(
+                    kw("ORDER BY"),
+                    list(self.order_by.iter().map(OrderByExpr::doc))
+                )
+            ]);
+        }
+        if let Some(limit) = &self.limit {
+            let limit = limit.doc();
+            parts.push(docs![Doc::HardLine, limit]);
+        }
+        Doc::Concat(parts)
+    }
+}
+
+impl LimitClause {
+    /// The clause in the syntax it was written in.
+    fn doc(&self) -> Doc {
+        match (self.syntax, &self.count, &self.offset) {
+            (LimitSyntax::Fetch, count, offset) => {
+                let offset = offset
+                    .as_ref()
+                    .map(|offset| docs![kw("OFFSET"), " ", offset.doc(), " ", kw("ROWS")]);
+                let fetch = count.as_ref().map(|count| {
+                    let only = if self.with_ties { "WITH TIES" } else { "ONLY" };
+                    docs![
+                        kw("FETCH FIRST"),
+                        " ",
+                        count.doc(),
+                        " ",
+                        kw("ROWS"),
+                        " ",
+                        kw(only)
+                    ]
+                });
+                match (offset, fetch) {
+                    (Some(offset), Some(fetch)) => docs![offset, " ", fetch],
+                    (Some(doc), None) | (None, Some(doc)) => doc,
+                    (None, None) => docs![],
+                }
+            }
+            (LimitSyntax::LimitComma, Some(count), Some(offset)) => {
+                docs![kw("LIMIT"), " ", offset.doc(), ", ", count.doc()]
+            }
+            (_, Some(count), Some(offset)) => docs![
+                kw("LIMIT"),
+                " ",
+                count.doc(),
+                " ",
+                kw("OFFSET"),
+                " ",
+                offset.doc()
+            ],
+            (_, Some(count), None) => docs![kw("LIMIT"), " ", count.doc()],
+            (_, None, Some(offset)) => docs![kw("OFFSET"), " ", offset.doc()],
+            (_, None, None) => kw("LIMIT ALL"),
+        }
+    }
+}
+
+impl Display for LimitClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl WithClause {
+    fn doc(&self) -> Doc {
+        let recursive = if self.recursive {
+            docs![kw("RECURSIVE"), " "]
+        } else {
+            docs![]
+        };
+        group(docs![
+            kw("WITH"),
+            " ",
+            recursive,
+            list(self.ctes.iter().map(Cte::doc))
+        ])
+    }
+}
+
+impl Display for WithClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl Cte {
+    fn doc(&self) -> Doc {
+        docs![
+            shown(&self.name),
+            column_list(&self.columns),
+            " ",
+            kw("AS"),
+            " ",
+            subquery(&self.query)
+        ]
+    }
+}
+
+impl Display for Cte {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl QueryBody {
+    fn doc(&self) -> Doc {
+        match self {
+            QueryBody::Select(select) => select.doc(),
+            QueryBody::SetOperation {
+                op,
+                all,
+                left,
+                right,
+            } => {
+                // Set operations associate to the left, and INTERSECT binds
+                // tighter than UNION and EXCEPT.
+                let intersect = *op == SetOperator::Intersect;
+                let left = match **left {
+                    QueryBody::SetOperation { op: inner, .. }
+                        if intersect && inner != SetOperator::Intersect =>
+                    {
+                        bracketed("(", left.doc(), ")")
+                    }
+                    _ => left.doc(),
+                };
+                let right = match **right {
+                    QueryBody::SetOperation { op: inner, .. }
+                        if intersect || inner != SetOperator::Intersect =>
+                    {
+                        bracketed("(", right.doc(), ")")
+                    }
+                    _ => right.doc(),
+                };
+                let op = match (op, all) {
+                    (SetOperator::Union, false) => "UNION",
+                    (SetOperator::Union, true) => "UNION ALL",
+                    (SetOperator::Intersect, false) => "INTERSECT",
+                    (SetOperator::Intersect, true) => "INTERSECT ALL",
+                    (SetOperator::Except, false) => "EXCEPT",
+                    (SetOperator::Except, true) => "EXCEPT ALL",
+                };
+                docs![left, Doc::HardLine, kw(op), Doc::HardLine, right]
+            }
+            QueryBody::Parenthesized(query) => subquery(query),
+        }
+    }
+}
+
+impl Display for QueryBody {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl OrderByExpr {
+    fn doc(&self) -> Doc {
+        docs![self.expr.doc(), sort_order(self.order, self.nulls)]
+    }
+}
+
+/// ` ASC`, ` DESC NULLS LAST`, and so on.
+fn sort_order(order: Option<SortOrder>, nulls: Option<NullsOrder>) -> Doc {
+    let order = match order {
+        Some(SortOrder::Asc) => docs![" ", kw("ASC")],
+        Some(SortOrder::Desc) => docs![" ", kw("DESC")],
+        None => docs![],
+    };
+    let nulls = match nulls {
+        Some(NullsOrder::First) => docs![" ", kw("NULLS FIRST")],
+        Some(NullsOrder::Last) => docs![" ", kw("NULLS LAST")],
+        None => docs![],
+    };
+    docs![order, nulls]
+}
+
+impl Display for OrderByExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl Hint {
+    fn doc(&self) -> Doc {
+        if self.args.is_empty() {
+            shown(&self.name)
+        } else {
+            docs![
+                shown(&self.name),
+                parenthesized(self.args.iter().map(HintArg::doc))
+            ]
+        }
+    }
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl HintArg {
+    fn doc(&self) -> Doc {
+        match self {
+            HintArg::Name(name) => shown(name),
+            HintArg::Number(text) | HintArg::Verbatim(text) => Doc::from(text.clone()),
+            HintArg::String(value) => string(value),
+        }
+    }
+}
+
+impl Select {
+    fn doc(&self) -> Doc {
+        let mut head = vec![kw("SELECT"), optimizer_hints(&self.optimizer_hints)];
+        match &self.select_as {
+            Some(SelectAs::Struct) => head.push(docs![" ", kw("AS STRUCT")]),
+            Some(SelectAs::Value) => head.push(docs![" ", kw("AS VALUE")]),
+            Some(SelectAs::TypeName(name)) => head.push(docs![" ", kw("AS"), " ", shown(name)]),
+            None => {}
+        }
+        match &self.distinct {
+            Some(Distinct::Distinct) => head.push(docs![" ", kw("DISTINCT")]),
+            Some(Distinct::All) => head.push(docs![" ", kw("ALL")]),
+            Some(Distinct::On(exprs)) => head.push(docs![
+                " ",
+                kw("DISTINCT ON"),
+                " ",
+                parenthesized(exprs.iter().map(|e| e.doc()))
+            ]),
+            None => {}
+        }
+
+        let mut parts = vec![clause(
+            Doc::Concat(head),
+            list(self.projection.iter().map(SelectItem::doc)),
+        )];
+        if let Some(from) = &self.from {
+            parts.push(Doc::HardLine);
+            parts.push(clause(kw("FROM"), from.doc()));
+        }
+        parts.push(where_clause(self.where_clause.as_deref()));
+        if let Some(group_by) = &self.group_by {
+            parts.push(Doc::HardLine);
+            parts.push(clause(
+                kw("GROUP BY"),
+                list(group_by.items.iter().map(GroupByItem::doc)),
+            ));
+        }
+        if let Some(having) = &self.having {
+            parts.push(Doc::HardLine);
+            parts.push(clause(kw("HAVING"), having.doc()));
+        }
+        if let Some(qualify) = &self.qualify {
+            parts.push(Doc::HardLine);
+            parts.push(clause(kw("QUALIFY"), qualify.doc()));
+        }
+        if !self.window.is_empty() {
+            parts.push(Doc::HardLine);
+            parts.push(clause(
+                kw("WINDOW"),
+                list(self.window.iter().map(WindowDef::doc)),
+            ));
+        }
+        Doc::Concat(parts)
+    }
+}
+
+impl Display for Select {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl SelectItem {
+    fn doc(&self) -> Doc {
+        /// `*` or `t.*`.
+        fn star(qualifier: Option<&ObjectName>) -> Doc {
+            match qualifier {
+                Some(qualifier) => docs![shown(qualifier), ".*"],
+                None => Doc::from("*"),
+            }
+        }
+
+        match self {
+            SelectItem::Expr { expr, alias } => match alias {
+                Some(alias) => docs![expr.doc(), " ", kw("AS"), " ", shown(alias)],
+                None => expr.doc(),
+            },
+            SelectItem::Wildcard => Doc::from("*"),
+            SelectItem::QualifiedWildcard { qualifier } => star(Some(qualifier)),
+            SelectItem::WildcardExcept { qualifier, except } => docs![
+                star(qualifier.as_ref()),
+                " ",
+                kw("EXCEPT"),
+                " ",
+                parenthesized(except.iter().map(shown))
+            ],
+            SelectItem::WildcardReplace { qualifier, replace } => docs![
+                star(qualifier.as_ref()),
+                " ",
+                kw("REPLACE"),
+                " ",
+                parenthesized(replace.iter().map(|(expr, name)| docs![
+                    expr.doc(),
+                    " ",
+                    kw("AS"),
+                    " ",
+                    shown(name)
+                ]))
+            ],
+        }
+    }
+}
+
+impl Display for SelectItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl FromClause {
+    fn doc(&self) -> Doc {
+        list(self.tables.iter().map(TableRef::doc))
+    }
+}
+
+impl Display for FromClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl GroupByItem {
+    fn doc(&self) -> Doc {
+        let exprs = |exprs: &[Box<Expr>]| parenthesized(exprs.iter().map(|e| e.doc()));
+        match self {
+            GroupByItem::Expr(expr) => expr.doc(),
+            GroupByItem::Rollup(items) => docs![kw("ROLLUP"), exprs(items)],
+            GroupByItem::Cube(items) => docs![kw("CUBE"), exprs(items)],
+            GroupByItem::GroupingSets(sets) => docs![
+                kw("GROUPING SETS"),
+                " ",
+                parenthesized(sets.iter().map(|set| exprs(set)))
+            ],
+        }
+    }
+}
+
+impl Display for GroupByItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl WindowDef {
+    fn doc(&self) -> Doc {
+        docs![
+            shown(&self.name),
+            " ",
+            kw("AS"),
+            " ",
+            bracketed("(", self.spec.doc(), ")")
+        ]
+    }
+}
+
+impl Display for WindowDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl TableRef {
+    /// The table reference as a layout document.
+    ///
+    /// A join chain breaks before each join keyword when the enclosing
+    /// group breaks.
+    fn doc(&self) -> Doc {
+        match &self.kind {
+            TableRefKind::Table {
+                name,
+                alias: table_alias,
+                hints: table_hints,
+                sample,
+            } => docs![
+                shown(name),
+                hints(table_hints),
+                alias(table_alias.as_ref()),
+                table_sample(sample.as_ref())
+            ],
+            TableRefKind::Subquery {
+                query,
+                alias: table_alias,
+                sample,
+                lateral,
+            } => docs![
+                lateral_keyword(*lateral),
+                subquery(query),
+                alias(table_alias.as_ref()),
+                table_sample(sample.as_ref())
+            ],
+            TableRefKind::Unnest {
+                expr,
+                alias: table_alias,
+                with_offset,
+                offset_alias,
+            } => {
+                let offset = match (with_offset, offset_alias) {
+                    (true, Some(name)) => {
+                        docs![" ", kw("WITH OFFSET"), " ", kw("AS"), " ", shown(name)]
+                    }
+                    (true, None) => docs![" ", kw("WITH OFFSET")],
+                    (false, _) => docs![],
+                };
+                docs![
+                    kw("UNNEST"),
+                    bracketed("(", expr.doc(), ")"),
+                    alias(table_alias.as_ref()),
+                    offset
+                ]
+            }
+            TableRefKind::Join {
+                left,
+                right,
+                join_type,
+                condition,
+            } => {
+                let join = match join_type {
+                    JoinType::Inner => "JOIN",
+                    JoinType::Left => "LEFT JOIN",
+                    JoinType::Right => "RIGHT JOIN",
+                    JoinType::Full => "FULL JOIN",
+                    JoinType::Cross => "CROSS JOIN",
+                    JoinType::Natural => "NATURAL JOIN",
+                    JoinType::LeftSemi => "LEFT SEMI JOIN",
+                    JoinType::RightSemi => "RIGHT SEMI JOIN",
+                    JoinType::LeftAnti => "LEFT ANTI JOIN",
+                    JoinType::RightAnti => "RIGHT ANTI JOIN",
+                };
+                // Joins associate to the left.
+                let right = match right.kind {
+                    TableRefKind::Join { .. } => bracketed("(", right.doc(), ")"),
+                    _ => right.doc(),
+                };
+                let condition = match condition {
+                    Some(JoinCondition::On(expr)) => docs![" ", kw("ON"), " ", expr.doc()],
+                    Some(JoinCondition::Using(columns)) => docs![
+                        " ",
+                        kw("USING"),
+                        " ",
+                        parenthesized(columns.iter().map(shown))
+                    ],
+                    None => docs![],
+                };
+                docs![left.doc(), Doc::Line, kw(join), " ", right, condition]
+            }
+            TableRefKind::Parenthesized(inner) => bracketed("(", inner.doc(), ")"),
+            TableRefKind::TableFunction {
+                name,
+                args,
+                alias: table_alias,
+                lateral,
+            } => docs![
+                lateral_keyword(*lateral),
+                shown(name),
+                parenthesized(args.iter().map(FunctionArg::doc)),
+                alias(table_alias.as_ref())
+            ],
+            TableRefKind::Pivot {
+                input,
+                aggregates,
+                for_column,
+                values,
+                alias: table_alias,
+            } => {
+                let aliased = |(expr, name): &(Box<Expr>, Option<Ident>)| match name {
+                    Some(name) => docs![expr.doc(), " ", kw("AS"), " ", shown(name)],
+                    None => expr.doc(),
+                };
+                let body = docs![
+                    list(aggregates.iter().map(aliased)),
+                    " ",
+                    kw("FOR"),
+                    " ",
+                    for_column.doc(),
+                    " ",
+                    kw("IN"),
+                    " ",
+                    parenthesized(values.iter().map(aliased))
+                ];
+                docs![
+                    input.operand_doc(),
+                    " ",
+                    kw("PIVOT"),
+                    bracketed("(", body, ")"),
+                    alias(table_alias.as_ref())
+                ]
+            }
+            TableRefKind::Unpivot {
+                input,
+                include_nulls,
+                value_columns,
+                name_column,
+                columns,
+                alias: table_alias,
+            } => {
+                let group = |group: &[Ident]| match group {
+                    [column] if value_columns.len() == 1 => shown(column),
+                    _ => parenthesized(group.iter().map(shown)),
+                };
+                let column = |(columns, label): &(Vec<Ident>, Option<Box<Expr>>)| match label {
+                    Some(label) => docs![group(columns), " ", kw("AS"), " ", label.doc()],
+                    None => group(columns),
+                };
+                let body = docs![
+                    group(value_columns),
+                    " ",
+                    kw("FOR"),
+                    " ",
+                    shown(name_column),
+                    " ",
+                    kw("IN"),
+                    " ",
+                    parenthesized(columns.iter().map(column))
+                ];
+                let nulls = if *include_nulls {
+                    docs![" ", kw("INCLUDE NULLS"), " "]
+                } else {
+                    docs![]
+                };
+                docs![
+                    input.operand_doc(),
+                    " ",
+                    kw("UNPIVOT"),
+                    nulls,
+                    bracketed("(", body, ")"),
+                    alias(table_alias.as_ref())
+                ]
+            }
+        }
+    }
+
+    /// The input of a PIVOT or UNPIVOT, bracketed if it is a join, which
+    /// would otherwise only take the operator on its right side.
+    fn operand_doc(&self) -> Doc {
+        match self.kind {
+            TableRefKind::Join { .. } => bracketed("(", self.doc(), ")"),
+            _ => self.doc(),
+        }
+    }
+}
+
+impl TableSample {
+    fn doc(&self) -> Doc {
+        let method = match self.method {
+            SampleMethod::Bernoulli => "BERNOULLI",
+            SampleMethod::System => "SYSTEM",
+            SampleMethod::Reservoir => "RESERVOIR",
+        };
+        let size = match &self.size {
+            SampleSize::Percent(n) => docs![n.doc(), " ", kw("PERCENT")],
+            SampleSize::Rows(n) => docs![n.doc(), " ", kw("ROWS")],
+        };
+        let seed = match &self.seed {
+            Some(seed) => docs![" ", kw("REPEATABLE"), bracketed("(", seed.doc(), ")")],
+            None => docs![],
+        };
+        docs![
+            kw("TABLESAMPLE"),
+            " ",
+            kw(method),
+            " ",
+            bracketed("(", size, ")"),
+            seed
+        ]
+    }
+}
+
+impl Display for TableSample {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl Display for TableRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl Alias {
+    fn doc(&self) -> Doc {
+        docs![kw("AS"), " ", shown(&self.name), column_list(&self.columns)]
+    }
+}
+
+impl Display for Alias {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl SqlOption {
+    fn doc(&self) -> Doc {
+        docs![shown(&self.name), " = ", self.value.doc()]
+    }
+}
+
+impl Display for SqlOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+// ============================================================================
+// Statements
+// ============================================================================
+
+impl Statement {
+    /// The statement as a layout document.
+    pub(crate) fn doc(&self) -> Doc {
+        self.kind.doc()
+    }
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl StatementKind {
+    /// `Empty` is an empty document.
+    fn doc(&self) -> Doc {
+        match self {
+            StatementKind::Query(query) => query.doc(),
+            StatementKind::Insert(insert) => insert.doc(),
+            StatementKind::Update(update) => update.doc(),
+            StatementKind::Delete(delete) => delete.doc(),
+            StatementKind::Merge(merge) => merge.doc(),
+            StatementKind::CreateDatabase(create) => create.doc(),
+            StatementKind::CreateTable(create) => create.doc(),
+            StatementKind::CreateView(create) => create.doc(),
+            StatementKind::CreateIndex(create) => create.doc(),
+            StatementKind::CreateFunction(create) => create.doc(),
+            StatementKind::CreateProcedure(create) => create.doc(),
+            StatementKind::AlterTable(alter) => alter.doc(),
+            StatementKind::AlterView(alter) => alter.doc(),
+            StatementKind::Drop(drop) => drop.doc(),
+            StatementKind::Truncate(truncate) => {
+                docs![kw("TRUNCATE TABLE"), " ", shown(&truncate.table)]
+            }
+            StatementKind::Begin(begin) => {
+                let mode = match begin.mode {
+                    Some(TransactionMode::ReadOnly) => docs![" ", kw("READ ONLY")],
+                    Some(TransactionMode::ReadWrite) => docs![" ", kw("READ WRITE")],
+                    None => docs![],
+                };
+                docs![kw("BEGIN TRANSACTION"), mode]
+            }
+            StatementKind::Commit => kw("COMMIT"),
+            StatementKind::Rollback(rollback) => match &rollback.savepoint {
+                Some(savepoint) => {
+                    docs![kw("ROLLBACK TO SAVEPOINT"), " ", shown(savepoint)]
+                }
+                None => kw("ROLLBACK"),
+            },
+            StatementKind::Explain(explain) => {
+                let analyze = if explain.analyze {
+                    docs![kw("ANALYZE"), " "]
+                } else {
+                    docs![]
+                };
+                let format = match explain.format {
+                    Some(ExplainFormat::Text) => docs![kw("FORMAT TEXT"), " "],
+                    Some(ExplainFormat::Json) => docs![kw("FORMAT JSON"), " "],
+                    None => docs![],
+                };
+                docs![kw("EXPLAIN"), " ", analyze, format, explain.statement.doc()]
+            }
+            StatementKind::Describe(describe) => {
+                docs![kw("DESCRIBE"), " ", shown(&describe.object)]
+            }
+            StatementKind::Show(show) => show.doc(),
+            StatementKind::Set(set) => {
+                let value = match &set.value {
+                    SetValue::Expr(expr) => expr.doc(),
+                    SetValue::Default => kw("DEFAULT"),
+                };
+                docs![kw("SET"), " ", shown(&set.variable), " = ", value]
+            }
+            StatementKind::Empty => docs![],
+        }
+    }
+}
+
+impl Display for StatementKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl InsertStatement {
+    fn doc(&self) -> Doc {
+        let action = match self.or_action {
+            Some(InsertOrAction::Ignore) => docs![" ", kw("OR IGNORE")],
+            Some(InsertOrAction::Replace) => docs![" ", kw("OR REPLACE")],
+            Some(InsertOrAction::Update) => docs![" ", kw("OR UPDATE")],
+            None => docs![],
+        };
+        let source = match &self.source {
+            InsertSource::Values(rows) => docs![
+                Doc::HardLine,
+                clause(
+                    kw("VALUES"),
+                    list(
+                        rows.iter()
+                            .map(|row| parenthesized(row.iter().map(|v| v.doc())))
+                    )
+                )
+            ],
+            InsertSource::Query(query) => docs![Doc::HardLine, query.doc()],
+            InsertSource::DefaultValues => docs![" ", kw("DEFAULT VALUES")],
+        };
+        docs![
+            kw("INSERT"),
+            optimizer_hints(&self.optimizer_hints),
+            action,
+            " ",
+            kw("INTO"),
+            " ",
+            shown(&self.table),
+            column_list(&self.columns),
+            source,
+            returning(self.returning.as_ref())
+        ]
+    }
+}
+
+impl Display for InsertStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl UpdateStatement {
+    fn doc(&self) -> Doc {
+        let from = match &self.from {
+            Some(from) => docs![Doc::HardLine, clause(kw("FROM"), from.doc())],
+            None => docs![],
+        };
+        docs![
+            kw("UPDATE"),
+            optimizer_hints(&self.optimizer_hints),
+            " ",
+            group(self.table.doc()),
+            Doc::HardLine,
+            clause(
+                kw("SET"),
+                list(self.assignments.iter().map(Assignment::doc))
+            ),
+            from,
+            where_clause(self.where_clause.as_deref()),
+            returning(self.returning.as_ref())
+        ]
+    }
+}
+
+impl Display for UpdateStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl Assignment {
+    fn doc(&self) -> Doc {
+        let target = match &self.target {
+            AssignmentTarget::Column(column) => shown(column),
+            AssignmentTarget::Path(parts) => path(parts),
+        };
+        docs![target, " = ", self.value.doc()]
+    }
+}
+
+impl Display for Assignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl DeleteStatement {
+    fn doc(&self) -> Doc {
+        docs![
+            kw("DELETE"),
+            optimizer_hints(&self.optimizer_hints),
+            " ",
+            kw("FROM"),
+            " ",
+            shown(&self.table),
+            hints(&self.hints),
+            alias(self.alias.as_ref()),
+            where_clause(self.where_clause.as_deref()),
+            returning(self.returning.as_ref())
+        ]
+    }
+}
+
+impl Display for DeleteStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl MergeStatement {
+    fn doc(&self) -> Doc {
+        let clauses: Vec<Doc> = self
+            .clauses
+            .iter()
+            .map(|c| docs![Doc::HardLine, c.doc()])
+            .collect();
+        docs![
+            kw("MERGE INTO"),
+            " ",
+            group(self.target.doc()),
+            Doc::HardLine,
+            kw("USING"),
+            " ",
+            group(self.source.doc()),
+            " ",
+            kw("ON"),
+            " ",
+            self.on.doc(),
+            Doc::Concat(clauses)
+        ]
+    }
+}
+
+impl Display for MergeStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl MergeClause {
+    fn doc(&self) -> Doc {
+        let (when, condition) = match self {
+            MergeClause::Matched { condition, .. } => ("WHEN MATCHED", condition),
+            MergeClause::NotMatched { condition, .. } => ("WHEN NOT MATCHED", condition),
+            MergeClause::NotMatchedBySource { condition, .. } => {
+                ("WHEN NOT MATCHED BY SOURCE", condition)
+            }
+        };
+        let condition = match condition {
+            Some(condition) => docs![" ", kw("AND"), " ", condition.doc()],
+            None => docs![],
+        };
+        let action = match self {
+            MergeClause::Matched { action, .. }
+            | MergeClause::NotMatchedBySource { action, .. } => match action {
+                MergeMatchedAction::Update { assignments } => clause(
+                    kw("UPDATE SET"),
+                    list(assignments.iter().map(Assignment::doc)),
+                ),
+                MergeMatchedAction::Delete => kw("DELETE"),
+            },
+            MergeClause::NotMatched { action, .. } => docs![
+                kw("INSERT"),
+                column_list(&action.columns),
+                " ",
+                kw("VALUES"),
+                " ",
+                parenthesized(action.values.iter().map(|v| v.doc()))
+            ],
+        };
+        docs![kw(when), condition, " ", kw("THEN"), " ", action]
+    }
+}
+
+impl Display for MergeClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl CreateDatabaseStatement {
+    fn doc(&self) -> Doc {
+        docs![
+            kw("CREATE DATABASE"),
+            " ",
+            if_exists(self.if_not_exists, "IF NOT EXISTS"),
+            shown(&self.name),
+            options(&self.options)
+        ]
+    }
+}
+
+impl Display for CreateDatabaseStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl CreateTableStatement {
+    fn doc(&self) -> Doc {
+        let mut parts = vec![kw("CREATE"), Doc::from(" ")];
+        if self.or_replace {
+            parts.push(docs![kw("OR REPLACE"), " "]);
+        }
+        if self.temporary {
+            parts.push(docs![kw("TEMP"), " "]);
+        }
+        parts.push(docs![
+            kw("TABLE"),
+            " ",
+            if_exists(self.if_not_exists, "IF NOT EXISTS"),
+            shown(&self.name)
+        ]);
+        if let Some(like) = &self.like {
+            parts.push(docs![" ", kw("LIKE"), " ", shown(like)]);
+        }
+        if let Some(clone) = &self.clone {
+            parts.push(docs![" ", kw("CLONE"), " ", shown(clone)]);
+        }
+        if !self.columns.is_empty() || !self.constraints.is_empty() {
+            let elements = self
+                .columns
+                .iter()
+                .map(ColumnDef::doc)
+                .chain(self.constraints.iter().map(TableConstraint::doc));
+            parts.push(docs![" ", parenthesized(elements)]);
+        }
+        if !self.partition_by.is_empty() {
+            parts.push(docs![
+                Doc::HardLine,
+                clause(
+                    kw("PARTITION BY"),
+                    list(self.partition_by.iter().map(|e| e.doc()))
+                )
+            ]);
+        }
+        if !self.cluster_by.is_empty() {
+            parts.push(docs![
+                Doc::HardLine,
+                clause(
+                    kw("CLUSTER BY"),
+                    list(self.cluster_by.iter().map(|e| e.doc()))
+                )
+            ]);
+        }
+        if !self.options.is_empty() {
+            parts.push(docs![
+                Doc::HardLine,
+                kw("OPTIONS"),
+                options_list(&self.options)
+            ]);
+        }
+        if let Some(query) = &self.as_query {
+            parts.push(docs![" ", kw("AS"), Doc::HardLine, query.doc()]);
+        }
+        Doc::Concat(parts)
+    }
+}
+
+impl Display for CreateTableStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl ColumnDef {
+    fn doc(&self) -> Doc {
+        let mut parts = vec![shown(&self.name)];
+        if let Some(data_type) = &self.data_type {
+            parts.push(docs![" ", data_type.doc()]);
+        }
+        for constraint in &self.constraints {
+            parts.push(docs![" ", constraint.doc()]);
+        }
+        parts.push(options(&self.options));
+        Doc::Concat(parts)
+    }
+}
+
+impl Display for ColumnDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl ColumnConstraint {
+    fn doc(&self) -> Doc {
+        match self {
+            ColumnConstraint::NotNull => kw("NOT NULL"),
+            ColumnConstraint::Null => kw("NULL"),
+            ColumnConstraint::PrimaryKey => kw("PRIMARY KEY"),
+            ColumnConstraint::Unique => kw("UNIQUE"),
+            ColumnConstraint::Default(expr) => docs![kw("DEFAULT"), " ", expr.doc()],
+            ColumnConstraint::Check(expr) => {
+                docs![kw("CHECK"), " ", bracketed("(", expr.doc(), ")")]
+            }
+            ColumnConstraint::References {
+                table,
+                columns,
+                on_delete,
+                on_update,
+            } => docs![
+                kw("REFERENCES"),
+                " ",
+                shown(table),
+                column_list(columns),
+                referential_actions(on_delete, on_update)
+            ],
+            ColumnConstraint::Generated { expr, always } => {
+                let when = if *always { "ALWAYS" } else { "BY DEFAULT" };
+                docs![
+                    kw("GENERATED"),
+                    " ",
+                    kw(when),
+                    " ",
+                    kw("AS"),
+                    " ",
+                    bracketed("(", expr.doc(), ")")
+                ]
+            }
+            ColumnConstraint::Hidden => kw("HIDDEN"),
+        }
+    }
+}
+
+impl Display for ColumnConstraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+/// The ON DELETE and ON UPDATE actions of a foreign key.
+fn referential_actions(
+    on_delete: &Option<ReferentialAction>,
+    on_update: &Option<ReferentialAction>,
+) -> Doc {
+    let mut parts = Vec::new();
+    if let Some(action) = on_delete {
+        parts.push(docs![" ", kw("ON DELETE"), " ", kw(action.name())]);
+    }
+    if let Some(action) = on_update {
+        parts.push(docs![" ", kw("ON UPDATE"), " ", kw(action.name())]);
+    }
+    Doc::Concat(parts)
+}
+
+impl ReferentialAction {
+    /// The keywords naming this action.
+    fn name(&self) -> &'static str {
+        match self {
+            ReferentialAction::NoAction => "NO ACTION",
+            ReferentialAction::Restrict => "RESTRICT",
+            ReferentialAction::Cascade => "CASCADE",
+            ReferentialAction::SetNull => "SET NULL",
+            ReferentialAction::SetDefault => "SET DEFAULT",
+        }
+    }
+}
+
+impl Display for ReferentialAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl TableConstraint {
+    fn doc(&self) -> Doc {
+        let name = match self {
+            TableConstraint::PrimaryKey { name, .. }
+            | TableConstraint::Unique { name, .. }
+            | TableConstraint::ForeignKey { name, .. }
+            | TableConstraint::Check { name, .. } => name,
+        };
+        let name = match name {
+            Some(name) => docs![kw("CONSTRAINT"), " ", shown(name), " "],
+            None => docs![],
+        };
+        let body = match self {
+            TableConstraint::PrimaryKey {
+                columns,
+                options: key_options,
+                ..
+            } => docs![
+                kw("PRIMARY KEY"),
+                " ",
+                parenthesized(columns.iter().map(SortKey::doc)),
+                options(key_options)
+            ],
+            TableConstraint::Unique { columns, .. } => {
+                docs![kw("UNIQUE"), " ", parenthesized(columns.iter().map(shown))]
+            }
+            TableConstraint::ForeignKey {
+                columns,
+                references_table,
+                references_columns,
+                on_delete,
+                on_update,
+                ..
+            } => docs![
+                kw("FOREIGN KEY"),
+                " ",
+                parenthesized(columns.iter().map(shown)),
+                " ",
+                kw("REFERENCES"),
+                " ",
+                shown(references_table),
+                " ",
+                parenthesized(references_columns.iter().map(shown)),
+                referential_actions(on_delete, on_update)
+            ],
+            TableConstraint::Check { expr, enforced, .. } => {
+                let enforced = match enforced {
+                    Some(true) => docs![" ", kw("ENFORCED")],
+                    Some(false) => docs![" ", kw("NOT ENFORCED")],
+                    None => docs![],
+                };
+                docs![kw("CHECK"), " ", bracketed("(", expr.doc(), ")"), enforced]
+            }
+        };
+        docs![name, body]
+    }
+}
+
+impl Display for TableConstraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl SortKey {
+    fn doc(&self) -> Doc {
+        docs![shown(&self.column), sort_order(self.order, self.nulls)]
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl CreateViewStatement {
+    fn doc(&self) -> Doc {
+        let mut parts = vec![kw("CREATE"), Doc::from(" ")];
+        if self.or_replace {
+            parts.push(docs![kw("OR REPLACE"), " "]);
+        }
+        if self.materialized {
+            parts.push(docs![kw("MATERIALIZED"), " "]);
+        }
+        parts.push(docs![
+            kw("VIEW"),
+            " ",
+            if_exists(self.if_not_exists, "IF NOT EXISTS"),
+            shown(&self.name),
+            column_list(&self.columns),
+            options(&self.options),
+            " ",
+            kw("AS"),
+            Doc::HardLine,
+            self.query.doc()
+        ]);
+        Doc::Concat(parts)
+    }
+}
+
+impl Display for CreateViewStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl CreateIndexStatement {
+    fn doc(&self) -> Doc {
+        let unique = if self.unique {
+            docs![kw("UNIQUE"), " "]
+        } else {
+            docs![]
+        };
+        let name = match &self.name {
+            Some(name) => docs![shown(name), " "],
+            None => docs![],
+        };
+        docs![
+            kw("CREATE"),
+            " ",
+            unique,
+            kw("INDEX"),
+            " ",
+            if_exists(self.if_not_exists, "IF NOT EXISTS"),
+            name,
+            kw("ON"),
+            " ",
+            shown(&self.table),
+            " ",
+            parenthesized(self.columns.iter().map(SortKey::doc)),
+            options(&self.options)
+        ]
+    }
+}
+
+impl Display for CreateIndexStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl CreateFunctionStatement {
+    fn doc(&self) -> Doc {
+        let mut parts = vec![kw("CREATE"), Doc::from(" ")];
+        if self.or_replace {
+            parts.push(docs![kw("OR REPLACE"), " "]);
+        }
+        if self.temporary {
+            parts.push(docs![kw("TEMP"), " "]);
+        }
+        parts.push(docs![
+            kw("FUNCTION"),
+            " ",
+            if_exists(self.if_not_exists, "IF NOT EXISTS"),
+            shown(&self.name),
+            parenthesized(self.params.iter().map(FunctionParam::doc))
+        ]);
+        if let Some(returns) = &self.returns {
+            parts.push(docs![" ", kw("RETURNS"), " ", returns.doc()]);
+        }
+        if let Some(language) = &self.language {
+            parts.push(docs![" ", kw("LANGUAGE"), " ", language.clone()]);
+        }
+        parts.push(options(&self.options));
+        match &self.body {
+            FunctionBody::Expr(expr) => {
+                parts.push(docs![" ", kw("AS"), " ", bracketed("(", expr.doc(), ")")])
+            }
+            FunctionBody::External(body) if body.is_empty() => {}
+            FunctionBody::External(body) => parts.push(docs![" ", kw("AS"), " ", string(body)]),
+            FunctionBody::Statements(statements) => parts.push(docs![" ", block(statements)]),
+        }
+        Doc::Concat(parts)
+    }
+}
+
+impl Display for CreateFunctionStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl FunctionParam {
+    fn doc(&self) -> Doc {
+        let name = match &self.name {
+            Some(name) => docs![shown(name), " "],
+            None => docs![],
+        };
+        let default = match &self.default {
+            Some(default) => docs![" ", kw("DEFAULT"), " ", default.doc()],
+            None => docs![],
+        };
+        docs![name, self.data_type.doc(), default]
+    }
+}
+
+impl Display for FunctionParam {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl CreateProcedureStatement {
+    fn doc(&self) -> Doc {
+        let or_replace = if self.or_replace {
+            docs![kw("OR REPLACE"), " "]
+        } else {
+            docs![]
+        };
+        docs![
+            kw("CREATE"),
+            " ",
+            or_replace,
+            kw("PROCEDURE"),
+            " ",
+            if_exists(self.if_not_exists, "IF NOT EXISTS"),
+            shown(&self.name),
+            parenthesized(self.params.iter().map(ProcedureParam::doc)),
+            options(&self.options),
+            " ",
+            block(&self.body)
+        ]
+    }
+}
+
+impl Display for CreateProcedureStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl ProcedureParam {
+    fn doc(&self) -> Doc {
+        let mode = match self.mode {
+            ParamMode::In => docs![],
+            ParamMode::Out => docs![kw("OUT"), " "],
+            ParamMode::InOut => docs![kw("INOUT"), " "],
+        };
+        let name = match &self.name {
+            Some(name) => docs![shown(name), " "],
+            None => docs![],
+        };
+        docs![mode, name, self.data_type.doc()]
+    }
+}
+
+impl Display for ProcedureParam {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl AlterTableStatement {
+    fn doc(&self) -> Doc {
+        let action = match &self.action {
+            AlterTableAction::AddColumn {
+                if_not_exists,
+                column,
+            } => docs![
+                kw("ADD COLUMN"),
+                " ",
+                if_exists(*if_not_exists, "IF NOT EXISTS"),
+                column.doc()
+            ],
+            AlterTableAction::DropColumn {
+                if_exists: set,
+                column,
+            } => docs![
+                kw("DROP COLUMN"),
+                " ",
+                if_exists(*set, "IF EXISTS"),
+                shown(column)
+            ],
+            AlterTableAction::AlterColumn { column, action } => {
+                let action = match action {
+                    AlterColumnAction::SetDataType(data_type) => {
+                        docs![kw("SET DATA TYPE"), " ", data_type.doc()]
+                    }
+                    AlterColumnAction::SetDefault(expr) => {
+                        docs![kw("SET DEFAULT"), " ", expr.doc()]
+                    }
+                    AlterColumnAction::DropDefault => kw("DROP DEFAULT"),
+                    AlterColumnAction::SetNotNull => kw("SET NOT NULL"),
+                    AlterColumnAction::DropNotNull => kw("DROP NOT NULL"),
+                    AlterColumnAction::SetOptions(options) => {
+                        docs![kw("SET OPTIONS"), " ", options_list(options)]
+                    }
+                };
+                docs![kw("ALTER COLUMN"), " ", shown(column), " ", action]
+            }
+            AlterTableAction::AddConstraint(constraint) => {
+                docs![kw("ADD"), " ", constraint.doc()]
+            }
+            AlterTableAction::DropConstraint {
+                if_exists: set,
+                name,
+            } => docs![
+                kw("DROP CONSTRAINT"),
+                " ",
+                if_exists(*set, "IF EXISTS"),
+                shown(name)
+            ],
+            AlterTableAction::RenameColumn { from, to } => docs![
+                kw("RENAME COLUMN"),
+                " ",
+                shown(from),
+                " ",
+                kw("TO"),
+                " ",
+                shown(to)
+            ],
+            AlterTableAction::RenameTable(name) => docs![kw("RENAME TO"), " ", shown(name)],
+            AlterTableAction::SetOptions(options) => {
+                docs![kw("SET OPTIONS"), " ", options_list(options)]
+            }
+        };
+        docs![
+            kw("ALTER TABLE"),
+            " ",
+            if_exists(self.if_exists, "IF EXISTS"),
+            shown(&self.name),
+            " ",
+            action
+        ]
+    }
+}
+
+impl Display for AlterTableStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl AlterViewStatement {
+    fn doc(&self) -> Doc {
+        let action = match &self.action {
+            AlterViewAction::SetOptions(options) => {
+                docs![kw("SET OPTIONS"), " ", options_list(options)]
+            }
+            AlterViewAction::SetQuery(query) => docs![kw("AS"), Doc::HardLine, query.doc()],
+        };
+        docs![
+            kw("ALTER VIEW"),
+            " ",
+            if_exists(self.if_exists, "IF EXISTS"),
+            shown(&self.name),
+            " ",
+            action
+        ]
+    }
+}
+
+impl Display for AlterViewStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl DropStatement {
+    fn doc(&self) -> Doc {
+        let object_type = match self.object_type {
+            ObjectType::Table => "TABLE",
+            ObjectType::View => "VIEW",
+            ObjectType::MaterializedView => "MATERIALIZED VIEW",
+            ObjectType::Index => "INDEX",
+            ObjectType::Function => "FUNCTION",
+            ObjectType::Procedure => "PROCEDURE",
+            ObjectType::Database => "DATABASE",
+            ObjectType::Schema => "SCHEMA",
+        };
+        let cascade = if self.cascade {
+            docs![" ", kw("CASCADE")]
+        } else {
+            docs![]
+        };
+        docs![
+            kw("DROP"),
+            " ",
+            kw(object_type),
+            " ",
+            if_exists(self.if_exists, "IF EXISTS"),
+            group(list(self.names.iter().map(shown))),
+            cascade
+        ]
+    }
+}
+
+impl Display for DropStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+impl ShowStatement {
+    fn doc(&self) -> Doc {
+        /// `KIND` or `KIND FROM x`.
+        fn from(kind: &'static str, source: &Option<Ident>) -> Doc {
+            match source {
+                Some(source) => docs![kw(kind), " ", kw("FROM"), " ", shown(source)],
+                None => kw(kind),
+            }
+        }
+
+        let object = match &self.object_type {
+            ShowObjectType::Tables { from: source } => from("TABLES", source),
+            ShowObjectType::Databases => kw("DATABASES"),
+            ShowObjectType::Schemas { from: source } => from("SCHEMAS", source),
+            ShowObjectType::Columns { from } => {
+                docs![kw("COLUMNS FROM"), " ", shown(from)]
+            }
+            ShowObjectType::Functions { from: source } => from("FUNCTIONS", source),
+            ShowObjectType::Variables => kw("VARIABLES"),
+        };
+        let filter = match &self.filter {
+            Some(ShowFilter::Like(pattern)) => docs![" ", kw("LIKE"), " ", string(pattern)],
+            Some(ShowFilter::Where(expr)) => docs![" ", kw("WHERE"), " ", expr.doc()],
+            None => docs![],
+        };
+        docs![kw("SHOW"), " ", object, filter]
+    }
+}
+
+impl Display for ShowStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.doc().write_flat(f)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::ast::{Expr, ExprKind};
+    use crate::error::Span;
+    use crate::parser::Parser;
+    use crate::testgen::TestGen;
+
+    /// Statement SQL from the parser tests, plus forms those tests do not
+    /// cover.
+    pub(crate) const CORPUS: &[&str] = &[
+        // Queries
+        "SELECT 1",
+        "SELECT * FROM users",
+        "SELECT /*+ BROADCAST_JOIN(o) INDEX(u idx_name) */ * FROM users u JOIN orders o ON u.id = o.user_id",
+        "DELETE /*+ PARALLEL(t, 4) */ FROM t WHERE a = 1",
+        "SELECT id FROM users WHERE active = true",
+        "SELECT * FROM users u JOIN orders o ON u.id = o.user_id",
+        "SELECT department, COUNT(*) FROM employees GROUP BY department",
+        "SELECT 1 UNION ALL SELECT 2",
+        "WITH cte AS (SELECT 1 AS x) SELECT * FROM cte",
+        "SELECT * FROM t ORDER BY id DESC LIMIT 10 OFFSET 5",
+        "SELECT * FROM users WHERE id = ? AND age > ?; SELECT ?, $2, $1",
+        "SELECT * FROM t WHERE a = @a AND b = ?",
+        "SELECT a, b FROM t WHERE a NOT BETWEEN 1 AND 2",
+        "SELECT AS STRUCT 1 AS x",
+        "SELECT AS VALUE STRUCT(1 AS a, 'x' AS b)",
+        "SELECT f(a => 1, b => 2), g(x, y)",
+        "WITH c AS (SELECT 1) SELECT * FROM c JOIN t USING (id) ORDER BY 1",
+        "SELECT 1 - -1, 2 + +2, - -3, -(-4)",
+        "SELECT a b, c FROM t",
+        "SELECT a AS b, c + 1 d FROM t",
+        "(SELECT 1 AS a) UNION ALL (SELECT 2)",
+        "(WITH x AS (SELECT 1 AS a) SELECT a FROM x)",
+        "SELECT * FROM t WHERE id IN (SELECT id FROM u)",
+        "SELECT * FROM t WHERE id NOT IN (SELECT id FROM u)",
+        "SELECT * FROM t WHERE EXISTS (SELECT 1 FROM u) AND NOT EXISTS (SELECT 2)",
+        "SELECT (SELECT MAX(id) FROM u) AS v",
+        "SELECT ARRAY(SELECT id FROM u) AS v",
+        "SELECT * FROM (SELECT 1 AS a) AS d",
+        "SELECT * FROM t JOIN (SELECT 1 AS a) d ON TRUE",
+        "SELECT * FROM t, LATERAL (SELECT t.a) AS d, LATERAL f(t.a) AS g",
+        "SELECT 1 UNION ALL (SELECT 2 UNION DISTINCT SELECT 3)",
+        "SELECT 1 INTERSECT DISTINCT SELECT 1 EXCEPT DISTINCT SELECT 2",
+        "SELECT ((SELECT 1) UNION ALL (SELECT 2) ORDER BY 1 LIMIT 1)",
+        "SELECT ((SELECT 1) + 1)",
+        "SELECT * FROM ((SELECT 1 AS a)) AS d",
+        "SELECT * FROM (t JOIN u ON TRUE)",
+        "SELECT 1 IN ((SELECT 1), 2)",
+        "WITH RECURSIVE r (n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM r WHERE n < 5) SELECT n FROM r",
+        "SELECT DISTINCT a FROM t",
+        "SELECT DISTINCT ON (a, b + 1) a, c FROM t ORDER BY a, b + 1",
+        "SELECT ALL a FROM t",
+        "SELECT * EXCEPT (a, b) FROM t",
+        "SELECT * REPLACE (UPPER(name) AS name) FROM users",
+        "SELECT * FROM a LEFT JOIN b ON a.id = b.id RIGHT OUTER JOIN c USING (id) \
+         FULL JOIN d ON TRUE CROSS JOIN e NATURAL JOIN f",
+        "SELECT * FROM a LEFT SEMI JOIN b ON a.id = b.id LEFT ANTI JOIN c ON TRUE",
+        "SELECT * FROM a JOIN (b JOIN c ON b.id = c.id) ON a.id = b.id",
+        "SELECT * FROM t AS x (a, b), u @{FORCE_INDEX = 'idx'} AS y",
+        "SELECT * FROM UNNEST([1, 2, 3]) AS n WITH OFFSET AS o",
+        "SELECT * FROM UNNEST(arr) WITH OFFSET",
+        "SELECT * FROM my_table_function(1, x => 'a') AS f",
+        "SELECT * FROM sales PIVOT(SUM(amount) FOR quarter IN ('Q1', 'Q2' AS q2)) AS p",
+        "SELECT * FROM sales AS s PIVOT(SUM(a) total, COUNT(*) AS n FOR s.q IN (1, 2)) \
+         UNPIVOT INCLUDE NULLS (v FOR k IN (total_1, n_1 'x'))",
+        "SELECT * FROM t UNPIVOT EXCLUDE NULLS ((a, b) FOR k IN ((a1, b1) AS 1, (a2, b2)))",
+        "SELECT * FROM (a JOIN b ON TRUE) PIVOT(MAX(x) FOR y IN ('z')), pivot AS unpivot",
+        "SELECT * FROM events TABLESAMPLE SYSTEM (10 PERCENT), t AS x TABLESAMPLE BERNOULLI (0.5)",
+        "SELECT * FROM t TABLESAMPLE RESERVOIR (100 ROWS) REPEATABLE (42) AS s",
+        "SELECT * FROM (SELECT 1) AS d TABLESAMPLE BERNOULLI (50 PERCENT) REPEATABLE (7)",
+        "SELECT a, SUM(b) FROM t GROUP BY ROLLUP(a, c), CUBE(d), GROUPING SETS ((a, b), c)",
+        "SELECT a FROM t GROUP BY a HAVING COUNT(*) > 1 QUALIFY ROW_NUMBER() OVER w = 1 \
+         WINDOW w AS (PARTITION BY a ORDER BY b)",
+        "SELECT * FROM t ORDER BY a ASC NULLS FIRST, b DESC NULLS LAST LIMIT ALL",
+        "SELECT * FROM t OFFSET 5",
+        "SELECT * FROM t LIMIT 1 + 1",
+        "SELECT * FROM t LIMIT 5, 10",
+        "SELECT * FROM t LIMIT ALL OFFSET 3",
+        "SELECT * FROM t ORDER BY a OFFSET 5 ROWS FETCH FIRST 10 ROWS WITH TIES",
+        "SELECT * FROM t FETCH FIRST 1 ROW ONLY",
+        "SELECT * FROM t OFFSET 2 ROWS",
+        "SELECT (SELECT a FROM t FETCH NEXT ROWS ONLY) FROM u",
+        "SELECT `select`, `a\\`b`, `back\\\\slash` FROM `my table` AS `t`",
+        "SELECT t.`col`, `proj`.`ds`.`tbl`.x FROM `proj`.`ds`.`tbl`",
+        // Expressions
+        "SELECT 42, 2.5, 1e10, 0.1, 'hello', TRUE, FALSE, NULL",
+        "SELECT 1e400, -1e400, x * 1e999",
+        "SELECT 'it\\'s', 'a\\nb\\tc', 'back\\\\slash', \"double\", 'caf\u{e9}', '\\x01'",
+        "SELECT b'bytes', b'\\x00\\xff', b'quote\\''",
+        "SELECT 1 + 2 * 3, (1 + 2) * 3, 1 - (2 - 3), 1 - 2 - 3, 2 * (3 / 4)",
+        "SELECT a > b, x = 10, a AND b, a OR b AND c, (a OR b) AND c, NOT a",
+        "SELECT NOT a = b, NOT (a = b), a = NOT b",
+        "SELECT NOT a = 1 AND NOT b, a = NOT b = c, (NOT a) = b, -NOT a = 1",
+        "SELECT a BETWEEN NOT b AND c, a LIKE NOT b IS NULL, NOT NOT a < b",
+        "SELECT a + b = c, (a + b) = c, a = b = c, a = (b = c)",
+        "SELECT a | b ^ c & d << 1 >> 2, a || b || c, ~a, a % b",
+        "SELECT COUNT(*), COUNT(DISTINCT x), UPPER('hello'), f()",
+        "SELECT STRING_AGG(name, ',' ORDER BY name DESC LIMIT 10) FROM t",
+        "SELECT CASE WHEN x > 0 THEN 'positive' ELSE 'non-positive' END",
+        "SELECT CASE x WHEN 1 THEN 'one' WHEN 2 THEN 'two' END",
+        "SELECT CAST(x AS INT64), SAFE_CAST(y AS STRING), CAST(z AS NUMERIC(10, 2))",
+        "SELECT CAST(a AS ARRAY<STRUCT<x INT64, y ARRAY<STRING> > >)",
+        "SELECT CAST(a AS DOUBLE), CAST(b AS VARCHAR(10)), CAST(c AS BYTES(4))",
+        "SELECT CAST(a AS RANGE<DATE>), CAST(b AS UINT64), CAST(c AS INTERVAL)",
+        "SELECT CAST(a AS EMAIL), CAST(b AS geo.GEOGRAPHY(4326)), CAST(c AS `my type`)",
+        "SELECT x BETWEEN 1 AND 10, x NOT BETWEEN 1 AND 10",
+        "SELECT x BETWEEN (1 + 1) AND (2 * 5), (x BETWEEN 1 AND 2) IS TRUE",
+        "SELECT x IN (1, 2, 3), x NOT IN (1), x IN (SELECT 1)",
+        "SELECT x IN UNNEST(arr), x NOT IN UNNEST([1, 2])",
+        "SELECT name LIKE 'John%', name NOT LIKE '%x' ESCAPE '!'",
+        "SELECT x IS NULL, x IS NOT NULL, x IS TRUE, x IS NOT FALSE, x + 1 IS NULL",
+        "SELECT [1, 2, 3], [], ARRAY<INT64>[1, 2], ARRAY<STRUCT<a INT64> >[]",
+        "SELECT (1, 'a'), STRUCT(1 AS a, 2), STRUCT()",
+        "SELECT arr[0], arr[OFFSET(1)], arr[ORDINAL(1)], arr[SAFE_OFFSET(0)], arr[SAFE_ORDINAL(2)]",
+        "SELECT s.a.b, (s).a, f(x).y, arr[0].field",
+        "SELECT EXTRACT(YEAR FROM d), EXTRACT(DAYOFWEEK FROM ts)",
+        "SELECT INTERVAL 1 DAY, INTERVAL -5 HOUR, d + INTERVAL (n * 2) MONTH",
+        "SELECT DATE '2024-01-01', TIMESTAMP '2024-01-01 00:00:00', JSON '{\"a\": 1}', NUMERIC '1.5'",
+        "SELECT IF(a > b, a, b), COALESCE(a, b, c), NULLIF(a, b), IFNULL(a, 0)",
+        "SELECT ROW_NUMBER() OVER (PARTITION BY a ORDER BY b DESC), SUM(x) OVER ()",
+        "SELECT SUM(x) OVER (ORDER BY t ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW)",
+        "SELECT SUM(x) OVER (ORDER BY t RANGE BETWEEN 2 PRECEDING AND 3 FOLLOWING EXCLUDE TIES)",
+        "SELECT AVG(x) OVER (GROUPS UNBOUNDED PRECEDING EXCLUDE NO OTHERS)",
+        "SELECT SUM(x) OVER (ROWS CURRENT ROW EXCLUDE CURRENT ROW), SUM(y) OVER (ROWS 1 PRECEDING EXCLUDE GROUP)",
+        "SELECT -x.y, -arr[1], -(a + b), NOT x IS NULL, -INTERVAL 1 DAY",
+        // DML
+        "INSERT INTO t (a, b) VALUES (1, 2), (3, 4)",
+        "INSERT INTO users (id, name) VALUES (1, 'Alice')",
+        "INSERT INTO archive SELECT * FROM users WHERE active = false",
+        "INSERT INTO t (SELECT 1)",
+        "INSERT INTO t (a) (SELECT 1) UNION ALL (SELECT 2)",
+        "INSERT INTO t WITH x AS (SELECT 1 AS a) SELECT a FROM x",
+        "INSERT OR IGNORE INTO t DEFAULT VALUES",
+        "INSERT OR REPLACE INTO t VALUES (1) RETURNING *",
+        "INSERT OR UPDATE INTO t (a) VALUES (DEFAULT_VALUE) RETURNING a, b AS c",
+        "UPDATE t SET a = 1 WHERE b NOT IN (1, 2)",
+        "UPDATE users SET name = 'Bob' WHERE id = 1",
+        "UPDATE users SET address.city = 'NYC' WHERE id = 1",
+        "UPDATE users SET profile.contact.email = 'test@example.com'",
+        "UPDATE users SET name = 'Bob', age = 30, active = true",
+        "UPDATE users @{ALLOW_FULL_SCAN = TRUE} AS u SET age = 1",
+        "UPDATE t SET a = s.a FROM s WHERE t.id = s.id RETURNING t.a",
+        "DELETE FROM users WHERE id = 1",
+        "DELETE FROM sessions @{ALLOW_FULL_SCAN = TRUE}",
+        "DELETE FROM users AS u WHERE u.id = 1 RETURNING *",
+        "MERGE INTO users AS t USING staged AS s ON t.id = s.id \
+         WHEN MATCHED AND s.deleted THEN DELETE \
+         WHEN MATCHED THEN UPDATE SET name = s.name, age = s.age \
+         WHEN NOT MATCHED THEN INSERT (id, name) VALUES (s.id, s.name) \
+         WHEN NOT MATCHED BY SOURCE AND t.age > 100 THEN DELETE",
+        "MERGE INTO t USING (SELECT 1 AS id) AS s ON t.id = s.id WHEN NOT MATCHED THEN INSERT VALUES (s.id)",
+        // DDL
+        "CREATE TABLE users (id INT64 NOT NULL PRIMARY KEY, name STRING, email STRING UNIQUE)",
+        "CREATE OR REPLACE TEMP TABLE t (a INT64 DEFAULT 1 NOT NULL, b STRING NULL CHECK (b != '-'), \
+         c INT64 REFERENCES u (id) ON DELETE CASCADE ON UPDATE SET NULL, \
+         d INT64 GENERATED ALWAYS AS (a + 1), e INT64 GENERATED BY DEFAULT AS (2) HIDDEN, \
+         f STRING OPTIONS(description = 'x'), \
+         CONSTRAINT pk PRIMARY KEY (a ASC, c DESC) OPTIONS(enforced = FALSE), UNIQUE (b), \
+         FOREIGN KEY (c) REFERENCES u (id) ON DELETE NO ACTION, \
+         CHECK (a > 0) NOT ENFORCED, CONSTRAINT ck CHECK (c > 0) ENFORCED) \
+         PARTITION BY ts CLUSTER BY a, b OPTIONS(expiration_days = 3)",
+        "CREATE TABLE IF NOT EXISTS t (a INT64, b ARRAY<STRUCT<x INT64, y STRING> >)",
+        "CREATE TABLE t (e EMAIL NOT NULL, g STRUCT<GEOGRAPHY(4326), p POINT>)",
+        "CREATE TABLE n AS SELECT 1 AS a",
+        "CREATE TEMP TABLE n (a, b) OPTIONS(description = 'd') AS SELECT 1, 2",
+        "CREATE TABLE n LIKE o",
+        "CREATE TABLE n CLONE o",
+        "CREATE TABLE t (a INT64 REFERENCES u ON DELETE RESTRICT ON UPDATE SET DEFAULT)",
+        "CREATE VIEW active_users AS SELECT * FROM users WHERE active = true",
+        "CREATE OR REPLACE MATERIALIZED VIEW v (a, b) OPTIONS(refresh = TRUE) AS SELECT 1, 2",
+        "CREATE VIEW IF NOT EXISTS v AS (SELECT 1) UNION ALL (SELECT 2)",
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx ON t (a, b DESC) OPTIONS(x = 1)",
+        "CREATE INDEX ON t (a)",
+        "CREATE FUNCTION add(x INT64, y INT64) RETURNS INT64 AS (x + y)",
+        "CREATE OR REPLACE TEMP FUNCTION f(x INT64, y INT64 DEFAULT 2) AS (x * y)",
+        "CREATE FUNCTION js(x STRING) RETURNS STRING LANGUAGE js OPTIONS(library = 'lib.js') \
+         AS 'return x.toUpperCase();'",
+        "CREATE FUNCTION IF NOT EXISTS ext(INT64) RETURNS INT64",
+        "CREATE PROCEDURE p(IN a INT64, OUT b STRING) BEGIN SELECT 1; END",
+        "CREATE OR REPLACE PROCEDURE p(INOUT a INT64) OPTIONS(x = 1) BEGIN SELECT 1; DELETE FROM t WHERE a = 1; END",
+        "CREATE DATABASE IF NOT EXISTS db OPTIONS(location = 'us')",
+        "ALTER TABLE users ADD COLUMN age INT64",
+        "ALTER TABLE IF EXISTS t ADD COLUMN IF NOT EXISTS a STRING NOT NULL",
+        "ALTER TABLE t DROP COLUMN IF EXISTS a",
+        "ALTER TABLE t DROP COLUMN a",
+        "ALTER TABLE t ALTER COLUMN a SET DATA TYPE NUMERIC(20)",
+        "ALTER TABLE t ALTER COLUMN a SET DEFAULT 'x' || 'y'",
+        "ALTER TABLE t ALTER COLUMN a DROP DEFAULT",
+        "ALTER TABLE t ALTER COLUMN a SET NOT NULL",
+        "ALTER TABLE t ALTER COLUMN a DROP NOT NULL",
+        "ALTER TABLE t ALTER COLUMN a SET OPTIONS (description = 'd')",
+        "ALTER TABLE t ADD CONSTRAINT fk FOREIGN KEY (a) REFERENCES u (id)",
+        "ALTER TABLE t ADD PRIMARY KEY (a)",
+        "ALTER TABLE t DROP CONSTRAINT IF EXISTS fk",
+        "ALTER TABLE t RENAME COLUMN a TO b",
+        "ALTER TABLE t RENAME TO u",
+        "ALTER TABLE t SET OPTIONS (description = 'd', labels = [1, 2])",
+        "ALTER VIEW IF EXISTS v SET OPTIONS (description = 'd')",
+        "ALTER VIEW v AS SELECT 2",
+        "DROP TABLE IF EXISTS users CASCADE",
+        "DROP VIEW a, b.c",
+        "DROP MATERIALIZED VIEW v",
+        "DROP INDEX idx",
+        "DROP FUNCTION f",
+        "DROP PROCEDURE p",
+        "DROP DATABASE db",
+        "DROP SCHEMA s",
+        "TRUNCATE TABLE t",
+        // Transactions and utilities
+        "BEGIN TRANSACTION",
+        "BEGIN TRANSACTION READ ONLY",
+        "BEGIN READ WRITE",
+        "COMMIT",
+        "ROLLBACK",
+        "ROLLBACK TO SAVEPOINT sp",
+        "EXPLAIN SELECT * FROM users",
+        "EXPLAIN ANALYZE FORMAT JSON SELECT 1",
+        "EXPLAIN FORMAT TEXT DELETE FROM t WHERE a = 1",
+        "DESCRIBE db.t",
+        "SHOW TABLES",
+        "SHOW TABLES FROM db LIKE 'u%'",
+        "SHOW SCHEMAS",
+        "SHOW DATABASES",
+        "SHOW COLUMNS FROM db.t WHERE name = 'id'",
+        "SHOW FUNCTIONS FROM db",
+        "SHOW VARIABLES",
+        "SET timezone = 'UTC'",
+        "SET search_path = DEFAULT",
+    ];
+
+    /// The `Debug` form of a node with spans and node ids removed.
+    fn structure<T: std::fmt::Debug>(node: &T) -> String {
+        const SPAN: &str = ", span: Span { ";
+        const NODE_ID: &str = ", id: NodeId(";
+
+        let raw = format!("{:?}", node);
+        let mut out = String::with_capacity(raw.len());
+        let mut rest = raw.as_str();
+        while let Some(c) = rest.chars().next() {
+            if let Some(after) = rest.strip_prefix(SPAN) {
+                rest = &after[after.find(" }").map_or(after.len(), |i| i + 2)..];
+            } else if let Some(after) = rest.strip_prefix(NODE_ID) {
+                rest = &after[after.find(')').map_or(after.len(), |i| i + 1)..];
+            } else {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        out
+    }
+
+    /// Print every statement in `sql`, reparse the output, and check that
+    /// the trees match and that printing is stable.
+    fn assert_round_trip(sql: &str) {
+        let statements = Parser::new(sql)
+            .parse()
+            .unwrap_or_else(|e| panic!("{}: {}", sql, e));
+        for statement in statements {
+            let printed = statement.to_string();
+            let reparsed = Parser::new(&printed)
+                .parse()
+                .unwrap_or_else(|e| panic!("{}\nprinted as {}\n{}", sql, printed, e));
+            assert_eq!(reparsed.len(), 1, "{}\nprinted as {}", sql, printed);
+            assert_eq!(
+                structure(&reparsed[0]),
+                structure(&statement),
+                "{}\nprinted as {}",
+                sql,
+                printed
+            );
+            assert_eq!(reparsed[0].to_string(), printed);
+        }
+    }
+
+    #[test]
+    fn test_corpus_round_trips() {
+        for sql in CORPUS {
+            assert_round_trip(sql);
+        }
+    }
+
+    #[test]
+    fn test_non_finite_floats() {
+        for (value, sql) in [
+            (f64::INFINITY, "1e999"),
+            (f64::NEG_INFINITY, "CAST('-inf' AS FLOAT64)"),
+            (f64::NAN, "CAST('NaN' AS FLOAT64)"),
+        ] {
+            let expr = Expr::new(ExprKind::Float(value), Span::default());
+            assert_eq!(expr.to_string(), sql);
+            let select = format!("SELECT {}", expr);
+            Parser::new(&select)
+                .parse()
+                .unwrap_or_else(|e| panic!("{}: {}", select, e));
+        }
+    }
+
+    #[test]
+    fn test_generated_queries_round_trip() {
+        for seed in 0..200 {
+            let mut gen = TestGen::new(seed);
+            let catalog = gen.catalog();
+            for _ in 0..5 {
+                let query = gen.query(&catalog).unwrap();
+                assert_round_trip(&query.statement.to_string());
+                assert_eq!(
+                    structure(&Parser::new(&query.statement.to_string()).parse().unwrap()[0]),
+                    structure(&query.statement),
+                    "seed {}: {}",
+                    seed,
+                    query.sql
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_printed_form() {
+        let cases = [
+            ("select a from t where x=1", "SELECT a FROM t WHERE x = 1"),
+            ("SELECT \"it's\\n\"", "SELECT 'it\\'s\\n'"),
+            ("SELECT `a\\`b`, `x\\\\y`", "SELECT `a\\`b`, `x\\\\y`"),
+            ("SELECT - -1", "SELECT - -1"),
+            ("SELECT CAST(x AS DOUBLE)", "SELECT CAST(x AS FLOAT64)"),
+            (
+                "SELECT CAST(x AS ARRAY<ARRAY<INT64> >)",
+                "SELECT CAST(x AS ARRAY<ARRAY<BIGINT> >)",
+            ),
+            (
+                "SELECT * FROM a INNER JOIN b USING(id)",
+                "SELECT * FROM a JOIN b USING (id)",
+            ),
+            ("SELECT a x FROM t y", "SELECT a AS x FROM t AS y"),
+            (
+                "SELECT * FROM t OFFSET 5 LIMIT 10",
+                "SELECT * FROM t LIMIT 10 OFFSET 5",
+            ),
+            ("SELECT * FROM t LIMIT 5, 10", "SELECT * FROM t LIMIT 5, 10"),
+            (
+                "SELECT * FROM t OFFSET 2 ROW FETCH NEXT ROW ONLY",
+                "SELECT * FROM t OFFSET 2 ROWS FETCH FIRST 1 ROWS ONLY",
+            ),
+        ];
+        for (sql, expected) in cases {
+            let statement = Parser::new(sql).parse().unwrap().remove(0);
+            assert_eq!(statement.to_string(), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_built_trees_get_parentheses() {
+        use crate::ast::*;
+        use crate::error::Span;
+
+        let ident = |name: &str| {
+            Expr::boxed(
+                ExprKind::Identifier(Ident::new(name, Span::default())),
+                Span::default(),
+            )
+        };
+        let binary =
+            |op, left, right| Expr::boxed(ExprKind::BinaryOp { op, left, right }, Span::default());
+
+        // (a + b) * c and a - (b - c)
+        let product = binary(
+            BinaryOp::Multiply,
+            binary(BinaryOp::Plus, ident("a"), ident("b")),
+            ident("c"),
+        );
+        assert_eq!(product.to_string(), "(a + b) * c");
+        let difference = binary(
+            BinaryOp::Minus,
+            ident("a"),
+            binary(BinaryOp::Minus, ident("b"), ident("c")),
+        );
+        assert_eq!(difference.to_string(), "a - (b - c)");
+
+        // NOT (a = b) and (a LIKE b) IS NULL
+        let not = Expr::boxed(
+            ExprKind::UnaryOp {
+                op: UnaryOp::Not,
+                expr: binary(BinaryOp::Eq, ident("a"), ident("b")),
+            },
+            Span::default(),
+        );
+        assert_eq!(not.to_string(), "NOT a = b");
+        let like = Expr::boxed(
+            ExprKind::Like {
+                expr: ident("a"),
+                pattern: ident("b"),
+                escape: None,
+                negated: false,
+                case_insensitive: false,
+            },
+            Span::default(),
+        );
+        let is_null = Expr::boxed(
+            ExprKind::IsExpr {
+                expr: like,
+                test: IsTest::Null,
+                negated: false,
+            },
+            Span::default(),
+        );
+        assert_eq!(is_null.to_string(), "(a LIKE b) IS NULL");
+    }
+
+    #[test]
+    fn test_long_chains_display() {
+        let terms: Vec<String> = (0..10_000).map(|i| format!("c{} * 2", i)).collect();
+        for sep in [" + ", " AND ", " OR "] {
+            let sql = terms.join(sep);
+            let expr = Parser::new(&sql).parse_expression().unwrap();
+            assert_eq!(expr.to_string(), sql);
+        }
+    }
+}
@@ -2,19 +2,34 @@
 //!
 //! This module defines the expression types for the SQL AST.
 
-use super::{DataTypeSpec, Ident, ObjectName, Query, WindowSpec};
+use super::{DataTypeSpec, Ident, NodeId, ObjectName, Query, WindowSpec};
 use crate::error::Span;
 
 /// An SQL expression.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// A chain of binary operators, such as `a + b + c`, nests one level per
+/// operator, so it can be far deeper than anything else the parser builds.
+/// Cloning, comparing, and dropping walk such a chain in a loop rather than
+/// recursing once per operator.
+#[derive(Debug)]
 pub struct Expr {
     pub kind: ExprKind,
     pub span: Span,
+    pub id: NodeId,
 }
 
 impl Expr {
     pub fn new(kind: ExprKind, span: Span) -> Self {
-        Self { kind, span }
+        Self {
+            kind,
+            span,
+            id: NodeId::UNASSIGNED,
+        }
+    }
+
+    /// The node ID assigned by the parser.
+    pub fn node_id(&self) -> NodeId {
+        self.id
     }
 
     /// Create a boxed expression.
@@ -23,6 +38,81 @@ impl Expr {
     }
 }
 
+impl Clone for Expr {
+    fn clone(&self) -> Self {
+        let mut links = Vec::new();
+        let mut leaf = self;
+        while let ExprKind::BinaryOp { op, left, right } = &leaf.kind {
+            links.push((leaf, *op, right));
+            leaf = left;
+        }
+        let mut expr = Expr {
+            kind: leaf.kind.clone(),
+            span: leaf.span,
+            id: leaf.id,
+        };
+        for (link, op, right) in links.into_iter().rev() {
+            expr = Expr {
+                kind: ExprKind::BinaryOp {
+                    op,
+                    left: Box::new(expr),
+                    right: right.clone(),
+                },
+                span: link.span,
+                id: link.id,
+            };
+        }
+        expr
+    }
+}
+
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut a, mut b) = (self, other);
+        loop {
+            if a.span != b.span || a.id != b.id {
+                return false;
+            }
+            match (&a.kind, &b.kind) {
+                (
+                    ExprKind::BinaryOp {
+                        op: a_op,
+                        left: a_left,
+                        right: a_right,
+                    },
+                    ExprKind::BinaryOp {
+                        op: b_op,
+                        left: b_left,
+                        right: b_right,
+                    },
+                ) => {
+                    if a_op != b_op || a_right != b_right {
+                        return false;
+                    }
+                    a = a_left;
+                    b = b_left;
+                }
+                (a_kind, b_kind) => return a_kind == b_kind,
+            }
+        }
+    }
+}
+
+impl Drop for Expr {
+    fn drop(&mut self) {
+        // Detach each left operand before its parent is dropped, so that
+        // dropping the parent only recurses into its right operand.
+        let ExprKind::BinaryOp { left, .. } = &mut self.kind else {
+            return;
+        };
+        let mut next = std::mem::replace(&mut left.kind, ExprKind::Null);
+        while let ExprKind::BinaryOp { left, .. } = &mut next {
+            let inner = std::mem::replace(&mut left.kind, ExprKind::Null);
+            next = inner;
+        }
+    }
+}
+
 /// Expression kind.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ExprKind {
@@ -54,6 +144,8 @@ pub enum ExprKind {
     CompoundIdentifier(Vec<Ident>),
     /// Parameter reference (@param or ?)
     Parameter(Parameter),
+    /// System variable reference: `@@name` or `@@scope.name`
+    SystemVariable { scope: Option<Ident>, name: Ident },
 
     // Operators
     /// Unary operator
@@ -79,12 +171,30 @@ pub enum ExprKind {
         list: InList,
         negated: bool,
     },
-    /// LIKE expression
+    /// LIKE expression, or ILIKE when `case_insensitive`
     Like {
         expr: Box<Expr>,
         pattern: Box<Expr>,
         escape: Option<Box<Expr>>,
         negated: bool,
+        case_insensitive: bool,
+    },
+    /// `expr [NOT] LIKE|ILIKE ANY|ALL (pattern, ...)`: whether `expr`
+    /// matches any or all of the patterns
+    QuantifiedLike {
+        expr: Box<Expr>,
+        quantifier: Quantifier,
+        patterns: Vec<Box<Expr>>,
+        escape: Option<Box<Expr>>,
+        negated: bool,
+        case_insensitive: bool,
+    },
+    /// `expr [NOT] SIMILAR TO pattern`, matching a SQL regular expression
+    SimilarTo {
+        expr: Box<Expr>,
+        pattern: Box<Expr>,
+        escape: Option<Box<Expr>>,
+        negated: bool,
     },
     /// IS NULL / IS NOT NULL / IS TRUE / IS FALSE / IS UNKNOWN
     IsExpr {
@@ -92,7 +202,10 @@ pub enum ExprKind {
         test: IsTest,
         negated: bool,
     },
-    /// IS DISTINCT FROM
+    /// `left IS [NOT] DISTINCT FROM right`, and `left <=> right` as the
+    /// negated form. A null-safe comparison: two NULLs are not distinct,
+    /// NULL is distinct from every other value, and the result is never
+    /// NULL.
     IsDistinct {
         left: Box<Expr>,
         right: Box<Expr>,
@@ -148,12 +261,14 @@ pub enum ExprKind {
     Subquery(Box<Query>),
     /// EXISTS subquery
     Exists { subquery: Box<Query>, negated: bool },
-    /// ANY/SOME/ALL subquery
-    SubqueryOp {
-        left: Box<Expr>,
+    /// `expr op ANY|SOME|ALL (...)`: whether the comparison holds for any
+    /// or all of the values of a subquery or list. A list of one array,
+    /// as in `x = ANY (array)`, compares with the array's elements.
+    QuantifiedComparison {
+        expr: Box<Expr>,
         op: BinaryOp,
-        modifier: SubqueryModifier,
-        subquery: Box<Query>,
+        quantifier: Quantifier,
+        list: InList,
     },
     /// IN subquery
     InSubquery {
@@ -208,8 +323,29 @@ pub struct StructField {
 /// Query parameter.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Parameter {
+    /// `@name`.
     Named(String),
+    /// `?`, numbered from 1 in order of appearance within a statement.
     Positional(u32),
+    /// `$1`, `$2`, ... with an explicit number.
+    Numbered(u32),
+}
+
+impl Parameter {
+    /// Whether this is a named `@name` parameter.
+    pub fn is_named(&self) -> bool {
+        matches!(self, Parameter::Named(_))
+    }
+}
+
+impl std::fmt::Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Parameter::Named(name) => write!(f, "@{}", name),
+            Parameter::Positional(_) => write!(f, "?"),
+            Parameter::Numbered(n) => write!(f, "${}", n),
+        }
+    }
 }
 
 /// Unary operator.
@@ -271,18 +407,18 @@ impl BinaryOp {
         match self {
             BinaryOp::Or => 1,
             BinaryOp::And => 2,
-            BinaryOp::BitwiseOr => 3,
-            BinaryOp::BitwiseXor => 4,
-            BinaryOp::BitwiseAnd => 5,
-            BinaryOp::LeftShift | BinaryOp::RightShift => 6,
-            BinaryOp::Plus | BinaryOp::Minus => 7,
-            BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo | BinaryOp::Concat => 8,
             BinaryOp::Eq
             | BinaryOp::NotEq
             | BinaryOp::Lt
             | BinaryOp::LtEq
             | BinaryOp::Gt
-            | BinaryOp::GtEq => 9,
+            | BinaryOp::GtEq => 4,
+            BinaryOp::BitwiseOr => 5,
+            BinaryOp::BitwiseXor => 6,
+            BinaryOp::BitwiseAnd => 7,
+            BinaryOp::LeftShift | BinaryOp::RightShift => 8,
+            BinaryOp::Plus | BinaryOp::Minus => 9,
+            BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo | BinaryOp::Concat => 10,
         }
     }
 
@@ -323,6 +459,8 @@ impl std::fmt::Display for BinaryOp {
 pub enum InList {
     Values(Vec<Box<Expr>>),
     Subquery(Box<Query>),
+    /// The elements of an array: `IN UNNEST(array)`
+    Unnest(Box<Expr>),
 }
 
 /// IS test type.
@@ -334,9 +472,9 @@ pub enum IsTest {
     Unknown,
 }
 
-/// Subquery modifier for ANY/SOME/ALL.
+/// The ANY, SOME or ALL of a quantified comparison or LIKE.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SubqueryModifier {
+pub enum Quantifier {
     Any,
     Some,
     All,
@@ -546,6 +684,21 @@ mod tests {
         assert!(BinaryOp::Multiply.precedence() > BinaryOp::Plus.precedence());
         assert!(BinaryOp::And.precedence() > BinaryOp::Or.precedence());
         assert!(BinaryOp::Eq.precedence() > BinaryOp::And.precedence());
+        assert!(BinaryOp::BitwiseOr.precedence() > BinaryOp::Eq.precedence());
+        assert!(BinaryOp::Plus.precedence() > BinaryOp::LeftShift.precedence());
+    }
+
+    #[test]
+    fn test_long_chain_clone_eq_drop() {
+        let sql = vec!["c"; 10_000].join(" + ");
+        let expr = crate::Parser::new(&sql).parse_expression().unwrap();
+        let copy = expr.clone();
+        assert_eq!(copy, expr);
+        drop(copy);
+        let other = crate::Parser::new(&sql.replacen('c', "d", 1))
+            .parse_expression()
+            .unwrap();
+        assert_ne!(other, expr);
     }
 
     #[test]
@@ -3,9 +3,13 @@
 //! This module defines the complete AST structure for representing parsed SQL,
 //! following standard SQL conventions.
 
+mod display;
 mod expr;
+mod matching;
+mod node;
 mod stmt;
 mod types;
+pub mod visit;
 
 // Re-export types module first (has DataTypeSpec needed by others)
 pub use types::StructField as TypeStructField;
@@ -14,9 +18,19 @@ pub use types::{DataTypeKind, DataTypeSpec};
 // Re-export expression types
 pub use expr::*;
 
+pub(crate) use display::write_quoted;
+
 // Re-export statement types
 pub use stmt::*;
 
+pub use matching::IdentifierMatching;
+pub use node::{assign_node_ids, NodeId, NodeIndex, NodeRef};
+pub(crate) use node::{walk_expr, walk_statement};
+
+/// SQL covering every kind of node, for round-trip tests.
+#[cfg(test)]
+pub(crate) use display::tests::CORPUS;
+
 use crate::error::Span;
 
 /// An identifier (table name, column name, etc.).
@@ -24,17 +38,31 @@ use crate::error::Span;
 pub struct Ident {
     /// The identifier value (unquoted or with quotes removed).
     pub value: String,
-    /// Whether this identifier was quoted (backticks).
+    /// Whether this identifier was quoted.
     pub quoted: bool,
+    /// The quotes used, when `quoted` is set.
+    pub quote_style: QuoteStyle,
     /// Source span.
     pub span: Span,
 }
 
+/// The delimiters of a quoted identifier.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum QuoteStyle {
+    /// `` `name` ``, with backslash escapes.
+    #[default]
+    Backtick,
+    /// `"name"`, with `""` for an embedded quote. Only parsed with
+    /// [`ParserOptions::double_quote_identifiers`](crate::parser::ParserOptions::double_quote_identifiers).
+    DoubleQuote,
+}
+
 impl Ident {
     pub fn new(value: impl Into<String>, span: Span) -> Self {
         Self {
             value: value.into(),
             quoted: false,
+            quote_style: QuoteStyle::Backtick,
             span,
         }
     }
@@ -43,10 +71,19 @@ impl Ident {
         Self {
             value: value.into(),
             quoted: true,
+            quote_style: QuoteStyle::Backtick,
             span,
         }
     }
 
+    /// A quoted identifier written with double quotes, as in ANSI SQL.
+    pub fn double_quoted(value: impl Into<String>, span: Span) -> Self {
+        Self {
+            quote_style: QuoteStyle::DoubleQuote,
+            ..Self::quoted(value, span)
+        }
+    }
+
     /// Check if this identifier matches another (case-insensitive for unquoted).
     pub fn matches(&self, other: &str) -> bool {
         if self.quoted {
@@ -55,14 +92,36 @@ impl Ident {
             self.value.eq_ignore_ascii_case(other)
         }
     }
+
+    /// Check if this identifier matches another under a matching policy.
+    ///
+    /// Quoted identifiers are always case-sensitive.
+    pub fn matches_with(&self, other: &str, matching: IdentifierMatching) -> bool {
+        if self.quoted {
+            matching.normalize_quoted(&self.value) == matching.normalize_quoted(other)
+        } else {
+            matching.matches(&self.value, other)
+        }
+    }
+
+    /// The comparison key for this identifier under a matching policy.
+    pub fn normalized(&self, matching: IdentifierMatching) -> String {
+        if self.quoted {
+            matching.normalize_quoted(&self.value)
+        } else {
+            matching.normalize(&self.value)
+        }
+    }
 }
 
 impl std::fmt::Display for Ident {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.quoted {
-            write!(f, "`{}`", self.value)
-        } else {
+        if !self.quoted {
             write!(f, "{}", self.value)
+        } else if self.quote_style == QuoteStyle::DoubleQuote {
+            write!(f, "\"{}\"", self.value.replace('"', "\"\""))
+        } else {
+            display::write_quoted(f, &self.value, '`')
         }
     }
 }
@@ -101,6 +160,11 @@ impl ObjectName {
         }
     }
 
+    /// The comparison keys of each name part under a matching policy.
+    pub fn normalized(&self, matching: IdentifierMatching) -> Vec<String> {
+        self.parts.iter().map(|p| p.normalized(matching)).collect()
+    }
+
     /// Get the catalog name (third-to-last part, if any).
     pub fn catalog(&self) -> Option<&Ident> {
         if self.parts.len() >= 3 {
@@ -216,6 +280,32 @@ pub struct SqlOption {
     pub value: Box<Expr>,
 }
 
+/// An optimizer hint, from a `/*+ ... */` comment right after SELECT,
+/// INSERT, UPDATE, or DELETE: `/*+ BROADCAST_JOIN(t) */`.
+///
+/// Hint names are not checked, so engine-specific hints pass through as
+/// written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub name: Ident,
+    /// Arguments in parentheses; empty when there are none.
+    pub args: Vec<HintArg>,
+    pub span: Span,
+}
+
+/// An optimizer hint argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HintArg {
+    /// A possibly qualified name: `t` or `db.t`.
+    Name(ObjectName),
+    /// A number, as written.
+    Number(String),
+    /// A string literal's value.
+    String(String),
+    /// Any other argument, as written, such as `t idx` or `a = 1`.
+    Verbatim(String),
+}
+
 /// An alias with optional column aliases.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Alias {
@@ -261,6 +351,14 @@ pub struct Query {
     pub order_by: Vec<OrderByExpr>,
     pub limit: Option<LimitClause>,
     pub span: Span,
+    pub id: NodeId,
+}
+
+impl Query {
+    /// The node ID assigned by the parser.
+    pub fn node_id(&self) -> NodeId {
+        self.id
+    }
 }
 
 /// The body of a query (SELECT, set operations, or subquery).
@@ -284,11 +382,30 @@ pub enum SetOperator {
     Except,
 }
 
-/// LIMIT clause.
+/// LIMIT clause, in any of its spellings.
 #[derive(Debug, Clone, PartialEq)]
 pub struct LimitClause {
     pub count: Option<Box<Expr>>,
     pub offset: Option<Box<Expr>>,
+    /// `FETCH FIRST n ROWS WITH TIES`: also return rows that tie with the
+    /// last row in the ORDER BY.
+    pub with_ties: bool,
+    /// The syntax the clause was written in.
+    pub syntax: LimitSyntax,
+}
+
+/// Spelling of a [`LimitClause`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitSyntax {
+    /// `LIMIT count [OFFSET offset]` or `OFFSET offset [LIMIT count]`.
+    Limit,
+    /// MySQL's `LIMIT offset, count`.
+    LimitComma,
+    /// The standard
+    /// `[OFFSET offset ROWS] [FETCH FIRST count ROWS {ONLY | WITH TIES}]`.
+    /// `FETCH NEXT` and `ROW` are read as `FETCH FIRST` and `ROWS`, and an
+    /// omitted count as 1.
+    Fetch,
 }
 
 /// ORDER BY expression.
@@ -312,7 +429,17 @@ pub struct Select {
     pub having: Option<Box<Expr>>,
     pub qualify: Option<Box<Expr>>,
     pub window: Vec<WindowDef>,
+    /// Optimizer hints written after SELECT.
+    pub optimizer_hints: Vec<Hint>,
     pub span: Span,
+    pub id: NodeId,
+}
+
+impl Select {
+    /// The node ID assigned by the parser.
+    pub fn node_id(&self) -> NodeId {
+        self.id
+    }
 }
 
 /// SELECT AS modifier for value tables.
@@ -331,6 +458,9 @@ pub enum SelectAs {
 pub enum Distinct {
     All,
     Distinct,
+    /// `DISTINCT ON (expr, ...)`: one row per distinct value of the
+    /// expressions, PostgreSQL style.
+    On(Vec<Box<Expr>>),
 }
 
 /// SELECT list item.
@@ -365,17 +495,44 @@ pub struct FromClause {
 
 /// Table reference in FROM clause.
 #[derive(Debug, Clone, PartialEq)]
-pub enum TableRef {
-    /// Simple table reference: `table [AS alias]`
+pub struct TableRef {
+    pub kind: TableRefKind,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+impl TableRef {
+    pub fn new(kind: TableRefKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            id: NodeId::UNASSIGNED,
+        }
+    }
+
+    /// The node ID assigned by the parser.
+    pub fn node_id(&self) -> NodeId {
+        self.id
+    }
+}
+
+/// Table reference kind.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableRefKind {
+    /// Simple table reference: `table [AS alias] [TABLESAMPLE ...]`
     Table {
         name: ObjectName,
         alias: Option<Alias>,
         hints: Vec<SqlOption>,
+        sample: Option<TableSample>,
     },
-    /// Subquery: `(SELECT ...) AS alias`
+    /// Subquery: `[LATERAL] (SELECT ...) AS alias [TABLESAMPLE ...]`
     Subquery {
         query: Box<Query>,
         alias: Option<Alias>,
+        sample: Option<TableSample>,
+        /// Whether the subquery can reference the FROM items before it.
+        lateral: bool,
     },
     /// UNNEST: `UNNEST(array) [AS alias] [WITH OFFSET [AS offset_alias]]`
     Unnest {
@@ -393,12 +550,67 @@ pub enum TableRef {
     },
     /// Parenthesized table reference
     Parenthesized(Box<TableRef>),
-    /// Table function: `TABLE_FUNCTION(...)`
+    /// Table function: `[LATERAL] TABLE_FUNCTION(...)`
     TableFunction {
         name: ObjectName,
         args: Vec<FunctionArg>,
         alias: Option<Alias>,
+        /// Whether the arguments can reference the FROM items before it.
+        lateral: bool,
+    },
+    /// PIVOT: `input PIVOT(SUM(x) [AS a], ... FOR column IN (value [AS b], ...)) [AS alias]`
+    Pivot {
+        input: Box<TableRef>,
+        /// Aggregate calls with optional aliases.
+        aggregates: Vec<(Box<Expr>, Option<Ident>)>,
+        for_column: Box<Expr>,
+        /// Pivoted values with optional column aliases.
+        values: Vec<(Box<Expr>, Option<Ident>)>,
+        alias: Option<Alias>,
     },
+    /// UNPIVOT: `input UNPIVOT [INCLUDE NULLS | EXCLUDE NULLS] (value FOR name IN (column [AS label], ...)) [AS alias]`
+    ///
+    /// The multi-column form `(v1, v2) FOR name IN ((a1, a2) AS label, ...)`
+    /// unpivots groups of columns into several value columns.
+    Unpivot {
+        input: Box<TableRef>,
+        include_nulls: bool,
+        value_columns: Vec<Ident>,
+        name_column: Ident,
+        /// Unpivoted column groups, each with an optional string or integer
+        /// label.
+        columns: Vec<(Vec<Ident>, Option<Box<Expr>>)>,
+        alias: Option<Alias>,
+    },
+}
+
+/// TABLESAMPLE clause: `TABLESAMPLE method (size) [REPEATABLE (seed)]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSample {
+    pub method: SampleMethod,
+    pub size: SampleSize,
+    pub seed: Option<Box<Expr>>,
+    pub span: Span,
+}
+
+/// Sampling method of a TABLESAMPLE clause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleMethod {
+    /// `BERNOULLI`: each row is kept with the given probability.
+    Bernoulli,
+    /// `SYSTEM`: storage blocks are kept with the given probability.
+    System,
+    /// `RESERVOIR`: a uniform sample of a fixed size.
+    Reservoir,
+}
+
+/// Sample size of a TABLESAMPLE clause.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleSize {
+    /// `(n PERCENT)`, or a bare `(n)`.
+    Percent(Box<Expr>),
+    /// `(n ROWS)`
+    Rows(Box<Expr>),
 }
 
 /// Type of JOIN.
@@ -448,9 +660,12 @@ pub struct WindowDef {
 /// Window specification.
 #[derive(Debug, Clone, PartialEq)]
 pub struct WindowSpec {
+    /// Named window this specification refines, as in `(w ORDER BY x)`.
+    pub base: Option<Ident>,
     pub partition_by: Vec<Box<Expr>>,
     pub order_by: Vec<OrderByExpr>,
     pub frame: Option<WindowFrame>,
+    pub span: Span,
 }
 
 /// Window frame specification.
@@ -459,6 +674,8 @@ pub struct WindowFrame {
     pub unit: WindowFrameUnit,
     pub start: WindowFrameBound,
     pub end: Option<WindowFrameBound>,
+    pub exclusion: Option<FrameExclusion>,
+    pub span: Span,
 }
 
 /// Window frame unit.
@@ -469,6 +686,15 @@ pub enum WindowFrameUnit {
     Groups,
 }
 
+/// Window frame exclusion (`EXCLUDE ...` after the frame bounds).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameExclusion {
+    CurrentRow,
+    Group,
+    Ties,
+    NoOthers,
+}
+
 /// Window frame bound.
 #[derive(Debug, Clone, PartialEq)]
 pub enum WindowFrameBound {
@@ -0,0 +1,351 @@
+//! Stable node identifiers and source-to-AST lookup.
+//!
+//! The parser numbers every [`Statement`], [`Query`], [`Select`],
+//! [`TableRef`] and [`Expr`] it produces. The numbering contract is:
+//!
+//! - IDs are dense, starting at 0 for the first statement of a
+//!   [`Parser`](crate::parser::Parser) and continuing across statements.
+//! - IDs are assigned in pre-order: a node's ID is smaller than the IDs of
+//!   all of its descendants, and siblings are numbered in source order.
+//! - Parsing identical input text yields identical IDs.
+//!
+//! Nodes built by hand carry [`NodeId::UNASSIGNED`] until they are numbered
+//! with [`assign_node_ids`].
+
+use super::visit::{self, Visitor, VisitorMut};
+use super::*;
+use crate::error::Span;
+use std::fmt;
+
+/// Identifier of an AST node, unique within one parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub u32);
+
+impl NodeId {
+    /// The ID of a node that has not been numbered.
+    pub const UNASSIGNED: NodeId = NodeId(u32::MAX);
+
+    /// The raw ID value.
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    /// Check whether this ID was assigned by numbering.
+    pub fn is_assigned(self) -> bool {
+        self != Self::UNASSIGNED
+    }
+}
+
+impl Default for NodeId {
+    fn default() -> Self {
+        Self::UNASSIGNED
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A borrowed reference to a numbered AST node.
+#[derive(Debug, Clone, Copy)]
+pub enum NodeRef<'a> {
+    Statement(&'a Statement),
+    Query(&'a Query),
+    Select(&'a Select),
+    TableRef(&'a TableRef),
+    Expr(&'a Expr),
+}
+
+impl<'a> NodeRef<'a> {
+    /// The node's ID.
+    pub fn id(&self) -> NodeId {
+        match self {
+            NodeRef::Statement(n) => n.id,
+            NodeRef::Query(n) => n.id,
+            NodeRef::Select(n) => n.id,
+            NodeRef::TableRef(n) => n.id,
+            NodeRef::Expr(n) => n.id,
+        }
+    }
+
+    /// The node's source span.
+    pub fn span(&self) -> Span {
+        match self {
+            NodeRef::Statement(n) => n.span,
+            NodeRef::Query(n) => n.span,
+            NodeRef::Select(n) => n.span,
+            NodeRef::TableRef(n) => n.span,
+            NodeRef::Expr(n) => n.span,
+        }
+    }
+}
+
+/// Lookup tables from node IDs to nodes and from source positions to the
+/// innermost node covering them.
+#[derive(Debug, Clone)]
+pub struct NodeIndex<'a> {
+    /// Nodes in pre-order.
+    nodes: Vec<NodeRef<'a>>,
+}
+
+impl<'a> NodeIndex<'a> {
+    /// Build an index over parsed statements.
+    pub fn build(statements: &'a [Statement]) -> Self {
+        let mut nodes = Vec::new();
+        for stmt in statements {
+            walk_statement(stmt, &mut |node| nodes.push(node));
+        }
+        nodes.sort_by_key(|n| n.id());
+        Self { nodes }
+    }
+
+    /// Number of indexed nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// All indexed nodes in ID order.
+    pub fn nodes(&self) -> &[NodeRef<'a>] {
+        &self.nodes
+    }
+
+    /// Look up a node by ID.
+    pub fn get(&self, id: NodeId) -> Option<NodeRef<'a>> {
+        self.nodes
+            .binary_search_by_key(&id, |n| n.id())
+            .ok()
+            .map(|i| self.nodes[i])
+    }
+
+    /// The source span of a node.
+    pub fn span(&self, id: NodeId) -> Option<Span> {
+        self.get(id).map(|n| n.span())
+    }
+
+    /// The smallest node whose span contains the byte offset `pos`.
+    ///
+    /// When several nodes share the smallest span, the innermost one (the
+    /// highest ID) wins.
+    pub fn node_at(&self, pos: usize) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .filter(|n| {
+                let span = n.span();
+                !span.is_synthetic() && span.start <= pos && pos < span.end
+            })
+            .min_by_key(|n| (n.span().len(), std::cmp::Reverse(n.id())))
+            .map(|n| n.id())
+    }
+}
+
+/// Visit `expr` and every numbered node nested in it, in pre-order.
+pub(crate) fn walk_expr<'a>(expr: &'a Expr, f: &mut dyn FnMut(NodeRef<'a>)) {
+    Collector(f).visit_expr(expr);
+}
+
+/// Visit `stmt` and every numbered node nested in it, in pre-order.
+pub(crate) fn walk_statement<'a>(stmt: &'a Statement, f: &mut dyn FnMut(NodeRef<'a>)) {
+    Collector(f).visit_statement(stmt);
+}
+
+/// Number every node of `stmt` in pre-order, starting at `*next`.
+///
+/// On return `*next` is one past the last ID used, so consecutive calls
+/// number several statements without collisions.
+pub fn assign_node_ids(stmt: &mut Statement, next: &mut u32) {
+    Numberer(next).visit_statement(stmt);
+}
+
+/// Passes each numbered node to a callback before visiting its children.
+struct Collector<'f, 'a>(&'f mut dyn FnMut(NodeRef<'a>));
+
+impl<'a> Visitor<'a> for Collector<'_, 'a> {
+    fn visit_statement(&mut self, stmt: &'a Statement) {
+        (self.0)(NodeRef::Statement(stmt));
+        visit::walk_statement(self, stmt);
+    }
+
+    fn visit_query(&mut self, query: &'a Query) {
+        (self.0)(NodeRef::Query(query));
+        visit::walk_query(self, query);
+    }
+
+    fn visit_select(&mut self, select: &'a Select) {
+        (self.0)(NodeRef::Select(select));
+        visit::walk_select(self, select);
+    }
+
+    fn visit_table_ref(&mut self, table: &'a TableRef) {
+        (self.0)(NodeRef::TableRef(table));
+        visit::walk_table_ref(self, table);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        visit::walk_binary_chain(self, expr, |v, e| (v.0)(NodeRef::Expr(e)));
+    }
+}
+
+/// Assigns consecutive IDs to numbered nodes before visiting their
+/// children.
+struct Numberer<'a>(&'a mut u32);
+
+impl Numberer<'_> {
+    fn next(&mut self) -> NodeId {
+        let id = NodeId(*self.0);
+        *self.0 += 1;
+        id
+    }
+}
+
+impl VisitorMut for Numberer<'_> {
+    fn visit_statement(&mut self, stmt: &mut Statement) {
+        stmt.id = self.next();
+        visit::walk_statement_mut(self, stmt);
+    }
+
+    fn visit_query(&mut self, query: &mut Query) {
+        query.id = self.next();
+        visit::walk_query_mut(self, query);
+    }
+
+    fn visit_select(&mut self, select: &mut Select) {
+        select.id = self.next();
+        visit::walk_select_mut(self, select);
+    }
+
+    fn visit_table_ref(&mut self, table: &mut TableRef) {
+        table.id = self.next();
+        visit::walk_table_ref_mut(self, table);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        visit::walk_binary_chain_mut(self, expr, |v, e| e.id = v.next());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn parse(sql: &str) -> Vec<Statement> {
+        Parser::new(sql).parse().expect("Failed to parse")
+    }
+
+    #[test]
+    fn test_ids_dense_and_preordered() {
+        let stmts = parse("SELECT a + 1 FROM t JOIN u ON t.id = u.id; SELECT (SELECT 2)");
+        let index = NodeIndex::build(&stmts);
+        let ids: Vec<u32> = index.nodes().iter().map(|n| n.id().as_u32()).collect();
+        assert_eq!(ids, (0..ids.len() as u32).collect::<Vec<_>>());
+
+        // Pre-order: each node precedes everything nested inside it.
+        let mut order = Vec::new();
+        walk_statement(&stmts[0], &mut |n| order.push(n.id()));
+        assert!(order.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(stmts[0].node_id(), NodeId(0));
+        assert_eq!(stmts[1].node_id().as_u32(), order.len() as u32);
+
+        assert!(matches!(index.get(NodeId(0)), Some(NodeRef::Statement(_))));
+        assert!(matches!(index.get(NodeId(1)), Some(NodeRef::Query(_))));
+        assert!(matches!(index.get(NodeId(2)), Some(NodeRef::Select(_))));
+        // `a + 1` comes before its operands.
+        let Some(NodeRef::Expr(sum)) = index.get(NodeId(3)) else {
+            panic!("expected expression");
+        };
+        assert!(matches!(sum.kind, ExprKind::BinaryOp { .. }));
+        assert!(index.get(NodeId(ids.len() as u32)).is_none());
+    }
+
+    #[test]
+    fn test_ids_stable_for_identical_input() {
+        let sql = "SELECT x FROM t WHERE y IN (SELECT z FROM u)";
+        assert_eq!(parse(sql), parse(sql));
+        let a = parse(sql);
+        let b = parse(sql);
+        let ids = |s: &[Statement]| -> Vec<(NodeId, Span)> {
+            NodeIndex::build(s)
+                .nodes()
+                .iter()
+                .map(|n| (n.id(), n.span()))
+                .collect()
+        };
+        assert_eq!(ids(&a), ids(&b));
+    }
+
+    #[test]
+    fn test_node_at_returns_innermost() {
+        let sql = "SELECT f(a * (b + c)) FROM t";
+        let stmts = parse(sql);
+        let index = NodeIndex::build(&stmts);
+
+        let pos = sql.find('c').unwrap();
+        let id = index.node_at(pos).unwrap();
+        let Some(NodeRef::Expr(e)) = index.get(id) else {
+            panic!("expected expression");
+        };
+        assert!(matches!(&e.kind, ExprKind::Identifier(i) if i.value == "c"));
+
+        let pos = sql.find('+').unwrap();
+        let Some(NodeRef::Expr(e)) = index.node_at(pos).and_then(|id| index.get(id)) else {
+            panic!("expected expression");
+        };
+        assert!(matches!(
+            e.kind,
+            ExprKind::BinaryOp {
+                op: BinaryOp::Plus,
+                ..
+            }
+        ));
+
+        let pos = sql.rfind('t').unwrap();
+        let Some(NodeRef::TableRef(t)) = index.node_at(pos).and_then(|id| index.get(id)) else {
+            panic!("expected table reference");
+        };
+        assert_eq!(t.span, Span::new(pos, pos + 1));
+        assert_eq!(index.span(t.id), Some(t.span));
+    }
+
+    #[test]
+    fn test_assign_node_ids_on_built_nodes() {
+        let mut stmt = Statement::new(
+            StatementKind::Query(Box::new(Query {
+                with: None,
+                body: QueryBody::Select(Box::new(Select {
+                    distinct: None,
+                    select_as: None,
+                    projection: vec![SelectItem::Expr {
+                        expr: Expr::boxed(ExprKind::Integer(1), Span::new(7, 8)),
+                        alias: None,
+                    }],
+                    from: None,
+                    where_clause: None,
+                    group_by: None,
+                    having: None,
+                    qualify: None,
+                    window: Vec::new(),
+                    optimizer_hints: Vec::new(),
+                    span: Span::new(0, 8),
+                    id: NodeId::UNASSIGNED,
+                })),
+                order_by: Vec::new(),
+                limit: None,
+                span: Span::new(0, 8),
+                id: NodeId::UNASSIGNED,
+            })),
+            Span::new(0, 8),
+        );
+        assert!(!stmt.node_id().is_assigned());
+        let mut next = 10;
+        assign_node_ids(&mut stmt, &mut next);
+        assert_eq!(stmt.node_id(), NodeId(10));
+        assert_eq!(next, 14);
+    }
+}
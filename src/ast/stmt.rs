@@ -3,8 +3,8 @@
 //! This module defines the statement types for SQL DDL and DML.
 
 use super::{
-    Alias, ColumnDef, DataTypeSpec, Expr, FromClause, Ident, ObjectName, Query, SelectItem,
-    SortKey, SqlOption, TableConstraint, TableRef,
+    Alias, ColumnDef, DataTypeSpec, Expr, FromClause, Hint, Ident, NodeId, ObjectName, Query,
+    SelectItem, SortKey, SqlOption, TableConstraint, TableRef,
 };
 use crate::error::Span;
 
@@ -13,11 +13,21 @@ use crate::error::Span;
 pub struct Statement {
     pub kind: StatementKind,
     pub span: Span,
+    pub id: NodeId,
 }
 
 impl Statement {
     pub fn new(kind: StatementKind, span: Span) -> Self {
-        Self { kind, span }
+        Self {
+            kind,
+            span,
+            id: NodeId::UNASSIGNED,
+        }
+    }
+
+    /// The node ID assigned by the parser.
+    pub fn node_id(&self) -> NodeId {
+        self.id
     }
 }
 
@@ -69,6 +79,8 @@ pub enum StatementKind {
 /// INSERT statement.
 #[derive(Debug, Clone, PartialEq)]
 pub struct InsertStatement {
+    /// Optimizer hints written after INSERT.
+    pub optimizer_hints: Vec<Hint>,
     pub or_action: Option<InsertOrAction>,
     pub table: ObjectName,
     pub columns: Vec<Ident>,
@@ -95,6 +107,8 @@ pub enum InsertSource {
 /// UPDATE statement.
 #[derive(Debug, Clone, PartialEq)]
 pub struct UpdateStatement {
+    /// Optimizer hints written after UPDATE.
+    pub optimizer_hints: Vec<Hint>,
     pub table: TableRef,
     pub assignments: Vec<Assignment>,
     pub from: Option<FromClause>,
@@ -119,7 +133,11 @@ pub enum AssignmentTarget {
 /// DELETE statement.
 #[derive(Debug, Clone, PartialEq)]
 pub struct DeleteStatement {
+    /// Optimizer hints written after DELETE.
+    pub optimizer_hints: Vec<Hint>,
     pub table: ObjectName,
+    /// Table hints written as `@{name = value, ...}` after the table name.
+    pub hints: Vec<SqlOption>,
     pub alias: Option<Alias>,
     pub where_clause: Option<Box<Expr>>,
     pub returning: Option<ReturningClause>,
@@ -3,8 +3,10 @@
 //! This module defines how data types are represented in the AST,
 //! separate from the runtime type system. Type names follow ISO SQL standards.
 
-use super::Ident;
-use crate::error::Span;
+use super::{Expr, Ident, ObjectName};
+use crate::catalog::TypeRegistry;
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::types::{self, SqlType};
 
 /// Data type specification as it appears in SQL.
 #[derive(Debug, Clone, PartialEq)]
@@ -17,6 +19,116 @@ impl DataTypeSpec {
     pub fn new(kind: DataTypeKind, span: Span) -> Self {
         Self { kind, span }
     }
+
+    /// Convert to a [`SqlType`], resolving custom types through a registry.
+    ///
+    /// NUMERIC keeps its precision and scale, VARCHAR and VARBINARY their
+    /// length, capped at `u32::MAX`, and ARRAY, STRUCT, and RANGE convert
+    /// their element and field types. The arguments of custom types are
+    /// dropped. A custom type that is not an alias in
+    /// the registry is an [`ErrorKind::UndefinedType`] error at the type's
+    /// span.
+    ///
+    /// ```
+    /// use vibesql::ast::{DataTypeKind, DataTypeSpec, Ident, ObjectName};
+    /// use vibesql::catalog::TypeRegistry;
+    /// use vibesql::error::Span;
+    /// use vibesql::types::SqlType;
+    ///
+    /// let mut registry = TypeRegistry::new();
+    /// registry.add_alias("EMAIL", SqlType::Varchar(None));
+    ///
+    /// let named = |name: &str| {
+    ///     let name = ObjectName::simple(Ident::new(name, Span::default()));
+    ///     DataTypeSpec::new(DataTypeKind::Custom(name, vec![]), Span::default())
+    /// };
+    /// assert_eq!(named("email").to_sql_type(&registry).unwrap(), SqlType::Varchar(None));
+    /// assert!(named("PHONE").to_sql_type(&registry).is_err());
+    /// assert!(SqlType::try_from(&named("EMAIL")).is_err());
+    /// ```
+    pub fn to_sql_type(&self, registry: &TypeRegistry) -> Result<SqlType> {
+        self.to_sql_type_with(&mut |name| Ok(registry.resolve(&name.join(".")).cloned()))
+    }
+
+    /// Convert to a [`SqlType`], resolving custom types with a callback.
+    ///
+    /// The callback receives the parts of a custom type's name and returns
+    /// the type it stands for, or `None` if it is unknown. Otherwise this
+    /// behaves like [`DataTypeSpec::to_sql_type`].
+    pub fn to_sql_type_with(
+        &self,
+        resolve: &mut dyn FnMut(&[String]) -> Result<Option<SqlType>>,
+    ) -> Result<SqlType> {
+        convert(self, resolve)
+    }
+}
+
+/// Converts without a type registry, so every custom type is an
+/// [`ErrorKind::UndefinedType`] error. Use [`DataTypeSpec::to_sql_type`] to
+/// resolve aliases.
+impl TryFrom<&DataTypeSpec> for SqlType {
+    type Error = Error;
+
+    fn try_from(spec: &DataTypeSpec) -> Result<SqlType> {
+        convert(spec, &mut |_| Ok(None))
+    }
+}
+
+fn convert(
+    spec: &DataTypeSpec,
+    resolve: &mut dyn FnMut(&[String]) -> Result<Option<SqlType>>,
+) -> Result<SqlType> {
+    Ok(match &spec.kind {
+        DataTypeKind::Bool => SqlType::Bool,
+        DataTypeKind::Int32 => SqlType::Int32,
+        DataTypeKind::Int64 => SqlType::Int64,
+        DataTypeKind::Uint32 => SqlType::Uint32,
+        DataTypeKind::Uint64 => SqlType::Uint64,
+        DataTypeKind::Float32 => SqlType::Float32,
+        DataTypeKind::Float64 => SqlType::Float64,
+        DataTypeKind::Numeric { precision, scale } => SqlType::Numeric {
+            precision: *precision,
+            scale: *scale,
+        },
+        DataTypeKind::Varchar { max_length } => SqlType::Varchar(max_length.map(length)),
+        DataTypeKind::Varbinary { max_length } => SqlType::Varbinary(max_length.map(length)),
+        DataTypeKind::Date => SqlType::Date,
+        DataTypeKind::Time => SqlType::Time,
+        DataTypeKind::Datetime => SqlType::Datetime,
+        DataTypeKind::Timestamp => SqlType::Timestamp,
+        DataTypeKind::Interval => SqlType::Interval,
+        DataTypeKind::Json => SqlType::Json,
+        DataTypeKind::Uuid => SqlType::Uuid,
+        DataTypeKind::Any => SqlType::Any,
+        DataTypeKind::Array(elem) => SqlType::Array(Box::new(convert(elem, resolve)?)),
+        DataTypeKind::Struct(fields) => SqlType::Struct(
+            fields
+                .iter()
+                .map(|f| {
+                    Ok(types::StructField {
+                        name: f.name.as_ref().map(|i| i.value.clone()),
+                        data_type: convert(&f.data_type, resolve)?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+        ),
+        DataTypeKind::Range(elem) => SqlType::Range(Box::new(convert(elem, resolve)?)),
+        DataTypeKind::Custom(name, _) => {
+            let parts: Vec<String> = name.parts.iter().map(|p| p.value.clone()).collect();
+            match resolve(&parts)? {
+                Some(resolved) => resolved,
+                None => {
+                    let kind = ErrorKind::UndefinedType(parts.join("."));
+                    return Err(Error::with_span(kind, spec.span));
+                }
+            }
+        }
+    })
+}
+
+/// A VARCHAR or VARBINARY length as a [`SqlType`] length.
+fn length(len: u64) -> u32 {
+    u32::try_from(len).unwrap_or(u32::MAX)
 }
 
 /// The kind of data type.
@@ -89,8 +201,13 @@ pub enum DataTypeKind {
     /// UUID type
     Uuid,
 
-    /// Named types (for user-defined types)
-    Named(Vec<Ident>),
+    /// A type the parser does not know, such as a user-defined type or a
+    /// dialect alias, with its arguments as in `GEOGRAPHY(4326)`. The
+    /// analyzer resolves it through the catalog.
+    Custom(ObjectName, Vec<Box<Expr>>),
+
+    /// Any type (ANY TYPE), for a column whose values are not checked
+    Any,
 }
 
 /// Struct field in a STRUCT type.
@@ -202,12 +319,18 @@ impl std::fmt::Display for DataTypeKind {
             DataTypeKind::Json => write!(f, "JSON"),
             DataTypeKind::Range(elem) => write!(f, "RANGE<{}>", elem.kind),
             DataTypeKind::Uuid => write!(f, "UUID"),
-            DataTypeKind::Named(parts) => {
-                for (i, part) in parts.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, ".")?;
+            DataTypeKind::Any => write!(f, "ANY TYPE"),
+            DataTypeKind::Custom(name, args) => {
+                write!(f, "{}", name)?;
+                if !args.is_empty() {
+                    write!(f, "(")?;
+                    for (i, arg) in args.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", arg)?;
                     }
-                    write!(f, "{}", part)?;
+                    write!(f, ")")?;
                 }
                 Ok(())
             }
@@ -256,6 +379,96 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_sql_type() {
+        let spec = |kind| DataTypeSpec::new(kind, Span::default());
+        let numeric = spec(DataTypeKind::Numeric {
+            precision: Some(10),
+            scale: Some(2),
+        });
+        let cases = [
+            (
+                numeric.clone(),
+                SqlType::Numeric {
+                    precision: Some(10),
+                    scale: Some(2),
+                },
+            ),
+            (
+                spec(DataTypeKind::Varchar {
+                    max_length: Some(20),
+                }),
+                SqlType::Varchar(Some(20)),
+            ),
+            (
+                spec(DataTypeKind::Varbinary {
+                    max_length: Some(u64::MAX),
+                }),
+                SqlType::Varbinary(Some(u32::MAX)),
+            ),
+            (
+                spec(DataTypeKind::Varchar { max_length: None }),
+                SqlType::Varchar(None),
+            ),
+            (
+                spec(DataTypeKind::Array(Box::new(numeric.clone()))),
+                SqlType::Array(Box::new(SqlType::Numeric {
+                    precision: Some(10),
+                    scale: Some(2),
+                })),
+            ),
+            (
+                spec(DataTypeKind::Struct(vec![
+                    StructField {
+                        name: Some(Ident::new("a", Span::default())),
+                        data_type: spec(DataTypeKind::Int64),
+                    },
+                    StructField {
+                        name: None,
+                        data_type: spec(DataTypeKind::Bool),
+                    },
+                ])),
+                SqlType::Struct(vec![
+                    types::StructField {
+                        name: Some("a".to_string()),
+                        data_type: SqlType::Int64,
+                    },
+                    types::StructField {
+                        name: None,
+                        data_type: SqlType::Bool,
+                    },
+                ]),
+            ),
+            (
+                spec(DataTypeKind::Range(Box::new(spec(DataTypeKind::Date)))),
+                SqlType::Range(Box::new(SqlType::Date)),
+            ),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(SqlType::try_from(&spec).unwrap(), expected, "{}", spec.kind);
+        }
+
+        // Custom types resolve only through a registry, including nested.
+        let named = DataTypeSpec::new(
+            DataTypeKind::Custom(
+                ObjectName::simple(Ident::new("money", Span::new(5, 10))),
+                vec![],
+            ),
+            Span::new(5, 10),
+        );
+        let array = spec(DataTypeKind::Array(Box::new(named.clone())));
+        let err = SqlType::try_from(&array).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::UndefinedType("money".to_string()));
+        assert_eq!(err.span(), Some(Span::new(5, 10)));
+
+        let mut registry = TypeRegistry::new();
+        registry.add_alias("MONEY", SqlType::Int64);
+        assert_eq!(
+            array.to_sql_type(&registry).unwrap(),
+            SqlType::Array(Box::new(SqlType::Int64))
+        );
+    }
+
     #[test]
     fn test_type_classification() {
         assert!(DataTypeKind::Int64.is_numeric());
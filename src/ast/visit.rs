@@ -0,0 +1,1086 @@
+//! Traversal of the AST.
+//!
+//! [`Visitor`] walks a tree by shared reference and [`VisitorMut`] by
+//! mutable reference. Every method has a default that recurses into the
+//! node's children with the matching `walk_*` function, so an implementation
+//! overrides only the nodes it cares about. An override that does not call
+//! the `walk_*` function skips the node's children.
+//!
+//! The parser builds a chain of binary operators such as `a + b + c` in a
+//! loop, so the chain can be far deeper than recursion allows. The default
+//! `visit_expr` walks one with [`walk_binary_chain`], in a loop; an
+//! override that calls [`walk_expr`] recurses once per operator instead.
+//!
+//! Children are visited in source order, and every expression, table
+//! reference, and query reachable from a statement is visited, including
+//! those in DDL such as column defaults and `OPTIONS` values.
+//!
+//! # Examples
+//!
+//! Collecting the tables a statement reads:
+//!
+//! ```
+//! use vibesql::ast::visit::{walk_table_ref, Visitor};
+//! use vibesql::ast::{TableRef, TableRefKind};
+//! use vibesql::parser::Parser;
+//!
+//! #[derive(Default)]
+//! struct Tables(Vec<String>);
+//!
+//! impl<'ast> Visitor<'ast> for Tables {
+//!     fn visit_table_ref(&mut self, table: &'ast TableRef) {
+//!         if let TableRefKind::Table { name, .. } = &table.kind {
+//!             self.0.push(name.to_string());
+//!         }
+//!         walk_table_ref(self, table);
+//!     }
+//! }
+//!
+//! let stmt = Parser::new("SELECT * FROM a JOIN b ON a.id = b.id WHERE a.x IN (SELECT x FROM c)")
+//!     .parse_statement()
+//!     .unwrap();
+//! let mut tables = Tables::default();
+//! tables.visit_statement(&stmt);
+//! assert_eq!(tables.0, ["a", "b", "c"]);
+//! ```
+//!
+//! Renaming a column:
+//!
+//! ```
+//! use vibesql::ast::visit::{walk_expr_mut, VisitorMut};
+//! use vibesql::ast::{Expr, ExprKind};
+//! use vibesql::parser::Parser;
+//!
+//! struct Rename;
+//!
+//! impl VisitorMut for Rename {
+//!     fn visit_expr(&mut self, expr: &mut Expr) {
+//!         if let ExprKind::Identifier(ident) = &mut expr.kind {
+//!             if ident.matches("old") {
+//!                 ident.value = "new".to_string();
+//!             }
+//!         }
+//!         walk_expr_mut(self, expr);
+//!     }
+//! }
+//!
+//! let mut stmt = Parser::new("SELECT old + 1 FROM t WHERE old > 0")
+//!     .parse_statement()
+//!     .unwrap();
+//! Rename.visit_statement(&mut stmt);
+//! assert_eq!(stmt.to_string(), "SELECT new + 1 FROM t WHERE new > 0");
+//! ```
+
+use super::*;
+
+pub use walk::{
+    walk_binary_chain, walk_cte, walk_data_type, walk_expr, walk_function_call, walk_order_by_expr,
+    walk_query, walk_select, walk_select_item, walk_statement, walk_table_ref, walk_window_spec,
+};
+pub use walk_mut::{
+    walk_binary_chain as walk_binary_chain_mut, walk_cte as walk_cte_mut,
+    walk_data_type as walk_data_type_mut, walk_expr as walk_expr_mut,
+    walk_function_call as walk_function_call_mut, walk_order_by_expr as walk_order_by_expr_mut,
+    walk_query as walk_query_mut, walk_select as walk_select_mut,
+    walk_select_item as walk_select_item_mut, walk_statement as walk_statement_mut,
+    walk_table_ref as walk_table_ref_mut, walk_window_spec as walk_window_spec_mut,
+};
+
+/// A read-only AST traversal. `'ast` is the lifetime of the tree, so a
+/// visitor can keep references to the nodes it sees.
+pub trait Visitor<'ast> {
+    /// Visit a statement, including statements nested in function and
+    /// procedure bodies and under `EXPLAIN`.
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        walk_statement(self, stmt);
+    }
+
+    /// Visit a query: a statement body, CTE, subquery, or derived table.
+    fn visit_query(&mut self, query: &'ast Query) {
+        walk_query(self, query);
+    }
+
+    /// Visit a common table expression.
+    fn visit_cte(&mut self, cte: &'ast Cte) {
+        walk_cte(self, cte);
+    }
+
+    /// Visit a SELECT, including each operand of a set operation.
+    fn visit_select(&mut self, select: &'ast Select) {
+        walk_select(self, select);
+    }
+
+    /// Visit a select-list or RETURNING item.
+    fn visit_select_item(&mut self, item: &'ast SelectItem) {
+        walk_select_item(self, item);
+    }
+
+    /// Visit a table reference, including both sides of a join.
+    fn visit_table_ref(&mut self, table: &'ast TableRef) {
+        walk_table_ref(self, table);
+    }
+
+    /// Visit an expression.
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_binary_chain(self, expr, |_, _| {});
+    }
+
+    /// Visit the call of a scalar, aggregate, or window function.
+    fn visit_function_call(&mut self, call: &'ast FunctionCall) {
+        walk_function_call(self, call);
+    }
+
+    /// Visit an inline or named window specification.
+    fn visit_window_spec(&mut self, spec: &'ast WindowSpec) {
+        walk_window_spec(self, spec);
+    }
+
+    /// Visit an ORDER BY item of a query, function call, or window.
+    fn visit_order_by_expr(&mut self, item: &'ast OrderByExpr) {
+        walk_order_by_expr(self, item);
+    }
+
+    /// Visit a data type, including the element and field types inside it.
+    fn visit_data_type(&mut self, data_type: &'ast DataTypeSpec) {
+        walk_data_type(self, data_type);
+    }
+}
+
+/// An AST traversal that can rewrite nodes in place.
+pub trait VisitorMut {
+    /// Visit a statement, including statements nested in function and
+    /// procedure bodies and under `EXPLAIN`.
+    fn visit_statement(&mut self, stmt: &mut Statement) {
+        walk_statement_mut(self, stmt);
+    }
+
+    /// Visit a query: a statement body, CTE, subquery, or derived table.
+    fn visit_query(&mut self, query: &mut Query) {
+        walk_query_mut(self, query);
+    }
+
+    /// Visit a common table expression.
+    fn visit_cte(&mut self, cte: &mut Cte) {
+        walk_cte_mut(self, cte);
+    }
+
+    /// Visit a SELECT, including each operand of a set operation.
+    fn visit_select(&mut self, select: &mut Select) {
+        walk_select_mut(self, select);
+    }
+
+    /// Visit a select-list or RETURNING item.
+    fn visit_select_item(&mut self, item: &mut SelectItem) {
+        walk_select_item_mut(self, item);
+    }
+
+    /// Visit a table reference, including both sides of a join.
+    fn visit_table_ref(&mut self, table: &mut TableRef) {
+        walk_table_ref_mut(self, table);
+    }
+
+    /// Visit an expression.
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_binary_chain_mut(self, expr, |_, _| {});
+    }
+
+    /// Visit the call of a scalar, aggregate, or window function.
+    fn visit_function_call(&mut self, call: &mut FunctionCall) {
+        walk_function_call_mut(self, call);
+    }
+
+    /// Visit an inline or named window specification.
+    fn visit_window_spec(&mut self, spec: &mut WindowSpec) {
+        walk_window_spec_mut(self, spec);
+    }
+
+    /// Visit an ORDER BY item of a query, function call, or window.
+    fn visit_order_by_expr(&mut self, item: &mut OrderByExpr) {
+        walk_order_by_expr_mut(self, item);
+    }
+
+    /// Visit a data type, including the element and field types inside it.
+    fn visit_data_type(&mut self, data_type: &mut DataTypeSpec) {
+        walk_data_type_mut(self, data_type);
+    }
+}
+
+/// Generates the `walk_*` functions for one of the visitor traits. Both
+/// sets are expanded from the same body so shared and mutable traversals
+/// visit the same nodes in the same order.
+macro_rules! define_walk {
+    ($module:ident, $visitor:ident $(<$lt:lifetime>)? $(, $m:ident)?) => {
+        mod $module {
+            use super::*;
+
+            /// Visit the children of a statement.
+            pub fn walk_statement<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                s: & $($lt)? $($m)? Statement,
+            ) {
+                match & $($m)? s.kind {
+                    StatementKind::Query(q) => v.visit_query(q),
+                    StatementKind::Insert(insert) => {
+                        match & $($m)? insert.source {
+                            InsertSource::Values(rows) => {
+                                for row in rows {
+                                    exprs(v, row);
+                                }
+                            }
+                            InsertSource::Query(q) => v.visit_query(q),
+                            InsertSource::DefaultValues => {}
+                        }
+                        returning(v, & $($m)? insert.returning);
+                    }
+                    StatementKind::Update(update) => {
+                        v.visit_table_ref(& $($m)? update.table);
+                        assignments(v, & $($m)? update.assignments);
+                        if let Some(from) = & $($m)? update.from {
+                            for t in & $($m)? from.tables {
+                                v.visit_table_ref(t);
+                            }
+                        }
+                        opt_expr(v, & $($m)? update.where_clause);
+                        returning(v, & $($m)? update.returning);
+                    }
+                    StatementKind::Delete(delete) => {
+                        options(v, & $($m)? delete.hints);
+                        opt_expr(v, & $($m)? delete.where_clause);
+                        returning(v, & $($m)? delete.returning);
+                    }
+                    StatementKind::Merge(merge) => {
+                        v.visit_table_ref(& $($m)? merge.target);
+                        v.visit_table_ref(& $($m)? merge.source);
+                        v.visit_expr(& $($m)? merge.on);
+                        for clause in & $($m)? merge.clauses {
+                            match clause {
+                                MergeClause::Matched { condition, action }
+                                | MergeClause::NotMatchedBySource { condition, action } => {
+                                    opt_expr(v, condition);
+                                    if let MergeMatchedAction::Update { assignments: a } = action {
+                                        assignments(v, a);
+                                    }
+                                }
+                                MergeClause::NotMatched { condition, action } => {
+                                    opt_expr(v, condition);
+                                    exprs(v, & $($m)? action.values);
+                                }
+                            }
+                        }
+                    }
+                    StatementKind::CreateDatabase(create) => options(v, & $($m)? create.options),
+                    StatementKind::CreateTable(create) => {
+                        for column in & $($m)? create.columns {
+                            column_def(v, column);
+                        }
+                        for constraint in & $($m)? create.constraints {
+                            if let TableConstraint::Check { expr, .. } = constraint {
+                                v.visit_expr(expr);
+                            }
+                        }
+                        exprs(v, & $($m)? create.partition_by);
+                        exprs(v, & $($m)? create.cluster_by);
+                        options(v, & $($m)? create.options);
+                        if let Some(q) = & $($m)? create.as_query {
+                            v.visit_query(q);
+                        }
+                    }
+                    StatementKind::CreateView(create) => {
+                        v.visit_query(& $($m)? create.query);
+                        options(v, & $($m)? create.options);
+                    }
+                    StatementKind::CreateIndex(create) => options(v, & $($m)? create.options),
+                    StatementKind::CreateFunction(create) => {
+                        for param in & $($m)? create.params {
+                            v.visit_data_type(& $($m)? param.data_type);
+                            opt_expr(v, & $($m)? param.default);
+                        }
+                        if let Some(returns) = & $($m)? create.returns {
+                            v.visit_data_type(returns);
+                        }
+                        match & $($m)? create.body {
+                            FunctionBody::Expr(e) => v.visit_expr(e),
+                            FunctionBody::Statements(body) => {
+                                for s in body {
+                                    v.visit_statement(s);
+                                }
+                            }
+                            FunctionBody::External(_) => {}
+                        }
+                        options(v, & $($m)? create.options);
+                    }
+                    StatementKind::CreateProcedure(create) => {
+                        for param in & $($m)? create.params {
+                            v.visit_data_type(& $($m)? param.data_type);
+                        }
+                        for s in & $($m)? create.body {
+                            v.visit_statement(s);
+                        }
+                        options(v, & $($m)? create.options);
+                    }
+                    StatementKind::AlterTable(alter) => match & $($m)? alter.action {
+                        AlterTableAction::AddColumn { column, .. } => column_def(v, column),
+                        AlterTableAction::AlterColumn { action, .. } => match action {
+                            AlterColumnAction::SetDataType(data_type) => {
+                                v.visit_data_type(data_type)
+                            }
+                            AlterColumnAction::SetDefault(e) => v.visit_expr(e),
+                            AlterColumnAction::SetOptions(o) => options(v, o),
+                            AlterColumnAction::DropDefault
+                            | AlterColumnAction::SetNotNull
+                            | AlterColumnAction::DropNotNull => {}
+                        },
+                        AlterTableAction::AddConstraint(TableConstraint::Check {
+                            expr, ..
+                        }) => v.visit_expr(expr),
+                        AlterTableAction::SetOptions(o) => options(v, o),
+                        AlterTableAction::AddConstraint(_)
+                        | AlterTableAction::DropColumn { .. }
+                        | AlterTableAction::DropConstraint { .. }
+                        | AlterTableAction::RenameColumn { .. }
+                        | AlterTableAction::RenameTable(_) => {}
+                    },
+                    StatementKind::AlterView(alter) => match & $($m)? alter.action {
+                        AlterViewAction::SetOptions(o) => options(v, o),
+                        AlterViewAction::SetQuery(q) => v.visit_query(q),
+                    },
+                    StatementKind::Explain(explain) => {
+                        v.visit_statement(& $($m)? explain.statement)
+                    }
+                    StatementKind::Show(show) => {
+                        if let Some(ShowFilter::Where(e)) = & $($m)? show.filter {
+                            v.visit_expr(e);
+                        }
+                    }
+                    StatementKind::Set(set) => {
+                        if let SetValue::Expr(e) = & $($m)? set.value {
+                            v.visit_expr(e);
+                        }
+                    }
+                    StatementKind::Drop(_)
+                    | StatementKind::Truncate(_)
+                    | StatementKind::Begin(_)
+                    | StatementKind::Commit
+                    | StatementKind::Rollback(_)
+                    | StatementKind::Describe(_)
+                    | StatementKind::Empty => {}
+                }
+            }
+
+            /// Visit the CTEs, body, ORDER BY, and LIMIT of a query.
+            pub fn walk_query<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                q: & $($lt)? $($m)? Query,
+            ) {
+                if let Some(with) = & $($m)? q.with {
+                    for cte in & $($m)? with.ctes {
+                        v.visit_cte(cte);
+                    }
+                }
+                query_body(v, & $($m)? q.body);
+                for item in & $($m)? q.order_by {
+                    v.visit_order_by_expr(item);
+                }
+                if let Some(limit) = & $($m)? q.limit {
+                    opt_expr(v, & $($m)? limit.count);
+                    opt_expr(v, & $($m)? limit.offset);
+                }
+            }
+
+            /// Visit the query of a CTE.
+            pub fn walk_cte<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                cte: & $($lt)? $($m)? Cte,
+            ) {
+                v.visit_query(& $($m)? cte.query);
+            }
+
+            fn query_body<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                body: & $($lt)? $($m)? QueryBody,
+            ) {
+                match body {
+                    QueryBody::Select(s) => v.visit_select(s),
+                    QueryBody::SetOperation { left, right, .. } => {
+                        query_body(v, left);
+                        query_body(v, right);
+                    }
+                    QueryBody::Parenthesized(q) => v.visit_query(q),
+                }
+            }
+
+            /// Visit the clauses of a SELECT.
+            pub fn walk_select<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                s: & $($lt)? $($m)? Select,
+            ) {
+                if let Some(Distinct::On(list)) = & $($m)? s.distinct {
+                    exprs(v, list);
+                }
+                for item in & $($m)? s.projection {
+                    v.visit_select_item(item);
+                }
+                if let Some(from) = & $($m)? s.from {
+                    for t in & $($m)? from.tables {
+                        v.visit_table_ref(t);
+                    }
+                }
+                opt_expr(v, & $($m)? s.where_clause);
+                if let Some(group_by) = & $($m)? s.group_by {
+                    for item in & $($m)? group_by.items {
+                        match item {
+                            GroupByItem::Expr(e) => v.visit_expr(e),
+                            GroupByItem::Rollup(list) | GroupByItem::Cube(list) => exprs(v, list),
+                            GroupByItem::GroupingSets(sets) => {
+                                for set in sets {
+                                    exprs(v, set);
+                                }
+                            }
+                        }
+                    }
+                }
+                opt_expr(v, & $($m)? s.having);
+                opt_expr(v, & $($m)? s.qualify);
+                for def in & $($m)? s.window {
+                    v.visit_window_spec(& $($m)? def.spec);
+                }
+            }
+
+            /// Visit the expressions of a select item.
+            pub fn walk_select_item<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                item: & $($lt)? $($m)? SelectItem,
+            ) {
+                match item {
+                    SelectItem::Expr { expr, .. } => v.visit_expr(expr),
+                    SelectItem::WildcardReplace { replace, .. } => {
+                        for (e, _) in replace {
+                            v.visit_expr(e);
+                        }
+                    }
+                    SelectItem::Wildcard
+                    | SelectItem::QualifiedWildcard { .. }
+                    | SelectItem::WildcardExcept { .. } => {}
+                }
+            }
+
+            /// Visit the queries, expressions, and joined tables of a table
+            /// reference.
+            pub fn walk_table_ref<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                t: & $($lt)? $($m)? TableRef,
+            ) {
+                match & $($m)? t.kind {
+                    TableRefKind::Table { hints, sample, .. } => {
+                        options(v, hints);
+                        if let Some(sample) = sample {
+                            table_sample(v, sample);
+                        }
+                    }
+                    TableRefKind::Subquery { query, sample, .. } => {
+                        v.visit_query(query);
+                        if let Some(sample) = sample {
+                            table_sample(v, sample);
+                        }
+                    }
+                    TableRefKind::Unnest { expr, .. } => v.visit_expr(expr),
+                    TableRefKind::Join {
+                        left,
+                        right,
+                        condition,
+                        ..
+                    } => {
+                        v.visit_table_ref(left);
+                        v.visit_table_ref(right);
+                        if let Some(JoinCondition::On(e)) = condition {
+                            v.visit_expr(e);
+                        }
+                    }
+                    TableRefKind::Parenthesized(inner) => v.visit_table_ref(inner),
+                    TableRefKind::TableFunction { args, .. } => function_args(v, args),
+                    TableRefKind::Pivot {
+                        input,
+                        aggregates,
+                        for_column,
+                        values,
+                        ..
+                    } => {
+                        v.visit_table_ref(input);
+                        for (e, _) in aggregates {
+                            v.visit_expr(e);
+                        }
+                        v.visit_expr(for_column);
+                        for (e, _) in values {
+                            v.visit_expr(e);
+                        }
+                    }
+                    TableRefKind::Unpivot { input, columns, .. } => {
+                        v.visit_table_ref(input);
+                        for (_, label) in columns {
+                            if let Some(e) = label {
+                                v.visit_expr(e);
+                            }
+                        }
+                    }
+                }
+            }
+
+            /// Visit the operands, calls, subqueries, and types of an
+            /// expression.
+            pub fn walk_expr<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                e: & $($lt)? $($m)? Expr,
+            ) {
+                match & $($m)? e.kind {
+                    ExprKind::Null
+                    | ExprKind::Boolean(_)
+                    | ExprKind::Integer(_)
+                    | ExprKind::Float(_)
+                    | ExprKind::String(_)
+                    | ExprKind::Bytes(_)
+                    | ExprKind::Identifier(_)
+                    | ExprKind::CompoundIdentifier(_)
+                    | ExprKind::Parameter(_)
+                    | ExprKind::SystemVariable { .. }
+                    | ExprKind::TypedLiteral { .. } => {}
+                    ExprKind::Array {
+                        element_type,
+                        elements,
+                    } => {
+                        if let Some(element_type) = element_type {
+                            v.visit_data_type(element_type);
+                        }
+                        exprs(v, elements);
+                    }
+                    ExprKind::Struct { fields } => {
+                        for field in fields {
+                            v.visit_expr(& $($m)? field.value);
+                        }
+                    }
+                    ExprKind::Cast {
+                        expr, data_type, ..
+                    } => {
+                        v.visit_expr(expr);
+                        v.visit_data_type(data_type);
+                    }
+                    ExprKind::UnaryOp { expr: inner, .. }
+                    | ExprKind::Extract { from: inner, .. }
+                    | ExprKind::FieldAccess { expr: inner, .. }
+                    | ExprKind::Interval { value: inner, .. }
+                    | ExprKind::Parenthesized(inner) => v.visit_expr(inner),
+                    ExprKind::BinaryOp { left, right, .. }
+                    | ExprKind::IsDistinct { left, right, .. }
+                    | ExprKind::Nullif { left, right } => {
+                        v.visit_expr(left);
+                        v.visit_expr(right);
+                    }
+                    ExprKind::IfNull {
+                        expr: left,
+                        null_replacement: right,
+                    } => {
+                        v.visit_expr(left);
+                        v.visit_expr(right);
+                    }
+                    ExprKind::Between {
+                        expr: inner,
+                        low,
+                        high,
+                        ..
+                    } => {
+                        v.visit_expr(inner);
+                        v.visit_expr(low);
+                        v.visit_expr(high);
+                    }
+                    ExprKind::In { expr: inner, list, .. } => {
+                        v.visit_expr(inner);
+                        match list {
+                            InList::Values(values) => exprs(v, values),
+                            InList::Subquery(q) => v.visit_query(q),
+                            InList::Unnest(array) => v.visit_expr(array),
+                        }
+                    }
+                    ExprKind::Like {
+                        expr: inner,
+                        pattern,
+                        escape,
+                        ..
+                    } => {
+                        v.visit_expr(inner);
+                        v.visit_expr(pattern);
+                        opt_expr(v, escape);
+                    }
+                    ExprKind::QuantifiedLike {
+                        expr: inner,
+                        patterns,
+                        escape,
+                        ..
+                    } => {
+                        v.visit_expr(inner);
+                        exprs(v, patterns);
+                        opt_expr(v, escape);
+                    }
+                    ExprKind::SimilarTo {
+                        expr: inner,
+                        pattern,
+                        escape,
+                        ..
+                    } => {
+                        v.visit_expr(inner);
+                        v.visit_expr(pattern);
+                        opt_expr(v, escape);
+                    }
+                    ExprKind::IsExpr { expr: inner, .. } => v.visit_expr(inner),
+                    ExprKind::Function(call) => v.visit_function_call(call),
+                    ExprKind::Aggregate(agg) => {
+                        v.visit_function_call(& $($m)? agg.function);
+                        opt_expr(v, & $($m)? agg.filter);
+                    }
+                    ExprKind::WindowFunction(wf) => {
+                        v.visit_function_call(& $($m)? wf.function);
+                        if let WindowSpecOrRef::Spec(spec) = & $($m)? wf.window {
+                            v.visit_window_spec(spec);
+                        }
+                    }
+                    ExprKind::Case {
+                        operand,
+                        conditions,
+                        else_result,
+                    } => {
+                        opt_expr(v, operand);
+                        for (when, then) in conditions {
+                            v.visit_expr(when);
+                            v.visit_expr(then);
+                        }
+                        opt_expr(v, else_result);
+                    }
+                    ExprKind::If {
+                        condition,
+                        then_expr,
+                        else_expr,
+                    } => {
+                        v.visit_expr(condition);
+                        v.visit_expr(then_expr);
+                        v.visit_expr(else_expr);
+                    }
+                    ExprKind::Coalesce(list) | ExprKind::Row(list) => exprs(v, list),
+                    ExprKind::Subquery(q) | ExprKind::Exists { subquery: q, .. } => {
+                        v.visit_query(q)
+                    }
+                    ExprKind::QuantifiedComparison {
+                        expr: inner,
+                        list,
+                        ..
+                    } => {
+                        v.visit_expr(inner);
+                        match list {
+                            InList::Values(values) => exprs(v, values),
+                            InList::Subquery(q) => v.visit_query(q),
+                            InList::Unnest(array) => v.visit_expr(array),
+                        }
+                    }
+                    ExprKind::InSubquery {
+                        expr: inner,
+                        subquery,
+                        ..
+                    } => {
+                        v.visit_expr(inner);
+                        v.visit_query(subquery);
+                    }
+                    ExprKind::ArraySubscript { array, index } => {
+                        v.visit_expr(array);
+                        match index {
+                            ArraySubscriptKind::Index(i)
+                            | ArraySubscriptKind::Offset(i)
+                            | ArraySubscriptKind::Ordinal(i)
+                            | ArraySubscriptKind::SafeOffset(i)
+                            | ArraySubscriptKind::SafeOrdinal(i) => v.visit_expr(i),
+                        }
+                    }
+                    ExprKind::SafeArraySubscript { array, index, .. } => {
+                        v.visit_expr(array);
+                        v.visit_expr(index);
+                    }
+                    ExprKind::JsonSubscript { expr: inner, key } => {
+                        v.visit_expr(inner);
+                        if let JsonKey::Index(i) = key {
+                            v.visit_expr(i);
+                        }
+                    }
+                }
+            }
+
+            /// Visit an expression the way calling `enter` and then
+            /// [`walk_expr`] on it would, when every binary operator among
+            /// its left operands is visited the same way, but in a loop
+            /// rather than recursing once per operator of a chain such as
+            /// `a + b + c`.
+            ///
+            /// `enter` is called on `e` and on each binary operator down
+            /// its left operands, outermost first. The operands of the
+            /// innermost one, then the right operands from the inside out,
+            /// are visited as usual.
+            pub fn walk_binary_chain<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                e: & $($lt)? $($m)? Expr,
+                mut enter: impl FnMut(&mut V, & $($lt)? $($m)? Expr),
+            ) {
+                let mut rights = Vec::new();
+                let mut e = e;
+                loop {
+                    enter(v, & $($m)? *e);
+                    let chained = matches!(
+                        &e.kind,
+                        ExprKind::BinaryOp { left, .. }
+                            if matches!(left.kind, ExprKind::BinaryOp { .. })
+                    );
+                    if !chained {
+                        walk_expr(v, e);
+                        break;
+                    }
+                    let ExprKind::BinaryOp { left, right, .. } = & $($m)? e.kind else {
+                        unreachable!("checked above")
+                    };
+                    rights.push(right);
+                    e = left;
+                }
+                for right in rights.into_iter().rev() {
+                    v.visit_expr(right);
+                }
+            }
+
+            /// Visit the arguments, ORDER BY, and LIMIT of a function call.
+            pub fn walk_function_call<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                call: & $($lt)? $($m)? FunctionCall,
+            ) {
+                function_args(v, & $($m)? call.args);
+                for item in & $($m)? call.order_by {
+                    v.visit_order_by_expr(item);
+                }
+                opt_expr(v, & $($m)? call.limit);
+            }
+
+            /// Visit the partitioning, ordering, and frame bounds of a
+            /// window.
+            pub fn walk_window_spec<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                spec: & $($lt)? $($m)? WindowSpec,
+            ) {
+                exprs(v, & $($m)? spec.partition_by);
+                for item in & $($m)? spec.order_by {
+                    v.visit_order_by_expr(item);
+                }
+                if let Some(frame) = & $($m)? spec.frame {
+                    frame_bound(v, & $($m)? frame.start);
+                    if let Some(end) = & $($m)? frame.end {
+                        frame_bound(v, end);
+                    }
+                }
+            }
+
+            /// Visit the expression of an ORDER BY item.
+            pub fn walk_order_by_expr<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                item: & $($lt)? $($m)? OrderByExpr,
+            ) {
+                v.visit_expr(& $($m)? item.expr);
+            }
+
+            /// Visit the element or field types, or the arguments, of a data type.
+            pub fn walk_data_type<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                data_type: & $($lt)? $($m)? DataTypeSpec,
+            ) {
+                match & $($m)? data_type.kind {
+                    DataTypeKind::Array(inner) | DataTypeKind::Range(inner) => {
+                        v.visit_data_type(inner)
+                    }
+                    DataTypeKind::Struct(fields) => {
+                        for field in fields {
+                            v.visit_data_type(& $($m)? field.data_type);
+                        }
+                    }
+                    DataTypeKind::Custom(_, args) => exprs(v, args),
+                    _ => {}
+                }
+            }
+
+            fn exprs<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                list: & $($lt)? $($m)? [Box<Expr>],
+            ) {
+                for e in list {
+                    v.visit_expr(e);
+                }
+            }
+
+            fn opt_expr<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                e: & $($lt)? $($m)? Option<Box<Expr>>,
+            ) {
+                if let Some(e) = e {
+                    v.visit_expr(e);
+                }
+            }
+
+            fn function_args<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                args: & $($lt)? $($m)? [FunctionArg],
+            ) {
+                for arg in args {
+                    match arg {
+                        FunctionArg::Unnamed(e) | FunctionArg::Named { value: e, .. } => {
+                            v.visit_expr(e)
+                        }
+                        FunctionArg::Star => {}
+                    }
+                }
+            }
+
+            fn frame_bound<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                bound: & $($lt)? $($m)? WindowFrameBound,
+            ) {
+                match bound {
+                    WindowFrameBound::Preceding(e) | WindowFrameBound::Following(e) => {
+                        opt_expr(v, e)
+                    }
+                    WindowFrameBound::CurrentRow => {}
+                }
+            }
+
+            fn assignments<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                list: & $($lt)? $($m)? [Assignment],
+            ) {
+                for assignment in list {
+                    v.visit_expr(& $($m)? assignment.value);
+                }
+            }
+
+            fn returning<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                r: & $($lt)? $($m)? Option<ReturningClause>,
+            ) {
+                if let Some(r) = r {
+                    for item in & $($m)? r.items {
+                        v.visit_select_item(item);
+                    }
+                }
+            }
+
+            fn options<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                list: & $($lt)? $($m)? [SqlOption],
+            ) {
+                for option in list {
+                    v.visit_expr(& $($m)? option.value);
+                }
+            }
+
+            fn table_sample<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                sample: & $($lt)? $($m)? TableSample,
+            ) {
+                match & $($m)? sample.size {
+                    SampleSize::Percent(e) | SampleSize::Rows(e) => v.visit_expr(e),
+                }
+                if let Some(seed) = & $($m)? sample.seed {
+                    v.visit_expr(seed);
+                }
+            }
+
+            fn column_def<$($lt,)? V: $visitor $(<$lt>)? + ?Sized>(
+                v: &mut V,
+                column: & $($lt)? $($m)? ColumnDef,
+            ) {
+                if let Some(data_type) = & $($m)? column.data_type {
+                    v.visit_data_type(data_type);
+                }
+                for constraint in & $($m)? column.constraints {
+                    match constraint {
+                        ColumnConstraint::Default(e)
+                        | ColumnConstraint::Check(e)
+                        | ColumnConstraint::Generated { expr: e, .. } => v.visit_expr(e),
+                        _ => {}
+                    }
+                }
+                options(v, & $($m)? column.options);
+            }
+        }
+    };
+}
+
+define_walk!(walk, Visitor<'ast>);
+define_walk!(walk_mut, VisitorMut, mut);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn parse(sql: &str) -> Statement {
+        Parser::new(sql).parse_statement().expect("Failed to parse")
+    }
+
+    /// Names of identifiers, in visiting order.
+    #[derive(Default)]
+    struct Identifiers<'ast> {
+        names: Vec<&'ast str>,
+        skip_subqueries: bool,
+    }
+
+    impl<'ast> Visitor<'ast> for Identifiers<'ast> {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let ExprKind::Identifier(ident) = &expr.kind {
+                self.names.push(&ident.value);
+            }
+            walk_expr(self, expr);
+        }
+
+        fn visit_query(&mut self, query: &'ast Query) {
+            if !self.skip_subqueries || self.names.is_empty() {
+                walk_query(self, query);
+            }
+        }
+    }
+
+    #[test]
+    fn test_visits_in_source_order() {
+        let stmt = parse(
+            "WITH w AS (SELECT a FROM t) SELECT b, f(c ORDER BY d) OVER (PARTITION BY e) \
+             FROM w JOIN u ON g WHERE h IN (SELECT i FROM v) ORDER BY j LIMIT 1",
+        );
+        let mut visitor = Identifiers::default();
+        visitor.visit_statement(&stmt);
+        assert_eq!(visitor.names, ["a", "b", "c", "d", "e", "g", "h", "i", "j"]);
+    }
+
+    #[test]
+    fn test_override_without_walk_skips_children() {
+        let stmt = parse("SELECT a FROM t WHERE b = (SELECT c FROM u) AND EXISTS (SELECT d)");
+        let mut visitor = Identifiers {
+            skip_subqueries: true,
+            ..Identifiers::default()
+        };
+        visitor.visit_statement(&stmt);
+        assert_eq!(visitor.names, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_visits_ddl_expressions_and_types() {
+        #[derive(Default)]
+        struct Counts {
+            exprs: usize,
+            types: Vec<String>,
+        }
+
+        impl Visitor<'_> for Counts {
+            fn visit_expr(&mut self, expr: &Expr) {
+                self.exprs += 1;
+                walk_expr(self, expr);
+            }
+
+            fn visit_data_type(&mut self, data_type: &DataTypeSpec) {
+                self.types.push(data_type.to_string());
+                walk_data_type(self, data_type);
+            }
+        }
+
+        let stmt = parse(
+            "CREATE TABLE t (a INT64 DEFAULT 1 + 2, b ARRAY<STRUCT<x STRING> > CHECK (b IS NOT NULL)) \
+             OPTIONS (description = 'x')",
+        );
+        let mut counts = Counts::default();
+        counts.visit_statement(&stmt);
+        // `1 + 2` and its operands, `b IS NOT NULL` and `b`, and `'x'`.
+        assert_eq!(counts.exprs, 6);
+        assert_eq!(
+            counts.types,
+            [
+                "BIGINT",
+                "ARRAY<STRUCT<x VARCHAR> >",
+                "STRUCT<x VARCHAR>",
+                "VARCHAR"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visitor_mut_rewrites_in_place() {
+        struct Rewrite;
+
+        impl VisitorMut for Rewrite {
+            fn visit_table_ref(&mut self, table: &mut TableRef) {
+                if let TableRefKind::Table { name, .. } = &mut table.kind {
+                    for part in &mut name.parts {
+                        part.value = part.value.to_uppercase();
+                    }
+                }
+                walk_table_ref_mut(self, table);
+            }
+
+            fn visit_expr(&mut self, expr: &mut Expr) {
+                walk_expr_mut(self, expr);
+                if let ExprKind::Integer(n) = expr.kind {
+                    expr.kind = ExprKind::Integer(n * 10);
+                }
+            }
+
+            fn visit_data_type(&mut self, data_type: &mut DataTypeSpec) {
+                if data_type.kind == DataTypeKind::Int32 {
+                    data_type.kind = DataTypeKind::Int64;
+                }
+                walk_data_type_mut(self, data_type);
+            }
+        }
+
+        let mut stmt = parse(
+            "SELECT CAST(a AS INT32), 1 FROM t JOIN (SELECT 2 FROM u) AS s ON TRUE \
+             WHERE b IN (SELECT c FROM v LIMIT 3)",
+        );
+        Rewrite.visit_statement(&mut stmt);
+        assert_eq!(
+            stmt.to_string(),
+            "SELECT CAST(a AS BIGINT), 10 FROM T JOIN (SELECT 20 FROM U) AS s ON TRUE \
+             WHERE b IN (SELECT c FROM V LIMIT 30)"
+        );
+    }
+
+    #[test]
+    fn test_walk_binary_chain() {
+        /// Expressions in visiting order, entered with `walk_binary_chain`
+        /// or with a plain `walk_expr`.
+        struct Order {
+            exprs: Vec<*const Expr>,
+            chain: bool,
+        }
+
+        impl Visitor<'_> for Order {
+            fn visit_expr(&mut self, expr: &Expr) {
+                if self.chain {
+                    walk_binary_chain(self, expr, |v, e| v.exprs.push(e));
+                } else {
+                    self.exprs.push(expr);
+                    walk_expr(self, expr);
+                }
+            }
+        }
+
+        let order = |stmt: &Statement, chain| {
+            let mut order = Order {
+                exprs: Vec::new(),
+                chain,
+            };
+            order.visit_statement(stmt);
+            order.exprs
+        };
+        let stmt = parse("SELECT a + b * (c - d - e) - f AND g OR -h || i = j, k");
+        assert_eq!(order(&stmt, true), order(&stmt, false));
+
+        // A chain far longer than recursion allows.
+        let terms: Vec<String> = (0..10_000).map(|i| format!("c{}", i)).collect();
+        let stmt = parse(&format!("SELECT {}", terms.join(" - ")));
+        assert_eq!(order(&stmt, true).len(), 2 * terms.len() - 1);
+    }
+}
@@ -0,0 +1,985 @@
+//! Builders for SQL statements.
+//!
+//! The builders construct AST nodes without spans or boxes, for generating
+//! SQL in code. Names are quoted when they are not plain identifiers, and
+//! values become literals, so a built statement displays as valid SQL
+//! however unusual its names and strings are.
+//!
+//! ```
+//! use vibesql::ast::SortOrder::Asc;
+//! use vibesql::builder::{col, lit, QueryBuilder};
+//!
+//! let query = QueryBuilder::select(["id", "name"])
+//!     .from("users")
+//!     .filter(col("age").gt(lit(21)))
+//!     .order_by("name", Asc)
+//!     .limit(10)
+//!     .build();
+//! assert_eq!(
+//!     query.to_string(),
+//!     "SELECT id, name FROM users WHERE age > 21 ORDER BY name ASC LIMIT 10"
+//! );
+//! ```
+//!
+//! Strings stand for column and table names wherever an expression or
+//! table is expected; string values are written with [`lit`].
+
+use crate::ast::*;
+use crate::error::Span;
+use crate::types::literal;
+use crate::types::value::is_plain_identifier;
+use crate::types::Value;
+
+/// A reference to a column. A dotted name such as `"u.id"` is qualified.
+pub fn col(name: &str) -> ExprBuilder {
+    let mut parts: Vec<Ident> = name.split('.').map(ident).collect();
+    let kind = if parts.len() == 1 {
+        ExprKind::Identifier(parts.remove(0))
+    } else {
+        ExprKind::CompoundIdentifier(parts)
+    };
+    ExprBuilder::new(kind)
+}
+
+/// A literal of a value: `lit(21)`, `lit("Ada")`, `lit(Value::Null)`.
+///
+/// The expression is the one the value's [`Display`](std::fmt::Display)
+/// writes, such as `DATE '2024-01-31'`, `-5`, or
+/// `DATE_FROM_UNIX_DATE(-800000)` for a date no literal writes.
+pub fn lit(value: impl Into<Value>) -> ExprBuilder {
+    let typed = |data_type, value: String| ExprKind::TypedLiteral { data_type, value };
+    let micros = |function: &str, micros: i64| func(function, [lit(micros)]);
+    let kind = match value.into() {
+        Value::Null => ExprKind::Null,
+        Value::Boolean(b) => ExprKind::Boolean(b),
+        // The lexer reads the digits of a negative literal as a positive
+        // number, which i64::MIN is too large for
+        Value::Int64(i64::MIN) => ExprKind::Parenthesized(lit(i64::MIN + 1).minus(lit(1)).boxed()),
+        Value::Int64(n) if n < 0 => return -lit(-n),
+        Value::Int64(n) => ExprKind::Integer(n),
+        Value::Float64(f) if !f.is_finite() => ExprKind::Cast {
+            expr: lit(f.to_string()).boxed(),
+            data_type: DataTypeSpec::new(DataTypeKind::Float64, Span::default()),
+            safe: false,
+        },
+        Value::Float64(f) if f.is_sign_negative() => return -lit(-f),
+        Value::Float64(f) => ExprKind::Float(f),
+        Value::Numeric(n) => typed(TypedLiteralType::Numeric, n.to_string()),
+        Value::String(s) => ExprKind::String(s),
+        Value::Bytes(b) => ExprKind::Bytes(b),
+        Value::Date(days) => match literal::Date::from_days_since_epoch(days) {
+            Some(date) => typed(TypedLiteralType::Date, date.to_string()),
+            None => return func("DATE_FROM_UNIX_DATE", [lit(i64::from(days))]),
+        },
+        Value::Time(n) => match literal::Time::from_micros_since_midnight(n) {
+            Some(time) => typed(TypedLiteralType::Time, time.to_string()),
+            None => return func("TIME", [micros("TIMESTAMP_MICROS", n)]),
+        },
+        Value::Datetime(n) => match literal::Datetime::from_micros_since_epoch(n) {
+            Some(datetime) => typed(TypedLiteralType::Datetime, datetime.to_string()),
+            None => return func("DATETIME", [micros("TIMESTAMP_MICROS", n)]),
+        },
+        Value::Timestamp(n) => match literal::Datetime::from_micros_since_epoch(n) {
+            Some(datetime) => typed(TypedLiteralType::Timestamp, format!("{}+00", datetime)),
+            None => return micros("TIMESTAMP_MICROS", n),
+        },
+        Value::Interval(interval) => {
+            let mut parts = interval.literal_parts().into_iter().map(|(count, unit)| {
+                ExprBuilder::new(ExprKind::Interval {
+                    value: lit(count).boxed(),
+                    unit,
+                })
+            });
+            let first = parts.next().expect("an interval has a literal part");
+            return parts.fold(first, ExprBuilder::plus);
+        }
+        Value::Array(values) => ExprKind::Array {
+            element_type: None,
+            elements: values.into_iter().map(|value| lit(value).boxed()).collect(),
+        },
+        Value::Struct(fields) => ExprKind::Struct {
+            fields: fields
+                .into_iter()
+                .map(|(name, value)| StructField {
+                    name: (!name.is_empty()).then(|| ident(&name)),
+                    value: lit(value).boxed(),
+                })
+                .collect(),
+        },
+        Value::Json(text) => typed(TypedLiteralType::Json, text),
+    };
+    ExprBuilder::new(kind)
+}
+
+/// A `@name` query parameter.
+pub fn param(name: &str) -> ExprBuilder {
+    ExprBuilder::new(ExprKind::Parameter(Parameter::Named(name.to_string())))
+}
+
+/// A call of the function `name`. A dotted name is qualified.
+pub fn func<E: Into<ExprBuilder>>(name: &str, args: impl IntoIterator<Item = E>) -> ExprBuilder {
+    ExprBuilder::new(ExprKind::Function(FunctionCall {
+        name: object_name(name),
+        args: args
+            .into_iter()
+            .map(|arg| FunctionArg::Unnamed(arg.into().boxed()))
+            .collect(),
+        distinct: false,
+        null_treatment: None,
+        order_by: Vec::new(),
+        limit: None,
+    }))
+}
+
+/// `COUNT(*)`.
+pub fn count_star() -> ExprBuilder {
+    let mut call = func::<ExprBuilder>("COUNT", []);
+    if let ExprKind::Function(call) = &mut call.expr.kind {
+        call.args.push(FunctionArg::Star);
+    }
+    call
+}
+
+/// A table to select from, join, or modify. A dotted name is qualified.
+pub fn table(name: &str) -> TableBuilder {
+    TableBuilder {
+        table: TableRef::new(
+            TableRefKind::Table {
+                name: object_name(name),
+                alias: None,
+                hints: Vec::new(),
+                sample: None,
+            },
+            Span::default(),
+        ),
+    }
+}
+
+/// An expression under construction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExprBuilder {
+    expr: Expr,
+}
+
+impl ExprBuilder {
+    fn new(kind: ExprKind) -> Self {
+        Self {
+            expr: Expr::new(kind, Span::default()),
+        }
+    }
+
+    /// The built expression.
+    pub fn build(self) -> Expr {
+        self.expr
+    }
+
+    fn boxed(self) -> Box<Expr> {
+        Box::new(self.expr)
+    }
+
+    fn binary(self, op: BinaryOp, right: impl Into<ExprBuilder>) -> Self {
+        Self::new(ExprKind::BinaryOp {
+            op,
+            left: self.boxed(),
+            right: right.into().boxed(),
+        })
+    }
+
+    /// `self = right`.
+    pub fn eq(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Eq, right)
+    }
+
+    /// `self != right`.
+    pub fn not_eq(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::NotEq, right)
+    }
+
+    /// `self < right`.
+    pub fn lt(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Lt, right)
+    }
+
+    /// `self <= right`.
+    pub fn lt_eq(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::LtEq, right)
+    }
+
+    /// `self > right`.
+    pub fn gt(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Gt, right)
+    }
+
+    /// `self >= right`.
+    pub fn gt_eq(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::GtEq, right)
+    }
+
+    /// `self AND right`.
+    pub fn and(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::And, right)
+    }
+
+    /// `self OR right`.
+    pub fn or(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Or, right)
+    }
+
+    /// `self + right`.
+    pub fn plus(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Plus, right)
+    }
+
+    /// `self - right`.
+    pub fn minus(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Minus, right)
+    }
+
+    /// `self * right`.
+    pub fn multiply(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Multiply, right)
+    }
+
+    /// `self / right`.
+    pub fn divide(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Divide, right)
+    }
+
+    /// `self % right`.
+    pub fn modulo(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Modulo, right)
+    }
+
+    /// `self || right`.
+    pub fn concat(self, right: impl Into<ExprBuilder>) -> Self {
+        self.binary(BinaryOp::Concat, right)
+    }
+
+    /// `self IS NULL`.
+    pub fn is_null(self) -> Self {
+        self.is(false)
+    }
+
+    /// `self IS NOT NULL`.
+    pub fn is_not_null(self) -> Self {
+        self.is(true)
+    }
+
+    fn is(self, negated: bool) -> Self {
+        Self::new(ExprKind::IsExpr {
+            expr: self.boxed(),
+            test: IsTest::Null,
+            negated,
+        })
+    }
+
+    /// `self BETWEEN low AND high`.
+    pub fn between(self, low: impl Into<ExprBuilder>, high: impl Into<ExprBuilder>) -> Self {
+        Self::new(ExprKind::Between {
+            expr: self.boxed(),
+            low: low.into().boxed(),
+            high: high.into().boxed(),
+            negated: false,
+        })
+    }
+
+    /// `self IN (values...)`.
+    pub fn in_list<E: Into<ExprBuilder>>(self, values: impl IntoIterator<Item = E>) -> Self {
+        Self::new(ExprKind::In {
+            expr: self.boxed(),
+            list: InList::Values(values.into_iter().map(|v| v.into().boxed()).collect()),
+            negated: false,
+        })
+    }
+
+    /// `self IN (query)`.
+    pub fn in_subquery(self, query: Query) -> Self {
+        Self::new(ExprKind::InSubquery {
+            expr: self.boxed(),
+            subquery: Box::new(query),
+            negated: false,
+        })
+    }
+
+    /// `self LIKE pattern`.
+    pub fn like(self, pattern: impl Into<ExprBuilder>) -> Self {
+        Self::new(ExprKind::Like {
+            expr: self.boxed(),
+            pattern: pattern.into().boxed(),
+            escape: None,
+            negated: false,
+            case_insensitive: false,
+        })
+    }
+
+    /// A select item naming this expression: `self AS alias`.
+    pub fn alias(self, alias: &str) -> SelectItem {
+        SelectItem::Expr {
+            expr: self.boxed(),
+            alias: Some(ident(alias)),
+        }
+    }
+}
+
+/// `NOT self`.
+impl std::ops::Not for ExprBuilder {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::new(ExprKind::UnaryOp {
+            op: UnaryOp::Not,
+            expr: self.boxed(),
+        })
+    }
+}
+
+/// `-self`.
+impl std::ops::Neg for ExprBuilder {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(ExprKind::UnaryOp {
+            op: UnaryOp::Minus,
+            expr: self.boxed(),
+        })
+    }
+}
+
+/// A column reference, as with [`col`].
+impl From<&str> for ExprBuilder {
+    fn from(name: &str) -> Self {
+        col(name)
+    }
+}
+
+impl From<Expr> for ExprBuilder {
+    fn from(expr: Expr) -> Self {
+        Self { expr }
+    }
+}
+
+impl From<ExprBuilder> for Expr {
+    fn from(builder: ExprBuilder) -> Self {
+        builder.expr
+    }
+}
+
+/// The expression, unnamed.
+impl From<ExprBuilder> for SelectItem {
+    fn from(builder: ExprBuilder) -> Self {
+        SelectItem::Expr {
+            expr: builder.boxed(),
+            alias: None,
+        }
+    }
+}
+
+/// A column, or `*` or `t.*` for all columns.
+impl From<&str> for SelectItem {
+    fn from(name: &str) -> Self {
+        match name.strip_suffix('*') {
+            Some("") => SelectItem::Wildcard,
+            Some(qualifier) if qualifier.ends_with('.') => SelectItem::QualifiedWildcard {
+                qualifier: object_name(&qualifier[..qualifier.len() - 1]),
+            },
+            _ => col(name).into(),
+        }
+    }
+}
+
+/// A table reference under construction, with the joins onto it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableBuilder {
+    table: TableRef,
+}
+
+impl TableBuilder {
+    /// A derived table: `(query) AS alias`.
+    pub fn subquery(query: Query, alias: &str) -> Self {
+        Self {
+            table: TableRef::new(
+                TableRefKind::Subquery {
+                    query: Box::new(query),
+                    alias: Some(Alias::new(ident(alias))),
+                    sample: None,
+                    lateral: false,
+                },
+                Span::default(),
+            ),
+        }
+    }
+
+    /// Name the table: `users AS u`. A join has no alias of its own, so
+    /// this names a joined table only before it is joined.
+    pub fn alias(mut self, name: &str) -> Self {
+        match &mut self.table.kind {
+            TableRefKind::Table { alias, .. } | TableRefKind::Subquery { alias, .. } => {
+                *alias = Some(Alias::new(ident(name)))
+            }
+            _ => {}
+        }
+        self
+    }
+
+    fn joined(
+        self,
+        join_type: JoinType,
+        right: impl Into<TableBuilder>,
+        condition: Option<JoinCondition>,
+    ) -> Self {
+        Self {
+            table: TableRef::new(
+                TableRefKind::Join {
+                    left: Box::new(self.table),
+                    right: Box::new(right.into().table),
+                    join_type,
+                    condition,
+                },
+                Span::default(),
+            ),
+        }
+    }
+
+    /// `self JOIN right ON on`.
+    pub fn join(self, right: impl Into<TableBuilder>, on: impl Into<ExprBuilder>) -> Self {
+        self.joined(JoinType::Inner, right, Some(on_condition(on)))
+    }
+
+    /// `self LEFT JOIN right ON on`.
+    pub fn left_join(self, right: impl Into<TableBuilder>, on: impl Into<ExprBuilder>) -> Self {
+        self.joined(JoinType::Left, right, Some(on_condition(on)))
+    }
+
+    /// `self RIGHT JOIN right ON on`.
+    pub fn right_join(self, right: impl Into<TableBuilder>, on: impl Into<ExprBuilder>) -> Self {
+        self.joined(JoinType::Right, right, Some(on_condition(on)))
+    }
+
+    /// `self FULL JOIN right ON on`.
+    pub fn full_join(self, right: impl Into<TableBuilder>, on: impl Into<ExprBuilder>) -> Self {
+        self.joined(JoinType::Full, right, Some(on_condition(on)))
+    }
+
+    /// `self JOIN right USING (columns...)`.
+    pub fn join_using<'a>(
+        self,
+        right: impl Into<TableBuilder>,
+        columns: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let columns = columns.into_iter().map(ident).collect();
+        self.joined(JoinType::Inner, right, Some(JoinCondition::Using(columns)))
+    }
+
+    /// `self CROSS JOIN right`.
+    pub fn cross_join(self, right: impl Into<TableBuilder>) -> Self {
+        self.joined(JoinType::Cross, right, None)
+    }
+
+    /// The built table reference.
+    pub fn build(self) -> TableRef {
+        self.table
+    }
+}
+
+/// A table, as with [`table`].
+impl From<&str> for TableBuilder {
+    fn from(name: &str) -> Self {
+        table(name)
+    }
+}
+
+/// A SELECT query under construction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryBuilder {
+    select: Select,
+    order_by: Vec<OrderByExpr>,
+    limit: Option<LimitClause>,
+}
+
+impl QueryBuilder {
+    /// `SELECT items...`, each a column name or an expression.
+    pub fn select<I: Into<SelectItem>>(items: impl IntoIterator<Item = I>) -> Self {
+        Self {
+            select: Select {
+                distinct: None,
+                select_as: None,
+                projection: items.into_iter().map(Into::into).collect(),
+                from: None,
+                where_clause: None,
+                group_by: None,
+                having: None,
+                qualify: None,
+                window: Vec::new(),
+                optimizer_hints: Vec::new(),
+                span: Span::default(),
+                id: NodeId::UNASSIGNED,
+            },
+            order_by: Vec::new(),
+            limit: None,
+        }
+    }
+
+    /// `SELECT DISTINCT`.
+    pub fn distinct(mut self) -> Self {
+        self.select.distinct = Some(Distinct::Distinct);
+        self
+    }
+
+    /// Add a table to the FROM clause. Tables added after the first are
+    /// cross joined with a comma.
+    pub fn from(mut self, table: impl Into<TableBuilder>) -> Self {
+        self.select
+            .from
+            .get_or_insert_with(|| FromClause { tables: Vec::new() })
+            .tables
+            .push(table.into().table);
+        self
+    }
+
+    /// Add a WHERE condition, ANDed with any added before.
+    pub fn filter(mut self, condition: impl Into<ExprBuilder>) -> Self {
+        self.select.where_clause = Some(and(self.select.where_clause.take(), condition));
+        self
+    }
+
+    /// `GROUP BY exprs...`.
+    pub fn group_by<E: Into<ExprBuilder>>(mut self, exprs: impl IntoIterator<Item = E>) -> Self {
+        let items = exprs
+            .into_iter()
+            .map(|expr| GroupByItem::Expr(expr.into().boxed()));
+        self.select
+            .group_by
+            .get_or_insert_with(|| GroupByClause { items: Vec::new() })
+            .items
+            .extend(items);
+        self
+    }
+
+    /// Add a HAVING condition, ANDed with any added before.
+    pub fn having(mut self, condition: impl Into<ExprBuilder>) -> Self {
+        self.select.having = Some(and(self.select.having.take(), condition));
+        self
+    }
+
+    /// Add an ORDER BY key.
+    pub fn order_by(mut self, expr: impl Into<ExprBuilder>, order: SortOrder) -> Self {
+        self.order_by.push(OrderByExpr {
+            expr: expr.into().boxed(),
+            order: Some(order),
+            nulls: None,
+        });
+        self
+    }
+
+    /// `LIMIT count`.
+    pub fn limit(mut self, count: i64) -> Self {
+        self.limit_clause().count = Some(lit(count).boxed());
+        self
+    }
+
+    /// `OFFSET offset`.
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.limit_clause().offset = Some(lit(offset).boxed());
+        self
+    }
+
+    fn limit_clause(&mut self) -> &mut LimitClause {
+        self.limit.get_or_insert(LimitClause {
+            count: None,
+            offset: None,
+            with_ties: false,
+            syntax: LimitSyntax::Limit,
+        })
+    }
+
+    /// The built query.
+    pub fn build(self) -> Query {
+        Query {
+            with: None,
+            body: QueryBody::Select(Box::new(self.select)),
+            order_by: self.order_by,
+            limit: self.limit,
+            span: Span::default(),
+            id: NodeId::UNASSIGNED,
+        }
+    }
+
+    /// The built query as a statement.
+    pub fn build_statement(self) -> Statement {
+        Statement::new(
+            StatementKind::Query(Box::new(self.build())),
+            Span::default(),
+        )
+    }
+}
+
+/// An INSERT statement under construction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsertBuilder {
+    insert: InsertStatement,
+}
+
+impl InsertBuilder {
+    /// `INSERT INTO table`, inserting a row of defaults until rows or a
+    /// query are added.
+    pub fn new(table: &str) -> Self {
+        Self {
+            insert: InsertStatement {
+                optimizer_hints: Vec::new(),
+                or_action: None,
+                table: object_name(table),
+                columns: Vec::new(),
+                source: InsertSource::DefaultValues,
+                returning: None,
+            },
+        }
+    }
+
+    /// The columns to insert into.
+    pub fn columns<'a>(mut self, columns: impl IntoIterator<Item = &'a str>) -> Self {
+        self.insert.columns = columns.into_iter().map(ident).collect();
+        self
+    }
+
+    /// Add a row to `VALUES`, replacing any query.
+    pub fn values<E: Into<ExprBuilder>>(mut self, row: impl IntoIterator<Item = E>) -> Self {
+        let row = row.into_iter().map(|value| value.into().boxed()).collect();
+        match &mut self.insert.source {
+            InsertSource::Values(rows) => rows.push(row),
+            source => *source = InsertSource::Values(vec![row]),
+        }
+        self
+    }
+
+    /// Insert the rows of a query, replacing any `VALUES`.
+    pub fn query(mut self, query: Query) -> Self {
+        self.insert.source = InsertSource::Query(Box::new(query));
+        self
+    }
+
+    /// `RETURNING items...`.
+    pub fn returning<I: Into<SelectItem>>(mut self, items: impl IntoIterator<Item = I>) -> Self {
+        self.insert.returning = Some(returning(items));
+        self
+    }
+
+    /// The built statement.
+    pub fn build(self) -> Statement {
+        Statement::new(StatementKind::Insert(self.insert), Span::default())
+    }
+}
+
+/// An UPDATE statement under construction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateBuilder {
+    update: UpdateStatement,
+}
+
+impl UpdateBuilder {
+    /// `UPDATE table`.
+    pub fn new(table: impl Into<TableBuilder>) -> Self {
+        Self {
+            update: UpdateStatement {
+                optimizer_hints: Vec::new(),
+                table: table.into().table,
+                assignments: Vec::new(),
+                from: None,
+                where_clause: None,
+                returning: None,
+            },
+        }
+    }
+
+    /// Add an assignment: `SET column = value`.
+    pub fn set(mut self, column: &str, value: impl Into<ExprBuilder>) -> Self {
+        self.update.assignments.push(Assignment {
+            target: AssignmentTarget::Column(ident(column)),
+            value: value.into().boxed(),
+        });
+        self
+    }
+
+    /// Add a WHERE condition, ANDed with any added before.
+    pub fn filter(mut self, condition: impl Into<ExprBuilder>) -> Self {
+        self.update.where_clause = Some(and(self.update.where_clause.take(), condition));
+        self
+    }
+
+    /// `RETURNING items...`.
+    pub fn returning<I: Into<SelectItem>>(mut self, items: impl IntoIterator<Item = I>) -> Self {
+        self.update.returning = Some(returning(items));
+        self
+    }
+
+    /// The built statement.
+    pub fn build(self) -> Statement {
+        Statement::new(StatementKind::Update(self.update), Span::default())
+    }
+}
+
+/// A DELETE statement under construction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteBuilder {
+    delete: DeleteStatement,
+}
+
+impl DeleteBuilder {
+    /// `DELETE FROM table`.
+    pub fn new(table: &str) -> Self {
+        Self {
+            delete: DeleteStatement {
+                optimizer_hints: Vec::new(),
+                table: object_name(table),
+                hints: Vec::new(),
+                alias: None,
+                where_clause: None,
+                returning: None,
+            },
+        }
+    }
+
+    /// Add a WHERE condition, ANDed with any added before.
+    pub fn filter(mut self, condition: impl Into<ExprBuilder>) -> Self {
+        self.delete.where_clause = Some(and(self.delete.where_clause.take(), condition));
+        self
+    }
+
+    /// `RETURNING items...`.
+    pub fn returning<I: Into<SelectItem>>(mut self, items: impl IntoIterator<Item = I>) -> Self {
+        self.delete.returning = Some(returning(items));
+        self
+    }
+
+    /// The built statement.
+    pub fn build(self) -> Statement {
+        Statement::new(StatementKind::Delete(self.delete), Span::default())
+    }
+}
+
+/// An identifier, quoted unless it can be written without quotes.
+fn ident(name: &str) -> Ident {
+    if is_plain_identifier(name) {
+        Ident::new(name, Span::default())
+    } else {
+        Ident::quoted(name, Span::default())
+    }
+}
+
+fn object_name(name: &str) -> ObjectName {
+    ObjectName::new(name.split('.').map(ident).collect(), Span::default())
+}
+
+fn on_condition(on: impl Into<ExprBuilder>) -> JoinCondition {
+    JoinCondition::On(on.into().boxed())
+}
+
+/// `condition`, ANDed onto `existing` if there is one.
+fn and(existing: Option<Box<Expr>>, condition: impl Into<ExprBuilder>) -> Box<Expr> {
+    match existing {
+        Some(existing) => ExprBuilder::from(*existing).and(condition).boxed(),
+        None => condition.into().boxed(),
+    }
+}
+
+fn returning<I: Into<SelectItem>>(items: impl IntoIterator<Item = I>) -> ReturningClause {
+    ReturningClause {
+        items: items.into_iter().map(Into::into).collect(),
+        with_action: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::Analyzer;
+    use crate::catalog::CatalogBuilder;
+    use crate::parser::Parser;
+    use crate::types::{Numeric, SqlType};
+
+    /// Check `stmt` displays as `expected`, which parses back to it.
+    fn assert_sql(stmt: Statement, expected: &str) {
+        assert_eq!(stmt.to_string(), expected);
+        let reparsed = Parser::new(expected).parse_statement().unwrap();
+        assert_eq!(reparsed.to_string(), expected);
+    }
+
+    #[test]
+    fn test_query_builder() {
+        let query = QueryBuilder::select([
+            SelectItem::from("u.name"),
+            count_star().alias("orders"),
+            func("SUM", [col("o.total").multiply(lit(1.5))]).into(),
+        ])
+        .from(
+            table("users")
+                .alias("u")
+                .left_join(table("orders").alias("o"), col("u.id").eq(col("o.user_id"))),
+        )
+        .filter(col("u.age").gt_eq(lit(21)).or(col("u.admin")))
+        .filter(!col("u.email").is_null())
+        .group_by(["u.name"])
+        .having(count_star().gt(lit(2)))
+        .order_by("orders", SortOrder::Desc)
+        .limit(10)
+        .offset(20)
+        .build_statement();
+        assert_sql(
+            query,
+            "SELECT u.name, COUNT(*) AS orders, SUM(o.total * 1.5) \
+             FROM users AS u LEFT JOIN orders AS o ON u.id = o.user_id \
+             WHERE (u.age >= 21 OR u.admin) AND NOT u.email IS NULL \
+             GROUP BY u.name HAVING COUNT(*) > 2 ORDER BY orders DESC LIMIT 10 OFFSET 20",
+        );
+
+        let query = QueryBuilder::select(["*"])
+            .distinct()
+            .from(table("a").join_using("b", ["id"]).cross_join("c"))
+            .from(TableBuilder::subquery(
+                QueryBuilder::select(["x"]).from("d").build(),
+                "sub",
+            ))
+            .filter(
+                col("a.n")
+                    .plus(lit(1))
+                    .multiply(lit(2))
+                    .between(lit(0), -lit(-3))
+                    .and(col("a.s").like(lit("x%")))
+                    .and(col("a.k").in_list([lit(1), lit(2)])),
+            )
+            .build_statement();
+        assert_sql(
+            query,
+            "SELECT DISTINCT * FROM a JOIN b USING (id) CROSS JOIN c, (SELECT x FROM d) AS sub \
+             WHERE ((a.n + 1) * 2) BETWEEN 0 AND - -3 AND a.s LIKE 'x%' AND a.k IN (1, 2)",
+        );
+    }
+
+    #[test]
+    fn test_names_and_literals() {
+        let query = QueryBuilder::select([SelectItem::from("select"), lit("it's").alias("a b")])
+            .from("my table")
+            .filter(col("x; DROP TABLE t").eq(param("id")))
+            .build_statement();
+        assert_sql(
+            query,
+            "SELECT `select`, 'it\\'s' AS `a b` FROM `my table` \
+             WHERE `x; DROP TABLE t` = @id",
+        );
+
+        let item = SelectItem::from("my table.*");
+        assert_eq!(item.to_string(), "`my table`.*");
+
+        let literals = [
+            (lit(Value::Null), "NULL"),
+            (lit(true), "TRUE"),
+            (lit(-5), "-5"),
+            (lit(i64::MIN), "(-9223372036854775807 - 1)"),
+            (lit(-0.5), "-0.5"),
+            (lit(vec![1u8, 2]), "b'\\x01\\x02'"),
+            (lit(Numeric::parse("1.50").unwrap()), "NUMERIC '1.5'"),
+            (
+                lit(Value::Array(vec![Value::Int64(1), Value::Null])),
+                "[1, NULL]",
+            ),
+        ];
+        for (builder, expected) in literals {
+            let expr = builder.build();
+            assert_eq!(expr.to_string(), expected);
+            assert_eq!(expr.span, Span::default());
+        }
+
+        // Values no literal writes are built as the expression they display
+        // as, and evaluate back to themselves
+        let interval = |months, days, micros| {
+            Value::Interval(crate::types::Interval {
+                months,
+                days,
+                micros,
+            })
+        };
+        let values = [
+            Value::Float64(f64::NEG_INFINITY),
+            Value::Float64(-0.0),
+            Value::Date(-800_000),
+            Value::Time(-1),
+            Value::Time(86_400_000_000),
+            Value::Datetime(i64::MIN),
+            Value::Timestamp(0),
+            interval(0, 0, i64::MIN),
+            interval(13, -2, 1_500),
+            Value::Struct(vec![
+                ("a b".to_string(), Value::Date(0)),
+                (String::new(), Value::Json("[1]".to_string())),
+            ]),
+        ];
+        for value in values {
+            let expr = lit(value.clone()).build();
+            let sql = value.to_string();
+            let parsed = Parser::new(&sql).parse_expression().unwrap();
+            assert_eq!(expr.to_string(), parsed.to_string());
+            let evaluated = crate::eval::evaluate(&expr, &(), &crate::eval::Params::new());
+            match value {
+                Value::Time(micros) => {
+                    let wrapped = Value::Time(micros.rem_euclid(86_400_000_000));
+                    assert_eq!(evaluated.unwrap(), wrapped, "{}", sql);
+                }
+                value => assert_eq!(evaluated.unwrap(), value, "{}", sql),
+            }
+        }
+    }
+
+    #[test]
+    fn test_dml_builders() {
+        assert_sql(
+            InsertBuilder::new("users")
+                .columns(["id", "name"])
+                .values([lit(1), lit("Ada")])
+                .values([lit(2), lit("Grace")])
+                .returning(["id"])
+                .build(),
+            "INSERT INTO users (id, name) VALUES (1, 'Ada'), (2, 'Grace') RETURNING id",
+        );
+        assert_sql(
+            InsertBuilder::new("archive")
+                .query(QueryBuilder::select(["*"]).from("users").build())
+                .build(),
+            "INSERT INTO archive SELECT * FROM users",
+        );
+        assert_sql(
+            UpdateBuilder::new(table("users").alias("u"))
+                .set("name", lit("Ada"))
+                .set("visits", col("visits").plus(lit(1)))
+                .filter(col("id").eq(lit(1)))
+                .build(),
+            "UPDATE users AS u SET name = 'Ada', visits = visits + 1 WHERE id = 1",
+        );
+        assert_sql(
+            DeleteBuilder::new("users")
+                .filter(col("id").in_subquery(QueryBuilder::select(["id"]).from("banned").build()))
+                .build(),
+            "DELETE FROM users WHERE id IN (SELECT id FROM banned)",
+        );
+    }
+
+    #[test]
+    fn test_built_query_analyzes() {
+        let catalog = CatalogBuilder::new()
+            .with_builtins()
+            .add_table("users", |t| {
+                t.primary_key("id", SqlType::Int64)
+                    .column("name", SqlType::Varchar(None))
+            })
+            .build();
+        let query = QueryBuilder::select(["id", "name"])
+            .from("users")
+            .filter(col("id").gt(lit(21)))
+            .build();
+        let mut analyzer = Analyzer::with_catalog(catalog);
+        let analyzed = analyzer.analyze_query_result(&query).unwrap();
+        let names: Vec<&str> = analyzed.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["id", "name"]);
+
+        let bad = QueryBuilder::select(["missing"]).from("users").build();
+        assert!(analyzer.analyze_query_result(&bad).is_err());
+    }
+}
@@ -0,0 +1,1112 @@
+//! Applying DDL statements to a [`MemoryCatalog`].
+//!
+//! [`MemoryCatalog::apply`] executes `CREATE TABLE`, `CREATE VIEW`,
+//! `DROP TABLE`, `DROP VIEW`, `ALTER TABLE`, and `CREATE FUNCTION` against
+//! the catalog and reports what happened, so tools such as migration
+//! runners can log statements that were skipped because of
+//! `IF [NOT] EXISTS`. Created tables keep their primary key, UNIQUE, and
+//! FOREIGN KEY constraints, and their columns keep NOT NULL, defaults, and
+//! `description` options. Renaming a column renames it in the table's
+//! constraints, and dropping one drops the constraints that include it.
+
+use super::{
+    split_table_name, Catalog, ColumnSchema, ForeignKey, FunctionSignature, MemoryCatalog,
+    TableSchema, UniqueConstraint, BUILTIN_SCHEMA,
+};
+use crate::analyzer::{create_function_signature, query_columns};
+use crate::ast::*;
+use crate::error::{Error, ErrorKind, Result, Span};
+use crate::types::SqlType;
+use std::sync::Arc;
+
+/// The effect of applying one DDL statement to a catalog.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplyOutcome {
+    /// A new table, view, or function was created.
+    Created,
+    /// `CREATE OR REPLACE` replaced an existing table or view.
+    Replaced {
+        /// The schema that was replaced.
+        old: TableSchema,
+    },
+    /// `CREATE OR REPLACE FUNCTION` replaced an existing function.
+    ReplacedFunction {
+        /// The signature that was replaced.
+        old: FunctionSignature,
+    },
+    /// `CREATE ... IF NOT EXISTS` found an existing table, view, or
+    /// function and did nothing.
+    SkippedExists,
+    /// One or more tables or views were dropped.
+    Dropped,
+    /// `IF EXISTS` found no table or view and did nothing.
+    SkippedMissing,
+    /// An existing table was altered.
+    Altered {
+        /// The changes made, empty if every action was skipped.
+        changes: Vec<TableChange>,
+    },
+}
+
+/// A single change made by `ALTER TABLE`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableChange {
+    /// A column was added.
+    AddColumn(ColumnSchema),
+    /// A column was dropped.
+    DropColumn(String),
+    /// A column was renamed.
+    RenameColumn { from: String, to: String },
+    /// The table was renamed.
+    RenameTable { from: String, to: String },
+    /// A column's data type was changed.
+    SetDataType {
+        column: String,
+        from: SqlType,
+        to: SqlType,
+    },
+    /// A column was made NOT NULL.
+    SetNotNull(String),
+    /// A column's NOT NULL constraint was removed.
+    DropNotNull(String),
+    /// A column's default value was removed.
+    DropDefault(String),
+}
+
+impl MemoryCatalog {
+    /// Apply a DDL statement to the catalog.
+    ///
+    /// Statements are applied atomically: on error the catalog is left
+    /// unchanged. Table and view DDL and `CREATE FUNCTION` are supported;
+    /// other statements return an [`ErrorKind::UnsupportedFeature`] error.
+    ///
+    /// `CREATE TABLE ... AS SELECT` and `CREATE VIEW` take their columns
+    /// from analyzing the query against the catalog. Views share the table
+    /// namespace and resolve like tables, but `DROP TABLE` and
+    /// `ALTER TABLE` reject them, and `DROP VIEW` rejects tables, with
+    /// [`ErrorKind::WrongObjectType`]. A created function's body is analyzed
+    /// against the catalog first, so later statements can call it with
+    /// argument checking.
+    ///
+    /// ```
+    /// use vibesql::catalog::{ApplyOutcome, MemoryCatalog};
+    /// use vibesql::Parser;
+    ///
+    /// let mut catalog = MemoryCatalog::new();
+    /// let stmts = Parser::new(
+    ///     "CREATE TABLE t (id INT64); CREATE TABLE IF NOT EXISTS t (id INT64)",
+    /// )
+    /// .parse()
+    /// .unwrap();
+    ///
+    /// assert_eq!(catalog.apply(&stmts[0]).unwrap(), ApplyOutcome::Created);
+    /// assert_eq!(catalog.apply(&stmts[1]).unwrap(), ApplyOutcome::SkippedExists);
+    /// ```
+    pub fn apply(&mut self, stmt: &Statement) -> Result<ApplyOutcome> {
+        match &stmt.kind {
+            StatementKind::CreateTable(create) => self.apply_create_table(create, stmt.span),
+            StatementKind::CreateView(create) => self.apply_create_view(create, stmt.span),
+            StatementKind::Drop(drop)
+                if matches!(
+                    drop.object_type,
+                    ObjectType::Table | ObjectType::View | ObjectType::MaterializedView
+                ) =>
+            {
+                self.apply_drop_table(drop, stmt.span)
+            }
+            StatementKind::AlterTable(alter) => self.apply_alter_table(alter, stmt.span),
+            StatementKind::CreateFunction(create) => self.apply_create_function(create, stmt.span),
+            _ => Err(Error::unsupported(
+                "applying this statement to a catalog",
+                stmt.span,
+            )),
+        }
+    }
+
+    fn apply_create_table(
+        &mut self,
+        create: &CreateTableStatement,
+        span: Span,
+    ) -> Result<ApplyOutcome> {
+        let creation = self.plan_create_table(create, &*self, span)?;
+        Ok(self.create(creation))
+    }
+
+    fn apply_create_view(
+        &mut self,
+        create: &CreateViewStatement,
+        span: Span,
+    ) -> Result<ApplyOutcome> {
+        let creation = self.plan_create_view(create, &*self, span)?;
+        Ok(self.create(creation))
+    }
+
+    /// Work out what a CREATE TABLE does to this catalog, resolving its
+    /// query, source table, and column types through `catalog`.
+    pub(super) fn plan_create_table<C: Catalog>(
+        &self,
+        create: &CreateTableStatement,
+        catalog: &C,
+        span: Span,
+    ) -> Result<Creation> {
+        let flags = (create.or_replace, create.if_not_exists);
+        self.plan_create(&create.name, flags, false, span, |table_name| {
+            if let Some(source) = create.like.as_ref().or(create.clone.as_ref()) {
+                source_table(catalog, source, span)
+            } else if let Some(query) = &create.as_query {
+                let mut columns = query_columns(catalog, query, self.identifier_matching())?;
+                let names: Vec<&Ident> = create.columns.iter().map(|c| &c.name).collect();
+                name_columns("table", &create.name, &mut columns, &names, span)?;
+                Ok(TableSchema::new(table_name.value.clone(), columns))
+            } else {
+                table_schema(create, catalog, span)
+            }
+        })
+    }
+
+    /// Work out what a CREATE VIEW does to this catalog, resolving its
+    /// query through `catalog`.
+    pub(super) fn plan_create_view<C: Catalog>(
+        &self,
+        create: &CreateViewStatement,
+        catalog: &C,
+        span: Span,
+    ) -> Result<Creation> {
+        let flags = (create.or_replace, create.if_not_exists);
+        self.plan_create(&create.name, flags, true, span, |table_name| {
+            let mut columns = query_columns(catalog, &create.query, self.identifier_matching())?;
+            let names: Vec<&Ident> = create.columns.iter().collect();
+            name_columns("view", &create.name, &mut columns, &names, span)?;
+            Ok(TableSchema::new(table_name.value.clone(), columns))
+        })
+    }
+
+    /// Work out what creating the table or view `name` does to this
+    /// catalog, given the statement's `(OR REPLACE, IF NOT EXISTS)` flags.
+    /// `build` defines the new table; it is not called when an existing
+    /// one is kept.
+    pub(super) fn plan_create(
+        &self,
+        name: &ObjectName,
+        (or_replace, if_not_exists): (bool, bool),
+        view: bool,
+        span: Span,
+        build: impl FnOnce(&Ident) -> Result<TableSchema>,
+    ) -> Result<Creation> {
+        if or_replace && if_not_exists {
+            return Err(Error::invalid_syntax(
+                "OR REPLACE and IF NOT EXISTS cannot be used together",
+                span,
+            ));
+        }
+
+        let (schema_name, table_name) = split_name(name, span)?;
+        let existing = self.find_table(&schema_name, table_name)?;
+
+        if let Some(keys) = &existing {
+            if if_not_exists {
+                return Ok(Creation::Skipped);
+            }
+            if !or_replace {
+                return Err(Error::with_span(
+                    ErrorKind::TableAlreadyExists(name.to_string()),
+                    span,
+                ));
+            }
+            self.check_object_type(keys, name, view, span)?;
+        }
+
+        let table = build(table_name)?;
+        let keys = match existing {
+            Some(keys) => keys,
+            None => self.new_table_keys(&schema_name, table_name)?,
+        };
+        Ok(Creation::Insert { keys, table, view })
+    }
+
+    /// Carry out a planned CREATE TABLE or CREATE VIEW.
+    pub(super) fn create(&mut self, creation: Creation) -> ApplyOutcome {
+        match creation {
+            Creation::Skipped => ApplyOutcome::SkippedExists,
+            Creation::Insert { keys, table, view } => {
+                if view {
+                    self.views.insert(keys.clone());
+                }
+                self.insert_table(keys, table)
+            }
+        }
+    }
+
+    fn apply_create_function(
+        &mut self,
+        create: &CreateFunctionStatement,
+        span: Span,
+    ) -> Result<ApplyOutcome> {
+        if create.or_replace && create.if_not_exists {
+            return Err(Error::invalid_syntax(
+                "OR REPLACE and IF NOT EXISTS cannot be used together",
+                span,
+            ));
+        }
+
+        let (schema_name, _) = split_name(&create.name, span)?;
+        let schema = self
+            .lookup_key(&self.functions, &schema_name)?
+            .unwrap_or_else(|| schema_name.value.clone());
+        if schema.eq_ignore_ascii_case(BUILTIN_SCHEMA) {
+            return Err(Error::unsupported(
+                format!("creating functions in the {} schema", BUILTIN_SCHEMA),
+                span,
+            ));
+        }
+
+        let sig = create_function_signature(&*self, create, self.identifier_matching())?;
+        let existing = self
+            .functions
+            .get(&schema)
+            .and_then(|functions| functions.get(&sig.name))
+            .and_then(|overloads| overloads.first())
+            .cloned();
+        match existing {
+            Some(_) if create.if_not_exists => Ok(ApplyOutcome::SkippedExists),
+            Some(old) if create.or_replace => {
+                self.add_schema_function(schema, sig);
+                Ok(ApplyOutcome::ReplacedFunction { old })
+            }
+            Some(_) => Err(Error::with_span(
+                ErrorKind::FunctionAlreadyExists(create.name.to_string()),
+                span,
+            )),
+            None => {
+                self.add_schema_function(schema, sig);
+                Ok(ApplyOutcome::Created)
+            }
+        }
+    }
+
+    fn apply_drop_table(&mut self, drop: &DropStatement, span: Span) -> Result<ApplyOutcome> {
+        // Resolve every name before removing anything so that a missing
+        // table leaves the catalog untouched.
+        let view = drop.object_type != ObjectType::Table;
+        let mut found = Vec::new();
+        for name in &drop.names {
+            let (schema_name, table_name) = split_name(name, span)?;
+            match self.find_table(&schema_name, table_name)? {
+                Some(keys) => {
+                    self.check_object_type(&keys, name, view, span)?;
+                    found.push(keys);
+                }
+                None if drop.if_exists => {}
+                None => {
+                    return Err(Error::with_span(
+                        ErrorKind::UndefinedTable(name.to_string()),
+                        span,
+                    ))
+                }
+            }
+        }
+
+        if found.is_empty() {
+            return Ok(ApplyOutcome::SkippedMissing);
+        }
+        for (schema_key, table_key) in found {
+            if let Some(schema) = self.schemas.get_mut(&schema_key) {
+                schema.tables.remove(&table_key);
+            }
+            self.views.remove(&(schema_key, table_key));
+        }
+        Ok(ApplyOutcome::Dropped)
+    }
+
+    fn apply_alter_table(
+        &mut self,
+        alter: &AlterTableStatement,
+        span: Span,
+    ) -> Result<ApplyOutcome> {
+        let (schema_name, table_name) = split_name(&alter.name, span)?;
+        let Some(keys) = self.find_table(&schema_name, table_name)? else {
+            if alter.if_exists {
+                return Ok(ApplyOutcome::SkippedMissing);
+            }
+            return Err(Error::with_span(
+                ErrorKind::UndefinedTable(alter.name.to_string()),
+                span,
+            ));
+        };
+        self.check_object_type(&keys, &alter.name, false, span)?;
+        let (schema_key, table_key) = keys;
+
+        let mut table = TableSchema::clone(&self.schemas[&schema_key].tables[&table_key]);
+        let mut changes = Vec::new();
+
+        match &alter.action {
+            AlterTableAction::AddColumn {
+                if_not_exists,
+                column,
+            } => {
+                if table.has_column(&column.name.value) {
+                    if !if_not_exists {
+                        return Err(Error::with_span(
+                            ErrorKind::DuplicateColumn(column.name.value.clone()),
+                            column.name.span,
+                        ));
+                    }
+                } else {
+                    table.columns.push(column_schema(column, &*self)?);
+                    add_column_constraints(&mut table, column);
+                    let added = table.columns.last().expect("column added above").clone();
+                    changes.push(TableChange::AddColumn(added));
+                }
+            }
+            AlterTableAction::DropColumn { if_exists, column } => {
+                match table.get_column_index(&column.value) {
+                    Some(index) => {
+                        let removed = table.columns.remove(index);
+                        table.drop_constraint_column(&removed.name);
+                        changes.push(TableChange::DropColumn(removed.name));
+                    }
+                    None if *if_exists => {}
+                    None => return Err(undefined_column(column)),
+                }
+            }
+            AlterTableAction::RenameColumn { from, to } => {
+                let index = table
+                    .get_column_index(&from.value)
+                    .ok_or_else(|| undefined_column(from))?;
+                if table.has_column(&to.value) {
+                    return Err(Error::with_span(
+                        ErrorKind::DuplicateColumn(to.value.clone()),
+                        to.span,
+                    ));
+                }
+                let old_name = std::mem::replace(&mut table.columns[index].name, to.value.clone());
+                table.rename_constraint_column(&old_name, &to.value);
+                changes.push(TableChange::RenameColumn {
+                    from: old_name,
+                    to: to.value.clone(),
+                });
+            }
+            AlterTableAction::RenameTable(new_name) => {
+                let Some(to) = new_name.parts.last() else {
+                    return Err(Error::invalid_syntax(
+                        format!("invalid table name '{}'", new_name),
+                        span,
+                    ));
+                };
+                let schema = Ident::quoted(schema_key.clone(), span);
+                if self.find_table(&schema, to)?.is_some() {
+                    return Err(Error::with_span(
+                        ErrorKind::TableAlreadyExists(to.value.clone()),
+                        span,
+                    ));
+                }
+                let to = to.value.clone();
+                let from = std::mem::replace(&mut table.name, to.clone());
+                changes.push(TableChange::RenameTable { from, to });
+            }
+            AlterTableAction::AlterColumn { column, action } => {
+                let index = table
+                    .get_column_index(&column.value)
+                    .ok_or_else(|| undefined_column(column))?;
+                let col = &mut table.columns[index];
+                match action {
+                    AlterColumnAction::SetDataType(spec) => {
+                        let to = spec.to_sql_type(&self.type_registry)?;
+                        let from = std::mem::replace(&mut col.data_type, to.clone());
+                        changes.push(TableChange::SetDataType {
+                            column: col.name.clone(),
+                            from,
+                            to,
+                        });
+                    }
+                    AlterColumnAction::SetNotNull => {
+                        col.nullable = false;
+                        changes.push(TableChange::SetNotNull(col.name.clone()));
+                    }
+                    AlterColumnAction::DropNotNull => {
+                        col.nullable = true;
+                        changes.push(TableChange::DropNotNull(col.name.clone()));
+                    }
+                    AlterColumnAction::DropDefault => {
+                        col.default_value = None;
+                        changes.push(TableChange::DropDefault(col.name.clone()));
+                    }
+                    AlterColumnAction::SetDefault(_) | AlterColumnAction::SetOptions(_) => {
+                        return Err(Error::unsupported(
+                            "this ALTER COLUMN action in apply",
+                            span,
+                        ));
+                    }
+                }
+            }
+            AlterTableAction::AddConstraint(_)
+            | AlterTableAction::DropConstraint { .. }
+            | AlterTableAction::SetOptions(_) => {
+                return Err(Error::unsupported("this ALTER TABLE action in apply", span));
+            }
+        }
+
+        let tables = &mut self
+            .schemas
+            .get_mut(&schema_key)
+            .expect("schema resolved above")
+            .tables;
+        tables.remove(&table_key);
+        tables.insert(table.name.clone(), Arc::new(table));
+
+        Ok(ApplyOutcome::Altered { changes })
+    }
+
+    /// Check whether a name refers to a view created by
+    /// [`MemoryCatalog::apply`].
+    pub fn is_view(&self, name: &[String]) -> bool {
+        let parts: Vec<Ident> = name
+            .iter()
+            .map(|p| Ident::new(p.clone(), Span::default()))
+            .collect();
+        split_table_name(&parts)
+            .and_then(|(schema_name, table_name)| {
+                self.find_table(&schema_name, table_name).ok().flatten()
+            })
+            .is_some_and(|keys| self.views.contains(&keys))
+    }
+
+    /// Fail with [`ErrorKind::WrongObjectType`] unless the table at `keys`
+    /// is a view exactly when `view` is set.
+    fn check_object_type(
+        &self,
+        keys: &(String, String),
+        name: &ObjectName,
+        view: bool,
+        span: Span,
+    ) -> Result<()> {
+        if self.views.contains(keys) == view {
+            return Ok(());
+        }
+        let expected = if view { "view" } else { "table" };
+        Err(Error::with_span(
+            ErrorKind::WrongObjectType {
+                name: name.to_string(),
+                expected,
+            },
+            span,
+        ))
+    }
+
+    /// The keys a new table is stored under, reusing the stored name of an
+    /// existing schema.
+    fn new_table_keys(&self, schema_name: &Ident, table_name: &Ident) -> Result<(String, String)> {
+        let schema_key = self
+            .lookup_key(&self.schemas, schema_name)?
+            .unwrap_or_else(|| schema_name.value.clone());
+        Ok((schema_key, table_name.value.clone()))
+    }
+
+    /// Store a table under its keys, reporting whether it replaced another.
+    fn insert_table(&mut self, keys: (String, String), mut table: TableSchema) -> ApplyOutcome {
+        let (schema_key, table_key) = keys;
+        table.name = table_key.clone();
+        match self
+            .add_schema(schema_key)
+            .tables
+            .insert(table_key, Arc::new(table))
+        {
+            Some(old) => ApplyOutcome::Replaced {
+                old: Arc::unwrap_or_clone(old),
+            },
+            None => ApplyOutcome::Created,
+        }
+    }
+
+    /// Find the stored schema and table keys for a table, failing if
+    /// either name is ambiguous.
+    fn find_table(
+        &self,
+        schema_name: &Ident,
+        table_name: &Ident,
+    ) -> Result<Option<(String, String)>> {
+        let Some(schema_key) = self.lookup_key(&self.schemas, schema_name)? else {
+            return Ok(None);
+        };
+        let table_key = self.lookup_key(&self.schemas[&schema_key].tables, table_name)?;
+        Ok(table_key.map(|table_key| (schema_key, table_key)))
+    }
+}
+
+/// What a CREATE TABLE or CREATE VIEW does, worked out before the catalog
+/// changes.
+pub(super) enum Creation {
+    /// `IF NOT EXISTS` found an existing table or view.
+    Skipped,
+    /// Store the table under `keys`, marking it as a view if `view` is set.
+    Insert {
+        keys: (String, String),
+        table: TableSchema,
+        view: bool,
+    },
+}
+
+/// Rename the columns a query defines after an explicit column list, if
+/// any, and check that no two columns share a name.
+fn name_columns(
+    kind: &str,
+    name: &ObjectName,
+    columns: &mut [ColumnSchema],
+    names: &[&Ident],
+    span: Span,
+) -> Result<()> {
+    if !names.is_empty() {
+        if names.len() != columns.len() {
+            return Err(Error::invalid_syntax(
+                format!(
+                    "{} '{}' has {} columns but {} column names",
+                    kind,
+                    name,
+                    columns.len(),
+                    names.len()
+                ),
+                span,
+            ));
+        }
+        for (column, name) in columns.iter_mut().zip(names) {
+            column.name = name.value.clone();
+        }
+    }
+    for (i, column) in columns.iter().enumerate() {
+        if columns[..i]
+            .iter()
+            .any(|c| c.name.eq_ignore_ascii_case(&column.name))
+        {
+            return Err(Error::with_span(
+                ErrorKind::DuplicateColumn(column.name.clone()),
+                span,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the source of `CREATE TABLE ... LIKE` or `CLONE`.
+fn source_table<C: Catalog>(catalog: &C, name: &ObjectName, span: Span) -> Result<TableSchema> {
+    catalog
+        .resolve_table_ident(&name.parts)?
+        .map(Arc::unwrap_or_clone)
+        .ok_or_else(|| Error::with_span(ErrorKind::UndefinedTable(name.to_string()), span))
+}
+
+fn split_name(name: &ObjectName, span: Span) -> Result<(Ident, &Ident)> {
+    split_table_name(&name.parts)
+        .ok_or_else(|| Error::invalid_syntax(format!("invalid table name '{}'", name), span))
+}
+
+fn undefined_column(column: &Ident) -> Error {
+    Error::with_span(
+        ErrorKind::UndefinedColumn(column.value.clone()),
+        column.span,
+    )
+}
+
+/// Build the schema for a `CREATE TABLE` with an explicit definition.
+///
+/// Column constraints come first, so a table-level PRIMARY KEY extends a
+/// column-level one. Constrained columns must exist; referenced tables are
+/// not checked, so tables can be created in any order. Custom types
+/// resolve through `catalog`.
+pub(super) fn table_schema<C: Catalog + ?Sized>(
+    create: &CreateTableStatement,
+    catalog: &C,
+    span: Span,
+) -> Result<TableSchema> {
+    let mut table = TableSchema::new(
+        create.name.to_string(),
+        Vec::with_capacity(create.columns.len()),
+    );
+    for def in &create.columns {
+        if table.has_column(&def.name.value) {
+            return Err(Error::with_span(
+                ErrorKind::DuplicateColumn(def.name.value.clone()),
+                span,
+            ));
+        }
+        table.columns.push(column_schema(def, catalog)?);
+        add_column_constraints(&mut table, def);
+    }
+
+    for constraint in &create.constraints {
+        match constraint {
+            TableConstraint::PrimaryKey { columns, .. } => {
+                let mut primary_key = table.primary_key().to_vec();
+                for key in columns {
+                    let name = existing_column(&table, &key.column)?;
+                    if !primary_key.iter().any(|k| k.eq_ignore_ascii_case(&name)) {
+                        primary_key.push(name);
+                    }
+                }
+                table.set_primary_key(primary_key);
+            }
+            TableConstraint::Unique { name, columns } => {
+                let columns = existing_columns(&table, columns)?;
+                let mut unique = UniqueConstraint::new(columns);
+                unique.name = name.as_ref().map(|n| n.value.clone());
+                table = table.with_unique(unique);
+            }
+            TableConstraint::ForeignKey {
+                name,
+                columns,
+                references_table,
+                references_columns,
+                on_delete,
+                on_update,
+            } => {
+                let foreign_key = ForeignKey {
+                    name: name.as_ref().map(|n| n.value.clone()),
+                    columns: existing_columns(&table, columns)?,
+                    referenced_table: name_parts(references_table),
+                    referenced_columns: references_columns
+                        .iter()
+                        .map(|c| c.value.clone())
+                        .collect(),
+                    on_delete: *on_delete,
+                    on_update: *on_update,
+                };
+                table = table.with_foreign_key(foreign_key);
+            }
+            TableConstraint::Check { .. } => {}
+        }
+    }
+    Ok(table)
+}
+
+/// Record the PRIMARY KEY, UNIQUE, and REFERENCES constraints of a column
+/// already added to `table`.
+fn add_column_constraints(table: &mut TableSchema, def: &ColumnDef) {
+    let column = def.name.value.clone();
+    for constraint in &def.constraints {
+        match constraint {
+            ColumnConstraint::PrimaryKey => {
+                let mut primary_key = table.primary_key().to_vec();
+                primary_key.push(column.clone());
+                table.set_primary_key(primary_key);
+            }
+            ColumnConstraint::Unique => {
+                table
+                    .unique_constraints
+                    .push(UniqueConstraint::new([column.clone()]));
+            }
+            ColumnConstraint::References {
+                table: references_table,
+                columns,
+                on_delete,
+                on_update,
+            } => table.foreign_keys.push(ForeignKey {
+                name: None,
+                columns: vec![column.clone()],
+                referenced_table: name_parts(references_table),
+                referenced_columns: columns.iter().map(|c| c.value.clone()).collect(),
+                on_delete: *on_delete,
+                on_update: *on_update,
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// The stored name of a constrained column.
+fn existing_column(table: &TableSchema, column: &Ident) -> Result<String> {
+    table
+        .get_column(&column.value)
+        .map(|c| c.name.clone())
+        .ok_or_else(|| undefined_column(column))
+}
+
+fn existing_columns(table: &TableSchema, columns: &[Ident]) -> Result<Vec<String>> {
+    columns.iter().map(|c| existing_column(table, c)).collect()
+}
+
+fn name_parts(name: &ObjectName) -> Vec<String> {
+    name.parts.iter().map(|p| p.value.clone()).collect()
+}
+
+fn column_schema<C: Catalog + ?Sized>(def: &ColumnDef, catalog: &C) -> Result<ColumnSchema> {
+    let data_type = match &def.data_type {
+        Some(spec) => spec.to_sql_type_with(&mut |name| catalog.resolve_type(name))?,
+        None => SqlType::Unknown,
+    };
+    let mut column = ColumnSchema::new(def.name.value.clone(), data_type);
+    if def.constraints.contains(&ColumnConstraint::NotNull) {
+        column = column.not_null();
+    }
+    for constraint in &def.constraints {
+        if let ColumnConstraint::Default(expr) = constraint {
+            column = column.with_default(expr.to_string());
+        }
+    }
+    for option in &def.options {
+        if let ExprKind::String(description) = &option.value.kind {
+            if option.name.value.eq_ignore_ascii_case("description") {
+                column = column.with_description(description.clone());
+            }
+        }
+    }
+    Ok(column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Catalog;
+    use crate::parser::Parser;
+
+    fn parse(sql: &str) -> Statement {
+        Parser::new(sql).parse().unwrap().remove(0)
+    }
+
+    fn apply(catalog: &mut MemoryCatalog, sql: &str) -> Result<ApplyOutcome> {
+        catalog.apply(&parse(sql))
+    }
+
+    fn catalog_with_t() -> MemoryCatalog {
+        let mut catalog = MemoryCatalog::new();
+        apply(
+            &mut catalog,
+            "CREATE TABLE t (id INT64 NOT NULL, name STRING)",
+        )
+        .unwrap();
+        catalog
+    }
+
+    fn table(catalog: &MemoryCatalog, name: &str) -> Option<Arc<TableSchema>> {
+        catalog.resolve_table(&[name.to_string()]).unwrap()
+    }
+
+    #[test]
+    fn test_create_flag_combinations() {
+        let create = "CREATE TABLE t (x INT64)";
+        let if_not_exists = "CREATE TABLE IF NOT EXISTS t (x INT64)";
+        let or_replace = "CREATE OR REPLACE TABLE t (x INT64)";
+
+        // Missing table: every form creates it.
+        for sql in [create, if_not_exists, or_replace] {
+            let mut catalog = MemoryCatalog::new();
+            assert_eq!(apply(&mut catalog, sql).unwrap(), ApplyOutcome::Created);
+            assert_eq!(table(&catalog, "t").unwrap().column_names(), vec!["x"]);
+        }
+
+        // Existing table, plain CREATE: error, schema untouched.
+        let mut catalog = catalog_with_t();
+        let err = apply(&mut catalog, create).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::TableAlreadyExists(name) if name == "t"));
+        assert_eq!(table(&catalog, "t").unwrap().columns.len(), 2);
+
+        // Existing table, IF NOT EXISTS: skipped, schema untouched.
+        let mut catalog = catalog_with_t();
+        assert_eq!(
+            apply(&mut catalog, if_not_exists).unwrap(),
+            ApplyOutcome::SkippedExists
+        );
+        assert_eq!(table(&catalog, "t").unwrap().columns.len(), 2);
+
+        // Existing table, OR REPLACE: replaced, old schema reported.
+        let mut catalog = catalog_with_t();
+        let old = table(&catalog, "t").unwrap();
+        assert_eq!(
+            apply(&mut catalog, or_replace).unwrap(),
+            ApplyOutcome::Replaced {
+                old: (*old).clone()
+            }
+        );
+        assert_eq!(table(&catalog, "t").unwrap().column_names(), vec!["x"]);
+    }
+
+    #[test]
+    fn test_or_replace_with_if_not_exists_is_rejected() {
+        let err = Parser::new("CREATE OR REPLACE TABLE IF NOT EXISTS t (x INT64)")
+            .parse()
+            .unwrap_err();
+        assert!(err.to_string().contains("OR REPLACE and IF NOT EXISTS"));
+
+        // Hand-built statements that skipped the parser are rejected too,
+        // whether or not the table exists.
+        let mut stmt = parse("CREATE OR REPLACE TABLE t (x INT64)");
+        if let StatementKind::CreateTable(create) = &mut stmt.kind {
+            create.if_not_exists = true;
+        }
+        for mut catalog in [MemoryCatalog::new(), catalog_with_t()] {
+            assert!(catalog.apply(&stmt).is_err());
+        }
+    }
+
+    #[test]
+    fn test_drop_flag_combinations() {
+        let mut catalog = catalog_with_t();
+        assert_eq!(
+            apply(&mut catalog, "DROP TABLE t").unwrap(),
+            ApplyOutcome::Dropped
+        );
+        assert!(table(&catalog, "t").is_none());
+
+        let mut catalog = MemoryCatalog::new();
+        let err = apply(&mut catalog, "DROP TABLE t").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UndefinedTable(_)));
+
+        let mut catalog = catalog_with_t();
+        assert_eq!(
+            apply(&mut catalog, "DROP TABLE IF EXISTS t").unwrap(),
+            ApplyOutcome::Dropped
+        );
+
+        let mut catalog = MemoryCatalog::new();
+        assert_eq!(
+            apply(&mut catalog, "DROP TABLE IF EXISTS t").unwrap(),
+            ApplyOutcome::SkippedMissing
+        );
+
+        // A missing name without IF EXISTS leaves the others in place.
+        let mut catalog = catalog_with_t();
+        assert!(apply(&mut catalog, "DROP TABLE t, missing").is_err());
+        assert!(table(&catalog, "t").is_some());
+    }
+
+    #[test]
+    fn test_alter_flag_combinations() {
+        let mut catalog = catalog_with_t();
+        let outcome = apply(&mut catalog, "ALTER TABLE t ADD COLUMN age INT64").unwrap();
+        assert_eq!(
+            outcome,
+            ApplyOutcome::Altered {
+                changes: vec![TableChange::AddColumn(ColumnSchema::new(
+                    "age",
+                    SqlType::Int64
+                ))],
+            }
+        );
+
+        let mut catalog = MemoryCatalog::new();
+        let err = apply(&mut catalog, "ALTER TABLE t ADD COLUMN age INT64").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UndefinedTable(_)));
+
+        let mut catalog = catalog_with_t();
+        let outcome = apply(&mut catalog, "ALTER TABLE IF EXISTS t DROP COLUMN name").unwrap();
+        assert_eq!(
+            outcome,
+            ApplyOutcome::Altered {
+                changes: vec![TableChange::DropColumn("name".to_string())],
+            }
+        );
+
+        let mut catalog = MemoryCatalog::new();
+        assert_eq!(
+            apply(&mut catalog, "ALTER TABLE IF EXISTS t DROP COLUMN name").unwrap(),
+            ApplyOutcome::SkippedMissing
+        );
+    }
+
+    #[test]
+    fn test_alter_column_changes() {
+        let mut catalog = catalog_with_t();
+        assert_eq!(
+            apply(
+                &mut catalog,
+                "ALTER TABLE t ADD COLUMN IF NOT EXISTS id INT64"
+            )
+            .unwrap(),
+            ApplyOutcome::Altered { changes: vec![] }
+        );
+        assert!(apply(&mut catalog, "ALTER TABLE t ADD COLUMN id INT64").is_err());
+
+        apply(&mut catalog, "ALTER TABLE t RENAME COLUMN name TO label").unwrap();
+        apply(&mut catalog, "ALTER TABLE t ALTER COLUMN id DROP NOT NULL").unwrap();
+        let outcome = apply(&mut catalog, "ALTER TABLE t RENAME TO items").unwrap();
+        assert_eq!(
+            outcome,
+            ApplyOutcome::Altered {
+                changes: vec![TableChange::RenameTable {
+                    from: "t".to_string(),
+                    to: "items".to_string(),
+                }],
+            }
+        );
+
+        assert!(table(&catalog, "t").is_none());
+        let items = table(&catalog, "items").unwrap();
+        assert_eq!(items.column_names(), vec!["id", "label"]);
+        assert!(items.get_column("id").unwrap().nullable);
+    }
+
+    #[test]
+    fn test_create_table_constraints() {
+        let mut catalog = MemoryCatalog::new();
+        apply(
+            &mut catalog,
+            "CREATE TABLE lines (
+                line INT32,
+                order_id INT64 REFERENCES orders (id) ON DELETE CASCADE,
+                sku STRING UNIQUE,
+                status STRING NOT NULL DEFAULT 'new',
+                PRIMARY KEY (order_id, line),
+                CONSTRAINT one_sku UNIQUE (order_id, sku)
+            )",
+        )
+        .unwrap();
+
+        let lines = table(&catalog, "lines").unwrap();
+        assert_eq!(lines.primary_key(), ["order_id", "line"]);
+        assert!(lines.get_column("line").unwrap().is_primary_key);
+        assert!(!lines.get_column("line").unwrap().nullable);
+        assert_eq!(
+            lines.get_column("status").unwrap().default_value.as_deref(),
+            Some("'new'")
+        );
+        let unique: Vec<_> = lines
+            .unique_constraints()
+            .iter()
+            .map(|u| (u.name.as_deref(), u.columns.join(",")))
+            .collect();
+        assert_eq!(
+            unique,
+            [
+                (None, "sku".to_string()),
+                (Some("one_sku"), "order_id,sku".to_string())
+            ]
+        );
+        let fk = &lines.foreign_keys()[0];
+        assert_eq!(fk.columns, ["order_id"]);
+        assert_eq!(fk.referenced_table, ["orders"]);
+        assert_eq!(fk.on_delete, Some(ReferentialAction::Cascade));
+
+        // A NOT NULL column with a default may be omitted from INSERT.
+        let insert = parse("INSERT INTO lines (line, order_id) VALUES (1, 2)");
+        crate::Analyzer::with_catalog(catalog.clone())
+            .analyze(&insert)
+            .unwrap();
+
+        // Constraints follow renamed columns and go away with dropped ones.
+        apply(&mut catalog, "ALTER TABLE lines RENAME COLUMN sku TO code").unwrap();
+        apply(&mut catalog, "ALTER TABLE lines DROP COLUMN line").unwrap();
+        let lines = table(&catalog, "lines").unwrap();
+        assert!(lines.primary_key().is_empty());
+        assert!(!lines.get_column("order_id").unwrap().is_primary_key);
+        assert_eq!(lines.unique_constraints()[1].columns, ["order_id", "code"]);
+
+        for sql in [
+            "CREATE TABLE bad (x INT64, PRIMARY KEY (y))",
+            "CREATE TABLE bad (x INT64, UNIQUE (x, y))",
+            "CREATE TABLE bad (x INT64, FOREIGN KEY (y) REFERENCES orders (id))",
+        ] {
+            let err = apply(&mut catalog, sql).unwrap_err();
+            assert!(
+                matches!(&err.kind, ErrorKind::UndefinedColumn(name) if name == "y"),
+                "{}: {:?}",
+                sql,
+                err
+            );
+            assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("y"));
+        }
+        assert!(table(&catalog, "bad").is_none());
+    }
+
+    #[test]
+    fn test_create_table_as_select() {
+        let mut catalog = catalog_with_t();
+        assert_eq!(
+            apply(
+                &mut catalog,
+                "CREATE TABLE named AS SELECT id, name AS label, 1 AS one FROM t"
+            )
+            .unwrap(),
+            ApplyOutcome::Created
+        );
+        let named = table(&catalog, "named").unwrap();
+        assert_eq!(named.column_names(), vec!["id", "label", "one"]);
+        assert_eq!(named.columns[1].data_type, SqlType::Varchar(None));
+        assert!(!named.columns[0].nullable);
+        assert!(named.columns[1].nullable);
+        assert!(!catalog.is_view(&["named".to_string()]));
+
+        let err = apply(&mut catalog, "CREATE TABLE bad AS SELECT missing FROM t").unwrap_err();
+        assert!(err.to_string().contains("missing"), "{}", err);
+        assert!(table(&catalog, "bad").is_none());
+
+        apply(
+            &mut catalog,
+            "CREATE TABLE renamed (a, b) AS SELECT id, name FROM t",
+        )
+        .unwrap();
+        let renamed = table(&catalog, "renamed").unwrap();
+        assert_eq!(renamed.column_names(), vec!["a", "b"]);
+        let err = apply(
+            &mut catalog,
+            "CREATE TABLE bad (a) AS SELECT id, name FROM t",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("2 columns but 1 column names"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_views() {
+        let mut catalog = catalog_with_t();
+        assert_eq!(
+            apply(&mut catalog, "CREATE VIEW v (key) AS SELECT id FROM t").unwrap(),
+            ApplyOutcome::Created
+        );
+        assert_eq!(table(&catalog, "v").unwrap().column_names(), vec!["key"]);
+        assert!(catalog.is_view(&["V".to_string()]));
+
+        assert!(matches!(
+            apply(&mut catalog, "CREATE VIEW v AS SELECT 1 AS x")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::TableAlreadyExists(_)
+        ));
+        assert_eq!(
+            apply(&mut catalog, "CREATE VIEW IF NOT EXISTS v AS SELECT 1 AS x").unwrap(),
+            ApplyOutcome::SkippedExists
+        );
+        let old = table(&catalog, "v").unwrap();
+        assert_eq!(
+            apply(
+                &mut catalog,
+                "CREATE OR REPLACE VIEW v AS SELECT name FROM t"
+            )
+            .unwrap(),
+            ApplyOutcome::Replaced {
+                old: (*old).clone()
+            }
+        );
+        assert_eq!(table(&catalog, "v").unwrap().column_names(), vec!["name"]);
+
+        // Views and tables are not interchangeable
+        for sql in [
+            "DROP TABLE v",
+            "ALTER TABLE v ADD COLUMN z INT64",
+            "CREATE OR REPLACE TABLE v (z INT64)",
+            "DROP VIEW t",
+            "CREATE OR REPLACE VIEW t AS SELECT 1 AS x",
+        ] {
+            let err = apply(&mut catalog, sql).unwrap_err();
+            assert!(
+                matches!(err.kind(), ErrorKind::WrongObjectType { .. }),
+                "{}: {}",
+                sql,
+                err
+            );
+        }
+        assert!(apply(&mut catalog, "CREATE VIEW w (a, b) AS SELECT id FROM t").is_err());
+        assert!(apply(&mut catalog, "CREATE VIEW w AS SELECT id, id FROM t").is_err());
+
+        assert_eq!(
+            apply(&mut catalog, "DROP VIEW v").unwrap(),
+            ApplyOutcome::Dropped
+        );
+        assert!(table(&catalog, "v").is_none());
+        assert!(!catalog.is_view(&["v".to_string()]));
+        assert_eq!(
+            apply(&mut catalog, "DROP VIEW IF EXISTS v").unwrap(),
+            ApplyOutcome::SkippedMissing
+        );
+    }
+}
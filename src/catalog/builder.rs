@@ -5,8 +5,14 @@
 //! - Custom tables and schemas
 //! - Custom type aliases
 //! - Built-in function selection
-
-use super::{ColumnSchema, FunctionSignature, MemoryCatalog, TableSchema, TypeRegistry};
+//! - Function packs
+
+use super::{
+    ColumnSchema, ForeignKey, FunctionPack, FunctionSignature, MemoryCatalog, TableSchema,
+    TypeRegistry, UniqueConstraint,
+};
+use crate::ast::IdentifierMatching;
+use crate::error::Result;
 use crate::types::SqlType;
 
 /// Builder for creating customized `MemoryCatalog` instances.
@@ -22,7 +28,7 @@ use crate::types::SqlType;
 ///     .add_scalar_function("MY_FUNC", SqlType::Int64)
 ///     .add_table("users", |t| {
 ///         t.column("id", SqlType::Int64)
-///          .column("name", SqlType::Varchar)
+///          .column("name", SqlType::Varchar(None))
 ///     })
 ///     .build();
 /// ```
@@ -49,6 +55,12 @@ impl CatalogBuilder {
         self
     }
 
+    /// Set the policy used to match schema and table names.
+    pub fn with_identifier_matching(mut self, matching: IdentifierMatching) -> Self {
+        self.catalog.set_identifier_matching(matching);
+        self
+    }
+
     /// Add a custom type alias.
     ///
     /// # Example
@@ -67,6 +79,29 @@ impl CatalogBuilder {
         self
     }
 
+    /// Register a function pack and its type aliases.
+    ///
+    /// Fails if another pack already defines one of the pack's functions
+    /// with a different signature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vibesql::catalog::{CatalogBuilder, StatsPack};
+    ///
+    /// let catalog = CatalogBuilder::new()
+    ///     .with_pack(&StatsPack::new())?
+    ///     .build();
+    /// # Ok::<(), vibesql::Error>(())
+    /// ```
+    pub fn with_pack(mut self, pack: &dyn FunctionPack) -> Result<Self> {
+        self.catalog.register_pack(pack)?;
+        for (alias, sql_type) in pack.type_aliases() {
+            self.type_registry.add_alias(alias, sql_type);
+        }
+        Ok(self)
+    }
+
     /// Add a scalar function.
     ///
     /// # Example
@@ -77,7 +112,7 @@ impl CatalogBuilder {
     ///
     /// let catalog = CatalogBuilder::new()
     ///     .add_scalar_function("HASH", SqlType::Int64)
-    ///     .add_scalar_function("ENCRYPT", SqlType::Varbinary)
+    ///     .add_scalar_function("ENCRYPT", SqlType::Varbinary(None))
     ///     .build();
     /// ```
     pub fn add_scalar_function(mut self, name: impl Into<String>, return_type: SqlType) -> Self {
@@ -131,7 +166,11 @@ impl CatalogBuilder {
     /// use vibesql::types::SqlType;
     ///
     /// let catalog = CatalogBuilder::new()
-    ///     .add_function(FunctionSignature::scalar("MY_FUNC", SqlType::Varchar))
+    ///     .add_function(
+    ///         FunctionSignature::scalar("MY_SUBSTR", SqlType::Varchar(None))
+    ///             .args([SqlType::Varchar(None), SqlType::Int64])
+    ///             .optional_arg(SqlType::Int64),
+    ///     )
     ///     .build();
     /// ```
     pub fn add_function(mut self, signature: FunctionSignature) -> Self {
@@ -139,6 +178,76 @@ impl CatalogBuilder {
         self
     }
 
+    /// Add an overload of a function.
+    ///
+    /// Calls are matched against the overloads in the order they were added,
+    /// preferring the one whose parameter types match the arguments exactly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vibesql::catalog::{CatalogBuilder, FunctionSignature};
+    /// use vibesql::types::SqlType;
+    ///
+    /// let catalog = CatalogBuilder::new()
+    ///     .add_overload(FunctionSignature::scalar("MY_ABS", SqlType::Int64).args([SqlType::Int64]))
+    ///     .add_overload(
+    ///         FunctionSignature::scalar("MY_ABS", SqlType::Float64).args([SqlType::Float64]),
+    ///     )
+    ///     .build();
+    /// ```
+    pub fn add_overload(mut self, signature: FunctionSignature) -> Self {
+        self.catalog.add_overload(signature);
+        self
+    }
+
+    /// Add a function to a schema, called as `schema.name(...)` or, when
+    /// the schema is on the function search path, by name alone.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vibesql::catalog::{CatalogBuilder, FunctionSignature};
+    /// use vibesql::types::SqlType;
+    ///
+    /// let catalog = CatalogBuilder::new()
+    ///     .with_builtins()
+    ///     .add_schema_function("geo", FunctionSignature::scalar("DISTANCE", SqlType::Float64))
+    ///     .with_function_search_path(["default", "geo", "builtin"])
+    ///     .build();
+    /// ```
+    pub fn add_schema_function(
+        mut self,
+        schema: impl Into<String>,
+        signature: FunctionSignature,
+    ) -> Self {
+        self.catalog.add_schema_function(schema, signature);
+        self
+    }
+
+    /// Add an overload of a function in a schema.
+    pub fn add_schema_overload(
+        mut self,
+        schema: impl Into<String>,
+        signature: FunctionSignature,
+    ) -> Self {
+        self.catalog.add_schema_overload(schema, signature);
+        self
+    }
+
+    /// Set the schemas searched, in order, for unqualified function names.
+    ///
+    /// The default path is `default` followed by
+    /// [`BUILTIN_SCHEMA`](super::BUILTIN_SCHEMA).
+    pub fn with_function_search_path<I, S>(mut self, schemas: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.catalog.set_function_search_path(schemas);
+        self
+    }
+
     /// Add a table using a builder closure.
     ///
     /// # Example
@@ -150,8 +259,8 @@ impl CatalogBuilder {
     /// let catalog = CatalogBuilder::new()
     ///     .add_table("users", |t| {
     ///         t.column("id", SqlType::Int64)
-    ///          .column("name", SqlType::Varchar)
-    ///          .column("email", SqlType::Varchar)
+    ///          .column("name", SqlType::Varchar(None))
+    ///          .column("email", SqlType::Varchar(None))
     ///     })
     ///     .build();
     /// ```
@@ -189,18 +298,21 @@ impl CatalogBuilder {
     }
 
     /// Build the catalog.
-    pub fn build(mut self) -> MemoryCatalog {
-        if self.include_builtins {
-            self.catalog.register_builtins();
-        }
-        self.catalog
+    ///
+    /// Functions added to the default schema, directly or through a pack,
+    /// shadow built-ins with the same name for unqualified calls.
+    pub fn build(self) -> MemoryCatalog {
+        self.build_with_registry().0
     }
 
     /// Build the catalog and return it along with the type registry.
+    ///
+    /// The catalog resolves custom types through a copy of the registry.
     pub fn build_with_registry(mut self) -> (MemoryCatalog, TypeRegistry) {
         if self.include_builtins {
             self.catalog.register_builtins();
         }
+        self.catalog.type_registry = self.type_registry.clone();
         (self.catalog, self.type_registry)
     }
 }
@@ -210,6 +322,8 @@ impl CatalogBuilder {
 pub struct TableBuilder {
     name: String,
     columns: Vec<ColumnSchema>,
+    unique_constraints: Vec<UniqueConstraint>,
+    foreign_keys: Vec<ForeignKey>,
 }
 
 impl TableBuilder {
@@ -218,6 +332,8 @@ impl TableBuilder {
         Self {
             name: name.into(),
             columns: Vec::new(),
+            unique_constraints: Vec::new(),
+            foreign_keys: Vec::new(),
         }
     }
 
@@ -234,22 +350,91 @@ impl TableBuilder {
         self
     }
 
+    /// Add a column with a default value, given as SQL text.
+    pub fn column_with_default(
+        mut self,
+        name: impl Into<String>,
+        data_type: SqlType,
+        default: impl Into<String>,
+    ) -> Self {
+        self.columns
+            .push(ColumnSchema::new(name, data_type).with_default(default));
+        self
+    }
+
     /// Add a primary key column.
+    ///
+    /// Calling this for several columns makes a composite primary key in
+    /// call order.
     pub fn primary_key(mut self, name: impl Into<String>, data_type: SqlType) -> Self {
         self.columns
             .push(ColumnSchema::new(name, data_type).primary_key());
         self
     }
 
+    /// Add a struct-typed column with the given fields.
+    ///
+    /// ```
+    /// use vibesql::catalog::TableBuilder;
+    /// use vibesql::types::SqlType;
+    ///
+    /// let table = TableBuilder::new("people")
+    ///     .struct_column("address", [("city", SqlType::Varchar(None)), ("zip", SqlType::Int32)])
+    ///     .build();
+    /// assert_eq!(table.columns[0].data_type.struct_fields().unwrap().len(), 2);
+    /// ```
+    pub fn struct_column<N: Into<String>>(
+        mut self,
+        name: impl Into<String>,
+        fields: impl IntoIterator<Item = (N, SqlType)>,
+    ) -> Self {
+        self.columns
+            .push(ColumnSchema::new(name, SqlType::struct_of(fields)));
+        self
+    }
+
     /// Add a column with full customization.
     pub fn add_column(mut self, column: ColumnSchema) -> Self {
         self.columns.push(column);
         self
     }
 
+    /// Add a UNIQUE constraint over the given columns.
+    pub fn unique<S: Into<String>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.unique_constraints.push(UniqueConstraint::new(columns));
+        self
+    }
+
+    /// Add a FOREIGN KEY constraint.
+    ///
+    /// ```
+    /// use vibesql::catalog::{ForeignKey, TableBuilder};
+    /// use vibesql::types::SqlType;
+    ///
+    /// let table = TableBuilder::new("orders")
+    ///     .primary_key("id", SqlType::Int64)
+    ///     .column_not_null("customer_id", SqlType::Int64)
+    ///     .column_with_default("status", SqlType::Varchar(None), "'new'")
+    ///     .foreign_key(ForeignKey::new(["customer_id"], ["customers"], ["id"]))
+    ///     .build();
+    /// assert_eq!(table.primary_key(), ["id"]);
+    /// assert_eq!(table.foreign_keys()[0].columns, ["customer_id"]);
+    /// ```
+    pub fn foreign_key(mut self, foreign_key: ForeignKey) -> Self {
+        self.foreign_keys.push(foreign_key);
+        self
+    }
+
     /// Build the table schema.
     pub fn build(self) -> TableSchema {
-        TableSchema::new(self.name, self.columns)
+        let mut table = TableSchema::new(self.name, self.columns);
+        for unique in self.unique_constraints {
+            table = table.with_unique(unique);
+        }
+        for foreign_key in self.foreign_keys {
+            table = table.with_foreign_key(foreign_key);
+        }
+        table
     }
 }
 
@@ -283,13 +468,42 @@ mod tests {
         assert!(agg.unwrap().is_aggregate);
     }
 
+    #[test]
+    fn test_builder_overloads() {
+        let catalog = CatalogBuilder::new()
+            .with_builtins()
+            .add_overload(
+                FunctionSignature::scalar("MY_ABS", SqlType::Int64).args([SqlType::Int64]),
+            )
+            .add_overload(
+                FunctionSignature::scalar("MY_ABS", SqlType::Float64).args([SqlType::Float64]),
+            )
+            .add_overload(
+                FunctionSignature::scalar("MY_ABS", SqlType::Int32).args([SqlType::Int64]),
+            )
+            .build();
+
+        let overloads = catalog
+            .resolve_function_overloads(&["my_abs".to_string()])
+            .unwrap();
+        assert_eq!(overloads.len(), 2);
+        assert_eq!(overloads[0].return_type, SqlType::Int32);
+        assert_eq!(overloads[1].return_type, SqlType::Float64);
+
+        // Built-in overloads are kept together
+        let abs = catalog
+            .resolve_function_overloads(&["ABS".to_string()])
+            .unwrap();
+        assert!(abs.len() > 1);
+    }
+
     #[test]
     fn test_builder_table() {
         let catalog = CatalogBuilder::new()
             .add_table("users", |t| {
                 t.primary_key("id", SqlType::Int64)
-                    .column_not_null("name", SqlType::Varchar)
-                    .column("email", SqlType::Varchar)
+                    .column_not_null("name", SqlType::Varchar(None))
+                    .column("email", SqlType::Varchar(None))
             })
             .build();
 
@@ -323,7 +537,7 @@ mod tests {
             .add_scalar_function("CUSTOM_HASH", SqlType::Int64)
             .add_table("products", |t| {
                 t.primary_key("id", SqlType::Int64)
-                    .column_not_null("name", SqlType::Varchar)
+                    .column_not_null("name", SqlType::Varchar(None))
                     .column("price", SqlType::Float64)
             })
             .build();
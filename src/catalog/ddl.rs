@@ -0,0 +1,353 @@
+//! Writing a [`MemoryCatalog`] as DDL and reading it back.
+//!
+//! [`MemoryCatalog::to_ddl`] emits `CREATE SCHEMA` and `CREATE TABLE`
+//! statements that [`MemoryCatalog::from_ddl`] turns back into an
+//! equivalent catalog, so a catalog can be stored as a SQL file and
+//! reviewed like one.
+
+use super::{ColumnSchema, MemoryCatalog, TableSchema};
+use crate::ast::{Ident, StatementKind};
+use crate::error::{Error, Result};
+use crate::parser::Parser;
+use crate::types::value::is_plain_identifier;
+use crate::types::{SqlType, Value};
+
+impl MemoryCatalog {
+    /// Write the catalog's schemas and tables as DDL.
+    ///
+    /// Every schema other than `default` gets a `CREATE SCHEMA` statement,
+    /// followed by a `CREATE TABLE` for every table, qualified by its
+    /// schema unless that is `default`. Schemas and tables are sorted by
+    /// name. Columns keep their NOT NULL constraints, defaults, and
+    /// descriptions, followed by table-level `PRIMARY KEY`, `UNIQUE`, and
+    /// `FOREIGN KEY` constraints. Views are written as tables with the
+    /// view's columns, since the catalog does not keep their queries.
+    ///
+    /// ```
+    /// use vibesql::catalog::{ColumnSchema, MemoryCatalog, TableSchema};
+    /// use vibesql::types::SqlType;
+    ///
+    /// let mut catalog = MemoryCatalog::new();
+    /// catalog.add_table(TableSchema::new(
+    ///     "users",
+    ///     vec![
+    ///         ColumnSchema::new("id", SqlType::Int64).primary_key(),
+    ///         ColumnSchema::new("name", SqlType::Varchar(None)),
+    ///     ],
+    /// ));
+    ///
+    /// assert_eq!(
+    ///     catalog.to_ddl(),
+    ///     "CREATE TABLE users (\n  id BIGINT NOT NULL,\n  name VARCHAR,\n  PRIMARY KEY (id)\n);\n"
+    /// );
+    /// ```
+    pub fn to_ddl(&self) -> String {
+        let mut schemas: Vec<_> = self.schemas.values().collect();
+        schemas.sort_by(|a, b| (a.name != "default", &a.name).cmp(&(b.name != "default", &b.name)));
+
+        let mut statements: Vec<String> = schemas
+            .iter()
+            .filter(|schema| schema.name != "default")
+            .map(|schema| format!("CREATE SCHEMA {}", ident(&schema.name)))
+            .collect();
+        for schema in schemas {
+            let mut tables: Vec<_> = schema.tables.values().collect();
+            tables.sort_by(|a, b| a.name.cmp(&b.name));
+            for table in tables {
+                let name = if schema.name == "default" {
+                    ident(&table.name)
+                } else {
+                    format!("{}.{}", ident(&schema.name), ident(&table.name))
+                };
+                statements.push(create_table(&name, table));
+            }
+        }
+
+        statements
+            .iter()
+            .map(|stmt| format!("{};\n", stmt))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Build a catalog from `CREATE SCHEMA` and `CREATE TABLE` statements,
+    /// such as those written by [`MemoryCatalog::to_ddl`].
+    ///
+    /// Tables are created as by [`MemoryCatalog::apply`]. Any other
+    /// statement is an [`ErrorKind::UnsupportedFeature`] error. The catalog
+    /// has no functions; call [`MemoryCatalog::register_builtins`] to
+    /// analyze queries against it.
+    ///
+    /// [`ErrorKind::UnsupportedFeature`]: crate::error::ErrorKind::UnsupportedFeature
+    ///
+    /// ```
+    /// use vibesql::catalog::{Catalog, MemoryCatalog};
+    ///
+    /// let catalog = MemoryCatalog::from_ddl(
+    ///     "CREATE SCHEMA sales; CREATE TABLE sales.orders (id INT64 PRIMARY KEY)",
+    /// )
+    /// .unwrap();
+    /// let name = ["sales".to_string(), "orders".to_string()];
+    /// assert!(catalog.resolve_table(&name).unwrap().is_some());
+    /// ```
+    pub fn from_ddl(sql: &str) -> Result<Self> {
+        let mut catalog = MemoryCatalog::new();
+        for stmt in Parser::new(sql).parse()? {
+            match &stmt.kind {
+                StatementKind::CreateDatabase(create) => {
+                    catalog.add_schema(create.name.value.clone());
+                }
+                StatementKind::CreateTable(_) => {
+                    catalog.apply(&stmt)?;
+                }
+                _ => {
+                    return Err(Error::unsupported(
+                        "statements other than CREATE SCHEMA and CREATE TABLE in catalog DDL",
+                        stmt.span,
+                    ))
+                }
+            }
+        }
+        Ok(catalog)
+    }
+}
+
+fn create_table(name: &str, table: &TableSchema) -> String {
+    let mut lines: Vec<String> = table.columns.iter().map(column_def).collect();
+    if !table.primary_key().is_empty() {
+        lines.push(format!("PRIMARY KEY ({})", idents(table.primary_key())));
+    }
+    for unique in table.unique_constraints() {
+        lines.push(format!(
+            "{}UNIQUE ({})",
+            constraint_name(&unique.name),
+            idents(&unique.columns)
+        ));
+    }
+    for foreign_key in table.foreign_keys() {
+        let mut line = format!(
+            "{}FOREIGN KEY ({}) REFERENCES {} ({})",
+            constraint_name(&foreign_key.name),
+            idents(&foreign_key.columns),
+            foreign_key
+                .referenced_table
+                .iter()
+                .map(|part| ident(part))
+                .collect::<Vec<_>>()
+                .join("."),
+            idents(&foreign_key.referenced_columns)
+        );
+        if let Some(action) = foreign_key.on_delete {
+            line.push_str(&format!(" ON DELETE {}", action));
+        }
+        if let Some(action) = foreign_key.on_update {
+            line.push_str(&format!(" ON UPDATE {}", action));
+        }
+        lines.push(line);
+    }
+    format!("CREATE TABLE {} (\n  {}\n)", name, lines.join(",\n  "))
+}
+
+fn column_def(column: &ColumnSchema) -> String {
+    let mut def = ident(&column.name);
+    // A column without a type is written without one.
+    if column.data_type != SqlType::Unknown {
+        def.push(' ');
+        def.push_str(&data_type(&column.data_type));
+    }
+    if !column.nullable {
+        def.push_str(" NOT NULL");
+    }
+    if let Some(default) = &column.default_value {
+        def.push_str(" DEFAULT ");
+        def.push_str(default);
+    }
+    if let Some(description) = &column.description {
+        let description = Value::String(description.clone());
+        def.push_str(&format!(" OPTIONS(description = {})", description));
+    }
+    def
+}
+
+/// A type as DDL, quoting struct field names that need it.
+fn data_type(ty: &SqlType) -> String {
+    match ty {
+        SqlType::Array(elem) => format!("ARRAY<{}>", data_type(elem)),
+        SqlType::Range(elem) => format!("RANGE<{}>", data_type(elem)),
+        SqlType::Struct(fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|field| match &field.name {
+                    Some(name) => format!("{} {}", ident(name), data_type(&field.data_type)),
+                    None => data_type(&field.data_type),
+                })
+                .collect();
+            format!("STRUCT<{}>", fields.join(", "))
+        }
+        SqlType::Any => "ANY TYPE".to_string(),
+        _ => ty.to_string(),
+    }
+}
+
+fn constraint_name(name: &Option<String>) -> String {
+    match name {
+        Some(name) => format!("CONSTRAINT {} ", ident(name)),
+        None => String::new(),
+    }
+}
+
+fn idents(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|name| ident(name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A name, quoted unless it can be written without quotes.
+fn ident(name: &str) -> String {
+    if is_plain_identifier(name) {
+        name.to_string()
+    } else {
+        Ident::quoted(name, Default::default()).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::ReferentialAction;
+    use crate::catalog::{ForeignKey, UniqueConstraint};
+    use crate::types::StructField;
+
+    fn catalog() -> MemoryCatalog {
+        let mut catalog = MemoryCatalog::new();
+        catalog.add_table(TableSchema::new(
+            "orders",
+            vec![
+                ColumnSchema::new("id", SqlType::Int64).primary_key(),
+                ColumnSchema::new("line", SqlType::Int32).primary_key(),
+                ColumnSchema::new(
+                    "amount",
+                    SqlType::Numeric {
+                        precision: Some(10),
+                        scale: Some(2),
+                    },
+                )
+                .not_null()
+                .with_default("0"),
+                ColumnSchema::new(
+                    "ratio",
+                    SqlType::Numeric {
+                        precision: Some(5),
+                        scale: None,
+                    },
+                ),
+                ColumnSchema::new("tags", SqlType::Array(Box::new(SqlType::Varchar(None))))
+                    .with_description("Free-form 'labels'"),
+                ColumnSchema::new(
+                    "shipping",
+                    SqlType::Struct(vec![
+                        StructField {
+                            name: Some("city".to_string()),
+                            data_type: SqlType::Varchar(Some(64)),
+                        },
+                        StructField {
+                            name: Some("select".to_string()),
+                            data_type: SqlType::Array(Box::new(SqlType::Struct(vec![
+                                StructField {
+                                    name: None,
+                                    data_type: SqlType::Float64,
+                                },
+                            ]))),
+                        },
+                    ]),
+                ),
+            ],
+        ));
+        catalog.add_schema("sales").tables.insert(
+            "Daily Totals".to_string(),
+            TableSchema::new(
+                "Daily Totals",
+                vec![
+                    ColumnSchema::new("day", SqlType::Date).not_null(),
+                    ColumnSchema::new("span", SqlType::Range(Box::new(SqlType::Timestamp))),
+                    ColumnSchema::new("raw", SqlType::Varbinary(Some(16))),
+                    ColumnSchema::new("extra", SqlType::Struct(vec![])),
+                    ColumnSchema::new("anything", SqlType::Any),
+                    ColumnSchema::new(
+                        "nested",
+                        SqlType::Array(Box::new(SqlType::Struct(vec![StructField {
+                            name: Some("item".to_string()),
+                            data_type: SqlType::Any,
+                        }]))),
+                    ),
+                ],
+            )
+            .with_primary_key(["raw", "day"])
+            .with_unique(UniqueConstraint {
+                name: Some("one span".to_string()),
+                columns: vec!["span".to_string()],
+            })
+            .with_foreign_key(ForeignKey {
+                on_delete: Some(ReferentialAction::Cascade),
+                ..ForeignKey::new(["day"], ["public", "calendar"], ["day"])
+            })
+            .into(),
+        );
+        catalog.add_schema("empty");
+        catalog
+    }
+
+    fn tables(catalog: &MemoryCatalog) -> Vec<(String, TableSchema)> {
+        let mut tables: Vec<_> = catalog
+            .schemas
+            .values()
+            .flat_map(|schema| {
+                schema
+                    .tables
+                    .values()
+                    .map(|table| (schema.name.clone(), TableSchema::clone(table)))
+            })
+            .collect();
+        tables.sort_by(|a, b| (&a.0, &a.1.name).cmp(&(&b.0, &b.1.name)));
+        tables
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let catalog = catalog();
+        let ddl = catalog.to_ddl();
+        let restored = MemoryCatalog::from_ddl(&ddl).unwrap();
+
+        assert_eq!(tables(&restored), tables(&catalog));
+        let mut schemas: Vec<_> = restored.schemas.keys().cloned().collect();
+        schemas.sort();
+        assert_eq!(schemas, ["default", "empty", "sales"]);
+        assert_eq!(restored.to_ddl(), ddl);
+    }
+
+    #[test]
+    fn test_ddl_text() {
+        let ddl = catalog().to_ddl();
+        assert!(ddl
+            .starts_with("CREATE SCHEMA empty;\n\nCREATE SCHEMA sales;\n\nCREATE TABLE orders ("));
+        assert!(ddl.contains("\n  amount NUMERIC(10, 2) NOT NULL DEFAULT 0,\n"));
+        assert!(ddl.contains(
+            "\n  tags ARRAY<VARCHAR> OPTIONS(description = 'Free-form \\'labels\\''),\n"
+        ));
+        assert!(ddl.contains("\n  PRIMARY KEY (id, line)\n);\n"));
+        assert!(ddl.contains("CREATE TABLE sales.`Daily Totals` ("));
+        assert!(ddl.contains(concat!(
+            "\n  PRIMARY KEY (raw, day),\n",
+            "  CONSTRAINT `one span` UNIQUE (span),\n",
+            "  FOREIGN KEY (day) REFERENCES public.calendar (day) ON DELETE CASCADE\n"
+        )));
+    }
+
+    #[test]
+    fn test_from_ddl_rejects_other_statements() {
+        let err = MemoryCatalog::from_ddl("CREATE TABLE t (x INT64); SELECT 1").unwrap_err();
+        assert!(err.to_string().contains("CREATE SCHEMA and CREATE TABLE"));
+    }
+}
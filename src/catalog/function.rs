@@ -1,6 +1,7 @@
 //! Function signature definitions.
 
-use crate::types::SqlType;
+use crate::ast::Expr;
+use crate::types::{SqlType, MAX_NUMERIC_PRECISION};
 
 /// Function signature for built-in and user-defined functions.
 #[derive(Debug, Clone, PartialEq)]
@@ -9,8 +10,10 @@ pub struct FunctionSignature {
     pub name: String,
     /// Parameter types (None means any type is accepted).
     pub parameters: Vec<FunctionParameter>,
-    /// Return type.
+    /// Return type, or the fallback when `return_rule` cannot derive one.
     pub return_type: SqlType,
+    /// How the result type is derived from the argument types.
+    pub return_rule: ReturnTypeRule,
     /// Whether this is an aggregate function.
     pub is_aggregate: bool,
     /// Whether this is a window function.
@@ -21,6 +24,31 @@ pub struct FunctionSignature {
     pub min_args: usize,
     /// Maximum number of arguments (None = unlimited).
     pub max_args: Option<usize>,
+    /// Body of a SQL-language function, kept for inlining.
+    pub body: Option<Box<Expr>>,
+}
+
+/// How a function's result type is derived from its argument types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReturnTypeRule {
+    /// Always the signature's declared return type.
+    #[default]
+    Fixed,
+    /// The type of the argument at this index.
+    SameAsArg(usize),
+    /// The common supertype of all arguments.
+    CommonSupertypeOfArgs,
+    /// An array of the type of the argument at this index.
+    ArrayOfArg(usize),
+    /// The element type of the array argument at this index.
+    ElementOfArg(usize),
+    /// For a `NUMERIC(p, s)` argument at this index, `NUMERIC(38, s)`, as
+    /// for SUM. Other argument types give the declared return type.
+    NumericSumOfArg(usize),
+    /// For a `NUMERIC(p, s)` argument at this index,
+    /// `NUMERIC(38, max(s, 6))`, as for AVG. Other argument types give the
+    /// declared return type.
+    NumericAverageOfArg(usize),
 }
 
 /// A function parameter.
@@ -43,11 +71,13 @@ impl FunctionSignature {
             name: name.into().to_uppercase(),
             parameters: Vec::new(),
             return_type,
+            return_rule: ReturnTypeRule::Fixed,
             is_aggregate: false,
             is_window: false,
             is_deterministic: true,
             min_args: 0,
             max_args: None,
+            body: None,
         }
     }
 
@@ -57,11 +87,13 @@ impl FunctionSignature {
             name: name.into().to_uppercase(),
             parameters: Vec::new(),
             return_type,
+            return_rule: ReturnTypeRule::Fixed,
             is_aggregate: true,
             is_window: false,
             is_deterministic: true,
             min_args: 0,
             max_args: None,
+            body: None,
         }
     }
 
@@ -71,11 +103,13 @@ impl FunctionSignature {
             name: name.into().to_uppercase(),
             parameters: Vec::new(),
             return_type,
+            return_rule: ReturnTypeRule::Fixed,
             is_aggregate: false,
             is_window: true,
             is_deterministic: true,
             min_args: 0,
             max_args: None,
+            body: None,
         }
     }
 
@@ -104,6 +138,105 @@ impl FunctionSignature {
         self
     }
 
+    /// Add required parameters of the given types.
+    ///
+    /// The accepted argument count is derived from the parameter list.
+    pub fn args(mut self, types: impl IntoIterator<Item = SqlType>) -> Self {
+        self.parameters
+            .extend(types.into_iter().map(FunctionParameter::unnamed));
+        self.derive_arg_counts();
+        self
+    }
+
+    /// Add an optional parameter of the given type.
+    pub fn optional_arg(mut self, data_type: SqlType) -> Self {
+        self.parameters
+            .push(FunctionParameter::unnamed(data_type).optional());
+        self.derive_arg_counts();
+        self
+    }
+
+    /// Add a trailing parameter that takes one or more values of the given
+    /// type.
+    pub fn variadic_arg(mut self, data_type: SqlType) -> Self {
+        self.parameters
+            .push(FunctionParameter::unnamed(data_type).variadic());
+        self.derive_arg_counts();
+        self
+    }
+
+    /// Recompute the argument counts from the parameter list.
+    fn derive_arg_counts(&mut self) {
+        self.min_args = self.parameters.iter().filter(|p| !p.optional).count();
+        self.max_args = if self.parameters.iter().any(|p| p.variadic) {
+            None
+        } else {
+            Some(self.parameters.len())
+        };
+    }
+
+    /// Get the parameter that receives the argument at `index`.
+    ///
+    /// Arguments past the end of the list go to a trailing variadic
+    /// parameter.
+    pub fn parameter(&self, index: usize) -> Option<&FunctionParameter> {
+        self.parameters
+            .get(index)
+            .or_else(|| self.parameters.last().filter(|p| p.variadic))
+    }
+
+    /// Check whether two signatures take the same parameter types.
+    pub fn same_parameters(&self, other: &FunctionSignature) -> bool {
+        self.parameters.len() == other.parameters.len()
+            && self
+                .parameters
+                .iter()
+                .zip(&other.parameters)
+                .all(|(a, b)| a.data_type == b.data_type && a.variadic == b.variadic)
+            && self.min_args == other.min_args
+            && self.max_args == other.max_args
+    }
+
+    /// Derive the result type from the argument types instead of always
+    /// returning the declared type.
+    pub fn with_return_rule(mut self, rule: ReturnTypeRule) -> Self {
+        self.return_rule = rule;
+        self
+    }
+
+    /// Get the result type of a call with the given argument types.
+    ///
+    /// Falls back to the declared return type when the rule cannot be
+    /// applied, such as a missing argument or arguments without a common
+    /// supertype.
+    pub fn resolve_return_type(&self, arg_types: &[SqlType]) -> SqlType {
+        let derived = match self.return_rule {
+            ReturnTypeRule::Fixed => None,
+            ReturnTypeRule::SameAsArg(i) => arg_types.get(i).cloned(),
+            ReturnTypeRule::CommonSupertypeOfArgs => {
+                arg_types.split_first().and_then(|(first, rest)| {
+                    rest.iter()
+                        .try_fold(first.clone(), |common, t| common.common_supertype(t))
+                })
+            }
+            ReturnTypeRule::ArrayOfArg(i) => arg_types
+                .get(i)
+                .map(|t| SqlType::Array(Box::new(t.clone()))),
+            ReturnTypeRule::ElementOfArg(i) => {
+                arg_types.get(i).and_then(|t| t.element_type()).cloned()
+            }
+            ReturnTypeRule::NumericSumOfArg(i) => numeric_aggregate(arg_types.get(i), 0),
+            ReturnTypeRule::NumericAverageOfArg(i) => numeric_aggregate(arg_types.get(i), 6),
+        };
+        derived.unwrap_or_else(|| self.return_type.clone())
+    }
+
+    /// Set the body expression of a SQL-language function.
+    pub fn with_body(mut self, body: Expr) -> Self {
+        self.body = Some(Box::new(body));
+        self
+    }
+
     /// Mark as non-deterministic.
     pub fn non_deterministic(mut self) -> Self {
         self.is_deterministic = false;
@@ -127,6 +260,22 @@ impl FunctionSignature {
     }
 }
 
+/// The result of a SUM or AVG over a NUMERIC argument: full precision, with
+/// at least `min_scale` digits of scale.
+fn numeric_aggregate(arg: Option<&SqlType>, min_scale: u8) -> Option<SqlType> {
+    match arg? {
+        SqlType::Numeric {
+            precision: Some(_),
+            scale,
+        } => Some(SqlType::Numeric {
+            precision: Some(MAX_NUMERIC_PRECISION),
+            scale: Some(scale.unwrap_or(0).max(min_scale)),
+        }),
+        numeric @ SqlType::Numeric { .. } => Some(numeric.clone()),
+        _ => None,
+    }
+}
+
 impl FunctionParameter {
     /// Create a new parameter.
     pub fn new(name: impl Into<String>, data_type: SqlType) -> Self {
@@ -196,4 +345,64 @@ mod tests {
         assert!(row_number.can_be_window());
         assert!(row_number.accepts_arg_count(0));
     }
+
+    #[test]
+    fn test_return_type_rules() {
+        let int = SqlType::Int64;
+        let ints = [int.clone()];
+        let max = FunctionSignature::aggregate("MAX", SqlType::Any)
+            .with_return_rule(ReturnTypeRule::SameAsArg(0));
+        assert_eq!(max.resolve_return_type(&ints), int);
+        assert_eq!(max.resolve_return_type(&[]), SqlType::Any);
+
+        let coalesce = FunctionSignature::scalar("COALESCE", SqlType::Any)
+            .with_return_rule(ReturnTypeRule::CommonSupertypeOfArgs);
+        assert_eq!(
+            coalesce.resolve_return_type(&[int.clone(), SqlType::Unknown, SqlType::Float64]),
+            SqlType::Float64
+        );
+        assert_eq!(
+            coalesce.resolve_return_type(&[int.clone(), SqlType::Bool]),
+            SqlType::Any
+        );
+
+        let array_agg = FunctionSignature::aggregate("ARRAY_AGG", SqlType::Any)
+            .with_return_rule(ReturnTypeRule::ArrayOfArg(0));
+        let int_array = SqlType::Array(Box::new(int.clone()));
+        assert_eq!(array_agg.resolve_return_type(&ints), int_array);
+        assert_eq!(array_agg.resolve_return_type(&[]), SqlType::Any);
+
+        let first = FunctionSignature::scalar("ARRAY_FIRST", SqlType::Any)
+            .with_return_rule(ReturnTypeRule::ElementOfArg(0));
+        assert_eq!(first.resolve_return_type(&[int_array]), int);
+        assert_eq!(first.resolve_return_type(&[int]), SqlType::Any);
+    }
+
+    #[test]
+    fn test_typed_parameters() {
+        let substr = FunctionSignature::scalar("SUBSTR", SqlType::Varchar(None))
+            .args([SqlType::Varchar(None), SqlType::Int64])
+            .optional_arg(SqlType::Int64);
+        assert_eq!(substr.min_args, 2);
+        assert_eq!(substr.max_args, Some(3));
+        assert!(!substr.accepts_arg_count(1));
+        assert!(substr.accepts_arg_count(3));
+        assert!(!substr.accepts_arg_count(4));
+        assert_eq!(
+            substr.parameter(2).and_then(|p| p.data_type.clone()),
+            Some(SqlType::Int64)
+        );
+        assert!(substr.parameter(3).is_none());
+
+        let concat = FunctionSignature::scalar("CONCAT", SqlType::Varchar(None))
+            .variadic_arg(SqlType::Varchar(None));
+        assert!(!concat.accepts_arg_count(0));
+        assert!(concat.accepts_arg_count(5));
+        assert!(concat.parameter(4).is_some_and(|p| p.variadic));
+
+        let abs_int = FunctionSignature::scalar("ABS", SqlType::Int64).args([SqlType::Int64]);
+        let abs_float = FunctionSignature::scalar("ABS", SqlType::Float64).args([SqlType::Float64]);
+        assert!(abs_int.same_parameters(&abs_int.clone()));
+        assert!(!abs_int.same_parameters(&abs_float));
+    }
 }
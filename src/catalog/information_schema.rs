@@ -0,0 +1,242 @@
+//! Virtual `information_schema` tables.
+//!
+//! [`InformationSchemaCatalog`] wraps another catalog and adds the
+//! `information_schema.tables`, `information_schema.columns`, and
+//! `information_schema.schemata` views with their SQL standard columns, so
+//! metadata queries can be analyzed against any backend:
+//!
+//! ```
+//! use vibesql::catalog::{Catalog, InformationSchemaCatalog, MemoryCatalog};
+//!
+//! let catalog = InformationSchemaCatalog::new(MemoryCatalog::new());
+//! let name = ["information_schema".to_string(), "columns".to_string()];
+//! let columns = catalog.resolve_table(&name).unwrap().unwrap();
+//! assert!(columns.get_column("ordinal_position").is_some());
+//! ```
+
+use super::{Catalog, ColumnSchema, FunctionInfo, FunctionSignature, TableSchema};
+use crate::ast::Ident;
+use crate::error::Result;
+use crate::types::SqlType;
+use std::sync::Arc;
+
+/// The schema holding the virtual tables.
+const SCHEMA: &str = "information_schema";
+
+/// The virtual table names, sorted.
+const TABLES: [&str; 3] = ["columns", "schemata", "tables"];
+
+/// A catalog that adds `information_schema` tables to another catalog.
+///
+/// Tables in the `information_schema` schema, matched case-insensitively
+/// with or without a catalog prefix, resolve to the virtual tables and
+/// shadow any tables the inner catalog has there. Every other lookup is
+/// delegated.
+#[derive(Debug, Clone, Default)]
+pub struct InformationSchemaCatalog<C: Catalog> {
+    inner: C,
+}
+
+impl<C: Catalog> InformationSchemaCatalog<C> {
+    /// Wrap a catalog.
+    pub fn new(inner: C) -> Self {
+        Self { inner }
+    }
+
+    /// Get the wrapped catalog.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Get the wrapped catalog mutably, for example to add tables.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner catalog.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+/// The virtual table a name refers to, if it is in `information_schema`.
+///
+/// `Some(None)` is an unknown table in the schema.
+fn virtual_table_name<S: AsRef<str>>(name: &[S]) -> Option<Option<&'static str>> {
+    let (schema, table) = match name {
+        [schema, table] | [_, schema, table] => (schema, table),
+        _ => return None,
+    };
+    if !schema.as_ref().eq_ignore_ascii_case(SCHEMA) {
+        return None;
+    }
+    Some(
+        TABLES
+            .iter()
+            .copied()
+            .find(|t| t.eq_ignore_ascii_case(table.as_ref())),
+    )
+}
+
+/// The schema of a virtual table.
+fn virtual_table(name: &str) -> TableSchema {
+    let identifier = |name: &str| ColumnSchema::new(name, SqlType::Varchar(None)).not_null();
+    let columns = match name {
+        "tables" => vec![
+            identifier("table_catalog"),
+            identifier("table_schema"),
+            identifier("table_name"),
+            identifier("table_type"),
+        ],
+        "columns" => vec![
+            identifier("table_catalog"),
+            identifier("table_schema"),
+            identifier("table_name"),
+            identifier("column_name"),
+            ColumnSchema::new("ordinal_position", SqlType::Int64).not_null(),
+            ColumnSchema::new("column_default", SqlType::Varchar(None)),
+            identifier("is_nullable"),
+            identifier("data_type"),
+            ColumnSchema::new("character_maximum_length", SqlType::Int64),
+            ColumnSchema::new("numeric_precision", SqlType::Int64),
+            ColumnSchema::new("numeric_scale", SqlType::Int64),
+        ],
+        "schemata" => vec![
+            identifier("catalog_name"),
+            identifier("schema_name"),
+            ColumnSchema::new("schema_owner", SqlType::Varchar(None)),
+        ],
+        _ => unreachable!("unknown information_schema table {}", name),
+    };
+    TableSchema::new(name, columns)
+}
+
+impl<C: Catalog> Catalog for InformationSchemaCatalog<C> {
+    fn resolve_table(&self, name: &[String]) -> Result<Option<Arc<TableSchema>>> {
+        match virtual_table_name(name) {
+            Some(table) => Ok(table.map(|table| Arc::new(virtual_table(table)))),
+            None => self.inner.resolve_table(name),
+        }
+    }
+
+    fn resolve_table_ident(&self, name: &[Ident]) -> Result<Option<Arc<TableSchema>>> {
+        let parts: Vec<&str> = name.iter().map(|p| p.value.as_str()).collect();
+        match virtual_table_name(&parts) {
+            Some(table) => Ok(table.map(|table| Arc::new(virtual_table(table)))),
+            None => self.inner.resolve_table_ident(name),
+        }
+    }
+
+    fn resolve_function(&self, name: &[String]) -> Result<Option<FunctionSignature>> {
+        self.inner.resolve_function(name)
+    }
+
+    fn resolve_function_overloads(&self, name: &[String]) -> Result<Vec<FunctionSignature>> {
+        self.inner.resolve_function_overloads(name)
+    }
+
+    fn resolve_type(&self, name: &[String]) -> Result<Option<SqlType>> {
+        self.inner.resolve_type(name)
+    }
+
+    fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>> {
+        match schema {
+            Some(schema) if schema.eq_ignore_ascii_case(SCHEMA) => {
+                Ok(TABLES.iter().map(|t| t.to_string()).collect())
+            }
+            _ => self.inner.list_tables(schema),
+        }
+    }
+
+    fn list_schemas(&self) -> Result<Vec<String>> {
+        let mut schemas = self.inner.list_schemas()?;
+        if !schemas.iter().any(|s| s.eq_ignore_ascii_case(SCHEMA)) {
+            schemas.push(SCHEMA.to_string());
+        }
+        Ok(schemas)
+    }
+
+    fn list_functions(&self) -> Result<Vec<FunctionInfo>> {
+        self.inner.list_functions()
+    }
+
+    fn default_schema(&self) -> &str {
+        self.inner.default_schema()
+    }
+
+    fn function_search_schemas(&self, name: &[String]) -> Result<Vec<String>> {
+        self.inner.function_search_schemas(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::MemoryCatalog;
+    use crate::{Analyzer, Parser, StatementKind};
+
+    fn name(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_information_schema_catalog() {
+        let mut inner = MemoryCatalog::new();
+        inner.add_table(TableSchema::new(
+            "users",
+            vec![ColumnSchema::new("id", SqlType::Int64)],
+        ));
+        let catalog = InformationSchemaCatalog::new(inner);
+
+        let tables = catalog
+            .resolve_table(&name(&["information_schema", "tables"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            tables.column_names(),
+            ["table_catalog", "table_schema", "table_name", "table_type"]
+        );
+
+        let columns = catalog
+            .resolve_table(&name(&["db", "INFORMATION_SCHEMA", "Columns"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(columns.name, "columns");
+        let position = columns.get_column("ordinal_position").unwrap();
+        assert_eq!(position.data_type, SqlType::Int64);
+        assert!(!position.nullable);
+        assert!(columns.get_column("column_default").unwrap().nullable);
+
+        assert!(catalog
+            .resolve_table(&name(&["information_schema", "views"]))
+            .unwrap()
+            .is_none());
+        assert!(catalog.resolve_table(&name(&["users"])).unwrap().is_some());
+        assert!(catalog.resolve_table(&name(&["tables"])).unwrap().is_none());
+
+        assert_eq!(
+            catalog.list_tables(Some("information_schema")).unwrap(),
+            ["columns", "schemata", "tables"]
+        );
+        assert_eq!(catalog.list_tables(None).unwrap(), ["users"]);
+        let mut schemas = catalog.list_schemas().unwrap();
+        schemas.sort();
+        assert_eq!(schemas, ["default", "information_schema"]);
+    }
+
+    #[test]
+    fn test_analyze_information_schema_query() {
+        let catalog = InformationSchemaCatalog::new(MemoryCatalog::new());
+        let sql = "SELECT table_name, ordinal_position FROM information_schema.columns \
+                   WHERE table_schema = 'default' ORDER BY ordinal_position";
+        let stmt = Parser::new(sql).parse().unwrap().remove(0);
+        let StatementKind::Query(query) = stmt.kind else {
+            panic!("expected a query");
+        };
+        let result = Analyzer::with_catalog(catalog)
+            .analyze_query_result(&query)
+            .unwrap();
+        let types: Vec<_> = result.columns.iter().map(|c| &c.data_type).collect();
+        assert_eq!(types, [&SqlType::Varchar(None), &SqlType::Int64]);
+    }
+}
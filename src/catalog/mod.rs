@@ -20,23 +20,39 @@
 //!     .add_scalar_function("MY_HASH", SqlType::Int64)
 //!     .add_table("users", |t| {
 //!         t.primary_key("id", SqlType::Int64)
-//!          .column("name", SqlType::Varchar)
+//!          .column("name", SqlType::Varchar(None))
 //!     })
 //!     .build();
 //! ```
 
+mod apply;
 mod builder;
+mod ddl;
 mod function;
+mod information_schema;
+mod names;
+mod pack;
 mod schema;
+mod session;
+mod shared;
 mod type_registry;
 
+pub use apply::{ApplyOutcome, TableChange};
 pub use builder::*;
 pub use function::*;
+pub use information_schema::InformationSchemaCatalog;
+pub use names::NameMap;
+pub use pack::{FunctionInfo, FunctionPack, StatsPack};
 pub use schema::*;
+pub use session::SessionCatalog;
+pub use shared::SharedCatalog;
 pub use type_registry::*;
 
-use crate::error::Result;
+use crate::ast::{Ident, IdentifierMatching};
+use crate::error::{Result, Span};
 use crate::types::SqlType;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// A catalog provides access to database schema information.
 ///
@@ -45,18 +61,56 @@ use crate::types::SqlType;
 pub trait Catalog: Send + Sync {
     /// Resolve a table by name, returning its schema.
     ///
-    /// The name parts are: `[catalog].[schema].table`
-    fn resolve_table(&self, name: &[String]) -> Result<Option<TableSchema>>;
+    /// The name parts are: `[catalog].[schema].table`, treated as unquoted
+    /// identifiers. Schemas are shared, so backends that keep them in an
+    /// `Arc` resolve tables without copying their columns.
+    fn resolve_table(&self, name: &[String]) -> Result<Option<Arc<TableSchema>>>;
+
+    /// Resolve a table by its identifiers, returning its schema.
+    ///
+    /// Unlike [`Catalog::resolve_table`], this knows which parts were quoted,
+    /// so a backend can match unquoted names case-insensitively and quoted
+    /// names exactly. The analyzer resolves tables through this method; the
+    /// default ignores quoting and calls `resolve_table`.
+    fn resolve_table_ident(&self, name: &[Ident]) -> Result<Option<Arc<TableSchema>>> {
+        let parts: Vec<String> = name.iter().map(|p| p.value.clone()).collect();
+        self.resolve_table(&parts)
+    }
 
     /// Resolve a function by name.
     fn resolve_function(&self, name: &[String]) -> Result<Option<FunctionSignature>>;
 
+    /// Resolve every overload of a function, in registration order.
+    ///
+    /// The type checker picks the overload that best matches a call's
+    /// arguments. Backends without overloads only need `resolve_function`.
+    fn resolve_function_overloads(&self, name: &[String]) -> Result<Vec<FunctionSignature>> {
+        Ok(self.resolve_function(name)?.into_iter().collect())
+    }
+
+    /// Resolve a custom type name, such as a type alias.
+    ///
+    /// The analyzer calls this for data types the parser does not know,
+    /// like `EMAIL` in `CAST(x AS EMAIL)`. Backends without custom types
+    /// keep the default, which knows none.
+    fn resolve_type(&self, name: &[String]) -> Result<Option<SqlType>> {
+        let _ = name;
+        Ok(None)
+    }
+
     /// Get all tables in a schema.
     fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>>;
 
     /// Get all schemas in the catalog.
     fn list_schemas(&self) -> Result<Vec<String>>;
 
+    /// Get all functions in the catalog, sorted by name.
+    ///
+    /// Backends that cannot enumerate their functions return an empty list.
+    fn list_functions(&self) -> Result<Vec<FunctionInfo>> {
+        Ok(Vec::new())
+    }
+
     /// Check if a table exists.
     fn table_exists(&self, name: &[String]) -> Result<bool> {
         Ok(self.resolve_table(name)?.is_some())
@@ -66,20 +120,136 @@ pub trait Catalog: Send + Sync {
     fn default_schema(&self) -> &str {
         "default"
     }
+
+    /// Get the schemas searched, in order, to resolve a function name.
+    ///
+    /// The analyzer lists them when a function is not found. Backends
+    /// without function schemas return an empty list.
+    fn function_search_schemas(&self, name: &[String]) -> Result<Vec<String>> {
+        let _ = name;
+        Ok(Vec::new())
+    }
 }
 
+/// Implement [`Catalog`] for a pointer type by delegating every method to
+/// the catalog it points to.
+macro_rules! delegate_catalog {
+    ($($pointer:ty),+) => {$(
+        impl<C: Catalog + ?Sized> Catalog for $pointer {
+            fn resolve_table(&self, name: &[String]) -> Result<Option<Arc<TableSchema>>> {
+                (**self).resolve_table(name)
+            }
+
+            fn resolve_table_ident(&self, name: &[Ident]) -> Result<Option<Arc<TableSchema>>> {
+                (**self).resolve_table_ident(name)
+            }
+
+            fn resolve_function(&self, name: &[String]) -> Result<Option<FunctionSignature>> {
+                (**self).resolve_function(name)
+            }
+
+            fn resolve_function_overloads(
+                &self,
+                name: &[String],
+            ) -> Result<Vec<FunctionSignature>> {
+                (**self).resolve_function_overloads(name)
+            }
+
+            fn resolve_type(&self, name: &[String]) -> Result<Option<SqlType>> {
+                (**self).resolve_type(name)
+            }
+
+            fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>> {
+                (**self).list_tables(schema)
+            }
+
+            fn list_schemas(&self) -> Result<Vec<String>> {
+                (**self).list_schemas()
+            }
+
+            fn list_functions(&self) -> Result<Vec<FunctionInfo>> {
+                (**self).list_functions()
+            }
+
+            fn table_exists(&self, name: &[String]) -> Result<bool> {
+                (**self).table_exists(name)
+            }
+
+            fn default_schema(&self) -> &str {
+                (**self).default_schema()
+            }
+
+            fn function_search_schemas(&self, name: &[String]) -> Result<Vec<String>> {
+                (**self).function_search_schemas(name)
+            }
+        }
+    )+};
+}
+
+delegate_catalog!(&C, Box<C>, Arc<C>);
+
+/// The schema built-in functions are registered in.
+///
+/// It comes last on the default function search path, so a function of
+/// the same name in the default schema shadows the built-in for
+/// unqualified calls, while `builtin.upper(x)` still reaches it.
+pub const BUILTIN_SCHEMA: &str = "builtin";
+
 /// An in-memory catalog for testing and simple use cases.
-#[derive(Debug, Default, Clone)]
+///
+/// Schema and table names are resolved using an [`IdentifierMatching`]
+/// policy, which defaults to [`IdentifierMatching::AsciiCaseInsensitive`].
+/// Tables are stored under the name they were registered with. A name
+/// that equals a stored name always matches it; otherwise unquoted names
+/// match under the policy, while quoted names must be equal apart from
+/// Unicode normalization, so tables differing only in case can coexist.
+/// An unquoted name matching several of them is ambiguous, and resolving
+/// it fails with [`ErrorKind::AmbiguousName`](crate::error::ErrorKind).
+///
+/// Functions are kept per schema. A qualified call such as `sales.f(x)`
+/// looks in that schema only, while an unqualified call tries each schema
+/// on the function search path in turn, by default `default` and then
+/// [`BUILTIN_SCHEMA`].
+///
+/// Custom types resolve through the catalog's [`TypeRegistry`].
+#[derive(Debug, Clone)]
 pub struct MemoryCatalog {
-    schemas: std::collections::HashMap<String, SchemaDefinition>,
-    functions: std::collections::HashMap<String, FunctionSignature>,
+    schemas: NameMap<SchemaDefinition>,
+    /// Overloads of each function by schema, in registration order.
+    functions: NameMap<HashMap<String, Vec<FunctionSignature>>>,
+    /// Schemas searched for unqualified function names, in order.
+    function_search_path: Vec<String>,
+    identifier_matching: IdentifierMatching,
+    /// Registered function packs, in registration order.
+    packs: Vec<String>,
+    /// The pack each pack-provided function in the default schema came
+    /// from.
+    function_packs: HashMap<String, String>,
+    /// Tables created by `CREATE VIEW`, as `(schema, table)` keys.
+    views: HashSet<(String, String)>,
+    type_registry: TypeRegistry,
+}
+
+impl Default for MemoryCatalog {
+    fn default() -> Self {
+        Self {
+            schemas: NameMap::new(),
+            functions: NameMap::new(),
+            function_search_path: vec!["default".to_string(), BUILTIN_SCHEMA.to_string()],
+            identifier_matching: IdentifierMatching::AsciiCaseInsensitive,
+            packs: Vec::new(),
+            function_packs: HashMap::new(),
+            views: HashSet::new(),
+            type_registry: TypeRegistry::new(),
+        }
+    }
 }
 
 /// A schema containing tables.
 #[derive(Debug, Default, Clone)]
 pub struct SchemaDefinition {
     pub name: String,
-    pub tables: std::collections::HashMap<String, TableSchema>,
+    pub tables: NameMap<Arc<TableSchema>>,
 }
 
 impl MemoryCatalog {
@@ -88,357 +258,620 @@ impl MemoryCatalog {
         Self::default()
     }
 
+    /// Set the policy used to match schema and table names.
+    pub fn set_identifier_matching(&mut self, matching: IdentifierMatching) {
+        self.identifier_matching = matching;
+    }
+
+    /// Get the policy used to match schema and table names.
+    pub fn identifier_matching(&self) -> IdentifierMatching {
+        self.identifier_matching
+    }
+
+    /// Get the registry custom type names resolve through.
+    pub fn type_registry(&self) -> &TypeRegistry {
+        &self.type_registry
+    }
+
+    /// Get mutable access to the type registry.
+    pub fn type_registry_mut(&mut self) -> &mut TypeRegistry {
+        &mut self.type_registry
+    }
+
+    /// Add a custom type alias, such as `EMAIL` for VARCHAR.
+    pub fn add_type_alias(&mut self, alias: impl Into<String>, sql_type: SqlType) {
+        self.type_registry.add_alias(alias, sql_type);
+    }
+
+    /// Look up an entry by name under the catalog's policy.
+    fn lookup<'a, V>(&self, map: &'a NameMap<V>, name: &Ident) -> Result<Option<&'a V>> {
+        Ok(map
+            .resolve(name, self.identifier_matching)?
+            .map(|key| &map[key]))
+    }
+
+    /// Find the stored key for a name under the catalog's policy.
+    fn lookup_key<V>(&self, map: &NameMap<V>, name: &Ident) -> Result<Option<String>> {
+        Ok(map.resolve(name, self.identifier_matching)?.cloned())
+    }
+
     /// Add a schema to the catalog.
     pub fn add_schema(&mut self, name: impl Into<String>) -> &mut SchemaDefinition {
         let name = name.into();
         self.schemas
-            .entry(name.clone())
-            .or_insert_with(|| SchemaDefinition {
+            .get_or_insert_with(name.clone(), || SchemaDefinition {
                 name,
-                tables: std::collections::HashMap::new(),
+                tables: NameMap::new(),
             })
     }
 
     /// Add a table to the default schema.
     pub fn add_table(&mut self, table: TableSchema) {
         let schema = self.add_schema("default");
-        schema.tables.insert(table.name.clone(), table);
+        schema.tables.insert(table.name.clone(), Arc::new(table));
     }
 
-    /// Add a function to the catalog.
+    /// Add a function to the default schema, replacing any overloads of the
+    /// same name there.
+    ///
+    /// Replacing a function that came from a pack clears its pack.
     pub fn add_function(&mut self, func: FunctionSignature) {
-        self.functions.insert(func.name.clone(), func);
+        self.add_schema_function("default", func);
+    }
+
+    /// Add an overload of a function in the default schema.
+    ///
+    /// An existing overload with the same parameter types is replaced;
+    /// otherwise the signature is added after the existing overloads.
+    /// Overloading a function that came from a pack clears its pack.
+    pub fn add_overload(&mut self, func: FunctionSignature) {
+        self.add_schema_overload("default", func);
+    }
+
+    /// Add a function to a schema, replacing any overloads of the same name
+    /// in that schema.
+    pub fn add_schema_function(&mut self, schema: impl Into<String>, func: FunctionSignature) {
+        let overloads = self.schema_overloads(schema.into(), &func.name);
+        *overloads = vec![func];
+    }
+
+    /// Add an overload of a function in a schema, as
+    /// [`MemoryCatalog::add_overload`] does for the default schema.
+    pub fn add_schema_overload(&mut self, schema: impl Into<String>, func: FunctionSignature) {
+        let overloads = self.schema_overloads(schema.into(), &func.name);
+        match overloads.iter_mut().find(|f| f.same_parameters(&func)) {
+            Some(existing) => *existing = func,
+            None => overloads.push(func),
+        }
+    }
+
+    /// The overloads of a function in a schema, for changing them.
+    fn schema_overloads(&mut self, schema: String, name: &str) -> &mut Vec<FunctionSignature> {
+        if schema == "default" {
+            self.function_packs.remove(name);
+        }
+        self.functions
+            .get_or_insert_with(schema, HashMap::new)
+            .entry(name.to_string())
+            .or_default()
+    }
+
+    /// Add a built-in function, replacing any built-in overloads of the
+    /// same name.
+    fn add_builtin(&mut self, func: FunctionSignature) {
+        self.add_schema_function(BUILTIN_SCHEMA, func);
+    }
+
+    /// Add an overload of a built-in function.
+    fn add_builtin_overload(&mut self, func: FunctionSignature) {
+        self.add_schema_overload(BUILTIN_SCHEMA, func);
+    }
+
+    /// Set the schemas searched, in order, for unqualified function names.
+    ///
+    /// Leaving [`BUILTIN_SCHEMA`] off the path hides the built-ins from
+    /// unqualified calls.
+    pub fn set_function_search_path<I, S>(&mut self, schemas: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.function_search_path = schemas.into_iter().map(Into::into).collect();
+    }
+
+    /// Get the schemas searched for unqualified function names.
+    pub fn function_search_path(&self) -> &[String] {
+        &self.function_search_path
+    }
+
+    /// The overloads a function name refers to: those in the named schema
+    /// for a qualified name, or in the first schema on the search path
+    /// that defines the function.
+    fn lookup_function(&self, name: &[String]) -> Result<Option<&Vec<FunctionSignature>>> {
+        let Some(func_name) = name.last().map(|n| n.to_uppercase()) else {
+            return Ok(None);
+        };
+        for schema in self.function_search_schemas(name)? {
+            let schema = Ident::new(schema, Span::default());
+            if let Some(overloads) = self
+                .lookup(&self.functions, &schema)?
+                .and_then(|functions| functions.get(&func_name))
+            {
+                return Ok(Some(overloads));
+            }
+        }
+        Ok(None)
     }
 
     /// Register built-in functions.
     ///
-    /// Note: Some aggregate functions like ARRAY_AGG use `Array<Any>` as return type.
-    /// Proper polymorphic type inference (e.g., ARRAY_AGG on INT64 returns `ARRAY<INT64>`)
-    /// requires the type checker to specialize return types based on input types at analysis time.
+    /// Polymorphic functions such as MAX, COALESCE, and ARRAY_AGG are declared
+    /// with `Any` and carry a [`ReturnTypeRule`] that the type checker uses to
+    /// derive the result type from the arguments (ARRAY_AGG on INT64 returns
+    /// `ARRAY<INT64>`).
     pub fn register_builtins(&mut self) {
         // ===== AGGREGATE FUNCTIONS =====
-        self.add_function(FunctionSignature::aggregate("COUNT", SqlType::Int64));
-        self.add_function(FunctionSignature::aggregate("COUNTIF", SqlType::Int64));
-        self.add_function(FunctionSignature::aggregate("SUM", SqlType::Float64));
-        self.add_function(FunctionSignature::aggregate("AVG", SqlType::Float64));
-        self.add_function(FunctionSignature::aggregate("MIN", SqlType::Any));
-        self.add_function(FunctionSignature::aggregate("MAX", SqlType::Any));
-        self.add_function(FunctionSignature::aggregate("ANY_VALUE", SqlType::Any));
-        self.add_function(FunctionSignature::aggregate(
-            "ARRAY_AGG",
-            SqlType::Array(Box::new(SqlType::Any)),
-        ));
-        self.add_function(FunctionSignature::aggregate(
-            "ARRAY_CONCAT_AGG",
-            SqlType::Array(Box::new(SqlType::Any)),
+        self.add_builtin(FunctionSignature::aggregate("COUNT", SqlType::Int64));
+        self.add_builtin(FunctionSignature::aggregate("COUNTIF", SqlType::Int64));
+        self.add_builtin(
+            FunctionSignature::aggregate("SUM", SqlType::Float64)
+                .with_return_rule(ReturnTypeRule::NumericSumOfArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::aggregate("AVG", SqlType::Float64)
+                .with_return_rule(ReturnTypeRule::NumericAverageOfArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::aggregate("MIN", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::aggregate("MAX", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::aggregate("ANY_VALUE", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::aggregate("ARRAY_AGG", SqlType::Array(Box::new(SqlType::Any)))
+                .with_return_rule(ReturnTypeRule::ArrayOfArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::aggregate(
+                "ARRAY_CONCAT_AGG",
+                SqlType::Array(Box::new(SqlType::Any)),
+            )
+            .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(FunctionSignature::aggregate(
+            "STRING_AGG",
+            SqlType::Varchar(None),
         ));
-        self.add_function(FunctionSignature::aggregate("STRING_AGG", SqlType::Varchar));
 
         // Bitwise aggregate functions
-        self.add_function(FunctionSignature::aggregate("BIT_AND", SqlType::Int64));
-        self.add_function(FunctionSignature::aggregate("BIT_OR", SqlType::Int64));
-        self.add_function(FunctionSignature::aggregate("BIT_XOR", SqlType::Int64));
+        self.add_builtin(FunctionSignature::aggregate("BIT_AND", SqlType::Int64));
+        self.add_builtin(FunctionSignature::aggregate("BIT_OR", SqlType::Int64));
+        self.add_builtin(FunctionSignature::aggregate("BIT_XOR", SqlType::Int64));
 
         // Logical aggregate functions
-        self.add_function(FunctionSignature::aggregate("LOGICAL_AND", SqlType::Bool));
-        self.add_function(FunctionSignature::aggregate("LOGICAL_OR", SqlType::Bool));
+        self.add_builtin(FunctionSignature::aggregate("LOGICAL_AND", SqlType::Bool));
+        self.add_builtin(FunctionSignature::aggregate("LOGICAL_OR", SqlType::Bool));
 
         // Grouping function (for ROLLUP/CUBE)
-        self.add_function(FunctionSignature::aggregate("GROUPING", SqlType::Int64));
+        self.add_builtin(FunctionSignature::aggregate("GROUPING", SqlType::Int64));
 
         // Statistical aggregate functions
-        self.add_function(FunctionSignature::aggregate("STDDEV", SqlType::Float64));
-        self.add_function(FunctionSignature::aggregate("STDDEV_POP", SqlType::Float64));
-        self.add_function(FunctionSignature::aggregate(
+        self.add_builtin(FunctionSignature::aggregate("STDDEV", SqlType::Float64));
+        self.add_builtin(FunctionSignature::aggregate("STDDEV_POP", SqlType::Float64));
+        self.add_builtin(FunctionSignature::aggregate(
             "STDDEV_SAMP",
             SqlType::Float64,
         ));
-        self.add_function(FunctionSignature::aggregate("VARIANCE", SqlType::Float64));
-        self.add_function(FunctionSignature::aggregate("VAR_POP", SqlType::Float64));
-        self.add_function(FunctionSignature::aggregate("VAR_SAMP", SqlType::Float64));
-        self.add_function(FunctionSignature::aggregate("CORR", SqlType::Float64));
-        self.add_function(FunctionSignature::aggregate("COVAR_POP", SqlType::Float64));
-        self.add_function(FunctionSignature::aggregate("COVAR_SAMP", SqlType::Float64));
+        self.add_builtin(FunctionSignature::aggregate("VARIANCE", SqlType::Float64));
+        self.add_builtin(FunctionSignature::aggregate("VAR_POP", SqlType::Float64));
+        self.add_builtin(FunctionSignature::aggregate("VAR_SAMP", SqlType::Float64));
+        self.add_builtin(FunctionSignature::aggregate("CORR", SqlType::Float64));
+        self.add_builtin(FunctionSignature::aggregate("COVAR_POP", SqlType::Float64));
+        self.add_builtin(FunctionSignature::aggregate("COVAR_SAMP", SqlType::Float64));
 
         // ===== WINDOW FUNCTIONS =====
-        self.add_function(FunctionSignature::window("ROW_NUMBER", SqlType::Int64));
-        self.add_function(FunctionSignature::window("RANK", SqlType::Int64));
-        self.add_function(FunctionSignature::window("DENSE_RANK", SqlType::Int64));
-        self.add_function(FunctionSignature::window("NTILE", SqlType::Int64));
-        self.add_function(FunctionSignature::window("LAG", SqlType::Any));
-        self.add_function(FunctionSignature::window("LEAD", SqlType::Any));
-        self.add_function(FunctionSignature::window("FIRST_VALUE", SqlType::Any));
-        self.add_function(FunctionSignature::window("LAST_VALUE", SqlType::Any));
-        self.add_function(FunctionSignature::window("NTH_VALUE", SqlType::Any));
-        self.add_function(FunctionSignature::window("CUME_DIST", SqlType::Float64));
-        self.add_function(FunctionSignature::window("PERCENT_RANK", SqlType::Float64));
-        self.add_function(FunctionSignature::window(
+        self.add_builtin(FunctionSignature::window("ROW_NUMBER", SqlType::Int64));
+        self.add_builtin(FunctionSignature::window("RANK", SqlType::Int64));
+        self.add_builtin(FunctionSignature::window("DENSE_RANK", SqlType::Int64));
+        self.add_builtin(FunctionSignature::window("NTILE", SqlType::Int64));
+        self.add_builtin(
+            FunctionSignature::window("LAG", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::window("LEAD", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::window("FIRST_VALUE", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::window("LAST_VALUE", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::window("NTH_VALUE", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(FunctionSignature::window("CUME_DIST", SqlType::Float64));
+        self.add_builtin(FunctionSignature::window("PERCENT_RANK", SqlType::Float64));
+        self.add_builtin(FunctionSignature::window(
             "PERCENTILE_CONT",
             SqlType::Float64,
         ));
-        self.add_function(FunctionSignature::window("PERCENTILE_DISC", SqlType::Any));
+        self.add_builtin(
+            FunctionSignature::window("PERCENTILE_DISC", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
 
         // ===== STRING FUNCTIONS =====
-        self.add_function(FunctionSignature::scalar("CONCAT", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("LENGTH", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("CHAR_LENGTH", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar(
-            "CHARACTER_LENGTH",
-            SqlType::Int64,
-        ));
-        self.add_function(FunctionSignature::scalar("BYTE_LENGTH", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("UPPER", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("LOWER", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("TRIM", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("LTRIM", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("RTRIM", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("LPAD", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("RPAD", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("SUBSTR", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("SUBSTRING", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("LEFT", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("RIGHT", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("REPLACE", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("REVERSE", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("REPEAT", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar(
+        let text = || SqlType::Varchar(None);
+        self.add_builtin(FunctionSignature::scalar("CONCAT", text()).variadic_arg(SqlType::Any));
+        for name in ["LENGTH", "CHAR_LENGTH", "CHARACTER_LENGTH"] {
+            self.add_builtin(FunctionSignature::scalar(name, SqlType::Int64).args([text()]));
+        }
+        self.add_builtin_overload(
+            FunctionSignature::scalar("LENGTH", SqlType::Int64).args([SqlType::Varbinary(None)]),
+        );
+        self.add_builtin(FunctionSignature::scalar("BYTE_LENGTH", SqlType::Int64).args([text()]));
+        self.add_builtin_overload(
+            FunctionSignature::scalar("BYTE_LENGTH", SqlType::Int64)
+                .args([SqlType::Varbinary(None)]),
+        );
+        for name in ["UPPER", "LOWER", "INITCAP"] {
+            self.add_builtin(FunctionSignature::scalar(name, text()).args([text()]));
+        }
+        for name in ["TRIM", "LTRIM", "RTRIM"] {
+            self.add_builtin(
+                FunctionSignature::scalar(name, text())
+                    .args([text()])
+                    .optional_arg(text()),
+            );
+        }
+        for name in ["LPAD", "RPAD"] {
+            self.add_builtin(
+                FunctionSignature::scalar(name, text())
+                    .args([text(), SqlType::Int64])
+                    .optional_arg(text()),
+            );
+        }
+        for name in ["SUBSTR", "SUBSTRING"] {
+            self.add_builtin(
+                FunctionSignature::scalar(name, text())
+                    .args([text(), SqlType::Int64])
+                    .optional_arg(SqlType::Int64),
+            );
+        }
+        for name in ["LEFT", "RIGHT", "REPEAT"] {
+            self.add_builtin(
+                FunctionSignature::scalar(name, text()).args([text(), SqlType::Int64]),
+            );
+        }
+        self.add_builtin(FunctionSignature::scalar("REPLACE", text()).args([
+            text(),
+            text(),
+            text(),
+        ]));
+        self.add_builtin(FunctionSignature::scalar("REVERSE", text()).args([text()]));
+        self.add_builtin_overload(
+            FunctionSignature::scalar("REVERSE", SqlType::Varbinary(None))
+                .args([SqlType::Varbinary(None)]),
+        );
+        self.add_builtin(FunctionSignature::scalar(
             "SPLIT",
-            SqlType::Array(Box::new(SqlType::Varchar)),
-        ));
-        self.add_function(FunctionSignature::scalar("STRPOS", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("INSTR", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("STARTS_WITH", SqlType::Bool));
-        self.add_function(FunctionSignature::scalar("ENDS_WITH", SqlType::Bool));
-        self.add_function(FunctionSignature::scalar("CONTAINS_SUBSTR", SqlType::Bool));
-        self.add_function(FunctionSignature::scalar("REGEXP_CONTAINS", SqlType::Bool));
-        self.add_function(FunctionSignature::scalar(
+            SqlType::Array(Box::new(SqlType::Varchar(None))),
+        ));
+        self.add_builtin(
+            FunctionSignature::scalar("STRPOS", SqlType::Int64).args([text(), text()]),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("INSTR", SqlType::Int64)
+                .args([text(), text()])
+                .optional_arg(SqlType::Int64)
+                .optional_arg(SqlType::Int64),
+        );
+        for name in ["STARTS_WITH", "ENDS_WITH"] {
+            self.add_builtin(FunctionSignature::scalar(name, SqlType::Bool).args([text(), text()]));
+        }
+        self.add_builtin(FunctionSignature::scalar("CONTAINS_SUBSTR", SqlType::Bool));
+        self.add_builtin(FunctionSignature::scalar("REGEXP_CONTAINS", SqlType::Bool));
+        self.add_builtin(FunctionSignature::scalar(
             "REGEXP_EXTRACT",
-            SqlType::Varchar,
+            SqlType::Varchar(None),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "REGEXP_EXTRACT_ALL",
-            SqlType::Array(Box::new(SqlType::Varchar)),
+            SqlType::Array(Box::new(SqlType::Varchar(None))),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "REGEXP_REPLACE",
-            SqlType::Varchar,
+            SqlType::Varchar(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar("REGEXP_INSTR", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("FORMAT", SqlType::Varchar(None)));
+        self.add_builtin(FunctionSignature::scalar(
+            "NORMALIZE",
+            SqlType::Varchar(None),
         ));
-        self.add_function(FunctionSignature::scalar("REGEXP_INSTR", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("FORMAT", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("NORMALIZE", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "NORMALIZE_AND_CASEFOLD",
-            SqlType::Varchar,
-        ));
-        self.add_function(FunctionSignature::scalar("TO_BASE32", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("TO_BASE64", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("FROM_BASE32", SqlType::Varbinary));
-        self.add_function(FunctionSignature::scalar("FROM_BASE64", SqlType::Varbinary));
-        self.add_function(FunctionSignature::scalar("TO_HEX", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("FROM_HEX", SqlType::Varbinary));
-        self.add_function(FunctionSignature::scalar("ASCII", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("CHR", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("UNICODE", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar(
+            SqlType::Varchar(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar(
+            "TO_BASE32",
+            SqlType::Varchar(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar(
+            "TO_BASE64",
+            SqlType::Varchar(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar(
+            "FROM_BASE32",
+            SqlType::Varbinary(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar(
+            "FROM_BASE64",
+            SqlType::Varbinary(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar("TO_HEX", SqlType::Varchar(None)));
+        self.add_builtin(FunctionSignature::scalar(
+            "FROM_HEX",
+            SqlType::Varbinary(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar("ASCII", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("CHR", SqlType::Varchar(None)));
+        self.add_builtin(FunctionSignature::scalar("UNICODE", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar(
             "TO_CODE_POINTS",
             SqlType::Array(Box::new(SqlType::Int64)),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "CODE_POINTS_TO_STRING",
-            SqlType::Varchar,
+            SqlType::Varchar(None),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "CODE_POINTS_TO_BYTES",
-            SqlType::Varbinary,
+            SqlType::Varbinary(None),
         ));
-        self.add_function(FunctionSignature::scalar("SOUNDEX", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("TRANSLATE", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("INITCAP", SqlType::Varchar));
+        self.add_builtin(FunctionSignature::scalar("SOUNDEX", SqlType::Varchar(None)));
+        self.add_builtin(FunctionSignature::scalar(
+            "TRANSLATE",
+            SqlType::Varchar(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar("INITCAP", SqlType::Varchar(None)));
 
         // ===== MATH FUNCTIONS =====
-        self.add_function(FunctionSignature::scalar("ABS", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("SIGN", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("CEIL", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("CEILING", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("FLOOR", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("ROUND", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("TRUNC", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("TRUNCATE", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("DIV", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("MOD", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("SQRT", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("CBRT", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("POW", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("POWER", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("EXP", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("LN", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("LOG", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("LOG10", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("LOG2", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("GREATEST", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("LEAST", SqlType::Any));
+        // Numeric overloads return the type of their argument. Integer
+        // arguments to functions without an INT64 overload take FLOAT64.
+        let numeric = || SqlType::Numeric {
+            precision: None,
+            scale: None,
+        };
+        for name in ["ABS", "SIGN"] {
+            for ty in [SqlType::Int64, numeric(), SqlType::Float64] {
+                self.add_builtin_overload(FunctionSignature::scalar(name, ty.clone()).args([ty]));
+            }
+        }
+        for name in [
+            "CEIL", "CEILING", "FLOOR", "SQRT", "CBRT", "EXP", "LN", "LOG10", "LOG2",
+        ] {
+            for ty in [SqlType::Float64, numeric()] {
+                self.add_builtin_overload(FunctionSignature::scalar(name, ty.clone()).args([ty]));
+            }
+        }
+        for name in ["ROUND", "TRUNC", "TRUNCATE"] {
+            for ty in [SqlType::Float64, numeric()] {
+                self.add_builtin_overload(
+                    FunctionSignature::scalar(name, ty.clone())
+                        .args([ty])
+                        .optional_arg(SqlType::Int64),
+                );
+            }
+        }
+        for name in ["POW", "POWER"] {
+            for ty in [SqlType::Float64, numeric()] {
+                self.add_builtin_overload(
+                    FunctionSignature::scalar(name, ty.clone()).args([ty.clone(), ty]),
+                );
+            }
+        }
+        for ty in [SqlType::Float64, numeric()] {
+            self.add_builtin_overload(
+                FunctionSignature::scalar("LOG", ty.clone())
+                    .args([ty.clone()])
+                    .optional_arg(ty),
+            );
+        }
+        for name in ["DIV", "MOD"] {
+            for ty in [SqlType::Int64, numeric()] {
+                self.add_builtin_overload(
+                    FunctionSignature::scalar(name, ty.clone()).args([ty.clone(), ty]),
+                );
+            }
+        }
+        self.add_builtin(
+            FunctionSignature::scalar("GREATEST", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::CommonSupertypeOfArgs),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("LEAST", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::CommonSupertypeOfArgs),
+        );
 
         // Trigonometric functions
-        self.add_function(FunctionSignature::scalar("SIN", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("COS", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("TAN", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("ASIN", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("ACOS", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("ATAN", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("ATAN2", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("SINH", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("COSH", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("TANH", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("ASINH", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("ACOSH", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("ATANH", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("COT", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("CSC", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("SEC", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("COTH", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("CSCH", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("SECH", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("SIN", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("COS", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("TAN", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("ASIN", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("ACOS", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("ATAN", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("ATAN2", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("SINH", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("COSH", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("TANH", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("ASINH", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("ACOSH", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("ATANH", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("COT", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("CSC", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("SEC", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("COTH", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("CSCH", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("SECH", SqlType::Float64));
 
         // IEEE floating point functions
-        self.add_function(FunctionSignature::scalar("IEEE_DIVIDE", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("IS_INF", SqlType::Bool));
-        self.add_function(FunctionSignature::scalar("IS_NAN", SqlType::Bool));
+        self.add_builtin(FunctionSignature::scalar("IEEE_DIVIDE", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("IS_INF", SqlType::Bool));
+        self.add_builtin(FunctionSignature::scalar("IS_NAN", SqlType::Bool));
 
         // Random and range
-        self.add_function(FunctionSignature::scalar("RAND", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("RANDOM", SqlType::Float64));
-        self.add_function(FunctionSignature::scalar("RANGE_BUCKET", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("RAND", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("RANDOM", SqlType::Float64));
+        self.add_builtin(FunctionSignature::scalar("RANGE_BUCKET", SqlType::Int64));
 
         // Bitwise functions
-        self.add_function(FunctionSignature::scalar("BIT_COUNT", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("BIT_COUNT", SqlType::Int64));
 
         // ===== DATE/TIME FUNCTIONS =====
-        self.add_function(FunctionSignature::scalar("CURRENT_DATE", SqlType::Date));
-        self.add_function(FunctionSignature::scalar("CURRENT_TIME", SqlType::Time));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("CURRENT_DATE", SqlType::Date));
+        self.add_builtin(FunctionSignature::scalar("CURRENT_TIME", SqlType::Time));
+        self.add_builtin(FunctionSignature::scalar(
             "CURRENT_DATETIME",
             SqlType::Datetime,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "CURRENT_TIMESTAMP",
             SqlType::Timestamp,
         ));
 
         // Date functions
-        self.add_function(FunctionSignature::scalar("DATE", SqlType::Date));
-        self.add_function(FunctionSignature::scalar("DATE_ADD", SqlType::Date));
-        self.add_function(FunctionSignature::scalar("DATE_SUB", SqlType::Date));
-        self.add_function(FunctionSignature::scalar("DATE_DIFF", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("DATE_TRUNC", SqlType::Date));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("DATE", SqlType::Date));
+        self.add_builtin(FunctionSignature::scalar("DATE_ADD", SqlType::Date));
+        self.add_builtin(FunctionSignature::scalar("DATE_SUB", SqlType::Date));
+        self.add_builtin(FunctionSignature::scalar("DATE_DIFF", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("DATE_TRUNC", SqlType::Date));
+        self.add_builtin(FunctionSignature::scalar(
             "DATE_FROM_UNIX_DATE",
             SqlType::Date,
         ));
-        self.add_function(FunctionSignature::scalar("FORMAT_DATE", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("PARSE_DATE", SqlType::Date));
-        self.add_function(FunctionSignature::scalar("UNIX_DATE", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("LAST_DAY", SqlType::Date));
+        self.add_builtin(FunctionSignature::scalar(
+            "FORMAT_DATE",
+            SqlType::Varchar(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar("PARSE_DATE", SqlType::Date));
+        self.add_builtin(FunctionSignature::scalar("UNIX_DATE", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("LAST_DAY", SqlType::Date));
 
         // Time functions
-        self.add_function(FunctionSignature::scalar("TIME", SqlType::Time));
-        self.add_function(FunctionSignature::scalar("TIME_ADD", SqlType::Time));
-        self.add_function(FunctionSignature::scalar("TIME_SUB", SqlType::Time));
-        self.add_function(FunctionSignature::scalar("TIME_DIFF", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("TIME_TRUNC", SqlType::Time));
-        self.add_function(FunctionSignature::scalar("FORMAT_TIME", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar("PARSE_TIME", SqlType::Time));
+        self.add_builtin(FunctionSignature::scalar("TIME", SqlType::Time));
+        self.add_builtin(FunctionSignature::scalar("TIME_ADD", SqlType::Time));
+        self.add_builtin(FunctionSignature::scalar("TIME_SUB", SqlType::Time));
+        self.add_builtin(FunctionSignature::scalar("TIME_DIFF", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("TIME_TRUNC", SqlType::Time));
+        self.add_builtin(FunctionSignature::scalar(
+            "FORMAT_TIME",
+            SqlType::Varchar(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar("PARSE_TIME", SqlType::Time));
 
         // Datetime functions
-        self.add_function(FunctionSignature::scalar("DATETIME", SqlType::Datetime));
-        self.add_function(FunctionSignature::scalar("DATETIME_ADD", SqlType::Datetime));
-        self.add_function(FunctionSignature::scalar("DATETIME_SUB", SqlType::Datetime));
-        self.add_function(FunctionSignature::scalar("DATETIME_DIFF", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("DATETIME", SqlType::Datetime));
+        self.add_builtin(FunctionSignature::scalar("DATETIME_ADD", SqlType::Datetime));
+        self.add_builtin(FunctionSignature::scalar("DATETIME_SUB", SqlType::Datetime));
+        self.add_builtin(FunctionSignature::scalar("DATETIME_DIFF", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar(
             "DATETIME_TRUNC",
             SqlType::Datetime,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "FORMAT_DATETIME",
-            SqlType::Varchar,
+            SqlType::Varchar(None),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "PARSE_DATETIME",
             SqlType::Datetime,
         ));
 
         // Timestamp functions
-        self.add_function(FunctionSignature::scalar("TIMESTAMP", SqlType::Timestamp));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("TIMESTAMP", SqlType::Timestamp));
+        self.add_builtin(FunctionSignature::scalar(
             "TIMESTAMP_ADD",
             SqlType::Timestamp,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "TIMESTAMP_SUB",
             SqlType::Timestamp,
         ));
-        self.add_function(FunctionSignature::scalar("TIMESTAMP_DIFF", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("TIMESTAMP_DIFF", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar(
             "TIMESTAMP_TRUNC",
             SqlType::Timestamp,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "FORMAT_TIMESTAMP",
-            SqlType::Varchar,
+            SqlType::Varchar(None),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "PARSE_TIMESTAMP",
             SqlType::Timestamp,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "TIMESTAMP_SECONDS",
             SqlType::Timestamp,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "TIMESTAMP_MILLIS",
             SqlType::Timestamp,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "TIMESTAMP_MICROS",
             SqlType::Timestamp,
         ));
-        self.add_function(FunctionSignature::scalar("UNIX_SECONDS", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("UNIX_MILLIS", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("UNIX_MICROS", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar("STRING", SqlType::Varchar));
+        self.add_builtin(FunctionSignature::scalar("UNIX_SECONDS", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("UNIX_MILLIS", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("UNIX_MICROS", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("STRING", SqlType::Varchar(None)));
 
         // Interval functions
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "MAKE_INTERVAL",
             SqlType::Interval,
         ));
-        self.add_function(FunctionSignature::scalar("JUSTIFY_DAYS", SqlType::Interval));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("JUSTIFY_DAYS", SqlType::Interval));
+        self.add_builtin(FunctionSignature::scalar(
             "JUSTIFY_HOURS",
             SqlType::Interval,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "JUSTIFY_INTERVAL",
             SqlType::Interval,
         ));
 
         // Extract (handled specially but good to have)
-        self.add_function(FunctionSignature::scalar("EXTRACT", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar("EXTRACT", SqlType::Int64));
 
         // Time series functions
-        self.add_function(FunctionSignature::scalar("DATE_BUCKET", SqlType::Date));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("DATE_BUCKET", SqlType::Date));
+        self.add_builtin(FunctionSignature::scalar(
             "DATETIME_BUCKET",
             SqlType::Datetime,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "TIMESTAMP_BUCKET",
             SqlType::Timestamp,
         ));
 
         // ===== TYPE CONVERSION =====
-        self.add_function(FunctionSignature::scalar("CAST", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("TRY_CAST", SqlType::Any));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("CAST", SqlType::Any));
+        self.add_builtin(FunctionSignature::scalar("TRY_CAST", SqlType::Any));
+        self.add_builtin(FunctionSignature::scalar(
             "PARSE_NUMERIC",
             SqlType::Numeric {
                 precision: None,
@@ -447,167 +880,215 @@ impl MemoryCatalog {
         ));
 
         // ===== CONDITIONAL FUNCTIONS =====
-        self.add_function(FunctionSignature::scalar("IF", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("IFNULL", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("NULLIF", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("COALESCE", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("NVL", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("ZeroIfNull", SqlType::Any));
+        self.add_builtin(FunctionSignature::scalar("IF", SqlType::Any));
+        self.add_builtin(
+            FunctionSignature::scalar("IFNULL", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::CommonSupertypeOfArgs),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("NULLIF", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("COALESCE", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::CommonSupertypeOfArgs),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("NVL", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::CommonSupertypeOfArgs),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("ZeroIfNull", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
 
         // ===== ARRAY FUNCTIONS =====
-        self.add_function(FunctionSignature::scalar("ARRAY_LENGTH", SqlType::Int64));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("ARRAY_LENGTH", SqlType::Int64));
+        self.add_builtin(FunctionSignature::scalar(
             "ARRAY_TO_STRING",
-            SqlType::Varchar,
-        ));
-        self.add_function(FunctionSignature::scalar(
-            "ARRAY_CONCAT",
-            SqlType::Array(Box::new(SqlType::Any)),
-        ));
-        self.add_function(FunctionSignature::scalar(
-            "ARRAY_REVERSE",
-            SqlType::Array(Box::new(SqlType::Any)),
+            SqlType::Varchar(None),
         ));
-        self.add_function(FunctionSignature::scalar(
-            "ARRAY_FILTER",
-            SqlType::Array(Box::new(SqlType::Any)),
-        ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(
+            FunctionSignature::scalar("ARRAY_CONCAT", SqlType::Array(Box::new(SqlType::Any)))
+                .with_return_rule(ReturnTypeRule::CommonSupertypeOfArgs),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("ARRAY_REVERSE", SqlType::Array(Box::new(SqlType::Any)))
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("ARRAY_FILTER", SqlType::Array(Box::new(SqlType::Any)))
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(FunctionSignature::scalar(
             "ARRAY_TRANSFORM",
             SqlType::Array(Box::new(SqlType::Any)),
         ));
-        self.add_function(FunctionSignature::scalar(
-            "ARRAY_SLICE",
-            SqlType::Array(Box::new(SqlType::Any)),
-        ));
-        self.add_function(FunctionSignature::scalar("ARRAY_FIRST", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("ARRAY_LAST", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("ARRAY_INCLUDES", SqlType::Bool));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(
+            FunctionSignature::scalar("ARRAY_SLICE", SqlType::Array(Box::new(SqlType::Any)))
+                .with_return_rule(ReturnTypeRule::SameAsArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("ARRAY_FIRST", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::ElementOfArg(0)),
+        );
+        self.add_builtin(
+            FunctionSignature::scalar("ARRAY_LAST", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::ElementOfArg(0)),
+        );
+        self.add_builtin(FunctionSignature::scalar("ARRAY_INCLUDES", SqlType::Bool));
+        self.add_builtin(FunctionSignature::scalar(
             "ARRAY_INCLUDES_ANY",
             SqlType::Bool,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "ARRAY_INCLUDES_ALL",
             SqlType::Bool,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "GENERATE_ARRAY",
             SqlType::Array(Box::new(SqlType::Int64)),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "GENERATE_DATE_ARRAY",
             SqlType::Array(Box::new(SqlType::Date)),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "GENERATE_TIMESTAMP_ARRAY",
             SqlType::Array(Box::new(SqlType::Timestamp)),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "FLATTEN",
             SqlType::Array(Box::new(SqlType::Any)),
         ));
 
         // ===== JSON FUNCTIONS =====
         // Standard extractors
-        self.add_function(FunctionSignature::scalar("JSON_QUERY", SqlType::Json));
-        self.add_function(FunctionSignature::scalar("JSON_VALUE", SqlType::Varchar));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("JSON_QUERY", SqlType::Json));
+        self.add_builtin(FunctionSignature::scalar(
+            "JSON_VALUE",
+            SqlType::Varchar(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar(
             "JSON_QUERY_ARRAY",
             SqlType::Array(Box::new(SqlType::Json)),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "JSON_VALUE_ARRAY",
-            SqlType::Array(Box::new(SqlType::Varchar)),
+            SqlType::Array(Box::new(SqlType::Varchar(None))),
         ));
 
         // JSON constructors
-        self.add_function(FunctionSignature::scalar("JSON_ARRAY", SqlType::Json));
-        self.add_function(FunctionSignature::scalar("JSON_OBJECT", SqlType::Json));
+        self.add_builtin(FunctionSignature::scalar("JSON_ARRAY", SqlType::Json));
+        self.add_builtin(FunctionSignature::scalar("JSON_OBJECT", SqlType::Json));
 
         // JSON mutators
-        self.add_function(FunctionSignature::scalar("JSON_SET", SqlType::Json));
-        self.add_function(FunctionSignature::scalar("JSON_REMOVE", SqlType::Json));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("JSON_SET", SqlType::Json));
+        self.add_builtin(FunctionSignature::scalar("JSON_REMOVE", SqlType::Json));
+        self.add_builtin(FunctionSignature::scalar(
             "JSON_ARRAY_APPEND",
             SqlType::Json,
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "JSON_ARRAY_INSERT",
             SqlType::Json,
         ));
-        self.add_function(FunctionSignature::scalar("JSON_STRIP_NULLS", SqlType::Json));
+        self.add_builtin(FunctionSignature::scalar("JSON_STRIP_NULLS", SqlType::Json));
 
         // JSON other
-        self.add_function(FunctionSignature::scalar("PARSE_JSON", SqlType::Json));
-        self.add_function(FunctionSignature::scalar("TO_JSON", SqlType::Json));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("PARSE_JSON", SqlType::Json));
+        self.add_builtin(FunctionSignature::scalar("TO_JSON", SqlType::Json));
+        self.add_builtin(FunctionSignature::scalar(
             "TO_JSON_STRING",
-            SqlType::Varchar,
+            SqlType::Varchar(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar(
+            "JSON_TYPE",
+            SqlType::Varchar(None),
         ));
-        self.add_function(FunctionSignature::scalar("JSON_TYPE", SqlType::Varchar));
 
         // ===== RANGE FUNCTIONS =====
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "RANGE",
             SqlType::Range(Box::new(SqlType::Any)),
         ));
-        self.add_function(FunctionSignature::scalar("RANGE_START", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("RANGE_END", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("RANGE_CONTAINS", SqlType::Bool));
-        self.add_function(FunctionSignature::scalar("RANGE_OVERLAPS", SqlType::Bool));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar("RANGE_START", SqlType::Any));
+        self.add_builtin(FunctionSignature::scalar("RANGE_END", SqlType::Any));
+        self.add_builtin(FunctionSignature::scalar("RANGE_CONTAINS", SqlType::Bool));
+        self.add_builtin(FunctionSignature::scalar("RANGE_OVERLAPS", SqlType::Bool));
+        self.add_builtin(FunctionSignature::scalar(
             "RANGE_INTERSECT",
             SqlType::Range(Box::new(SqlType::Any)),
         ));
-        self.add_function(FunctionSignature::scalar(
+        self.add_builtin(FunctionSignature::scalar(
             "GENERATE_RANGE_ARRAY",
             SqlType::Array(Box::new(SqlType::Range(Box::new(SqlType::Any)))),
         ));
 
         // ===== HASH FUNCTIONS =====
-        self.add_function(FunctionSignature::scalar("MD5", SqlType::Varbinary));
-        self.add_function(FunctionSignature::scalar("SHA1", SqlType::Varbinary));
-        self.add_function(FunctionSignature::scalar("SHA256", SqlType::Varbinary));
-        self.add_function(FunctionSignature::scalar("SHA512", SqlType::Varbinary));
+        self.add_builtin(FunctionSignature::scalar("MD5", SqlType::Varbinary(None)));
+        self.add_builtin(FunctionSignature::scalar("SHA1", SqlType::Varbinary(None)));
+        self.add_builtin(FunctionSignature::scalar(
+            "SHA256",
+            SqlType::Varbinary(None),
+        ));
+        self.add_builtin(FunctionSignature::scalar(
+            "SHA512",
+            SqlType::Varbinary(None),
+        ));
 
         // ===== UUID FUNCTIONS =====
-        self.add_function(FunctionSignature::scalar("GENERATE_UUID", SqlType::Uuid));
+        self.add_builtin(FunctionSignature::scalar("GENERATE_UUID", SqlType::Uuid));
 
         // ===== ERROR HANDLING =====
-        self.add_function(FunctionSignature::scalar("ERROR", SqlType::Unknown));
-        self.add_function(FunctionSignature::scalar("IFERROR", SqlType::Any));
-        self.add_function(FunctionSignature::scalar("ISERROR", SqlType::Bool));
+        self.add_builtin(FunctionSignature::scalar("ERROR", SqlType::Unknown));
+        self.add_builtin(
+            FunctionSignature::scalar("IFERROR", SqlType::Any)
+                .with_return_rule(ReturnTypeRule::CommonSupertypeOfArgs),
+        );
+        self.add_builtin(FunctionSignature::scalar("ISERROR", SqlType::Bool));
     }
 }
 
 impl Catalog for MemoryCatalog {
-    fn resolve_table(&self, name: &[String]) -> Result<Option<TableSchema>> {
-        let (schema_name, table_name) = match name.len() {
-            1 => ("default", name[0].as_str()),
-            2 => (name[0].as_str(), name[1].as_str()),
-            3 => (name[1].as_str(), name[2].as_str()), // Ignore catalog for now
-            _ => return Ok(None),
+    fn resolve_table(&self, name: &[String]) -> Result<Option<Arc<TableSchema>>> {
+        let parts: Vec<Ident> = name
+            .iter()
+            .map(|p| Ident::new(p.clone(), Span::default()))
+            .collect();
+        self.resolve_table_ident(&parts)
+    }
+
+    fn resolve_table_ident(&self, name: &[Ident]) -> Result<Option<Arc<TableSchema>>> {
+        let Some((schema_name, table_name)) = split_table_name(name) else {
+            return Ok(None);
         };
 
+        match self.lookup(&self.schemas, &schema_name)? {
+            Some(schema) => Ok(self.lookup(&schema.tables, table_name)?.cloned()),
+            None => Ok(None),
+        }
+    }
+
+    fn resolve_function(&self, name: &[String]) -> Result<Option<FunctionSignature>> {
         Ok(self
-            .schemas
-            .get(schema_name)
-            .and_then(|s| s.tables.get(table_name))
+            .lookup_function(name)?
+            .and_then(|overloads| overloads.first())
             .cloned())
     }
 
-    fn resolve_function(&self, name: &[String]) -> Result<Option<FunctionSignature>> {
-        // For now, just use the last part (function name)
-        let func_name = name.last().map(|s| s.to_uppercase()).unwrap_or_default();
-        Ok(self.functions.get(&func_name).cloned())
+    fn resolve_function_overloads(&self, name: &[String]) -> Result<Vec<FunctionSignature>> {
+        Ok(self.lookup_function(name)?.cloned().unwrap_or_default())
+    }
+
+    fn resolve_type(&self, name: &[String]) -> Result<Option<SqlType>> {
+        Ok(self.type_registry.resolve(&name.join(".")).cloned())
     }
 
     fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>> {
-        let schema_name = schema.unwrap_or("default");
+        let schema_name = Ident::new(schema.unwrap_or("default"), Span::default());
         Ok(self
-            .schemas
-            .get(schema_name)
+            .lookup(&self.schemas, &schema_name)?
             .map(|s| s.tables.keys().cloned().collect())
             .unwrap_or_default())
     }
@@ -615,6 +1096,48 @@ impl Catalog for MemoryCatalog {
     fn list_schemas(&self) -> Result<Vec<String>> {
         Ok(self.schemas.keys().cloned().collect())
     }
+
+    fn list_functions(&self) -> Result<Vec<FunctionInfo>> {
+        let mut functions: Vec<FunctionInfo> = self
+            .functions
+            .iter()
+            .flat_map(|(schema, functions)| {
+                functions
+                    .values()
+                    .flatten()
+                    .map(move |signature| FunctionInfo {
+                        signature: signature.clone(),
+                        schema: schema.clone(),
+                        pack: if schema == "default" {
+                            self.function_packs.get(&signature.name).cloned()
+                        } else {
+                            None
+                        },
+                    })
+            })
+            .collect();
+        functions
+            .sort_by(|a, b| (&a.signature.name, &a.schema).cmp(&(&b.signature.name, &b.schema)));
+        Ok(functions)
+    }
+
+    fn function_search_schemas(&self, name: &[String]) -> Result<Vec<String>> {
+        Ok(match name {
+            [_] => self.function_search_path.clone(),
+            [schema, _] | [_, schema, _] => vec![schema.clone()], // Ignore catalog for now
+            _ => Vec::new(),
+        })
+    }
+}
+
+/// Split `[catalog].[schema].table` into schema and table names.
+fn split_table_name(name: &[Ident]) -> Option<(Ident, &Ident)> {
+    match name {
+        [table] => Some((Ident::new("default", table.span), table)),
+        [schema, table] => Some((schema.clone(), table)),
+        [_, schema, table] => Some((schema.clone(), table)), // Ignore catalog for now
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -625,14 +1148,14 @@ mod tests {
     fn test_memory_catalog() {
         let mut catalog = MemoryCatalog::new();
 
-        catalog.add_table(TableSchema {
-            name: "users".to_string(),
-            columns: vec![
+        catalog.add_table(TableSchema::new(
+            "users",
+            vec![
                 ColumnSchema::new("id", SqlType::Int64).not_null(),
-                ColumnSchema::new("name", SqlType::Varchar),
-                ColumnSchema::new("email", SqlType::Varchar),
+                ColumnSchema::new("name", SqlType::Varchar(None)),
+                ColumnSchema::new("email", SqlType::Varchar(None)),
             ],
-        });
+        ));
 
         let table = catalog.resolve_table(&["users".to_string()]).unwrap();
         assert!(table.is_some());
@@ -651,4 +1174,244 @@ mod tests {
         assert!(count.is_some());
         assert!(count.unwrap().is_aggregate);
     }
+
+    #[test]
+    fn test_schema_qualified_functions() {
+        let name =
+            |parts: &[&str]| -> Vec<String> { parts.iter().map(|p| p.to_string()).collect() };
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        catalog.add_function(FunctionSignature::scalar("UPPER", SqlType::Int64));
+        catalog.add_schema_function(
+            "geo",
+            FunctionSignature::scalar("DISTANCE", SqlType::Float64),
+        );
+
+        // The default schema shadows built-ins; qualifying reaches them
+        let upper = catalog.resolve_function(&name(&["upper"])).unwrap();
+        assert_eq!(upper.unwrap().return_type, SqlType::Int64);
+        let upper = catalog
+            .resolve_function(&name(&["BUILTIN", "upper"]))
+            .unwrap();
+        assert_ne!(upper.unwrap().return_type, SqlType::Int64);
+
+        // Functions outside the search path need qualifying
+        assert!(catalog
+            .resolve_function(&name(&["distance"]))
+            .unwrap()
+            .is_none());
+        assert!(catalog
+            .resolve_function(&name(&["geo", "distance"]))
+            .unwrap()
+            .is_some());
+        assert!(catalog
+            .resolve_function(&name(&["geo", "upper"]))
+            .unwrap()
+            .is_none());
+        catalog.set_function_search_path(["geo", BUILTIN_SCHEMA]);
+        assert!(catalog
+            .resolve_function(&name(&["distance"]))
+            .unwrap()
+            .is_some());
+        let upper = catalog.resolve_function(&name(&["upper"])).unwrap();
+        assert_ne!(upper.unwrap().return_type, SqlType::Int64);
+
+        let schemas = |f: &str| -> Vec<String> {
+            catalog
+                .list_functions()
+                .unwrap()
+                .into_iter()
+                .filter(|info| info.signature.name == f)
+                .map(|info| info.schema)
+                .collect()
+        };
+        assert_eq!(schemas("UPPER"), [BUILTIN_SCHEMA, "default"]);
+
+        let stmts = crate::Parser::new("SELECT nope(1); SELECT geo.nope(1)")
+            .parse()
+            .unwrap();
+        let mut analyzer = crate::Analyzer::with_catalog(&catalog);
+        let err = analyzer.analyze(&stmts[0]).unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with("function 'NOPE' not found (searched geo, builtin)"),
+            "{}",
+            err
+        );
+        let err = analyzer.analyze(&stmts[1]).unwrap_err();
+        assert!(err.to_string().ends_with("(searched geo)"), "{}", err);
+    }
+
+    #[test]
+    fn test_create_function_in_schema() {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        let stmts = crate::Parser::new(
+            "CREATE FUNCTION util.twice(x INT64) AS (x * 2); \
+             CREATE FUNCTION upper(x STRING) AS (LENGTH(x)); \
+             CREATE FUNCTION builtin.lower(x STRING) AS (x)",
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(catalog.apply(&stmts[0]).unwrap(), ApplyOutcome::Created);
+        assert_eq!(catalog.apply(&stmts[1]).unwrap(), ApplyOutcome::Created);
+        assert!(catalog.apply(&stmts[2]).is_err());
+
+        let twice = ["UTIL".to_string(), "twice".to_string()];
+        assert!(catalog.resolve_function(&twice).unwrap().is_some());
+        let upper = catalog.resolve_function(&["upper".to_string()]).unwrap();
+        assert_eq!(upper.unwrap().return_type, SqlType::Int64);
+    }
+
+    #[test]
+    fn test_identifier_matching() {
+        let mut catalog = MemoryCatalog::new();
+        catalog.add_table(TableSchema::new("caf\u{e9}", vec![]));
+        catalog.add_table(TableSchema::new("Users", vec![]));
+
+        let nfd = ["cafe\u{301}".to_string()];
+        let upper = ["USERS".to_string()];
+
+        catalog.set_identifier_matching(IdentifierMatching::Exact);
+        assert!(catalog.resolve_table(&nfd).unwrap().is_none());
+        assert!(catalog.resolve_table(&upper).unwrap().is_none());
+
+        catalog.set_identifier_matching(IdentifierMatching::AsciiCaseInsensitive);
+        assert!(catalog.resolve_table(&nfd).unwrap().is_none());
+        assert!(catalog.resolve_table(&upper).unwrap().is_some());
+
+        catalog.set_identifier_matching(IdentifierMatching::UnicodeCaseFold);
+        let table = catalog.resolve_table(&nfd).unwrap().unwrap();
+        assert_eq!(table.name, "caf\u{e9}");
+        assert!(catalog
+            .resolve_table(&["DEFAULT".to_string(), "users".to_string()])
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_mixed_case_schema_and_table() {
+        let mut catalog = MemoryCatalog::new();
+        catalog.add_schema("Sales").tables.insert(
+            "Orders".to_string(),
+            TableSchema::new("Orders", vec![]).into(),
+        );
+
+        let unquoted = |parts: &[&str]| -> Vec<Ident> {
+            parts
+                .iter()
+                .map(|p| Ident::new(*p, Span::default()))
+                .collect()
+        };
+        let quoted = |parts: &[&str]| -> Vec<Ident> {
+            parts
+                .iter()
+                .map(|p| Ident::quoted(*p, Span::default()))
+                .collect()
+        };
+
+        for name in [
+            ["sales", "orders"],
+            ["SALES", "Orders"],
+            ["Sales", "ORDERS"],
+        ] {
+            let parts: Vec<String> = name.iter().map(|p| p.to_string()).collect();
+            assert!(
+                catalog.resolve_table(&parts).unwrap().is_some(),
+                "{:?}",
+                name
+            );
+            assert!(catalog
+                .resolve_table_ident(&unquoted(&name))
+                .unwrap()
+                .is_some());
+        }
+        assert!(catalog
+            .resolve_table_ident(&quoted(&["Sales", "Orders"]))
+            .unwrap()
+            .is_some());
+        assert!(catalog
+            .resolve_table_ident(&quoted(&["sales", "Orders"]))
+            .unwrap()
+            .is_none());
+        assert!(catalog
+            .resolve_table_ident(&quoted(&["Sales", "orders"]))
+            .unwrap()
+            .is_none());
+        assert_eq!(catalog.list_tables(Some("SALES")).unwrap(), ["Orders"]);
+    }
+
+    #[test]
+    fn test_tables_differing_only_in_case() {
+        let mut catalog = MemoryCatalog::new();
+        let stmts =
+            crate::Parser::new("CREATE TABLE `Users` (a INT64); CREATE TABLE `users` (b INT64)")
+                .parse()
+                .unwrap();
+        for stmt in &stmts {
+            assert_eq!(catalog.apply(stmt).unwrap(), ApplyOutcome::Created);
+        }
+
+        let resolve = |ident: Ident| {
+            catalog
+                .resolve_table_ident(&[ident])
+                .unwrap()
+                .map(|t| t.columns[0].name.clone())
+        };
+        assert_eq!(
+            resolve(Ident::quoted("Users", Span::default())),
+            Some("a".into())
+        );
+        assert_eq!(
+            resolve(Ident::quoted("users", Span::default())),
+            Some("b".into())
+        );
+        assert_eq!(resolve(Ident::quoted("USERS", Span::default())), None);
+        // An exact match wins over the others
+        assert_eq!(
+            resolve(Ident::new("users", Span::default())),
+            Some("b".into())
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_table_name() {
+        let mut catalog = MemoryCatalog::new();
+        catalog.add_table(TableSchema::new("Foo", vec![]));
+        catalog.add_table(TableSchema::new("FOO", vec![]));
+
+        let err = catalog
+            .resolve_table_ident(&[Ident::new("foo", Span::new(14, 17))])
+            .unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &crate::error::ErrorKind::AmbiguousName {
+                name: "foo".to_string(),
+                candidates: vec!["FOO".to_string(), "Foo".to_string()],
+            }
+        );
+        assert_eq!(err.span(), Some(Span::new(14, 17)));
+
+        let stmt = crate::Parser::new("SELECT * FROM foo")
+            .parse_statement()
+            .unwrap();
+        let err = crate::Analyzer::with_catalog(catalog.clone())
+            .analyze(&stmt)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("ambiguous table 'foo' could be any of: FOO, Foo"),
+            "{}",
+            err
+        );
+        assert_eq!(err.span(), Some(Span::new(14, 17)));
+
+        // Dropping one of them leaves the other as the only match
+        let drop = crate::Parser::new("DROP TABLE `FOO`")
+            .parse_statement()
+            .unwrap();
+        catalog.apply(&drop).unwrap();
+        let table = catalog.resolve_table(&["foo".to_string()]).unwrap();
+        assert_eq!(table.unwrap().name, "Foo");
+    }
 }
@@ -0,0 +1,269 @@
+//! Maps keyed by stored names that resolve SQL identifiers.
+//!
+//! A [`NameMap`] keeps, next to its entries, an index from each stored
+//! name's comparison key under the case-insensitive [`IdentifierMatching`]
+//! policies, so resolving an identifier costs a hash lookup rather than a
+//! scan of every name.
+
+use crate::ast::{Ident, IdentifierMatching};
+use crate::error::{Error, ErrorKind, Result};
+use std::collections::HashMap;
+use std::ops::Index;
+
+/// A map from stored names to entries, indexed for identifier lookups.
+///
+/// Entries are keyed by the name they were stored under, as with a
+/// `HashMap`. [`NameMap::resolve`] finds the name an identifier refers to
+/// under a matching policy.
+#[derive(Debug, Clone)]
+pub struct NameMap<V> {
+    entries: HashMap<String, V>,
+    /// Stored names by their [`IdentifierMatching::AsciiCaseInsensitive`]
+    /// key.
+    ascii: HashMap<String, Vec<String>>,
+    /// Stored names by their [`IdentifierMatching::UnicodeCaseFold`] key.
+    folded: HashMap<String, Vec<String>>,
+}
+
+impl<V> Default for NameMap<V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            ascii: HashMap::new(),
+            folded: HashMap::new(),
+        }
+    }
+}
+
+impl<V> NameMap<V> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store an entry under a name, returning the entry it replaced.
+    pub fn insert(&mut self, name: String, value: V) -> Option<V> {
+        if !self.entries.contains_key(&name) {
+            self.index_name(&name);
+        }
+        self.entries.insert(name, value)
+    }
+
+    /// Remove the entry stored under a name, returning it.
+    pub fn remove(&mut self, name: &str) -> Option<V> {
+        let value = self.entries.remove(name)?;
+        unindex(
+            &mut self.ascii,
+            IdentifierMatching::AsciiCaseInsensitive,
+            name,
+        );
+        unindex(&mut self.folded, IdentifierMatching::UnicodeCaseFold, name);
+        Some(value)
+    }
+
+    /// Get the entry stored under a name, creating it if there is none.
+    pub fn get_or_insert_with(&mut self, name: String, f: impl FnOnce() -> V) -> &mut V {
+        if !self.entries.contains_key(&name) {
+            self.index_name(&name);
+        }
+        self.entries.entry(name).or_insert_with(f)
+    }
+
+    /// Get the entry stored under exactly this name.
+    pub fn get(&self, name: &str) -> Option<&V> {
+        self.entries.get(name)
+    }
+
+    /// Get mutable access to the entry stored under exactly this name.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut V> {
+        self.entries.get_mut(name)
+    }
+
+    /// Check whether an entry is stored under exactly this name.
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Find the stored name an identifier refers to.
+    ///
+    /// A name equal to a stored name always refers to it. Otherwise an
+    /// unquoted identifier matches stored names under the policy, while a
+    /// quoted one must be equal apart from Unicode normalization. An
+    /// identifier matching several stored names, none of them exactly,
+    /// fails with [`ErrorKind::AmbiguousName`] listing them; quoting it
+    /// picks one.
+    pub fn resolve(&self, name: &Ident, matching: IdentifierMatching) -> Result<Option<&String>> {
+        if let Some((key, _)) = self.entries.get_key_value(&name.value) {
+            return Ok(Some(key));
+        }
+        let index = match matching {
+            IdentifierMatching::Exact => return Ok(None),
+            IdentifierMatching::AsciiCaseInsensitive => &self.ascii,
+            IdentifierMatching::UnicodeCaseFold => &self.folded,
+        };
+        let Some(names) = index.get(&matching.normalize(&name.value)) else {
+            return Ok(None);
+        };
+        let mut found: Vec<&String> = names
+            .iter()
+            .filter(|stored| name.matches_with(stored, matching))
+            .collect();
+        match found.len() {
+            0 => Ok(None),
+            1 => Ok(found.pop()),
+            _ => {
+                let mut candidates: Vec<String> = found.into_iter().cloned().collect();
+                candidates.sort();
+                Err(Error::with_span(
+                    ErrorKind::AmbiguousName {
+                        name: name.value.clone(),
+                        candidates,
+                    },
+                    name.span,
+                ))
+            }
+        }
+    }
+
+    /// The stored names, in arbitrary order.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.keys()
+    }
+
+    /// The entries, in arbitrary order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.values()
+    }
+
+    /// The stored names and their entries, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &V)> {
+        self.entries.iter()
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn index_name(&mut self, name: &str) {
+        for (index, matching) in [
+            (&mut self.ascii, IdentifierMatching::AsciiCaseInsensitive),
+            (&mut self.folded, IdentifierMatching::UnicodeCaseFold),
+        ] {
+            index
+                .entry(matching.normalize(name))
+                .or_default()
+                .push(name.to_string());
+        }
+    }
+}
+
+/// Remove a stored name from one policy's index.
+fn unindex(index: &mut HashMap<String, Vec<String>>, matching: IdentifierMatching, name: &str) {
+    let key = matching.normalize(name);
+    if let Some(names) = index.get_mut(&key) {
+        names.retain(|stored| stored != name);
+        if names.is_empty() {
+            index.remove(&key);
+        }
+    }
+}
+
+impl<V> Index<&str> for NameMap<V> {
+    type Output = V;
+
+    fn index(&self, name: &str) -> &V {
+        &self.entries[name]
+    }
+}
+
+impl<V> FromIterator<(String, V)> for NameMap<V> {
+    fn from_iter<I: IntoIterator<Item = (String, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (name, value) in iter {
+            map.insert(name, value);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Span;
+
+    fn unquoted(name: &str) -> Ident {
+        Ident::new(name, Span::default())
+    }
+
+    #[test]
+    fn test_resolve() {
+        let map: NameMap<i32> = [("Users".to_string(), 1), ("Straße".to_string(), 2)]
+            .into_iter()
+            .collect();
+        let ascii = IdentifierMatching::AsciiCaseInsensitive;
+        let folded = IdentifierMatching::UnicodeCaseFold;
+
+        assert_eq!(
+            map.resolve(&unquoted("USERS"), ascii).unwrap(),
+            Some(&"Users".to_string())
+        );
+        assert_eq!(
+            map.resolve(&unquoted("STRAßE"), folded).unwrap(),
+            Some(&"Straße".to_string())
+        );
+        assert_eq!(
+            map.resolve(&unquoted("USERS"), IdentifierMatching::Exact)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            map.resolve(&Ident::quoted("USERS", Span::default()), ascii)
+                .unwrap(),
+            None
+        );
+        assert_eq!(map.resolve(&unquoted("orders"), ascii).unwrap(), None);
+    }
+
+    #[test]
+    fn test_resolve_ambiguous() {
+        let mut map: NameMap<i32> = [("Foo".to_string(), 1), ("FOO".to_string(), 2)]
+            .into_iter()
+            .collect();
+        let ascii = IdentifierMatching::AsciiCaseInsensitive;
+
+        let err = map.resolve(&unquoted("foo"), ascii).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ErrorKind::AmbiguousName {
+                name: "foo".to_string(),
+                candidates: vec!["FOO".to_string(), "Foo".to_string()],
+            }
+        );
+        // An exact or quoted name picks one of them.
+        assert_eq!(
+            map.resolve(&unquoted("Foo"), ascii).unwrap(),
+            Some(&"Foo".to_string())
+        );
+        let quoted = Ident::quoted("FOO", Span::default());
+        assert_eq!(
+            map.resolve(&quoted, ascii).unwrap(),
+            Some(&"FOO".to_string())
+        );
+
+        // Removing one leaves the other as the only match.
+        map.remove("FOO");
+        assert_eq!(
+            map.resolve(&unquoted("foo"), ascii).unwrap(),
+            Some(&"Foo".to_string())
+        );
+        map.remove("Foo");
+        assert_eq!(map.resolve(&unquoted("foo"), ascii).unwrap(), None);
+        assert!(map.ascii.is_empty() && map.folded.is_empty());
+    }
+}
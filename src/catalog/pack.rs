@@ -0,0 +1,257 @@
+//! Function packs: named bundles of functions registered together.
+//!
+//! A [`FunctionPack`] groups related [`FunctionSignature`]s (and optionally
+//! type aliases) under a name, so services that share a set of extension
+//! functions can register them into every catalog with one call instead of
+//! copying registration lists around.
+//!
+//! ```
+//! use vibesql::catalog::{Catalog, CatalogBuilder, StatsPack};
+//!
+//! let catalog = CatalogBuilder::new()
+//!     .with_builtins()
+//!     .with_pack(&StatsPack::new())
+//!     .unwrap()
+//!     .build();
+//!
+//! assert_eq!(catalog.packs(), ["stats"]);
+//! assert_eq!(catalog.function_pack("MEDIAN"), Some("stats"));
+//! ```
+//!
+//! # Publishing packs
+//!
+//! Packs are plain trait implementations, so a third-party crate only needs
+//! to depend on `vibesql-rs` and export a type implementing [`FunctionPack`]:
+//!
+//! ```
+//! use vibesql::catalog::{FunctionPack, FunctionParameter, FunctionSignature};
+//! use vibesql::types::SqlType;
+//!
+//! /// Geospatial functions.
+//! pub struct GeoPack;
+//!
+//! impl FunctionPack for GeoPack {
+//!     fn name(&self) -> &str {
+//!         "geo"
+//!     }
+//!
+//!     fn functions(&self) -> Vec<FunctionSignature> {
+//!         vec![FunctionSignature::scalar("ST_DISTANCE", SqlType::Float64)
+//!             .with_param(FunctionParameter::new("a", SqlType::Varbinary(None)))
+//!             .with_param(FunctionParameter::new("b", SqlType::Varbinary(None)))
+//!             .with_args(2)]
+//!     }
+//!
+//!     fn type_aliases(&self) -> Vec<(String, SqlType)> {
+//!         vec![("GEOGRAPHY".to_string(), SqlType::Varbinary(None))]
+//!     }
+//! }
+//! ```
+//!
+//! Consumers then call `CatalogBuilder::with_pack(&GeoPack)` or
+//! [`MemoryCatalog::register_pack`]. Keep pack names stable: they appear in
+//! conflict errors and in [`FunctionInfo::pack`].
+
+use super::{FunctionParameter, FunctionSignature, MemoryCatalog};
+use crate::error::{Error, ErrorKind, Result};
+use crate::types::SqlType;
+
+/// A named collection of functions and type aliases.
+pub trait FunctionPack {
+    /// The pack name, reported in introspection and conflict errors.
+    fn name(&self) -> &str;
+
+    /// The functions provided by this pack.
+    fn functions(&self) -> Vec<FunctionSignature>;
+
+    /// Type aliases provided by this pack, as `(alias, type)` pairs.
+    fn type_aliases(&self) -> Vec<(String, SqlType)> {
+        Vec::new()
+    }
+}
+
+/// Function metadata returned by [`Catalog::list_functions`](super::Catalog::list_functions).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionInfo {
+    /// The function signature.
+    pub signature: FunctionSignature,
+    /// The schema the function is registered in.
+    pub schema: String,
+    /// The pack that registered the function, if any.
+    pub pack: Option<String>,
+}
+
+/// Statistical aggregate functions: MEDIAN, MODE, and PERCENTILE.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StatsPack;
+
+impl StatsPack {
+    /// Create the pack.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl FunctionPack for StatsPack {
+    fn name(&self) -> &str {
+        "stats"
+    }
+
+    fn functions(&self) -> Vec<FunctionSignature> {
+        vec![
+            FunctionSignature::aggregate("MEDIAN", SqlType::Float64)
+                .with_param(FunctionParameter::new("value", SqlType::Float64))
+                .with_args(1),
+            FunctionSignature::aggregate("MODE", SqlType::Any)
+                .with_param(FunctionParameter::any("value"))
+                .with_args(1),
+            FunctionSignature::aggregate("PERCENTILE", SqlType::Float64)
+                .with_param(FunctionParameter::new("value", SqlType::Float64))
+                .with_param(FunctionParameter::new("fraction", SqlType::Float64))
+                .with_args(2),
+        ]
+    }
+}
+
+impl MemoryCatalog {
+    /// Register every function in a pack.
+    ///
+    /// Fails without registering anything if another pack already defines a
+    /// function with the same name and a different signature. Registering
+    /// an identical signature again is allowed. The pack's type aliases are
+    /// added to the catalog's type registry.
+    pub fn register_pack(&mut self, pack: &dyn FunctionPack) -> Result<()> {
+        let functions = pack.functions();
+        for func in &functions {
+            let Some(owner) = self.function_packs.get(&func.name) else {
+                continue;
+            };
+            if owner != pack.name()
+                && self
+                    .functions
+                    .get("default")
+                    .and_then(|functions| functions.get(&func.name))
+                    .map(Vec::as_slice)
+                    != Some(std::slice::from_ref(func))
+            {
+                return Err(Error::new(ErrorKind::FunctionPackConflict {
+                    function: func.name.clone(),
+                    packs: Box::new((owner.clone(), pack.name().to_string())),
+                }));
+            }
+        }
+
+        for func in functions {
+            let name = func.name.clone();
+            self.add_function(func);
+            self.function_packs.insert(name, pack.name().to_string());
+        }
+        for (alias, sql_type) in pack.type_aliases() {
+            self.type_registry.add_alias(alias, sql_type);
+        }
+        if !self.packs.iter().any(|p| p == pack.name()) {
+            self.packs.push(pack.name().to_string());
+        }
+        Ok(())
+    }
+
+    /// Names of the packs registered in this catalog, in registration order.
+    pub fn packs(&self) -> &[String] {
+        &self.packs
+    }
+
+    /// The pack that registered a function, if any.
+    pub fn function_pack(&self, name: &str) -> Option<&str> {
+        self.function_packs
+            .get(&name.to_uppercase())
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{Catalog, CatalogBuilder};
+
+    struct OtherStats;
+
+    impl FunctionPack for OtherStats {
+        fn name(&self) -> &str {
+            "other_stats"
+        }
+
+        fn functions(&self) -> Vec<FunctionSignature> {
+            vec![FunctionSignature::aggregate("MEDIAN", SqlType::Int64).with_args(1)]
+        }
+
+        fn type_aliases(&self) -> Vec<(String, SqlType)> {
+            vec![("SCORE".to_string(), SqlType::Float64)]
+        }
+    }
+
+    #[test]
+    fn test_register_stats_pack() {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        catalog.register_pack(&StatsPack::new()).unwrap();
+
+        let median = catalog
+            .resolve_function(&["median".to_string()])
+            .unwrap()
+            .unwrap();
+        assert!(median.is_aggregate);
+        assert_eq!(median.parameters.len(), 1);
+        assert_eq!(catalog.packs(), ["stats"]);
+        assert_eq!(catalog.function_pack("percentile"), Some("stats"));
+        assert_eq!(catalog.function_pack("COUNT"), None);
+
+        // Registering the same pack again is a no-op.
+        catalog.register_pack(&StatsPack::new()).unwrap();
+        assert_eq!(catalog.packs(), ["stats"]);
+    }
+
+    #[test]
+    fn test_pack_conflict_names_both_packs() {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_pack(&StatsPack::new()).unwrap();
+
+        let err = catalog.register_pack(&OtherStats).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("MEDIAN"), "{}", message);
+        assert!(message.contains("'stats'"), "{}", message);
+        assert!(message.contains("'other_stats'"), "{}", message);
+
+        // Nothing from the conflicting pack was registered.
+        assert_eq!(catalog.packs(), ["stats"]);
+        assert_eq!(catalog.function_pack("MEDIAN"), Some("stats"));
+    }
+
+    #[test]
+    fn test_builder_with_pack() {
+        let (catalog, registry) = CatalogBuilder::new()
+            .with_builtins()
+            .with_pack(&OtherStats)
+            .unwrap()
+            .build_with_registry();
+
+        assert_eq!(registry.resolve("SCORE"), Some(&SqlType::Float64));
+        let functions = catalog.list_functions().unwrap();
+        let median = functions
+            .iter()
+            .find(|f| f.signature.name == "MEDIAN")
+            .unwrap();
+        assert_eq!(median.pack.as_deref(), Some("other_stats"));
+        assert_eq!(median.signature.return_type, SqlType::Int64);
+        let count = functions
+            .iter()
+            .find(|f| f.signature.name == "COUNT")
+            .unwrap();
+        assert_eq!(count.pack, None);
+
+        assert!(CatalogBuilder::new()
+            .with_pack(&StatsPack::new())
+            .unwrap()
+            .with_pack(&OtherStats)
+            .is_err());
+    }
+}
@@ -1,23 +1,138 @@
 //! Schema definitions for tables and columns.
 
+use crate::ast::ReferentialAction;
 use crate::types::SqlType;
 
 /// Schema information for a table.
+///
+/// The primary key is kept in step with the columns'
+/// [`ColumnSchema::is_primary_key`] flags: [`TableSchema::new`] takes it
+/// from the flagged columns in column order, and
+/// [`TableSchema::with_primary_key`] sets the flags.
 #[derive(Debug, Clone, PartialEq)]
 pub struct TableSchema {
     /// Table name.
     pub name: String,
     /// Columns in the table.
     pub columns: Vec<ColumnSchema>,
+    /// Primary key columns, in key order.
+    pub(crate) primary_key: Vec<String>,
+    /// UNIQUE constraints.
+    pub(crate) unique_constraints: Vec<UniqueConstraint>,
+    /// FOREIGN KEY constraints.
+    pub(crate) foreign_keys: Vec<ForeignKey>,
 }
 
 impl TableSchema {
     /// Create a new table schema.
+    ///
+    /// Columns marked with [`ColumnSchema::primary_key`] form the primary
+    /// key, in column order.
     pub fn new(name: impl Into<String>, columns: Vec<ColumnSchema>) -> Self {
+        let primary_key = columns
+            .iter()
+            .filter(|c| c.is_primary_key)
+            .map(|c| c.name.clone())
+            .collect();
         Self {
             name: name.into(),
             columns,
+            primary_key,
+            unique_constraints: Vec::new(),
+            foreign_keys: Vec::new(),
+        }
+    }
+
+    /// Set the primary key columns, in key order.
+    ///
+    /// The named columns become primary key columns and NOT NULL, and
+    /// other columns stop being primary key columns. An empty list removes
+    /// the primary key.
+    pub fn with_primary_key<S: Into<String>>(
+        mut self,
+        columns: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.set_primary_key(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Add a UNIQUE constraint.
+    pub fn with_unique(mut self, constraint: UniqueConstraint) -> Self {
+        self.unique_constraints.push(constraint);
+        self
+    }
+
+    /// Add a FOREIGN KEY constraint.
+    pub fn with_foreign_key(mut self, foreign_key: ForeignKey) -> Self {
+        self.foreign_keys.push(foreign_key);
+        self
+    }
+
+    /// The primary key columns in key order, empty if the table has no
+    /// primary key.
+    ///
+    /// ```
+    /// use vibesql::catalog::TableSchemaBuilder;
+    /// use vibesql::types::SqlType;
+    ///
+    /// let table = TableSchemaBuilder::new("order_lines")
+    ///     .add_column("line", SqlType::Int32)
+    ///     .add_column("order_id", SqlType::Int64)
+    ///     .primary_key(["order_id", "line"])
+    ///     .build();
+    /// assert_eq!(table.primary_key(), ["order_id", "line"]);
+    /// assert!(!table.get_column("line").unwrap().nullable);
+    /// ```
+    pub fn primary_key(&self) -> &[String] {
+        &self.primary_key
+    }
+
+    /// The table's UNIQUE constraints.
+    pub fn unique_constraints(&self) -> &[UniqueConstraint] {
+        &self.unique_constraints
+    }
+
+    /// The table's FOREIGN KEY constraints.
+    pub fn foreign_keys(&self) -> &[ForeignKey] {
+        &self.foreign_keys
+    }
+
+    pub(crate) fn set_primary_key(&mut self, columns: Vec<String>) {
+        for column in &mut self.columns {
+            column.is_primary_key = columns.iter().any(|k| k.eq_ignore_ascii_case(&column.name));
+            if column.is_primary_key {
+                column.nullable = false;
+            }
+        }
+        self.primary_key = columns;
+    }
+
+    /// Rename a column in the table's constraints.
+    pub(crate) fn rename_constraint_column(&mut self, from: &str, to: &str) {
+        let rename = |columns: &mut Vec<String>| {
+            for column in columns.iter_mut() {
+                if column.eq_ignore_ascii_case(from) {
+                    *column = to.to_string();
+                }
+            }
+        };
+        rename(&mut self.primary_key);
+        for unique in &mut self.unique_constraints {
+            rename(&mut unique.columns);
+        }
+        for foreign_key in &mut self.foreign_keys {
+            rename(&mut foreign_key.columns);
+        }
+    }
+
+    /// Drop the constraints that include a column.
+    pub(crate) fn drop_constraint_column(&mut self, name: &str) {
+        let includes = |columns: &[String]| columns.iter().any(|c| c.eq_ignore_ascii_case(name));
+        if includes(&self.primary_key) {
+            self.set_primary_key(Vec::new());
         }
+        self.unique_constraints.retain(|u| !includes(&u.columns));
+        self.foreign_keys.retain(|f| !includes(&f.columns));
     }
 
     /// Get a column by name (case-insensitive).
@@ -101,11 +216,80 @@ impl ColumnSchema {
     }
 }
 
+/// A UNIQUE constraint over one or more columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniqueConstraint {
+    /// Constraint name, if given.
+    pub name: Option<String>,
+    /// Columns that are unique together.
+    pub columns: Vec<String>,
+}
+
+impl UniqueConstraint {
+    /// Create an unnamed UNIQUE constraint.
+    pub fn new<S: Into<String>>(columns: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            name: None,
+            columns: columns.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A FOREIGN KEY constraint referencing another table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignKey {
+    /// Constraint name, if given.
+    pub name: Option<String>,
+    /// Referencing columns in this table.
+    pub columns: Vec<String>,
+    /// Name parts of the referenced table, such as `["sales", "orders"]`.
+    pub referenced_table: Vec<String>,
+    /// Referenced columns, parallel to `columns`.
+    pub referenced_columns: Vec<String>,
+    /// Action on a deleted referenced row, if given.
+    pub on_delete: Option<ReferentialAction>,
+    /// Action on an updated referenced row, if given.
+    pub on_update: Option<ReferentialAction>,
+}
+
+impl ForeignKey {
+    /// Create an unnamed FOREIGN KEY constraint without actions.
+    ///
+    /// ```
+    /// use vibesql::catalog::ForeignKey;
+    ///
+    /// let fk = ForeignKey::new(["customer_id"], ["customers"], ["id"]);
+    /// assert_eq!(fk.referenced_table, ["customers"]);
+    /// ```
+    pub fn new<C, T, R>(
+        columns: impl IntoIterator<Item = C>,
+        referenced_table: impl IntoIterator<Item = T>,
+        referenced_columns: impl IntoIterator<Item = R>,
+    ) -> Self
+    where
+        C: Into<String>,
+        T: Into<String>,
+        R: Into<String>,
+    {
+        Self {
+            name: None,
+            columns: columns.into_iter().map(Into::into).collect(),
+            referenced_table: referenced_table.into_iter().map(Into::into).collect(),
+            referenced_columns: referenced_columns.into_iter().map(Into::into).collect(),
+            on_delete: None,
+            on_update: None,
+        }
+    }
+}
+
 /// Builder for creating table schemas.
 #[derive(Debug, Default)]
 pub struct TableSchemaBuilder {
     name: String,
     columns: Vec<ColumnSchema>,
+    primary_key: Option<Vec<String>>,
+    unique_constraints: Vec<UniqueConstraint>,
+    foreign_keys: Vec<ForeignKey>,
 }
 
 impl TableSchemaBuilder {
@@ -113,7 +297,7 @@ impl TableSchemaBuilder {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
-            columns: Vec::new(),
+            ..Self::default()
         }
     }
 
@@ -129,12 +313,34 @@ impl TableSchemaBuilder {
         self
     }
 
+    /// Set the primary key columns, in key order, instead of taking them
+    /// from the columns marked as primary keys.
+    pub fn primary_key<S: Into<String>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.primary_key = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Add a UNIQUE constraint over the given columns.
+    pub fn unique<S: Into<String>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.unique_constraints.push(UniqueConstraint::new(columns));
+        self
+    }
+
+    /// Add a FOREIGN KEY constraint.
+    pub fn foreign_key(mut self, foreign_key: ForeignKey) -> Self {
+        self.foreign_keys.push(foreign_key);
+        self
+    }
+
     /// Build the table schema.
     pub fn build(self) -> TableSchema {
-        TableSchema {
-            name: self.name,
-            columns: self.columns,
+        let mut table = TableSchema::new(self.name, self.columns);
+        if let Some(primary_key) = self.primary_key {
+            table.set_primary_key(primary_key);
         }
+        table.unique_constraints = self.unique_constraints;
+        table.foreign_keys = self.foreign_keys;
+        table
     }
 }
 
@@ -188,8 +394,8 @@ mod tests {
     fn test_table_schema() {
         let table = TableSchemaBuilder::new("users")
             .column(ColumnSchema::new("id", SqlType::Int64).primary_key())
-            .column(ColumnSchema::new("name", SqlType::Varchar).not_null())
-            .column(ColumnSchema::new("email", SqlType::Varchar))
+            .column(ColumnSchema::new("name", SqlType::Varchar(None)).not_null())
+            .column(ColumnSchema::new("email", SqlType::Varchar(None)))
             .build();
 
         assert_eq!(table.name, "users");
@@ -0,0 +1,334 @@
+//! Session-scoped tables layered over a base catalog.
+//!
+//! A [`SessionCatalog`] keeps the tables and views a session creates, such
+//! as `CREATE TEMP TABLE` results, apart from the catalog it wraps, so a
+//! session can create and drop them without changing a catalog other
+//! sessions share.
+
+use super::{ApplyOutcome, Catalog, FunctionInfo, FunctionSignature, MemoryCatalog, TableSchema};
+use crate::ast::{Ident, IdentifierMatching, ObjectName, Statement, StatementKind};
+use crate::error::{Error, Result, Span};
+use crate::types::SqlType;
+use std::sync::Arc;
+
+/// Session-local tables and views over a base catalog.
+///
+/// Session tables shadow base tables of the same name, and everything
+/// else, including functions and types, comes from the base catalog. The
+/// base catalog is never changed.
+///
+/// ```
+/// use vibesql::catalog::{CatalogBuilder, SessionCatalog};
+/// use vibesql::types::SqlType;
+/// use vibesql::{Analyzer, Parser};
+///
+/// let base = CatalogBuilder::new()
+///     .with_builtins()
+///     .add_table("users", |t| t.column("id", SqlType::Int64))
+///     .build();
+/// let script = Parser::new(
+///     "CREATE TEMP TABLE active AS SELECT id FROM users WHERE id > 10; \
+///      SELECT COUNT(*) FROM active",
+/// )
+/// .parse()
+/// .unwrap();
+///
+/// let mut analyzer = Analyzer::with_catalog(SessionCatalog::new(&base));
+/// analyzer.analyze_script(&script).unwrap();
+/// assert!(analyzer.catalog().temp_table("active").is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionCatalog<C: Catalog> {
+    base: C,
+    /// The session's tables and views, kept in the default schema unless
+    /// they were created with a qualified name.
+    session: MemoryCatalog,
+}
+
+impl<C: Catalog> SessionCatalog<C> {
+    /// Start a session over a base catalog.
+    pub fn new(base: C) -> Self {
+        Self {
+            base,
+            session: MemoryCatalog::new(),
+        }
+    }
+
+    /// Get the base catalog.
+    pub fn base(&self) -> &C {
+        &self.base
+    }
+
+    /// End the session, returning the base catalog.
+    pub fn into_base(self) -> C {
+        self.base
+    }
+
+    /// Set the policy used to match the names of session tables.
+    pub fn set_identifier_matching(&mut self, matching: IdentifierMatching) {
+        self.session.set_identifier_matching(matching);
+    }
+
+    /// Add a session table, replacing any session table or view of the
+    /// same name and returning it.
+    pub fn create_temp_table(&mut self, table: TableSchema) -> Option<Arc<TableSchema>> {
+        let keys = ("default".to_string(), table.name.clone());
+        self.session.views.remove(&keys);
+        self.session
+            .add_schema(keys.0)
+            .tables
+            .insert(keys.1, Arc::new(table))
+    }
+
+    /// Add a session view from its output columns, as a CTE that lasts for
+    /// the session.
+    pub fn create_temp_view(&mut self, view: TableSchema) -> Option<Arc<TableSchema>> {
+        let keys = ("default".to_string(), view.name.clone());
+        let replaced = self.create_temp_table(view);
+        self.session.views.insert(keys);
+        replaced
+    }
+
+    /// Drop a session table or view, returning it. Base tables are never
+    /// dropped.
+    pub fn drop_temp_table(&mut self, name: &str) -> Option<Arc<TableSchema>> {
+        let name = Ident::new(name, Span::default());
+        let schema = self.session.schemas.get("default")?;
+        let key = self.session.lookup_key(&schema.tables, &name).ok()??;
+        self.session
+            .views
+            .remove(&("default".to_string(), key.clone()));
+        self.session.schemas.get_mut("default")?.tables.remove(&key)
+    }
+
+    /// Get a session table or view by name.
+    pub fn temp_table(&self, name: &str) -> Option<Arc<TableSchema>> {
+        self.session.resolve_table(&[name.to_string()]).ok()?
+    }
+
+    /// Check whether a name refers to a session view.
+    pub fn is_temp_view(&self, name: &str) -> bool {
+        self.session.is_view(&[name.to_string()])
+    }
+
+    /// Apply a `CREATE TABLE`, `CREATE VIEW`, or `DROP` to the session's
+    /// tables, as [`MemoryCatalog::apply`] does.
+    ///
+    /// Queries and `LIKE` sources resolve against both layers, while
+    /// existing names are only checked among session tables, so a session
+    /// table can shadow a base table. Dropping a table the session does
+    /// not hold is an [`ErrorKind::UndefinedTable`] error.
+    ///
+    /// [`ErrorKind::UndefinedTable`]: crate::error::ErrorKind::UndefinedTable
+    pub fn apply(&mut self, stmt: &Statement) -> Result<ApplyOutcome> {
+        let creation = match &stmt.kind {
+            StatementKind::CreateTable(create) => {
+                self.session.plan_create_table(create, &*self, stmt.span)?
+            }
+            StatementKind::CreateView(create) => {
+                self.session.plan_create_view(create, &*self, stmt.span)?
+            }
+            StatementKind::Drop(_) => return self.session.apply(stmt),
+            _ => {
+                return Err(Error::unsupported(
+                    "applying this statement to a session",
+                    stmt.span,
+                ))
+            }
+        };
+        Ok(self.session.create(creation))
+    }
+
+    /// Store the table or view a `CREATE TABLE` or `CREATE VIEW` defines,
+    /// given the schema the analyzer derived for it, honoring the
+    /// statement's OR REPLACE and IF NOT EXISTS.
+    pub(crate) fn create_derived(
+        &mut self,
+        stmt: &Statement,
+        table: TableSchema,
+    ) -> Result<ApplyOutcome> {
+        let (name, flags, view) = match &stmt.kind {
+            StatementKind::CreateTable(create) => (
+                &create.name,
+                (create.or_replace, create.if_not_exists),
+                false,
+            ),
+            StatementKind::CreateView(create) => (
+                &create.name,
+                (create.or_replace, create.if_not_exists),
+                true,
+            ),
+            _ => return self.apply(stmt),
+        };
+        let creation = self
+            .session
+            .plan_create(name, flags, view, stmt.span, |_| Ok(table))?;
+        Ok(self.session.create(creation))
+    }
+
+    /// Get the session table or view a name refers to.
+    pub(crate) fn session_table(&self, name: &ObjectName) -> Option<Arc<TableSchema>> {
+        self.session.resolve_table_ident(&name.parts).ok()?
+    }
+}
+
+impl<C: Catalog> Catalog for SessionCatalog<C> {
+    fn resolve_table(&self, name: &[String]) -> Result<Option<Arc<TableSchema>>> {
+        match self.session.resolve_table(name)? {
+            Some(table) => Ok(Some(table)),
+            None => self.base.resolve_table(name),
+        }
+    }
+
+    fn resolve_table_ident(&self, name: &[Ident]) -> Result<Option<Arc<TableSchema>>> {
+        match self.session.resolve_table_ident(name)? {
+            Some(table) => Ok(Some(table)),
+            None => self.base.resolve_table_ident(name),
+        }
+    }
+
+    fn resolve_function(&self, name: &[String]) -> Result<Option<FunctionSignature>> {
+        self.base.resolve_function(name)
+    }
+
+    fn resolve_function_overloads(&self, name: &[String]) -> Result<Vec<FunctionSignature>> {
+        self.base.resolve_function_overloads(name)
+    }
+
+    fn resolve_type(&self, name: &[String]) -> Result<Option<SqlType>> {
+        self.base.resolve_type(name)
+    }
+
+    fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>> {
+        let mut tables = self.base.list_tables(schema)?;
+        for table in self.session.list_tables(schema)? {
+            if !tables.contains(&table) {
+                tables.push(table);
+            }
+        }
+        Ok(tables)
+    }
+
+    fn list_schemas(&self) -> Result<Vec<String>> {
+        let mut schemas = self.base.list_schemas()?;
+        for schema in self.session.list_schemas()? {
+            if !schemas.contains(&schema) {
+                schemas.push(schema);
+            }
+        }
+        Ok(schemas)
+    }
+
+    fn list_functions(&self) -> Result<Vec<FunctionInfo>> {
+        self.base.list_functions()
+    }
+
+    fn default_schema(&self) -> &str {
+        self.base.default_schema()
+    }
+
+    fn function_search_schemas(&self, name: &[String]) -> Result<Vec<String>> {
+        self.base.function_search_schemas(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::Analyzer;
+    use crate::catalog::{CatalogBuilder, ColumnSchema};
+    use crate::parser::Parser;
+
+    fn parse(sql: &str) -> Vec<Statement> {
+        Parser::new(sql).parse().unwrap()
+    }
+
+    fn base() -> MemoryCatalog {
+        CatalogBuilder::new()
+            .with_builtins()
+            .add_table("users", |t| {
+                t.primary_key("id", SqlType::Int64)
+                    .column("name", SqlType::Varchar(None))
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_temp_table_shadows_base_table() {
+        let base = base();
+        let mut session = SessionCatalog::new(&base);
+        session.create_temp_table(TableSchema::new(
+            "USERS",
+            vec![ColumnSchema::new("score", SqlType::Float64)],
+        ));
+        session.create_temp_view(TableSchema::new(
+            "recent",
+            vec![ColumnSchema::new("id", SqlType::Int64)],
+        ));
+        assert!(session.is_temp_view("recent"));
+        assert!(!session.is_temp_view("users"));
+
+        let mut tables = session.list_tables(None).unwrap();
+        tables.sort();
+        assert_eq!(tables, ["USERS", "recent", "users"]);
+
+        let mut analyzer = Analyzer::with_catalog(&session);
+        analyzer
+            .analyze(&parse("SELECT score FROM users")[0])
+            .unwrap();
+        assert!(analyzer
+            .analyze(&parse("SELECT name FROM users")[0])
+            .is_err());
+
+        assert!(session.drop_temp_table("users").is_some());
+        assert!(session.drop_temp_table("users").is_none());
+        let mut analyzer = Analyzer::with_catalog(&session);
+        analyzer
+            .analyze(&parse("SELECT name FROM users")[0])
+            .unwrap();
+        assert_eq!(base.list_tables(None).unwrap(), ["users"]);
+    }
+
+    #[test]
+    fn test_analyze_script() {
+        let base = base();
+        let mut analyzer = Analyzer::with_catalog(SessionCatalog::new(&base));
+        analyzer
+            .analyze_script(&parse(
+                "CREATE TEMP TABLE users AS SELECT id, UPPER(name) AS shout FROM users; \
+                 CREATE TEMP TABLE scores (user_id INT64 NOT NULL, score FLOAT64); \
+                 CREATE VIEW top AS SELECT user_id FROM scores WHERE score > 9; \
+                 SELECT u.shout, s.score FROM users u JOIN scores s ON s.user_id = u.id; \
+                 INSERT INTO scores SELECT user_id, 1.0 FROM top; \
+                 DROP TABLE scores",
+            ))
+            .unwrap();
+
+        let session = analyzer.catalog();
+        let users = session.temp_table("users").unwrap();
+        assert_eq!(users.columns[1].name, "shout");
+        assert!(session.temp_table("scores").is_none());
+        assert!(session.is_temp_view("top"));
+        assert!(base.resolve_table(&["top".to_string()]).unwrap().is_none());
+
+        // Later statements see what earlier ones dropped
+        let err = analyzer
+            .analyze_script(&parse("DROP VIEW top; SELECT * FROM top"))
+            .unwrap_err();
+        assert!(err.to_string().contains("top"), "{}", err);
+
+        // Dropping a base table leaves it alone; creating a session table
+        // twice fails
+        let err = analyzer
+            .analyze_script(&parse(
+                "DROP TABLE users; DROP TABLE users; \
+                 CREATE TEMP TABLE t (x INT64); CREATE TEMP TABLE t (y INT64)",
+            ))
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{}", err);
+        assert!(base
+            .resolve_table(&["users".to_string()])
+            .unwrap()
+            .is_some());
+    }
+}
@@ -0,0 +1,225 @@
+//! A catalog shared between threads.
+//!
+//! [`Catalog`] is implemented for `Arc<C>`, so a catalog that never changes
+//! can be shared by wrapping it in an `Arc` and giving each analyzer a
+//! clone of the pointer. [`SharedCatalog`] adds a lock for catalogs that
+//! change while they are in use, such as a server applying DDL from one
+//! connection while analyzing queries from others.
+
+use super::{ApplyOutcome, Catalog, FunctionInfo, FunctionSignature, MemoryCatalog, TableSchema};
+use crate::ast::{Ident, Statement};
+use crate::error::Result;
+use crate::types::SqlType;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A [`MemoryCatalog`] behind a read-write lock.
+///
+/// Cloning a `SharedCatalog` is cheap and shares the catalog. Each lookup
+/// holds the read lock for that call only, and resolved tables are shared
+/// `Arc`s, so an analysis keeps the tables it resolved even if DDL replaces
+/// them before it finishes. DDL applied through [`SharedCatalog::apply`] or
+/// [`SharedCatalog::write`] is seen by lookups that start after it.
+///
+/// A lock poisoned by a panicking writer is recovered rather than
+/// propagating the panic; [`MemoryCatalog::apply`] leaves the catalog
+/// unchanged when it fails.
+///
+/// ```
+/// use std::thread;
+/// use vibesql::catalog::{MemoryCatalog, SharedCatalog};
+/// use vibesql::{Analyzer, Parser};
+///
+/// let catalog = SharedCatalog::new(MemoryCatalog::new());
+/// let ddl = Parser::new("CREATE TABLE users (id INT64)").parse().unwrap();
+/// catalog.apply(&ddl[0]).unwrap();
+///
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         let catalog = catalog.clone();
+///         s.spawn(move || {
+///             let stmt = Parser::new("SELECT id FROM users").parse().unwrap();
+///             Analyzer::with_catalog(catalog).analyze(&stmt[0]).unwrap();
+///         });
+///     }
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedCatalog {
+    inner: Arc<RwLock<MemoryCatalog>>,
+}
+
+impl SharedCatalog {
+    /// Share a catalog.
+    pub fn new(catalog: MemoryCatalog) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(catalog)),
+        }
+    }
+
+    /// Lock the catalog for reading.
+    pub fn read(&self) -> RwLockReadGuard<'_, MemoryCatalog> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the catalog for writing, for changes beyond
+    /// [`SharedCatalog::apply`] such as adding tables or functions.
+    pub fn write(&self) -> RwLockWriteGuard<'_, MemoryCatalog> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Apply a DDL statement, as [`MemoryCatalog::apply`] does.
+    pub fn apply(&self, stmt: &Statement) -> Result<ApplyOutcome> {
+        self.write().apply(stmt)
+    }
+
+    /// Copy the catalog as it is now. Tables are shared with the copy
+    /// rather than cloned.
+    pub fn snapshot(&self) -> MemoryCatalog {
+        self.read().clone()
+    }
+}
+
+impl From<MemoryCatalog> for SharedCatalog {
+    fn from(catalog: MemoryCatalog) -> Self {
+        Self::new(catalog)
+    }
+}
+
+impl Catalog for SharedCatalog {
+    fn resolve_table(&self, name: &[String]) -> Result<Option<Arc<TableSchema>>> {
+        self.read().resolve_table(name)
+    }
+
+    fn resolve_table_ident(&self, name: &[Ident]) -> Result<Option<Arc<TableSchema>>> {
+        self.read().resolve_table_ident(name)
+    }
+
+    fn resolve_function(&self, name: &[String]) -> Result<Option<FunctionSignature>> {
+        self.read().resolve_function(name)
+    }
+
+    fn resolve_function_overloads(&self, name: &[String]) -> Result<Vec<FunctionSignature>> {
+        self.read().resolve_function_overloads(name)
+    }
+
+    fn resolve_type(&self, name: &[String]) -> Result<Option<SqlType>> {
+        self.read().resolve_type(name)
+    }
+
+    fn list_tables(&self, schema: Option<&str>) -> Result<Vec<String>> {
+        self.read().list_tables(schema)
+    }
+
+    fn list_schemas(&self) -> Result<Vec<String>> {
+        self.read().list_schemas()
+    }
+
+    fn list_functions(&self) -> Result<Vec<FunctionInfo>> {
+        self.read().list_functions()
+    }
+
+    fn table_exists(&self, name: &[String]) -> Result<bool> {
+        self.read().table_exists(name)
+    }
+
+    fn function_search_schemas(&self, name: &[String]) -> Result<Vec<String>> {
+        self.read().function_search_schemas(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::Analyzer;
+    use crate::catalog::{CatalogBuilder, ColumnSchema};
+    use crate::parser::Parser;
+    use std::thread;
+
+    fn parse(sql: &str) -> Statement {
+        Parser::new(sql).parse().unwrap().remove(0)
+    }
+
+    fn users() -> MemoryCatalog {
+        CatalogBuilder::new()
+            .with_builtins()
+            .add_table("users", |t| {
+                t.primary_key("id", SqlType::Int64)
+                    .column("name", SqlType::Varchar(None))
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_arc_catalog_is_shared_across_threads() {
+        let catalog = Arc::new(users());
+        let first = catalog.resolve_table(&["users".to_string()]).unwrap();
+        let second = catalog.resolve_table(&["users".to_string()]).unwrap();
+        assert!(Arc::ptr_eq(&first.unwrap(), &second.unwrap()));
+
+        thread::scope(|s| {
+            for i in 0..8 {
+                let catalog = Arc::clone(&catalog);
+                s.spawn(move || {
+                    let mut analyzer = Analyzer::with_catalog(catalog);
+                    let sql = format!(
+                        "SELECT name, COUNT(*) FROM users WHERE id > {} GROUP BY name",
+                        i
+                    );
+                    analyzer.analyze(&parse(&sql)).unwrap();
+                    assert!(analyzer.analyze(&parse("SELECT x FROM users")).is_err());
+                });
+            }
+        });
+
+        // Boxed trait objects are catalogs too.
+        let boxed: Box<dyn Catalog> = Box::new(users());
+        assert!(Analyzer::with_catalog(boxed)
+            .analyze(&parse("SELECT id FROM users"))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_shared_catalog_sees_online_ddl() {
+        let shared = SharedCatalog::new(users());
+        let before = shared
+            .resolve_table(&["users".to_string()])
+            .unwrap()
+            .unwrap();
+
+        thread::scope(|s| {
+            let writer = shared.clone();
+            s.spawn(move || {
+                writer
+                    .apply(&parse("CREATE TABLE events (id INT64, user_id INT64)"))
+                    .unwrap();
+                writer
+                    .apply(&parse("ALTER TABLE users ADD COLUMN email STRING"))
+                    .unwrap();
+            });
+            for _ in 0..4 {
+                let reader = shared.clone();
+                s.spawn(move || {
+                    let mut analyzer = Analyzer::with_catalog(reader);
+                    analyzer
+                        .analyze(&parse("SELECT id, name FROM users"))
+                        .unwrap();
+                });
+            }
+        });
+
+        let mut analyzer = Analyzer::with_catalog(shared.clone());
+        analyzer
+            .analyze(&parse(
+                "SELECT u.email FROM users u JOIN events e ON e.user_id = u.id",
+            ))
+            .unwrap();
+
+        // Tables resolved before the DDL are unchanged.
+        assert!(before.get_column("email").is_none());
+        shared.write().add_table(TableSchema::new(
+            "t",
+            vec![ColumnSchema::new("x", SqlType::Int64)],
+        ));
+        assert!(shared.snapshot().table_exists(&["t".to_string()]).unwrap());
+    }
+}
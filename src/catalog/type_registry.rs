@@ -19,12 +19,12 @@ use std::collections::HashMap;
 /// let mut registry = TypeRegistry::new();
 ///
 /// // Add custom alias
-/// registry.add_alias("TEXT", SqlType::Varchar);
+/// registry.add_alias("TEXT", SqlType::Varchar(None));
 /// registry.add_alias("SERIAL", SqlType::Int32);
 ///
 /// // Resolve alias to canonical type
-/// assert_eq!(registry.resolve("TEXT"), Some(&SqlType::Varchar));
-/// assert_eq!(registry.resolve("VARCHAR"), Some(&SqlType::Varchar));
+/// assert_eq!(registry.resolve("TEXT"), Some(&SqlType::Varchar(None)));
+/// assert_eq!(registry.resolve("VARCHAR"), Some(&SqlType::Varchar(None)));
 /// ```
 #[derive(Debug, Clone)]
 pub struct TypeRegistry {
@@ -92,17 +92,24 @@ impl TypeRegistry {
             .insert("DOUBLE PRECISION".to_string(), SqlType::Float64);
 
         // String aliases
-        self.aliases.insert("VARCHAR".to_string(), SqlType::Varchar);
-        self.aliases.insert("STRING".to_string(), SqlType::Varchar);
-        self.aliases.insert("TEXT".to_string(), SqlType::Varchar);
-        self.aliases.insert("CHAR".to_string(), SqlType::Varchar);
+        self.aliases
+            .insert("VARCHAR".to_string(), SqlType::Varchar(None));
+        self.aliases
+            .insert("STRING".to_string(), SqlType::Varchar(None));
+        self.aliases
+            .insert("TEXT".to_string(), SqlType::Varchar(None));
+        self.aliases
+            .insert("CHAR".to_string(), SqlType::Varchar(None));
 
         // Binary aliases
         self.aliases
-            .insert("VARBINARY".to_string(), SqlType::Varbinary);
-        self.aliases.insert("BYTES".to_string(), SqlType::Varbinary);
-        self.aliases.insert("BYTEA".to_string(), SqlType::Varbinary);
-        self.aliases.insert("BLOB".to_string(), SqlType::Varbinary);
+            .insert("VARBINARY".to_string(), SqlType::Varbinary(None));
+        self.aliases
+            .insert("BYTES".to_string(), SqlType::Varbinary(None));
+        self.aliases
+            .insert("BYTEA".to_string(), SqlType::Varbinary(None));
+        self.aliases
+            .insert("BLOB".to_string(), SqlType::Varbinary(None));
 
         // Date/time aliases
         self.aliases.insert("DATE".to_string(), SqlType::Date);
@@ -201,8 +208,8 @@ mod tests {
         assert_eq!(registry.resolve("INT"), Some(&SqlType::Int32));
         assert_eq!(registry.resolve("INTEGER"), Some(&SqlType::Int32));
         assert_eq!(registry.resolve("BIGINT"), Some(&SqlType::Int64));
-        assert_eq!(registry.resolve("VARCHAR"), Some(&SqlType::Varchar));
-        assert_eq!(registry.resolve("TEXT"), Some(&SqlType::Varchar));
+        assert_eq!(registry.resolve("VARCHAR"), Some(&SqlType::Varchar(None)));
+        assert_eq!(registry.resolve("TEXT"), Some(&SqlType::Varchar(None)));
         assert_eq!(registry.resolve("BOOLEAN"), Some(&SqlType::Bool));
 
         // Case insensitive
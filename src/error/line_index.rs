@@ -0,0 +1,138 @@
+//! Converting byte offsets to line and column numbers.
+//!
+//! [`Span`](super::Span) offsets are bytes, which is what slicing needs but
+//! not what a person reading an error wants. [`LineIndex`] records where
+//! each line starts once, then answers lookups with a binary search:
+//!
+//! ```
+//! use vibesql::error::{LineCol, LineIndex};
+//!
+//! let sql = "SELECT *\nFROM t\nWHERE";
+//! let index = LineIndex::new(sql);
+//! assert_eq!(index.line_col(sql.find("t").unwrap()), LineCol { line: 2, column: 6 });
+//! assert_eq!(index.line(3), Some("WHERE"));
+//! ```
+
+use std::fmt;
+
+/// A 1-based line and column in source text.
+///
+/// Columns count `char`s, so a multi-byte UTF-8 character is one column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number in characters, starting at 1.
+    pub column: usize,
+}
+
+impl fmt::Display for LineCol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Line start offsets of a source text.
+///
+/// Lines end at `\n`; a `\r` before it is not part of the line text.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    /// Byte offset of the start of each line; the first is always 0.
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Index the lines of `source`.
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// The indexed source.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Number of lines; an empty source has one empty line.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The line and column of a byte offset.
+    ///
+    /// Offsets past the end map to the end of the source, and offsets
+    /// inside a multi-byte character to that character.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        let offset = floor_char_boundary(self.source, offset);
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let start = self.line_starts[line - 1];
+        LineCol {
+            line,
+            column: self.source[start..offset].chars().count() + 1,
+        }
+    }
+
+    /// The byte offset at which a 1-based line starts.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        line.checked_sub(1)
+            .and_then(|i| self.line_starts.get(i))
+            .copied()
+    }
+
+    /// The text of a 1-based line, without its line terminator.
+    pub fn line(&self, line: usize) -> Option<&'a str> {
+        let start = self.line_start(line)?;
+        let end = self
+            .line_start(line + 1)
+            .map_or(self.source.len(), |next| next - 1);
+        Some(self.source[start..end].trim_end_matches('\r'))
+    }
+}
+
+/// The largest char boundary in `s` at or before `pos`.
+pub(super) fn floor_char_boundary(s: &str, pos: usize) -> usize {
+    let mut pos = pos.min(s.len());
+    while !s.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_col() {
+        let sql = "SELECT '\u{e9}t\u{e9}',\r\n  x\n\nFROM";
+        let index = LineIndex::new(sql);
+        assert_eq!(index.line_count(), 4);
+        let at = |needle: &str| index.line_col(sql.find(needle).unwrap());
+        assert_eq!(at("SELECT"), LineCol { line: 1, column: 1 });
+        assert_eq!(
+            at(","),
+            LineCol {
+                line: 1,
+                column: 13
+            }
+        );
+        assert_eq!(at("x"), LineCol { line: 2, column: 3 });
+        assert_eq!(at("FROM"), LineCol { line: 4, column: 1 });
+        assert_eq!(index.line_col(sql.len() + 10).to_string(), "4:5");
+        // Inside the two-byte character.
+        assert_eq!(index.line_col(9), LineCol { line: 1, column: 9 });
+
+        assert_eq!(index.line(1), Some("SELECT '\u{e9}t\u{e9}',"));
+        assert_eq!(index.line(3), Some(""));
+        assert_eq!(index.line(4), Some("FROM"));
+        assert_eq!(index.line(0), None);
+        assert_eq!(index.line(5), None);
+        assert_eq!(LineIndex::new("").line(1), Some(""));
+    }
+}
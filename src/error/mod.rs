@@ -3,6 +3,12 @@
 //! This module provides error types and utilities for representing and
 //! displaying parsing and analysis errors with source location information.
 
+mod line_index;
+mod render;
+
+pub use line_index::{LineCol, LineIndex};
+pub use render::{render_snippet, SnippetOptions};
+
 use std::fmt;
 
 /// A span in the source code, represented as byte offsets.
@@ -36,8 +42,31 @@ impl Span {
         }
     }
 
+    /// Create a span for a token that does not come from the source text,
+    /// such as one inserted by a preprocessor.
+    pub const fn synthetic() -> Self {
+        Self {
+            start: usize::MAX,
+            end: usize::MAX,
+        }
+    }
+
+    /// Check if this span was created with [`Span::synthetic`].
+    pub fn is_synthetic(&self) -> bool {
+        self.start == usize::MAX && self.end == usize::MAX
+    }
+
     /// Merge two spans into one that covers both.
+    ///
+    /// Synthetic spans carry no position, so merging with one returns the
+    /// other span unchanged.
     pub fn merge(self, other: Span) -> Span {
+        if self.is_synthetic() {
+            return other;
+        }
+        if other.is_synthetic() {
+            return self;
+        }
         Span {
             start: self.start.min(other.start),
             end: self.end.max(other.end),
@@ -78,11 +107,18 @@ pub enum ErrorKind {
     ExpectedKeyword(String),
     InvalidSyntax(String),
     UnsupportedFeature(String),
+    TrailingComma,
+    DuplicateOperator(String),
+    DuplicateKeyword(String),
 
     // Analyzer errors
     UndefinedColumn(String),
     UndefinedTable(String),
+    TableAlreadyExists(String),
     UndefinedFunction(String),
+    UndefinedType(String),
+    UndefinedVariable(String),
+    FunctionAlreadyExists(String),
     AmbiguousColumn(String),
     TypeMismatch {
         expected: String,
@@ -99,6 +135,52 @@ pub enum ErrorKind {
     InvalidOrderBy(String),
     InvalidAggregateUsage(String),
     InvalidWindowFunction(String),
+    /// A statement rejected by an analyzer policy; `code` identifies the
+    /// policy.
+    PolicyViolation {
+        code: &'static str,
+        message: String,
+    },
+
+    // Catalog errors
+    /// Two packs define `function` differently; `packs` holds the existing
+    /// and the new pack name.
+    FunctionPackConflict {
+        function: String,
+        packs: Box<(String, String)>,
+    },
+    /// A DDL statement named an object of another kind, such as
+    /// `DROP VIEW` on a table; `expected` is the kind the statement takes.
+    WrongObjectType {
+        name: String,
+        expected: &'static str,
+    },
+    /// An unquoted name that matches several stored names, none of them
+    /// exactly, such as `foo` with tables `Foo` and `FOO`.
+    AmbiguousName {
+        name: String,
+        candidates: Vec<String>,
+    },
+
+    // Evaluation errors
+    /// A value that does not convert to a type; the message names both
+    /// and why.
+    InvalidCast(String),
+    /// Arithmetic whose result does not fit its type; the message names
+    /// the operation.
+    NumericOverflow(String),
+    DivisionByZero,
+    /// A subscript outside an array; `index` is as written, whether
+    /// counted from 0 or 1.
+    ArrayIndexOutOfBounds {
+        index: i64,
+        len: usize,
+    },
+
+    // Encoding errors
+    /// Input to [`Statement::decode`](crate::Statement::decode) that is not
+    /// a valid encoding of this version.
+    InvalidEncoding(String),
 
     // General errors
     Internal(String),
@@ -126,11 +208,20 @@ impl fmt::Display for ErrorKind {
             ErrorKind::ExpectedKeyword(kw) => write!(f, "expected keyword '{}'", kw),
             ErrorKind::InvalidSyntax(msg) => write!(f, "invalid syntax: {}", msg),
             ErrorKind::UnsupportedFeature(feat) => write!(f, "unsupported feature: {}", feat),
+            ErrorKind::TrailingComma => write!(f, "trailing comma in select list"),
+            ErrorKind::DuplicateOperator(op) => write!(f, "duplicate operator '{}'", op),
+            ErrorKind::DuplicateKeyword(kw) => write!(f, "duplicate {} keyword", kw),
 
             // Analyzer errors
             ErrorKind::UndefinedColumn(name) => write!(f, "undefined column '{}'", name),
             ErrorKind::UndefinedTable(name) => write!(f, "undefined table '{}'", name),
+            ErrorKind::TableAlreadyExists(name) => write!(f, "table '{}' already exists", name),
             ErrorKind::UndefinedFunction(name) => write!(f, "undefined function '{}'", name),
+            ErrorKind::UndefinedType(name) => write!(f, "undefined type '{}'", name),
+            ErrorKind::UndefinedVariable(name) => write!(f, "undefined variable '{}'", name),
+            ErrorKind::FunctionAlreadyExists(name) => {
+                write!(f, "function '{}' already exists", name)
+            }
             ErrorKind::AmbiguousColumn(name) => write!(f, "ambiguous column reference '{}'", name),
             ErrorKind::TypeMismatch { expected, found } => {
                 write!(f, "type mismatch: expected {}, found {}", expected, found)
@@ -152,6 +243,36 @@ impl fmt::Display for ErrorKind {
             ErrorKind::InvalidOrderBy(msg) => write!(f, "invalid ORDER BY: {}", msg),
             ErrorKind::InvalidAggregateUsage(msg) => write!(f, "invalid aggregate usage: {}", msg),
             ErrorKind::InvalidWindowFunction(msg) => write!(f, "invalid window function: {}", msg),
+            ErrorKind::PolicyViolation { message, .. } => write!(f, "{}", message),
+
+            // Catalog errors
+            ErrorKind::FunctionPackConflict { function, packs } => write!(
+                f,
+                "function '{}' is defined with different signatures by packs '{}' and '{}'",
+                function, packs.0, packs.1
+            ),
+            ErrorKind::WrongObjectType { name, expected } => {
+                write!(f, "'{}' is not a {}", name, expected)
+            }
+            ErrorKind::AmbiguousName { name, candidates } => write!(
+                f,
+                "ambiguous name '{}' could be any of: {}",
+                name,
+                candidates.join(", ")
+            ),
+
+            // Evaluation errors
+            ErrorKind::InvalidCast(msg) => write!(f, "cannot cast {}", msg),
+            ErrorKind::NumericOverflow(msg) => write!(f, "numeric overflow: {}", msg),
+            ErrorKind::DivisionByZero => write!(f, "division by zero"),
+            ErrorKind::ArrayIndexOutOfBounds { index, len } => write!(
+                f,
+                "array index {} is out of bounds for an array of length {}",
+                index, len
+            ),
+
+            // Encoding errors
+            ErrorKind::InvalidEncoding(msg) => write!(f, "invalid encoded statement: {}", msg),
 
             // General errors
             ErrorKind::Internal(msg) => write!(f, "internal error: {}", msg),
@@ -159,6 +280,66 @@ impl fmt::Display for ErrorKind {
     }
 }
 
+/// A source edit that would fix a reported problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// Short description of the fix.
+    pub message: String,
+    /// The source range to replace.
+    pub span: Span,
+    /// Replacement text; empty for a deletion.
+    pub replacement: String,
+}
+
+impl Suggestion {
+    /// Create a suggestion that replaces `span` with `replacement`.
+    pub fn new(message: impl Into<String>, span: Span, replacement: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Create a suggestion that deletes `span`.
+    pub fn delete(message: impl Into<String>, span: Span) -> Self {
+        Self::new(message, span, "")
+    }
+
+    /// Create a suggestion that inserts `text` at `pos`.
+    pub fn insert(message: impl Into<String>, pos: usize, text: impl Into<String>) -> Self {
+        Self::new(message, Span::empty(pos), text)
+    }
+}
+
+/// A non-fatal note about the source, such as a likely typo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Description of the issue.
+    pub message: String,
+    /// The source span the note refers to.
+    pub span: Span,
+    /// Possible fixes.
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic without suggestions.
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Attach a suggested fix.
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+}
+
 /// An error with source location information.
 #[derive(Debug, Clone)]
 pub struct Error {
@@ -168,6 +349,8 @@ pub struct Error {
     span: Option<Span>,
     /// Optional context message.
     context: Option<String>,
+    /// Suggested fixes. Boxed to keep `Error` small on the happy path.
+    suggestions: Box<[Suggestion]>,
 }
 
 impl Error {
@@ -177,6 +360,7 @@ impl Error {
             kind,
             span: None,
             context: None,
+            suggestions: Box::default(),
         }
     }
 
@@ -186,6 +370,7 @@ impl Error {
             kind,
             span: Some(span),
             context: None,
+            suggestions: Box::default(),
         }
     }
 
@@ -195,6 +380,19 @@ impl Error {
         self
     }
 
+    /// Attach a suggested fix to this error.
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        let mut suggestions = std::mem::take(&mut self.suggestions).into_vec();
+        suggestions.push(suggestion);
+        self.suggestions = suggestions.into_boxed_slice();
+        self
+    }
+
+    /// Get the suggested fixes for this error.
+    pub fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+
     /// Get the span of this error, if any.
     pub fn span(&self) -> Option<Span> {
         self.span
@@ -205,6 +403,19 @@ impl Error {
         &self.kind
     }
 
+    /// The context message, if any.
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+
+    /// The policy code of a [`ErrorKind::PolicyViolation`].
+    pub fn code(&self) -> Option<&'static str> {
+        match self.kind {
+            ErrorKind::PolicyViolation { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
     // Convenience constructors for common errors
     pub fn unexpected_char(c: char, pos: usize) -> Self {
         Self::with_span(ErrorKind::UnexpectedCharacter(c), Span::point(pos))
@@ -260,6 +471,10 @@ impl Error {
         Self::with_span(ErrorKind::InvalidEscapeSequence(seq.into()), span)
     }
 
+    pub fn invalid_bytes_literal(span: Span) -> Self {
+        Self::with_span(ErrorKind::InvalidBytesLiteral, span)
+    }
+
     pub fn invalid_number(num: impl Into<String>, span: Span) -> Self {
         Self::with_span(ErrorKind::InvalidNumber(num.into()), span)
     }
@@ -276,7 +491,7 @@ impl fmt::Display for Error {
         if let Some(ref ctx) = self.context {
             write!(f, " ({})", ctx)?;
         }
-        if let Some(span) = self.span {
+        if let Some(span) = self.span.filter(|s| !s.is_synthetic()) {
             write!(f, " at position {}", span.start)?;
         }
         Ok(())
@@ -301,6 +516,15 @@ mod tests {
         assert_eq!(merged.end, 30);
     }
 
+    #[test]
+    fn test_span_merge_synthetic() {
+        let span = Span::new(4, 9);
+        assert_eq!(span.merge(Span::synthetic()), span);
+        assert_eq!(Span::synthetic().merge(span), span);
+        assert!(Span::synthetic().is_synthetic());
+        assert!(!span.is_synthetic());
+    }
+
     #[test]
     fn test_error_display() {
         let err = Error::unexpected_char('$', 5);
@@ -0,0 +1,291 @@
+//! Rendering source snippets under error messages.
+//!
+//! Generated SQL often arrives as one very long line, so snippets are
+//! windowed: at most [`SnippetOptions::max_width`] characters of the line
+//! are shown around the span, with `...` marking trimmed text on either
+//! side. Columns are counted in `char`s, not display width, so a caret
+//! stays under the right character for multi-byte UTF-8 but may drift
+//! after double-width characters such as CJK ideographs.
+
+use super::line_index::{floor_char_boundary, LineIndex};
+use super::{Error, Span};
+
+/// Marker for trimmed text.
+const ELLIPSIS: &str = "...";
+
+/// Options for [`render_snippet`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetOptions {
+    /// Maximum number of source characters shown, excluding ellipses.
+    pub max_width: usize,
+}
+
+impl Default for SnippetOptions {
+    fn default() -> Self {
+        Self { max_width: 120 }
+    }
+}
+
+impl SnippetOptions {
+    /// Create options with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of source characters shown.
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+        self
+    }
+}
+
+/// Render the line containing `span` with a caret line underneath.
+///
+/// The span is underlined in full when it fits in the window. A span that
+/// is wider than the window, or continues past the end of its line, is
+/// underlined up to the window edge and followed by `...`. The result is
+/// two lines separated by `\n`, without a trailing newline.
+pub fn render_snippet(source: &str, span: Span, options: &SnippetOptions) -> String {
+    let start = floor_char_boundary(source, span.start);
+    let end = floor_char_boundary(source, span.end.max(span.start));
+    let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = source[start..]
+        .find('\n')
+        .map(|i| start + i)
+        .unwrap_or(source.len());
+    let line = source[line_start..line_end].trim_end_matches('\r');
+
+    let col_start = source[line_start..start].chars().count();
+    let col_end = col_start
+        + source[start..end.min(line_start + line.len())]
+            .chars()
+            .count();
+    let line_len = line.chars().count();
+    let width = options.max_width.max(1);
+
+    // Pick the visible character range [win_start, win_end).
+    let (win_start, win_end) = if line_len <= width {
+        (0, line_len)
+    } else if col_end - col_start <= width {
+        let context = (width - (col_end - col_start)) / 2;
+        let win_start = col_start.saturating_sub(context);
+        let win_end = (win_start + width).min(line_len);
+        (win_end - width, win_end)
+    } else {
+        let win_start = col_start.saturating_sub(width / 4);
+        (win_start, win_start + width)
+    };
+
+    let mut text = String::new();
+    if win_start > 0 {
+        text.push_str(ELLIPSIS);
+    }
+    text.extend(line.chars().skip(win_start).take(win_end - win_start));
+    if win_end < line_len {
+        text.push_str(ELLIPSIS);
+    }
+
+    let prefix = if win_start > 0 { ELLIPSIS.len() } else { 0 };
+    let underline_end = col_end.min(win_end);
+    let mut carets = " ".repeat(prefix + col_start - win_start);
+    carets.push_str(&"^".repeat(underline_end.saturating_sub(col_start).max(1)));
+    if col_end > win_end || end > line_start + line.len() {
+        carets.push_str(ELLIPSIS);
+    }
+
+    format!("{}\n{}", text, carets)
+}
+
+impl Error {
+    /// Render this error with a snippet of `source` under the message.
+    ///
+    /// Errors without a span, or with a synthetic one, render as the
+    /// message alone.
+    pub fn render(&self, source: &str, options: &SnippetOptions) -> String {
+        let mut out = format!("Error: {}", self);
+        if let Some(span) = self.span().filter(|s| !s.is_synthetic()) {
+            out.push_str("\n  |");
+            for line in render_snippet(source, span, options).lines() {
+                out.push_str("\n  | ");
+                out.push_str(line);
+            }
+        }
+        out
+    }
+
+    /// Render this error in the style of rustc, with the line and column
+    /// of its span and a snippet of `sql` under a line number gutter:
+    ///
+    /// ```text
+    /// error: expected expression, found keyword From
+    ///  --> 2:14
+    ///   |
+    /// 2 | FROM t WHERE FROM
+    ///   |              ^^^^
+    /// ```
+    ///
+    /// A span that crosses lines shows its first line, underlined to the
+    /// end and followed by `...`, and its last line; a `...` gutter stands
+    /// for the lines between. Long lines are windowed as by
+    /// [`render_snippet`]. Errors without a span, or with a synthetic one,
+    /// render as the message alone.
+    pub fn display_with_source(&self, sql: &str) -> String {
+        let mut out = format!("error: {}", self.kind);
+        if let Some(context) = &self.context {
+            out.push_str(&format!(" ({})", context));
+        }
+        let Some(span) = self.span.filter(|s| !s.is_synthetic()) else {
+            return out;
+        };
+
+        let options = SnippetOptions::default();
+        let index = LineIndex::new(sql);
+        let start = index.line_col(span.start);
+        let last = index.line_col(span.end.saturating_sub(1).max(span.start));
+        let width = last.line.to_string().len();
+        let gutter = " ".repeat(width);
+        out.push_str(&format!("\n{}--> {}\n{} |", gutter, start, gutter));
+
+        let push_snippet = |out: &mut String, line: usize, span: Span| {
+            let snippet = render_snippet(sql, span, &options);
+            let mut lines = snippet.lines();
+            let text = lines.next().unwrap_or_default();
+            let carets = lines.next().unwrap_or_default();
+            out.push_str(&format!("\n{:>width$} | {}", line, text, width = width));
+            out.push_str(&format!("\n{} | {}", gutter, carets));
+        };
+        push_snippet(&mut out, start.line, span);
+        if last.line > start.line {
+            if last.line > start.line + 1 {
+                out.push_str("\n...");
+            }
+            let line_start = index.line_start(last.line).unwrap_or_default();
+            let text = index.line(last.line).unwrap_or_default();
+            let indent = text.len() - text.trim_start().len();
+            push_snippet(
+                &mut out,
+                last.line,
+                Span::new(line_start + indent, span.end),
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::parser::Parser;
+
+    /// The caret column and the (char) column of `needle` in the text line.
+    fn caret_and_needle(snippet: &str, needle: &str) -> (usize, usize) {
+        let mut lines = snippet.lines();
+        let text = lines.next().unwrap();
+        let carets = lines.next().unwrap();
+        let byte = text.find(needle).unwrap();
+        (
+            carets.chars().take_while(|c| *c == ' ').count(),
+            text[..byte].chars().count(),
+        )
+    }
+
+    #[test]
+    fn test_long_line_is_windowed() {
+        let columns: Vec<String> = (0..2000).map(|i| format!("c{}", i)).collect();
+        let sql = format!("SELECT {} FROM FROM t", columns.join(", "));
+        assert!(sql.len() > 10_000);
+
+        let err = Parser::new(&sql).parse().unwrap_err();
+        let options = SnippetOptions::default();
+        let snippet = render_snippet(&sql, err.span().unwrap(), &options);
+        let (caret, token) = caret_and_needle(&snippet, "FROM t");
+        assert_eq!(caret, token, "{}", snippet);
+        for line in snippet.lines() {
+            assert!(line.chars().count() <= options.max_width + 2 * ELLIPSIS.len());
+        }
+        assert!(snippet.starts_with(ELLIPSIS));
+
+        let rendered = err.render(&sql, &options);
+        assert!(rendered.len() < 400, "{}", rendered);
+        assert!(rendered.starts_with("Error: "));
+    }
+
+    #[test]
+    fn test_short_line_underlines_span() {
+        let sql = "SELECT * FROM users WHERE";
+        let snippet = render_snippet(sql, Span::new(14, 19), &SnippetOptions::default());
+        assert_eq!(snippet, "SELECT * FROM users WHERE\n              ^^^^^");
+    }
+
+    #[test]
+    fn test_multibyte_caret_alignment() {
+        let sql = format!("SELECT '{}' AS x, @ FROM t", "\u{e9}".repeat(200));
+        let pos = sql.find('@').unwrap();
+        let snippet = render_snippet(
+            &sql,
+            Span::point(pos),
+            &SnippetOptions::new().with_max_width(40),
+        );
+        let (caret, token) = caret_and_needle(&snippet, "@");
+        assert_eq!(caret, token, "{}", snippet);
+        assert!(snippet.lines().next().unwrap().starts_with(ELLIPSIS));
+    }
+
+    #[test]
+    fn test_span_wider_than_window() {
+        let sql = format!("SELECT {}", "y".repeat(1000));
+        let snippet = render_snippet(
+            &sql,
+            Span::new(300, 900),
+            &SnippetOptions::new().with_max_width(60),
+        );
+        let carets = snippet.lines().nth(1).unwrap();
+        // 15 characters of context after the leading ellipsis, then the
+        // span clamped at the window edge.
+        assert_eq!(carets, format!("{}{}...", " ".repeat(18), "^".repeat(45)));
+    }
+
+    #[test]
+    fn test_display_with_source() {
+        let sql = "SELECT *\nFROM t WHERE FROM";
+        let err = Parser::new(sql).parse().unwrap_err();
+        assert_eq!(
+            err.display_with_source(sql),
+            "error: expected expression, found keyword From\n \
+             --> 2:14\n  \
+             |\n\
+             2 | FROM t WHERE FROM\n  \
+             |              ^^^^"
+        );
+
+        let err = Error::analyzer("bad").with_context("here");
+        assert_eq!(
+            err.display_with_source(sql),
+            "error: internal error: bad (here)"
+        );
+    }
+
+    #[test]
+    fn test_display_multiline_span() {
+        let sql = format!("{}SELECT (a\n  + b\n  + c\n  + d) FROM t", "\n".repeat(8));
+        let start = sql.find('(').unwrap();
+        let end = sql.find(')').unwrap() + 1;
+        let err = Error::with_span(ErrorKind::Internal("bad".into()), Span::new(start, end));
+        let rendered = err.display_with_source(&sql);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "error: internal error: bad",
+                "  --> 9:8",
+                "   |",
+                " 9 | SELECT (a",
+                "   |        ^^...",
+                "...",
+                "12 |   + d) FROM t",
+                "   |   ^^^^",
+            ]
+        );
+    }
+}
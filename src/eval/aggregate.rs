@@ -0,0 +1,1440 @@
+//! Aggregate functions for the evaluator.
+//!
+//! An [`Aggregator`] accumulates the argument values of the rows of one
+//! group and produces the aggregate's result. Accumulators of the same
+//! function can be [merged](Aggregator::merge), so groups can be
+//! aggregated in parts and combined.
+//!
+//! ```
+//! use vibesql::eval::{AggregateOptions, AggregateRegistry};
+//! use vibesql::types::Value;
+//!
+//! let registry = AggregateRegistry::with_builtins();
+//! let mut avg = registry.create("AVG", &AggregateOptions::default()).unwrap();
+//! for n in [Value::Int64(1), Value::Null, Value::Int64(4)] {
+//!     avg.update(&[n]).unwrap();
+//! }
+//! assert_eq!(avg.finalize().unwrap(), Value::Float64(2.5));
+//! ```
+
+use super::{evaluate, Params};
+use crate::ast::{FunctionCall, NullTreatment, NullsOrder, SortOrder};
+use crate::error::{Error, ErrorKind, Result};
+use crate::types::{SqlType, Value};
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+/// The accumulated state of an aggregate function over the rows of a
+/// group.
+pub trait Aggregator: Any + Send {
+    /// Reset to the state before any row.
+    fn init(&mut self);
+
+    /// Add a row, given the values of the aggregate's arguments. COUNT(*)
+    /// takes no arguments.
+    fn update(&mut self, args: &[Value]) -> Result<()>;
+
+    /// Add the rows `other` has accumulated. `other` is an accumulator of
+    /// the same function, created with the same options.
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()>;
+
+    /// The result over the rows added so far.
+    fn finalize(&mut self) -> Result<Value>;
+
+    /// `self`, for [`merge`](Self::merge) to downcast the other
+    /// accumulator to its own type.
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Creates an accumulator of an aggregate function with no rows.
+pub type AggregateFactory = Arc<dyn Fn() -> Box<dyn Aggregator> + Send + Sync>;
+
+/// The modifiers of an aggregate call, which [`AggregateRegistry::create`]
+/// applies around the function's own accumulator.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AggregateOptions {
+    /// Aggregate each distinct combination of argument values once.
+    pub distinct: bool,
+    /// Skip rows whose first argument is NULL (`IGNORE NULLS`). Only
+    /// ARRAY_AGG keeps NULLs otherwise.
+    pub ignore_nulls: bool,
+    /// The order rows are added in. Each row passed to
+    /// [`Aggregator::update`] then carries one sort key per entry after
+    /// its arguments.
+    pub order_by: Vec<SortKey>,
+    /// Aggregate at most this many rows, the first in `order_by` order.
+    pub limit: Option<usize>,
+}
+
+/// The direction of one ORDER BY key of an aggregate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortKey {
+    pub descending: bool,
+    pub nulls_first: bool,
+}
+
+impl SortKey {
+    /// An ascending key, with NULLs first.
+    pub fn ascending() -> Self {
+        Self {
+            descending: false,
+            nulls_first: true,
+        }
+    }
+
+    /// A descending key, with NULLs last.
+    pub fn descending() -> Self {
+        Self {
+            descending: true,
+            nulls_first: false,
+        }
+    }
+
+    fn compare(&self, a: &Value, b: &Value) -> Ordering {
+        let ordering = match (a.is_null(), b.is_null()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) if self.nulls_first => return Ordering::Less,
+            (true, false) => return Ordering::Greater,
+            (false, true) if self.nulls_first => return Ordering::Greater,
+            (false, true) => return Ordering::Less,
+            (false, false) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+        };
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+impl AggregateOptions {
+    /// The modifiers written in `call`: DISTINCT, IGNORE NULLS, ORDER BY
+    /// and a constant LIMIT. NULLs sort first in ascending order and last
+    /// in descending order unless the key says otherwise.
+    pub fn from_call(call: &FunctionCall) -> Result<Self> {
+        let order_by = call
+            .order_by
+            .iter()
+            .map(|item| {
+                let descending = item.order == Some(SortOrder::Desc);
+                let nulls_first = match item.nulls {
+                    Some(nulls) => nulls == NullsOrder::First,
+                    None => !descending,
+                };
+                SortKey {
+                    descending,
+                    nulls_first,
+                }
+            })
+            .collect();
+        let limit = match &call.limit {
+            None => None,
+            Some(limit) => match evaluate(limit, &(), &Params::new())? {
+                Value::Int64(n) if n >= 0 => Some(n as usize),
+                other => {
+                    return Err(Error::with_span(
+                        ErrorKind::TypeMismatch {
+                            expected: "a non-negative integer".to_string(),
+                            found: other.to_string(),
+                        },
+                        limit.span,
+                    ))
+                }
+            },
+        };
+        Ok(Self {
+            distinct: call.distinct,
+            ignore_nulls: call.null_treatment == Some(NullTreatment::IgnoreNulls),
+            order_by,
+            limit,
+        })
+    }
+
+    fn is_plain(&self) -> bool {
+        !self.distinct && self.order_by.is_empty() && self.limit.is_none()
+    }
+}
+
+/// The aggregate functions an embedder can execute, by name. Names match
+/// ignoring ASCII case.
+#[derive(Clone)]
+pub struct AggregateRegistry {
+    aggregates: HashMap<String, AggregateFactory>,
+}
+
+impl Default for AggregateRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+impl fmt::Debug for AggregateRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<_> = self.aggregates.keys().collect();
+        names.sort();
+        f.debug_struct("AggregateRegistry")
+            .field("aggregates", &names)
+            .finish()
+    }
+}
+
+impl AggregateRegistry {
+    /// A registry with no aggregates.
+    pub fn new() -> Self {
+        Self {
+            aggregates: HashMap::new(),
+        }
+    }
+
+    /// A registry with the aggregates
+    /// [`register_builtins`](crate::MemoryCatalog::register_builtins)
+    /// declares, except GROUPING, which depends on the grouping set
+    /// rather than on rows. Results have the types the analyzer gives
+    /// them, so SUM and AVG over integers are DOUBLE PRECISION and over
+    /// NUMERIC are NUMERIC. Every aggregate but COUNT(*) and ARRAY_AGG
+    /// skips NULL arguments, and every one but COUNT and COUNTIF is NULL
+    /// over no rows.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register("COUNT", || Box::new(Count::default()));
+        registry.register("COUNTIF", || Box::new(CountIf::default()));
+        registry.register("SUM", || Box::new(Sum::default()));
+        registry.register("AVG", || Box::new(Avg::default()));
+        registry.register("MIN", || Box::new(Extreme::new("MIN", Ordering::Less)));
+        registry.register("MAX", || Box::new(Extreme::new("MAX", Ordering::Greater)));
+        registry.register("ANY_VALUE", || Box::new(AnyValue::default()));
+        registry.register("ARRAY_AGG", || Box::new(ArrayAgg::default()));
+        registry.register("ARRAY_CONCAT_AGG", || Box::new(ArrayConcatAgg::default()));
+        registry.register("STRING_AGG", || Box::new(StringAgg::default()));
+        for (name, op) in [
+            ("BIT_AND", BitOp::And),
+            ("BIT_OR", BitOp::Or),
+            ("BIT_XOR", BitOp::Xor),
+        ] {
+            registry.register(name, move || Box::new(Bitwise::new(name, op)));
+        }
+        for (name, and) in [("LOGICAL_AND", true), ("LOGICAL_OR", false)] {
+            registry.register(name, move || Box::new(Logical::new(name, and)));
+        }
+        for (name, sample, root) in [
+            ("VARIANCE", true, false),
+            ("VAR_SAMP", true, false),
+            ("VAR_POP", false, false),
+            ("STDDEV", true, true),
+            ("STDDEV_SAMP", true, true),
+            ("STDDEV_POP", false, true),
+        ] {
+            registry.register(name, move || Box::new(Variance::new(name, sample, root)));
+        }
+        for (name, statistic) in [
+            ("CORR", Statistic::Correlation),
+            ("COVAR_SAMP", Statistic::SampleCovariance),
+            ("COVAR_POP", Statistic::PopulationCovariance),
+        ] {
+            registry.register(name, move || Box::new(Covariance::new(name, statistic)));
+        }
+        registry
+    }
+
+    /// Add an aggregate, replacing any with the same name.
+    pub fn register(
+        &mut self,
+        name: &str,
+        factory: impl Fn() -> Box<dyn Aggregator> + Send + Sync + 'static,
+    ) {
+        self.aggregates
+            .insert(name.to_ascii_uppercase(), Arc::new(factory));
+    }
+
+    /// Whether there is an aggregate called `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.aggregates.contains_key(&name.to_ascii_uppercase())
+    }
+
+    /// A new accumulator of the aggregate called `name`, with `options`
+    /// applied; `None` if there is no such aggregate.
+    pub fn create(&self, name: &str, options: &AggregateOptions) -> Option<Box<dyn Aggregator>> {
+        let inner = self.aggregates.get(&name.to_ascii_uppercase())?();
+        if options.is_plain() && !options.ignore_nulls {
+            return Some(inner);
+        }
+        Some(Box::new(Modified {
+            inner,
+            options: options.clone(),
+            seen: HashSet::new(),
+            rows: Vec::new(),
+        }))
+    }
+}
+
+/// An accumulator with DISTINCT, IGNORE NULLS, ORDER BY or LIMIT applied.
+/// Unless only IGNORE NULLS is, rows are kept until the result is asked
+/// for, and then added to the inner accumulator in order.
+struct Modified {
+    inner: Box<dyn Aggregator>,
+    options: AggregateOptions,
+    /// The encoded arguments of the rows kept, under DISTINCT.
+    seen: HashSet<Vec<u8>>,
+    /// Rows kept, with their sort keys.
+    rows: Vec<Vec<Value>>,
+}
+
+impl Modified {
+    fn keep(&mut self, row: &[Value]) -> Result<()> {
+        let Some(key_start) = row.len().checked_sub(self.options.order_by.len()) else {
+            return Err(Error::new(ErrorKind::Internal(format!(
+                "{} sort keys for an aggregate ordered by {}",
+                row.len(),
+                self.options.order_by.len()
+            ))));
+        };
+        if self.options.ignore_nulls && row[..key_start].first().is_some_and(Value::is_null) {
+            return Ok(());
+        }
+        if self.options.is_plain() {
+            return self.inner.update(row);
+        }
+        if self.options.distinct {
+            let mut encoded = Vec::new();
+            for value in &row[..key_start] {
+                encode(value, &mut encoded);
+            }
+            if !self.seen.insert(encoded) {
+                return Ok(());
+            }
+        }
+        self.rows.push(row.to_vec());
+        Ok(())
+    }
+}
+
+impl Aggregator for Modified {
+    fn init(&mut self) {
+        self.inner.init();
+        self.seen.clear();
+        self.rows.clear();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        self.keep(args)
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        let other = downcast::<Self>(other)?;
+        if self.options.is_plain() {
+            return self.inner.merge(&*other.inner);
+        }
+        for row in &other.rows {
+            self.keep(row)?;
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        if self.options.is_plain() {
+            return self.inner.finalize();
+        }
+        let keys = &self.options.order_by;
+        let key_start = |row: &[Value]| row.len() - keys.len();
+        let mut rows: Vec<&Vec<Value>> = self.rows.iter().collect();
+        rows.sort_by(|a, b| {
+            let (a, b) = (&a[key_start(a)..], &b[key_start(b)..]);
+            keys.iter()
+                .zip(a.iter().zip(b))
+                .map(|(key, (a, b))| key.compare(a, b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        rows.truncate(self.options.limit.unwrap_or(usize::MAX));
+        self.inner.init();
+        for row in rows {
+            self.inner.update(&row[..key_start(row)])?;
+        }
+        self.inner.finalize()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+struct Count {
+    count: i64,
+}
+
+impl Aggregator for Count {
+    fn init(&mut self) {
+        *self = Self::default();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        if args.first().is_none_or(|value| !value.is_null()) {
+            self.count += 1;
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        self.count += downcast::<Self>(other)?.count;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        Ok(Value::Int64(self.count))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+struct CountIf {
+    count: i64,
+}
+
+impl Aggregator for CountIf {
+    fn init(&mut self) {
+        *self = Self::default();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        match first("COUNTIF", args)? {
+            Value::Boolean(true) => self.count += 1,
+            Value::Boolean(false) | Value::Null => {}
+            other => return Err(type_error("BOOLEAN", other)),
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        self.count += downcast::<Self>(other)?.count;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        Ok(Value::Int64(self.count))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// An exact running total; integers overflow into NUMERIC as
+/// [`Value::add`] does.
+#[derive(Default)]
+struct Sum {
+    total: Option<Value>,
+    numeric: bool,
+}
+
+impl Sum {
+    fn add(&mut self, value: &Value) -> Result<()> {
+        match value {
+            Value::Null => return Ok(()),
+            Value::Numeric(_) => self.numeric = true,
+            Value::Int64(_) | Value::Float64(_) => {}
+            other => return Err(type_error("a number", other)),
+        }
+        self.total = Some(match &self.total {
+            Some(total) => total.add(value)?,
+            None => value.clone(),
+        });
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &Sum) -> Result<()> {
+        self.numeric |= other.numeric;
+        if let Some(total) = &other.total {
+            self.add(total)?;
+        }
+        Ok(())
+    }
+
+    /// The total as NUMERIC if a NUMERIC was added, and otherwise as
+    /// DOUBLE PRECISION.
+    fn total(&self) -> Result<Value> {
+        let result_type = if self.numeric {
+            SqlType::Numeric {
+                precision: None,
+                scale: None,
+            }
+        } else {
+            SqlType::Float64
+        };
+        match &self.total {
+            Some(total) => total.cast_to(&result_type),
+            None => Ok(Value::Null),
+        }
+    }
+}
+
+impl Aggregator for Sum {
+    fn init(&mut self) {
+        *self = Self::default();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        self.add(first("SUM", args)?)
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        Sum::merge(self, downcast::<Self>(other)?)
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        self.total()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+struct Avg {
+    sum: Sum,
+    count: i64,
+}
+
+impl Aggregator for Avg {
+    fn init(&mut self) {
+        *self = Self::default();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        let value = first("AVG", args)?;
+        self.sum.add(value)?;
+        if !value.is_null() {
+            self.count += 1;
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        let other = downcast::<Self>(other)?;
+        self.sum.merge(&other.sum)?;
+        self.count += other.count;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        if self.count == 0 {
+            return Ok(Value::Null);
+        }
+        self.sum.total()?.div(&Value::Int64(self.count))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// MIN or MAX. NaN is less and greater than every other value, so once
+/// seen it is the result.
+struct Extreme {
+    name: &'static str,
+    wanted: Ordering,
+    best: Option<Value>,
+}
+
+impl Extreme {
+    fn new(name: &'static str, wanted: Ordering) -> Self {
+        Self {
+            name,
+            wanted,
+            best: None,
+        }
+    }
+
+    fn add(&mut self, value: &Value) -> Result<()> {
+        let is_nan = |v: &Value| v.partial_cmp(v).is_none();
+        match &self.best {
+            _ if value.is_null() => {}
+            Some(best) if is_nan(best) => {}
+            None => self.best = Some(value.clone()),
+            Some(_) if is_nan(value) => self.best = Some(value.clone()),
+            Some(best) => match value.partial_cmp(best) {
+                Some(ordering) if ordering == self.wanted => self.best = Some(value.clone()),
+                Some(_) => {}
+                None => return Err(type_error(best.type_name(), value)),
+            },
+        }
+        Ok(())
+    }
+}
+
+impl Aggregator for Extreme {
+    fn init(&mut self) {
+        self.best = None;
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        self.add(first(self.name, args)?)
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        match &downcast::<Self>(other)?.best {
+            Some(best) => self.add(best),
+            None => Ok(()),
+        }
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        Ok(self.best.clone().unwrap_or(Value::Null))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+struct AnyValue {
+    value: Option<Value>,
+}
+
+impl Aggregator for AnyValue {
+    fn init(&mut self) {
+        *self = Self::default();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        let value = first("ANY_VALUE", args)?;
+        if self.value.is_none() && !value.is_null() {
+            self.value = Some(value.clone());
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        if self.value.is_none() {
+            self.value = downcast::<Self>(other)?.value.clone();
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        Ok(self.value.clone().unwrap_or(Value::Null))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// ARRAY_AGG, which keeps NULL elements unless IGNORE NULLS skips them.
+#[derive(Default)]
+struct ArrayAgg {
+    elements: Vec<Value>,
+}
+
+impl Aggregator for ArrayAgg {
+    fn init(&mut self) {
+        *self = Self::default();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        self.elements.push(first("ARRAY_AGG", args)?.clone());
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        let other = downcast::<Self>(other)?;
+        self.elements.extend(other.elements.iter().cloned());
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        if self.elements.is_empty() {
+            return Ok(Value::Null);
+        }
+        Ok(Value::Array(self.elements.clone()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+struct ArrayConcatAgg {
+    elements: Option<Vec<Value>>,
+}
+
+impl Aggregator for ArrayConcatAgg {
+    fn init(&mut self) {
+        *self = Self::default();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        match first("ARRAY_CONCAT_AGG", args)? {
+            Value::Null => {}
+            Value::Array(values) => self
+                .elements
+                .get_or_insert_with(Vec::new)
+                .extend(values.iter().cloned()),
+            other => return Err(type_error("ARRAY", other)),
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        if let Some(values) = &downcast::<Self>(other)?.elements {
+            self.update(&[Value::Array(values.clone())])?;
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        Ok(self.elements.clone().map_or(Value::Null, Value::Array))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// STRING_AGG(value [, separator]) over strings or bytes. The separator
+/// defaults to a comma; each row's separator is written before its value.
+#[derive(Default)]
+struct StringAgg {
+    joined: Option<Value>,
+    /// The separator of the first row, for merging onto another
+    /// accumulator.
+    first_separator: Option<Value>,
+}
+
+impl StringAgg {
+    fn append(&mut self, value: &Value, separator: Value) -> Result<()> {
+        let separator = match (value, separator) {
+            (_, Value::Null) if self.joined.is_some() => return Ok(()),
+            (Value::Bytes(_), Value::String(s)) if s == "," => Value::Bytes(b",".to_vec()),
+            (_, separator) => separator,
+        };
+        if self.first_separator.is_none() {
+            self.first_separator = Some(separator.clone());
+        }
+        self.joined = Some(match (self.joined.take(), value) {
+            (None, Value::String(_) | Value::Bytes(_)) => value.clone(),
+            (None, other) => return Err(type_error("VARCHAR", other)),
+            (Some(Value::String(mut joined)), Value::String(s)) => match separator {
+                Value::String(separator) => {
+                    joined.push_str(&separator);
+                    joined.push_str(s);
+                    Value::String(joined)
+                }
+                other => return Err(type_error("VARCHAR", &other)),
+            },
+            (Some(Value::Bytes(mut joined)), Value::Bytes(b)) => match separator {
+                Value::Bytes(separator) => {
+                    joined.extend_from_slice(&separator);
+                    joined.extend_from_slice(b);
+                    Value::Bytes(joined)
+                }
+                other => return Err(type_error("VARBINARY", &other)),
+            },
+            (Some(joined), other) => return Err(type_error(joined.type_name(), other)),
+        });
+        Ok(())
+    }
+}
+
+impl Aggregator for StringAgg {
+    fn init(&mut self) {
+        *self = Self::default();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        let value = first("STRING_AGG", args)?;
+        if value.is_null() {
+            return Ok(());
+        }
+        let separator = match args.get(1) {
+            Some(separator) => separator.clone(),
+            None => Value::from(","),
+        };
+        self.append(value, separator)
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        let other = downcast::<Self>(other)?;
+        if let (Some(joined), Some(separator)) = (&other.joined, &other.first_separator) {
+            self.append(joined, separator.clone())?;
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        Ok(self.joined.clone().unwrap_or(Value::Null))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Clone, Copy)]
+enum BitOp {
+    And,
+    Or,
+    Xor,
+}
+
+struct Bitwise {
+    name: &'static str,
+    op: BitOp,
+    result: Option<i64>,
+}
+
+impl Bitwise {
+    fn new(name: &'static str, op: BitOp) -> Self {
+        Self {
+            name,
+            op,
+            result: None,
+        }
+    }
+
+    fn add(&mut self, n: i64) {
+        self.result = Some(match (self.result, self.op) {
+            (None, _) => n,
+            (Some(r), BitOp::And) => r & n,
+            (Some(r), BitOp::Or) => r | n,
+            (Some(r), BitOp::Xor) => r ^ n,
+        });
+    }
+}
+
+impl Aggregator for Bitwise {
+    fn init(&mut self) {
+        self.result = None;
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        match first(self.name, args)? {
+            Value::Null => {}
+            Value::Int64(n) => self.add(*n),
+            other => return Err(type_error("BIGINT", other)),
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        if let Some(n) = downcast::<Self>(other)?.result {
+            self.add(n);
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        Ok(self.result.map_or(Value::Null, Value::Int64))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// LOGICAL_AND or LOGICAL_OR.
+struct Logical {
+    name: &'static str,
+    and: bool,
+    result: Option<bool>,
+}
+
+impl Logical {
+    fn new(name: &'static str, and: bool) -> Self {
+        Self {
+            name,
+            and,
+            result: None,
+        }
+    }
+
+    fn add(&mut self, b: bool) {
+        let result = self.result.unwrap_or(self.and);
+        self.result = Some(if self.and { result && b } else { result || b });
+    }
+}
+
+impl Aggregator for Logical {
+    fn init(&mut self) {
+        self.result = None;
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        match first(self.name, args)? {
+            Value::Null => {}
+            Value::Boolean(b) => self.add(*b),
+            other => return Err(type_error("BOOLEAN", other)),
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        if let Some(b) = downcast::<Self>(other)?.result {
+            self.add(b);
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        Ok(self.result.map_or(Value::Null, Value::Boolean))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The count, mean and sum of squared deviations of a series, updated
+/// with Welford's method and merged with Chan's.
+#[derive(Default, Clone, Copy)]
+struct Moments {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn add(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn merge(&mut self, other: &Moments) {
+        if other.n == 0.0 {
+            return;
+        }
+        let n = self.n + other.n;
+        let delta = other.mean - self.mean;
+        self.m2 += other.m2 + delta * delta * self.n * other.n / n;
+        self.mean += delta * other.n / n;
+        self.n = n;
+    }
+
+    /// The population or sample variance; `None` without enough rows.
+    fn variance(&self, sample: bool) -> Option<f64> {
+        let divisor = if sample { self.n - 1.0 } else { self.n };
+        (divisor > 0.0).then(|| self.m2 / divisor)
+    }
+}
+
+/// The variance or standard deviation, of the population or a sample.
+struct Variance {
+    name: &'static str,
+    sample: bool,
+    root: bool,
+    moments: Moments,
+}
+
+impl Variance {
+    fn new(name: &'static str, sample: bool, root: bool) -> Self {
+        Self {
+            name,
+            sample,
+            root,
+            moments: Moments::default(),
+        }
+    }
+}
+
+impl Aggregator for Variance {
+    fn init(&mut self) {
+        self.moments = Moments::default();
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        if let Some(x) = number(first(self.name, args)?)? {
+            self.moments.add(x);
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        self.moments.merge(&downcast::<Self>(other)?.moments);
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        let variance = self.moments.variance(self.sample);
+        let result = if self.root {
+            variance.map(f64::sqrt)
+        } else {
+            variance
+        };
+        Ok(result.map_or(Value::Null, Value::Float64))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Statistic {
+    Correlation,
+    SampleCovariance,
+    PopulationCovariance,
+}
+
+/// CORR, COVAR_SAMP or COVAR_POP over the rows where both arguments are
+/// not NULL.
+struct Covariance {
+    name: &'static str,
+    statistic: Statistic,
+    x: Moments,
+    y: Moments,
+    /// The sum of the products of the deviations from the means.
+    co_moment: f64,
+}
+
+impl Covariance {
+    fn new(name: &'static str, statistic: Statistic) -> Self {
+        Self {
+            name,
+            statistic,
+            x: Moments::default(),
+            y: Moments::default(),
+            co_moment: 0.0,
+        }
+    }
+}
+
+impl Aggregator for Covariance {
+    fn init(&mut self) {
+        self.x = Moments::default();
+        self.y = Moments::default();
+        self.co_moment = 0.0;
+    }
+
+    fn update(&mut self, args: &[Value]) -> Result<()> {
+        let [x, y] = args else {
+            return Err(Error::new(ErrorKind::InvalidArgumentCount {
+                function: self.name.to_string(),
+                expected: 2,
+                found: args.len(),
+            }));
+        };
+        let (Some(x), Some(y)) = (number(x)?, number(y)?) else {
+            return Ok(());
+        };
+        let dx = x - self.x.mean;
+        self.x.add(x);
+        self.y.add(y);
+        self.co_moment += dx * (y - self.y.mean);
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &dyn Aggregator) -> Result<()> {
+        let other = downcast::<Self>(other)?;
+        if other.x.n == 0.0 {
+            return Ok(());
+        }
+        let n = self.x.n + other.x.n;
+        let dx = other.x.mean - self.x.mean;
+        let dy = other.y.mean - self.y.mean;
+        self.co_moment += other.co_moment + dx * dy * self.x.n * other.x.n / n;
+        self.x.merge(&other.x);
+        self.y.merge(&other.y);
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<Value> {
+        let n = self.x.n;
+        let result = match self.statistic {
+            Statistic::PopulationCovariance => (n > 0.0).then(|| self.co_moment / n),
+            Statistic::SampleCovariance => (n > 1.0).then(|| self.co_moment / (n - 1.0)),
+            Statistic::Correlation => {
+                (n > 1.0).then(|| self.co_moment / (self.x.m2 * self.y.m2).sqrt())
+            }
+        };
+        Ok(result.map_or(Value::Null, Value::Float64))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The first argument of an aggregate that takes one.
+fn first<'a>(function: &str, args: &'a [Value]) -> Result<&'a Value> {
+    args.first().ok_or_else(|| {
+        Error::new(ErrorKind::InvalidArgumentCount {
+            function: function.to_string(),
+            expected: 1,
+            found: 0,
+        })
+    })
+}
+
+/// A numeric argument as a float; `None` for NULL.
+fn number(value: &Value) -> Result<Option<f64>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Int64(_) | Value::Float64(_) | Value::Numeric(_) => {
+            match value.cast_to(&SqlType::Float64)? {
+                Value::Float64(f) => Ok(Some(f)),
+                other => Err(type_error("DOUBLE PRECISION", &other)),
+            }
+        }
+        other => Err(type_error("a number", other)),
+    }
+}
+
+fn downcast<T: Aggregator>(other: &dyn Aggregator) -> Result<&T> {
+    other.as_any().downcast_ref::<T>().ok_or_else(|| {
+        Error::new(ErrorKind::Internal(
+            "merging accumulators of different aggregates".to_string(),
+        ))
+    })
+}
+
+fn type_error(expected: &str, value: &Value) -> Error {
+    Error::new(ErrorKind::TypeMismatch {
+        expected: expected.to_string(),
+        found: value.type_name().to_string(),
+    })
+}
+
+/// Append an encoding of `value` that is the same for equal values of one
+/// type, for DISTINCT: NaNs are all alike, and zero has one sign.
+fn encode(value: &Value, out: &mut Vec<u8>) {
+    let mut bytes = |tag: u8, bytes: &[u8]| {
+        out.push(tag);
+        out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        out.extend_from_slice(bytes);
+    };
+    match value {
+        Value::Null => bytes(0, &[]),
+        Value::Boolean(b) => bytes(1, &[u8::from(*b)]),
+        Value::Int64(n) => bytes(2, &n.to_le_bytes()),
+        Value::Float64(f) if f.is_nan() => bytes(3, &[]),
+        Value::Float64(f) => bytes(3, &(f + 0.0).to_le_bytes()),
+        Value::Numeric(n) => bytes(4, n.to_string().as_bytes()),
+        Value::String(s) => bytes(5, s.as_bytes()),
+        Value::Bytes(b) => bytes(6, b),
+        Value::Date(days) => bytes(7, &days.to_le_bytes()),
+        Value::Time(micros) => bytes(8, &micros.to_le_bytes()),
+        Value::Datetime(micros) => bytes(9, &micros.to_le_bytes()),
+        Value::Timestamp(micros) => bytes(10, &micros.to_le_bytes()),
+        Value::Interval(interval) => bytes(11, &interval.normalized_micros().to_le_bytes()),
+        Value::Json(json) => bytes(12, json.as_bytes()),
+        Value::Array(values) => {
+            bytes(13, &(values.len() as u64).to_le_bytes());
+            for value in values {
+                encode(value, out);
+            }
+        }
+        Value::Struct(fields) => {
+            bytes(14, &(fields.len() as u64).to_le_bytes());
+            for (_, value) in fields {
+                encode(value, out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Catalog;
+    use crate::parser::Parser;
+    use crate::types::Numeric;
+    use crate::MemoryCatalog;
+
+    /// Run `name` with `options` over `rows`, once in a single accumulator
+    /// and once split in two and merged, and check the two agree.
+    fn aggregate(name: &str, options: &AggregateOptions, rows: &[Vec<Value>]) -> Result<Value> {
+        let registry = AggregateRegistry::with_builtins();
+        let mut whole = registry.create(name, options).unwrap();
+        for row in rows {
+            whole.update(row)?;
+        }
+        let result = whole.finalize()?;
+
+        let (left, right) = rows.split_at(rows.len() / 2);
+        let mut merged = registry.create(name, options).unwrap();
+        let mut other = registry.create(name, options).unwrap();
+        for row in left {
+            merged.update(row)?;
+        }
+        for row in right {
+            other.update(row)?;
+        }
+        merged.merge(&*other)?;
+        let merged = merged.finalize()?;
+        match (&result, &merged) {
+            (Value::Float64(a), Value::Float64(b)) => {
+                assert!((a - b).abs() < 1e-9, "{}: {} merged as {}", name, a, b)
+            }
+            _ => assert_eq!(result, merged, "{} merged", name),
+        }
+        Ok(result)
+    }
+
+    fn column(values: &[Value]) -> Vec<Vec<Value>> {
+        values.iter().map(|v| vec![v.clone()]).collect()
+    }
+
+    #[test]
+    fn test_builtin_aggregates() {
+        use Value::{Boolean, Float64, Int64, Null};
+
+        let ints = column(&[Int64(4), Null, Int64(1), Int64(7), Int64(4)]);
+        let plain = AggregateOptions::default();
+        let cases = [
+            ("COUNT", &ints, Int64(4)),
+            ("SUM", &ints, Float64(16.0)),
+            ("AVG", &ints, Float64(4.0)),
+            ("MIN", &ints, Int64(1)),
+            ("MAX", &ints, Int64(7)),
+            ("ANY_VALUE", &ints, Int64(4)),
+            ("BIT_OR", &ints, Int64(7)),
+            ("BIT_XOR", &ints, Int64(6)),
+            ("VAR_POP", &ints, Float64(4.5)),
+            ("VARIANCE", &ints, Float64(6.0)),
+            ("STDDEV", &ints, Float64(6f64.sqrt())),
+            (
+                "ARRAY_AGG",
+                &ints,
+                Value::Array(vec![Int64(4), Null, Int64(1), Int64(7), Int64(4)]),
+            ),
+        ];
+        for (name, rows, expected) in cases {
+            assert_eq!(aggregate(name, &plain, rows).unwrap(), expected, "{}", name);
+        }
+
+        let bools = column(&[Boolean(true), Null, Boolean(false)]);
+        assert_eq!(
+            aggregate("LOGICAL_AND", &plain, &bools).unwrap(),
+            Boolean(false)
+        );
+        assert_eq!(
+            aggregate("LOGICAL_OR", &plain, &bools).unwrap(),
+            Boolean(true)
+        );
+        assert_eq!(aggregate("COUNTIF", &plain, &bools).unwrap(), Int64(1));
+
+        let pairs: Vec<Vec<Value>> = [(1, 2), (2, 4), (3, 7)]
+            .iter()
+            .map(|&(x, y)| vec![Int64(x), Int64(y)])
+            .chain([vec![Int64(9), Null]])
+            .collect();
+        assert_eq!(
+            aggregate("COVAR_POP", &plain, &pairs).unwrap(),
+            Float64(5.0 / 3.0)
+        );
+        assert_eq!(
+            aggregate("COVAR_SAMP", &plain, &pairs).unwrap(),
+            Float64(2.5)
+        );
+        let corr = aggregate("CORR", &plain, &pairs).unwrap();
+        assert!(matches!(corr, Float64(r) if (r - 0.9933992677987828).abs() < 1e-12));
+
+        let numerics = column(&[
+            Value::Numeric(Numeric::parse("1.25").unwrap()),
+            Value::Numeric(Numeric::parse("2.5").unwrap()),
+        ]);
+        assert_eq!(
+            aggregate("SUM", &plain, &numerics).unwrap(),
+            Value::Numeric(Numeric::parse("3.75").unwrap())
+        );
+        let arrays = column(&[
+            Value::Array(vec![Int64(1)]),
+            Null,
+            Value::Array(vec![Int64(2)]),
+        ]);
+        assert_eq!(
+            aggregate("ARRAY_CONCAT_AGG", &plain, &arrays).unwrap(),
+            Value::Array(vec![Int64(1), Int64(2)])
+        );
+    }
+
+    #[test]
+    fn test_empty_and_null_groups() {
+        let plain = AggregateOptions::default();
+        let nulls = column(&[Value::Null, Value::Null]);
+        for name in ["COUNT", "COUNTIF"] {
+            assert_eq!(aggregate(name, &plain, &nulls).unwrap(), Value::Int64(0));
+        }
+        for name in ["SUM", "AVG", "MIN", "STRING_AGG", "LOGICAL_AND", "VARIANCE"] {
+            assert_eq!(
+                aggregate(name, &plain, &nulls).unwrap(),
+                Value::Null,
+                "{}",
+                name
+            );
+            assert_eq!(
+                aggregate(name, &plain, &[]).unwrap(),
+                Value::Null,
+                "{}",
+                name
+            );
+        }
+        // COUNT(*) counts rows, NULL or not
+        let star = vec![Vec::new(); 3];
+        assert_eq!(aggregate("COUNT", &plain, &star).unwrap(), Value::Int64(3));
+        // Sample statistics need two rows
+        let one = column(&[Value::Int64(1)]);
+        assert_eq!(aggregate("STDDEV_SAMP", &plain, &one).unwrap(), Value::Null);
+        assert_eq!(
+            aggregate("STDDEV_POP", &plain, &one).unwrap(),
+            Value::Float64(0.0)
+        );
+    }
+
+    #[test]
+    fn test_aggregate_options() {
+        let names = ["b", "a", "c", "a", "b"];
+        let rows: Vec<Vec<Value>> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| vec![Value::from(*name), Value::from("-"), Value::Int64(i as i64)])
+            .collect();
+        let without_keys: Vec<Vec<Value>> = rows.iter().map(|row| row[..2].to_vec()).collect();
+
+        let plain = AggregateOptions::default();
+        assert_eq!(
+            aggregate("STRING_AGG", &plain, &without_keys).unwrap(),
+            Value::from("b-a-c-a-b")
+        );
+        let distinct = AggregateOptions {
+            distinct: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            aggregate("STRING_AGG", &distinct, &without_keys).unwrap(),
+            Value::from("b-a-c")
+        );
+        let by_name_desc = AggregateOptions {
+            order_by: vec![SortKey::descending()],
+            ..Default::default()
+        };
+        let keyed_by_name: Vec<Vec<Value>> = without_keys
+            .iter()
+            .map(|row| vec![row[0].clone(), row[1].clone(), row[0].clone()])
+            .collect();
+        assert_eq!(
+            aggregate("STRING_AGG", &by_name_desc, &keyed_by_name).unwrap(),
+            Value::from("c-b-b-a-a")
+        );
+        let last_two = AggregateOptions {
+            order_by: vec![SortKey::descending()],
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            aggregate(
+                "ARRAY_AGG",
+                &last_two,
+                &column(&names.map(Value::from))
+                    .into_iter()
+                    .zip(0..)
+                    .map(|(mut row, i)| {
+                        row.push(Value::Int64(i));
+                        row
+                    })
+                    .collect::<Vec<_>>()
+            )
+            .unwrap(),
+            Value::Array(vec![Value::from("b"), Value::from("a")])
+        );
+
+        let distinct_count = AggregateOptions {
+            distinct: true,
+            ..Default::default()
+        };
+        let floats = column(&[
+            Value::Float64(0.0),
+            Value::Float64(-0.0),
+            Value::Float64(f64::NAN),
+            Value::Float64(f64::NAN),
+            Value::Null,
+        ]);
+        assert_eq!(
+            aggregate("COUNT", &distinct_count, &floats).unwrap(),
+            Value::Int64(2)
+        );
+        let ignore_nulls = AggregateOptions {
+            ignore_nulls: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            aggregate("ARRAY_AGG", &ignore_nulls, &floats[..2]).unwrap(),
+            Value::Array(vec![Value::Float64(0.0), Value::Float64(-0.0)])
+        );
+        assert_eq!(
+            aggregate("ARRAY_AGG", &ignore_nulls, &floats[4..]).unwrap(),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_options_from_call() {
+        let expr = Parser::new("STRING_AGG(DISTINCT x, ',' ORDER BY x DESC, y NULLS LAST LIMIT 3)")
+            .parse_expression()
+            .unwrap();
+        let crate::ast::ExprKind::Function(call) = &expr.kind else {
+            panic!("not a call: {:?}", expr.kind);
+        };
+        let options = AggregateOptions::from_call(call).unwrap();
+        assert_eq!(
+            options,
+            AggregateOptions {
+                distinct: true,
+                ignore_nulls: false,
+                order_by: vec![
+                    SortKey::descending(),
+                    SortKey {
+                        descending: false,
+                        nulls_first: false,
+                    },
+                ],
+                limit: Some(3),
+            }
+        );
+    }
+
+    #[test]
+    fn test_aggregate_errors() {
+        let plain = AggregateOptions::default();
+        let err = aggregate("SUM", &plain, &column(&[Value::from("a")])).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::TypeMismatch { .. }));
+        let mixed = column(&[Value::Int64(1), Value::from("a")]);
+        let err = aggregate("MAX", &plain, &mixed).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::TypeMismatch { .. }));
+        let err = aggregate("CORR", &plain, &column(&[Value::Int64(1)])).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::InvalidArgumentCount { .. }));
+
+        let registry = AggregateRegistry::with_builtins();
+        let mut sum = registry.create("SUM", &plain).unwrap();
+        let count = registry.create("COUNT", &plain).unwrap();
+        let err = sum.merge(&*count).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Internal(_)));
+    }
+
+    #[test]
+    fn test_registry_covers_catalog_aggregates() {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+        let registry = AggregateRegistry::with_builtins();
+        let missing: Vec<String> = catalog
+            .list_functions()
+            .unwrap()
+            .into_iter()
+            .map(|f| f.signature)
+            .filter(|f| f.is_aggregate && f.name != "GROUPING" && !registry.contains(&f.name))
+            .map(|f| f.name)
+            .collect();
+        assert!(missing.is_empty(), "no accumulator for {:?}", missing);
+    }
+}
@@ -0,0 +1,356 @@
+//! Scalar functions for the evaluator.
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::types::{SqlType, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// An implementation of a scalar function, called with the values of its
+/// arguments.
+pub type ScalarFunction = Arc<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>;
+
+/// The scalar functions an [`Evaluator`](super::Evaluator) can call, by
+/// name. Names match ignoring ASCII case.
+#[derive(Clone)]
+pub struct FunctionRegistry {
+    functions: HashMap<String, ScalarFunction>,
+}
+
+impl Default for FunctionRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+impl fmt::Debug for FunctionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<_> = self.functions.keys().collect();
+        names.sort();
+        f.debug_struct("FunctionRegistry")
+            .field("functions", &names)
+            .finish()
+    }
+}
+
+impl FunctionRegistry {
+    /// A registry with no functions.
+    pub fn new() -> Self {
+        Self {
+            functions: HashMap::new(),
+        }
+    }
+
+    /// A registry with the built-in string, math, and date functions:
+    /// UPPER, LOWER, LENGTH, CHAR_LENGTH, CHARACTER_LENGTH, BYTE_LENGTH,
+    /// CONCAT, TRIM, LTRIM, RTRIM, REPLACE, STARTS_WITH, ENDS_WITH, ABS,
+    /// SIGN, MOD, GREATEST, LEAST, DATE_FROM_UNIX_DATE, TIMESTAMP_MICROS,
+    /// and TIME and DATETIME of a timestamp or datetime. Each returns NULL
+    /// when an argument is NULL.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register_strict("UPPER", |args| {
+            let [s] = strings("UPPER", args)?;
+            Ok(Value::String(s.to_uppercase()))
+        });
+        registry.register_strict("LOWER", |args| {
+            let [s] = strings("LOWER", args)?;
+            Ok(Value::String(s.to_lowercase()))
+        });
+        for name in ["LENGTH", "CHAR_LENGTH", "CHARACTER_LENGTH"] {
+            registry.register_strict(name, move |args| match fixed::<1>(name, args)? {
+                [Value::String(s)] => Ok(Value::Int64(s.chars().count() as i64)),
+                [Value::Bytes(b)] if name == "LENGTH" => Ok(Value::Int64(b.len() as i64)),
+                [other] => Err(type_error("VARCHAR", other)),
+            });
+        }
+        registry.register_strict("BYTE_LENGTH", |args| {
+            match fixed::<1>("BYTE_LENGTH", args)? {
+                [Value::String(s)] => Ok(Value::Int64(s.len() as i64)),
+                [Value::Bytes(b)] => Ok(Value::Int64(b.len() as i64)),
+                [other] => Err(type_error("VARCHAR", other)),
+            }
+        });
+        registry.register_strict("CONCAT", |args| {
+            let mut result = String::new();
+            for arg in args {
+                match arg {
+                    Value::String(s) => result.push_str(s),
+                    other => match other.cast_to(&crate::types::SqlType::Varchar(None))? {
+                        Value::String(s) => result.push_str(&s),
+                        other => return Err(type_error("VARCHAR", &other)),
+                    },
+                }
+            }
+            Ok(Value::String(result))
+        });
+        registry.register_strict("TRIM", |args| trim("TRIM", args, |s, c| s.trim_matches(c)));
+        registry.register_strict("LTRIM", |args| {
+            trim("LTRIM", args, |s, c| s.trim_start_matches(c))
+        });
+        registry.register_strict("RTRIM", |args| {
+            trim("RTRIM", args, |s, c| s.trim_end_matches(c))
+        });
+        registry.register_strict("REPLACE", |args| {
+            let [s, from, to] = strings("REPLACE", args)?;
+            // Replacing the empty string would insert `to` between every
+            // character; it leaves the string as it is instead
+            if from.is_empty() {
+                return Ok(Value::String(s.to_string()));
+            }
+            Ok(Value::String(s.replace(from, to)))
+        });
+        registry.register_strict("STARTS_WITH", |args| {
+            let [s, prefix] = strings("STARTS_WITH", args)?;
+            Ok(Value::Boolean(s.starts_with(prefix)))
+        });
+        registry.register_strict("ENDS_WITH", |args| {
+            let [s, suffix] = strings("ENDS_WITH", args)?;
+            Ok(Value::Boolean(s.ends_with(suffix)))
+        });
+        registry.register_strict("ABS", |args| match fixed::<1>("ABS", args)? {
+            [value] if value.partial_cmp(&Value::Int64(0)) == Some(Ordering::Less) => {
+                Value::Int64(0).sub(value)
+            }
+            [value @ (Value::Int64(_) | Value::Float64(_) | Value::Numeric(_))] => {
+                Ok(value.clone())
+            }
+            [other] => Err(type_error("a number", other)),
+        });
+        registry.register_strict("SIGN", |args| match fixed::<1>("SIGN", args)? {
+            [value @ (Value::Int64(_) | Value::Float64(_) | Value::Numeric(_))] => {
+                let sign = match value.partial_cmp(&Value::Int64(0)) {
+                    Some(Ordering::Less) => -1,
+                    Some(Ordering::Equal) => 0,
+                    Some(Ordering::Greater) => 1,
+                    None => return Ok(value.clone()),
+                };
+                Value::Int64(sign).cast_to(&value.data_type())
+            }
+            [other] => Err(type_error("a number", other)),
+        });
+        registry.register_strict("MOD", |args| {
+            let [a, b] = fixed::<2>("MOD", args)?;
+            a.rem(b)
+        });
+        registry.register_strict("GREATEST", |args| {
+            extreme("GREATEST", args, Ordering::Greater)
+        });
+        registry.register_strict("LEAST", |args| extreme("LEAST", args, Ordering::Less));
+        registry.register_strict("DATE_FROM_UNIX_DATE", |args| {
+            match fixed::<1>("DATE_FROM_UNIX_DATE", args)? {
+                [Value::Int64(days)] => i32::try_from(*days).map(Value::Date).map_err(|_| {
+                    Error::new(ErrorKind::NumericOverflow(format!(
+                        "DATE_FROM_UNIX_DATE({})",
+                        days
+                    )))
+                }),
+                [other] => Err(type_error("BIGINT", other)),
+            }
+        });
+        registry.register_strict("TIMESTAMP_MICROS", |args| {
+            match fixed::<1>("TIMESTAMP_MICROS", args)? {
+                [Value::Int64(micros)] => Ok(Value::Timestamp(*micros)),
+                [other] => Err(type_error("BIGINT", other)),
+            }
+        });
+        for (name, target) in [("TIME", SqlType::Time), ("DATETIME", SqlType::Datetime)] {
+            registry.register_strict(name, move |args| match fixed::<1>(name, args)? {
+                [value @ (Value::Datetime(_) | Value::Timestamp(_))] => value.cast_to(&target),
+                [other] => Err(type_error("TIMESTAMP", other)),
+            });
+        }
+        registry
+    }
+
+    /// Add a function, replacing any with the same name.
+    pub fn register(
+        &mut self,
+        name: &str,
+        function: impl Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+    ) {
+        self.functions
+            .insert(name.to_ascii_uppercase(), Arc::new(function));
+    }
+
+    /// Add a function that returns NULL, without being called, when an
+    /// argument is NULL.
+    fn register_strict(
+        &mut self,
+        name: &str,
+        function: impl Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+    ) {
+        self.register(name, move |args| {
+            if args.iter().any(Value::is_null) {
+                return Ok(Value::Null);
+            }
+            function(args)
+        });
+    }
+
+    /// The function called `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<&ScalarFunction> {
+        self.functions.get(&name.to_ascii_uppercase())
+    }
+
+    /// Whether there is a function called `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+}
+
+/// The arguments of a function taking `N` of them.
+fn fixed<'a, const N: usize>(function: &str, args: &'a [Value]) -> Result<&'a [Value; N]> {
+    args.try_into().map_err(|_| {
+        Error::new(ErrorKind::InvalidArgumentCount {
+            function: function.to_string(),
+            expected: N,
+            found: args.len(),
+        })
+    })
+}
+
+/// The arguments of a function taking `N` strings.
+fn strings<'a, const N: usize>(function: &str, args: &'a [Value]) -> Result<[&'a str; N]> {
+    let args = fixed::<N>(function, args)?;
+    let mut strings = [""; N];
+    for (string, arg) in strings.iter_mut().zip(args) {
+        *string = match arg {
+            Value::String(s) => s,
+            other => return Err(type_error("VARCHAR", other)),
+        };
+    }
+    Ok(strings)
+}
+
+fn trim(
+    function: &str,
+    args: &[Value],
+    trim: for<'a> fn(&'a str, &[char]) -> &'a str,
+) -> Result<Value> {
+    let (s, characters) = match args.len() {
+        1 => (strings::<1>(function, args)?[0], " "),
+        _ => {
+            let [s, characters] = strings::<2>(function, args)?;
+            (s, characters)
+        }
+    };
+    let characters: Vec<char> = characters.chars().collect();
+    Ok(Value::String(trim(s, &characters).to_string()))
+}
+
+/// The greatest or least of the arguments, by `wanted`.
+fn extreme(function: &str, args: &[Value], wanted: Ordering) -> Result<Value> {
+    let Some(first) = args.first() else {
+        return Err(Error::new(ErrorKind::InvalidArgumentCount {
+            function: function.to_string(),
+            expected: 1,
+            found: 0,
+        }));
+    };
+    let mut best = first;
+    for arg in args {
+        // NaN is greater and less than everything
+        if arg.partial_cmp(arg).is_none() {
+            return Ok(arg.clone());
+        }
+        match arg.partial_cmp(best) {
+            Some(ordering) if ordering == wanted => best = arg,
+            Some(_) => {}
+            None => return Err(type_error(best.type_name(), arg)),
+        }
+    }
+    Ok(best.clone())
+}
+
+fn type_error(expected: &str, value: &Value) -> Error {
+    Error::new(ErrorKind::TypeMismatch {
+        expected: expected.to_string(),
+        found: value.type_name().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::{evaluate, Params};
+    use crate::parser::Parser;
+
+    fn eval(sql: &str) -> Result<Value> {
+        let expr = Parser::new(sql).parse_expression().unwrap();
+        evaluate(&expr, &(), &Params::new())
+    }
+
+    #[test]
+    fn test_builtins() {
+        let cases = [
+            ("UPPER('straße')", Value::from("STRASSE")),
+            ("lower('ABC')", Value::from("abc")),
+            ("LENGTH('héllo')", Value::Int64(5)),
+            ("BYTE_LENGTH('héllo')", Value::Int64(6)),
+            ("LENGTH(b'ab')", Value::Int64(2)),
+            ("CONCAT('a', 1, TRUE)", Value::from("a1true")),
+            ("CONCAT('a', NULL)", Value::Null),
+            ("TRIM('  a ')", Value::from("a")),
+            ("LTRIM('xxaxx', 'x')", Value::from("axx")),
+            ("RTRIM('xxaxx', 'x')", Value::from("xxa")),
+            ("REPLACE('banana', 'an', '_')", Value::from("b__a")),
+            ("REPLACE('ab', TRIM(' '), '_')", Value::from("ab")),
+            ("STARTS_WITH('banana', 'ban')", Value::Boolean(true)),
+            ("ENDS_WITH('banana', 'ban')", Value::Boolean(false)),
+            ("ABS(-3)", Value::Int64(3)),
+            ("ABS(-2.5)", Value::Float64(2.5)),
+            ("SIGN(-2.5)", Value::Float64(-1.0)),
+            ("SIGN(0)", Value::Int64(0)),
+            ("MOD(7, 3)", Value::Int64(1)),
+            ("GREATEST(1, 2.5, 2)", Value::Float64(2.5)),
+            ("LEAST('b', 'a', 'c')", Value::from("a")),
+            ("GREATEST(1, NULL)", Value::Null),
+            ("DATE_FROM_UNIX_DATE(-1)", Value::Date(-1)),
+            ("TIMESTAMP_MICROS(5)", Value::Timestamp(5)),
+            (
+                "TIME(TIMESTAMP '1970-01-02 00:00:01+00')",
+                Value::Time(1_000_000),
+            ),
+            ("DATETIME(TIMESTAMP_MICROS(-1))", Value::Datetime(-1)),
+            ("TIME(NULL)", Value::Null),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(eval(sql).unwrap(), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_builtin_errors() {
+        let err = eval("UPPER('a', 'b')").unwrap_err();
+        assert_eq!(
+            err.kind,
+            ErrorKind::InvalidArgumentCount {
+                function: "UPPER".to_string(),
+                expected: 1,
+                found: 2,
+            }
+        );
+        let err = eval("UPPER(1)").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::TypeMismatch { .. }));
+        let err = eval("GREATEST(1, 'a')").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::TypeMismatch { .. }));
+        let err = eval("MOD(1, 0)").unwrap_err();
+        assert_eq!(err.kind, ErrorKind::DivisionByZero);
+        let err = eval("DATE_FROM_UNIX_DATE(9223372036854775807)").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::NumericOverflow(_)));
+        let err = eval("TIME(DATE '2024-01-01')").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::TypeMismatch { .. }));
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = FunctionRegistry::new();
+        assert!(!registry.contains("upper"));
+        registry.register("Twice", |args| args[0].add(&args[0]));
+        let twice = registry.get("TWICE").unwrap();
+        assert_eq!(twice(&[Value::Int64(4)]).unwrap(), Value::Int64(8));
+        assert!(FunctionRegistry::default().contains("Upper"));
+    }
+}
@@ -0,0 +1,1074 @@
+//! Evaluation of expressions against a row of values.
+//!
+//! [`evaluate`] computes the [`Value`] of an expression for one row, with
+//! SQL semantics: NULL propagates through operators, AND and OR follow
+//! three-valued logic, and IN and the quantified comparisons are NULL when
+//! no candidate decides them. This is enough for an embedder to run WHERE
+//! filters and computed select items over its own storage; subqueries,
+//! aggregates and window functions are left to the executor.
+//!
+//! Columns are read through a [`RowAccessor`], parameters from [`Params`],
+//! and function calls dispatch to a [`FunctionRegistry`].
+//!
+//! ```
+//! use vibesql::eval::{evaluate, Params};
+//! use vibesql::parser::Parser;
+//! use vibesql::types::Value;
+//! use std::collections::HashMap;
+//!
+//! let row = HashMap::from([("price".to_string(), Value::Int64(12))]);
+//! let expr = Parser::new("CASE WHEN price > 10 THEN UPPER('dear') END")
+//!     .parse_expression()
+//!     .unwrap();
+//! let value = evaluate(&expr, &row, &Params::new()).unwrap();
+//! assert_eq!(value, Value::from("DEAR"));
+//! ```
+
+mod aggregate;
+mod functions;
+
+pub use aggregate::{AggregateFactory, AggregateOptions, AggregateRegistry, Aggregator, SortKey};
+pub use functions::{FunctionRegistry, ScalarFunction};
+
+use crate::ast::*;
+use crate::error::{Error, ErrorKind, Result};
+use crate::types::{SqlType, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// The columns of the row an expression is evaluated against.
+pub trait RowAccessor {
+    /// The value of `column`, read through `table` when the reference is
+    /// qualified with a table name or alias; `None` if the row has no such
+    /// column.
+    fn get(&self, table: Option<&str>, column: &str) -> Option<Value>;
+}
+
+/// No columns, for evaluating expressions that read none.
+impl RowAccessor for () {
+    fn get(&self, _table: Option<&str>, _column: &str) -> Option<Value> {
+        None
+    }
+}
+
+/// Columns by unqualified name, ignoring ASCII case.
+impl RowAccessor for HashMap<String, Value> {
+    fn get(&self, _table: Option<&str>, column: &str) -> Option<Value> {
+        self.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(column))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+/// Values for the parameter placeholders of an expression.
+///
+/// `?` placeholders take positional values in order of appearance, and
+/// `$n` takes the n-th, counted from 1. Named `@name` placeholders match
+/// ignoring ASCII case.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    positional: Vec<Value>,
+    named: HashMap<String, Value>,
+}
+
+impl Params {
+    /// No parameter values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the value of the next positional parameter.
+    pub fn with_positional(mut self, value: impl Into<Value>) -> Self {
+        self.positional.push(value.into());
+        self
+    }
+
+    /// Set the value of the `@name` parameter.
+    pub fn with_named(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.named.insert(name.to_ascii_lowercase(), value.into());
+        self
+    }
+
+    /// The value bound to `parameter`, if any.
+    pub fn get(&self, parameter: &Parameter) -> Option<&Value> {
+        match parameter {
+            Parameter::Named(name) => self.named.get(&name.to_ascii_lowercase()),
+            Parameter::Positional(n) | Parameter::Numbered(n) => {
+                self.positional.get((*n as usize).checked_sub(1)?)
+            }
+        }
+    }
+}
+
+/// Evaluate `expr` against `row`, calling the scalar functions of
+/// [`FunctionRegistry::with_builtins`].
+///
+/// Errors carry the span of the expression that failed: an unknown column
+/// is [`ErrorKind::UndefinedColumn`], an operand of the wrong type
+/// [`ErrorKind::TypeMismatch`], and forms this module does not evaluate,
+/// such as subqueries, [`ErrorKind::UnsupportedFeature`].
+pub fn evaluate(expr: &Expr, row: &dyn RowAccessor, params: &Params) -> Result<Value> {
+    Evaluator::default().evaluate(expr, row, params)
+}
+
+/// Evaluates expressions with a given set of scalar functions.
+#[derive(Clone, Default)]
+pub struct Evaluator {
+    functions: FunctionRegistry,
+}
+
+impl Evaluator {
+    /// An evaluator calling `functions`.
+    pub fn new(functions: FunctionRegistry) -> Self {
+        Self { functions }
+    }
+
+    /// The functions calls dispatch to.
+    pub fn functions(&self) -> &FunctionRegistry {
+        &self.functions
+    }
+
+    /// Evaluate `expr` against `row`, as [`evaluate`] does.
+    pub fn evaluate(&self, expr: &Expr, row: &dyn RowAccessor, params: &Params) -> Result<Value> {
+        Context {
+            functions: &self.functions,
+            row,
+            params,
+        }
+        .eval(expr)
+    }
+}
+
+struct Context<'a> {
+    functions: &'a FunctionRegistry,
+    row: &'a dyn RowAccessor,
+    params: &'a Params,
+}
+
+impl Context<'_> {
+    fn eval(&self, expr: &Expr) -> Result<Value> {
+        self.eval_kind(&expr.kind).map_err(|err| match err.span() {
+            Some(_) => err,
+            None => Error::with_span(err.kind, expr.span),
+        })
+    }
+
+    fn eval_kind(&self, kind: &ExprKind) -> Result<Value> {
+        match kind {
+            ExprKind::Null
+            | ExprKind::Boolean(_)
+            | ExprKind::Integer(_)
+            | ExprKind::Float(_)
+            | ExprKind::String(_)
+            | ExprKind::Bytes(_) => Ok(Value::from_literal(kind).expect("a literal has a value")),
+            ExprKind::TypedLiteral { data_type, value } => {
+                match Value::parse_typed_literal(*data_type, value) {
+                    Ok(Some(value)) => Ok(value),
+                    Ok(None) => Err(unsupported(format!("{} literals", data_type))),
+                    Err(reason) => Err(Error::new(ErrorKind::InvalidCast(format!(
+                        "'{}' to {}: {}",
+                        value, data_type, reason
+                    )))),
+                }
+            }
+            ExprKind::Interval { value, unit } => {
+                let count = match self.eval(value)? {
+                    Value::Null => return Ok(Value::Null),
+                    Value::Int64(n) => n,
+                    // `INTERVAL '1' DAY` quotes its count
+                    Value::String(s) => s.trim().parse().map_err(|_| {
+                        Error::new(ErrorKind::InvalidCast(format!(
+                            "'{}' to INTERVAL {}: not a whole number",
+                            s, unit
+                        )))
+                    })?,
+                    other => return Err(type_error("BIGINT", &other)),
+                };
+                let literal = ExprKind::Interval {
+                    value: Box::new(Expr::new(ExprKind::Integer(count), value.span)),
+                    unit: *unit,
+                };
+                Value::from_literal(&literal).ok_or_else(|| {
+                    Error::new(ErrorKind::NumericOverflow(format!(
+                        "INTERVAL {} {}",
+                        count, unit
+                    )))
+                })
+            }
+            ExprKind::Array {
+                element_type,
+                elements,
+            } => {
+                let element_type = element_type.as_deref().map(SqlType::try_from).transpose()?;
+                let values = elements
+                    .iter()
+                    .map(|element| {
+                        let value = self.eval(element)?;
+                        match &element_type {
+                            Some(data_type) => value.cast_to(data_type),
+                            None => Ok(value),
+                        }
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Value::Array(values))
+            }
+            ExprKind::Struct { fields } => {
+                let fields = fields
+                    .iter()
+                    .map(|field| {
+                        let name = field.name.as_ref().map(|n| n.value.clone());
+                        Ok((name.unwrap_or_default(), self.eval(&field.value)?))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Value::Struct(fields))
+            }
+            ExprKind::Row(values) => {
+                let fields = values
+                    .iter()
+                    .map(|value| Ok((String::new(), self.eval(value)?)))
+                    .collect::<Result<_>>()?;
+                Ok(Value::Struct(fields))
+            }
+
+            ExprKind::Identifier(ident) => self.column(std::slice::from_ref(ident)),
+            ExprKind::CompoundIdentifier(parts) => self.column(parts),
+            ExprKind::Parameter(parameter) => self
+                .params
+                .get(parameter)
+                .cloned()
+                .ok_or_else(|| Error::new(ErrorKind::UndefinedVariable(parameter.to_string()))),
+            ExprKind::Parenthesized(inner) => self.eval(inner),
+
+            ExprKind::UnaryOp { op, expr } => unary_op(*op, &self.eval(expr)?),
+            ExprKind::BinaryOp { op, left, right } => {
+                // A chain such as `a + b + c` is evaluated from the inside
+                // out in a loop, rather than recursing once per operator.
+                // Errors of the outermost operator get its span from eval.
+                let mut links = vec![(*op, right, None)];
+                let mut first = left;
+                while let ExprKind::BinaryOp { op, left, right } = &first.kind {
+                    links.push((*op, right, Some(first.span)));
+                    first = left;
+                }
+                let mut value = self.eval(first)?;
+                for (op, right, span) in links.into_iter().rev() {
+                    // AND and OR decided by their left operand skip the right
+                    value = match (op, &value) {
+                        (BinaryOp::And, Value::Boolean(false))
+                        | (BinaryOp::Or, Value::Boolean(true)) => value,
+                        _ => {
+                            binary_op(op, &value, &self.eval(right)?).map_err(|err| match span {
+                                Some(span) if err.span().is_none() => {
+                                    Error::with_span(err.kind, span)
+                                }
+                                _ => err,
+                            })?
+                        }
+                    };
+                }
+                Ok(value)
+            }
+
+            ExprKind::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let value = self.eval(expr)?;
+                let above = binary_op(BinaryOp::GtEq, &value, &self.eval(low)?)?;
+                let below = binary_op(BinaryOp::LtEq, &value, &self.eval(high)?)?;
+                let between = binary_op(BinaryOp::And, &above, &below)?;
+                negate_if(*negated, between)
+            }
+            ExprKind::In {
+                expr,
+                list,
+                negated,
+            } => {
+                let value = self.eval(expr)?;
+                let found = self.quantified(BinaryOp::Eq, Quantifier::Any, &value, list)?;
+                negate_if(*negated, found)
+            }
+            ExprKind::QuantifiedComparison {
+                expr,
+                op,
+                quantifier,
+                list,
+            } => {
+                let value = self.eval(expr)?;
+                self.quantified(*op, *quantifier, &value, list)
+            }
+            ExprKind::Like {
+                expr,
+                pattern,
+                escape,
+                negated,
+                case_insensitive,
+            } => {
+                let value = self.eval(expr)?;
+                let pattern = self.eval(pattern)?;
+                let escape = self.escape(escape.as_deref())?;
+                let matched = like(&value, &pattern, escape, *case_insensitive)?;
+                negate_if(*negated, matched)
+            }
+            ExprKind::QuantifiedLike {
+                expr,
+                quantifier,
+                patterns,
+                escape,
+                negated,
+                case_insensitive,
+            } => {
+                let value = self.eval(expr)?;
+                let escape = self.escape(escape.as_deref())?;
+                let matches = patterns
+                    .iter()
+                    .map(|pattern| like(&value, &self.eval(pattern)?, escape, *case_insensitive));
+                negate_if(*negated, combine(*quantifier, matches)?)
+            }
+            ExprKind::IsExpr {
+                expr,
+                test,
+                negated,
+            } => {
+                let value = self.eval(expr)?;
+                let result = match test {
+                    IsTest::Null => value.is_null(),
+                    IsTest::True => truth(&value)? == Some(true),
+                    IsTest::False => truth(&value)? == Some(false),
+                    IsTest::Unknown => truth(&value)?.is_none(),
+                };
+                Ok(Value::Boolean(result != *negated))
+            }
+            ExprKind::IsDistinct {
+                left,
+                right,
+                negated,
+            } => {
+                let (left, right) = (self.eval(left)?, self.eval(right)?);
+                let distinct = match (left.is_null(), right.is_null()) {
+                    (true, true) => false,
+                    (true, false) | (false, true) => true,
+                    (false, false) => {
+                        binary_op(BinaryOp::NotEq, &left, &right)? == Value::Boolean(true)
+                    }
+                };
+                Ok(Value::Boolean(distinct != *negated))
+            }
+
+            ExprKind::Function(call) => self.call(call),
+            ExprKind::Cast {
+                expr,
+                data_type,
+                safe,
+            } => {
+                let target = SqlType::try_from(data_type)?;
+                let value = self.eval(expr)?;
+                match value.cast_to(&target) {
+                    Err(_) if *safe => Ok(Value::Null),
+                    result => result,
+                }
+            }
+
+            ExprKind::Case {
+                operand,
+                conditions,
+                else_result,
+            } => {
+                let operand = operand.as_deref().map(|e| self.eval(e)).transpose()?;
+                for (when, then) in conditions {
+                    let taken = match &operand {
+                        Some(operand) => {
+                            binary_op(BinaryOp::Eq, operand, &self.eval(when)?)?
+                                == Value::Boolean(true)
+                        }
+                        None => self.condition(when)? == Some(true),
+                    };
+                    if taken {
+                        return self.eval(then);
+                    }
+                }
+                match else_result {
+                    Some(result) => self.eval(result),
+                    None => Ok(Value::Null),
+                }
+            }
+            ExprKind::If {
+                condition,
+                then_expr,
+                else_expr,
+            } => match self.condition(condition)? {
+                Some(true) => self.eval(then_expr),
+                _ => self.eval(else_expr),
+            },
+            ExprKind::Coalesce(exprs) => self.coalesce(exprs.iter().map(|e| &**e)),
+            ExprKind::Nullif { left, right } => self.null_if(left, right),
+            ExprKind::IfNull {
+                expr,
+                null_replacement,
+            } => self.coalesce([&**expr, &**null_replacement]),
+
+            ExprKind::FieldAccess { expr, field } => field_of(self.eval(expr)?, &field.value),
+            ExprKind::ArraySubscript { array, index } => {
+                let (index, one_based, safe) = match index {
+                    ArraySubscriptKind::Index(index) | ArraySubscriptKind::Offset(index) => {
+                        (index, false, false)
+                    }
+                    ArraySubscriptKind::Ordinal(index) => (index, true, false),
+                    ArraySubscriptKind::SafeOffset(index) => (index, false, true),
+                    ArraySubscriptKind::SafeOrdinal(index) => (index, true, true),
+                };
+                self.subscript(array, index, one_based, safe)
+            }
+            ExprKind::SafeArraySubscript {
+                array,
+                index,
+                offset_type,
+            } => self.subscript(array, index, *offset_type == ArrayOffsetType::Ordinal, true),
+
+            ExprKind::SystemVariable { .. } => Err(unsupported("system variables")),
+            ExprKind::SimilarTo { .. } => Err(unsupported("SIMILAR TO")),
+            ExprKind::Aggregate(_) => Err(unsupported("aggregate functions")),
+            ExprKind::WindowFunction(_) => Err(unsupported("window functions")),
+            ExprKind::Extract { .. } => Err(unsupported("EXTRACT")),
+            ExprKind::JsonSubscript { .. } => Err(unsupported("JSON subscripts")),
+            ExprKind::Subquery(_) | ExprKind::Exists { .. } | ExprKind::InSubquery { .. } => {
+                Err(unsupported("subqueries"))
+            }
+        }
+    }
+
+    /// Evaluate a condition, which must be a boolean.
+    fn condition(&self, expr: &Expr) -> Result<Option<bool>> {
+        truth(&self.eval(expr)?).map_err(|err| Error::with_span(err.kind, expr.span))
+    }
+
+    /// Read a column, then follow the remaining parts as struct fields.
+    /// `a.b` is column `b` of table `a` if the row has one, and otherwise
+    /// field `b` of column `a`.
+    fn column(&self, parts: &[Ident]) -> Result<Value> {
+        let qualified = match parts {
+            [table, column, ..] => self
+                .row
+                .get(Some(&table.value), &column.value)
+                .map(|value| (value, 2)),
+            _ => None,
+        };
+        let found = qualified.or_else(|| {
+            let column = parts.first()?;
+            Some((self.row.get(None, &column.value)?, 1))
+        });
+        let Some((mut value, used)) = found else {
+            let name = parts.iter().map(|p| p.value.as_str()).collect::<Vec<_>>();
+            return Err(Error::new(ErrorKind::UndefinedColumn(name.join("."))));
+        };
+        for field in &parts[used..] {
+            value = field_of(value, &field.value)?;
+        }
+        Ok(value)
+    }
+
+    /// Compare `value` with each candidate of `list` by `op`: ANY is TRUE
+    /// when one comparison is, ALL when every one is, and either is NULL
+    /// when the comparisons that are NULL decide it.
+    fn quantified(
+        &self,
+        op: BinaryOp,
+        quantifier: Quantifier,
+        value: &Value,
+        list: &InList,
+    ) -> Result<Value> {
+        let candidates = match list {
+            InList::Values(values) => values
+                .iter()
+                .map(|v| self.eval(v))
+                .collect::<Result<Vec<_>>>()?,
+            InList::Unnest(array) => match self.eval(array)? {
+                Value::Array(values) => values,
+                Value::Null => Vec::new(),
+                other => return Err(type_error("ARRAY", &other)),
+            },
+            InList::Subquery(_) => return Err(unsupported("subqueries")),
+        };
+        let comparisons = candidates
+            .iter()
+            .map(|candidate| binary_op(op, value, candidate));
+        combine(quantifier, comparisons)
+    }
+
+    /// The ESCAPE character of a LIKE, if it has one.
+    fn escape(&self, escape: Option<&Expr>) -> Result<Option<char>> {
+        let Some(escape) = escape else {
+            return Ok(None);
+        };
+        match self.eval(escape)? {
+            Value::String(s) if s.chars().count() == 1 => Ok(s.chars().next()),
+            Value::Null => Ok(None),
+            other => Err(Error::with_span(
+                ErrorKind::TypeMismatch {
+                    expected: "a single-character escape".to_string(),
+                    found: other.to_string(),
+                },
+                escape.span,
+            )),
+        }
+    }
+
+    fn call(&self, call: &FunctionCall) -> Result<Value> {
+        let name = call
+            .name
+            .parts
+            .iter()
+            .map(|p| p.value.as_str())
+            .collect::<Vec<_>>()
+            .join(".");
+        let exprs = call
+            .args
+            .iter()
+            .map(|arg| match arg {
+                FunctionArg::Unnamed(expr) => Ok(&**expr),
+                FunctionArg::Named { .. } => Err(unsupported("named function arguments")),
+                FunctionArg::Star => Err(unsupported(format!("{}(*)", name))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // The parser leaves these conditionals as calls; like CASE, they
+        // evaluate only the arguments they need
+        match (name.to_ascii_uppercase().as_str(), &exprs[..]) {
+            ("COALESCE", exprs) => return self.coalesce(exprs.iter().copied()),
+            ("IFNULL", [expr, replacement]) => return self.coalesce([*expr, *replacement]),
+            ("NULLIF", [left, right]) => return self.null_if(left, right),
+            _ => {}
+        }
+        let function = self
+            .functions
+            .get(&name)
+            .ok_or_else(|| Error::new(ErrorKind::UndefinedFunction(name.clone())))?;
+        let args = exprs
+            .into_iter()
+            .map(|expr| self.eval(expr))
+            .collect::<Result<Vec<_>>>()?;
+        function(&args)
+    }
+
+    /// The first of `exprs` that is not NULL, evaluating no further.
+    fn coalesce<'e>(&self, exprs: impl IntoIterator<Item = &'e Expr>) -> Result<Value> {
+        for expr in exprs {
+            let value = self.eval(expr)?;
+            if !value.is_null() {
+                return Ok(value);
+            }
+        }
+        Ok(Value::Null)
+    }
+
+    fn null_if(&self, left: &Expr, right: &Expr) -> Result<Value> {
+        let left = self.eval(left)?;
+        match binary_op(BinaryOp::Eq, &left, &self.eval(right)?)? {
+            Value::Boolean(true) => Ok(Value::Null),
+            _ => Ok(left),
+        }
+    }
+
+    fn subscript(&self, array: &Expr, index: &Expr, one_based: bool, safe: bool) -> Result<Value> {
+        let (values, index) = match (self.eval(array)?, self.eval(index)?) {
+            (Value::Null, _) | (_, Value::Null) => return Ok(Value::Null),
+            (Value::Array(values), Value::Int64(index)) => (values, index),
+            (Value::Array(_), other) => return Err(type_error("BIGINT", &other)),
+            (other, _) => return Err(type_error("ARRAY", &other)),
+        };
+        let position = if one_based {
+            index.checked_sub(1)
+        } else {
+            Some(index)
+        };
+        match position.and_then(|p| usize::try_from(p).ok()) {
+            Some(position) if position < values.len() => Ok(values[position].clone()),
+            _ if safe => Ok(Value::Null),
+            _ => Err(Error::new(ErrorKind::ArrayIndexOutOfBounds {
+                index,
+                len: values.len(),
+            })),
+        }
+    }
+}
+
+/// Apply a unary operator, with NULL giving NULL.
+pub(crate) fn unary_op(op: UnaryOp, value: &Value) -> Result<Value> {
+    match (op, value) {
+        (_, Value::Null) => Ok(Value::Null),
+        (UnaryOp::Not, _) => Ok(Value::Boolean(!truth(value)?.expect("not NULL"))),
+        (UnaryOp::Plus, Value::Int64(_) | Value::Float64(_) | Value::Numeric(_)) => {
+            Ok(value.clone())
+        }
+        (UnaryOp::Minus, Value::Int64(n)) => n
+            .checked_neg()
+            .map(Value::Int64)
+            .ok_or_else(|| Error::new(ErrorKind::NumericOverflow(format!("-{}", value)))),
+        (UnaryOp::Minus, Value::Float64(f)) => Ok(Value::Float64(-f)),
+        (UnaryOp::Minus, Value::Numeric(_)) => Value::Int64(0).sub(value),
+        (UnaryOp::BitwiseNot, Value::Int64(n)) => Ok(Value::Int64(!n)),
+        (UnaryOp::BitwiseNot, _) => Err(type_error("BIGINT", value)),
+        _ => Err(type_error("a number", value)),
+    }
+}
+
+/// Apply a binary operator.
+///
+/// AND and OR follow three-valued logic, so `NULL AND FALSE` is FALSE and
+/// `NULL OR TRUE` is TRUE; other operators return NULL when either operand
+/// is NULL. Comparisons and arithmetic are those of [`Value`], except that
+/// NaN compares unequal to everything.
+pub(crate) fn binary_op(op: BinaryOp, left: &Value, right: &Value) -> Result<Value> {
+    use Value::{Boolean, Int64, Null};
+
+    if let BinaryOp::And | BinaryOp::Or = op {
+        let decisive = op == BinaryOp::Or;
+        let (l, r) = (truth(left)?, truth(right)?);
+        return Ok(match (l, r) {
+            (Some(b), _) | (_, Some(b)) if b == decisive => Boolean(decisive),
+            (Some(_), Some(_)) => Boolean(!decisive),
+            _ => Null,
+        });
+    }
+    if left.is_null() || right.is_null() {
+        return Ok(Null);
+    }
+    let compared = |test: fn(Ordering) -> bool| match left.partial_cmp(right) {
+        Some(ordering) => Ok(Boolean(test(ordering))),
+        // Values that are not comparable even with themselves hold a NaN,
+        // which is unequal to everything
+        None if left.partial_cmp(left).is_none() || right.partial_cmp(right).is_none() => {
+            Ok(Boolean(op == BinaryOp::NotEq))
+        }
+        None => Err(Error::new(ErrorKind::TypeMismatch {
+            expected: left.type_name().to_string(),
+            found: right.type_name().to_string(),
+        })),
+    };
+    match op {
+        BinaryOp::Eq => compared(Ordering::is_eq),
+        BinaryOp::NotEq => compared(Ordering::is_ne),
+        BinaryOp::Lt => compared(Ordering::is_lt),
+        BinaryOp::LtEq => compared(Ordering::is_le),
+        BinaryOp::Gt => compared(Ordering::is_gt),
+        BinaryOp::GtEq => compared(Ordering::is_ge),
+
+        BinaryOp::Plus => left.add(right),
+        BinaryOp::Minus => left.sub(right),
+        BinaryOp::Multiply => left.mul(right),
+        BinaryOp::Divide => left.div(right),
+        BinaryOp::Modulo => left.rem(right),
+
+        BinaryOp::Concat => match (left, right) {
+            (Value::String(a), Value::String(b)) => Ok(Value::String(format!("{}{}", a, b))),
+            (Value::Bytes(a), Value::Bytes(b)) => Ok(Value::Bytes([&a[..], &b[..]].concat())),
+            (Value::Array(a), Value::Array(b)) => Ok(Value::Array([&a[..], &b[..]].concat())),
+            (Value::String(_), _) => Err(type_error("VARCHAR", right)),
+            _ => Err(type_error("VARCHAR", left)),
+        },
+
+        _ => {
+            let (a, b) = match (left, right) {
+                (Int64(a), Int64(b)) => (*a, *b),
+                (Int64(_), _) => return Err(type_error("BIGINT", right)),
+                _ => return Err(type_error("BIGINT", left)),
+            };
+            // Shifts move bits of the two's complement form, so a shift by
+            // 64 or more leaves none
+            let shift = |shift: fn(u64, u32) -> Option<u64>| match u32::try_from(b) {
+                Ok(n) => Ok(Int64(shift(a as u64, n).unwrap_or(0) as i64)),
+                Err(_) => Err(type_error("a non-negative shift", right)),
+            };
+            match op {
+                BinaryOp::BitwiseAnd => Ok(Int64(a & b)),
+                BinaryOp::BitwiseOr => Ok(Int64(a | b)),
+                BinaryOp::BitwiseXor => Ok(Int64(a ^ b)),
+                BinaryOp::LeftShift => shift(u64::checked_shl),
+                _ => shift(u64::checked_shr),
+            }
+        }
+    }
+}
+
+/// A boolean operand as TRUE, FALSE or unknown.
+fn truth(value: &Value) -> Result<Option<bool>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Boolean(b) => Ok(Some(*b)),
+        other => Err(type_error("BOOLEAN", other)),
+    }
+}
+
+/// Combine boolean results under ANY or ALL, with three-valued logic.
+fn combine(quantifier: Quantifier, results: impl Iterator<Item = Result<Value>>) -> Result<Value> {
+    let decisive = quantifier != Quantifier::All;
+    let mut unknown = false;
+    for result in results {
+        match truth(&result?)? {
+            Some(b) if b == decisive => return Ok(Value::Boolean(decisive)),
+            Some(_) => {}
+            None => unknown = true,
+        }
+    }
+    Ok(if unknown {
+        Value::Null
+    } else {
+        Value::Boolean(!decisive)
+    })
+}
+
+/// NOT `value` when `negated`.
+fn negate_if(negated: bool, value: Value) -> Result<Value> {
+    if negated {
+        unary_op(UnaryOp::Not, &value)
+    } else {
+        Ok(value)
+    }
+}
+
+/// Field `name` of a struct value, matched ignoring ASCII case.
+fn field_of(value: Value, name: &str) -> Result<Value> {
+    match value {
+        Value::Null => Ok(Value::Null),
+        Value::Struct(fields) => fields
+            .into_iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+            .ok_or_else(|| Error::new(ErrorKind::UndefinedColumn(name.to_string()))),
+        other => Err(type_error("STRUCT", &other)),
+    }
+}
+
+/// Whether `value` matches LIKE `pattern`, in which `%` matches any run of
+/// characters, `_` any one character, and `escape` makes the character
+/// after it literal. Bytes match bytes.
+fn like(value: &Value, pattern: &Value, escape: Option<char>, fold_case: bool) -> Result<Value> {
+    let chars = |s: &str| -> Vec<char> {
+        if fold_case {
+            s.chars().flat_map(char::to_lowercase).collect()
+        } else {
+            s.chars().collect()
+        }
+    };
+    let bytes = |b: &[u8]| -> Vec<char> { b.iter().map(|&b| char::from(b)).collect() };
+    let (text, pattern_chars) = match (value, pattern) {
+        (Value::Null, _) | (_, Value::Null) => return Ok(Value::Null),
+        (Value::String(text), Value::String(pattern)) => (chars(text), chars(pattern)),
+        (Value::Bytes(text), Value::Bytes(pattern)) => (bytes(text), bytes(pattern)),
+        (Value::String(_) | Value::Bytes(_), other) => {
+            return Err(type_error(value.type_name(), other));
+        }
+        (other, _) => return Err(type_error("VARCHAR", other)),
+    };
+
+    enum Token {
+        Any,
+        One,
+        Char(char),
+    }
+    let mut tokens = Vec::new();
+    let mut rest = pattern_chars.into_iter();
+    while let Some(c) = rest.next() {
+        tokens.push(match c {
+            c if Some(c) == escape => match rest.next() {
+                Some(c) => Token::Char(c),
+                None => {
+                    return Err(Error::new(ErrorKind::TypeMismatch {
+                        expected: "a character after the LIKE escape".to_string(),
+                        found: "the end of the pattern".to_string(),
+                    }))
+                }
+            },
+            '%' => Token::Any,
+            '_' => Token::One,
+            c => Token::Char(c),
+        });
+    }
+
+    // Match greedily, backtracking to the last % on a mismatch; each % only
+    // ever needs to absorb more characters, so one saved position suffices
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some(Token::One) => (t, p) = (t + 1, p + 1),
+            Some(Token::Char(c)) if *c == text[t] => (t, p) = (t + 1, p + 1),
+            Some(Token::Any) => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            _ => match backtrack {
+                Some((any, absorbed)) => {
+                    backtrack = Some((any, absorbed + 1));
+                    (t, p) = (absorbed + 1, any + 1);
+                }
+                None => return Ok(Value::Boolean(false)),
+            },
+        }
+    }
+    let matched = tokens[p..].iter().all(|token| matches!(token, Token::Any));
+    Ok(Value::Boolean(matched))
+}
+
+fn type_error(expected: &str, value: &Value) -> Error {
+    Error::new(ErrorKind::TypeMismatch {
+        expected: expected.to_string(),
+        found: value.type_name().to_string(),
+    })
+}
+
+fn unsupported(feature: impl Into<String>) -> Error {
+    Error::new(ErrorKind::UnsupportedFeature(feature.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Span;
+    use crate::parser::Parser;
+
+    struct TestRow;
+
+    impl RowAccessor for TestRow {
+        fn get(&self, table: Option<&str>, column: &str) -> Option<Value> {
+            let value = match (table, column) {
+                (None | Some("u"), "id") => Value::Int64(7),
+                (None | Some("u"), "name") => Value::from("Ada"),
+                (None | Some("u"), "age") => Value::Null,
+                (None, "point") => Value::Struct(vec![
+                    ("x".to_string(), Value::Int64(1)),
+                    ("y".to_string(), Value::Int64(2)),
+                ]),
+                (None, "tags") => Value::Array(vec![Value::from("a"), Value::from("b")]),
+                _ => return None,
+            };
+            Some(value)
+        }
+    }
+
+    fn eval(sql: &str) -> Result<Value> {
+        let expr = Parser::new(sql).parse_expression().unwrap();
+        let params = Params::new()
+            .with_positional(10i64)
+            .with_named("Max_Rows", 3i64);
+        evaluate(&expr, &TestRow, &params)
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let cases = [
+            ("1 + 2 * 3", Value::Int64(7)),
+            ("id * 2 + ?", Value::Int64(24)),
+            ("u.name || '!'", Value::from("Ada!")),
+            ("point.y", Value::Int64(2)),
+            ("tags[OFFSET(1)]", Value::from("b")),
+            ("tags[SAFE_ORDINAL(3)]", Value::Null),
+            ("age + 1", Value::Null),
+            ("@max_rows > 2", Value::Boolean(true)),
+            ("-(2.5)", Value::Float64(-2.5)),
+            ("CAST('12' AS BIGINT) / 5", Value::Int64(2)),
+            ("SAFE_CAST('x' AS BIGINT)", Value::Null),
+            (
+                "[1, 2] || [3]",
+                Value::Array(vec![1i64.into(), 2i64.into(), 3i64.into()]),
+            ),
+            ("1 << 3 | 1", Value::Int64(9)),
+            ("1 + 1 = 2", Value::Boolean(true)),
+            ("2 * 3 > 5 AND 10 - 4 <= 6", Value::Boolean(true)),
+            ("id + 1 <> 8 OR 1 | 2 = 3", Value::Boolean(true)),
+            ("1 << 2 < 1 + 2", Value::Boolean(false)),
+            ("NOT 1 + 1 = 3", Value::Boolean(true)),
+            (
+                "INTERVAL '1' DAY + INTERVAL 2 HOUR",
+                Value::Interval(crate::types::Interval {
+                    months: 0,
+                    days: 1,
+                    micros: 7_200_000_000,
+                }),
+            ),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(eval(sql).unwrap(), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_value_display_evaluates() {
+        let interval = |months, days, micros| {
+            Value::Interval(crate::types::Interval {
+                months,
+                days,
+                micros,
+            })
+        };
+        let values = [
+            Value::Int64(i64::MIN),
+            Value::Float64(f64::NAN),
+            Value::Date(-800_000),
+            Value::Time(86_399_999_999),
+            Value::Datetime(i64::MIN),
+            Value::Datetime(0),
+            Value::Timestamp(i64::MAX),
+            interval(0, 0, i64::MIN),
+            interval(-24, 3, -1_500),
+            interval(0, 0, 0),
+            Value::Array(vec![Value::Date(i32::MIN), Value::Null]),
+        ];
+        for value in values {
+            let sql = value.to_string();
+            let evaluated = eval(&sql).unwrap_or_else(|e| panic!("{}: {}", sql, e));
+            assert!(
+                evaluated == value
+                    || matches!((&value, &evaluated), (Value::Float64(a), Value::Float64(b))
+                        if a.is_nan() && b.is_nan()),
+                "{}: {:?}",
+                sql,
+                evaluated
+            );
+        }
+
+        // No expression builds a time outside one day, so it is written
+        // as one that wraps to the time of day
+        assert_eq!(
+            eval(&Value::Time(-1).to_string()).unwrap(),
+            Value::Time(86_399_999_999)
+        );
+        assert_eq!(
+            eval(&Value::Time(86_400_000_000).to_string()).unwrap(),
+            Value::Time(0)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_long_chain() {
+        let sql = vec!["1"; 10_000].join(" + ");
+        assert_eq!(eval(&sql).unwrap(), Value::Int64(10_000));
+    }
+
+    #[test]
+    fn test_three_valued_logic() {
+        let cases = [
+            ("age > 1 AND FALSE", Value::Boolean(false)),
+            ("age > 1 AND TRUE", Value::Null),
+            ("age > 1 OR TRUE", Value::Boolean(true)),
+            ("NOT (age > 1)", Value::Null),
+            ("id IN (1, 7)", Value::Boolean(true)),
+            ("id IN (1, NULL)", Value::Null),
+            ("id NOT IN (1, 2)", Value::Boolean(true)),
+            ("id IN UNNEST([1, 2])", Value::Boolean(false)),
+            ("id > ALL (1, 2)", Value::Boolean(true)),
+            ("id > ALL (1, NULL)", Value::Null),
+            ("id = ANY (1, NULL, 7)", Value::Boolean(true)),
+            ("id BETWEEN 1 AND 10", Value::Boolean(true)),
+            ("id NOT BETWEEN 1 AND age", Value::Null),
+            ("age IS NULL", Value::Boolean(true)),
+            ("(age > 1) IS UNKNOWN", Value::Boolean(true)),
+            ("(id > 1) IS NOT TRUE", Value::Boolean(false)),
+            ("age IS DISTINCT FROM NULL", Value::Boolean(false)),
+            ("id IS DISTINCT FROM age", Value::Boolean(true)),
+            (
+                "CAST('NaN' AS FLOAT64) = CAST('NaN' AS FLOAT64)",
+                Value::Boolean(false),
+            ),
+            // The right operand is not evaluated when the left decides
+            ("FALSE AND missing", Value::Boolean(false)),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(eval(sql).unwrap(), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let cases = [
+            (
+                "CASE WHEN id > 5 THEN 'big' ELSE 'small' END",
+                Value::from("big"),
+            ),
+            (
+                "CASE id WHEN 1 THEN 'one' WHEN 7 THEN 'seven' END",
+                Value::from("seven"),
+            ),
+            ("CASE WHEN age > 1 THEN 1 END", Value::Null),
+            ("IF(age > 1, 'yes', 'no')", Value::from("no")),
+            ("COALESCE(age, NULL, id)", Value::Int64(7)),
+            ("IFNULL(age, 0)", Value::Int64(0)),
+            ("NULLIF(id, 7)", Value::Null),
+            // Untaken branches are not evaluated
+            ("COALESCE(id, 1 / 0)", Value::Int64(7)),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(eval(sql).unwrap(), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_like() {
+        let cases = [
+            ("'Ada' LIKE 'A%'", Value::Boolean(true)),
+            ("'Ada' LIKE '_d_'", Value::Boolean(true)),
+            ("'Ada' LIKE 'a%'", Value::Boolean(false)),
+            ("'Ada' ILIKE 'a%'", Value::Boolean(true)),
+            ("'abcabd' LIKE '%ab%d'", Value::Boolean(true)),
+            ("'abc' LIKE '%b'", Value::Boolean(false)),
+            ("'10%' LIKE '10!%' ESCAPE '!'", Value::Boolean(true)),
+            ("'100' LIKE '10!%' ESCAPE '!'", Value::Boolean(false)),
+            ("TRIM(' ') LIKE '%'", Value::Boolean(true)),
+            ("name NOT LIKE '%x%'", Value::Boolean(true)),
+            ("name LIKE ANY ('B%', 'A%')", Value::Boolean(true)),
+            ("name LIKE ALL ('A%', NULL)", Value::Null),
+            ("b'ab' LIKE b'a_'", Value::Boolean(true)),
+            ("age LIKE 'x'", Value::Null),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(eval(sql).unwrap(), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        let err = eval("missing + 1").unwrap_err();
+        assert_eq!(err.to_string(), "undefined column 'missing' at position 0");
+        let err = eval("id / (id - 7)").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::DivisionByZero));
+        assert_eq!(err.span(), Some(Span::new(0, 13)));
+        let err = eval("name + 1").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::TypeMismatch { .. }));
+        let err = eval("IF(name, 1, 2)").unwrap_err();
+        assert_eq!(err.span(), Some(Span::new(3, 7)));
+        let err = eval("tags[OFFSET(2)]").unwrap_err();
+        assert!(matches!(
+            err.kind,
+            ErrorKind::ArrayIndexOutOfBounds { index: 2, len: 2 }
+        ));
+        let err = eval("$2").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::UndefinedVariable(_)));
+        let err = eval("NO_SUCH_FUNCTION(1)").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::UndefinedFunction(_)));
+        let err = eval("EXISTS (SELECT 1)").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::UnsupportedFeature(_)));
+    }
+
+    #[test]
+    fn test_custom_functions() {
+        let mut functions = FunctionRegistry::with_builtins();
+        functions.register("double", |args| args[0].mul(&Value::Int64(2)));
+        functions.register("UPPER", |_| Ok(Value::from("overridden")));
+        let evaluator = Evaluator::new(functions);
+        let expr = Parser::new("DOUBLE(id) || '' = '14' AND UPPER('a') = 'overridden'")
+            .parse_expression()
+            .unwrap();
+        let err = evaluator
+            .evaluate(&expr, &TestRow, &Params::new())
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::TypeMismatch { .. }));
+        let expr = Parser::new("DOUBLE(id) = 14 AND UPPER('a') = 'overridden'")
+            .parse_expression()
+            .unwrap();
+        assert_eq!(
+            evaluator.evaluate(&expr, &TestRow, &Params::new()).unwrap(),
+            Value::Boolean(true)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Layout documents for SQL output.
+//!
+//! AST nodes describe their SQL as a [`Doc`]: text and keywords with
+//! possible line breaks, grouped so that each group is laid out on one line
+//! when it fits and broken across indented lines when it does not. `Display`
+//! renders every document on a single line; the formatter renders it within
+//! a width.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{CommaStyle, FormatOptions, KeywordCase};
+
+/// A SQL layout document.
+#[derive(Debug, Clone)]
+pub(crate) enum Doc {
+    /// Literal text: punctuation, identifiers, and literals.
+    Text(Cow<'static, str>),
+    /// Upper-case keyword text, one or more words, cased on output.
+    Keyword(Cow<'static, str>),
+    /// A space, or a line break when the enclosing group is broken.
+    Line,
+    /// Nothing, or a line break when the enclosing group is broken.
+    SoftLine,
+    /// A line break whenever output spans lines, and a space otherwise.
+    /// Groups containing one never fit on a line.
+    HardLine,
+    Concat(Vec<Doc>),
+    /// Indent the lines breaks inside by one level.
+    Nest(Box<Doc>),
+    /// Lay out the contents on one line if they fit.
+    Group(Box<Doc>),
+    /// Comma-separated items, one per line when the enclosing group is
+    /// broken.
+    List(Vec<Doc>),
+}
+
+/// Concatenate documents, converting `&'static str` and `String` to text.
+macro_rules! docs {
+    ($($part:expr),* $(,)?) => {
+        $crate::format::doc::Doc::Concat(vec![$($crate::format::doc::Doc::from($part)),*])
+    };
+}
+pub(crate) use docs;
+
+impl From<&'static str> for Doc {
+    fn from(text: &'static str) -> Self {
+        Doc::Text(Cow::Borrowed(text))
+    }
+}
+
+impl From<String> for Doc {
+    fn from(text: String) -> Self {
+        Doc::Text(Cow::Owned(text))
+    }
+}
+
+/// A keyword.
+pub(crate) fn kw(keyword: &'static str) -> Doc {
+    Doc::Keyword(Cow::Borrowed(keyword))
+}
+
+/// A group laid out on one line if it fits.
+pub(crate) fn group(doc: Doc) -> Doc {
+    Doc::Group(Box::new(doc))
+}
+
+/// A document indented by one level.
+pub(crate) fn nest(doc: Doc) -> Doc {
+    Doc::Nest(Box::new(doc))
+}
+
+/// Comma-separated items.
+pub(crate) fn list(items: impl IntoIterator<Item = Doc>) -> Doc {
+    Doc::List(items.into_iter().collect())
+}
+
+/// `open`, the contents indented on their own lines when they do not fit,
+/// and `close`.
+pub(crate) fn bracketed(open: &'static str, contents: Doc, close: &'static str) -> Doc {
+    group(docs![
+        open,
+        nest(docs![Doc::SoftLine, contents]),
+        Doc::SoftLine,
+        close
+    ])
+}
+
+/// A keyword followed by a group of contents that moves to its own
+/// indented line when it does not fit: `WHERE a = 1`, or `WHERE` over an
+/// indented condition.
+pub(crate) fn clause(keyword: Doc, contents: Doc) -> Doc {
+    group(docs![keyword, nest(docs![Doc::Line, contents])])
+}
+
+impl Doc {
+    /// Write the document on one line with keywords upper-case.
+    pub(crate) fn write_flat(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match self {
+            Doc::Text(text) | Doc::Keyword(text) => f.write_str(text),
+            Doc::Line | Doc::HardLine => f.write_str(" "),
+            Doc::SoftLine => Ok(()),
+            Doc::Concat(docs) => docs.iter().try_for_each(|d| d.write_flat(f)),
+            Doc::Nest(doc) | Doc::Group(doc) => doc.write_flat(f),
+            Doc::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    item.write_flat(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// The document on one line.
+    pub(crate) fn flat(&self) -> String {
+        let mut out = String::new();
+        // Writing to a String cannot fail.
+        let _ = self.write_flat(&mut out);
+        out
+    }
+
+    /// Whether the single-line rendering starts with `c`.
+    pub(crate) fn starts_with(&self, c: char) -> bool {
+        self.first_text().is_some_and(|t| t.starts_with(c))
+    }
+
+    /// The first non-empty text, if it comes before any line break.
+    fn first_text(&self) -> Option<&str> {
+        match self {
+            Doc::Text(text) | Doc::Keyword(text) if !text.is_empty() => Some(text),
+            Doc::Text(_) | Doc::Keyword(_) | Doc::SoftLine => None,
+            Doc::Line | Doc::HardLine => Some(" "),
+            Doc::Concat(docs) | Doc::List(docs) => docs.iter().find_map(Doc::first_text),
+            Doc::Nest(doc) | Doc::Group(doc) => doc.first_text(),
+        }
+    }
+}
+
+/// Renders documents within a width.
+pub(crate) struct Printer<'a> {
+    options: &'a FormatOptions,
+    /// Source spellings of keywords, by upper-case word, for
+    /// [`KeywordCase::Preserve`].
+    spellings: &'a HashMap<String, String>,
+    /// Case for keywords without a source spelling.
+    fallback: KeywordCase,
+    out: String,
+    column: usize,
+}
+
+impl<'a> Printer<'a> {
+    pub(crate) fn new(
+        options: &'a FormatOptions,
+        spellings: &'a HashMap<String, String>,
+        fallback: KeywordCase,
+    ) -> Self {
+        Self {
+            options,
+            spellings,
+            fallback,
+            out: String::new(),
+            column: 0,
+        }
+    }
+
+    /// Render `doc` and return the output.
+    pub(crate) fn print(mut self, doc: &Doc) -> String {
+        self.render(doc, 0, false);
+        self.out
+    }
+
+    fn render(&mut self, doc: &Doc, indent: usize, flat: bool) {
+        match doc {
+            Doc::Text(text) => self.push(text),
+            Doc::Keyword(keyword) => self.push_keyword(keyword),
+            Doc::Line | Doc::HardLine if flat => self.push(" "),
+            Doc::Line | Doc::HardLine => self.newline(indent),
+            Doc::SoftLine if flat => {}
+            Doc::SoftLine => self.newline(indent),
+            Doc::Concat(docs) => {
+                for doc in docs {
+                    self.render(doc, indent, flat);
+                }
+            }
+            Doc::Nest(doc) => self.render(doc, indent + self.options.indent_width, flat),
+            Doc::Group(doc) => {
+                let flat = flat || self.fits(doc);
+                self.render(doc, indent, flat);
+            }
+            Doc::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        match (flat, self.options.comma_style) {
+                            (true, _) => self.push(", "),
+                            (false, CommaStyle::Trailing) => {
+                                self.push(",");
+                                self.newline(indent);
+                            }
+                            (false, CommaStyle::Leading) => {
+                                self.newline(indent);
+                                self.push(", ");
+                            }
+                        }
+                    }
+                    self.render(item, indent, flat);
+                }
+            }
+        }
+    }
+
+    /// Whether `doc` fits on the rest of the current line.
+    fn fits(&self, doc: &Doc) -> bool {
+        fn take(budget: &mut usize, width: usize) -> bool {
+            match budget.checked_sub(width) {
+                Some(rest) => {
+                    *budget = rest;
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn consume(doc: &Doc, budget: &mut usize) -> bool {
+            match doc {
+                Doc::Text(text) | Doc::Keyword(text) => take(budget, text.chars().count()),
+                Doc::Line => take(budget, 1),
+                Doc::SoftLine => true,
+                Doc::HardLine => false,
+                Doc::Concat(docs) => docs.iter().all(|d| consume(d, budget)),
+                Doc::Nest(doc) | Doc::Group(doc) => consume(doc, budget),
+                Doc::List(items) => items
+                    .iter()
+                    .enumerate()
+                    .all(|(i, item)| (i == 0 || take(budget, 2)) && consume(item, budget)),
+            }
+        }
+
+        let mut budget = self.options.max_width.saturating_sub(self.column);
+        consume(doc, &mut budget)
+    }
+
+    fn push(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.column += text.chars().count();
+    }
+
+    fn push_keyword(&mut self, keyword: &str) {
+        for (i, word) in keyword.split(' ').enumerate() {
+            if i > 0 {
+                self.push(" ");
+            }
+            let spelled = match self.options.keyword_case {
+                KeywordCase::Upper => None,
+                KeywordCase::Lower => Some(word.to_ascii_lowercase()),
+                KeywordCase::Preserve => match (self.spellings.get(word), self.fallback) {
+                    (Some(spelling), _) => Some(spelling.clone()),
+                    (None, KeywordCase::Lower) => Some(word.to_ascii_lowercase()),
+                    (None, _) => None,
+                },
+            };
+            match spelled {
+                Some(spelled) => self.push(&spelled),
+                None => self.push(word),
+            }
+        }
+    }
+
+    fn newline(&mut self, indent: usize) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        self.out.push('\n');
+        self.out.push_str(&" ".repeat(indent));
+        self.column = indent;
+    }
+}
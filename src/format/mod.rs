@@ -0,0 +1,384 @@
+//! Pretty-printing SQL.
+//!
+//! [`format_statement`] lays a statement out over indented lines: each
+//! clause starts a line, and lists, conditions, and parenthesized subqueries
+//! stay on one line when they fit within [`FormatOptions::max_width`] and
+//! are broken one item per line when they do not. `CASE` puts each `WHEN`
+//! on its own line. The output parses back to an equivalent tree, as the
+//! single-line [`Display`](std::fmt::Display) output of the AST does.
+//!
+//! [`format_sql`] parses and formats a script, and can keep the keyword
+//! spelling of the source with [`KeywordCase::Preserve`].
+
+pub(crate) mod doc;
+
+use std::collections::HashMap;
+
+use crate::ast::{Statement, StatementKind};
+use crate::error::Result;
+use crate::lexer::{Lexer, TokenKind};
+use crate::parser::Parser;
+
+use doc::Printer;
+
+/// How keywords are cased in formatted output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum KeywordCase {
+    /// `SELECT`, `FROM`.
+    #[default]
+    Upper,
+    /// `select`, `from`.
+    Lower,
+    /// As spelled in the source. Only [`format_sql`] sees the source;
+    /// [`format_statement`] writes keywords upper-case.
+    Preserve,
+}
+
+/// Where the comma goes when a list is broken over lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CommaStyle {
+    /// At the end of each line but the last.
+    #[default]
+    Trailing,
+    /// At the start of each line but the first.
+    Leading,
+}
+
+/// Options for [`format_statement`] and [`format_sql`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Spaces per indentation level.
+    pub indent_width: usize,
+    /// Keyword casing.
+    pub keyword_case: KeywordCase,
+    /// Line width that groups are kept within where possible. Lines can
+    /// still run over when a single identifier or literal is too long.
+    pub max_width: usize,
+    /// Comma placement in broken lists.
+    pub comma_style: CommaStyle,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            keyword_case: KeywordCase::Upper,
+            max_width: 80,
+            comma_style: CommaStyle::Trailing,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Create options with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of spaces per indentation level.
+    pub fn with_indent_width(mut self, indent_width: usize) -> Self {
+        self.indent_width = indent_width;
+        self
+    }
+
+    /// Set the keyword casing.
+    pub fn with_keyword_case(mut self, keyword_case: KeywordCase) -> Self {
+        self.keyword_case = keyword_case;
+        self
+    }
+
+    /// Set the line width.
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Set the comma placement in broken lists.
+    pub fn with_comma_style(mut self, comma_style: CommaStyle) -> Self {
+        self.comma_style = comma_style;
+        self
+    }
+}
+
+/// Format a statement over indented lines, without a trailing semicolon or
+/// newline.
+///
+/// # Example
+///
+/// ```
+/// use vibesql::format::{format_statement, FormatOptions};
+/// use vibesql::parser::Parser;
+///
+/// let stmt = Parser::new("select a, b from t where a > 1")
+///     .parse_statement()
+///     .unwrap();
+/// assert_eq!(
+///     format_statement(&stmt, &FormatOptions::new()),
+///     "SELECT a, b\nFROM t\nWHERE a > 1"
+/// );
+/// ```
+pub fn format_statement(statement: &Statement, options: &FormatOptions) -> String {
+    Printer::new(options, &HashMap::new(), KeywordCase::Upper).print(&statement.doc())
+}
+
+/// Parse `sql` and format each statement, ending each with `;` and
+/// separating them with a blank line.
+///
+/// With [`KeywordCase::Preserve`], each keyword is written as it was first
+/// spelled in `sql`. Keywords the source does not contain follow its
+/// predominant case.
+pub fn format_sql(sql: &str, options: &FormatOptions) -> Result<String> {
+    let statements = Parser::new(sql).parse()?;
+    let (spellings, fallback) = if options.keyword_case == KeywordCase::Preserve {
+        keyword_spellings(sql)?
+    } else {
+        (HashMap::new(), KeywordCase::Upper)
+    };
+
+    let formatted: Vec<String> = statements
+        .iter()
+        .filter(|stmt| !matches!(stmt.kind, StatementKind::Empty))
+        .map(|stmt| {
+            let printer = Printer::new(options, &spellings, fallback);
+            format!("{};", printer.print(&stmt.doc()))
+        })
+        .collect();
+    Ok(formatted.join("\n\n"))
+}
+
+/// The first source spelling of each keyword, by upper-case text, and the
+/// case most keywords are written in.
+fn keyword_spellings(sql: &str) -> Result<(HashMap<String, String>, KeywordCase)> {
+    let mut spellings = HashMap::new();
+    let (mut lower, mut other) = (0usize, 0usize);
+    for token in Lexer::new(sql).tokenize()? {
+        if !matches!(
+            token.kind,
+            TokenKind::Keyword(_) | TokenKind::Boolean(_) | TokenKind::Null
+        ) {
+            continue;
+        }
+        if token.text.chars().all(|c| !c.is_ascii_uppercase()) {
+            lower += 1;
+        } else {
+            other += 1;
+        }
+        spellings
+            .entry(token.text.to_ascii_uppercase())
+            .or_insert(token.text);
+    }
+    let fallback = if lower > other {
+        KeywordCase::Lower
+    } else {
+        KeywordCase::Upper
+    };
+    Ok((spellings, fallback))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testgen::TestGen;
+
+    fn format(sql: &str, options: &FormatOptions) -> String {
+        let stmt = Parser::new(sql).parse_statement().unwrap();
+        format_statement(&stmt, options)
+    }
+
+    #[test]
+    fn test_short_clauses_stay_on_one_line() {
+        assert_eq!(
+            format(
+                "select a, b from t join u on t.id = u.id where a > 1 group by a, b \
+                 order by a desc limit 10",
+                &FormatOptions::new()
+            ),
+            "SELECT a, b\n\
+             FROM t JOIN u ON t.id = u.id\n\
+             WHERE a > 1\n\
+             GROUP BY a, b\n\
+             ORDER BY a DESC\n\
+             LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_long_select_list_wraps() {
+        let options = FormatOptions::new().with_max_width(40);
+        assert_eq!(
+            format(
+                "SELECT customer_name, customer_email, customer_phone FROM customers",
+                &options
+            ),
+            "SELECT\n  customer_name,\n  customer_email,\n  customer_phone\nFROM customers"
+        );
+        assert_eq!(
+            format(
+                "SELECT customer_name, customer_email, customer_phone FROM customers",
+                &options
+                    .with_comma_style(CommaStyle::Leading)
+                    .with_indent_width(4)
+            ),
+            "SELECT\n    customer_name\n    , customer_email\n    , customer_phone\nFROM customers"
+        );
+    }
+
+    #[test]
+    fn test_case_puts_each_when_on_its_own_line() {
+        assert_eq!(
+            format(
+                "SELECT CASE WHEN a > 0 THEN 'pos' WHEN a < 0 THEN 'neg' ELSE 'zero' END AS sign",
+                &FormatOptions::new()
+            ),
+            "SELECT\n\
+             \x20 CASE\n\
+             \x20   WHEN a > 0 THEN 'pos'\n\
+             \x20   WHEN a < 0 THEN 'neg'\n\
+             \x20   ELSE 'zero'\n\
+             \x20 END AS sign"
+        );
+    }
+
+    #[test]
+    fn test_subqueries_and_ctes_are_indented() {
+        assert_eq!(
+            format(
+                "WITH big AS (SELECT id FROM orders WHERE total > 100) \
+                 SELECT name FROM customers WHERE id IN (SELECT id FROM big) AND active",
+                &FormatOptions::new()
+            ),
+            "WITH big AS (\n\
+             \x20 SELECT id\n\
+             \x20 FROM orders\n\
+             \x20 WHERE total > 100\n\
+             )\n\
+             SELECT name\n\
+             FROM customers\n\
+             WHERE\n\
+             \x20 id IN (\n\
+             \x20   SELECT id\n\
+             \x20   FROM big\n\
+             \x20 )\n\
+             \x20 AND active"
+        );
+    }
+
+    #[test]
+    fn test_keyword_case() {
+        let sql = "Select a From t Where b IS NOT NULL";
+        let lower = FormatOptions::new().with_keyword_case(KeywordCase::Lower);
+        assert_eq!(format(sql, &lower), "select a\nfrom t\nwhere b is not null");
+
+        // Without the source, preserved keywords are upper-case.
+        let preserve = FormatOptions::new().with_keyword_case(KeywordCase::Preserve);
+        assert_eq!(
+            format(sql, &preserve),
+            "SELECT a\nFROM t\nWHERE b IS NOT NULL"
+        );
+        assert_eq!(
+            format_sql(sql, &preserve).unwrap(),
+            "Select a\nFrom t\nWhere b IS NOT NULL;"
+        );
+        // Keywords missing from the source follow its predominant case.
+        assert_eq!(
+            format_sql("select a from t left outer join u using (id)", &preserve).unwrap(),
+            "select a\nfrom t left join u using (id);"
+        );
+    }
+
+    #[test]
+    fn test_format_sql_separates_statements() {
+        assert_eq!(
+            format_sql(
+                "select 1;; delete from t where a = 1;",
+                &FormatOptions::new()
+            )
+            .unwrap(),
+            "SELECT 1;\n\nDELETE FROM t\nWHERE a = 1;"
+        );
+        assert!(format_sql("select from", &FormatOptions::new()).is_err());
+    }
+
+    /// Format `sql` with several option sets and check that each result
+    /// parses back to the same tree.
+    fn assert_reparses(sql: &str) {
+        let statement = Parser::new(sql)
+            .parse_statement()
+            .unwrap_or_else(|e| panic!("{}: {}", sql, e));
+        let option_sets = [
+            FormatOptions::new(),
+            FormatOptions::new()
+                .with_max_width(20)
+                .with_comma_style(CommaStyle::Leading)
+                .with_keyword_case(KeywordCase::Lower),
+            FormatOptions::new().with_max_width(0).with_indent_width(4),
+        ];
+        for options in &option_sets {
+            let formatted = format_statement(&statement, options);
+            let reparsed = Parser::new(&formatted)
+                .parse_statement()
+                .unwrap_or_else(|e| panic!("{}\nformatted as\n{}\n{}", sql, formatted, e));
+            // Display output parses back to the tree it was printed from,
+            // so equal output means equal trees.
+            assert_eq!(
+                reparsed.to_string(),
+                statement.to_string(),
+                "formatted as\n{}",
+                formatted
+            );
+        }
+    }
+
+    #[test]
+    fn test_formatted_output_reparses() {
+        for sql in [
+            "SELECT a, CASE x WHEN 1 THEN 'one' END, -(-1), NOT a = b FROM t",
+            "SELECT * FROM a LEFT JOIN b USING (id) CROSS JOIN (c JOIN d ON TRUE)",
+            "SELECT * FROM (SELECT 1 AS a) AS d, UNNEST([1, 2]) AS n WITH OFFSET",
+            "SELECT 1 UNION ALL (SELECT 2 UNION DISTINCT SELECT 3) ORDER BY 1 LIMIT 1",
+            "WITH RECURSIVE r (n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM r) SELECT n FROM r",
+            "SELECT a FROM t GROUP BY ROLLUP(a, b) HAVING COUNT(*) > 1 \
+             QUALIFY ROW_NUMBER() OVER w = 1 WINDOW w AS (PARTITION BY a ORDER BY b)",
+            "SELECT SUM(x) OVER (PARTITION BY a ORDER BY b ROWS BETWEEN 1 PRECEDING AND CURRENT ROW)",
+            "SELECT STRING_AGG(DISTINCT name, ',' ORDER BY name LIMIT 3) FILTER (WHERE a) FROM t",
+            "SELECT CAST(a AS ARRAY<STRUCT<x INT64, y ARRAY<STRING> > >), ARRAY<INT64>[1, 2]",
+            "SELECT * FROM t WHERE a IN (1, 2) AND EXISTS (SELECT 1) OR NOT b",
+            "INSERT INTO t (a, b) VALUES (1, 2), (3, 4) RETURNING a",
+            "INSERT INTO t SELECT * FROM u",
+            "UPDATE t AS x SET a = 1, b.c = 2 FROM u WHERE x.id = u.id",
+            "DELETE FROM t WHERE a = 1",
+            "MERGE INTO t USING s ON t.id = s.id WHEN MATCHED AND s.a > 1 THEN UPDATE SET a = s.a \
+             WHEN NOT MATCHED THEN INSERT (id, a) VALUES (s.id, s.a) \
+             WHEN NOT MATCHED BY SOURCE THEN DELETE",
+            "CREATE OR REPLACE TABLE t (id INT64 NOT NULL, tags ARRAY<STRING>, \
+             PRIMARY KEY (id)) PARTITION BY id CLUSTER BY id OPTIONS (description = 'x')",
+            "CREATE TABLE t AS SELECT 1 AS a",
+            "CREATE VIEW v (a) AS SELECT a FROM t WHERE a > 1",
+            "CREATE FUNCTION f(x INT64) RETURNS INT64 AS (x + 1)",
+            "CREATE PROCEDURE p(IN x INT64) BEGIN SELECT x; SELECT 2; END",
+            "ALTER TABLE t ADD COLUMN IF NOT EXISTS c STRING",
+            "DROP TABLE IF EXISTS a, b CASCADE",
+            "EXPLAIN SELECT a FROM t",
+        ] {
+            assert_reparses(sql);
+        }
+        for seed in 0..100 {
+            let mut gen = TestGen::new(seed);
+            let catalog = gen.catalog();
+            for _ in 0..5 {
+                let query = gen.query(&catalog).unwrap();
+                assert_reparses(&query.statement.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_long_chain() {
+        let terms: Vec<String> = (0..10_000).map(|i| format!("c{} = 1", i)).collect();
+        let sql = format!("SELECT a FROM t WHERE {}", terms.join(" AND "));
+        let statement = Parser::new(&sql).parse_statement().unwrap();
+        let formatted = format_statement(&statement, &FormatOptions::new());
+        let reparsed = Parser::new(&formatted).parse_statement().unwrap();
+        assert_eq!(reparsed.to_string(), sql);
+    }
+}
@@ -0,0 +1,588 @@
+//! [`ToJson`] for the AST.
+
+use super::{json_enum, json_struct, write_str, Object, ToJson};
+use crate::ast::*;
+
+// Shared nodes, queries, and clauses.
+
+json_struct! {
+    Ident { value, quoted, quote_style, span }
+    ObjectName { parts, span }
+    ColumnDef { name, data_type, constraints, options, span }
+    SortKey { column, order, nulls }
+    SqlOption { name, value }
+    Hint { name, args, span }
+    Alias { name, columns }
+    WithClause { recursive, ctes, span }
+    Cte { name, columns, query, span }
+    Query { with, body, order_by, limit, span, id }
+    LimitClause { count, offset, with_ties, syntax }
+    OrderByExpr { expr, order, nulls }
+    Select {
+        distinct, select_as, projection, from, where_clause, group_by, having, qualify, window,
+        optimizer_hints, span, id,
+    }
+    FromClause { tables }
+    TableRef { kind, span, id }
+    TableSample { method, size, seed, span }
+    GroupByClause { items }
+    WindowDef { name, spec }
+    WindowSpec { base, partition_by, order_by, frame, span }
+    WindowFrame { unit, start, end, exclusion, span }
+}
+
+json_enum! {
+    ColumnConstraint {
+        NotNull,
+        Null,
+        PrimaryKey,
+        Unique,
+        Default(value),
+        Check(value),
+        References { table, columns, on_delete, on_update },
+        Generated { expr, always },
+        Hidden,
+    }
+    ReferentialAction {
+        NoAction,
+        Restrict,
+        Cascade,
+        SetNull,
+        SetDefault,
+    }
+    TableConstraint {
+        PrimaryKey { name, columns, options },
+        Unique { name, columns },
+        ForeignKey { name, columns, references_table, references_columns, on_delete, on_update },
+        Check { name, expr, enforced },
+    }
+    SortOrder {
+        Asc,
+        Desc,
+    }
+    NullsOrder {
+        First,
+        Last,
+    }
+    HintArg {
+        Name(value),
+        Number(value),
+        String(value),
+        Verbatim(value),
+    }
+    QueryBody {
+        Select(value),
+        SetOperation { op, all, left, right },
+        Parenthesized(value),
+    }
+    QuoteStyle {
+        Backtick,
+        DoubleQuote,
+    }
+    SetOperator {
+        Union,
+        Intersect,
+        Except,
+    }
+    LimitSyntax {
+        Limit,
+        LimitComma,
+        Fetch,
+    }
+    SelectAs {
+        Struct,
+        Value,
+        TypeName(value),
+    }
+    Distinct {
+        All,
+        Distinct,
+        On(value),
+    }
+    SelectItem {
+        Expr { expr, alias },
+        Wildcard,
+        QualifiedWildcard { qualifier },
+        WildcardExcept { qualifier, except },
+        WildcardReplace { qualifier, replace },
+    }
+    TableRefKind {
+        Table { name, alias, hints, sample },
+        Subquery { query, alias, sample, lateral },
+        Unnest { expr, alias, with_offset, offset_alias },
+        Join { left, right, join_type, condition },
+        Parenthesized(value),
+        TableFunction { name, args, alias, lateral },
+        Pivot { input, aggregates, for_column, values, alias },
+        Unpivot { input, include_nulls, value_columns, name_column, columns, alias },
+    }
+    SampleMethod {
+        Bernoulli,
+        System,
+        Reservoir,
+    }
+    SampleSize {
+        Percent(value),
+        Rows(value),
+    }
+    JoinType {
+        Inner,
+        Left,
+        Right,
+        Full,
+        Cross,
+        Natural,
+        LeftSemi,
+        RightSemi,
+        LeftAnti,
+        RightAnti,
+    }
+    JoinCondition {
+        On(value),
+        Using(value),
+    }
+    GroupByItem {
+        Expr(value),
+        Rollup(value),
+        Cube(value),
+        GroupingSets(value),
+    }
+    WindowFrameUnit {
+        Rows,
+        Range,
+        Groups,
+    }
+    FrameExclusion {
+        CurrentRow,
+        Group,
+        Ties,
+        NoOthers,
+    }
+    WindowFrameBound {
+        CurrentRow,
+        Preceding(value),
+        Following(value),
+    }
+}
+
+// Expressions.
+
+/// As an object of its kind, span, and ID, but with the left operands of a
+/// chain of binary operators such as `a + b + c` written in a loop rather
+/// than by recursing once per operator.
+impl ToJson for Expr {
+    fn write_json(&self, out: &mut String) {
+        let mut links = Vec::new();
+        let mut first = self;
+        while let ExprKind::BinaryOp { op, left, right } = &first.kind {
+            out.push_str("{\"kind\":{\"type\":\"BinaryOp\",\"op\":");
+            op.write_json(out);
+            out.push_str(",\"left\":");
+            links.push((right, first.span, first.id));
+            first = left;
+        }
+        let mut object = Object::new(out);
+        object.member("kind", &first.kind);
+        object.member("span", &first.span);
+        object.member("id", &first.id);
+        object.finish();
+        for (right, span, id) in links.into_iter().rev() {
+            out.push_str(",\"right\":");
+            right.write_json(out);
+            out.push_str("},\"span\":");
+            span.write_json(out);
+            out.push_str(",\"id\":");
+            id.write_json(out);
+            out.push('}');
+        }
+    }
+}
+
+json_struct! {
+    StructField { name, value }
+    FunctionCall { name, args, distinct, null_treatment, order_by, limit }
+    AggregateCall { function, filter }
+    WindowFunctionCall { function, window }
+}
+
+json_enum! {
+    ExprKind {
+        Null,
+        Boolean(value),
+        Integer(value),
+        Float(value),
+        String(value),
+        Bytes(value),
+        Array { element_type, elements },
+        Struct { fields },
+        Identifier(value),
+        CompoundIdentifier(value),
+        Parameter(value),
+        SystemVariable { scope, name },
+        UnaryOp { op, expr },
+        BinaryOp { op, left, right },
+        Between { expr, low, high, negated },
+        In { expr, list, negated },
+        Like { expr, pattern, escape, negated, case_insensitive },
+        QuantifiedLike { expr, quantifier, patterns, escape, negated, case_insensitive },
+        SimilarTo { expr, pattern, escape, negated },
+        IsExpr { expr, test, negated },
+        IsDistinct { left, right, negated },
+        Function(value),
+        Aggregate(value),
+        WindowFunction(value),
+        Cast { expr, data_type, safe },
+        Extract { field, from },
+        Case { operand, conditions, else_result },
+        If { condition, then_expr, else_expr },
+        Coalesce(value),
+        Nullif { left, right },
+        IfNull { expr, null_replacement },
+        Subquery(value),
+        Exists { subquery, negated },
+        QuantifiedComparison { expr, op, quantifier, list },
+        InSubquery { expr, subquery, negated },
+        ArraySubscript { array, index },
+        SafeArraySubscript { array, index, offset_type },
+        FieldAccess { expr, field },
+        JsonSubscript { expr, key },
+        Interval { value, unit },
+        TypedLiteral { data_type, value },
+        Parenthesized(value),
+        Row(value),
+    }
+    Parameter {
+        Named(value),
+        Positional(value),
+        Numbered(value),
+    }
+    UnaryOp {
+        Plus,
+        Minus,
+        Not,
+        BitwiseNot,
+    }
+    BinaryOp {
+        Plus,
+        Minus,
+        Multiply,
+        Divide,
+        Modulo,
+        Eq,
+        NotEq,
+        Lt,
+        LtEq,
+        Gt,
+        GtEq,
+        And,
+        Or,
+        BitwiseAnd,
+        BitwiseOr,
+        BitwiseXor,
+        LeftShift,
+        RightShift,
+        Concat,
+    }
+    InList {
+        Values(value),
+        Subquery(value),
+        Unnest(value),
+    }
+    IsTest {
+        Null,
+        True,
+        False,
+        Unknown,
+    }
+    Quantifier {
+        Any,
+        Some,
+        All,
+    }
+    ArraySubscriptKind {
+        Index(value),
+        Offset(value),
+        Ordinal(value),
+        SafeOffset(value),
+        SafeOrdinal(value),
+    }
+    ArrayOffsetType {
+        Offset,
+        Ordinal,
+    }
+    JsonKey {
+        String(value),
+        Index(value),
+    }
+    FunctionArg {
+        Unnamed(value),
+        Named { name, value },
+        Star,
+    }
+    NullTreatment {
+        RespectNulls,
+        IgnoreNulls,
+    }
+    WindowSpecOrRef {
+        Spec(value),
+        Ref(value),
+    }
+    DateTimePart {
+        Year,
+        Month,
+        Day,
+        Hour,
+        Minute,
+        Second,
+        Millisecond,
+        Microsecond,
+        Nanosecond,
+        Dayofweek,
+        Dayofyear,
+        Week,
+        Quarter,
+        Date,
+        Time,
+        Datetime,
+        Isoweek,
+        Isoyear,
+    }
+    IntervalUnit {
+        Year,
+        Month,
+        Day,
+        Hour,
+        Minute,
+        Second,
+        Millisecond,
+        Microsecond,
+        Nanosecond,
+        Week,
+        Quarter,
+    }
+    TypedLiteralType {
+        Date,
+        Time,
+        Timestamp,
+        Datetime,
+        Json,
+        Numeric,
+        Bignumeric,
+        Range,
+    }
+}
+
+// Statements.
+
+json_struct! {
+    Statement { kind, span, id }
+    InsertStatement { optimizer_hints, or_action, table, columns, source, returning }
+    UpdateStatement { optimizer_hints, table, assignments, from, where_clause, returning }
+    Assignment { target, value }
+    DeleteStatement { optimizer_hints, table, hints, alias, where_clause, returning }
+    MergeStatement { target, source, on, clauses }
+    MergeNotMatchedAction { columns, values }
+    ReturningClause { items, with_action }
+    CreateDatabaseStatement { name, if_not_exists, options }
+    CreateTableStatement {
+        or_replace, temporary, if_not_exists, name, columns, constraints, partition_by, cluster_by,
+        options, as_query, like, clone,
+    }
+    CreateViewStatement { or_replace, materialized, if_not_exists, name, columns, query, options }
+    CreateIndexStatement { unique, if_not_exists, name, table, columns, options }
+    CreateFunctionStatement {
+        or_replace, temporary, if_not_exists, name, params, returns, language, body, options,
+    }
+    FunctionParam { name, data_type, default }
+    CreateProcedureStatement { or_replace, if_not_exists, name, params, body, options }
+    ProcedureParam { mode, name, data_type }
+    AlterTableStatement { if_exists, name, action }
+    AlterViewStatement { if_exists, name, action }
+    DropStatement { object_type, if_exists, names, cascade }
+    TruncateStatement { table }
+    BeginStatement { mode }
+    RollbackStatement { savepoint }
+    ExplainStatement { analyze, format, statement }
+    DescribeStatement { object }
+    ShowStatement { object_type, filter }
+    SetStatement { variable, value }
+    DeclareStatement { names, data_type, default }
+    SetVariableStatement { names, value }
+    IfStatement { condition, then_body, elseif_clauses, else_body }
+    LoopStatement { body }
+    WhileStatement { condition, body }
+    ForStatement { variable, query, body }
+    RaiseStatement { message }
+    CallStatement { name, args }
+    BeginEndStatement { statements, exception_handlers }
+    ExceptionHandler { when, body }
+}
+
+json_enum! {
+    StatementKind {
+        Query(value),
+        Insert(value),
+        Update(value),
+        Delete(value),
+        Merge(value),
+        CreateDatabase(value),
+        CreateTable(value),
+        CreateView(value),
+        CreateIndex(value),
+        CreateFunction(value),
+        CreateProcedure(value),
+        AlterTable(value),
+        AlterView(value),
+        Drop(value),
+        Truncate(value),
+        Begin(value),
+        Commit,
+        Rollback(value),
+        Explain(value),
+        Describe(value),
+        Show(value),
+        Set(value),
+        Empty,
+    }
+    InsertOrAction {
+        Ignore,
+        Replace,
+        Update,
+    }
+    InsertSource {
+        Values(value),
+        Query(value),
+        DefaultValues,
+    }
+    AssignmentTarget {
+        Column(value),
+        Path(value),
+    }
+    MergeClause {
+        Matched { condition, action },
+        NotMatched { condition, action },
+        NotMatchedBySource { condition, action },
+    }
+    MergeMatchedAction {
+        Update { assignments },
+        Delete,
+    }
+    FunctionBody {
+        Expr(value),
+        Statements(value),
+        External(value),
+    }
+    ParamMode {
+        In,
+        Out,
+        InOut,
+    }
+    AlterTableAction {
+        AddColumn { if_not_exists, column },
+        DropColumn { if_exists, column },
+        AlterColumn { column, action },
+        AddConstraint(value),
+        DropConstraint { if_exists, name },
+        RenameColumn { from, to },
+        RenameTable(value),
+        SetOptions(value),
+    }
+    AlterColumnAction {
+        SetDataType(value),
+        SetDefault(value),
+        DropDefault,
+        SetNotNull,
+        DropNotNull,
+        SetOptions(value),
+    }
+    AlterViewAction {
+        SetOptions(value),
+        SetQuery(value),
+    }
+    ObjectType {
+        Table,
+        View,
+        MaterializedView,
+        Index,
+        Function,
+        Procedure,
+        Database,
+        Schema,
+    }
+    TransactionMode {
+        ReadOnly,
+        ReadWrite,
+    }
+    ExplainFormat {
+        Text,
+        Json,
+    }
+    ShowObjectType {
+        Tables { from },
+        Databases,
+        Schemas { from },
+        Columns { from },
+        Functions { from },
+        Variables,
+    }
+    ShowFilter {
+        Like(value),
+        Where(value),
+    }
+    SetValue {
+        Expr(value),
+        Default,
+    }
+    ProceduralStatement {
+        Declare(value),
+        Set(value),
+        If(value),
+        Loop(value),
+        While(value),
+        For(value),
+        Break,
+        Continue,
+        Return(value),
+        Raise(value),
+        Call(value),
+        Begin(value),
+    }
+    ExceptionWhen {
+        Error,
+        Named(value),
+    }
+}
+
+// Data types.
+
+json_struct! {
+    DataTypeSpec { kind, span }
+    TypeStructField { name, data_type }
+}
+
+json_enum! {
+    DataTypeKind {
+        Bool,
+        Int32,
+        Int64,
+        Uint32,
+        Uint64,
+        Float32,
+        Float64,
+        Numeric { precision, scale },
+        Varchar { max_length },
+        Varbinary { max_length },
+        Date,
+        Time,
+        Datetime,
+        Timestamp,
+        Interval,
+        Array(value),
+        Struct(value),
+        Json,
+        Range(value),
+        Uuid,
+        Custom(a, b),
+        Any,
+    }
+}
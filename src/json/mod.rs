@@ -0,0 +1,418 @@
+//! JSON serialization of parse and analysis results.
+//!
+//! [`ToJson`] is implemented for every AST node, for [`AnalyzedQuery`] and
+//! the types it holds, and for [`Error`] and [`AnalyzerError`]. The output
+//! is compact JSON with members in a fixed order:
+//!
+//! - A struct is an object with one member per field, named as in Rust.
+//! - An enum variant without data is its name as a string, such as
+//!   `"Asc"`. Any other variant is an object whose `"type"` member is the
+//!   variant name, followed by its fields, or by `"value"` for a variant
+//!   holding one unnamed value and `"values"`, an array, for several.
+//! - `None` is `null` and a `Vec` is an array. A [`Span`] is
+//!   `{"start":..,"end":..}` in byte offsets, a [`NodeId`] is a number,
+//!   or `null` if unassigned, and a [`SqlType`] is its SQL name.
+//!
+//! ```
+//! use vibesql::json::ToJson;
+//! use vibesql::Parser;
+//!
+//! let stmt = Parser::new("SELECT 1").parse_statement().unwrap();
+//! let json = stmt.to_json();
+//! assert!(json.starts_with(r#"{"kind":{"type":"Query","value":{"with":null,"#));
+//! ```
+//!
+//! Field and variant names follow the Rust types, so adding a field to a
+//! node adds a member to its object; existing members keep their names and
+//! positions.
+
+mod ast;
+mod result;
+
+use std::fmt::Write;
+
+use crate::analyzer::AnalyzerError;
+use crate::ast::NodeId;
+use crate::error::{Error, Span};
+use crate::types::{Numeric, SqlType};
+
+/// Serialization to JSON.
+pub trait ToJson {
+    /// Append the JSON for `self` to `out`.
+    fn write_json(&self, out: &mut String);
+
+    /// The JSON for `self`.
+    fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out);
+        out
+    }
+}
+
+/// Append `s` as a JSON string.
+fn write_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Writes the members of a JSON object.
+struct Object<'a> {
+    out: &'a mut String,
+    empty: bool,
+}
+
+impl<'a> Object<'a> {
+    fn new(out: &'a mut String) -> Self {
+        out.push('{');
+        Self { out, empty: true }
+    }
+
+    /// An object for an enum variant, starting with its `"type"` member.
+    fn tagged(out: &'a mut String, variant: &str) -> Self {
+        let mut object = Self::new(out);
+        object.member("type", variant);
+        object
+    }
+
+    fn member<T: ToJson + ?Sized>(&mut self, name: &str, value: &T) {
+        if !self.empty {
+            self.out.push(',');
+        }
+        self.empty = false;
+        write_str(self.out, name);
+        self.out.push(':');
+        value.write_json(self.out);
+    }
+
+    fn finish(self) {
+        self.out.push('}');
+    }
+}
+
+/// Implement [`ToJson`] for structs, as objects with one member per field.
+macro_rules! json_struct {
+    ($($ty:ty { $($field:ident),* $(,)? })*) => {$(
+        impl ToJson for $ty {
+            fn write_json(&self, out: &mut String) {
+                let Self { $($field),* } = self;
+                let mut object = Object::new(out);
+                $(object.member(stringify!($field), $field);)*
+                object.finish();
+            }
+        }
+    )*};
+}
+
+/// Implement [`ToJson`] for enums, as described in the module docs.
+macro_rules! json_enum {
+    ($($ty:ty {
+        $($variant:ident $(( $($value:ident),+ ))? $({ $($field:ident),* })?),* $(,)?
+    })*) => {$(
+        impl ToJson for $ty {
+            fn write_json(&self, out: &mut String) {
+                match self {
+                    $(Self::$variant $(( $($value),+ ))? $({ $($field),* })? => {
+                        json_enum!(@variant out, $variant $(( $($value),+ ))? $({ $($field),* })?)
+                    })*
+                }
+            }
+        }
+    )*};
+    (@variant $out:ident, $variant:ident) => {
+        write_str($out, stringify!($variant))
+    };
+    (@variant $out:ident, $variant:ident ($value:ident)) => {{
+        let mut object = Object::tagged($out, stringify!($variant));
+        object.member("value", $value);
+        object.finish();
+    }};
+    (@variant $out:ident, $variant:ident ($($value:ident),+)) => {{
+        let mut object = Object::tagged($out, stringify!($variant));
+        object.member("values", &[$($value as &dyn ToJson),+][..]);
+        object.finish();
+    }};
+    (@variant $out:ident, $variant:ident { $($field:ident),* }) => {{
+        let mut object = Object::tagged($out, stringify!($variant));
+        $(object.member(stringify!($field), $field);)*
+        object.finish();
+    }};
+}
+
+use json_enum;
+use json_struct;
+
+impl ToJson for str {
+    fn write_json(&self, out: &mut String) {
+        write_str(out, self);
+    }
+}
+
+impl ToJson for String {
+    fn write_json(&self, out: &mut String) {
+        write_str(out, self);
+    }
+}
+
+impl ToJson for char {
+    fn write_json(&self, out: &mut String) {
+        write_str(out, self.encode_utf8(&mut [0; 4]));
+    }
+}
+
+impl ToJson for bool {
+    fn write_json(&self, out: &mut String) {
+        out.push_str(if *self { "true" } else { "false" });
+    }
+}
+
+macro_rules! json_integer {
+    ($($ty:ty),*) => {$(
+        impl ToJson for $ty {
+            fn write_json(&self, out: &mut String) {
+                let _ = write!(out, "{}", self);
+            }
+        }
+    )*};
+}
+
+json_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64);
+
+impl ToJson for f64 {
+    /// Non-finite numbers, which JSON cannot represent, are `null`.
+    fn write_json(&self, out: &mut String) {
+        if self.is_finite() {
+            let _ = write!(out, "{}", self);
+        } else {
+            out.push_str("null");
+        }
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn write_json(&self, out: &mut String) {
+        (**self).write_json(out);
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn write_json(&self, out: &mut String) {
+        (**self).write_json(out);
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn write_json(&self, out: &mut String) {
+        match self {
+            Some(value) => value.write_json(out),
+            None => out.push_str("null"),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn write_json(&self, out: &mut String) {
+        out.push('[');
+        for (i, value) in self.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            value.write_json(out);
+        }
+        out.push(']');
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn write_json(&self, out: &mut String) {
+        self.as_slice().write_json(out);
+    }
+}
+
+impl<A: ToJson, B: ToJson> ToJson for (A, B) {
+    fn write_json(&self, out: &mut String) {
+        [&self.0 as &dyn ToJson, &self.1][..].write_json(out);
+    }
+}
+
+impl ToJson for Span {
+    fn write_json(&self, out: &mut String) {
+        let mut object = Object::new(out);
+        object.member("start", &self.start);
+        object.member("end", &self.end);
+        object.finish();
+    }
+}
+
+impl ToJson for NodeId {
+    /// [`NodeId::UNASSIGNED`] is `null`.
+    fn write_json(&self, out: &mut String) {
+        if *self == NodeId::UNASSIGNED {
+            out.push_str("null");
+        } else {
+            self.0.write_json(out);
+        }
+    }
+}
+
+impl ToJson for SqlType {
+    fn write_json(&self, out: &mut String) {
+        write_str(out, &self.to_string());
+    }
+}
+
+/// A NUMERIC as a string, which keeps every digit.
+impl ToJson for Numeric {
+    fn write_json(&self, out: &mut String) {
+        write_str(out, &self.to_string());
+    }
+}
+
+impl ToJson for Error {
+    /// An object with the error's `kind`, its `message`, and its `span`,
+    /// `context`, and `suggestions`.
+    fn write_json(&self, out: &mut String) {
+        let mut object = Object::new(out);
+        object.member("kind", self.kind());
+        object.member("message", &self.kind().to_string());
+        object.member("span", &self.span());
+        object.member("context", &self.context());
+        object.member("suggestions", self.suggestions());
+        object.finish();
+    }
+}
+
+impl ToJson for AnalyzerError {
+    /// An object with the error's `kind`, its `message`, including any
+    /// suggestion, and its `span` and `suggestion`.
+    fn write_json(&self, out: &mut String) {
+        let mut object = Object::new(out);
+        object.member("kind", &self.kind);
+        object.member("message", &self.to_string());
+        object.member("span", &self.span);
+        object.member("suggestion", &self.suggestion);
+        object.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::CatalogBuilder;
+    use crate::{Analyzer, Parser, StatementKind};
+
+    #[test]
+    fn test_json_strings() {
+        assert_eq!("a\"b\\c\n\t\u{1}é".to_json(), r#""a\"b\\c\n\t\u0001é""#);
+        assert_eq!(Some(1.5).to_json(), "1.5");
+        assert_eq!(f64::NAN.to_json(), "null");
+        assert_eq!(None::<u32>.to_json(), "null");
+        assert_eq!(vec![("a".to_string(), true)].to_json(), r#"[["a",true]]"#);
+    }
+
+    #[test]
+    fn test_expr_json() {
+        let expr = Parser::new("-x IS NULL").parse_expression().unwrap();
+        assert_eq!(
+            expr.to_json(),
+            concat!(
+                r#"{"kind":{"type":"IsExpr","expr":{"kind":{"type":"UnaryOp","op":"Minus","#,
+                r#""expr":{"kind":{"type":"Identifier","value":{"value":"x","quoted":false,"#,
+                r#""quote_style":"Backtick","span":{"start":1,"end":2}}},"#,
+                r#""span":{"start":1,"end":2},"id":null}},"#,
+                r#""span":{"start":0,"end":2},"id":null},"test":"Null","negated":false},"#,
+                r#""span":{"start":0,"end":10},"id":null}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_statement_json() {
+        let stmt = Parser::new("DROP TABLE IF EXISTS t")
+            .parse_statement()
+            .unwrap();
+        assert_eq!(
+            stmt.to_json(),
+            concat!(
+                r#"{"kind":{"type":"Drop","value":{"object_type":"Table","if_exists":true,"#,
+                r#""names":[{"parts":[{"value":"t","quoted":false,"quote_style":"Backtick","#,
+                r#""span":{"start":21,"end":22}}],"#,
+                r#""span":{"start":21,"end":22}}],"cascade":false}},"#,
+                r#""span":{"start":0,"end":22},"id":0}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_long_chain_json() {
+        let sql = format!("SELECT {}", vec!["c"; 10_000].join(" + "));
+        let stmt = Parser::new(&sql).parse_statement().unwrap();
+        assert_eq!(
+            stmt.to_json().matches(r#""type":"BinaryOp""#).count(),
+            9_999
+        );
+    }
+
+    #[test]
+    fn test_error_json() {
+        let err = Parser::new("SELECT FROM").parse().unwrap_err();
+        assert_eq!(
+            err.to_json(),
+            concat!(
+                r#"{"kind":{"type":"UnexpectedToken","expected":"expression","#,
+                r#""found":"keyword From"},"message":"expected expression, found keyword From","#,
+                r#""span":{"start":7,"end":11},"context":null,"suggestions":[]}"#,
+            )
+        );
+
+        let catalog = CatalogBuilder::new()
+            .add_table("t", |t| t.column("a", SqlType::Int64))
+            .build();
+        let stmt = Parser::new("SELECT b FROM t").parse_statement().unwrap();
+        let err = Analyzer::with_catalog(catalog).analyze(&stmt).unwrap_err();
+        assert!(
+            err.to_json().starts_with(r#"{"kind":{"type":"#),
+            "{}",
+            err.to_json()
+        );
+    }
+
+    #[test]
+    fn test_analyzed_query_json() {
+        let catalog = CatalogBuilder::new()
+            .add_table("t", |t| t.column("a", SqlType::Int64))
+            .build();
+        let stmt = Parser::new("SELECT a, 'x' AS b FROM t")
+            .parse_statement()
+            .unwrap();
+        let StatementKind::Query(query) = stmt.kind else {
+            panic!("expected a query");
+        };
+        let result = Analyzer::with_catalog(catalog)
+            .analyze_query_result(&query)
+            .unwrap();
+        assert_eq!(
+            result.to_json(),
+            concat!(
+                r#"{"columns":[{"name":"a","data_type":"BIGINT","nullable":true,"#,
+                r#""source":{"table":"t","column":"a","column_index":0},"constant_value":null},"#,
+                r#"{"name":"b","data_type":"VARCHAR","nullable":false,"source":null,"#,
+                r#""constant_value":{"type":"String","value":"x"}}],"#,
+                r#""has_aggregation":false,"has_window_functions":false,"windows":[],"sort_keys":[],"#,
+                r#""implicit_cross_joins":[]}"#,
+            )
+        );
+    }
+}
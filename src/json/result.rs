@@ -0,0 +1,165 @@
+//! [`ToJson`] for analysis results and error kinds.
+
+use super::{json_enum, json_struct, write_str, Object, ToJson};
+use crate::analyzer::*;
+use crate::error::{ErrorKind, Suggestion};
+use crate::types::value::Interval;
+use crate::types::Value;
+
+// Analysis results.
+
+json_struct! {
+    AnalyzedQuery {
+        columns, has_aggregation, has_window_functions, windows, sort_keys, implicit_cross_joins,
+    }
+    OutputColumn { name, data_type, nullable, source, constant_value }
+    ColumnOrigin { table, column, column_index }
+}
+
+json_struct! {
+    TypedWindow { function, data_type, spec, sort_keys }
+    SortKey { target, data_type, order, nulls, collation }
+}
+
+json_enum! {
+    SortTarget {
+        Output(value),
+        Expr(value),
+    }
+}
+
+// Constant values.
+
+json_struct! {
+    Interval { months, days, micros }
+}
+
+json_enum! {
+    Value {
+        Null,
+        Boolean(value),
+        Int64(value),
+        Float64(value),
+        Numeric(value),
+        String(value),
+        Bytes(value),
+        Date(value),
+        Time(value),
+        Datetime(value),
+        Timestamp(value),
+        Interval(value),
+        Array(value),
+        Struct(value),
+        Json(value),
+    }
+}
+
+// Errors.
+
+json_struct! {
+    Suggestion { message, span, replacement }
+}
+
+json_enum! {
+    ErrorKind {
+        UnexpectedCharacter(value),
+        UnterminatedString,
+        UnterminatedBlockComment,
+        InvalidEscapeSequence(value),
+        InvalidNumber(value),
+        InvalidHexLiteral,
+        InvalidBytesLiteral,
+        UnexpectedToken { expected, found },
+        UnexpectedEof,
+        ExpectedExpression,
+        ExpectedIdentifier,
+        ExpectedKeyword(value),
+        InvalidSyntax(value),
+        UnsupportedFeature(value),
+        TrailingComma,
+        DuplicateOperator(value),
+        DuplicateKeyword(value),
+        UndefinedColumn(value),
+        UndefinedTable(value),
+        TableAlreadyExists(value),
+        UndefinedFunction(value),
+        UndefinedType(value),
+        UndefinedVariable(value),
+        FunctionAlreadyExists(value),
+        AmbiguousColumn(value),
+        TypeMismatch { expected, found },
+        InvalidArgumentCount { function, expected, found },
+        DuplicateColumn(value),
+        DuplicateAlias(value),
+        InvalidGroupBy(value),
+        InvalidOrderBy(value),
+        InvalidAggregateUsage(value),
+        InvalidWindowFunction(value),
+        PolicyViolation { code, message },
+        FunctionPackConflict { function, packs },
+        WrongObjectType { name, expected },
+        AmbiguousName { name, candidates },
+        InvalidCast(value),
+        NumericOverflow(value),
+        DivisionByZero,
+        ArrayIndexOutOfBounds { index, len },
+        InvalidEncoding(value),
+        Internal(value),
+    }
+}
+
+json_enum! {
+    AnalyzerErrorKind {
+        TableNotFound { name },
+        ColumnNotFound { name, table },
+        FieldNotFound { name, parent, available },
+        AmbiguousColumn { name, candidates },
+        AmbiguousTable { name, candidates },
+        FunctionNotFound { name, searched },
+        TypeNotFound { name },
+        VariableNotFound { name },
+        UndefinedParameter { function, name },
+        WrongArgumentCount { function, expected_min, expected_max, actual },
+        TypeMismatch { expected, actual, context },
+        InvalidAssignment { column, column_type, value_type },
+        NullAssignment { column, column_type },
+        InsertColumnCountMismatch { expected, found },
+        MissingInsertColumn { column },
+        TypesNotComparable { left, right },
+        InvalidAggregateUse { function, reason },
+        InvalidWindowUse { function, reason },
+        InvalidWindowSpec { reason },
+        InvalidTableSample { reason },
+        InvalidPivot { operator, reason },
+        DuplicateAlias { name },
+        DuplicateGroupByColumn { name },
+        DuplicateColumn { name },
+        ColumnListMismatch { relation, expected, found },
+        NonAggregatedColumn { column },
+        PositionOutOfRange { clause, position, count },
+        OrderByNotInSelect { column },
+        DistinctOnOrderMismatch { expr },
+        HavingWithoutGroupBy,
+        WithTiesWithoutOrderBy,
+        InvalidSubquery { reason },
+        SubqueryColumnCount { expected, found },
+        DivisionByZero,
+        InvalidCast { from, to },
+        InvalidLiteral { value, expected_type, reason },
+        DuplicateCte { name },
+        TooManyCtes { limit },
+        ExpressionTooDeep { limit },
+        ImplicitCrossJoin,
+        MissingJoinCondition,
+        CrossJoinCondition,
+        InvalidMerge { reason },
+        InvalidRecursiveCte { reason },
+        NotOrderable { data_type },
+        UnguardedWrite { statement },
+        NoNaturalJoinColumns,
+        StarNotAllowed { context },
+        SetOperationColumnMismatch { left, right },
+        SetOperationTypeMismatch { position, left, right },
+        Other { message },
+    }
+}
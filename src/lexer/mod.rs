@@ -5,9 +5,10 @@
 
 mod token;
 
-pub use token::{Keyword, Token, TokenKind};
+pub use token::{Keyword, Token, TokenKind, TokenWithTrivia};
 
 use crate::error::{Error, Result, Span};
+use std::collections::VecDeque;
 
 /// SQL Lexer that tokenizes input into a stream of tokens.
 pub struct Lexer<'a> {
@@ -20,7 +21,17 @@ pub struct Lexer<'a> {
     /// Start position of current token.
     start: usize,
     /// Peeked tokens (for lookahead).
-    peeked: Vec<Token>,
+    peeked: VecDeque<Token>,
+    /// Largest number of tokens buffered for lookahead so far.
+    max_lookahead: usize,
+    /// Caller-provided tokens to replay instead of scanning the input.
+    replay: Option<std::vec::IntoIter<Token>>,
+    /// Whether comments are returned as tokens instead of skipped.
+    keep_comments: bool,
+    /// Whether the last token can be followed by an optimizer hint.
+    hint_allowed: bool,
+    /// Whether `"..."` is a quoted identifier rather than a string.
+    double_quote_identifiers: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -31,7 +42,111 @@ impl<'a> Lexer<'a> {
             bytes: input.as_bytes(),
             pos: 0,
             start: 0,
-            peeked: Vec::new(),
+            peeked: VecDeque::new(),
+            max_lookahead: 0,
+            replay: None,
+            keep_comments: false,
+            hint_allowed: false,
+            double_quote_identifiers: false,
+        }
+    }
+
+    /// Lex `"..."` as a quoted identifier, as in ANSI SQL, instead of a
+    /// string literal.
+    ///
+    /// A doubled `""` stands for one `"` inside the name, and backslashes
+    /// are not escapes. Backtick identifiers and prefixed strings such as
+    /// `b"..."` are unaffected.
+    pub fn with_double_quote_identifiers(mut self, enabled: bool) -> Self {
+        self.double_quote_identifiers = enabled;
+        self
+    }
+
+    /// Create a lexer that returns comments as
+    /// [`TokenKind::LineComment`] and [`TokenKind::BlockComment`] tokens
+    /// instead of skipping them.
+    ///
+    /// The parser does not accept comment tokens, but
+    /// [`from_tokens`](Self::from_tokens) drops them, so a kept stream can
+    /// still be parsed with [`Parser::from_tokens`](crate::Parser::from_tokens).
+    pub fn with_comments(input: &'a str) -> Self {
+        Self {
+            keep_comments: true,
+            ..Self::new(input)
+        }
+    }
+
+    /// Create a lexer that replays a previously produced token stream.
+    ///
+    /// `input` is the original source the tokens were lexed from; it is only
+    /// used for positions. Tokens are returned in order and an EOF token is
+    /// synthesized at the end of `input` once they run out. The position
+    /// tracks the end of the last consumed token that has a real span.
+    /// Comment tokens are dropped.
+    pub fn from_tokens(tokens: Vec<Token>, input: &'a str) -> Self {
+        let tokens: Vec<Token> = tokens.into_iter().filter(|t| !t.is_comment()).collect();
+        Self {
+            replay: Some(tokens.into_iter()),
+            ..Self::new(input)
+        }
+    }
+
+    /// Lex all remaining tokens, excluding the final EOF token.
+    pub fn tokenize(&mut self) -> Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+        loop {
+            let token = self.next_token_result()?;
+            if token.is_eof() {
+                return Ok(tokens);
+            }
+            tokens.push(token);
+        }
+    }
+
+    /// Lex all remaining tokens with the comments around them attached as
+    /// trivia, ending with the EOF token.
+    ///
+    /// A comment that starts on the line where the previous token ends is
+    /// trailing trivia of that token; every other comment is leading trivia
+    /// of the token after it, so comments at the end of the input lead the
+    /// EOF token. Comments are kept whichever constructor made the lexer.
+    ///
+    /// ```
+    /// use vibesql::lexer::Lexer;
+    ///
+    /// let sql = "SELECT id, -- the key\n  /* the name */ name";
+    /// let tokens = Lexer::new(sql).tokenize_with_trivia().unwrap();
+    /// assert_eq!(tokens[2].trailing[0].text, "-- the key");
+    /// assert_eq!(tokens[3].leading[0].text, "/* the name */");
+    /// ```
+    pub fn tokenize_with_trivia(&mut self) -> Result<Vec<TokenWithTrivia>> {
+        self.keep_comments = true;
+        let mut tokens: Vec<TokenWithTrivia> = Vec::new();
+        let mut leading = Vec::new();
+        loop {
+            let token = self.next_token_result()?;
+            if token.is_comment() {
+                match tokens.last_mut() {
+                    Some(last)
+                        if leading.is_empty()
+                            && !self.input[last.token.span.end..token.span.start]
+                                .contains('\n') =>
+                    {
+                        last.trailing.push(token)
+                    }
+                    _ => leading.push(token),
+                }
+                continue;
+            }
+            let eof = token.is_eof();
+            tokens.push(TokenWithTrivia {
+                token,
+                leading: std::mem::take(&mut leading),
+                trailing: Vec::new(),
+            });
+            if eof {
+                return Ok(tokens);
+            }
         }
     }
 
@@ -45,31 +160,40 @@ impl<'a> Lexer<'a> {
         &self.input[self.pos..]
     }
 
+    /// Largest lookahead depth used so far, in tokens.
+    ///
+    /// `peek` counts as a depth of 1 and `peek_nth(n)` as `n + 1`.
+    pub fn max_lookahead(&self) -> usize {
+        self.max_lookahead
+    }
+
     /// Peek at the next token without consuming it.
     pub fn peek(&mut self) -> Result<&Token> {
-        if self.peeked.is_empty() {
-            let token = self.next_token()?;
-            self.peeked.push(token);
-        }
-        Ok(&self.peeked[0])
+        self.peek_nth(0)
     }
 
     /// Peek at the nth token ahead (0 = next token).
+    ///
+    /// Buffers `n + 1` tokens; consuming from the buffer is O(1).
     pub fn peek_nth(&mut self, n: usize) -> Result<&Token> {
         while self.peeked.len() <= n {
             let token = self.next_token()?;
-            self.peeked.push(token);
+            self.peeked.push_back(token);
         }
+        self.max_lookahead = self.max_lookahead.max(n + 1);
         Ok(&self.peeked[n])
     }
 
     /// Get the next token, consuming it.
     pub fn next_token_result(&mut self) -> Result<Token> {
-        if !self.peeked.is_empty() {
-            // Remove from front to maintain order
-            return Ok(self.peeked.remove(0));
+        let token = match self.peeked.pop_front() {
+            Some(token) => token,
+            None => self.next_token()?,
+        };
+        if self.replay.is_some() && !token.span.is_synthetic() {
+            self.pos = token.span.end;
         }
-        self.next_token()
+        Ok(token)
     }
 
     /// Consume the next token if it matches the expected kind.
@@ -106,6 +230,25 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Expect and consume the `>` closing a type parameter list.
+    ///
+    /// A `>>` token is split in two, so nested types such as
+    /// `ARRAY<ARRAY<INT64>>` close one level at a time.
+    pub(crate) fn expect_closing_angle(&mut self) -> Result<Token> {
+        if self.peek()?.kind != TokenKind::RightShift {
+            return self.expect(&TokenKind::Gt);
+        }
+        let token = self.next_token_result()?;
+        let mid = token.span.start + 1;
+        let rest = Token::new(TokenKind::Gt, Span::new(mid, token.span.end), ">");
+        self.peeked.push_front(rest);
+        Ok(Token::new(
+            TokenKind::Gt,
+            Span::new(token.span.start, mid),
+            ">",
+        ))
+    }
+
     /// Expect and consume a specific keyword.
     pub fn expect_keyword(&mut self, keyword: Keyword) -> Result<Token> {
         let token = self.next_token_result()?;
@@ -122,7 +265,42 @@ impl<'a> Lexer<'a> {
 
     /// Internal: get the next token from input.
     fn next_token(&mut self) -> Result<Token> {
-        self.skip_whitespace_and_comments()?;
+        if let Some(replay) = &mut self.replay {
+            return Ok(replay
+                .next()
+                .unwrap_or_else(|| Token::new(TokenKind::Eof, Span::empty(self.input.len()), "")));
+        }
+
+        // A `/*+ ... */` comment right after SELECT, INSERT, UPDATE, or
+        // DELETE is an optimizer hint rather than a comment.
+        let hint_allowed = std::mem::take(&mut self.hint_allowed);
+        self.skip_whitespace();
+        if hint_allowed && self.remaining().starts_with("/*+") {
+            self.start = self.pos;
+            self.skip_block_comment()?;
+            let text = &self.input[self.start + 3..self.pos - 2];
+            self.hint_allowed = true;
+            return Ok(self.make_token(TokenKind::Hint(text.to_string())));
+        }
+
+        let token = self.scan_token()?;
+        self.hint_allowed = (hint_allowed && token.is_comment())
+            || matches!(
+                token.kind,
+                TokenKind::Keyword(
+                    Keyword::Select | Keyword::Insert | Keyword::Update | Keyword::Delete
+                )
+            );
+        Ok(token)
+    }
+
+    /// Scan the next token from the input.
+    fn scan_token(&mut self) -> Result<Token> {
+        if self.keep_comments {
+            self.skip_whitespace();
+        } else {
+            self.skip_whitespace_and_comments()?;
+        }
 
         self.start = self.pos;
 
@@ -130,6 +308,20 @@ impl<'a> Lexer<'a> {
             return Ok(self.make_token(TokenKind::Eof));
         }
 
+        if self.keep_comments {
+            if self.check('/') && self.check_next('*') {
+                self.skip_block_comment()?;
+                let text = &self.input[self.start + 2..self.pos - 2];
+                return Ok(self.make_token(TokenKind::BlockComment(text.to_string())));
+            }
+            if (self.check('-') && self.check_next('-')) || self.check('#') {
+                let marker = if self.check('#') { 1 } else { 2 };
+                self.skip_line_comment();
+                let text = self.input[self.start + marker..self.pos].trim_end_matches('\r');
+                return Ok(self.make_token(TokenKind::LineComment(text.to_string())));
+            }
+        }
+
         let c = self.advance();
 
         match c {
@@ -148,9 +340,18 @@ impl<'a> Lexer<'a> {
             '^' => Ok(self.make_token(TokenKind::Caret)),
             '~' => Ok(self.make_token(TokenKind::Tilde)),
             '?' => Ok(self.make_token(TokenKind::Question)),
-            '@' => Ok(self.make_token(TokenKind::At)),
+            '@' => {
+                if self.matches('@') {
+                    Ok(self.make_token(TokenKind::DoubleAt))
+                } else {
+                    Ok(self.make_token(TokenKind::At))
+                }
+            }
             '#' => Ok(self.make_token(TokenKind::Hash)),
-            '$' => Ok(self.make_token(TokenKind::Dollar)),
+            '$' if self.peek_char().is_some_and(|c| c.is_ascii_digit()) => {
+                self.scan_numbered_parameter()
+            }
+            '$' => self.scan_dollar(),
             '\\' => Ok(self.make_token(TokenKind::Backslash)),
 
             // Multi-character tokens
@@ -227,6 +428,7 @@ impl<'a> Lexer<'a> {
             }
 
             // String literals
+            '"' if self.double_quote_identifiers => self.scan_double_quoted_identifier(),
             '\'' | '"' => self.scan_string(c),
 
             // Backtick quoted identifier
@@ -323,6 +525,12 @@ impl<'a> Lexer<'a> {
         let first = self.advance();
         let first_lower = first.to_ascii_lowercase();
 
+        // e'...' is a PostgreSQL escape string; escapes apply as usual.
+        if first_lower == 'e' && self.check('\'') {
+            self.advance();
+            return self.scan_string('\'');
+        }
+
         // Check for raw/bytes string prefixes
         if first_lower == 'r' || first_lower == 'b' {
             if let Some(second) = self.peek_char() {
@@ -416,6 +624,34 @@ impl<'a> Lexer<'a> {
         Ok(self.make_token(TokenKind::String(value)))
     }
 
+    /// Scan what follows a `$` that does not start a `$1` parameter.
+    ///
+    /// `$$` or `$tag$`, with a tag of letters, digits, and underscores,
+    /// opens a dollar-quoted string as in PostgreSQL, which runs to the
+    /// next copy of the same tag and has no escapes. Otherwise the `$` is a
+    /// token of its own.
+    fn scan_dollar(&mut self) -> Result<Token> {
+        let rest = self.remaining();
+        let tag_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        if !rest[tag_len..].starts_with('$') {
+            return Ok(self.make_token(TokenKind::Dollar));
+        }
+
+        let body_start = self.pos + tag_len + 1;
+        let tag = &self.input[self.start..body_start];
+        let Some(len) = self.input[body_start..].find(tag) else {
+            self.pos = self.input.len();
+            return Err(Error::unterminated_string(Span::new(
+                self.start, body_start,
+            )));
+        };
+        let value = self.input[body_start..body_start + len].to_string();
+        self.pos = body_start + len + tag.len();
+        Ok(self.make_token(TokenKind::String(value)))
+    }
+
     /// Scan a raw string literal (r"..." or r'...').
     fn scan_raw_string(&mut self, quote: char) -> Result<Token> {
         let triple = self.matches(quote) && self.matches(quote);
@@ -463,7 +699,7 @@ impl<'a> Lexer<'a> {
                     if self.matches(quote) && self.matches(quote) {
                         break;
                     } else {
-                        bytes.push(c as u8);
+                        bytes.push(quote as u8);
                     }
                 } else {
                     break;
@@ -473,8 +709,12 @@ impl<'a> Lexer<'a> {
                 bytes.extend(escaped);
             } else if c == '\n' && !triple {
                 return Err(Error::unterminated_string(Span::new(self.start, self.pos)));
-            } else {
+            } else if c.is_ascii() {
                 bytes.push(c as u8);
+            } else {
+                // Other bytes must be written as escapes.
+                let span = Span::new(self.pos - c.len_utf8(), self.pos);
+                return Err(Error::invalid_bytes_literal(span));
             }
         }
 
@@ -483,11 +723,10 @@ impl<'a> Lexer<'a> {
 
     /// Scan an escape sequence in a string.
     fn scan_escape_sequence(&mut self) -> Result<String> {
+        // The backslash has been consumed.
+        let start = self.pos - 1;
         if self.is_at_end() {
-            return Err(Error::invalid_escape(
-                "\\",
-                Span::new(self.pos - 1, self.pos),
-            ));
+            return Err(Error::invalid_escape("\\", Span::new(start, self.pos)));
         }
 
         let c = self.advance();
@@ -515,13 +754,13 @@ impl<'a> Lexer<'a> {
                         } else {
                             return Err(Error::invalid_escape(
                                 format!("\\{}", c),
-                                Span::new(self.pos - 2, self.pos),
+                                Span::new(start, self.pos),
                             ));
                         }
                     } else {
                         return Err(Error::invalid_escape(
                             format!("\\{}", c),
-                            Span::new(self.pos - 2, self.pos),
+                            Span::new(start, self.pos),
                         ));
                     }
                 }
@@ -530,7 +769,7 @@ impl<'a> Lexer<'a> {
                 } else {
                     Err(Error::invalid_escape(
                         format!("\\{:o}", value),
-                        Span::new(self.pos - 4, self.pos),
+                        Span::new(start, self.pos),
                     ))
                 }
             }
@@ -538,21 +777,17 @@ impl<'a> Lexer<'a> {
                 // Hex escape: exactly 2 hex digits
                 let mut value = 0u32;
                 for _ in 0..2 {
-                    if let Some(d) = self.peek_char() {
-                        if d.is_ascii_hexdigit() {
+                    match self.peek_char().and_then(|d| d.to_digit(16)) {
+                        Some(digit) => {
                             self.advance();
-                            value = value * 16 + d.to_digit(16).unwrap();
-                        } else {
+                            value = value * 16 + digit;
+                        }
+                        None => {
                             return Err(Error::invalid_escape(
                                 format!("\\{}", c),
-                                Span::new(self.pos - 2, self.pos),
+                                Span::new(start, self.pos),
                             ));
                         }
-                    } else {
-                        return Err(Error::invalid_escape(
-                            format!("\\{}", c),
-                            Span::new(self.pos - 2, self.pos),
-                        ));
                     }
                 }
                 if let Some(ch) = char::from_u32(value) {
@@ -560,7 +795,7 @@ impl<'a> Lexer<'a> {
                 } else {
                     Err(Error::invalid_escape(
                         format!("\\x{:02x}", value),
-                        Span::new(self.pos - 4, self.pos),
+                        Span::new(start, self.pos),
                     ))
                 }
             }
@@ -568,28 +803,21 @@ impl<'a> Lexer<'a> {
                 // Unicode escape: exactly 4 hex digits
                 let mut value = 0u32;
                 for _ in 0..4 {
-                    if let Some(d) = self.peek_char() {
-                        if d.is_ascii_hexdigit() {
+                    match self.peek_char().and_then(|d| d.to_digit(16)) {
+                        Some(digit) => {
                             self.advance();
-                            value = value * 16 + d.to_digit(16).unwrap();
-                        } else {
-                            return Err(Error::invalid_escape(
-                                "\\u",
-                                Span::new(self.pos - 2, self.pos),
-                            ));
+                            value = value * 16 + digit;
+                        }
+                        None => {
+                            return Err(Error::invalid_escape("\\u", Span::new(start, self.pos)));
                         }
-                    } else {
-                        return Err(Error::invalid_escape(
-                            "\\u",
-                            Span::new(self.pos - 2, self.pos),
-                        ));
                     }
                 }
                 // Check for surrogate pairs (D800-DFFF)
                 if (0xD800..=0xDFFF).contains(&value) {
                     return Err(Error::invalid_escape(
                         format!("\\u{:04x}", value),
-                        Span::new(self.pos - 6, self.pos),
+                        Span::new(start, self.pos),
                     ));
                 }
                 if let Some(ch) = char::from_u32(value) {
@@ -597,7 +825,7 @@ impl<'a> Lexer<'a> {
                 } else {
                     Err(Error::invalid_escape(
                         format!("\\u{:04x}", value),
-                        Span::new(self.pos - 6, self.pos),
+                        Span::new(start, self.pos),
                     ))
                 }
             }
@@ -605,28 +833,21 @@ impl<'a> Lexer<'a> {
                 // Unicode escape: exactly 8 hex digits
                 let mut value = 0u32;
                 for _ in 0..8 {
-                    if let Some(d) = self.peek_char() {
-                        if d.is_ascii_hexdigit() {
+                    match self.peek_char().and_then(|d| d.to_digit(16)) {
+                        Some(digit) => {
                             self.advance();
-                            value = value * 16 + d.to_digit(16).unwrap();
-                        } else {
-                            return Err(Error::invalid_escape(
-                                "\\U",
-                                Span::new(self.pos - 2, self.pos),
-                            ));
+                            value = value * 16 + digit;
+                        }
+                        None => {
+                            return Err(Error::invalid_escape("\\U", Span::new(start, self.pos)));
                         }
-                    } else {
-                        return Err(Error::invalid_escape(
-                            "\\U",
-                            Span::new(self.pos - 2, self.pos),
-                        ));
                     }
                 }
                 // Check for surrogate pairs and values > 10FFFF
                 if (0xD800..=0xDFFF).contains(&value) || value > 0x10FFFF {
                     return Err(Error::invalid_escape(
                         format!("\\U{:08x}", value),
-                        Span::new(self.pos - 10, self.pos),
+                        Span::new(start, self.pos),
                     ));
                 }
                 if let Some(ch) = char::from_u32(value) {
@@ -634,24 +855,23 @@ impl<'a> Lexer<'a> {
                 } else {
                     Err(Error::invalid_escape(
                         format!("\\U{:08x}", value),
-                        Span::new(self.pos - 10, self.pos),
+                        Span::new(start, self.pos),
                     ))
                 }
             }
             _ => Err(Error::invalid_escape(
                 format!("\\{}", c),
-                Span::new(self.pos - 2, self.pos),
+                Span::new(start, self.pos),
             )),
         }
     }
 
     /// Scan an escape sequence in bytes literal.
     fn scan_bytes_escape_sequence(&mut self) -> Result<Vec<u8>> {
+        // The backslash has been consumed.
+        let start = self.pos - 1;
         if self.is_at_end() {
-            return Err(Error::invalid_escape(
-                "\\",
-                Span::new(self.pos - 1, self.pos),
-            ));
+            return Err(Error::invalid_escape("\\", Span::new(start, self.pos)));
         }
 
         let c = self.advance();
@@ -669,52 +889,54 @@ impl<'a> Lexer<'a> {
             '\'' => Ok(vec![b'\'']),
             '`' => Ok(vec![b'`']),
             '0'..='7' => {
-                let mut value = c as u8 - b'0';
+                let mut value = u32::from(c as u8 - b'0');
                 for _ in 0..2 {
                     if let Some(d) = self.peek_char() {
                         if ('0'..='7').contains(&d) {
                             self.advance();
-                            value = value * 8 + (d as u8 - b'0');
+                            value = value * 8 + u32::from(d as u8 - b'0');
                         } else {
                             return Err(Error::invalid_escape(
                                 format!("\\{}", c),
-                                Span::new(self.pos - 2, self.pos),
+                                Span::new(start, self.pos),
                             ));
                         }
                     } else {
                         return Err(Error::invalid_escape(
                             format!("\\{}", c),
-                            Span::new(self.pos - 2, self.pos),
+                            Span::new(start, self.pos),
                         ));
                     }
                 }
-                Ok(vec![value])
+                match u8::try_from(value) {
+                    Ok(byte) => Ok(vec![byte]),
+                    Err(_) => Err(Error::invalid_escape(
+                        format!("\\{:o}", value),
+                        Span::new(start, self.pos),
+                    )),
+                }
             }
             'x' | 'X' => {
                 let mut value = 0u8;
                 for _ in 0..2 {
-                    if let Some(d) = self.peek_char() {
-                        if d.is_ascii_hexdigit() {
+                    match self.peek_char().and_then(|d| d.to_digit(16)) {
+                        Some(digit) => {
                             self.advance();
-                            value = value * 16 + d.to_digit(16).unwrap() as u8;
-                        } else {
+                            value = value * 16 + digit as u8;
+                        }
+                        None => {
                             return Err(Error::invalid_escape(
                                 format!("\\{}", c),
-                                Span::new(self.pos - 2, self.pos),
+                                Span::new(start, self.pos),
                             ));
                         }
-                    } else {
-                        return Err(Error::invalid_escape(
-                            format!("\\{}", c),
-                            Span::new(self.pos - 2, self.pos),
-                        ));
                     }
                 }
                 Ok(vec![value])
             }
             _ => Err(Error::invalid_escape(
                 format!("\\{}", c),
-                Span::new(self.pos - 2, self.pos),
+                Span::new(start, self.pos),
             )),
         }
     }
@@ -750,7 +972,50 @@ impl<'a> Lexer<'a> {
         Ok(self.make_token(TokenKind::QuotedIdentifier(value)))
     }
 
+    /// Scan a double-quoted identifier ("identifier"), with `""` for `"`.
+    fn scan_double_quoted_identifier(&mut self) -> Result<Token> {
+        let mut value = String::new();
+
+        loop {
+            if self.is_at_end() {
+                return Err(Error::unterminated_string(Span::new(self.start, self.pos)));
+            }
+
+            let c = self.advance();
+
+            if c == '"' {
+                if !self.matches('"') {
+                    break;
+                }
+                value.push('"');
+            } else {
+                value.push(c);
+            }
+        }
+
+        if value.is_empty() {
+            return Err(Error::invalid_syntax(
+                "empty quoted identifier",
+                Span::new(self.start, self.pos),
+            ));
+        }
+
+        Ok(self.make_token(TokenKind::QuotedIdentifier(value)))
+    }
+
     /// Scan a number literal.
+    /// Scan the digits of a `$1`-style parameter; the `$` is consumed.
+    fn scan_numbered_parameter(&mut self) -> Result<Token> {
+        while self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
+            self.advance();
+        }
+        let text = &self.input[self.start..self.pos];
+        match text[1..].parse::<u32>() {
+            Ok(n) if n > 0 => Ok(self.make_token(TokenKind::Parameter(n))),
+            _ => Err(Error::invalid_number(text, Span::new(self.start, self.pos))),
+        }
+    }
+
     fn scan_number(&mut self) -> Result<Token> {
         // Check for hex literal
         if self.check('0') && self.check_next_char(|c| c == 'x' || c == 'X') {
@@ -858,8 +1123,13 @@ impl<'a> Lexer<'a> {
         self.input[self.pos..].chars().next()
     }
 
+    /// Consume the next character. At the end of the input this returns
+    /// `'\0'` without moving; callers check [`is_at_end`](Self::is_at_end)
+    /// first.
     fn advance(&mut self) -> char {
-        let c = self.input[self.pos..].chars().next().unwrap();
+        let Some(c) = self.peek_char() else {
+            return '\0';
+        };
         self.pos += c.len_utf8();
         c
     }
@@ -996,6 +1266,73 @@ line'''"#,
         );
     }
 
+    #[test]
+    fn test_numbered_parameters() {
+        assert_eq!(
+            tokenize("$1 $23 $ x"),
+            vec![
+                TokenKind::Parameter(1),
+                TokenKind::Parameter(23),
+                TokenKind::Dollar,
+                TokenKind::Identifier("x".to_string()),
+            ]
+        );
+        assert!(Lexer::new("$0").next_token_result().is_err());
+    }
+
+    #[test]
+    fn test_system_variable_prefix() {
+        assert_eq!(
+            tokenize("@@version @x @{"),
+            vec![
+                TokenKind::DoubleAt,
+                TokenKind::Identifier("version".to_string()),
+                TokenKind::At,
+                TokenKind::Identifier("x".to_string()),
+                TokenKind::At,
+                TokenKind::LeftBrace,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dollar_quoted_strings() {
+        assert_eq!(
+            tokenize(r"$$a\n'b'$$ $tag$ $$ $x$ $tag$ $1 $_1$c$_1$"),
+            vec![
+                TokenKind::String(r"a\n'b'".to_string()),
+                TokenKind::String(" $$ $x$ ".to_string()),
+                TokenKind::Parameter(1),
+                TokenKind::String("c".to_string()),
+            ]
+        );
+        assert_eq!(
+            tokenize("$x"),
+            vec![TokenKind::Dollar, TokenKind::Identifier("x".to_string())]
+        );
+
+        let sql = "SELECT $fn$ body $Fn$";
+        let err = Lexer::new(sql).tokenize().unwrap_err();
+        let span = err.span().unwrap();
+        assert_eq!(
+            &sql[span.start..span.end],
+            "$fn$",
+            "span of the opening tag"
+        );
+    }
+
+    #[test]
+    fn test_escape_strings() {
+        assert_eq!(
+            tokenize(r"E'a\nb' e'\x41' E"),
+            vec![
+                TokenKind::String("a\nb".to_string()),
+                TokenKind::String("A".to_string()),
+                TokenKind::Identifier("E".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_comments() {
         let tokens = tokenize("SELECT -- comment\n* /* block */ FROM");
@@ -1021,6 +1358,38 @@ line'''"#,
         );
     }
 
+    #[test]
+    fn test_double_quote_identifiers() {
+        let sql = r#""a ""b"" \n" `c` "SELECT" 'd' b"e" """f""""#;
+        let mut lexer = Lexer::new(sql).with_double_quote_identifiers(true);
+        let kinds: Vec<TokenKind> = lexer
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::QuotedIdentifier(r#"a "b" \n"#.to_string()),
+                TokenKind::QuotedIdentifier("c".to_string()),
+                TokenKind::QuotedIdentifier("SELECT".to_string()),
+                TokenKind::String("d".to_string()),
+                TokenKind::Bytes(b"e".to_vec()),
+                TokenKind::QuotedIdentifier(r#""f""#.to_string()),
+            ]
+        );
+
+        let mut lexer = Lexer::new(r#""" "x"#).with_double_quote_identifiers(true);
+        assert!(lexer.next_token_result().is_err(), "empty identifier");
+        let mut lexer = Lexer::new(r#""x"#).with_double_quote_identifiers(true);
+        assert_eq!(
+            lexer.next_token_result().unwrap_err().kind,
+            crate::error::ErrorKind::UnterminatedString
+        );
+        assert_eq!(tokenize(r#""x""#), vec![TokenKind::String("x".to_string())]);
+    }
+
     #[test]
     fn test_escape_sequences() {
         let tokens = tokenize(r"'hello\nworld' '\x41\x42'");
@@ -1076,4 +1445,57 @@ line'''"#,
         assert!(matches!(tokens[0], TokenKind::Keyword(Keyword::Select)));
         assert!(matches!(tokens[tokens.len() - 1], TokenKind::Integer(10)));
     }
+
+    #[test]
+    fn test_keep_comments() {
+        let sql = "SELECT a, -- first\r\n/* second /* nested */ */ b # third\nFROM t";
+        let tokens = Lexer::with_comments(sql).tokenize().unwrap();
+        let comments: Vec<(&TokenKind, &str)> = tokens
+            .iter()
+            .filter(|t| t.is_comment())
+            .map(|t| (&t.kind, &sql[t.span.start..t.span.end]))
+            .collect();
+        assert_eq!(
+            comments,
+            [
+                (&TokenKind::LineComment(" first".into()), "-- first\r"),
+                (
+                    &TokenKind::BlockComment(" second /* nested */ ".into()),
+                    "/* second /* nested */ */"
+                ),
+                (&TokenKind::LineComment(" third".into()), "# third"),
+            ]
+        );
+        assert_eq!(tokens.len(), 9);
+
+        // The parser sees the stream without its comments.
+        let stmts = crate::Parser::from_tokens(tokens, sql).parse().unwrap();
+        assert_eq!(stmts[0].to_string(), "SELECT a, b FROM t");
+        assert!(Lexer::with_comments("/* open").tokenize().is_err());
+    }
+
+    #[test]
+    fn test_tokenize_with_trivia() {
+        let sql = "-- header\nSELECT id, -- key\n  name /* label */\nFROM t -- source\n-- end";
+        let tokens = Lexer::new(sql).tokenize_with_trivia().unwrap();
+        let texts = |trivia: &[Token]| trivia.iter().map(|t| t.text.clone()).collect::<Vec<_>>();
+        let summary: Vec<(String, Vec<String>, Vec<String>)> = tokens
+            .iter()
+            .map(|t| (t.token.text.clone(), texts(&t.leading), texts(&t.trailing)))
+            .collect();
+        let none = Vec::<String>::new;
+        assert_eq!(
+            summary,
+            [
+                ("SELECT".into(), vec!["-- header".to_string()], none()),
+                ("id".into(), none(), none()),
+                (",".into(), none(), vec!["-- key".to_string()]),
+                ("name".into(), none(), vec!["/* label */".to_string()]),
+                ("FROM".into(), none(), none()),
+                ("t".into(), none(), vec!["-- source".to_string()]),
+                ("".into(), vec!["-- end".to_string()], none()),
+            ]
+        );
+        assert!(tokens.last().unwrap().token.is_eof());
+    }
 }
@@ -27,6 +27,11 @@ impl Token {
         }
     }
 
+    /// Create a token that has no position in the source text.
+    pub fn synthetic(kind: TokenKind, text: impl Into<String>) -> Self {
+        Self::new(kind, Span::synthetic(), text)
+    }
+
     /// Check if this token is a specific keyword.
     pub fn is_keyword(&self, kw: Keyword) -> bool {
         matches!(&self.kind, TokenKind::Keyword(k) if *k == kw)
@@ -60,6 +65,27 @@ impl Token {
     pub fn is_eof(&self) -> bool {
         matches!(&self.kind, TokenKind::Eof)
     }
+
+    /// Check if this is a line or block comment.
+    pub fn is_comment(&self) -> bool {
+        matches!(
+            &self.kind,
+            TokenKind::LineComment(_) | TokenKind::BlockComment(_)
+        )
+    }
+}
+
+/// A token with the comments attached to it, from
+/// [`Lexer::tokenize_with_trivia`](super::Lexer::tokenize_with_trivia).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenWithTrivia {
+    /// The token.
+    pub token: Token,
+    /// Comments between the previous token's trailing comments and this
+    /// token, in order.
+    pub leading: Vec<Token>,
+    /// Comments after this token on the same line, in order.
+    pub trailing: Vec<Token>,
 }
 
 impl fmt::Display for Token {
@@ -124,10 +150,22 @@ pub enum TokenKind {
     Arrow,        // ->
     FatArrow,     // =>
     At,           // @
+    DoubleAt,     // @@
     Question,     // ?
     Hash,         // #
     Dollar,       // $
-    Backslash,    // \
+    /// Numbered parameter placeholder: `$1`, `$2`, ...
+    Parameter(u32),
+    Backslash, // \
+
+    // Comments, produced only by lexers that keep them
+    /// `-- text` or `# text`, holding the text after the marker.
+    LineComment(String),
+    /// `/* text */`, holding the text between the delimiters.
+    BlockComment(String),
+    /// `/*+ text */` right after SELECT, INSERT, UPDATE, or DELETE,
+    /// holding the text between the delimiters.
+    Hint(String),
 
     // Special tokens
     Eof,
@@ -181,10 +219,14 @@ impl fmt::Display for TokenKind {
             TokenKind::Arrow => write!(f, "->"),
             TokenKind::FatArrow => write!(f, "=>"),
             TokenKind::At => write!(f, "@"),
+            TokenKind::DoubleAt => write!(f, "@@"),
             TokenKind::Question => write!(f, "?"),
             TokenKind::Hash => write!(f, "#"),
             TokenKind::Dollar => write!(f, "$"),
+            TokenKind::Parameter(n) => write!(f, "parameter ${}", n),
             TokenKind::Backslash => write!(f, "\\"),
+            TokenKind::LineComment(_) | TokenKind::BlockComment(_) => write!(f, "comment"),
+            TokenKind::Hint(_) => write!(f, "optimizer hint"),
             TokenKind::Eof => write!(f, "end of input"),
             TokenKind::Error(msg) => write!(f, "error: {}", msg),
         }
@@ -312,6 +354,7 @@ pub enum Keyword {
     Assertion,
     Avg,
     Begin,
+    Bernoulli,
     Bigint,
     Binary,
     Bit,
@@ -409,8 +452,10 @@ pub enum Keyword {
     Hour,
     Hours,
     Identity,
+    Ilike,
     Immediate,
     Import,
+    Include,
     Index,
     Indicator,
     Inout,
@@ -480,6 +525,7 @@ pub enum Keyword {
     Operator,
     Option,
     Options,
+    Others,
     Out,
     Output,
     Overlaps,
@@ -522,8 +568,10 @@ pub enum Keyword {
     Release,
     Rename,
     Repeat,
+    Repeatable,
     Replace,
     Replica,
+    Reservoir,
     Restrict,
     Return,
     Returning,
@@ -590,6 +638,7 @@ pub enum Keyword {
     Temp,
     Temporary,
     Text,
+    Ties,
     Time,
     Timestamp,
     Timezone,
@@ -882,6 +931,7 @@ lazy_static_keyword_map! {
         "ASSERTION" => Assertion,
         "AVG" => Avg,
         "BEGIN" => Begin,
+        "BERNOULLI" => Bernoulli,
         "BIGINT" => Bigint,
         "BINARY" => Binary,
         "BIT" => Bit,
@@ -979,8 +1029,10 @@ lazy_static_keyword_map! {
         "HOUR" => Hour,
         "HOURS" => Hours,
         "IDENTITY" => Identity,
+        "ILIKE" => Ilike,
         "IMMEDIATE" => Immediate,
         "IMPORT" => Import,
+        "INCLUDE" => Include,
         "INDEX" => Index,
         "INDICATOR" => Indicator,
         "INOUT" => Inout,
@@ -1050,6 +1102,7 @@ lazy_static_keyword_map! {
         "OPERATOR" => Operator,
         "OPTION" => Option,
         "OPTIONS" => Options,
+        "OTHERS" => Others,
         "OUT" => Out,
         "OUTPUT" => Output,
         "OVERLAPS" => Overlaps,
@@ -1092,8 +1145,10 @@ lazy_static_keyword_map! {
         "RELEASE" => Release,
         "RENAME" => Rename,
         "REPEAT" => Repeat,
+        "REPEATABLE" => Repeatable,
         "REPLACE" => Replace,
         "REPLICA" => Replica,
+        "RESERVOIR" => Reservoir,
         "RESTRICT" => Restrict,
         "RETURN" => Return,
         "RETURNING" => Returning,
@@ -1159,6 +1214,7 @@ lazy_static_keyword_map! {
         "TEMP" => Temp,
         "TEMPORARY" => Temporary,
         "TEXT" => Text,
+        "TIES" => Ties,
         "TIME" => Time,
         "TIMESTAMP" => Timestamp,
         "TIMEZONE" => Timezone,
@@ -38,8 +38,8 @@
 //!     .add_scalar_function("MY_HASH", SqlType::Int64)
 //!     .add_table("users", |t| {
 //!         t.primary_key("id", SqlType::Int64)
-//!          .column("name", SqlType::Varchar)
-//!          .column("email", SqlType::Varchar)
+//!          .column("name", SqlType::Varchar(None))
+//!          .column("email", SqlType::Varchar(None))
 //!     })
 //!     .build();
 //! ```
@@ -55,19 +55,34 @@
 //! | `SqlType::Int64` | BIGINT |
 //! | `SqlType::Float32` | REAL |
 //! | `SqlType::Float64` | DOUBLE PRECISION |
-//! | `SqlType::Varchar` | VARCHAR |
-//! | `SqlType::Varbinary` | VARBINARY |
+//! | `SqlType::Varchar(None)` | VARCHAR |
+//! | `SqlType::Varchar(Some(10))` | VARCHAR(10) |
+//! | `SqlType::Varbinary(None)` | VARBINARY |
 
+pub mod analysis;
 pub mod analyzer;
+#[cfg(test)]
+mod api_manifest;
 pub mod ast;
+pub mod builder;
 pub mod catalog;
 pub mod error;
+pub mod eval;
+pub mod format;
+pub mod json;
 pub mod lexer;
+pub mod lineage;
 pub mod parser;
+pub mod rewrite;
+pub mod session;
+#[cfg(any(test, feature = "testing"))]
+pub mod testgen;
 pub mod types;
+pub mod wire;
 
 // Re-export main types for convenience
-pub use analyzer::{AnalyzedQuery, Analyzer, AnalyzerError, OutputColumn};
+pub use analysis::{references, StatementReferences};
+pub use analyzer::{AnalyzedQuery, Analyzer, AnalyzerError, OutputColumn, StatementAnalysis};
 pub use ast::*;
 pub use catalog::{
     Catalog, CatalogBuilder, ColumnSchema, FunctionSignature, MemoryCatalog, TableBuilder,
@@ -75,5 +90,6 @@ pub use catalog::{
 };
 pub use error::{Error, Result};
 pub use lexer::{Lexer, Token, TokenKind};
-pub use parser::Parser;
+pub use parser::{is_complete_statement, Parser};
+pub use rewrite::{fingerprint, normalize};
 pub use types::{SqlType, Value};
@@ -0,0 +1,786 @@
+//! Column-level lineage: which table columns feed each output column.
+//!
+//! [`extract`] analyzes a statement and follows each output column of its
+//! query back to the catalog table columns it is computed from, through
+//! CTEs, subqueries, and set operations. Each source is a
+//! [`Dependency`] whose [`DependencyKind`] says whether the column is copied
+//! unchanged, computed from the source, or aggregated over it. Columns
+//! that decide which rows are returned, in WHERE, JOIN, GROUP BY, HAVING,
+//! and QUALIFY clauses, are reported separately as
+//! [`LineageGraph::filter_dependencies`].
+//!
+//! ```
+//! use vibesql::catalog::CatalogBuilder;
+//! use vibesql::lineage::{self, DependencyKind, TableColumn};
+//! use vibesql::{Parser, SqlType};
+//!
+//! let catalog = CatalogBuilder::new()
+//!     .with_builtins()
+//!     .add_table("users", |t| t.column("id", SqlType::Int64).column("name", SqlType::Varchar(None)))
+//!     .add_table("orders", |t| t.column("user_id", SqlType::Int64).column("amount", SqlType::Float64))
+//!     .build();
+//! let stmt = Parser::new(
+//!     "SELECT u.name, SUM(o.amount) total FROM users u JOIN orders o ON u.id = o.user_id \
+//!      GROUP BY u.name",
+//! )
+//! .parse_statement()
+//! .unwrap();
+//!
+//! let graph = lineage::extract(&stmt, &catalog).unwrap();
+//! let total = graph.column("total").unwrap();
+//! let source = total.sources.iter().next().unwrap();
+//! assert_eq!(source.column, TableColumn::new("orders", "amount"));
+//! assert_eq!(source.kind, DependencyKind::Aggregated);
+//! assert!(graph.filter_dependencies.contains(&TableColumn::new("users", "id")));
+//! ```
+//!
+//! Table functions and the outputs of PIVOT and UNPIVOT have no tracked
+//! sources.
+
+use crate::analyzer::{Analyzer, AnalyzerOptions, OutputColumn};
+use crate::ast::visit::{walk_binary_chain, walk_expr, Visitor};
+use crate::ast::*;
+use crate::catalog::Catalog;
+use crate::error::Result;
+use std::collections::BTreeSet;
+
+/// A column of a catalog table, or of the target of a statement.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TableColumn {
+    /// The table's name, as stored in the catalog.
+    pub table: String,
+    pub column: String,
+}
+
+impl TableColumn {
+    /// Create a table column reference.
+    pub fn new(table: impl Into<String>, column: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            column: column.into(),
+        }
+    }
+}
+
+/// How an output column depends on a source column.
+///
+/// The kinds are ordered: a dependency through several steps has the
+/// greatest kind of its steps, so a copy of an aggregate is aggregated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DependencyKind {
+    /// The output column is the source column, unchanged.
+    Direct,
+    /// The output column is computed from the source column.
+    Derived,
+    /// The output column aggregates the source column over several rows.
+    Aggregated,
+}
+
+/// A source column an output column depends on.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dependency {
+    pub column: TableColumn,
+    pub kind: DependencyKind,
+}
+
+/// The lineage of one output column.
+#[derive(Debug, Clone)]
+pub struct ColumnLineage {
+    /// The output column, as the analyzer reports it.
+    pub column: OutputColumn,
+    /// The table column the output column is written to: a column of the
+    /// target of `INSERT ... SELECT`, `CREATE TABLE ... AS`, or
+    /// `CREATE VIEW`.
+    pub target: Option<TableColumn>,
+    /// The source columns the output column is computed from. A source
+    /// reached in different ways appears once for each kind.
+    pub sources: BTreeSet<Dependency>,
+}
+
+/// The lineage of a statement's output columns, from [`extract`].
+#[derive(Debug, Clone, Default)]
+pub struct LineageGraph {
+    /// Each output column of the statement's query, in order.
+    pub columns: Vec<ColumnLineage>,
+    /// The source columns read by the WHERE, JOIN, GROUP BY, HAVING, and
+    /// QUALIFY clauses of the query and its subqueries.
+    pub filter_dependencies: BTreeSet<TableColumn>,
+}
+
+impl LineageGraph {
+    /// The lineage of the first output column named `name`.
+    pub fn column(&self, name: &str) -> Option<&ColumnLineage> {
+        self.columns.iter().find(|c| c.column.name == name)
+    }
+}
+
+/// Extract the column lineage of a statement.
+///
+/// The statement is analyzed against `catalog` first, and analysis errors
+/// are returned. Queries, `INSERT ... SELECT`, `CREATE TABLE ... AS`, and
+/// `CREATE VIEW` have lineage; other statements give an empty graph.
+pub fn extract<C: Catalog>(stmt: &Statement, catalog: &C) -> Result<LineageGraph> {
+    let options = AnalyzerOptions::default();
+    let mut analyzer = Analyzer::with_catalog_and_options(catalog, options.clone());
+    analyzer.analyze(stmt)?;
+
+    // The query and, when it is written somewhere, the target's name and
+    // column names.
+    let (query, target) = match &stmt.kind {
+        StatementKind::Query(query) => (query, None),
+        StatementKind::Insert(InsertStatement {
+            table,
+            columns,
+            source: InsertSource::Query(query),
+            ..
+        }) => {
+            let parts: Vec<String> = table.parts.iter().map(|i| i.value.clone()).collect();
+            let schema = catalog.resolve_table(&parts)?;
+            let names = if columns.is_empty() {
+                schema
+                    .iter()
+                    .flat_map(|s| s.columns.iter().map(|c| c.name.clone()))
+                    .collect()
+            } else {
+                columns.iter().map(|c| c.value.clone()).collect()
+            };
+            let name = schema.map_or_else(|| table.to_string(), |s| s.name.clone());
+            (query, Some((name, names)))
+        }
+        StatementKind::CreateTable(CreateTableStatement {
+            name,
+            as_query: Some(query),
+            ..
+        }) => (query, Some((name.to_string(), Vec::new()))),
+        StatementKind::CreateView(view) => {
+            let columns = view.columns.iter().map(|c| c.value.clone()).collect();
+            (&view.query, Some((view.name.to_string(), columns)))
+        }
+        _ => return Ok(LineageGraph::default()),
+    };
+    let analyzed = analyzer.analyze_query_result(query)?;
+
+    let mut extractor = Extractor {
+        catalog,
+        matching: options.identifier_matching,
+        ctes: Vec::new(),
+        filters: BTreeSet::new(),
+    };
+    let lineage = extractor.query(query, None);
+    let columns = analyzed
+        .columns
+        .into_iter()
+        .zip(lineage)
+        .enumerate()
+        .map(|(i, (column, (_, sources)))| {
+            let target = target.as_ref().map(|(table, names)| {
+                let name = names.get(i).unwrap_or(&column.name);
+                TableColumn::new(table.clone(), name.clone())
+            });
+            ColumnLineage {
+                column,
+                target,
+                sources,
+            }
+        })
+        .collect();
+    Ok(LineageGraph {
+        columns,
+        filter_dependencies: extractor.filters,
+    })
+}
+
+/// The sources of one column.
+type Sources = BTreeSet<Dependency>;
+
+/// The columns of a query or FROM item, by name, with their sources.
+type Columns = Vec<(String, Sources)>;
+
+/// Sources reached through a step of kind `kind`.
+fn through(sources: &Sources, kind: DependencyKind) -> impl Iterator<Item = Dependency> + '_ {
+    sources.iter().map(move |d| Dependency {
+        column: d.column.clone(),
+        kind: d.kind.max(kind),
+    })
+}
+
+/// The FROM items of a SELECT, chained to those of enclosing queries.
+struct Frame<'p> {
+    items: Vec<(String, Columns)>,
+    /// Columns coalesced from both inputs of USING and NATURAL joins, which
+    /// unqualified names find first.
+    join_columns: Columns,
+    outer: Option<&'p Frame<'p>>,
+}
+
+impl Frame<'_> {
+    fn resolve(&self, matching: IdentifierMatching, parts: &[Ident]) -> Option<&Sources> {
+        let qualified = |table: &Ident, column: &Ident| {
+            self.items
+                .iter()
+                .find(|(alias, _)| matching.matches(alias, &table.value))
+                .and_then(|(_, columns)| find_column(matching, columns, &column.value))
+        };
+        // `t.c` or `schema.t.c`, else a column possibly followed by struct
+        // fields.
+        let found = match parts {
+            [table, column, ..] => qualified(table, column).or_else(|| match parts {
+                [_, table, column] => qualified(table, column),
+                _ => None,
+            }),
+            _ => None,
+        };
+        let found = found.or_else(|| {
+            let column = &parts.first()?.value;
+            find_column(matching, &self.join_columns, column).or_else(|| {
+                self.items
+                    .iter()
+                    .find_map(|(_, columns)| find_column(matching, columns, column))
+            })
+        });
+        found.or_else(|| self.outer?.resolve(matching, parts))
+    }
+}
+
+fn find_column<'c>(
+    matching: IdentifierMatching,
+    columns: &'c Columns,
+    name: &str,
+) -> Option<&'c Sources> {
+    columns
+        .iter()
+        .find(|(column, _)| matching.matches(column, name))
+        .map(|(_, sources)| sources)
+}
+
+struct Extractor<'a, C: Catalog> {
+    catalog: &'a C,
+    matching: IdentifierMatching,
+    /// CTEs in scope, innermost last.
+    ctes: Vec<(String, Columns)>,
+    filters: BTreeSet<TableColumn>,
+}
+
+impl<C: Catalog> Extractor<'_, C> {
+    fn query(&mut self, query: &Query, outer: Option<&Frame>) -> Columns {
+        let defined = self.ctes.len();
+        if let Some(with) = &query.with {
+            for cte in &with.ctes {
+                // A recursive CTE's references to itself resolve on a
+                // second pass, to the columns found by the first.
+                let mut columns = self.query(&cte.query, outer);
+                if with.recursive {
+                    self.ctes.push((cte.name.value.clone(), columns));
+                    columns = self.query(&cte.query, outer);
+                    self.ctes.pop();
+                }
+                for ((name, _), alias) in columns.iter_mut().zip(&cte.columns) {
+                    *name = alias.value.clone();
+                }
+                self.ctes.push((cte.name.value.clone(), columns));
+            }
+        }
+        let columns = self.body(&query.body, outer);
+        self.ctes.truncate(defined);
+        columns
+    }
+
+    fn body(&mut self, body: &QueryBody, outer: Option<&Frame>) -> Columns {
+        match body {
+            QueryBody::Select(select) => self.select(select, outer),
+            QueryBody::SetOperation { left, right, .. } => {
+                let mut columns = self.body(left, outer);
+                let right = self.body(right, outer);
+                for ((_, sources), (_, other)) in columns.iter_mut().zip(right) {
+                    sources.extend(other);
+                }
+                columns
+            }
+            QueryBody::Parenthesized(query) => self.query(query, outer),
+        }
+    }
+
+    fn select(&mut self, select: &Select, outer: Option<&Frame>) -> Columns {
+        let mut frame = Frame {
+            items: Vec::new(),
+            join_columns: Vec::new(),
+            outer,
+        };
+        let mut from_columns = Vec::new();
+        for table_ref in select.from.iter().flat_map(|from| &from.tables) {
+            from_columns.extend(self.table_ref(table_ref, &mut frame));
+        }
+
+        let mut filters: Vec<&Expr> = Vec::new();
+        filters.extend(select.where_clause.as_deref());
+        for item in select.group_by.iter().flat_map(|g| &g.items) {
+            match item {
+                GroupByItem::Expr(expr) => filters.push(expr),
+                GroupByItem::Rollup(exprs) | GroupByItem::Cube(exprs) => {
+                    filters.extend(exprs.iter().map(|e| &**e))
+                }
+                GroupByItem::GroupingSets(sets) => {
+                    filters.extend(sets.iter().flatten().map(|e| &**e))
+                }
+            }
+        }
+        filters.extend(select.having.as_deref());
+        filters.extend(select.qualify.as_deref());
+        for expr in filters {
+            self.filter(expr, &frame);
+        }
+
+        let mut columns = Columns::new();
+        for item in &select.projection {
+            match item {
+                SelectItem::Expr { expr, alias } => {
+                    let name = match (alias, &expr.kind) {
+                        (Some(alias), _) => Some(alias),
+                        (None, ExprKind::Identifier(ident)) => Some(ident),
+                        (None, ExprKind::CompoundIdentifier(parts)) => parts.last(),
+                        (None, ExprKind::Function(call)) => call.name.parts.last(),
+                        (None, ExprKind::Aggregate(call)) => call.function.name.parts.last(),
+                        (None, ExprKind::WindowFunction(call)) => call.function.name.parts.last(),
+                        _ => None,
+                    };
+                    let name =
+                        name.map_or_else(|| format!("_col{}", columns.len()), |i| i.value.clone());
+                    columns.push((name, self.expr(expr, &frame)));
+                }
+                SelectItem::Wildcard => columns.extend(from_columns.iter().cloned()),
+                SelectItem::QualifiedWildcard { qualifier } => {
+                    columns.extend(self.qualified_columns(&frame, qualifier));
+                }
+                SelectItem::WildcardExcept { qualifier, except } => {
+                    let source = match qualifier {
+                        Some(qualifier) => self.qualified_columns(&frame, qualifier),
+                        None => from_columns.clone(),
+                    };
+                    columns.extend(source.into_iter().filter(|(name, _)| {
+                        !except.iter().any(|e| self.matching.matches(name, &e.value))
+                    }));
+                }
+                SelectItem::WildcardReplace { qualifier, replace } => {
+                    let source = match qualifier {
+                        Some(qualifier) => self.qualified_columns(&frame, qualifier),
+                        None => from_columns.clone(),
+                    };
+                    for (name, sources) in source {
+                        let replacement = replace
+                            .iter()
+                            .find(|(_, ident)| self.matching.matches(&name, &ident.value));
+                        let sources = match replacement {
+                            Some((expr, _)) => self.expr(expr, &frame),
+                            None => sources,
+                        };
+                        columns.push((name, sources));
+                    }
+                }
+            }
+        }
+        columns
+    }
+
+    fn qualified_columns(&self, frame: &Frame, qualifier: &ObjectName) -> Columns {
+        let Some(table) = qualifier.parts.last() else {
+            return Columns::new();
+        };
+        frame
+            .items
+            .iter()
+            .find(|(alias, _)| self.matching.matches(alias, &table.value))
+            .map(|(_, columns)| columns.clone())
+            .unwrap_or_default()
+    }
+
+    /// Add a FROM item, or the items of a join, to `frame`, returning the
+    /// columns `SELECT *` expands it to.
+    fn table_ref(&mut self, table_ref: &TableRef, frame: &mut Frame) -> Columns {
+        let (alias_name, mut columns, alias) = match &table_ref.kind {
+            TableRefKind::Table { name, alias, .. } => {
+                let table = name.parts.last().map_or("", |i| i.value.as_str());
+                let cte = match name.parts.as_slice() {
+                    [_] => self
+                        .ctes
+                        .iter()
+                        .rev()
+                        .find(|(cte, _)| self.matching.matches(cte, table)),
+                    _ => None,
+                };
+                let columns = match cte {
+                    Some((_, columns)) => columns.clone(),
+                    None => self.table_columns(name),
+                };
+                (table.to_string(), columns, alias)
+            }
+            TableRefKind::Subquery {
+                query,
+                alias,
+                lateral,
+                ..
+            } => {
+                let columns = if *lateral {
+                    self.query(query, Some(frame))
+                } else {
+                    self.query(query, frame.outer)
+                };
+                ("_subquery".to_string(), columns, alias)
+            }
+            TableRefKind::Unnest { expr, alias, .. } => {
+                let sources = through(&self.expr(expr, frame), DependencyKind::Derived).collect();
+                let name = alias.as_ref().map_or("value", |a| a.name.value.as_str());
+                (
+                    "_unnest".to_string(),
+                    vec![(name.to_string(), sources)],
+                    alias,
+                )
+            }
+            TableRefKind::Join {
+                left,
+                right,
+                join_type,
+                condition,
+            } => return self.join(left, right, *join_type, condition.as_ref(), frame),
+            TableRefKind::Parenthesized(inner) => return self.table_ref(inner, frame),
+            TableRefKind::TableFunction { .. }
+            | TableRefKind::Pivot { .. }
+            | TableRefKind::Unpivot { .. } => return Columns::new(),
+        };
+        let alias_name = alias.as_ref().map_or(alias_name, |a| a.name.value.clone());
+        for ((name, _), renamed) in columns
+            .iter_mut()
+            .zip(alias.iter().flat_map(|a| &a.columns))
+        {
+            *name = renamed.value.clone();
+        }
+        frame.items.push((alias_name, columns.clone()));
+        columns
+    }
+
+    fn join(
+        &mut self,
+        left: &TableRef,
+        right: &TableRef,
+        join_type: JoinType,
+        condition: Option<&JoinCondition>,
+        frame: &mut Frame,
+    ) -> Columns {
+        let left = self.table_ref(left, frame);
+        let right = self.table_ref(right, frame);
+        let names: Vec<String> = match condition {
+            Some(JoinCondition::On(expr)) => {
+                self.filter(expr, frame);
+                Vec::new()
+            }
+            Some(JoinCondition::Using(idents)) => idents.iter().map(|i| i.value.clone()).collect(),
+            None if join_type == JoinType::Natural => left
+                .iter()
+                .filter(|(l, _)| right.iter().any(|(r, _)| self.matching.matches(l, r)))
+                .map(|(name, _)| name.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+        if names.is_empty() {
+            let mut columns = left;
+            columns.extend(right);
+            return columns;
+        }
+
+        // The coalesced columns come first, then the rest of each side, as
+        // with the analyzer's default `UsingWildcardMode::Coalesced`.
+        let is_join_column = |name: &str| names.iter().any(|n| self.matching.matches(n, name));
+        let mut columns = Columns::new();
+        for name in &names {
+            let mut sources = Sources::new();
+            for (_, side) in left
+                .iter()
+                .chain(&right)
+                .filter(|(c, _)| self.matching.matches(c, name))
+            {
+                sources.extend(through(side, DependencyKind::Derived));
+                self.filters.extend(side.iter().map(|d| d.column.clone()));
+            }
+            columns.push((name.clone(), sources));
+        }
+        frame.join_columns.extend(columns.iter().cloned());
+        columns.extend(left.into_iter().filter(|(c, _)| !is_join_column(c)));
+        columns.extend(right.into_iter().filter(|(c, _)| !is_join_column(c)));
+        columns
+    }
+
+    /// The columns of a catalog table, each its own direct source.
+    fn table_columns(&self, name: &ObjectName) -> Columns {
+        let Ok(Some(schema)) = self.catalog.resolve_table_ident(&name.parts) else {
+            return Columns::new();
+        };
+        schema
+            .columns
+            .iter()
+            .map(|column| {
+                let source = Dependency {
+                    column: TableColumn::new(schema.name.clone(), column.name.clone()),
+                    kind: DependencyKind::Direct,
+                };
+                (column.name.clone(), BTreeSet::from([source]))
+            })
+            .collect()
+    }
+
+    /// The sources of a select item. A plain column reference copies its
+    /// column; any other expression is derived from the columns it reads.
+    fn expr(&mut self, expr: &Expr, frame: &Frame) -> Sources {
+        let mut inner = expr;
+        while let ExprKind::Parenthesized(e) = &inner.kind {
+            inner = e;
+        }
+        let kind = match inner.kind {
+            ExprKind::Identifier(_) | ExprKind::CompoundIdentifier(_) => DependencyKind::Direct,
+            _ => DependencyKind::Derived,
+        };
+        let mut collector = SourceCollector {
+            extractor: self,
+            frame,
+            kind,
+            sources: Sources::new(),
+        };
+        collector.visit_expr(expr);
+        collector.sources
+    }
+
+    /// Record the columns a filtering expression reads.
+    fn filter(&mut self, expr: &Expr, frame: &Frame) {
+        let sources = self.expr(expr, frame);
+        self.filters.extend(sources.into_iter().map(|d| d.column));
+    }
+
+    fn is_aggregate(&self, call: &FunctionCall) -> bool {
+        let name: Vec<String> = call.name.parts.iter().map(|i| i.value.clone()).collect();
+        matches!(self.catalog.resolve_function(&name), Ok(Some(f)) if f.is_aggregate)
+    }
+}
+
+/// Collects the sources of the columns an expression reads.
+struct SourceCollector<'x, 'a, 'p, C: Catalog> {
+    extractor: &'x mut Extractor<'a, C>,
+    frame: &'x Frame<'p>,
+    /// The kind of the step from the expression to the columns it reads:
+    /// aggregated inside an aggregate call.
+    kind: DependencyKind,
+    sources: Sources,
+}
+
+impl<C: Catalog> SourceCollector<'_, '_, '_, C> {
+    fn add(&mut self, sources: &Sources, kind: DependencyKind) {
+        let kind = kind.max(self.kind);
+        self.sources.extend(through(sources, kind));
+    }
+
+    fn visit_aggregated(&mut self, expr: &Expr) {
+        let kind = std::mem::replace(&mut self.kind, DependencyKind::Aggregated);
+        walk_expr(self, expr);
+        self.kind = kind;
+    }
+}
+
+impl<C: Catalog> Visitor<'_> for SourceCollector<'_, '_, '_, C> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Identifier(ident) => {
+                let parts = std::slice::from_ref(ident);
+                if let Some(sources) = self.frame.resolve(self.extractor.matching, parts) {
+                    self.add(&sources.clone(), DependencyKind::Direct);
+                }
+            }
+            ExprKind::CompoundIdentifier(parts) => {
+                if let Some(sources) = self.frame.resolve(self.extractor.matching, parts) {
+                    self.add(&sources.clone(), DependencyKind::Direct);
+                }
+            }
+            ExprKind::Aggregate(_) => self.visit_aggregated(expr),
+            ExprKind::Function(call) if self.extractor.is_aggregate(call) => {
+                self.visit_aggregated(expr)
+            }
+            ExprKind::WindowFunction(call) if self.extractor.is_aggregate(&call.function) => {
+                self.visit_aggregated(expr)
+            }
+            _ => walk_binary_chain(self, expr, |_, _| {}),
+        }
+    }
+
+    /// A subquery's value is derived from its output columns. Its own
+    /// filters are recorded as it is extracted.
+    fn visit_query(&mut self, query: &Query) {
+        let columns = self.extractor.query(query, Some(self.frame));
+        for (_, sources) in columns {
+            self.add(&sources, DependencyKind::Derived);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{CatalogBuilder, MemoryCatalog};
+    use crate::parser::Parser;
+    use crate::types::SqlType;
+
+    fn catalog() -> MemoryCatalog {
+        CatalogBuilder::new()
+            .with_builtins()
+            .add_table("users", |t| {
+                t.column("id", SqlType::Int64)
+                    .column("name", SqlType::Varchar(None))
+                    .column("age", SqlType::Int64)
+            })
+            .add_table("orders", |t| {
+                t.column("id", SqlType::Int64)
+                    .column("user_id", SqlType::Int64)
+                    .column("amount", SqlType::Float64)
+            })
+            .add_table("totals", |t| {
+                t.column("user_name", SqlType::Varchar(None))
+                    .column("total", SqlType::Float64)
+            })
+            .build()
+    }
+
+    fn extract_sql(sql: &str) -> Result<LineageGraph> {
+        let stmt = Parser::new(sql).parse_statement()?;
+        extract(&stmt, &catalog())
+    }
+
+    /// The sources of each output column, as `(kind, "table.column")`.
+    fn sources(graph: &LineageGraph) -> Vec<Vec<(DependencyKind, String)>> {
+        graph
+            .columns
+            .iter()
+            .map(|c| {
+                c.sources
+                    .iter()
+                    .map(|d| (d.kind, format!("{}.{}", d.column.table, d.column.column)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn filters(graph: &LineageGraph) -> Vec<String> {
+        graph
+            .filter_dependencies
+            .iter()
+            .map(|c| format!("{}.{}", c.table, c.column))
+            .collect()
+    }
+
+    use DependencyKind::*;
+
+    #[test]
+    fn test_direct_and_aggregated_sources() {
+        let graph = extract_sql(
+            "SELECT u.name, SUM(o.amount) total, u.age + 1 AS next_age \
+             FROM users u JOIN orders o ON u.id = o.user_id GROUP BY u.name, u.age",
+        )
+        .unwrap();
+        assert_eq!(graph.columns[1].column.name, "total");
+        assert_eq!(
+            sources(&graph),
+            [
+                vec![(Direct, "users.name".to_string())],
+                vec![(Aggregated, "orders.amount".to_string())],
+                vec![(Derived, "users.age".to_string())],
+            ]
+        );
+        assert_eq!(
+            filters(&graph),
+            ["orders.user_id", "users.age", "users.id", "users.name"]
+        );
+        assert!(graph.columns.iter().all(|c| c.target.is_none()));
+    }
+
+    #[test]
+    fn test_sources_through_ctes_subqueries_and_set_operations() {
+        let graph = extract_sql(
+            "WITH big AS (SELECT user_id, amount * 2 AS doubled FROM orders WHERE amount > 10) \
+             SELECT b.doubled, x.n FROM big b, (SELECT COUNT(id) AS n FROM users) x \
+             UNION ALL SELECT age, id FROM users",
+        )
+        .unwrap();
+        assert_eq!(
+            sources(&graph),
+            [
+                vec![
+                    (Derived, "orders.amount".to_string()),
+                    (Direct, "users.age".to_string()),
+                ],
+                vec![
+                    (Direct, "users.id".to_string()),
+                    (Aggregated, "users.id".to_string()),
+                ],
+            ]
+        );
+        assert_eq!(filters(&graph), ["orders.amount"]);
+
+        // A scalar subquery's value derives from its output, and a
+        // correlated condition is a filter.
+        let graph = extract_sql(
+            "SELECT id, (SELECT MAX(o.amount) FROM orders o WHERE o.user_id = u.id) AS top \
+             FROM users u",
+        )
+        .unwrap();
+        assert_eq!(
+            sources(&graph)[1],
+            [(Aggregated, "orders.amount".to_string())]
+        );
+        assert_eq!(filters(&graph), ["orders.user_id", "users.id"]);
+
+        // USING columns coalesce both inputs.
+        let graph = extract_sql("SELECT * FROM users JOIN orders USING (id)").unwrap();
+        let names: Vec<&str> = graph
+            .columns
+            .iter()
+            .map(|c| c.column.name.as_str())
+            .collect();
+        assert_eq!(names, ["id", "name", "age", "user_id", "amount"]);
+        assert_eq!(
+            sources(&graph)[0],
+            [
+                (Derived, "orders.id".to_string()),
+                (Derived, "users.id".to_string()),
+            ]
+        );
+        assert_eq!(sources(&graph)[4], [(Direct, "orders.amount".to_string())]);
+        assert_eq!(filters(&graph), ["orders.id", "users.id"]);
+    }
+
+    #[test]
+    fn test_insert_select_targets() {
+        let graph = extract_sql(
+            "INSERT INTO totals SELECT u.name, SUM(o.amount) FROM users u \
+             JOIN orders o ON u.id = o.user_id GROUP BY u.name",
+        )
+        .unwrap();
+        let targets: Vec<_> = graph.columns.iter().map(|c| c.target.clone()).collect();
+        assert_eq!(
+            targets,
+            [
+                Some(TableColumn::new("totals", "user_name")),
+                Some(TableColumn::new("totals", "total")),
+            ]
+        );
+        assert_eq!(
+            sources(&graph)[1],
+            [(Aggregated, "orders.amount".to_string())]
+        );
+
+        let graph = extract_sql("CREATE VIEW adults (who) AS SELECT name FROM users").unwrap();
+        assert_eq!(
+            graph.columns[0].target,
+            Some(TableColumn::new("adults", "who"))
+        );
+
+        assert!(extract_sql("DELETE FROM users WHERE id = 1")
+            .unwrap()
+            .columns
+            .is_empty());
+        assert!(extract_sql("SELECT missing FROM users").is_err());
+    }
+}
@@ -2,16 +2,41 @@
 //!
 //! This binary provides a command-line interface for parsing and analyzing SQL.
 
-use std::io::{self, BufRead, Write};
-use vibesql::{Error, Parser};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use vibesql::analyzer::{Analyzer, AnalyzerOptions};
+use vibesql::ast::{ShowObjectType, StatementKind};
+use vibesql::catalog::MemoryCatalog;
+use vibesql::json::ToJson;
+use vibesql::session::Session;
+use vibesql::{fingerprint, is_complete_statement, normalize, Error, Parser};
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let json = args.iter().any(|arg| arg == "--json");
+    let fingerprint = args.iter().any(|arg| arg == "--fingerprint");
+    args.retain(|arg| arg != "--json" && arg != "--fingerprint");
 
-    if args.len() > 1 {
+    if json || fingerprint {
+        // Parse SQL from the arguments, or else all of stdin
+        let mut sql = args.join(" ");
+        if args.is_empty() {
+            if let Err(e) = io::stdin().read_to_string(&mut sql) {
+                eprintln!("Error reading input: {}", e);
+                std::process::exit(1);
+            }
+        }
+        let parsed = if json {
+            print_json(&sql)
+        } else {
+            print_fingerprints(&sql)
+        };
+        if !parsed {
+            std::process::exit(1);
+        }
+    } else if !args.is_empty() {
         // Parse SQL from command line argument
-        let sql = args[1..].join(" ");
-        parse_and_print(&sql);
+        let sql = args.join(" ");
+        parse_and_print(&sql, &mut Session::new());
     } else {
         // Interactive REPL mode
         run_repl();
@@ -20,28 +45,57 @@ fn main() {
 
 fn run_repl() {
     println!("VibeSQL Parser v{}", env!("CARGO_PKG_VERSION"));
-    println!("Enter SQL statements (Ctrl+D to exit):\n");
+    println!("Enter SQL statements ending in ';' (Ctrl+D to exit).");
+    println!("An empty line or \\g runs an unfinished statement.\n");
 
     let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
     let mut stdout = io::stdout();
+    // Lines of the statement being entered.
+    let mut buffer = String::new();
+    // Variables assigned by SET, which last until exit.
+    let mut session = Session::new();
 
     loop {
-        print!("sql> ");
+        print!("{}", if buffer.is_empty() { "sql> " } else { "  -> " });
         stdout.flush().unwrap();
 
         let mut input = String::new();
         match stdin.lock().read_line(&mut input) {
-            Ok(0) => break, // EOF
+            Ok(0) => {
+                // EOF discards an unfinished statement at the prompt, and
+                // runs the rest of a script piped in.
+                if buffer.trim().is_empty() {
+                    break;
+                }
+                if interactive {
+                    println!("\n(statement discarded)");
+                } else {
+                    run(&buffer, &mut session);
+                }
+                buffer.clear();
+            }
             Ok(_) => {
-                let sql = input.trim();
-                if sql.is_empty() {
-                    continue;
+                let line = input.trim_end();
+                if buffer.is_empty() {
+                    let command = line.trim();
+                    if command.eq_ignore_ascii_case("quit") || command.eq_ignore_ascii_case("exit")
+                    {
+                        break;
+                    }
                 }
-                if sql.eq_ignore_ascii_case("quit") || sql.eq_ignore_ascii_case("exit") {
-                    break;
+                let (line, force) = match line.strip_suffix("\\g") {
+                    Some(line) => (line, true),
+                    None => (line, line.trim().is_empty()),
+                };
+                buffer.push_str(line);
+                buffer.push('\n');
+                if buffer.trim().is_empty() {
+                    buffer.clear();
+                } else if force || is_complete_statement(&buffer) {
+                    run(&buffer, &mut session);
+                    buffer.clear();
                 }
-                parse_and_print(sql);
-                println!();
             }
             Err(e) => {
                 eprintln!("Error reading input: {}", e);
@@ -51,34 +105,105 @@ fn run_repl() {
     }
 }
 
-fn parse_and_print(sql: &str) {
+/// Parse and print the statements entered at the prompt.
+fn run(sql: &str, session: &mut Session) {
+    parse_and_print(sql.trim(), session);
+    println!();
+}
+
+fn parse_and_print(sql: &str, session: &mut Session) {
     let mut parser = Parser::new(sql);
-    match parser.parse() {
+    let mut count = 0;
+    // Print each statement as soon as it parses. EXPLAIN prints the
+    // analyzed tree instead, against a catalog of just the builtins and the
+    // session's variables, SET assigns a session variable, and SHOW
+    // VARIABLES lists them.
+    for result in parser.iter_statements() {
+        match result {
+            Ok(stmt) if matches!(stmt.kind, StatementKind::Set(_)) => {
+                count += 1;
+                if let StatementKind::Set(set) = &stmt.kind {
+                    match session.apply_set(set) {
+                        Ok(()) => println!("  [{}] SET {}", count, set.variable),
+                        Err(e) => print_error(sql, &e),
+                    }
+                }
+            }
+            Ok(stmt) if matches!(&stmt.kind, StatementKind::Show(show) if show.object_type == ShowObjectType::Variables) =>
+            {
+                count += 1;
+                if let StatementKind::Show(show) = &stmt.kind {
+                    match session.show_variables(show.filter.as_ref()) {
+                        Ok(variables) => {
+                            println!("  [{}]", count);
+                            for (definition, value) in variables {
+                                println!("    {} = {:?}", definition.name, value);
+                            }
+                        }
+                        Err(e) => print_error(sql, &e),
+                    }
+                }
+            }
+            Ok(stmt) if matches!(stmt.kind, StatementKind::Explain(_)) => {
+                count += 1;
+                let mut catalog = MemoryCatalog::new();
+                catalog.register_builtins();
+                let options = AnalyzerOptions::new().with_session(session.clone());
+                match Analyzer::with_catalog_and_options(catalog, options).explain(&stmt) {
+                    Ok(explained) => {
+                        println!("  [{}]", count);
+                        for line in explained.to_string().lines() {
+                            println!("    {}", line);
+                        }
+                    }
+                    Err(e) => print_error(sql, &e),
+                }
+            }
+            Ok(stmt) => {
+                count += 1;
+                println!("  [{}] {:?}", count, stmt);
+            }
+            Err(e) => {
+                print_error(sql, &e);
+                return;
+            }
+        }
+    }
+    println!("Parsed {} statement(s)", count);
+}
+
+/// Print the statements as `{"statements":[...]}`, or the error as
+/// `{"error":{...}}` on stderr. Returns whether the input parsed.
+fn print_json(sql: &str) -> bool {
+    match Parser::new(sql).parse() {
+        Ok(statements) => {
+            println!("{{\"statements\":{}}}", statements.to_json());
+            true
+        }
+        Err(e) => {
+            eprintln!("{{\"error\":{}}}", e.to_json());
+            false
+        }
+    }
+}
+
+/// Print the fingerprint of each statement, in hex, and its normalized
+/// text. Returns whether the input parsed.
+fn print_fingerprints(sql: &str) -> bool {
+    match Parser::new(sql).parse() {
         Ok(statements) => {
-            println!("Parsed {} statement(s):", statements.len());
-            for (i, stmt) in statements.iter().enumerate() {
-                println!("  [{}] {:?}", i + 1, stmt);
+            for stmt in statements {
+                println!("{:016x}  {}", fingerprint(&stmt), normalize(stmt));
             }
+            true
         }
         Err(e) => {
             print_error(sql, &e);
+            false
         }
     }
 }
 
 fn print_error(sql: &str, error: &Error) {
-    eprintln!("Error: {}", error);
-    if let Some(span) = error.span() {
-        let line_start = sql[..span.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
-        let line_end = sql[span.start..]
-            .find('\n')
-            .map(|i| span.start + i)
-            .unwrap_or(sql.len());
-        let line = &sql[line_start..line_end];
-        let col = span.start - line_start;
-
-        eprintln!("  |");
-        eprintln!("  | {}", line);
-        eprintln!("  | {}^", " ".repeat(col));
-    }
+    eprintln!("{}", error.display_with_source(sql));
 }
@@ -4,10 +4,10 @@
 //! for handling operator precedence correctly.
 
 use crate::ast::*;
-use crate::error::{Error, Result, Span};
-use crate::lexer::{Keyword, TokenKind};
+use crate::error::{Error, ErrorKind, Result, Span, Suggestion};
+use crate::lexer::{Keyword, Token, TokenKind};
 
-use super::Parser;
+use super::{quoted_ident, ParameterRef, Parser};
 
 impl<'a> Parser<'a> {
     /// Parse an expression.
@@ -17,19 +17,51 @@ impl<'a> Parser<'a> {
 
     /// Parse an expression with a minimum precedence.
     fn parse_expression_with_precedence(&mut self, min_precedence: u8) -> Result<Box<Expr>> {
-        let mut left = self.parse_unary_expression()?;
+        self.nested(|p| p.parse_operators(min_precedence))
+    }
 
-        loop {
-            // Clone the token kind to avoid borrow issues
-            let token_kind = self.peek()?.kind.clone();
+    /// Parse an operand and the operators that follow it, down to a minimum
+    /// precedence. A chain of left-associative operators is built in this
+    /// loop, so only operands parsed by recursion nest a level deeper.
+    fn parse_operators(&mut self, min_precedence: u8) -> Result<Box<Expr>> {
+        let mut left = if self.check_keyword(Keyword::Not)? {
+            self.parse_not_expression(min_precedence.max(NOT_PRECEDENCE))?
+        } else {
+            self.parse_unary_expression()?
+        };
 
+        loop {
             // Check for binary operator
-            if let Some((op, precedence)) = self.get_binary_op(&token_kind) {
+            if let Some((op, precedence)) = binary_op(&self.peek()?.kind) {
                 if precedence < min_precedence {
                     break;
                 }
 
-                self.advance()?;
+                let op_token = self.advance()?;
+                // A repeated operator such as `a = = 1` is a typo; `+` and `-`
+                // are excluded because they double as unary prefixes.
+                if !matches!(op, BinaryOp::Plus | BinaryOp::Minus) {
+                    self.reject_duplicate_operator(&op_token)?;
+                }
+
+                // `expr op ANY|SOME|ALL (...)`
+                if precedence == COMPARISON_PRECEDENCE {
+                    if let Some(quantifier) = self.peek_quantifier()? {
+                        self.advance()?;
+                        let (list, end) = self.parse_in_list()?;
+                        let span = left.span.merge(end);
+                        left = Expr::boxed(
+                            ExprKind::QuantifiedComparison {
+                                expr: left,
+                                op,
+                                quantifier,
+                                list,
+                            },
+                            span,
+                        );
+                        continue;
+                    }
+                }
                 let next_precedence = if op.is_left_associative() {
                     precedence + 1
                 } else {
@@ -41,12 +73,31 @@ impl<'a> Parser<'a> {
 
                 left = Expr::boxed(ExprKind::BinaryOp { op, left, right }, span);
             }
+            // `<=>` is `IS NOT DISTINCT FROM` with comparison precedence
+            else if self.check(&TokenKind::SafeEq)? {
+                if COMPARISON_PRECEDENCE < min_precedence {
+                    break;
+                }
+                let op_token = self.advance()?;
+                self.reject_duplicate_operator(&op_token)?;
+                let right = self.parse_expression_with_precedence(POSTFIX_PRECEDENCE)?;
+                let span = left.span.merge(right.span);
+                left = Expr::boxed(
+                    ExprKind::IsDistinct {
+                        left,
+                        right,
+                        negated: true,
+                    },
+                    span,
+                );
+            }
             // Check for AND
             else if self.check_keyword(Keyword::And)? {
                 if BinaryOp::And.precedence() < min_precedence {
                     break;
                 }
-                self.advance()?;
+                let op_token = self.advance()?;
+                self.reject_duplicate_operator(&op_token)?;
                 let right =
                     self.parse_expression_with_precedence(BinaryOp::And.precedence() + 1)?;
                 let span = left.span.merge(right.span);
@@ -64,7 +115,8 @@ impl<'a> Parser<'a> {
                 if BinaryOp::Or.precedence() < min_precedence {
                     break;
                 }
-                self.advance()?;
+                let op_token = self.advance()?;
+                self.reject_duplicate_operator(&op_token)?;
                 let right = self.parse_expression_with_precedence(BinaryOp::Or.precedence() + 1)?;
                 let span = left.span.merge(right.span);
                 left = Expr::boxed(
@@ -77,8 +129,8 @@ impl<'a> Parser<'a> {
                 );
             }
             // Check for postfix operators and special expressions
-            else if let Some(new_left) = self.try_parse_postfix_expression(left.clone())? {
-                left = new_left;
+            else if let Some(postfix) = self.peek_postfix()? {
+                left = self.parse_postfix_expression(left, postfix)?;
             } else {
                 break;
             }
@@ -87,18 +139,54 @@ impl<'a> Parser<'a> {
         Ok(left)
     }
 
-    /// Parse a unary expression (NOT, -, +, ~).
+    /// Parse `NOT` and an operand that extends over operators of at least
+    /// `min_precedence`, so `NOT a = 1 AND b` is `(NOT (a = 1)) AND b`,
+    /// while in `a = NOT b = c` the operand is only `b`.
+    fn parse_not_expression(&mut self, min_precedence: u8) -> Result<Box<Expr>> {
+        let start = self.advance()?.span.start;
+        let expr = self.parse_expression_with_precedence(min_precedence)?;
+        let span = Span::new(start, expr.span.end);
+        Ok(Expr::boxed(
+            ExprKind::UnaryOp {
+                op: UnaryOp::Not,
+                expr,
+            },
+            span,
+        ))
+    }
+
+    /// Reject an operator token that is immediately repeated.
+    fn reject_duplicate_operator(&mut self, op_token: &Token) -> Result<()> {
+        let next = self.peek()?;
+        if next.kind == op_token.kind {
+            let span = next.span;
+            return Err(Error::with_span(
+                ErrorKind::DuplicateOperator(op_token.text.clone()),
+                span,
+            )
+            .with_suggestion(Suggestion::delete("remove the repeated operator", span)));
+        }
+        Ok(())
+    }
+
+    /// Parse a unary expression (NOT, -, +, ~). A NOT operand here is
+    /// itself unary, as in `-NOT a`; see [`Self::parse_not_expression`].
     fn parse_unary_expression(&mut self) -> Result<Box<Expr>> {
-        // Clone token info to avoid borrow issues
-        let (start, token_kind) = {
+        let (start, unary_op) = {
             let token = self.peek()?;
-            (token.span.start, token.kind.clone())
+            let op = match token.kind {
+                TokenKind::Plus => Some(UnaryOp::Plus),
+                TokenKind::Minus => Some(UnaryOp::Minus),
+                TokenKind::Tilde => Some(UnaryOp::BitwiseNot),
+                _ => None,
+            };
+            (token.span.start, op)
         };
 
         // NOT
         if self.check_keyword(Keyword::Not)? {
             self.advance()?;
-            let expr = self.parse_unary_expression()?;
+            let expr = self.nested(Self::parse_unary_expression)?;
             let span = Span::new(start, expr.span.end);
             return Ok(Expr::boxed(
                 ExprKind::UnaryOp {
@@ -149,59 +237,24 @@ impl<'a> Parser<'a> {
             return self.parse_if_expression();
         }
 
-        // Unary + and -
-        match &token_kind {
-            TokenKind::Plus => {
-                self.advance()?;
-                let expr = self.parse_unary_expression()?;
-                let span = Span::new(start, expr.span.end);
-                return Ok(Expr::boxed(
-                    ExprKind::UnaryOp {
-                        op: UnaryOp::Plus,
-                        expr,
-                    },
-                    span,
-                ));
-            }
-            TokenKind::Minus => {
-                self.advance()?;
-                let expr = self.parse_unary_expression()?;
-                let span = Span::new(start, expr.span.end);
-                return Ok(Expr::boxed(
-                    ExprKind::UnaryOp {
-                        op: UnaryOp::Minus,
-                        expr,
-                    },
-                    span,
-                ));
-            }
-            TokenKind::Tilde => {
-                self.advance()?;
-                let expr = self.parse_unary_expression()?;
-                let span = Span::new(start, expr.span.end);
-                return Ok(Expr::boxed(
-                    ExprKind::UnaryOp {
-                        op: UnaryOp::BitwiseNot,
-                        expr,
-                    },
-                    span,
-                ));
-            }
-            _ => {}
+        // Unary +, -, and ~
+        if let Some(op) = unary_op {
+            self.advance()?;
+            let expr = self.nested(Self::parse_unary_expression)?;
+            let span = Span::new(start, expr.span.end);
+            return Ok(Expr::boxed(ExprKind::UnaryOp { op, expr }, span));
         }
 
         self.parse_primary_expression()
     }
 
     /// Parse a primary expression (atoms: literals, identifiers, function calls, etc.).
-    fn parse_primary_expression(&mut self) -> Result<Box<Expr>> {
-        // Clone token info to avoid borrow conflicts
-        let (span, token_kind) = {
-            let token = self.peek()?;
-            (token.span, token.kind.clone())
-        };
+    pub(super) fn parse_primary_expression(&mut self) -> Result<Box<Expr>> {
+        let span = self.peek()?.span;
 
-        match token_kind {
+        // Only `Copy` parts of the token are bound, so matching does not
+        // clone identifier or string text.
+        match self.peek()?.kind {
             // Literals
             TokenKind::Null => {
                 self.advance()?;
@@ -219,18 +272,23 @@ impl<'a> Parser<'a> {
                 self.advance()?;
                 Ok(Expr::boxed(ExprKind::Float(value), span))
             }
-            TokenKind::String(value) => {
-                self.advance()?;
-                Ok(Expr::boxed(ExprKind::String(value), span))
-            }
-            TokenKind::Bytes(value) => {
-                self.advance()?;
-                Ok(Expr::boxed(ExprKind::Bytes(value), span))
+            TokenKind::String(_) | TokenKind::Bytes(_) => {
+                // Move the value out of the consumed token.
+                let kind = match self.advance()?.kind {
+                    TokenKind::String(value) => ExprKind::String(value),
+                    TokenKind::Bytes(value) => ExprKind::Bytes(value),
+                    _ => return Err(Error::expected_expression(span)),
+                };
+                Ok(Expr::boxed(kind, span))
             }
 
-            // Typed literals (DATE, TIME, TIMESTAMP, JSON, etc.)
+            // Typed literals (DATE, TIME, TIMESTAMP, JSON, etc.), unless
+            // the keyword names a conversion function such as `DATE(ts)`
             TokenKind::Keyword(kw) => {
                 if let Some(lit_type) = self.keyword_to_typed_literal(kw) {
+                    if self.peek_nth(1)?.kind == TokenKind::LeftParen {
+                        return self.parse_identifier_or_function();
+                    }
                     self.advance()?;
                     let string_token = self.advance()?;
                     if let TokenKind::String(value) = string_token.kind {
@@ -277,17 +335,29 @@ impl<'a> Parser<'a> {
                     _ => return Err(Error::expected_identifier(name_token.span)),
                 };
                 let end_span = name_token.span;
-                Ok(Expr::boxed(
-                    ExprKind::Parameter(Parameter::Named(name)),
-                    Span::new(span.start, end_span.end),
-                ))
+                let span = Span::new(span.start, end_span.end);
+                Ok(self.parameter(Parameter::Named(name), span))
+            }
+            TokenKind::DoubleAt => {
+                self.advance()?;
+                let mut name = self.parse_identifier_allow_reserved()?;
+                let scope = if self.consume(&TokenKind::Dot)?.is_some() {
+                    let scoped = self.parse_identifier_allow_reserved()?;
+                    Some(std::mem::replace(&mut name, scoped))
+                } else {
+                    None
+                };
+                let span = Span::new(span.start, name.span.end);
+                Ok(Expr::boxed(ExprKind::SystemVariable { scope, name }, span))
             }
             TokenKind::Question => {
                 self.advance()?;
-                Ok(Expr::boxed(
-                    ExprKind::Parameter(Parameter::Positional(0)),
-                    span,
-                ))
+                self.positional_count += 1;
+                Ok(self.parameter(Parameter::Positional(self.positional_count), span))
+            }
+            TokenKind::Parameter(n) => {
+                self.advance()?;
+                Ok(self.parameter(Parameter::Numbered(n), span))
             }
 
             // Parenthesized expression or subquery
@@ -300,105 +370,136 @@ impl<'a> Parser<'a> {
         }
     }
 
-    /// Try to parse postfix expressions (field access, array subscript, etc.).
-    fn try_parse_postfix_expression(&mut self, left: Box<Expr>) -> Result<Option<Box<Expr>>> {
-        // Clone token kind to avoid borrow issues
-        let token_kind = self.peek()?.kind.clone();
+    /// The postfix operator the next tokens start, if any.
+    fn peek_postfix(&mut self) -> Result<Option<Postfix>> {
+        let postfix = match self.peek()?.kind {
+            TokenKind::Dot => Postfix::FieldAccess,
+            TokenKind::LeftBracket => Postfix::Subscript,
+            TokenKind::Keyword(Keyword::Between) => Postfix::Between { negated: false },
+            TokenKind::Keyword(Keyword::In) => Postfix::In { negated: false },
+            TokenKind::Keyword(Keyword::Like) => Postfix::Like {
+                negated: false,
+                case_insensitive: false,
+            },
+            TokenKind::Keyword(Keyword::Ilike) => Postfix::Like {
+                negated: false,
+                case_insensitive: true,
+            },
+            // SIMILAR is not reserved, so it is only an operator before TO.
+            TokenKind::Keyword(Keyword::Similar) => {
+                if !self.peek_nth(1)?.is_keyword(Keyword::To) {
+                    return Ok(None);
+                }
+                Postfix::SimilarTo { negated: false }
+            }
+            TokenKind::Keyword(Keyword::Is) => Postfix::Is,
+            TokenKind::Keyword(Keyword::Not) => match self.peek_nth(1)?.kind {
+                TokenKind::Keyword(Keyword::Between) => Postfix::Between { negated: true },
+                TokenKind::Keyword(Keyword::In) => Postfix::In { negated: true },
+                TokenKind::Keyword(Keyword::Like) => Postfix::Like {
+                    negated: true,
+                    case_insensitive: false,
+                },
+                TokenKind::Keyword(Keyword::Ilike) => Postfix::Like {
+                    negated: true,
+                    case_insensitive: true,
+                },
+                TokenKind::Keyword(Keyword::Similar) => Postfix::SimilarTo { negated: true },
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(postfix))
+    }
 
-        match token_kind {
+    /// Parse a postfix operator found by [`peek_postfix`](Self::peek_postfix)
+    /// (field access, array subscript, etc.) applied to `left`.
+    fn parse_postfix_expression(&mut self, left: Box<Expr>, postfix: Postfix) -> Result<Box<Expr>> {
+        self.advance()?;
+        match postfix {
             // Field access: expr.field
-            TokenKind::Dot => {
-                self.advance()?;
+            Postfix::FieldAccess => {
                 let field = self.parse_identifier()?;
                 let span = left.span.merge(field.span);
-                Ok(Some(Expr::boxed(
+                Ok(Expr::boxed(
                     ExprKind::FieldAccess { expr: left, field },
                     span,
-                )))
+                ))
             }
 
             // Array subscript: expr[index]
-            TokenKind::LeftBracket => {
-                self.advance()?;
+            Postfix::Subscript => {
                 let index = self.parse_array_subscript_index()?;
                 let end_token = self.expect(&TokenKind::RightBracket)?;
                 let span = left.span.merge(end_token.span);
-                Ok(Some(Expr::boxed(
+                Ok(Expr::boxed(
                     ExprKind::ArraySubscript { array: left, index },
                     span,
-                )))
+                ))
             }
 
-            // BETWEEN
-            TokenKind::Keyword(Keyword::Between) => {
-                self.advance()?;
-                let low = self.parse_expression_with_precedence(10)?;
+            // [NOT] BETWEEN
+            Postfix::Between { negated } => {
+                if negated {
+                    self.advance()?;
+                }
+                let low = self.parse_expression_with_precedence(POSTFIX_PRECEDENCE)?;
                 self.expect_keyword(Keyword::And)?;
-                let high = self.parse_expression_with_precedence(10)?;
+                let high = self.parse_expression_with_precedence(POSTFIX_PRECEDENCE)?;
                 let span = left.span.merge(high.span);
-                Ok(Some(Expr::boxed(
+                Ok(Expr::boxed(
                     ExprKind::Between {
                         expr: left,
                         low,
                         high,
-                        negated: false,
+                        negated,
                     },
                     span,
-                )))
+                ))
             }
 
-            // NOT BETWEEN
-            TokenKind::Keyword(Keyword::Not) => {
-                let next = self.peek_nth(1)?;
-                if next.is_keyword(Keyword::Between) {
-                    self.advance()?; // NOT
-                    self.advance()?; // BETWEEN
-                    let low = self.parse_expression_with_precedence(10)?;
-                    self.expect_keyword(Keyword::And)?;
-                    let high = self.parse_expression_with_precedence(10)?;
-                    let span = left.span.merge(high.span);
-                    return Ok(Some(Expr::boxed(
-                        ExprKind::Between {
-                            expr: left,
-                            low,
-                            high,
-                            negated: true,
-                        },
-                        span,
-                    )));
-                }
-                if next.is_keyword(Keyword::In) {
-                    self.advance()?; // NOT
-                    self.advance()?; // IN
-                    return self.parse_in_expression(left, true);
-                }
-                if next.is_keyword(Keyword::Like) {
-                    self.advance()?; // NOT
-                    self.advance()?; // LIKE
-                    return self.parse_like_expression(left, true);
+            // [NOT] IN
+            Postfix::In { negated } => {
+                if negated {
+                    self.advance()?;
                 }
-                Ok(None)
+                self.parse_in_expression(left, negated)
             }
 
-            // IN
-            TokenKind::Keyword(Keyword::In) => {
-                self.advance()?;
-                self.parse_in_expression(left, false)
+            // [NOT] LIKE and [NOT] ILIKE
+            Postfix::Like {
+                negated,
+                case_insensitive,
+            } => {
+                if negated {
+                    self.advance()?;
+                }
+                self.parse_like_expression(left, negated, case_insensitive)
             }
 
-            // LIKE
-            TokenKind::Keyword(Keyword::Like) => {
-                self.advance()?;
-                self.parse_like_expression(left, false)
+            // [NOT] SIMILAR TO
+            Postfix::SimilarTo { negated } => {
+                if negated {
+                    self.advance()?;
+                }
+                self.expect_keyword(Keyword::To)?;
+                let pattern = self.parse_expression_with_precedence(POSTFIX_PRECEDENCE)?;
+                let escape = self.parse_like_escape()?;
+                let end = escape.as_ref().unwrap_or(&pattern).span;
+                let span = left.span.merge(end);
+                Ok(Expr::boxed(
+                    ExprKind::SimilarTo {
+                        expr: left,
+                        pattern,
+                        escape,
+                        negated,
+                    },
+                    span,
+                ))
             }
 
             // IS
-            TokenKind::Keyword(Keyword::Is) => {
-                self.advance()?;
-                self.parse_is_expression(left)
-            }
-
-            _ => Ok(None),
+            Postfix::Is => self.parse_is_expression(left),
         }
     }
 
@@ -499,18 +600,35 @@ impl<'a> Parser<'a> {
     /// Parse a window specification or reference.
     fn parse_window_spec_or_ref(&mut self) -> Result<WindowSpecOrRef> {
         if self.check(&TokenKind::LeftParen)? {
-            self.expect(&TokenKind::LeftParen)?;
-            let spec = self.parse_window_spec()?;
-            self.expect(&TokenKind::RightParen)?;
-            Ok(WindowSpecOrRef::Spec(spec))
+            Ok(WindowSpecOrRef::Spec(
+                self.parse_parenthesized_window_spec()?,
+            ))
         } else {
             let name = self.parse_identifier()?;
             Ok(WindowSpecOrRef::Ref(name))
         }
     }
 
+    /// Parse a parenthesized window specification. Its span includes the
+    /// parentheses.
+    pub(super) fn parse_parenthesized_window_spec(&mut self) -> Result<WindowSpec> {
+        let start = self.expect(&TokenKind::LeftParen)?.span.start;
+        let mut spec = self.parse_window_spec()?;
+        self.expect(&TokenKind::RightParen)?;
+        spec.span = Span::new(start, self.previous_end());
+        Ok(spec)
+    }
+
     /// Parse a window specification.
     pub(super) fn parse_window_spec(&mut self) -> Result<WindowSpec> {
+        let start = self.peek()?.span.start;
+        let base = match self.peek()?.kind {
+            TokenKind::Identifier(_) | TokenKind::QuotedIdentifier(_) => {
+                Some(self.parse_identifier()?)
+            }
+            _ => None,
+        };
+
         let partition_by = if self.consume_keyword(Keyword::Partition)?.is_some() {
             self.expect_keyword(Keyword::By)?;
             self.parse_comma_separated(|p| p.parse_expression())?
@@ -526,16 +644,28 @@ impl<'a> Parser<'a> {
         };
 
         let frame = self.parse_optional_window_frame()?;
+        if frame.is_none() {
+            let token = self.peek()?;
+            if token.is_keyword(Keyword::Exclude) {
+                return Err(Error::invalid_syntax(
+                    "EXCLUDE requires a ROWS, RANGE, or GROUPS frame",
+                    token.span,
+                ));
+            }
+        }
 
         Ok(WindowSpec {
+            base,
             partition_by,
             order_by,
             frame,
+            span: Span::new(start, self.previous_end().max(start)),
         })
     }
 
     /// Parse an optional window frame.
     fn parse_optional_window_frame(&mut self) -> Result<Option<WindowFrame>> {
+        let span_start = self.peek()?.span.start;
         let unit = if self.consume_keyword(Keyword::Rows)?.is_some() {
             WindowFrameUnit::Rows
         } else if self.consume_keyword(Keyword::Range)?.is_some() {
@@ -555,7 +685,43 @@ impl<'a> Parser<'a> {
             (self.parse_window_frame_bound()?, None)
         };
 
-        Ok(Some(WindowFrame { unit, start, end }))
+        let exclusion = self.parse_optional_frame_exclusion()?;
+
+        Ok(Some(WindowFrame {
+            unit,
+            start,
+            end,
+            exclusion,
+            span: Span::new(span_start, self.previous_end()),
+        }))
+    }
+
+    /// Parse an optional frame exclusion clause.
+    fn parse_optional_frame_exclusion(&mut self) -> Result<Option<FrameExclusion>> {
+        if self.consume_keyword(Keyword::Exclude)?.is_none() {
+            return Ok(None);
+        }
+
+        let exclusion = if self.consume_keyword(Keyword::Current)?.is_some() {
+            self.expect_keyword(Keyword::Row)?;
+            FrameExclusion::CurrentRow
+        } else if self.consume_keyword(Keyword::Group)?.is_some() {
+            FrameExclusion::Group
+        } else if self.consume_keyword(Keyword::Ties)?.is_some() {
+            FrameExclusion::Ties
+        } else if self.consume_keyword(Keyword::No)?.is_some() {
+            self.expect_keyword(Keyword::Others)?;
+            FrameExclusion::NoOthers
+        } else {
+            let token = self.peek()?;
+            return Err(Error::unexpected_token(
+                "CURRENT ROW, GROUP, TIES, or NO OTHERS",
+                format!("{}", token.kind),
+                token.span,
+            ));
+        };
+
+        Ok(Some(exclusion))
     }
 
     /// Parse a window frame bound.
@@ -629,21 +795,53 @@ impl<'a> Parser<'a> {
         Ok(ArraySubscriptKind::Index(idx))
     }
 
+    /// Record a parameter placeholder and build its expression.
+    fn parameter(&mut self, parameter: Parameter, span: Span) -> Box<Expr> {
+        self.parameters.push(ParameterRef {
+            parameter: parameter.clone(),
+            span,
+        });
+        Expr::boxed(ExprKind::Parameter(parameter), span)
+    }
+
+    /// Parse what follows an opening parenthesis in expression position.
+    ///
+    /// A leading `(SELECT ...)` is parsed as an expression first; if a set
+    /// operation, ORDER BY, or LIMIT follows it, it was the first operand
+    /// of a query, as in `((SELECT 1) UNION (SELECT 2))`.
+    fn parse_paren_contents(&mut self) -> Result<ParenContents> {
+        if self.check_query_start()? {
+            return Ok(ParenContents::Query(self.parse_query()?));
+        }
+        let nested = self.check(&TokenKind::LeftParen)?;
+        let mut expr = self.parse_expression()?;
+        if nested && self.check_query_continuation()? {
+            match std::mem::replace(&mut expr.kind, ExprKind::Null) {
+                ExprKind::Subquery(first) => {
+                    let query = self.continue_subquery(*first, expr.span.start)?;
+                    return Ok(ParenContents::Query(query));
+                }
+                kind => expr.kind = kind,
+            }
+        }
+        Ok(ParenContents::Expr(expr))
+    }
+
     /// Parse a parenthesized expression or subquery.
     fn parse_parenthesized_expression(&mut self) -> Result<Box<Expr>> {
         let start = self.expect(&TokenKind::LeftParen)?.span.start;
 
         // Check if this is a subquery
-        if self.check_keyword(Keyword::Select)? || self.check_keyword(Keyword::With)? {
-            let query = self.parse_query()?;
-            let end = self.expect(&TokenKind::RightParen)?.span.end;
-            return Ok(Expr::boxed(
-                ExprKind::Subquery(Box::new(query)),
-                Span::new(start, end),
-            ));
-        }
-
-        let expr = self.parse_expression()?;
+        let expr = match self.parse_paren_contents()? {
+            ParenContents::Query(query) => {
+                let end = self.expect(&TokenKind::RightParen)?.span.end;
+                return Ok(Expr::boxed(
+                    ExprKind::Subquery(Box::new(query)),
+                    Span::new(start, end),
+                ));
+            }
+            ParenContents::Expr(expr) => expr,
+        };
 
         // Check for tuple/struct: (expr1, expr2, ...)
         if self.check(&TokenKind::Comma)? {
@@ -697,7 +895,7 @@ impl<'a> Parser<'a> {
         // Check for type parameter: ARRAY<type>
         let element_type = if self.consume(&TokenKind::Lt)?.is_some() {
             let data_type = self.parse_data_type()?;
-            self.expect(&TokenKind::Gt)?;
+            self.expect_closing_angle()?;
             Some(Box::new(data_type))
         } else {
             None
@@ -706,12 +904,10 @@ impl<'a> Parser<'a> {
         // Parse array elements or subquery
         if self.check(&TokenKind::LeftParen)? {
             // ARRAY(subquery)
-            self.advance()?;
-            let query = self.parse_query()?;
-            let end = self.expect(&TokenKind::RightParen)?.span.end;
+            let (query, parens) = self.parse_subquery()?;
             return Ok(Expr::boxed(
                 ExprKind::Subquery(Box::new(query)),
-                Span::new(start, end),
+                Span::new(start, parens.end),
             ));
         }
 
@@ -736,8 +932,9 @@ impl<'a> Parser<'a> {
     fn parse_struct_expression(&mut self) -> Result<Box<Expr>> {
         let start = self.expect_keyword(Keyword::Struct)?.span.start;
 
-        // Check for type parameter: STRUCT<...>
-        if self.check(&TokenKind::Lt)? {
+        // Check for type parameter: STRUCT<...>, or `STRUCT<>`, which
+        // lexes as the `<>` operator
+        if self.consume(&TokenKind::LtGt)?.is_none() && self.check(&TokenKind::Lt)? {
             // STRUCT<field_type, ...>(values)
             // For now, skip type parameters
             self.advance()?;
@@ -880,7 +1077,7 @@ impl<'a> Parser<'a> {
     fn parse_interval_expression(&mut self) -> Result<Box<Expr>> {
         let start = self.expect_keyword(Keyword::Interval)?.span.start;
 
-        let value = self.parse_expression_with_precedence(10)?;
+        let value = self.parse_expression_with_precedence(POSTFIX_PRECEDENCE)?;
 
         let unit_token = self.advance()?;
         let unit = match IntervalUnit::parse(&unit_token.text) {
@@ -923,9 +1120,8 @@ impl<'a> Parser<'a> {
     /// Parse EXISTS expression.
     fn parse_exists_expression(&mut self) -> Result<Box<Expr>> {
         let start = self.expect_keyword(Keyword::Exists)?.span.start;
-        self.expect(&TokenKind::LeftParen)?;
-        let query = self.parse_query()?;
-        let end = self.expect(&TokenKind::RightParen)?.span.end;
+        let (query, parens) = self.parse_subquery()?;
+        let end = parens.end;
 
         Ok(Expr::boxed(
             ExprKind::Exists {
@@ -937,61 +1133,131 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse IN expression.
-    fn parse_in_expression(&mut self, left: Box<Expr>, negated: bool) -> Result<Option<Box<Expr>>> {
-        self.expect(&TokenKind::LeftParen)?;
+    fn parse_in_expression(&mut self, left: Box<Expr>, negated: bool) -> Result<Box<Expr>> {
+        let (list, end) = self.parse_in_list()?;
+        let span = left.span.merge(end);
 
-        let list = if self.check_keyword(Keyword::Select)? || self.check_keyword(Keyword::With)? {
-            let query = self.parse_query()?;
-            InList::Subquery(Box::new(query))
-        } else {
-            let values = self.parse_comma_separated(|p| p.parse_expression())?;
-            InList::Values(values)
-        };
-
-        let end = self.expect(&TokenKind::RightParen)?.span.end;
-        let span = left.span.merge(Span::new(end - 1, end));
-
-        Ok(Some(Expr::boxed(
+        Ok(Expr::boxed(
             ExprKind::In {
                 expr: left,
                 list,
                 negated,
             },
             span,
-        )))
+        ))
     }
 
-    /// Parse LIKE expression.
+    /// Parse the values of IN or a quantified comparison: a parenthesized
+    /// subquery or list, or `UNNEST(array)`. Returns the span of the
+    /// closing parenthesis with the list.
+    fn parse_in_list(&mut self) -> Result<(InList, Span)> {
+        let unnest = self.consume_keyword(Keyword::Unnest)?.is_some();
+        self.expect(&TokenKind::LeftParen)?;
+
+        let list = if unnest {
+            InList::Unnest(self.parse_expression()?)
+        } else {
+            match self.parse_paren_contents()? {
+                ParenContents::Query(query) => InList::Subquery(Box::new(query)),
+                ParenContents::Expr(first) => {
+                    let mut values = vec![first];
+                    while self.consume(&TokenKind::Comma)?.is_some() {
+                        values.push(self.parse_expression()?);
+                    }
+                    InList::Values(values)
+                }
+            }
+        };
+
+        let end = self.expect(&TokenKind::RightParen)?.span.end;
+        Ok((list, Span::new(end - 1, end)))
+    }
+
+    /// The ANY, SOME or ALL at the current token, if there is one.
+    fn peek_quantifier(&mut self) -> Result<Option<Quantifier>> {
+        Ok(match self.peek()?.kind {
+            TokenKind::Keyword(Keyword::Any) => Some(Quantifier::Any),
+            TokenKind::Keyword(Keyword::Some) => Some(Quantifier::Some),
+            TokenKind::Keyword(Keyword::All) => Some(Quantifier::All),
+            _ => None,
+        })
+    }
+
+    /// Parse LIKE expression, after `LIKE` or `ILIKE`: a pattern, or
+    /// `ANY`, `SOME` or `ALL` and a parenthesized list of patterns.
     fn parse_like_expression(
         &mut self,
         left: Box<Expr>,
         negated: bool,
-    ) -> Result<Option<Box<Expr>>> {
-        let pattern = self.parse_expression_with_precedence(10)?;
-
-        let escape = if self.consume_keyword(Keyword::Escape)?.is_some() {
-            Some(self.parse_expression_with_precedence(10)?)
-        } else {
-            None
+        case_insensitive: bool,
+    ) -> Result<Box<Expr>> {
+        let Some(quantifier) = self.peek_quantifier()? else {
+            let pattern = self.parse_expression_with_precedence(POSTFIX_PRECEDENCE)?;
+            let escape = self.parse_like_escape()?;
+            let end = escape.as_ref().unwrap_or(&pattern).span;
+            let span = left.span.merge(end);
+            return Ok(Expr::boxed(
+                ExprKind::Like {
+                    expr: left,
+                    pattern,
+                    escape,
+                    negated,
+                    case_insensitive,
+                },
+                span,
+            ));
         };
 
-        let span = left.span.merge(pattern.span);
+        self.advance()?;
+        self.expect(&TokenKind::LeftParen)?;
+        let patterns = self.parse_comma_separated(|p| p.parse_expression())?;
+        let end = self.expect(&TokenKind::RightParen)?.span;
+        let escape = self.parse_like_escape()?;
+        let span = left.span.merge(escape.as_ref().map_or(end, |e| e.span));
 
-        Ok(Some(Expr::boxed(
-            ExprKind::Like {
+        Ok(Expr::boxed(
+            ExprKind::QuantifiedLike {
                 expr: left,
-                pattern,
+                quantifier,
+                patterns,
                 escape,
                 negated,
+                case_insensitive,
             },
             span,
-        )))
+        ))
     }
 
-    /// Parse IS expression (IS NULL, IS NOT NULL, IS TRUE, etc.).
-    fn parse_is_expression(&mut self, left: Box<Expr>) -> Result<Option<Box<Expr>>> {
+    /// Parse the optional `ESCAPE` clause of a pattern match.
+    fn parse_like_escape(&mut self) -> Result<Option<Box<Expr>>> {
+        if self.consume_keyword(Keyword::Escape)?.is_some() {
+            Ok(Some(
+                self.parse_expression_with_precedence(POSTFIX_PRECEDENCE)?,
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parse IS expression (IS NULL, IS NOT NULL, IS TRUE, IS UNKNOWN,
+    /// IS DISTINCT FROM, etc.).
+    fn parse_is_expression(&mut self, left: Box<Expr>) -> Result<Box<Expr>> {
         let negated = self.consume_keyword(Keyword::Not)?.is_some();
 
+        if self.consume_keyword(Keyword::Distinct)?.is_some() {
+            self.expect_keyword(Keyword::From)?;
+            let right = self.parse_expression_with_precedence(POSTFIX_PRECEDENCE)?;
+            let span = left.span.merge(right.span);
+            return Ok(Expr::boxed(
+                ExprKind::IsDistinct {
+                    left,
+                    right,
+                    negated,
+                },
+                span,
+            ));
+        }
+
         // Note: NULL, TRUE, FALSE are special tokens, not keywords
         let test = if self.consume(&TokenKind::Null)?.is_some() {
             IsTest::Null
@@ -999,49 +1265,27 @@ impl<'a> Parser<'a> {
             IsTest::True
         } else if self.consume(&TokenKind::Boolean(false))?.is_some() {
             IsTest::False
+        } else if self.consume_keyword(Keyword::Unknown)?.is_some() {
+            IsTest::Unknown
         } else {
             let token = self.advance()?;
             return Err(Error::unexpected_token(
-                "NULL, TRUE, FALSE, or UNKNOWN",
+                "NULL, TRUE, FALSE, UNKNOWN, or DISTINCT FROM",
                 format!("{}", token.kind),
                 token.span,
             ));
         };
 
-        let span = Span::new(left.span.start, self.current_position());
+        let span = Span::new(left.span.start, self.previous_end());
 
-        Ok(Some(Expr::boxed(
+        Ok(Expr::boxed(
             ExprKind::IsExpr {
                 expr: left,
                 test,
                 negated,
             },
             span,
-        )))
-    }
-
-    /// Get binary operator and its precedence from token.
-    fn get_binary_op(&self, kind: &TokenKind) -> Option<(BinaryOp, u8)> {
-        match kind {
-            TokenKind::Plus => Some((BinaryOp::Plus, 7)),
-            TokenKind::Minus => Some((BinaryOp::Minus, 7)),
-            TokenKind::Star => Some((BinaryOp::Multiply, 8)),
-            TokenKind::Slash => Some((BinaryOp::Divide, 8)),
-            TokenKind::Percent => Some((BinaryOp::Modulo, 8)),
-            TokenKind::Eq => Some((BinaryOp::Eq, 9)),
-            TokenKind::NotEq | TokenKind::LtGt => Some((BinaryOp::NotEq, 9)),
-            TokenKind::Lt => Some((BinaryOp::Lt, 9)),
-            TokenKind::LtEq => Some((BinaryOp::LtEq, 9)),
-            TokenKind::Gt => Some((BinaryOp::Gt, 9)),
-            TokenKind::GtEq => Some((BinaryOp::GtEq, 9)),
-            TokenKind::Ampersand => Some((BinaryOp::BitwiseAnd, 5)),
-            TokenKind::Pipe => Some((BinaryOp::BitwiseOr, 3)),
-            TokenKind::Caret => Some((BinaryOp::BitwiseXor, 4)),
-            TokenKind::LeftShift => Some((BinaryOp::LeftShift, 6)),
-            TokenKind::RightShift => Some((BinaryOp::RightShift, 6)),
-            TokenKind::DoublePipe => Some((BinaryOp::Concat, 8)),
-            _ => None,
-        }
+        ))
     }
 
     /// Convert keyword to typed literal type.
@@ -1058,16 +1302,26 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse a data type specification.
+    ///
+    /// Each type nests a level, so element types of `ARRAY`, `RANGE`, and
+    /// `STRUCT` count toward [`Parser::with_max_depth`].
     pub fn parse_data_type(&mut self) -> Result<DataTypeSpec> {
+        self.nested(Self::parse_data_type_at_depth)
+    }
+
+    /// Parse a data type specification at the current nesting level.
+    fn parse_data_type_at_depth(&mut self) -> Result<DataTypeSpec> {
         let token = self.advance()?;
         let start = token.span.start;
 
         let kind = match &token.kind {
             TokenKind::Keyword(kw) => self.parse_data_type_from_keyword(*kw, token.span)?,
             TokenKind::Identifier(name) => {
-                // Named type or alias
-                let upper = name.to_uppercase();
-                self.parse_data_type_from_name(&upper, token.span)?
+                self.parse_data_type_from_name(Ident::new(name.clone(), token.span))?
+            }
+            TokenKind::QuotedIdentifier(name) => {
+                let name = quoted_ident(name.clone(), &token.text, token.span);
+                self.parse_custom_type(name)?
             }
             _ => {
                 return Err(Error::unexpected_token(
@@ -1078,7 +1332,7 @@ impl<'a> Parser<'a> {
             }
         };
 
-        let end = self.current_position();
+        let end = self.previous_end();
         Ok(DataTypeSpec::new(kind, Span::new(start, end)))
     }
 
@@ -1103,7 +1357,11 @@ impl<'a> Parser<'a> {
             Keyword::Float32 | Keyword::Real => Ok(DataTypeKind::Float32),
 
             // 64-bit float (DOUBLE, DOUBLE PRECISION, FLOAT, FLOAT64)
-            Keyword::Float64 | Keyword::Float | Keyword::Double => Ok(DataTypeKind::Float64),
+            Keyword::Float64 | Keyword::Float => Ok(DataTypeKind::Float64),
+            Keyword::Double => {
+                self.consume_keyword(Keyword::Precision)?;
+                Ok(DataTypeKind::Float64)
+            }
 
             // Fixed precision decimal (NUMERIC, DECIMAL)
             Keyword::Numeric | Keyword::Decimal => self.parse_numeric_type(),
@@ -1128,6 +1386,10 @@ impl<'a> Parser<'a> {
             // Other types
             Keyword::Json => Ok(DataTypeKind::Json),
             Keyword::Uuid => Ok(DataTypeKind::Uuid),
+            Keyword::Any => {
+                self.expect_keyword(Keyword::Type)?;
+                Ok(DataTypeKind::Any)
+            }
 
             // Complex types
             Keyword::Array => self.parse_array_type(),
@@ -1142,8 +1404,13 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_data_type_from_name(&mut self, name: &str, span: Span) -> Result<DataTypeKind> {
-        match name {
+    /// Parse a type named by an identifier: a built-in type or alias, or
+    /// else a custom type for the analyzer to resolve.
+    fn parse_data_type_from_name(&mut self, name: Ident) -> Result<DataTypeKind> {
+        if name.quoted {
+            return self.parse_custom_type(name);
+        }
+        match name.value.to_uppercase().as_str() {
             // Boolean
             "BOOL" | "BOOLEAN" => Ok(DataTypeKind::Bool),
 
@@ -1185,8 +1452,33 @@ impl<'a> Parser<'a> {
             "JSON" | "JSONB" => Ok(DataTypeKind::Json),
             "UUID" => Ok(DataTypeKind::Uuid),
 
-            _ => Err(Error::unexpected_token("data type", name, span)),
+            _ => self.parse_custom_type(name),
+        }
+    }
+
+    /// Parse the rest of a custom type name and its arguments, as in
+    /// `geo.GEOGRAPHY(4326)`.
+    fn parse_custom_type(&mut self, first: Ident) -> Result<DataTypeKind> {
+        let start = first.span.start;
+        let mut parts = vec![first];
+        while self.consume(&TokenKind::Dot)?.is_some() {
+            parts.push(self.parse_identifier()?);
         }
+        let end = parts.last().map(|p| p.span.end).unwrap_or(start);
+        let name = ObjectName::new(parts, Span::new(start, end));
+
+        let args = if self.consume(&TokenKind::LeftParen)?.is_some() {
+            let args = if self.check(&TokenKind::RightParen)? {
+                Vec::new()
+            } else {
+                self.parse_comma_separated(|p| p.parse_expression())?
+            };
+            self.expect(&TokenKind::RightParen)?;
+            args
+        } else {
+            Vec::new()
+        };
+        Ok(DataTypeKind::Custom(name, args))
     }
 
     fn parse_numeric_type(&mut self) -> Result<DataTypeKind> {
@@ -1231,20 +1523,24 @@ impl<'a> Parser<'a> {
     fn parse_array_type(&mut self) -> Result<DataTypeKind> {
         self.expect(&TokenKind::Lt)?;
         let element_type = self.parse_data_type()?;
-        self.expect(&TokenKind::Gt)?;
+        self.expect_closing_angle()?;
         Ok(DataTypeKind::Array(Box::new(element_type)))
     }
 
     fn parse_struct_type(&mut self) -> Result<DataTypeKind> {
+        // `STRUCT<>` lexes its brackets as the `<>` operator.
+        if self.consume(&TokenKind::LtGt)?.is_some() {
+            return Ok(DataTypeKind::Struct(Vec::new()));
+        }
         self.expect(&TokenKind::Lt)?;
 
-        let fields = if self.check(&TokenKind::Gt)? {
+        let fields = if self.check_closing_angle()? {
             Vec::new()
         } else {
             self.parse_comma_separated(|p| p.parse_struct_type_field())?
         };
 
-        self.expect(&TokenKind::Gt)?;
+        self.expect_closing_angle()?;
         Ok(DataTypeKind::Struct(fields))
     }
 
@@ -1259,14 +1555,16 @@ impl<'a> Parser<'a> {
         let (name, data_type) = if has_name {
             let maybe_name = self.parse_identifier()?;
             // Check if this was actually a type name
-            if self.check(&TokenKind::Comma)? || self.check(&TokenKind::Gt)? {
+            if self.check(&TokenKind::Comma)?
+                || self.check_closing_angle()?
+                || self.check(&TokenKind::LeftParen)?
+                || self.check(&TokenKind::Dot)?
+            {
                 // It was a type, not a field name
-                // Need to reparse as a type - for simplicity, treat as named type
-                let dt = DataTypeSpec::new(
-                    DataTypeKind::Named(vec![maybe_name.clone()]),
-                    maybe_name.span,
-                );
-                (None, dt)
+                let start = maybe_name.span.start;
+                let kind = self.parse_data_type_from_name(maybe_name)?;
+                let end = self.previous_end();
+                (None, DataTypeSpec::new(kind, Span::new(start, end)))
             } else {
                 let dt = self.parse_data_type()?;
                 (Some(maybe_name), dt)
@@ -1282,7 +1580,7 @@ impl<'a> Parser<'a> {
     fn parse_range_type(&mut self) -> Result<DataTypeKind> {
         self.expect(&TokenKind::Lt)?;
         let element_type = self.parse_data_type()?;
-        self.expect(&TokenKind::Gt)?;
+        self.expect_closing_angle()?;
         Ok(DataTypeKind::Range(Box::new(element_type)))
     }
 
@@ -1299,6 +1597,70 @@ impl<'a> Parser<'a> {
     }
 }
 
+/// The precedence of comparison operators, including `<=>`, which bind
+/// more loosely than arithmetic and bitwise operators.
+const COMPARISON_PRECEDENCE: u8 = 4;
+
+/// The minimum precedence of operands inside postfix forms, such as the
+/// bounds of BETWEEN, which is above every binary operator.
+const POSTFIX_PRECEDENCE: u8 = 11;
+
+/// The minimum precedence of a `NOT` operand, which takes comparisons and
+/// the operators above them but stops at `AND` and `OR`.
+const NOT_PRECEDENCE: u8 = 3;
+
+/// Get binary operator and its precedence from token.
+fn binary_op(kind: &TokenKind) -> Option<(BinaryOp, u8)> {
+    let op = match kind {
+        TokenKind::Plus => BinaryOp::Plus,
+        TokenKind::Minus => BinaryOp::Minus,
+        TokenKind::Star => BinaryOp::Multiply,
+        TokenKind::Slash => BinaryOp::Divide,
+        TokenKind::Percent => BinaryOp::Modulo,
+        TokenKind::Eq => BinaryOp::Eq,
+        TokenKind::NotEq | TokenKind::LtGt => BinaryOp::NotEq,
+        TokenKind::Lt => BinaryOp::Lt,
+        TokenKind::LtEq => BinaryOp::LtEq,
+        TokenKind::Gt => BinaryOp::Gt,
+        TokenKind::GtEq => BinaryOp::GtEq,
+        TokenKind::Ampersand => BinaryOp::BitwiseAnd,
+        TokenKind::Pipe => BinaryOp::BitwiseOr,
+        TokenKind::Caret => BinaryOp::BitwiseXor,
+        TokenKind::LeftShift => BinaryOp::LeftShift,
+        TokenKind::RightShift => BinaryOp::RightShift,
+        TokenKind::DoublePipe => BinaryOp::Concat,
+        _ => return None,
+    };
+    Some((op, op.precedence()))
+}
+
+/// A postfix operator, classified before any of its tokens are consumed.
+#[derive(Debug, Clone, Copy)]
+enum Postfix {
+    FieldAccess,
+    Subscript,
+    Between {
+        negated: bool,
+    },
+    In {
+        negated: bool,
+    },
+    Like {
+        negated: bool,
+        case_insensitive: bool,
+    },
+    SimilarTo {
+        negated: bool,
+    },
+    Is,
+}
+
+/// What follows an opening parenthesis in expression position.
+enum ParenContents {
+    Expr(Box<Expr>),
+    Query(Query),
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1311,6 +1673,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)] // 3.14 is a literal, not PI
     fn test_literals() {
         let expr = parse_expr("42");
         assert!(matches!(expr.kind, ExprKind::Integer(42)));
@@ -1319,7 +1682,7 @@ mod tests {
         assert!(matches!(expr.kind, ExprKind::Float(f) if (f - 3.14).abs() < 0.001));
 
         let expr = parse_expr("'hello'");
-        assert!(matches!(expr.kind, ExprKind::String(s) if s == "hello"));
+        assert!(matches!(&expr.kind, ExprKind::String(s) if s == "hello"));
 
         let expr = parse_expr("TRUE");
         assert!(matches!(expr.kind, ExprKind::Boolean(true)));
@@ -1374,6 +1737,17 @@ mod tests {
                 ..
             }
         ));
+
+        // Comparisons bind more loosely than arithmetic and bitwise operators
+        for sql in ["a + 1 = b * 2", "a | b < c & d", "a << 1 >= b || c"] {
+            let expr = parse_expr(sql);
+            let ExprKind::BinaryOp { op, left, right } = &expr.kind else {
+                panic!("{}: expected a comparison, got {:?}", sql, expr.kind);
+            };
+            assert_eq!(op.precedence(), BinaryOp::Eq.precedence(), "{}", sql);
+            assert!(matches!(left.kind, ExprKind::BinaryOp { .. }), "{}", sql);
+            assert!(matches!(right.kind, ExprKind::BinaryOp { .. }), "{}", sql);
+        }
     }
 
     #[test]
@@ -1406,6 +1780,80 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_not_precedence() {
+        // NOT takes a whole comparison, but stops at AND and OR
+        let expr = parse_expr("NOT a = 1 AND NOT b IS NULL OR c");
+        assert_eq!(
+            expr.to_string(),
+            "NOT a = 1 AND NOT b IS NULL OR c",
+            "{:?}",
+            expr
+        );
+        let ExprKind::BinaryOp {
+            op: BinaryOp::Or,
+            left,
+            ..
+        } = &expr.kind
+        else {
+            panic!("expected OR, got {:?}", expr.kind);
+        };
+        let ExprKind::BinaryOp {
+            op: BinaryOp::And,
+            left,
+            right,
+        } = &left.kind
+        else {
+            panic!("expected AND, got {:?}", left.kind);
+        };
+        let ExprKind::UnaryOp {
+            op: UnaryOp::Not,
+            expr: negated,
+        } = &left.kind
+        else {
+            panic!("expected NOT, got {:?}", left.kind);
+        };
+        assert!(matches!(
+            negated.kind,
+            ExprKind::BinaryOp {
+                op: BinaryOp::Eq,
+                ..
+            }
+        ));
+        let ExprKind::UnaryOp {
+            op: UnaryOp::Not,
+            expr: negated,
+        } = &right.kind
+        else {
+            panic!("expected NOT, got {:?}", right.kind);
+        };
+        assert!(matches!(negated.kind, ExprKind::IsExpr { .. }));
+
+        // After an operator, NOT takes only what that operator's right
+        // operand would, and inside a prefix operator only a unary operand
+        let expr = parse_expr("a = NOT b = c");
+        let ExprKind::BinaryOp {
+            op: BinaryOp::Eq,
+            left,
+            ..
+        } = &expr.kind
+        else {
+            panic!("expected =, got {:?}", expr.kind);
+        };
+        assert!(matches!(
+            &left.kind,
+            ExprKind::BinaryOp { right, .. } if matches!(right.kind, ExprKind::UnaryOp { .. })
+        ));
+        let expr = parse_expr("-NOT a = 1");
+        assert!(matches!(
+            expr.kind,
+            ExprKind::BinaryOp {
+                op: BinaryOp::Eq,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_function_call() {
         let expr = parse_expr("COUNT(*)");
@@ -1418,6 +1866,25 @@ mod tests {
 
         let expr = parse_expr("UPPER('hello')");
         assert!(matches!(expr.kind, ExprKind::Function(_)));
+
+        // A typed literal keyword before `(` names a conversion function
+        for sql in [
+            "DATE(ts)",
+            "TIME(ts)",
+            "DATETIME(d, t)",
+            "TIMESTAMP(s)",
+            "JSON(s)",
+        ] {
+            assert!(
+                matches!(parse_expr(sql).kind, ExprKind::Function(_)),
+                "{}",
+                sql
+            );
+        }
+        assert!(matches!(
+            parse_expr("DATE '2024-01-01'").kind,
+            ExprKind::TypedLiteral { .. }
+        ));
     }
 
     #[test]
@@ -1436,6 +1903,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cast_to_custom_type() {
+        let cast_type = |sql: &str| match &parse_expr(sql).kind {
+            ExprKind::Cast { data_type, .. } => data_type.clone(),
+            kind => panic!("Expected Cast, got {:?}", kind),
+        };
+
+        let dt = cast_type("CAST(x AS geo.Geography(4326, 'wgs84'))");
+        let DataTypeKind::Custom(name, args) = &dt.kind else {
+            panic!("Expected custom type, got {:?}", dt.kind);
+        };
+        assert_eq!(name.to_string(), "geo.Geography");
+        assert_eq!(args.len(), 2);
+        assert_eq!(dt.kind.to_string(), "geo.Geography(4326, 'wgs84')");
+
+        let dt = cast_type("CAST(x AS `email address`)");
+        assert!(matches!(&dt.kind, DataTypeKind::Custom(name, args)
+            if name.parts[0].quoted && args.is_empty()));
+
+        // Built-in names still parse as built-in types, and a bare name in
+        // a STRUCT is a field type.
+        assert_eq!(cast_type("CAST(x AS int64)").kind, DataTypeKind::Int64);
+        let dt = cast_type("CAST(x AS STRUCT<EMAIL, INT64, p POINT(2)>)");
+        let DataTypeKind::Struct(fields) = &dt.kind else {
+            panic!("Expected struct, got {:?}", dt.kind);
+        };
+        assert!(fields[0].name.is_none());
+        assert!(matches!(fields[0].data_type.kind, DataTypeKind::Custom(..)));
+        assert_eq!(fields[1].data_type.kind, DataTypeKind::Int64);
+        assert_eq!(fields[2].data_type.kind.to_string(), "POINT(2)");
+    }
+
+    #[test]
+    fn test_cast_to_nested_type() {
+        let cast_type = |sql: &str| match &parse_expr(sql).kind {
+            ExprKind::Cast { data_type, .. } => data_type.kind.to_string(),
+            kind => panic!("Expected Cast, got {:?}", kind),
+        };
+
+        // `>>` closes two type parameter lists.
+        assert_eq!(
+            cast_type("CAST(x AS ARRAY<ARRAY<INT64>>)"),
+            "ARRAY<ARRAY<BIGINT>>"
+        );
+        assert_eq!(
+            cast_type("CAST(x AS STRUCT<a ARRAY<EMAIL>>)"),
+            "STRUCT<a ARRAY<EMAIL>>"
+        );
+        assert_eq!(
+            cast_type("CAST(x AS ARRAY<STRUCT<RANGE<DATE>>>)"),
+            "ARRAY<STRUCT<RANGE<DATE>>>"
+        );
+        // The display form of FLOAT64 parses back.
+        assert_eq!(cast_type("CAST(x AS DOUBLE PRECISION)"), "DOUBLE PRECISION");
+        // `<>` is the empty parameter list of STRUCT.
+        assert_eq!(cast_type("CAST(x AS STRUCT<>)"), "STRUCT<>");
+        assert_eq!(cast_type("CAST(x AS ARRAY<STRUCT<>>)"), "ARRAY<STRUCT<>>");
+        assert_eq!(cast_type("CAST(x AS ARRAY<ANY TYPE>)"), "ARRAY<ANY TYPE>");
+    }
+
     #[test]
     fn test_between() {
         let expr = parse_expr("x BETWEEN 1 AND 10");
@@ -1452,6 +1979,19 @@ mod tests {
     fn test_in_list() {
         let expr = parse_expr("x IN (1, 2, 3)");
         assert!(matches!(expr.kind, ExprKind::In { negated: false, .. }));
+
+        let expr = parse_expr("x NOT IN UNNEST(tags)");
+        assert!(matches!(
+            expr.kind,
+            ExprKind::In {
+                negated: true,
+                list: InList::Unnest(_),
+                ..
+            }
+        ));
+        for sql in ["x IN UNNEST tags", "x IN UNNEST()", "x IN UNNEST(a, b)"] {
+            assert!(Parser::new(sql).parse_expression().is_err(), "{}", sql);
+        }
     }
 
     #[test]
@@ -1460,6 +2000,63 @@ mod tests {
         assert!(matches!(expr.kind, ExprKind::Like { negated: false, .. }));
     }
 
+    #[test]
+    fn test_ilike_similar_to_and_quantified_like() {
+        let expr = parse_expr("name NOT ILIKE 'j%' ESCAPE '!'");
+        assert!(matches!(
+            expr.kind,
+            ExprKind::Like {
+                negated: true,
+                case_insensitive: true,
+                escape: Some(_),
+                ..
+            }
+        ));
+        assert_eq!(expr.span.end, "name NOT ILIKE 'j%' ESCAPE '!'".len());
+
+        let expr = parse_expr("name NOT SIMILAR TO '(a|b)%'");
+        assert!(matches!(
+            expr.kind,
+            ExprKind::SimilarTo { negated: true, .. }
+        ));
+        assert_eq!(expr.to_string(), "name NOT SIMILAR TO '(a|b)%'");
+
+        let expr = parse_expr("name ILIKE ALL ('a%', '%z') ESCAPE '!' AND x LIKE ANY (y)");
+        let ExprKind::BinaryOp { left, right, .. } = &expr.kind else {
+            panic!("expected AND, got {:?}", expr.kind);
+        };
+        let ExprKind::QuantifiedLike {
+            quantifier: Quantifier::All,
+            patterns,
+            escape: Some(_),
+            case_insensitive: true,
+            ..
+        } = &left.kind
+        else {
+            panic!("expected ILIKE ALL, got {:?}", left.kind);
+        };
+        assert_eq!(patterns.len(), 2);
+        assert!(matches!(
+            right.kind,
+            ExprKind::QuantifiedLike {
+                quantifier: Quantifier::Any,
+                case_insensitive: false,
+                ..
+            }
+        ));
+        assert_eq!(
+            expr.to_string(),
+            "name ILIKE ALL ('a%', '%z') ESCAPE '!' AND x LIKE ANY (y)"
+        );
+
+        // SIMILAR is not reserved: without TO it is a name.
+        let expr = parse_expr("similar");
+        assert!(matches!(expr.kind, ExprKind::Identifier(_)));
+        assert!(Parser::new("SELECT a LIKE ANY ()")
+            .parse_statement()
+            .is_err());
+    }
+
     #[test]
     fn test_is_null() {
         let expr = parse_expr("x IS NULL");
@@ -1483,6 +2080,122 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_is_boolean_tests() {
+        let expr = parse_expr("x IS NOT UNKNOWN");
+        assert!(matches!(
+            expr.kind,
+            ExprKind::IsExpr {
+                test: IsTest::Unknown,
+                negated: true,
+                ..
+            }
+        ));
+        assert_eq!(expr.to_string(), "x IS NOT UNKNOWN");
+        assert_eq!(expr.span.end, "x IS NOT UNKNOWN".len());
+
+        // Each IS binds to the operand before it, and tighter than AND.
+        let expr = parse_expr("a IS NULL AND b IS NOT TRUE");
+        let ExprKind::BinaryOp { left, right, .. } = &expr.kind else {
+            panic!("expected AND, got {:?}", expr.kind);
+        };
+        assert!(matches!(
+            left.kind,
+            ExprKind::IsExpr {
+                test: IsTest::Null,
+                ..
+            }
+        ));
+        assert!(matches!(
+            right.kind,
+            ExprKind::IsExpr {
+                test: IsTest::True,
+                negated: true,
+                ..
+            }
+        ));
+        assert_eq!(expr.to_string(), "a IS NULL AND b IS NOT TRUE");
+
+        let expr = parse_expr("x IS FALSE IS NOT NULL");
+        assert_eq!(expr.to_string(), "x IS FALSE IS NOT NULL");
+
+        assert!(Parser::new("SELECT x IS MAYBE").parse_statement().is_err());
+    }
+
+    #[test]
+    fn test_is_distinct_from() {
+        let expr = parse_expr("a IS NOT DISTINCT FROM -b");
+        let ExprKind::IsDistinct {
+            right,
+            negated: true,
+            ..
+        } = &expr.kind
+        else {
+            panic!("expected IS NOT DISTINCT FROM, got {:?}", expr.kind);
+        };
+        assert!(matches!(right.kind, ExprKind::UnaryOp { .. }));
+
+        // `<=>` is the same node, binding like `=`.
+        let expr = parse_expr("a <=> b AND c IS DISTINCT FROM NULL");
+        let ExprKind::BinaryOp { left, right, .. } = &expr.kind else {
+            panic!("expected AND, got {:?}", expr.kind);
+        };
+        assert!(matches!(
+            left.kind,
+            ExprKind::IsDistinct { negated: true, .. }
+        ));
+        assert!(matches!(
+            right.kind,
+            ExprKind::IsDistinct { negated: false, .. }
+        ));
+        assert_eq!(
+            expr.to_string(),
+            "a IS NOT DISTINCT FROM b AND c IS DISTINCT FROM NULL"
+        );
+
+        assert!(Parser::new("SELECT a IS DISTINCT b")
+            .parse_statement()
+            .is_err());
+        assert!(Parser::new("SELECT a <=> <=> b").parse_statement().is_err());
+    }
+
+    #[test]
+    fn test_quantified_comparison() {
+        let expr = parse_expr("age > ALL (SELECT age FROM users) AND id = SOME (1, 2)");
+        let ExprKind::BinaryOp { left, right, .. } = &expr.kind else {
+            panic!("expected AND, got {:?}", expr.kind);
+        };
+        assert!(matches!(
+            left.kind,
+            ExprKind::QuantifiedComparison {
+                op: BinaryOp::Gt,
+                quantifier: Quantifier::All,
+                list: InList::Subquery(_),
+                ..
+            }
+        ));
+        let ExprKind::QuantifiedComparison {
+            quantifier: Quantifier::Some,
+            list: InList::Values(values),
+            ..
+        } = &right.kind
+        else {
+            panic!("expected = SOME, got {:?}", right.kind);
+        };
+        assert_eq!(values.len(), 2);
+        assert_eq!(
+            expr.to_string(),
+            "age > ALL (SELECT age FROM users) AND id = SOME (1, 2)"
+        );
+        assert_eq!(left.span.end, "age > ALL (SELECT age FROM users)".len());
+
+        let expr = parse_expr("x <> ANY (tags)");
+        assert_eq!(expr.to_string(), "x != ANY (tags)");
+
+        assert!(Parser::new("SELECT a = ANY b").parse_statement().is_err());
+        assert!(Parser::new("SELECT a + ANY (1)").parse_statement().is_err());
+    }
+
     #[test]
     fn test_array_literal() {
         let expr = parse_expr("[1, 2, 3]");
@@ -1507,4 +2220,79 @@ mod tests {
             panic!("Expected BinaryOp");
         }
     }
+
+    fn frame_exclusion(sql: &str) -> Option<FrameExclusion> {
+        let expr = parse_expr(sql);
+        let ExprKind::WindowFunction(wf) = &expr.kind else {
+            panic!("Expected WindowFunction");
+        };
+        let WindowSpecOrRef::Spec(spec) = &wf.window else {
+            panic!("Expected inline window spec");
+        };
+        spec.frame.as_ref().expect("Expected frame").exclusion
+    }
+
+    #[test]
+    fn test_window_frame_exclusion() {
+        let frame = "SUM(x) OVER (ORDER BY y ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING";
+        let cases = [
+            ("", None),
+            (" EXCLUDE CURRENT ROW", Some(FrameExclusion::CurrentRow)),
+            (" EXCLUDE GROUP", Some(FrameExclusion::Group)),
+            (" EXCLUDE TIES", Some(FrameExclusion::Ties)),
+            (" EXCLUDE NO OTHERS", Some(FrameExclusion::NoOthers)),
+        ];
+        for (clause, expected) in cases {
+            let sql = format!("{}{})", frame, clause);
+            assert_eq!(frame_exclusion(&sql), expected, "{}", sql);
+        }
+
+        assert_eq!(
+            frame_exclusion("SUM(x) OVER (ORDER BY y GROUPS CURRENT ROW EXCLUDE TIES)"),
+            Some(FrameExclusion::Ties)
+        );
+    }
+
+    #[test]
+    fn test_window_frame_exclusion_errors() {
+        for sql in [
+            "SUM(x) OVER (ORDER BY y EXCLUDE TIES)",
+            "SUM(x) OVER (ORDER BY y ROWS CURRENT ROW EXCLUDE)",
+            "SUM(x) OVER (ORDER BY y ROWS CURRENT ROW EXCLUDE NO)",
+        ] {
+            assert!(Parser::new(sql).parse_expression().is_err(), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_window_spec_base_and_spans() {
+        let sql = "SUM(x) OVER ( w ORDER BY y ROWS 2 PRECEDING)";
+        let expr = parse_expr(sql);
+        let ExprKind::WindowFunction(wf) = &expr.kind else {
+            panic!("Expected WindowFunction");
+        };
+        let WindowSpecOrRef::Spec(spec) = &wf.window else {
+            panic!("Expected inline window spec");
+        };
+        assert_eq!(spec.base.as_ref().map(|b| b.value.as_str()), Some("w"));
+        assert_eq!(
+            &sql[spec.span.start..spec.span.end],
+            "( w ORDER BY y ROWS 2 PRECEDING)"
+        );
+        let frame = spec.frame.as_ref().unwrap();
+        assert_eq!(&sql[frame.span.start..frame.span.end], "ROWS 2 PRECEDING");
+        assert_eq!(
+            expr.to_string(),
+            "SUM(x) OVER (w ORDER BY y ROWS 2 PRECEDING)"
+        );
+
+        let expr = parse_expr("RANK() OVER (PARTITION BY a)");
+        let ExprKind::WindowFunction(wf) = &expr.kind else {
+            panic!("Expected WindowFunction");
+        };
+        let WindowSpecOrRef::Spec(spec) = &wf.window else {
+            panic!("Expected inline window spec");
+        };
+        assert!(spec.base.is_none());
+    }
 }
@@ -0,0 +1,234 @@
+//! Optimizer hint parsing.
+//!
+//! The lexer turns a `/*+ ... */` comment right after SELECT, INSERT,
+//! UPDATE, or DELETE into a [`TokenKind::Hint`] token holding the comment
+//! text. That text is lexed again here as a list of `NAME` or
+//! `NAME(arg, ...)` hints, optionally separated by commas.
+
+use super::Parser;
+use crate::ast::*;
+use crate::error::{Error, Result, Span};
+use crate::lexer::{Lexer, Token, TokenKind};
+
+impl<'a> Parser<'a> {
+    /// Parse the optimizer hints, if any, after a statement keyword.
+    pub(super) fn parse_optimizer_hints(&mut self) -> Result<Vec<Hint>> {
+        let mut hints = Vec::new();
+        while matches!(self.peek()?.kind, TokenKind::Hint(_)) {
+            let token = self.advance()?;
+            if let TokenKind::Hint(text) = &token.kind {
+                // The text starts after the `/*+` opener.
+                hints.extend(parse_hints(text, token.span.start + 3)?);
+            }
+        }
+        Ok(hints)
+    }
+}
+
+/// Parse hint text found at byte `offset` of the source.
+fn parse_hints(text: &str, offset: usize) -> Result<Vec<Hint>> {
+    let shift = |span: Span| Span::new(span.start + offset, span.end + offset);
+    let tokens = Lexer::new(text).tokenize().map_err(|e| match e.span() {
+        Some(span) => Error::with_span(e.kind.clone(), shift(span)),
+        None => e,
+    })?;
+
+    let mut hints = Vec::new();
+    let mut i = 0;
+    while let Some(token) = tokens.get(i) {
+        i += 1;
+        let name = match &token.kind {
+            TokenKind::Comma => continue,
+            TokenKind::Identifier(_) | TokenKind::Keyword(_) => {
+                Ident::new(token.text.clone(), shift(token.span))
+            }
+            TokenKind::QuotedIdentifier(value) => Ident::quoted(value.clone(), shift(token.span)),
+            _ => {
+                return Err(Error::unexpected_token(
+                    "hint name",
+                    token.text.clone(),
+                    shift(token.span),
+                ))
+            }
+        };
+
+        let mut end = token.span.end;
+        let mut args = Vec::new();
+        if let Some(open) = tokens.get(i).filter(|t| t.kind == TokenKind::LeftParen) {
+            i += 1;
+            let mut depth = 0usize;
+            let mut arg_start = i;
+            loop {
+                let Some(t) = tokens.get(i) else {
+                    return Err(Error::unexpected_token(
+                        "')'",
+                        "end of hint",
+                        shift(open.span),
+                    ));
+                };
+                i += 1;
+                match t.kind {
+                    TokenKind::LeftParen => depth += 1,
+                    TokenKind::RightParen if depth > 0 => depth -= 1,
+                    TokenKind::Comma | TokenKind::RightParen if depth == 0 => {
+                        let arg = &tokens[arg_start..i - 1];
+                        if !arg.is_empty() {
+                            args.push(hint_arg(arg, text, &shift));
+                        }
+                        arg_start = i;
+                        if t.kind == TokenKind::RightParen {
+                            end = t.span.end;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        hints.push(Hint {
+            name,
+            args,
+            span: shift(Span::new(token.span.start, end)),
+        });
+    }
+    Ok(hints)
+}
+
+/// Classify the tokens of one hint argument.
+fn hint_arg(tokens: &[Token], text: &str, shift: &impl Fn(Span) -> Span) -> HintArg {
+    if let [token] = tokens {
+        match &token.kind {
+            TokenKind::Integer(_) | TokenKind::Float(_) => {
+                return HintArg::Number(token.text.clone())
+            }
+            TokenKind::String(value) => return HintArg::String(value.clone()),
+            _ => {}
+        }
+    }
+
+    // A name is identifiers separated by dots.
+    let mut parts = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let part = match &token.kind {
+            TokenKind::Dot if i % 2 == 1 => continue,
+            TokenKind::Identifier(_) | TokenKind::Keyword(_) if i % 2 == 0 => {
+                Ident::new(token.text.clone(), shift(token.span))
+            }
+            TokenKind::QuotedIdentifier(value) if i % 2 == 0 => {
+                Ident::quoted(value.clone(), shift(token.span))
+            }
+            _ => {
+                parts.clear();
+                break;
+            }
+        };
+        parts.push(part);
+    }
+    if tokens.len() % 2 == 1 && !parts.is_empty() {
+        let span = shift(Span::new(
+            tokens[0].span.start,
+            tokens[tokens.len() - 1].span.end,
+        ));
+        return HintArg::Name(ObjectName::new(parts, span));
+    }
+
+    let (start, end) = (tokens[0].span.start, tokens[tokens.len() - 1].span.end);
+    HintArg::Verbatim(text[start..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(sql: &str) -> Statement {
+        Parser::new(sql).parse().unwrap().remove(0)
+    }
+
+    fn select_hints(stmt: &Statement) -> &[Hint] {
+        let StatementKind::Query(query) = &stmt.kind else {
+            panic!("expected a query");
+        };
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected a SELECT");
+        };
+        &select.optimizer_hints
+    }
+
+    #[test]
+    fn test_select_hints() {
+        let sql = "SELECT /*+ BROADCAST_JOIN(t, db.u) MAX_EXECUTION_TIME(1000) */ \
+                   a FROM t /* not a hint */";
+        let stmt = parse_one(sql);
+        let hints = select_hints(&stmt);
+        assert_eq!(hints.len(), 2);
+        assert_eq!(hints[0].name.value, "BROADCAST_JOIN");
+        assert_eq!(
+            &sql[hints[0].span.start..hints[0].span.end],
+            "BROADCAST_JOIN(t, db.u)"
+        );
+        let HintArg::Name(name) = &hints[0].args[1] else {
+            panic!("expected a name");
+        };
+        assert_eq!(&sql[name.span.start..name.span.end], "db.u");
+        assert_eq!(hints[1].args, [HintArg::Number("1000".into())]);
+        assert_eq!(
+            stmt.to_string(),
+            "SELECT /*+ BROADCAST_JOIN(t, db.u) MAX_EXECUTION_TIME(1000) */ a FROM t"
+        );
+
+        // Unknown names, odd arguments, and commas between hints are kept.
+        let stmt = parse_one(
+            "SELECT /*+ INDEX(t idx), set_var(sort_buffer_size = 16), Label('x'), NO_MERGE */ 1",
+        );
+        let hints = select_hints(&stmt);
+        assert_eq!(
+            hints[0].args,
+            [HintArg::Verbatim("t idx".into())],
+            "{:?}",
+            hints
+        );
+        assert_eq!(hints[1].name.value, "set_var");
+        assert_eq!(hints[2].args, [HintArg::String("x".into())]);
+        assert!(hints[3].args.is_empty());
+        assert_eq!(
+            stmt.to_string(),
+            "SELECT /*+ INDEX(t idx) set_var(sort_buffer_size = 16) Label('x') NO_MERGE */ 1"
+        );
+
+        // Only a hint right after the keyword counts.
+        assert!(select_hints(&parse_one("SELECT 1 /*+ NO_MERGE */")).is_empty());
+        assert!(select_hints(&parse_one("SELECT /* c */ /*+ NO_MERGE */ 1")).is_empty());
+    }
+
+    #[test]
+    fn test_dml_hints() {
+        for sql in [
+            "INSERT /*+ APPEND */ INTO t VALUES (1)",
+            "UPDATE /*+ INDEX(t idx_a) */ t SET a = 1",
+            "DELETE /*+ PARALLEL(t, 4) */ FROM t WHERE a = 1",
+            "INSERT INTO t SELECT /*+ NO_MERGE */ * FROM u",
+        ] {
+            let stmt = parse_one(sql);
+            assert_eq!(stmt.to_string(), sql);
+        }
+        let StatementKind::Delete(delete) = parse_one("DELETE /*+ PARALLEL(t, 4) */ FROM t").kind
+        else {
+            panic!("expected DELETE");
+        };
+        assert_eq!(delete.optimizer_hints[0].args.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_hints() {
+        let sql = "SELECT /*+ 42 */ 1";
+        let err = Parser::new(sql).parse().unwrap_err();
+        let span = err.span().unwrap();
+        assert_eq!(&sql[span.start..span.end], "42");
+
+        let sql = "SELECT /*+ INDEX(t */ 1";
+        let err = Parser::new(sql).parse().unwrap_err();
+        let span = err.span().unwrap();
+        assert_eq!(&sql[span.start..span.end], "(");
+    }
+}
@@ -4,20 +4,142 @@
 //! into an Abstract Syntax Tree (AST).
 
 mod expr;
+mod hint;
 mod query;
+mod split;
 mod stmt;
 
+pub use split::{is_complete_statement, split_statements};
+
 use crate::ast::*;
-use crate::error::{Error, Result, Span};
+use crate::error::{Diagnostic, Error, ErrorKind, Result, Span, Suggestion};
 use crate::lexer::{Keyword, Lexer, Token, TokenKind};
 
+/// The most tokens the grammar ever inspects before consuming one.
+///
+/// Every lookahead in the parser is a fixed `peek_nth(1)` check (named
+/// arguments, parameter names, `NOT BETWEEN`, `SELECT AS STRUCT`), so
+/// parsing stays linear in the input no matter how long a list is.
+pub const MAX_LOOKAHEAD: usize = 2;
+
+/// The default limit on how deeply a parsed statement may nest.
+///
+/// Parsing, analyzing, and printing a statement this deep fits in a 2 MiB
+/// thread stack. Unoptimized builds use over ten times the stack per level
+/// that optimized builds do, so their limit is lower. See
+/// [`Parser::with_max_depth`].
+pub const DEFAULT_MAX_DEPTH: usize = if cfg!(debug_assertions) { 24 } else { 200 };
+
+/// Keywords that begin a statement, where error recovery can resume.
+const STATEMENT_KEYWORDS: [Keyword; 17] = [
+    Keyword::Select,
+    Keyword::With,
+    Keyword::Insert,
+    Keyword::Update,
+    Keyword::Delete,
+    Keyword::Merge,
+    Keyword::Create,
+    Keyword::Alter,
+    Keyword::Drop,
+    Keyword::Truncate,
+    Keyword::Begin,
+    Keyword::Commit,
+    Keyword::Rollback,
+    Keyword::Explain,
+    Keyword::Describe,
+    Keyword::Show,
+    Keyword::Set,
+];
+
+/// Counters describing the work done by a [`Parser`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParserMetrics {
+    /// Largest lookahead depth used, in tokens; at most [`MAX_LOOKAHEAD`].
+    pub max_lookahead: usize,
+}
+
+/// Dialect settings for a [`Parser`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Whether `"..."` is a quoted identifier, as in ANSI SQL and
+    /// PostgreSQL, rather than a string literal, as in BigQuery and MySQL.
+    /// See [`Lexer::with_double_quote_identifiers`].
+    pub double_quote_identifiers: bool,
+}
+
+impl ParserOptions {
+    /// Create options with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treat `"..."` as a quoted identifier instead of a string.
+    pub fn with_double_quote_identifiers(mut self, enabled: bool) -> Self {
+        self.double_quote_identifiers = enabled;
+        self
+    }
+}
+
+/// A parameter placeholder found while parsing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterRef {
+    /// The parameter.
+    pub parameter: Parameter,
+    /// Where the placeholder appears in the source.
+    pub span: Span,
+}
+
+/// Iterator over the statements of a [`Parser`], parsed on demand.
+///
+/// Created by [`Parser::iter_statements`].
+pub struct Statements<'p, 'a> {
+    parser: &'p mut Parser<'a>,
+    done: bool,
+}
+
+impl Iterator for Statements<'_, '_> {
+    type Item = Result<Statement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.parser.next_statement() {
+            Ok(Some(stmt)) => Some(Ok(stmt)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl std::iter::FusedIterator for Statements<'_, '_> {}
+
 /// SQL Parser.
 ///
 /// The parser converts SQL text into an Abstract Syntax Tree (AST).
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
-    #[allow(dead_code)]
     input: &'a str,
+    /// Non-fatal notes collected while parsing.
+    diagnostics: Vec<Diagnostic>,
+    /// Next node ID to assign; IDs are unique across one parser.
+    next_node_id: u32,
+    /// End offset of the most recently consumed token.
+    prev_end: usize,
+    /// Parameter placeholders in order of appearance.
+    parameters: Vec<ParameterRef>,
+    /// `?` placeholders seen so far in the current statement.
+    positional_count: u32,
+    /// Nesting depth at the current position.
+    depth: usize,
+    /// Deepest nesting allowed before parsing fails.
+    max_depth: usize,
 }
 
 impl<'a> Parser<'a> {
@@ -26,33 +148,273 @@ impl<'a> Parser<'a> {
         Self {
             lexer: Lexer::new(input),
             input,
+            diagnostics: Vec::new(),
+            next_node_id: 0,
+            prev_end: 0,
+            parameters: Vec::new(),
+            positional_count: 0,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Create a parser over a caller-provided token stream.
+    ///
+    /// This lets tools lex `source`, rewrite the tokens (for example to
+    /// substitute template placeholders) and parse the result. Tokens keep
+    /// whatever spans they carry, so errors on untouched tokens point into
+    /// `source`; inserted tokens should use [`Span::synthetic`]. Comment
+    /// tokens are ignored.
+    pub fn from_tokens(tokens: Vec<Token>, source: &'a str) -> Self {
+        Self {
+            lexer: Lexer::from_tokens(tokens, source),
+            input: source,
+            diagnostics: Vec::new(),
+            next_node_id: 0,
+            prev_end: 0,
+            parameters: Vec::new(),
+            positional_count: 0,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Use dialect `options` instead of the defaults.
+    ///
+    /// ```
+    /// use vibesql::parser::{Parser, ParserOptions};
+    ///
+    /// let sql = r#"SELECT "name" FROM "users""#;
+    /// let options = ParserOptions::new().with_double_quote_identifiers(true);
+    /// let stmt = Parser::new(sql).with_options(options).parse_statement().unwrap();
+    /// assert_eq!(stmt.to_string(), sql);
+    /// ```
+    ///
+    /// A parser made with [`from_tokens`](Self::from_tokens) parses the
+    /// tokens it was given, so lexing options have no effect on it.
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.lexer = self
+            .lexer
+            .with_double_quote_identifiers(options.double_quote_identifiers);
+        self
+    }
+
+    /// Limit how deeply a statement may nest, [`DEFAULT_MAX_DEPTH`] unless
+    /// set.
+    ///
+    /// Each parenthesis, subquery, prefix operator such as `NOT` or `-`,
+    /// and operand of a right-associative or higher-precedence operator
+    /// counts as a level. Flat chains, such as `a OR b OR c`, a run of
+    /// joins, or `UNION ALL` branches, are built in a loop and take no extra
+    /// levels however long they are. Deeper nesting fails with
+    /// [`ErrorKind::InvalidSyntax`] at the token where the limit was
+    /// reached, rather than overflowing the stack.
+    ///
+    /// ```
+    /// use vibesql::Parser;
+    ///
+    /// let sql = format!("SELECT {}1{}", "(".repeat(20), ")".repeat(20));
+    /// assert!(Parser::new(&sql).parse().is_ok());
+    /// assert!(Parser::new(&sql).with_max_depth(10).parse().is_err());
+    /// ```
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Non-fatal diagnostics produced so far, such as a projection item
+    /// that is probably missing a comma.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Parameter placeholders parsed so far, in order of appearance.
+    ///
+    /// `?` placeholders are numbered from 1 within each statement, so the
+    /// second statement of a script starts again at `Positional(1)`.
+    pub fn parameters(&self) -> &[ParameterRef] {
+        &self.parameters
+    }
+
+    /// Whether the parameters parsed so far mix `@name`, `?`, and `$n`
+    /// styles. Such input parses, but most drivers cannot bind it.
+    pub fn has_mixed_parameters(&self) -> bool {
+        let style = |p: &ParameterRef| std::mem::discriminant(&p.parameter);
+        self.parameters
+            .first()
+            .is_some_and(|first| self.parameters.iter().any(|p| style(p) != style(first)))
+    }
+
+    /// Counters for the work done so far.
+    pub fn metrics(&self) -> ParserMetrics {
+        ParserMetrics {
+            max_lookahead: self.lexer.max_lookahead(),
         }
     }
 
     /// Parse all statements from the input.
     pub fn parse(&mut self) -> Result<Vec<Statement>> {
-        let mut statements = Vec::new();
+        self.iter_statements().collect()
+    }
 
-        loop {
-            // Skip empty statements (standalone semicolons)
-            while self.consume(&TokenKind::Semicolon)?.is_some() {}
+    /// Parse statements one at a time, as the iterator is advanced.
+    ///
+    /// Each statement can be processed and dropped before the next is
+    /// parsed, so a long script never has to be held as ASTs all at once.
+    /// The iterator ends after the last statement or the first error.
+    ///
+    /// ```
+    /// use vibesql::Parser;
+    ///
+    /// let mut parser = Parser::new("SELECT 1; SELECT 2; SELECT FROM");
+    /// let mut statements = parser.iter_statements();
+    /// assert!(statements.next().unwrap().is_ok());
+    /// assert!(statements.next().unwrap().is_ok());
+    /// assert!(statements.next().unwrap().is_err());
+    /// assert!(statements.next().is_none());
+    /// ```
+    pub fn iter_statements(&mut self) -> Statements<'_, 'a> {
+        Statements {
+            parser: self,
+            done: false,
+        }
+    }
+
+    /// Parse the next statement, or return `None` at the end of input.
+    fn next_statement(&mut self) -> Result<Option<Statement>> {
+        // Skip empty statements (standalone semicolons)
+        while self.consume(&TokenKind::Semicolon)?.is_some() {}
+
+        if self.check_eof()? {
+            return Ok(None);
+        }
 
-            if self.check_eof()? {
+        let stmt = self.parse_statement()?;
+
+        // Optional semicolon between statements
+        self.consume(&TokenKind::Semicolon)?;
+
+        Ok(Some(stmt))
+    }
+
+    /// Parse all statements from the input, recovering from syntax errors.
+    ///
+    /// A statement that fails to parse is dropped and its error recorded;
+    /// parsing resumes after the next `;`, or at a statement keyword such as
+    /// `SELECT` that starts a line outside parentheses. Returns the
+    /// statements that parsed and every error, both in source order. Input
+    /// without errors gives the same statements as [`parse`](Self::parse).
+    ///
+    /// ```
+    /// use vibesql::Parser;
+    ///
+    /// let (statements, errors) = Parser::new("SELECT 1; SELECT FROM; SELECT 2").parse_with_recovery();
+    /// assert_eq!(statements.len(), 2);
+    /// assert_eq!(errors.len(), 1);
+    /// ```
+    pub fn parse_with_recovery(&mut self) -> (Vec<Statement>, Vec<Error>) {
+        let mut statements = Vec::new();
+        let mut errors = Vec::new();
+
+        while let Some(token) = self.peek_recovering(&mut errors) {
+            if token.is_eof() {
                 break;
             }
+            if token.kind == TokenKind::Semicolon {
+                let _ = self.advance();
+                continue;
+            }
+            let begin = self.previous_end();
+            match self.parse_statement() {
+                Ok(stmt) => statements.push(stmt),
+                Err(err) => {
+                    errors.push(err);
+                    self.synchronize(begin, &mut errors);
+                }
+            }
+        }
 
-            let stmt = self.parse_statement()?;
-            statements.push(stmt);
+        (statements, errors)
+    }
 
-            // Optional semicolon between statements
-            self.consume(&TokenKind::Semicolon)?;
+    /// Skip the rest of a statement that failed to parse.
+    ///
+    /// Stops after a `;`, or before a statement keyword that starts a line
+    /// outside parentheses. Such a keyword is skipped if nothing after
+    /// `begin`, where the statement started, has been consumed yet, so
+    /// recovery always makes progress.
+    fn synchronize(&mut self, begin: usize, errors: &mut Vec<Error>) {
+        let mut depth = 0usize;
+        while let Some(token) = self.peek_recovering(errors) {
+            match token.kind {
+                TokenKind::Eof => return,
+                TokenKind::Semicolon => {
+                    let _ = self.advance();
+                    return;
+                }
+                TokenKind::LeftParen => depth += 1,
+                TokenKind::RightParen => depth = depth.saturating_sub(1),
+                _ if depth == 0
+                    && self.previous_end() > begin
+                    && self.starts_statement_line(&token) =>
+                {
+                    return
+                }
+                _ => {}
+            }
+            let _ = self.advance();
         }
+    }
 
-        Ok(statements)
+    /// Peek at the next token, recording and skipping past lexer errors.
+    ///
+    /// Returns `None` if the lexer cannot make progress.
+    fn peek_recovering(&mut self, errors: &mut Vec<Error>) -> Option<Token> {
+        loop {
+            let position = self.current_position();
+            match self.peek() {
+                Ok(token) => return Some(token.clone()),
+                Err(err) => {
+                    errors.push(err);
+                    if self.current_position() == position {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether a token is a statement keyword with only whitespace before
+    /// it on its line.
+    fn starts_statement_line(&self, token: &Token) -> bool {
+        STATEMENT_KEYWORDS.iter().any(|&k| token.is_keyword(k))
+            && self.input.get(..token.span.start).is_some_and(|before| {
+                before
+                    .rsplit('\n')
+                    .next()
+                    .is_some_and(|line| line.trim().is_empty())
+            })
     }
 
     /// Parse a single statement.
+    ///
+    /// Every node of the returned statement is numbered; see
+    /// [`NodeId`](crate::ast::NodeId) for the numbering contract.
     pub fn parse_statement(&mut self) -> Result<Statement> {
+        self.positional_count = 0;
+        let mut stmt = self.parse_nested_statement()?;
+        assign_node_ids(&mut stmt, &mut self.next_node_id);
+        Ok(stmt)
+    }
+
+    /// Parse a statement nested inside another one, leaving numbering to
+    /// the enclosing [`Parser::parse_statement`] call.
+    fn parse_nested_statement(&mut self) -> Result<Statement> {
+        self.nested(Self::parse_statement_at_depth)
+    }
+
+    /// Parse a statement at the current nesting level.
+    fn parse_statement_at_depth(&mut self) -> Result<Statement> {
         let token = self.peek()?;
         let start = token.span.start;
 
@@ -110,6 +472,21 @@ impl<'a> Parser<'a> {
     // Parser utilities
     // ========================================================================
 
+    /// Run `f` one nesting level deeper, failing past the limit.
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= self.max_depth {
+            let span = self.peek()?.span;
+            return Err(Error::with_span(
+                ErrorKind::InvalidSyntax("expression nesting too deep".into()),
+                span,
+            ));
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
     /// Get the current position in the input.
     fn current_position(&self) -> usize {
         self.lexer.position()
@@ -122,12 +499,31 @@ impl<'a> Parser<'a> {
 
     /// Peek at the nth token ahead.
     fn peek_nth(&mut self, n: usize) -> Result<&Token> {
+        debug_assert!(
+            n < MAX_LOOKAHEAD,
+            "lookahead of {} exceeds MAX_LOOKAHEAD",
+            n + 1
+        );
         self.lexer.peek_nth(n)
     }
 
+    /// End offset of the most recently consumed token.
+    fn previous_end(&self) -> usize {
+        self.prev_end
+    }
+
+    /// Record a consumed token's end offset.
+    fn track(&mut self, token: Token) -> Token {
+        if !token.span.is_synthetic() {
+            self.prev_end = token.span.end;
+        }
+        token
+    }
+
     /// Advance to the next token and return it.
     fn advance(&mut self) -> Result<Token> {
-        self.lexer.next_token_result()
+        let token = self.lexer.next_token_result()?;
+        Ok(self.track(token))
     }
 
     /// Check if the next token is EOF.
@@ -147,22 +543,57 @@ impl<'a> Parser<'a> {
 
     /// Consume the next token if it matches.
     fn consume(&mut self, expected: &TokenKind) -> Result<Option<Token>> {
-        self.lexer.consume(expected)
+        let token = self.lexer.consume(expected)?;
+        Ok(token.map(|t| self.track(t)))
     }
 
     /// Consume a keyword if it matches.
     fn consume_keyword(&mut self, keyword: Keyword) -> Result<Option<Token>> {
-        self.lexer.consume_keyword(keyword)
+        let token = self.lexer.consume_keyword(keyword)?;
+        Ok(token.map(|t| self.track(t)))
     }
 
     /// Expect and consume a specific token.
     fn expect(&mut self, expected: &TokenKind) -> Result<Token> {
-        self.lexer.expect(expected)
+        let token = self.lexer.expect(expected)?;
+        Ok(self.track(token))
+    }
+
+    /// Check if the next token closes a type parameter list, as `>` or the
+    /// first half of `>>`.
+    fn check_closing_angle(&mut self) -> Result<bool> {
+        Ok(matches!(
+            self.peek()?.kind,
+            TokenKind::Gt | TokenKind::RightShift
+        ))
+    }
+
+    /// Expect and consume the `>` closing a type parameter list.
+    fn expect_closing_angle(&mut self) -> Result<Token> {
+        let token = self.lexer.expect_closing_angle()?;
+        Ok(self.track(token))
     }
 
     /// Expect and consume a specific keyword.
     fn expect_keyword(&mut self, keyword: Keyword) -> Result<Token> {
-        self.lexer.expect_keyword(keyword)
+        let token = self.lexer.expect_keyword(keyword)?;
+        Ok(self.track(token))
+    }
+
+    /// Reject a clause keyword that is immediately repeated, as in
+    /// `WHERE WHERE`. Call after consuming the first occurrence.
+    fn reject_duplicate_keyword(&mut self, keyword: Keyword) -> Result<()> {
+        let token = self.peek()?;
+        if token.is_keyword(keyword) {
+            let span = token.span;
+            let name = token.text.to_uppercase();
+            return Err(
+                Error::with_span(ErrorKind::DuplicateKeyword(name.clone()), span).with_suggestion(
+                    Suggestion::delete(format!("remove the repeated {}", name), span),
+                ),
+            );
+        }
+        Ok(())
     }
 
     /// Parse an identifier.
@@ -170,7 +601,7 @@ impl<'a> Parser<'a> {
         let token = self.advance()?;
         match token.kind {
             TokenKind::Identifier(name) => Ok(Ident::new(name, token.span)),
-            TokenKind::QuotedIdentifier(name) => Ok(Ident::quoted(name, token.span)),
+            TokenKind::QuotedIdentifier(name) => Ok(quoted_ident(name, &token.text, token.span)),
             TokenKind::Keyword(kw) if !kw.is_reserved() => Ok(Ident::new(token.text, token.span)),
             _ => Err(Error::expected_identifier(token.span)),
         }
@@ -181,15 +612,25 @@ impl<'a> Parser<'a> {
         let token = self.advance()?;
         match token.kind {
             TokenKind::Identifier(name) => Ok(Ident::new(name, token.span)),
-            TokenKind::QuotedIdentifier(name) => Ok(Ident::quoted(name, token.span)),
+            TokenKind::QuotedIdentifier(name) => Ok(quoted_ident(name, &token.text, token.span)),
             TokenKind::Keyword(_) => Ok(Ident::new(token.text, token.span)),
             _ => Err(Error::expected_identifier(token.span)),
         }
     }
 
     /// Parse an object name (possibly qualified: schema.table).
+    ///
+    /// A reserved keyword is accepted as a qualifier when a dot follows, so
+    /// the default schema can be written as `default.users`.
     fn parse_object_name(&mut self) -> Result<ObjectName> {
-        let mut parts = vec![self.parse_identifier()?];
+        let qualifier = matches!(self.peek()?.kind, TokenKind::Keyword(_))
+            && self.peek_nth(1)?.kind == TokenKind::Dot;
+        let first = if qualifier {
+            self.parse_identifier_allow_reserved()?
+        } else {
+            self.parse_identifier()?
+        };
+        let mut parts = vec![first];
         let start = parts[0].span.start;
 
         while self.consume(&TokenKind::Dot)?.is_some() {
@@ -252,6 +693,16 @@ impl<'a> Parser<'a> {
     }
 }
 
+/// The identifier for a quoted identifier token, with the quote style of
+/// its source `text`.
+fn quoted_ident(value: String, text: &str, span: Span) -> Ident {
+    if text.starts_with('"') {
+        Ident::double_quoted(value, span)
+    } else {
+        Ident::quoted(value, span)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -273,6 +724,130 @@ mod tests {
         assert!(stmts.is_empty());
     }
 
+    #[test]
+    fn test_double_quote_identifiers() {
+        let sql = r#"SELECT "name", "a""b" AS "Select", `c`, 'd' FROM "users""#;
+        let options = ParserOptions::new().with_double_quote_identifiers(true);
+        let stmt = Parser::new(sql)
+            .with_options(options)
+            .parse_statement()
+            .unwrap();
+        assert_eq!(stmt.to_string(), sql);
+
+        let StatementKind::Query(query) = &stmt.kind else {
+            panic!("expected a query");
+        };
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected a select");
+        };
+        let styles: Vec<Option<QuoteStyle>> = select
+            .projection
+            .iter()
+            .map(|item| match item {
+                SelectItem::Expr { expr, .. } => match &expr.kind {
+                    ExprKind::Identifier(ident) => {
+                        assert!(ident.quoted);
+                        Some(ident.quote_style)
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            styles,
+            [
+                Some(QuoteStyle::DoubleQuote),
+                Some(QuoteStyle::DoubleQuote),
+                Some(QuoteStyle::Backtick),
+                None,
+            ]
+        );
+
+        // By default double quotes delimit strings.
+        let stmt = parse_stmt(r#"SELECT "name" FROM t"#);
+        assert_eq!(stmt.to_string(), "SELECT 'name' FROM t");
+    }
+
+    #[test]
+    fn test_parameters_in_order() {
+        let sql = "SELECT * FROM users WHERE id = ? AND age > ?; SELECT ?, $2, $1";
+        let mut parser = Parser::new(sql);
+        parser.parse().unwrap();
+        let params: Vec<(String, &str)> = parser
+            .parameters()
+            .iter()
+            .map(|p| (format!("{:?}", p.parameter), &sql[p.span.start..p.span.end]))
+            .collect();
+        assert_eq!(
+            params,
+            [
+                ("Positional(1)".to_string(), "?"),
+                ("Positional(2)".to_string(), "?"),
+                ("Positional(1)".to_string(), "?"),
+                ("Numbered(2)".to_string(), "$2"),
+                ("Numbered(1)".to_string(), "$1"),
+            ]
+        );
+        let rendered: Vec<String> = parser
+            .parameters()
+            .iter()
+            .map(|p| p.parameter.to_string())
+            .collect();
+        assert_eq!(rendered, ["?", "?", "?", "$2", "$1"]);
+        assert!(parser.has_mixed_parameters());
+    }
+
+    #[test]
+    fn test_mixed_named_and_positional_parameters() {
+        let mut parser = Parser::new("SELECT @a, @b");
+        parser.parse().unwrap();
+        assert!(!parser.has_mixed_parameters());
+        assert_eq!(parser.parameters()[1].parameter.to_string(), "@b");
+
+        let mut parser = Parser::new("SELECT * FROM t WHERE a = @a AND b = ?");
+        parser.parse().unwrap();
+        assert!(parser.has_mixed_parameters());
+        assert!(parser.parameters()[0].parameter.is_named());
+        assert_eq!(parser.parameters()[1].parameter, Parameter::Positional(1));
+    }
+
+    #[test]
+    fn test_system_variables() {
+        let mut parser = Parser::new("SELECT @@version, @@SESSION.max_rows + @p");
+        let stmt = parser.parse_statement().unwrap();
+        assert_eq!(
+            stmt.to_string(),
+            "SELECT @@version, @@SESSION.max_rows + @p"
+        );
+        // System variables are not parameters.
+        assert_eq!(parser.parameters().len(), 1);
+
+        let StatementKind::Query(query) = &stmt.kind else {
+            panic!("expected a query");
+        };
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected a SELECT");
+        };
+        let SelectItem::Expr { expr, .. } = &select.projection[1] else {
+            panic!("expected an expression");
+        };
+        let ExprKind::BinaryOp { left, .. } = &expr.kind else {
+            panic!("expected a binary operator");
+        };
+        match &left.kind {
+            ExprKind::SystemVariable { scope, name } => {
+                assert_eq!(scope.as_ref().unwrap().value, "SESSION");
+                assert_eq!(name.value, "max_rows");
+                assert_eq!(left.span, Span::new(18, 36));
+            }
+            other => panic!("expected a system variable, got {:?}", other),
+        }
+
+        assert!(Parser::new("SELECT @@").parse_statement().is_err());
+        assert!(Parser::new("SELECT @@1").parse_statement().is_err());
+    }
+
     #[test]
     fn test_semicolons() {
         let stmts = parse_all(";;;");
@@ -284,4 +859,213 @@ mod tests {
         let stmts = parse_all("SELECT 1; SELECT 2; SELECT 3");
         assert_eq!(stmts.len(), 3);
     }
+
+    #[test]
+    fn test_parse_with_recovery() {
+        let sql = "SELECT 1;\n\
+                   SELECT a FROM WHERE;\n\
+                   SELECT (1 + FROM t\n\
+                   SELECT 2;\n\
+                   INSERT INTO t (a,, b) SELECT 3;\n\
+                   SELECT 'x' ! 4;\n\
+                   SELECT 5";
+        let (stmts, errors) = Parser::new(sql).parse_with_recovery();
+        let texts: Vec<String> = stmts.iter().map(|s| s.to_string()).collect();
+        assert_eq!(texts, ["SELECT 1", "SELECT 2", "SELECT 5"]);
+        let found: Vec<&str> = errors
+            .iter()
+            .map(|e| e.span().unwrap())
+            .map(|span| &sql[span.start..span.end])
+            .collect();
+        assert_eq!(found, ["WHERE", "FROM", ",", "!"]);
+
+        let (stmts, errors) = Parser::new("SELECT 1; SELECT 2").parse_with_recovery();
+        assert_eq!(stmts, parse_all("SELECT 1; SELECT 2"));
+        assert!(errors.is_empty());
+
+        let (stmts, errors) = Parser::new("SELECT 1; SELECT 'open").parse_with_recovery();
+        assert_eq!(stmts.len(), 1);
+        assert_eq!(errors.len(), 1);
+    }
+
+    /// Replace `{{name}}` placeholders with synthetic identifier tokens.
+    fn substitute(source: &str, bindings: &[(&str, &str)]) -> Vec<Token> {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let window = &tokens[i..tokens.len().min(i + 5)];
+            if let [open1, open2, name, close1, close2] = window {
+                if open1.kind == TokenKind::LeftBrace
+                    && open2.kind == TokenKind::LeftBrace
+                    && close1.kind == TokenKind::RightBrace
+                    && close2.kind == TokenKind::RightBrace
+                {
+                    let key = name.text.as_str();
+                    let value = bindings.iter().find(|(k, _)| *k == key).unwrap().1;
+                    out.push(Token::synthetic(
+                        TokenKind::Identifier(value.to_string()),
+                        value,
+                    ));
+                    i += 5;
+                    continue;
+                }
+            }
+            out.push(tokens[i].clone());
+            i += 1;
+        }
+        out
+    }
+
+    #[test]
+    fn test_from_tokens_placeholder_substitution() {
+        let source = "SELECT id FROM {{table}} WHERE id > 1";
+        let tokens = substitute(source, &[("table", "orders")]);
+        let stmt = Parser::from_tokens(tokens, source)
+            .parse_statement()
+            .unwrap();
+
+        let StatementKind::Query(query) = &stmt.kind else {
+            panic!("expected query");
+        };
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected select");
+        };
+        let TableRefKind::Table { name, .. } = &select.from.as_ref().unwrap().tables[0].kind else {
+            panic!("expected table");
+        };
+        assert_eq!(name.parts[0].value, "orders");
+        assert!(name.parts[0].span.is_synthetic());
+        assert_eq!(stmt.span, Span::new(0, source.len()));
+    }
+
+    #[test]
+    fn test_from_tokens_error_keeps_source_span() {
+        let source = "SELECT id FROM {{table}} WHERE id > )";
+        let tokens = substitute(source, &[("table", "orders")]);
+        let err = Parser::from_tokens(tokens, source)
+            .parse_statement()
+            .unwrap_err();
+        let offset = source.find(')').unwrap();
+        assert_eq!(err.span(), Some(Span::new(offset, offset + 1)));
+    }
+
+    #[test]
+    fn test_lookahead_stays_bounded() {
+        let corpus = [
+            "SELECT a, b FROM t WHERE a NOT BETWEEN 1 AND 2",
+            "SELECT AS STRUCT 1 AS x",
+            "SELECT f(a => 1, b => 2), g(x, y)",
+            "CREATE FUNCTION add(x INT64, y INT64) RETURNS INT64 AS (x + y)",
+            "CREATE PROCEDURE p(IN a INT64, OUT b STRING) BEGIN SELECT 1; END",
+            "WITH c AS (SELECT 1) SELECT * FROM c JOIN t USING (id) ORDER BY 1",
+            "INSERT INTO t (a, b) VALUES (1, 2), (3, 4)",
+            "UPDATE t SET a = 1 WHERE b NOT IN (1, 2)",
+        ];
+        for sql in corpus {
+            let mut parser = Parser::new(sql);
+            parser.parse().unwrap_or_else(|e| panic!("{}: {}", sql, e));
+            let depth = parser.metrics().max_lookahead;
+            assert!(depth <= MAX_LOOKAHEAD, "{}: lookahead {}", sql, depth);
+        }
+
+        let mut parser = Parser::new("SELECT f(a => 1)");
+        parser.parse().unwrap();
+        assert_eq!(parser.metrics().max_lookahead, 2);
+    }
+
+    #[test]
+    fn test_long_parameter_list_lookahead() {
+        let params: Vec<String> = (0..2000).map(|i| format!("p{} INT64", i)).collect();
+        let sql = format!(
+            "CREATE FUNCTION f({}) RETURNS INT64 AS (1)",
+            params.join(", ")
+        );
+        let mut parser = Parser::new(&sql);
+        parser.parse().unwrap();
+        assert_eq!(parser.metrics().max_lookahead, MAX_LOOKAHEAD);
+    }
+
+    #[test]
+    fn test_long_operator_chain() {
+        let terms: Vec<String> = (0..10_000).map(|i| format!("c{}", i)).collect();
+        let sql = format!("SELECT {}", terms.join(" + "));
+        let stmt = Parser::new(&sql).parse_statement().unwrap();
+        let StatementKind::Query(query) = &stmt.kind else {
+            panic!("expected a query");
+        };
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected a SELECT");
+        };
+        let SelectItem::Expr { expr, .. } = &select.projection[0] else {
+            panic!("expected an expression");
+        };
+        // Left-associative: the last term is the outermost right operand.
+        let ExprKind::BinaryOp { op, right, .. } = &expr.kind else {
+            panic!("expected a binary operator");
+        };
+        assert_eq!(*op, BinaryOp::Plus);
+        assert!(matches!(&right.kind, ExprKind::Identifier(id) if id.value == "c9999"));
+    }
+
+    #[test]
+    fn test_nesting_depth_limit() {
+        let too_deep = |sql: &str| {
+            let err = Parser::new(sql).parse().unwrap_err();
+            assert_eq!(
+                err.kind,
+                ErrorKind::InvalidSyntax("expression nesting too deep".into()),
+                "{}",
+                &sql[..40]
+            );
+            err.span().unwrap()
+        };
+        let repeat = |n: usize, item: &str| item.repeat(n);
+
+        // Far deeper than the stack would allow if parsing recursed on.
+        let n = 100_000;
+        let span = too_deep(&format!("SELECT {}1{}", repeat(n, "("), repeat(n, ")")));
+        assert_eq!(span.start, span.end - 1, "span of a single token");
+        too_deep(&format!("SELECT {}1", repeat(n, "NOT -")));
+        too_deep(&format!("SELECT {}1", repeat(n, "(SELECT ")));
+        too_deep(&format!("SELECT * FROM {}t", repeat(n, "(")));
+        too_deep(&format!("{}SELECT 1", repeat(n, "EXPLAIN ")));
+
+        too_deep(&format!(
+            "SELECT {}1{}",
+            repeat(1000, "("),
+            repeat(1000, ")")
+        ));
+        too_deep(&format!(
+            "SELECT {}1{}",
+            repeat(1000, "(SELECT "),
+            repeat(1000, ")")
+        ));
+
+        // Flat chains take no levels, however long.
+        let parses = |sql: &str| {
+            if let Err(err) = Parser::new(sql).parse() {
+                panic!("{}: {}", &sql[..40], err);
+            }
+        };
+        let terms: Vec<String> = (0..1000).map(|i| format!("a = {}", i)).collect();
+        parses(&format!("SELECT * FROM t WHERE {}", terms.join(" OR ")));
+        parses(&format!("SELECT * FROM t WHERE {}", terms.join(" AND ")));
+        parses(&format!(
+            "SELECT * FROM t{}",
+            repeat(1000, " JOIN t USING (a)")
+        ));
+        parses(&format!("SELECT 1{}", repeat(1000, " UNION ALL SELECT 1")));
+        parses(&format!("SELECT 1{}", repeat(1000, " INTERSECT SELECT 1")));
+        parses(&format!("SELECT a{}", repeat(1000, "[0]")));
+        parses(&format!("SELECT 'a'{}", repeat(1000, " || 'a'")));
+
+        // The limit is per level, not per statement.
+        let wide: Vec<String> = (0..1000).map(|i| format!("((a + {}))", i)).collect();
+        parses(&format!("SELECT {}", wide.join(", ")));
+        let n = DEFAULT_MAX_DEPTH - 3;
+        let sql = format!("SELECT {}1{}", repeat(n, "("), repeat(n, ")"));
+        parses(&sql);
+        assert!(Parser::new(&sql).with_max_depth(n / 2).parse().is_err());
+    }
 }
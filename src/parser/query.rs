@@ -2,12 +2,17 @@
 
 use super::Parser;
 use crate::ast::*;
-use crate::error::{Error, Result, Span};
+use crate::error::{Diagnostic, Error, ErrorKind, Result, Span, Suggestion};
 use crate::lexer::{Keyword, TokenKind};
 
 impl<'a> Parser<'a> {
     /// Parse a complete query (WITH clause, SELECT, set operations, ORDER BY, LIMIT).
     pub fn parse_query(&mut self) -> Result<Query> {
+        self.nested(Self::parse_query_at_depth)
+    }
+
+    /// Parse a query at the current nesting level.
+    fn parse_query_at_depth(&mut self) -> Result<Query> {
         let start = self.current_position();
 
         // Parse optional WITH clause
@@ -19,7 +24,65 @@ impl<'a> Parser<'a> {
 
         // Parse the query body
         let body = self.parse_query_body()?;
+        self.parse_query_tail(start, with, body)
+    }
+
+    /// Whether the next token begins a query without a leading parenthesis.
+    ///
+    /// Every position that accepts either a query or something else after
+    /// an opening parenthesis asks this, so they all agree on what a
+    /// subquery can start with.
+    pub(super) fn check_query_start(&mut self) -> Result<bool> {
+        Ok(self.check_keyword(Keyword::Select)? || self.check_keyword(Keyword::With)?)
+    }
 
+    /// Parse a parenthesized subquery: `(query)`.
+    ///
+    /// Returns the query and the span of the parentheses.
+    pub(super) fn parse_subquery(&mut self) -> Result<(Query, Span)> {
+        let start = self.expect(&TokenKind::LeftParen)?.span.start;
+        let query = self.parse_query()?;
+        let end = self.expect(&TokenKind::RightParen)?.span.end;
+        Ok((query, Span::new(start, end)))
+    }
+
+    /// Whether the next token continues a query after a complete operand.
+    pub(super) fn check_query_continuation(&mut self) -> Result<bool> {
+        for keyword in [
+            Keyword::Union,
+            Keyword::Intersect,
+            Keyword::Except,
+            Keyword::Order,
+            Keyword::Limit,
+            Keyword::Offset,
+            Keyword::Fetch,
+        ] {
+            if self.check_keyword(keyword)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Continue a query whose first operand was parsed as a parenthesized
+    /// subquery.
+    ///
+    /// In `((SELECT 1) UNION (SELECT 2))` the inner `(SELECT 1)` can only be
+    /// told apart from an expression or table reference once the `UNION`
+    /// is seen, so callers parse it the usual way and hand it over here.
+    pub(super) fn continue_subquery(&mut self, first: Query, start: usize) -> Result<Query> {
+        let left = QueryBody::Parenthesized(Box::new(first));
+        let body = self.parse_set_operations(left)?;
+        self.parse_query_tail(start, None, body)
+    }
+
+    /// Parse ORDER BY and LIMIT after a query body.
+    fn parse_query_tail(
+        &mut self,
+        start: usize,
+        with: Option<WithClause>,
+        body: QueryBody,
+    ) -> Result<Query> {
         // Parse ORDER BY
         let order_by = if self.consume_keyword(Keyword::Order)?.is_some() {
             self.expect_keyword(Keyword::By)?;
@@ -38,6 +101,7 @@ impl<'a> Parser<'a> {
             order_by,
             limit,
             span: Span::new(start, end),
+            id: NodeId::UNASSIGNED,
         })
     }
 
@@ -72,9 +136,7 @@ impl<'a> Parser<'a> {
         };
 
         self.expect_keyword(Keyword::As)?;
-        self.expect(&TokenKind::LeftParen)?;
-        let query = Box::new(self.parse_query()?);
-        self.expect(&TokenKind::RightParen)?;
+        let query = Box::new(self.parse_subquery()?.0);
 
         let end = self.current_position();
         Ok(Cte {
@@ -87,45 +149,69 @@ impl<'a> Parser<'a> {
 
     /// Parse query body (SELECT, set operations, or parenthesized query).
     fn parse_query_body(&mut self) -> Result<QueryBody> {
-        let mut left = self.parse_query_primary()?;
+        let left = self.parse_query_primary()?;
+        self.parse_set_operations(left)
+    }
 
-        // Check for set operations
+    /// Parse set operations following a first operand.
+    ///
+    /// INTERSECT binds tighter than UNION and EXCEPT, which associate to the
+    /// left: `a UNION b INTERSECT c EXCEPT d` is `(a UNION (b INTERSECT c))
+    /// EXCEPT d`.
+    fn parse_set_operations(&mut self, left: QueryBody) -> Result<QueryBody> {
+        let mut left = self.parse_intersections(left)?;
         loop {
             let op = if self.consume_keyword(Keyword::Union)?.is_some() {
-                Some(SetOperator::Union)
-            } else if self.consume_keyword(Keyword::Intersect)?.is_some() {
-                Some(SetOperator::Intersect)
+                SetOperator::Union
             } else if self.consume_keyword(Keyword::Except)?.is_some() {
-                Some(SetOperator::Except)
-            } else {
-                None
-            };
-
-            if let Some(op) = op {
-                let all = self.consume_keyword(Keyword::All)?.is_some();
-                if !all {
-                    self.consume_keyword(Keyword::Distinct)?;
-                }
-                let right = self.parse_query_primary()?;
-                left = QueryBody::SetOperation {
-                    op,
-                    all,
-                    left: Box::new(left),
-                    right: Box::new(right),
-                };
+                SetOperator::Except
             } else {
                 break;
-            }
+            };
+            let all = self.parse_set_quantifier()?;
+            let right = self.parse_query_primary()?;
+            let right = self.parse_intersections(right)?;
+            left = QueryBody::SetOperation {
+                op,
+                all,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
         }
 
         Ok(left)
     }
 
+    /// Parse a left-associative chain of INTERSECT operations following a
+    /// first operand.
+    fn parse_intersections(&mut self, mut left: QueryBody) -> Result<QueryBody> {
+        while self.consume_keyword(Keyword::Intersect)?.is_some() {
+            let all = self.parse_set_quantifier()?;
+            let right = self.parse_query_primary()?;
+            left = QueryBody::SetOperation {
+                op: SetOperator::Intersect,
+                all,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    /// Parse the optional ALL or DISTINCT after a set operator, returning
+    /// whether it was ALL.
+    fn parse_set_quantifier(&mut self) -> Result<bool> {
+        let all = self.consume_keyword(Keyword::All)?.is_some();
+        if !all {
+            self.consume_keyword(Keyword::Distinct)?;
+        }
+        Ok(all)
+    }
+
     /// Parse a primary query (SELECT or parenthesized query).
     fn parse_query_primary(&mut self) -> Result<QueryBody> {
-        if self.consume(&TokenKind::LeftParen)?.is_some() {
-            let query = self.parse_query()?;
-            self.expect(&TokenKind::RightParen)?;
+        if self.check(&TokenKind::LeftParen)? {
+            let (query, _) = self.parse_subquery()?;
             Ok(QueryBody::Parenthesized(Box::new(query)))
         } else {
             let select = self.parse_select()?;
@@ -137,13 +223,22 @@ impl<'a> Parser<'a> {
     fn parse_select(&mut self) -> Result<Select> {
         let start = self.current_position();
         self.expect_keyword(Keyword::Select)?;
+        let optimizer_hints = self.parse_optimizer_hints()?;
+        self.reject_duplicate_keyword(Keyword::Select)?;
 
         // Parse SELECT AS STRUCT/VALUE (value table syntax)
         let select_as = self.parse_select_as()?;
 
         // Parse DISTINCT/ALL
         let distinct = if self.consume_keyword(Keyword::Distinct)?.is_some() {
-            Some(Distinct::Distinct)
+            if self.consume_keyword(Keyword::On)?.is_some() {
+                self.expect(&TokenKind::LeftParen)?;
+                let exprs = self.parse_comma_separated(|p| p.parse_expression())?;
+                self.expect(&TokenKind::RightParen)?;
+                Some(Distinct::On(exprs))
+            } else {
+                Some(Distinct::Distinct)
+            }
         } else if self.consume_keyword(Keyword::All)?.is_some() {
             Some(Distinct::All)
         } else {
@@ -151,10 +246,11 @@ impl<'a> Parser<'a> {
         };
 
         // Parse projection list
-        let projection = self.parse_comma_separated(|p| p.parse_select_item())?;
+        let projection = self.parse_projection()?;
 
         // Parse FROM clause
         let from = if self.consume_keyword(Keyword::From)?.is_some() {
+            self.reject_duplicate_keyword(Keyword::From)?;
             Some(self.parse_from_clause()?)
         } else {
             None
@@ -162,6 +258,7 @@ impl<'a> Parser<'a> {
 
         // Parse WHERE clause
         let where_clause = if self.consume_keyword(Keyword::Where)?.is_some() {
+            self.reject_duplicate_keyword(Keyword::Where)?;
             Some(self.parse_expression()?)
         } else {
             None
@@ -177,6 +274,7 @@ impl<'a> Parser<'a> {
 
         // Parse HAVING clause
         let having = if self.consume_keyword(Keyword::Having)?.is_some() {
+            self.reject_duplicate_keyword(Keyword::Having)?;
             Some(self.parse_expression()?)
         } else {
             None
@@ -207,7 +305,9 @@ impl<'a> Parser<'a> {
             having,
             qualify,
             window,
+            optimizer_hints,
             span: Span::new(start, end),
+            id: NodeId::UNASSIGNED,
         })
     }
 
@@ -241,6 +341,36 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse a SELECT item.
+    /// Parse the SELECT list, reporting a trailing comma before the next
+    /// clause as its own error.
+    fn parse_projection(&mut self) -> Result<Vec<SelectItem>> {
+        let mut items = vec![self.parse_select_item()?];
+
+        while let Some(comma) = self.consume(&TokenKind::Comma)? {
+            let next = self.peek()?;
+            let ends_list = match &next.kind {
+                TokenKind::Eof | TokenKind::Semicolon | TokenKind::RightParen => true,
+                TokenKind::Keyword(kw) => matches!(
+                    kw,
+                    Keyword::From
+                        | Keyword::Where
+                        | Keyword::Group
+                        | Keyword::Having
+                        | Keyword::Order
+                        | Keyword::Limit
+                ),
+                _ => false,
+            };
+            if ends_list {
+                return Err(Error::with_span(ErrorKind::TrailingComma, comma.span)
+                    .with_suggestion(Suggestion::delete("remove the comma", comma.span)));
+            }
+            items.push(self.parse_select_item()?);
+        }
+
+        Ok(items)
+    }
+
     pub(super) fn parse_select_item(&mut self) -> Result<SelectItem> {
         // Check for wildcard
         if self.consume(&TokenKind::Star)?.is_some() {
@@ -306,8 +436,40 @@ impl<'a> Parser<'a> {
         }
 
         // Parse optional alias
+        let explicit_as = self.check_keyword(Keyword::As)?;
         let alias = self.parse_optional_alias()?;
 
+        // `SELECT a b` is legal but usually a missing comma.
+        if let (false, Some(alias)) = (explicit_as, &alias) {
+            let column = match &expr.kind {
+                ExprKind::Identifier(ident) => Some(ident.value.clone()),
+                ExprKind::CompoundIdentifier(parts) => Some(
+                    parts
+                        .iter()
+                        .map(|p| p.value.as_str())
+                        .collect::<Vec<_>>()
+                        .join("."),
+                ),
+                _ => None,
+            };
+            if let Some(column) = column {
+                self.diagnostics.push(
+                    Diagnostic::new(
+                        format!(
+                            "'{}' is treated as an alias for '{}'; add a comma if both are columns",
+                            alias.value, column
+                        ),
+                        alias.span,
+                    )
+                    .with_suggestion(Suggestion::insert(
+                        "insert a comma",
+                        expr.span.end,
+                        ",",
+                    )),
+                );
+            }
+        }
+
         Ok(SelectItem::Expr { expr, alias })
     }
 
@@ -319,6 +481,11 @@ impl<'a> Parser<'a> {
 
     /// Parse a table reference.
     pub(super) fn parse_table_ref(&mut self) -> Result<TableRef> {
+        self.nested(Self::parse_joins)
+    }
+
+    /// Parse a table reference and the joins that follow it.
+    fn parse_joins(&mut self) -> Result<TableRef> {
         let mut left = self.parse_table_primary()?;
 
         // Parse joins
@@ -327,12 +494,16 @@ impl<'a> Parser<'a> {
             if let Some(jt) = join_type {
                 let right = self.parse_table_primary()?;
                 let condition = self.parse_join_condition(jt)?;
-                left = TableRef::Join {
-                    left: Box::new(left),
-                    right: Box::new(right),
-                    join_type: jt,
-                    condition,
-                };
+                let span = Span::new(left.span.start, self.previous_end());
+                left = TableRef::new(
+                    TableRefKind::Join {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                        join_type: jt,
+                        condition,
+                    },
+                    span,
+                );
             } else {
                 break;
             }
@@ -341,24 +512,199 @@ impl<'a> Parser<'a> {
         Ok(left)
     }
 
-    /// Parse a primary table reference.
+    /// Parse a primary table reference, with any PIVOT and UNPIVOT
+    /// operators applied to it.
     fn parse_table_primary(&mut self) -> Result<TableRef> {
+        let start = self.peek()?.span.start;
+        let kind = self.parse_table_primary_kind()?;
+        let mut table = TableRef::new(kind, Span::new(start, self.previous_end()));
+
+        // PIVOT and UNPIVOT are non-reserved, so they only start an
+        // operator when followed by its opening tokens.
+        loop {
+            let (opens_pivot, opens_unpivot) = match self.peek_nth(1)?.kind {
+                TokenKind::LeftParen => (true, true),
+                TokenKind::Keyword(Keyword::Include | Keyword::Exclude) => (false, true),
+                _ => (false, false),
+            };
+            let kind = if opens_pivot && self.check_keyword(Keyword::Pivot)? {
+                self.advance()?;
+                self.parse_pivot(table)?
+            } else if opens_unpivot && self.check_keyword(Keyword::Unpivot)? {
+                self.advance()?;
+                self.parse_unpivot(table)?
+            } else {
+                return Ok(table);
+            };
+            table = TableRef::new(kind, Span::new(start, self.previous_end()));
+        }
+    }
+
+    /// Parse the body of a PIVOT operator after the PIVOT keyword:
+    /// `(agg [AS alias], ... FOR column IN (value [AS alias], ...)) [AS alias]`.
+    fn parse_pivot(&mut self, input: TableRef) -> Result<TableRefKind> {
+        let aliased = |p: &mut Self| -> Result<(Box<Expr>, Option<Ident>)> {
+            let expr = p.parse_expression()?;
+            Ok((expr, p.parse_optional_alias()?))
+        };
+        self.expect(&TokenKind::LeftParen)?;
+        let aggregates = self.parse_comma_separated(aliased)?;
+        self.expect_keyword(Keyword::For)?;
+        let for_column = self.parse_primary_expression()?;
+        self.expect_keyword(Keyword::In)?;
+        self.expect(&TokenKind::LeftParen)?;
+        let values = self.parse_comma_separated(aliased)?;
+        self.expect(&TokenKind::RightParen)?;
+        self.expect(&TokenKind::RightParen)?;
+        let alias = self.parse_optional_table_alias()?;
+        Ok(TableRefKind::Pivot {
+            input: Box::new(input),
+            aggregates,
+            for_column,
+            values,
+            alias,
+        })
+    }
+
+    /// Parse the body of an UNPIVOT operator after the UNPIVOT keyword:
+    /// `[INCLUDE NULLS | EXCLUDE NULLS] (value FOR name IN (column [AS label], ...)) [AS alias]`.
+    fn parse_unpivot(&mut self, input: TableRef) -> Result<TableRefKind> {
+        let include_nulls = if self.consume_keyword(Keyword::Include)?.is_some() {
+            self.expect_keyword(Keyword::Nulls)?;
+            true
+        } else {
+            if self.consume_keyword(Keyword::Exclude)?.is_some() {
+                self.expect_keyword(Keyword::Nulls)?;
+            }
+            false
+        };
+        self.expect(&TokenKind::LeftParen)?;
+        let value_columns = self.parse_unpivot_column_group()?;
+        self.expect_keyword(Keyword::For)?;
+        let name_column = self.parse_identifier()?;
+        self.expect_keyword(Keyword::In)?;
+        self.expect(&TokenKind::LeftParen)?;
+        let columns = self.parse_comma_separated(|p| {
+            let group = p.parse_unpivot_column_group()?;
+            Ok((group, p.parse_unpivot_label()?))
+        })?;
+        self.expect(&TokenKind::RightParen)?;
+        self.expect(&TokenKind::RightParen)?;
+        let alias = self.parse_optional_table_alias()?;
+        Ok(TableRefKind::Unpivot {
+            input: Box::new(input),
+            include_nulls,
+            value_columns,
+            name_column,
+            columns,
+            alias,
+        })
+    }
+
+    /// Parse a column name or a parenthesized list of column names.
+    fn parse_unpivot_column_group(&mut self) -> Result<Vec<Ident>> {
+        if self.consume(&TokenKind::LeftParen)?.is_some() {
+            let columns = self.parse_comma_separated(|p| p.parse_identifier())?;
+            self.expect(&TokenKind::RightParen)?;
+            Ok(columns)
+        } else {
+            Ok(vec![self.parse_identifier()?])
+        }
+    }
+
+    /// Parse an optional `[AS] 'label'` or `[AS] 1` after an unpivoted
+    /// column group.
+    fn parse_unpivot_label(&mut self) -> Result<Option<Box<Expr>>> {
+        let has_as = self.consume_keyword(Keyword::As)?.is_some();
+        let token = self.peek()?;
+        let kind = match &token.kind {
+            TokenKind::String(s) => ExprKind::String(s.clone()),
+            TokenKind::Integer(n) => ExprKind::Integer(*n),
+            _ if has_as => {
+                return Err(Error::unexpected_token(
+                    "string or integer label",
+                    token.text.clone(),
+                    token.span,
+                ))
+            }
+            _ => return Ok(None),
+        };
+        let token = self.advance()?;
+        Ok(Some(Box::new(Expr::new(kind, token.span))))
+    }
+
+    /// Parse the kind of a primary table reference.
+    fn parse_table_primary_kind(&mut self) -> Result<TableRefKind> {
+        // LATERAL applies to a subquery or table function.
+        if self.consume_keyword(Keyword::Lateral)?.is_some() {
+            let next = self.peek()?.clone();
+            return match self.parse_table_primary_kind()? {
+                TableRefKind::Subquery {
+                    query,
+                    alias,
+                    sample,
+                    ..
+                } => Ok(TableRefKind::Subquery {
+                    query,
+                    alias,
+                    sample,
+                    lateral: true,
+                }),
+                TableRefKind::TableFunction {
+                    name, args, alias, ..
+                } => Ok(TableRefKind::TableFunction {
+                    name,
+                    args,
+                    alias,
+                    lateral: true,
+                }),
+                _ => Err(Error::unexpected_token(
+                    "subquery or table function after LATERAL",
+                    next.text,
+                    next.span,
+                )),
+            };
+        }
+
         // Check for parenthesized table ref or subquery
         if self.consume(&TokenKind::LeftParen)?.is_some() {
-            // Could be subquery or parenthesized table ref
-            if self.check_keyword(Keyword::Select)? || self.check_keyword(Keyword::With)? {
+            if self.check_query_start()? {
                 let query = self.parse_query()?;
                 self.expect(&TokenKind::RightParen)?;
                 let alias = self.parse_optional_table_alias()?;
-                return Ok(TableRef::Subquery {
+                return Ok(TableRefKind::Subquery {
                     query: Box::new(query),
                     alias,
+                    sample: self.parse_optional_table_sample()?,
+                    lateral: false,
                 });
-            } else {
-                let inner = self.parse_table_ref()?;
-                self.expect(&TokenKind::RightParen)?;
-                return Ok(TableRef::Parenthesized(Box::new(inner)));
             }
+
+            // `((SELECT ...) UNION ...)` and `((SELECT ...)) AS t` are
+            // subqueries whose first operand is itself parenthesized.
+            let inner = self.parse_table_ref()?;
+            let first = match inner.kind {
+                TableRefKind::Subquery {
+                    query,
+                    alias: None,
+                    sample: None,
+                    lateral: false,
+                } => *query,
+                kind => {
+                    self.expect(&TokenKind::RightParen)?;
+                    let inner = TableRef::new(kind, inner.span);
+                    return Ok(TableRefKind::Parenthesized(Box::new(inner)));
+                }
+            };
+            let query = self.continue_subquery(first, inner.span.start)?;
+            self.expect(&TokenKind::RightParen)?;
+            let alias = self.parse_optional_table_alias()?;
+            return Ok(TableRefKind::Subquery {
+                query: Box::new(query),
+                alias,
+                sample: self.parse_optional_table_sample()?,
+                lateral: false,
+            });
         }
 
         // Check for UNNEST
@@ -378,7 +724,7 @@ impl<'a> Parser<'a> {
             } else {
                 (false, None)
             };
-            return Ok(TableRef::Unnest {
+            return Ok(TableRefKind::Unnest {
                 expr,
                 alias,
                 with_offset,
@@ -398,21 +744,84 @@ impl<'a> Parser<'a> {
             };
             self.expect(&TokenKind::RightParen)?;
             let alias = self.parse_optional_table_alias()?;
-            return Ok(TableRef::TableFunction { name, args, alias });
+            return Ok(TableRefKind::TableFunction {
+                name,
+                args,
+                alias,
+                lateral: false,
+            });
         }
 
-        // Parse optional hints
-        let hints = if self.consume(&TokenKind::At)?.is_some() {
+        // The sample may come before or after the alias.
+        let hints = self.parse_table_hints()?;
+        let sample = self.parse_optional_table_sample()?;
+        let alias = self.parse_optional_table_alias()?;
+        let sample = match sample {
+            Some(sample) => Some(sample),
+            None => self.parse_optional_table_sample()?,
+        };
+        Ok(TableRefKind::Table {
+            name,
+            alias,
+            hints,
+            sample,
+        })
+    }
+
+    /// Parse an optional
+    /// `TABLESAMPLE method (size [PERCENT | ROWS]) [REPEATABLE (seed)]`.
+    fn parse_optional_table_sample(&mut self) -> Result<Option<TableSample>> {
+        let Some(keyword) = self.consume_keyword(Keyword::Tablesample)? else {
+            return Ok(None);
+        };
+        let token = self.advance()?;
+        let method = match token.kind {
+            TokenKind::Keyword(Keyword::Bernoulli) => SampleMethod::Bernoulli,
+            TokenKind::Keyword(Keyword::System) => SampleMethod::System,
+            TokenKind::Keyword(Keyword::Reservoir) => SampleMethod::Reservoir,
+            _ => {
+                return Err(Error::unexpected_token(
+                    "BERNOULLI, SYSTEM, or RESERVOIR",
+                    token.text,
+                    token.span,
+                ))
+            }
+        };
+        self.expect(&TokenKind::LeftParen)?;
+        let amount = self.parse_expression()?;
+        let size = if self.consume_keyword(Keyword::Rows)?.is_some() {
+            SampleSize::Rows(amount)
+        } else {
+            self.consume_keyword(Keyword::Percent)?;
+            SampleSize::Percent(amount)
+        };
+        self.expect(&TokenKind::RightParen)?;
+        let seed = if self.consume_keyword(Keyword::Repeatable)?.is_some() {
+            self.expect(&TokenKind::LeftParen)?;
+            let seed = self.parse_expression()?;
+            self.expect(&TokenKind::RightParen)?;
+            Some(seed)
+        } else {
+            None
+        };
+        Ok(Some(TableSample {
+            method,
+            size,
+            seed,
+            span: Span::new(keyword.span.start, self.previous_end()),
+        }))
+    }
+
+    /// Parse optional table hints: `@{name = value, ...}`.
+    pub(super) fn parse_table_hints(&mut self) -> Result<Vec<SqlOption>> {
+        if self.consume(&TokenKind::At)?.is_some() {
             self.expect(&TokenKind::LeftBrace)?;
             let opts = self.parse_comma_separated(|p| p.parse_sql_option())?;
             self.expect(&TokenKind::RightBrace)?;
-            opts
+            Ok(opts)
         } else {
-            Vec::new()
-        };
-
-        let alias = self.parse_optional_table_alias()?;
-        Ok(TableRef::Table { name, alias, hints })
+            Ok(Vec::new())
+        }
     }
 
     /// Parse SQL option (key = value).
@@ -494,10 +903,11 @@ impl<'a> Parser<'a> {
         Ok(None)
     }
 
-    /// Parse JOIN condition.
+    /// Parse JOIN condition. A CROSS JOIN condition is parsed so that the
+    /// analyzer can reject it.
     fn parse_join_condition(&mut self, join_type: JoinType) -> Result<Option<JoinCondition>> {
         match join_type {
-            JoinType::Cross | JoinType::Natural => Ok(None),
+            JoinType::Natural => Ok(None),
             _ => {
                 if self.consume_keyword(Keyword::On)?.is_some() {
                     let expr = self.parse_expression()?;
@@ -588,24 +998,39 @@ impl<'a> Parser<'a> {
         Ok(OrderByExpr { expr, order, nulls })
     }
 
-    /// Parse LIMIT/OFFSET clause.
+    /// Parse a LIMIT, OFFSET, or FETCH clause.
     fn parse_limit_clause(&mut self) -> Result<Option<LimitClause>> {
+        let limit = |count, offset, syntax| LimitClause {
+            count,
+            offset,
+            with_ties: false,
+            syntax,
+        };
         if self.consume_keyword(Keyword::Limit)?.is_some() {
-            let count = if self.consume_keyword(Keyword::All)?.is_some() {
-                None
-            } else {
-                Some(self.parse_expression()?)
-            };
-
-            let offset = if self.consume_keyword(Keyword::Offset)?.is_some() {
-                Some(self.parse_expression()?)
-            } else {
-                None
-            };
-
-            Ok(Some(LimitClause { count, offset }))
+            if self.consume_keyword(Keyword::All)?.is_some() {
+                let offset = self.parse_optional_offset()?;
+                return Ok(Some(limit(None, offset, LimitSyntax::Limit)));
+            }
+            let first = self.parse_expression()?;
+            if self.consume(&TokenKind::Comma)?.is_some() {
+                let count = self.parse_expression()?;
+                return Ok(Some(limit(
+                    Some(count),
+                    Some(first),
+                    LimitSyntax::LimitComma,
+                )));
+            }
+            let offset = self.parse_optional_offset()?;
+            Ok(Some(limit(Some(first), offset, LimitSyntax::Limit)))
         } else if self.consume_keyword(Keyword::Offset)?.is_some() {
             let offset = Some(self.parse_expression()?);
+            let rows = self.consume_rows()?;
+            if self.check_keyword(Keyword::Fetch)? {
+                return self.parse_fetch(offset).map(Some);
+            }
+            if rows {
+                return Ok(Some(limit(None, offset, LimitSyntax::Fetch)));
+            }
 
             let count = if self.consume_keyword(Keyword::Limit)?.is_some() {
                 if self.consume_keyword(Keyword::All)?.is_some() {
@@ -617,17 +1042,71 @@ impl<'a> Parser<'a> {
                 None
             };
 
-            Ok(Some(LimitClause { count, offset }))
+            Ok(Some(limit(count, offset, LimitSyntax::Limit)))
+        } else if self.check_keyword(Keyword::Fetch)? {
+            self.parse_fetch(None).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parse an optional `OFFSET offset` after `LIMIT count`.
+    fn parse_optional_offset(&mut self) -> Result<Option<Box<Expr>>> {
+        if self.consume_keyword(Keyword::Offset)?.is_some() {
+            Ok(Some(self.parse_expression()?))
         } else {
             Ok(None)
         }
     }
 
+    /// Consume `ROW` or `ROWS`, returning whether either was present.
+    fn consume_rows(&mut self) -> Result<bool> {
+        Ok(self.consume_keyword(Keyword::Rows)?.is_some()
+            || self.consume_keyword(Keyword::Row)?.is_some())
+    }
+
+    /// Parse `FETCH {FIRST | NEXT} [count] {ROW | ROWS} {ONLY | WITH TIES}`.
+    fn parse_fetch(&mut self, offset: Option<Box<Expr>>) -> Result<LimitClause> {
+        let fetch = self.expect_keyword(Keyword::Fetch)?;
+        if self.consume_keyword(Keyword::First)?.is_none() {
+            self.expect_keyword(Keyword::Next)?;
+        }
+        let count = if self.check_keyword(Keyword::Row)? || self.check_keyword(Keyword::Rows)? {
+            Expr::boxed(
+                ExprKind::Integer(1),
+                Span::new(fetch.span.start, self.previous_end()),
+            )
+        } else {
+            self.parse_expression()?
+        };
+        if !self.consume_rows()? {
+            let token = self.peek()?;
+            return Err(Error::expected_keyword("ROWS", token.span));
+        }
+        let with_ties = if self.consume_keyword(Keyword::With)?.is_some() {
+            self.expect_keyword(Keyword::Ties)?;
+            true
+        } else {
+            self.expect_keyword(Keyword::Only)?;
+            false
+        };
+        Ok(LimitClause {
+            count: Some(count),
+            offset,
+            with_ties,
+            syntax: LimitSyntax::Fetch,
+        })
+    }
+
     /// Parse a named window definition.
     fn parse_named_window_def(&mut self) -> Result<WindowDef> {
         let name = self.parse_identifier()?;
         self.expect_keyword(Keyword::As)?;
-        let spec = self.parse_window_spec()?;
+        let spec = if self.check(&TokenKind::LeftParen)? {
+            self.parse_parenthesized_window_spec()?
+        } else {
+            self.parse_window_spec()?
+        };
         Ok(WindowDef { name, spec })
     }
 }
@@ -689,12 +1168,278 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_select_distinct_on() {
+        let query = parse_query("SELECT DISTINCT ON (a, b + 1) a, c FROM t ORDER BY a");
+        if let QueryBody::Select(select) = query.body {
+            match select.distinct {
+                Some(Distinct::On(exprs)) => assert_eq!(exprs.len(), 2),
+                other => panic!("Expected DISTINCT ON, got {:?}", other),
+            }
+        } else {
+            panic!("Expected SELECT");
+        }
+
+        for sql in [
+            "SELECT DISTINCT ON a FROM t",
+            "SELECT DISTINCT ON () a FROM t",
+            "SELECT DISTINCT ON (a a FROM t",
+        ] {
+            let err = parse_error(sql);
+            assert!(
+                matches!(
+                    err.kind,
+                    ErrorKind::UnexpectedToken { .. } | ErrorKind::ExpectedExpression
+                ),
+                "{}: {:?}",
+                sql,
+                err
+            );
+        }
+    }
+
     #[test]
     fn test_union() {
         let query = parse_query("SELECT 1 UNION ALL SELECT 2");
         assert!(matches!(query.body, QueryBody::SetOperation { .. }));
     }
 
+    /// The shape of a set operation tree, with operations in parentheses
+    /// and parenthesized queries in brackets.
+    fn set_operation_shape(body: &QueryBody) -> String {
+        match body {
+            QueryBody::Select(select) => select.to_string(),
+            QueryBody::SetOperation {
+                op,
+                all,
+                left,
+                right,
+            } => format!(
+                "({} {:?}{} {})",
+                set_operation_shape(left),
+                op,
+                if *all { " ALL" } else { "" },
+                set_operation_shape(right)
+            ),
+            QueryBody::Parenthesized(query) => format!("[{}]", set_operation_shape(&query.body)),
+        }
+    }
+
+    #[test]
+    fn test_set_operation_precedence() {
+        let cases = [
+            (
+                "SELECT 1 UNION SELECT 2 INTERSECT SELECT 3",
+                "(SELECT 1 Union (SELECT 2 Intersect SELECT 3))",
+            ),
+            (
+                "SELECT 1 INTERSECT SELECT 2 UNION SELECT 3",
+                "((SELECT 1 Intersect SELECT 2) Union SELECT 3)",
+            ),
+            (
+                "SELECT 1 UNION SELECT 2 EXCEPT SELECT 3 UNION ALL SELECT 4",
+                "(((SELECT 1 Union SELECT 2) Except SELECT 3) Union ALL SELECT 4)",
+            ),
+            (
+                "SELECT 1 EXCEPT ALL SELECT 2 INTERSECT ALL SELECT 3 INTERSECT SELECT 4",
+                "(SELECT 1 Except ALL ((SELECT 2 Intersect ALL SELECT 3) Intersect SELECT 4))",
+            ),
+            (
+                "SELECT 1 UNION DISTINCT SELECT 2 INTERSECT SELECT 3 UNION ALL SELECT 4",
+                "((SELECT 1 Union (SELECT 2 Intersect SELECT 3)) Union ALL SELECT 4)",
+            ),
+            (
+                "(SELECT 1 UNION SELECT 2) INTERSECT SELECT 3",
+                "([(SELECT 1 Union SELECT 2)] Intersect SELECT 3)",
+            ),
+            (
+                "SELECT 1 INTERSECT (SELECT 2 UNION ALL SELECT 3)",
+                "(SELECT 1 Intersect [(SELECT 2 Union ALL SELECT 3)])",
+            ),
+        ];
+        for (sql, shape) in cases {
+            let query = parse_query(sql);
+            assert_eq!(set_operation_shape(&query.body), shape, "{}", sql);
+            // Display keeps the grouping.
+            let reparsed = parse_query(&query.to_string());
+            assert_eq!(set_operation_shape(&reparsed.body), shape, "{}", sql);
+        }
+
+        // The parenthesized first operand of a subquery continues the same way.
+        let Some(SelectItem::Expr { expr, .. }) =
+            select_items("SELECT ((SELECT 1) UNION SELECT 2 INTERSECT SELECT 3)").pop()
+        else {
+            panic!("expected expression item");
+        };
+        let ExprKind::Subquery(query) = &expr.kind else {
+            panic!("expected subquery, got {:?}", expr.kind);
+        };
+        assert_eq!(
+            set_operation_shape(&query.body),
+            "([SELECT 1] Union (SELECT 2 Intersect SELECT 3))"
+        );
+
+        // Every operator needs a right operand, and takes one quantifier.
+        for (sql, at) in [
+            ("SELECT 1 INTERSECT", 18),
+            ("SELECT 1 UNION SELECT 2 INTERSECT ALL", 37),
+            ("SELECT 1 INTERSECT SELECT 2 UNION", 33),
+            ("SELECT 1 INTERSECT ALL DISTINCT SELECT 2", 23),
+        ] {
+            let err = parse_error(sql);
+            assert_eq!(err.span().map(|s| s.start), Some(at), "{}: {}", sql, err);
+        }
+    }
+
+    #[test]
+    fn test_pivot_and_unpivot() {
+        let sql = "SELECT * FROM sales AS s PIVOT(SUM(amount) AS total, COUNT(*) \
+                   FOR quarter IN ('Q1' AS first, 'Q2')) AS p";
+        let query = parse_query(sql);
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected select");
+        };
+        let table = &select.from.as_ref().unwrap().tables[0];
+        assert_eq!(&sql[table.span.start..table.span.end], &sql[14..]);
+        let TableRefKind::Pivot {
+            input,
+            aggregates,
+            for_column,
+            values,
+            alias,
+        } = &table.kind
+        else {
+            panic!("expected PIVOT, got {:?}", table.kind);
+        };
+        assert_eq!(input.to_string(), "sales AS s");
+        assert_eq!(aggregates.len(), 2);
+        assert_eq!(aggregates[0].1.as_ref().unwrap().value, "total");
+        assert!(aggregates[1].1.is_none());
+        assert_eq!(for_column.to_string(), "quarter");
+        assert_eq!(values[0].1.as_ref().unwrap().value, "first");
+        assert_eq!(values[1].0.kind, ExprKind::String("Q2".to_string()));
+        assert_eq!(alias.as_ref().unwrap().name.value, "p");
+
+        let query = parse_query("SELECT * FROM t UNPIVOT ((x, y) FOR k IN ((a, b) 'ab', (c, d)))");
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected select");
+        };
+        let table = &select.from.as_ref().unwrap().tables[0];
+        let TableRefKind::Unpivot {
+            include_nulls,
+            value_columns,
+            name_column,
+            columns,
+            alias,
+            ..
+        } = &table.kind
+        else {
+            panic!("expected UNPIVOT, got {:?}", table.kind);
+        };
+        assert!(!include_nulls);
+        assert_eq!(value_columns.len(), 2);
+        assert_eq!(name_column.value, "k");
+        assert_eq!(columns.len(), 2);
+        assert_eq!(
+            columns[0].1.as_ref().unwrap().kind,
+            ExprKind::String("ab".to_string())
+        );
+        assert!(columns[1].1.is_none());
+        assert!(alias.is_none());
+
+        // PIVOT and UNPIVOT remain usable as names.
+        let query = parse_query("SELECT pivot FROM unpivot AS pivot");
+        assert!(matches!(query.body, QueryBody::Select(_)));
+
+        let err = parse_error("SELECT * FROM t UNPIVOT (v FOR k IN (a AS b))");
+        assert!(
+            err.to_string().contains("string or integer label"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_table_sample() {
+        let sample_of = |sql: &str| -> TableSample {
+            let query = parse_query(sql);
+            let QueryBody::Select(select) = query.body else {
+                panic!("expected select");
+            };
+            match select.from.unwrap().tables.remove(0).kind {
+                TableRefKind::Table { sample, .. } | TableRefKind::Subquery { sample, .. } => {
+                    sample.unwrap_or_else(|| panic!("no sample in {}", sql))
+                }
+                other => panic!("expected table or subquery, got {:?}", other),
+            }
+        };
+
+        let sql = "SELECT * FROM events TABLESAMPLE SYSTEM (10 PERCENT)";
+        let sample = sample_of(sql);
+        assert_eq!(sample.method, SampleMethod::System);
+        assert!(matches!(sample.size, SampleSize::Percent(_)));
+        assert!(sample.seed.is_none());
+        assert_eq!(
+            &sql[sample.span.start..sample.span.end],
+            "TABLESAMPLE SYSTEM (10 PERCENT)"
+        );
+
+        let sample = sample_of("SELECT * FROM t AS x TABLESAMPLE BERNOULLI (5) REPEATABLE (1)");
+        assert_eq!(sample.method, SampleMethod::Bernoulli);
+        assert!(matches!(sample.size, SampleSize::Percent(_)));
+        assert_eq!(sample.seed.unwrap().kind, ExprKind::Integer(1));
+
+        let sample = sample_of("SELECT * FROM t TABLESAMPLE RESERVOIR (100 ROWS) AS x");
+        assert_eq!(sample.method, SampleMethod::Reservoir);
+        assert!(matches!(sample.size, SampleSize::Rows(_)));
+
+        sample_of("SELECT * FROM (SELECT 1) AS d TABLESAMPLE SYSTEM (1 PERCENT)");
+
+        let err = parse_error("SELECT * FROM t TABLESAMPLE BLOCK (10)");
+        assert!(
+            err.to_string().contains("BERNOULLI, SYSTEM, or RESERVOIR"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_limit_syntaxes() {
+        let limit = |sql: &str| parse_query(sql).limit.expect("expected a limit");
+        let int = |expr: &Option<Box<Expr>>| expr.as_ref().map(|e| e.kind.clone());
+
+        let l = limit("SELECT 1 LIMIT 10 OFFSET 5");
+        assert_eq!(l.syntax, LimitSyntax::Limit);
+        assert_eq!(int(&l.count), Some(ExprKind::Integer(10)));
+        assert_eq!(int(&l.offset), Some(ExprKind::Integer(5)));
+
+        let l = limit("SELECT 1 LIMIT 5, 10");
+        assert_eq!(l.syntax, LimitSyntax::LimitComma);
+        assert_eq!(int(&l.count), Some(ExprKind::Integer(10)));
+        assert_eq!(int(&l.offset), Some(ExprKind::Integer(5)));
+
+        let l = limit("SELECT 1 ORDER BY 1 OFFSET 5 ROWS FETCH FIRST 10 ROWS ONLY");
+        assert_eq!(l.syntax, LimitSyntax::Fetch);
+        assert_eq!(int(&l.count), Some(ExprKind::Integer(10)));
+        assert_eq!(int(&l.offset), Some(ExprKind::Integer(5)));
+        assert!(!l.with_ties);
+
+        let l = limit("SELECT 1 ORDER BY 1 FETCH NEXT 3 ROW WITH TIES");
+        assert!(l.with_ties);
+        assert!(l.offset.is_none());
+
+        let l = limit("SELECT 1 FETCH FIRST ROW ONLY");
+        assert_eq!(int(&l.count), Some(ExprKind::Integer(1)));
+
+        let l = limit("SELECT 1 OFFSET 2 ROWS");
+        assert_eq!(l.syntax, LimitSyntax::Fetch);
+        assert!(l.count.is_none());
+
+        let err = parse_error("SELECT 1 FETCH FIRST 10 ONLY");
+        assert!(err.to_string().contains("ROWS"), "{}", err);
+        parse_error("SELECT 1 FETCH FIRST 10 ROWS");
+    }
+
     #[test]
     fn test_with_clause() {
         let query = parse_query("WITH cte AS (SELECT 1 AS x) SELECT * FROM cte");
@@ -707,4 +1452,206 @@ mod tests {
         assert_eq!(query.order_by.len(), 1);
         assert!(query.limit.is_some());
     }
+
+    fn parse_error(sql: &str) -> Error {
+        Parser::new(sql)
+            .parse()
+            .expect_err("expected a parse error")
+    }
+
+    #[test]
+    fn test_trailing_comma_in_select_list() {
+        for sql in [
+            "SELECT a, b, FROM t",
+            "SELECT a, GROUP BY a",
+            "SELECT a, ORDER BY a",
+            "SELECT a,",
+        ] {
+            let err = parse_error(sql);
+            assert_eq!(err.kind, ErrorKind::TrailingComma, "{}", sql);
+            let comma = sql.rfind(',').unwrap();
+            assert_eq!(err.span(), Some(Span::new(comma, comma + 1)));
+            assert_eq!(
+                err.suggestions(),
+                &[Suggestion::delete(
+                    "remove the comma",
+                    Span::new(comma, comma + 1)
+                )]
+            );
+        }
+        assert!(err_message("SELECT a, b, FROM t").contains("trailing comma in select list"));
+    }
+
+    #[test]
+    fn test_duplicate_operator() {
+        let sql = "SELECT * FROM t WHERE a = = 1";
+        let err = parse_error(sql);
+        assert_eq!(err.kind, ErrorKind::DuplicateOperator("=".to_string()));
+        let second = sql.rfind('=').unwrap();
+        assert_eq!(err.span(), Some(Span::new(second, second + 1)));
+        assert_eq!(err.suggestions()[0].replacement, "");
+
+        let err = parse_error("SELECT * FROM t WHERE a AND AND b");
+        assert!(matches!(err.kind, ErrorKind::DuplicateOperator(_)));
+
+        // Unary signs after a binary operator are still accepted.
+        Parser::new("SELECT 1 - -1, 2 + +2").parse().unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_clause_keyword() {
+        let err = parse_error("SELECT a FROM FROM t");
+        assert_eq!(err.kind, ErrorKind::DuplicateKeyword("FROM".to_string()));
+        assert!(err.to_string().contains("duplicate FROM keyword"));
+        assert_eq!(err.span(), Some(Span::new(14, 18)));
+
+        let err = parse_error("SELECT a FROM t WHERE WHERE a = 1");
+        assert_eq!(err.kind, ErrorKind::DuplicateKeyword("WHERE".to_string()));
+    }
+
+    #[test]
+    fn test_missing_comma_alias_note() {
+        let sql = "SELECT a b, c FROM t";
+        let mut parser = Parser::new(sql);
+        parser.parse().unwrap();
+        let notes = parser.diagnostics();
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0]
+            .message
+            .contains("'b' is treated as an alias for 'a'"));
+        assert_eq!(notes[0].span, Span::new(9, 10));
+        assert_eq!(
+            notes[0].suggestions[0],
+            Suggestion::insert("insert a comma", 8, ",")
+        );
+
+        let mut parser = Parser::new("SELECT a AS b, c + 1 d FROM t");
+        parser.parse().unwrap();
+        assert!(parser.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_with_subquery_in_every_position() {
+        let with = "WITH x AS (SELECT 1 AS a) SELECT a FROM x";
+        let set_op = "(SELECT 1 AS a) UNION ALL (SELECT 2)";
+        for sub in [with, set_op, "(WITH x AS (SELECT 1 AS a) SELECT a FROM x)"] {
+            let cases = [
+                format!("SELECT * FROM t WHERE id IN ({})", sub),
+                format!("SELECT * FROM t WHERE id NOT IN ({})", sub),
+                format!("SELECT * FROM t WHERE EXISTS ({})", sub),
+                format!("SELECT ({}) AS v", sub),
+                format!("SELECT ARRAY({}) AS v", sub),
+                format!("SELECT * FROM ({}) AS d", sub),
+                format!("SELECT * FROM t JOIN ({}) d ON TRUE", sub),
+                format!("SELECT 1 UNION ALL ({})", sub),
+                format!("WITH c AS ({}) SELECT * FROM c", sub),
+                format!("INSERT INTO t ({})", sub),
+                format!("INSERT INTO t (a) {}", with),
+                format!("CREATE VIEW v AS {}", with),
+                format!("CREATE TABLE n AS {}", with),
+            ];
+            for sql in cases {
+                Parser::new(&sql)
+                    .parse()
+                    .unwrap_or_else(|e| panic!("{}: {}", sql, e));
+            }
+        }
+    }
+
+    #[test]
+    fn test_parenthesized_subquery_operands() {
+        let Some(SelectItem::Expr { expr, .. }) =
+            select_items("SELECT ((SELECT 1) UNION ALL (SELECT 2) ORDER BY 1 LIMIT 1)").pop()
+        else {
+            panic!("expected expression item");
+        };
+        let ExprKind::Subquery(query) = &expr.kind else {
+            panic!("expected subquery, got {:?}", expr.kind);
+        };
+        assert!(matches!(query.body, QueryBody::SetOperation { .. }));
+        assert!(query.limit.is_some());
+
+        // A parenthesized subquery inside a larger expression stays one.
+        let Some(SelectItem::Expr { expr, .. }) = select_items("SELECT ((SELECT 1) + 1)").pop()
+        else {
+            panic!("expected expression item");
+        };
+        assert!(matches!(expr.kind, ExprKind::Parenthesized(_)));
+
+        let query = parse_query("SELECT * FROM ((SELECT 1 AS a)) AS d");
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected select");
+        };
+        let table = &select.from.as_ref().unwrap().tables[0];
+        let TableRefKind::Subquery { query, alias, .. } = &table.kind else {
+            panic!("expected derived table, got {:?}", table.kind);
+        };
+        assert!(matches!(query.body, QueryBody::Parenthesized(_)));
+        assert_eq!(alias.as_ref().unwrap().name.value, "d");
+
+        let query = parse_query("SELECT * FROM (t JOIN u ON TRUE)");
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected select");
+        };
+        assert!(matches!(
+            select.from.as_ref().unwrap().tables[0].kind,
+            TableRefKind::Parenthesized(_)
+        ));
+
+        let Some(SelectItem::Expr { expr, .. }) = select_items("SELECT 1 IN ((SELECT 1), 2)").pop()
+        else {
+            panic!("expected expression item");
+        };
+        assert!(matches!(
+            &expr.kind,
+            ExprKind::In { list: InList::Values(values), .. } if values.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_lateral() {
+        let query = parse_query("SELECT * FROM t, LATERAL (SELECT t.a) AS x, LATERAL f(t.a)");
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected select");
+        };
+        let tables = &select.from.as_ref().unwrap().tables;
+        assert!(matches!(
+            tables[1].kind,
+            TableRefKind::Subquery { lateral: true, .. }
+        ));
+        assert!(matches!(
+            tables[2].kind,
+            TableRefKind::TableFunction { lateral: true, .. }
+        ));
+
+        let query = parse_query("SELECT * FROM t JOIN (SELECT 1) AS x ON TRUE");
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected select");
+        };
+        let TableRefKind::Join { right, .. } = &select.from.as_ref().unwrap().tables[0].kind else {
+            panic!("expected join");
+        };
+        assert!(matches!(
+            right.kind,
+            TableRefKind::Subquery { lateral: false, .. }
+        ));
+
+        assert!(err_message("SELECT * FROM t, LATERAL u")
+            .contains("subquery or table function after LATERAL"));
+        let sql = "SELECT * FROM t JOIN LATERAL (u JOIN v ON TRUE) ON TRUE";
+        let err = parse_error(sql);
+        assert!(err.to_string().contains("after LATERAL"), "{}", err);
+        assert_eq!(err.span().map(|s| &sql[s.start..s.end]), Some("("));
+    }
+
+    fn select_items(sql: &str) -> Vec<SelectItem> {
+        match parse_query(sql).body {
+            QueryBody::Select(select) => select.projection,
+            body => panic!("expected select, got {:?}", body),
+        }
+    }
+
+    fn err_message(sql: &str) -> String {
+        parse_error(sql).to_string()
+    }
 }
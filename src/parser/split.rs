@@ -0,0 +1,196 @@
+//! Splitting a script into statements without parsing them.
+
+use crate::error::{ErrorKind, Span};
+use crate::lexer::{Lexer, TokenKind};
+
+/// Find the statements of a script without building ASTs.
+///
+/// Statements end at a `;` outside parentheses. The lexer is used to find
+/// them, so a `;` inside a string, quoted identifier, or comment does not
+/// split. Each piece is returned with its span, from its first token to its
+/// last, without the `;` or surrounding whitespace and comments; empty
+/// statements are skipped. If the lexer fails, for example on an
+/// unterminated string, the rest of the input is one final piece.
+///
+/// ```
+/// use vibesql::parser::split_statements;
+///
+/// let pieces = split_statements("SELECT ';'; -- a;\n INSERT INTO t VALUES (f(1; 2));;");
+/// let texts: Vec<&str> = pieces.iter().map(|(_, text)| *text).collect();
+/// assert_eq!(texts, ["SELECT ';'", "INSERT INTO t VALUES (f(1; 2))"]);
+/// ```
+pub fn split_statements(sql: &str) -> Vec<(Span, &str)> {
+    let mut pieces = Vec::new();
+    let mut push = |start: usize, end: usize| {
+        pieces.push((Span::new(start, end), &sql[start..end]));
+    };
+
+    let mut lexer = Lexer::new(sql);
+    // Start and end of the statement so far, if it has any tokens.
+    let mut current: Option<(usize, usize)> = None;
+    let mut depth = 0usize;
+    loop {
+        let token = match lexer.next_token_result() {
+            Ok(token) => token,
+            Err(err) => {
+                let start = current
+                    .map(|(start, _)| start)
+                    .or(err.span().map(|span| span.start))
+                    .unwrap_or(lexer.position());
+                let end = sql.trim_end().len();
+                if start < end {
+                    push(start, end);
+                }
+                return pieces;
+            }
+        };
+        match token.kind {
+            TokenKind::Eof => break,
+            TokenKind::Semicolon if depth == 0 => {
+                if let Some((start, end)) = current.take() {
+                    push(start, end);
+                }
+                continue;
+            }
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        let start = current.map_or(token.span.start, |(start, _)| start);
+        current = Some((start, token.span.end));
+    }
+    if let Some((start, end)) = current {
+        push(start, end);
+    }
+    pieces
+}
+
+/// Whether `sql` ends with a complete statement.
+///
+/// That is, whether its last token is a `;` outside parentheses, found with
+/// the lexer so that a `;` in a string or comment does not count. Input
+/// that ends inside a string or block comment is incomplete. Input the
+/// lexer rejects for another reason, including a single-quoted string cut
+/// off by a line break, counts as complete so the error can be reported. Lets an interactive prompt keep reading lines until a
+/// statement is finished.
+///
+/// ```
+/// use vibesql::is_complete_statement;
+///
+/// assert!(is_complete_statement("SELECT 1; -- done"));
+/// assert!(!is_complete_statement("CREATE TABLE t (\n  a INT;"));
+/// assert!(!is_complete_statement("SELECT ';"));
+/// ```
+pub fn is_complete_statement(sql: &str) -> bool {
+    let mut lexer = Lexer::new(sql);
+    let mut depth = 0usize;
+    let mut terminated = false;
+    loop {
+        let token = match lexer.next_token_result() {
+            Ok(token) => token,
+            Err(err) => {
+                return match (&err.kind, err.span()) {
+                    (ErrorKind::UnterminatedBlockComment, _) => false,
+                    (ErrorKind::UnterminatedString, Some(span)) => {
+                        // Only triple-quoted strings may span lines.
+                        let text = &sql[span.start..span.end];
+                        let quotes = text.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+                        text.contains('\n')
+                            && !quotes.starts_with("'''")
+                            && !quotes.starts_with("\"\"\"")
+                    }
+                    _ => true,
+                };
+            }
+        };
+        match token.kind {
+            TokenKind::Eof => return terminated,
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        terminated = depth == 0 && token.kind == TokenKind::Semicolon;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    fn texts(sql: &str) -> Vec<&str> {
+        split_statements(sql).into_iter().map(|(_, t)| t).collect()
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let sql = "/* lead; */ SELECT 'a;b', \"c;d\" FROM t -- x; y\n;\n\nUPDATE t SET a = 1";
+        let pieces = split_statements(sql);
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(
+            pieces[0].1, "SELECT 'a;b', \"c;d\" FROM t",
+            "comments around a statement are not part of it"
+        );
+        assert_eq!(
+            &sql[pieces[1].0.start..pieces[1].0.end],
+            "UPDATE t SET a = 1"
+        );
+
+        // Comments inside a statement are kept.
+        assert_eq!(texts("SELECT 1 /* ; */ + 2;"), ["SELECT 1 /* ; */ + 2"]);
+        assert_eq!(texts(";; ;"), Vec::<&str>::new());
+        assert_eq!(
+            texts("SELECT (1;2); SELECT 3"),
+            ["SELECT (1;2)", "SELECT 3"]
+        );
+
+        // An unterminated string runs to the end of the input.
+        assert_eq!(
+            texts("SELECT 1; SELECT 'oops; SELECT 2  "),
+            ["SELECT 1", "SELECT 'oops; SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_iter_statements() {
+        let sql = "SELECT 1;; SELECT 2; SELECT 3";
+        let mut parser = Parser::new(sql);
+        let statements: Vec<_> = parser.iter_statements().collect();
+        assert_eq!(statements.len(), 3);
+        assert!(statements.iter().all(|s| s.is_ok()));
+
+        // The pieces of a script parse to the same statements.
+        for ((_, text), stmt) in split_statements(sql).into_iter().zip(statements) {
+            let piece = Parser::new(text).parse_statement().unwrap();
+            assert_eq!(piece.to_string(), stmt.unwrap().to_string());
+        }
+
+        let mut parser = Parser::new("SELECT 1; SELECT FROM; SELECT 3");
+        let results: Vec<_> = parser.iter_statements().collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[1].is_err());
+    }
+
+    #[test]
+    fn test_is_complete_statement() {
+        assert!(is_complete_statement("SELECT 1;"));
+        assert!(is_complete_statement("SELECT 1; /* trailing */\n"));
+        assert!(is_complete_statement("SELECT (1);;"));
+        assert!(!is_complete_statement(""));
+        assert!(!is_complete_statement("-- ;"));
+        assert!(!is_complete_statement("SELECT 1; SELECT 2"));
+        assert!(!is_complete_statement("SELECT f(1;"));
+        assert!(!is_complete_statement("SELECT 1 /* ;"));
+        assert!(!is_complete_statement("SELECT \"a;"));
+        assert!(!is_complete_statement("SELECT '''a;\nb;"));
+        assert!(is_complete_statement("SELECT 'a;\n';"));
+        assert!(!is_complete_statement(
+            "CREATE FUNCTION f() AS $$\nSELECT 1;"
+        ));
+        assert!(is_complete_statement(
+            "CREATE FUNCTION f() AS $$\nSELECT 1;\n$$;"
+        ));
+        // Other lexer errors are left for the parser to report.
+        assert!(is_complete_statement("SELECT !"));
+    }
+}
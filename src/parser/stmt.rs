@@ -13,6 +13,7 @@ impl<'a> Parser<'a> {
     /// Parse INSERT statement.
     pub fn parse_insert(&mut self) -> Result<StatementKind> {
         self.expect_keyword(Keyword::Insert)?;
+        let optimizer_hints = self.parse_optimizer_hints()?;
 
         // Optional OR action
         let or_action = if self.consume_keyword(Keyword::Or)?.is_some() {
@@ -37,8 +38,15 @@ impl<'a> Parser<'a> {
         self.expect_keyword(Keyword::Into)?;
         let table = self.parse_object_name()?;
 
-        // Optional column list
-        let columns = if self.consume(&TokenKind::LeftParen)?.is_some() {
+        // Optional column list; `INSERT INTO t (SELECT ...)` has a
+        // parenthesized query source instead.
+        let column_list = self.check(&TokenKind::LeftParen)?
+            && !matches!(
+                self.peek_nth(1)?.kind,
+                TokenKind::Keyword(Keyword::Select | Keyword::With) | TokenKind::LeftParen
+            );
+        let columns = if column_list {
+            self.advance()?;
             let cols = self.parse_comma_separated(|p| p.parse_identifier())?;
             self.expect(&TokenKind::RightParen)?;
             cols
@@ -67,6 +75,7 @@ impl<'a> Parser<'a> {
         let returning = self.parse_returning_clause()?;
 
         Ok(StatementKind::Insert(InsertStatement {
+            optimizer_hints,
             or_action,
             table,
             columns,
@@ -78,6 +87,7 @@ impl<'a> Parser<'a> {
     /// Parse UPDATE statement.
     pub fn parse_update(&mut self) -> Result<StatementKind> {
         self.expect_keyword(Keyword::Update)?;
+        let optimizer_hints = self.parse_optimizer_hints()?;
         let table = self.parse_table_ref()?;
 
         self.expect_keyword(Keyword::Set)?;
@@ -102,6 +112,7 @@ impl<'a> Parser<'a> {
         let returning = self.parse_returning_clause()?;
 
         Ok(StatementKind::Update(UpdateStatement {
+            optimizer_hints,
             table,
             assignments,
             from,
@@ -135,9 +146,11 @@ impl<'a> Parser<'a> {
     /// Parse DELETE statement.
     pub fn parse_delete(&mut self) -> Result<StatementKind> {
         self.expect_keyword(Keyword::Delete)?;
+        let optimizer_hints = self.parse_optimizer_hints()?;
         self.expect_keyword(Keyword::From)?;
 
         let table = self.parse_object_name()?;
+        let hints = self.parse_table_hints()?;
         let alias = self.parse_optional_table_alias()?;
 
         // Optional WHERE clause
@@ -151,7 +164,9 @@ impl<'a> Parser<'a> {
         let returning = self.parse_returning_clause()?;
 
         Ok(StatementKind::Delete(DeleteStatement {
+            optimizer_hints,
             table,
+            hints,
             alias,
             where_clause,
             returning,
@@ -320,15 +335,25 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Parse an optional IF NOT EXISTS clause, rejecting it after OR REPLACE.
+    fn parse_create_if_not_exists(&mut self, or_replace: bool) -> Result<bool> {
+        let Some(if_token) = self.consume_keyword(Keyword::If)? else {
+            return Ok(false);
+        };
+        self.expect_keyword(Keyword::Not)?;
+        self.expect_keyword(Keyword::Exists)?;
+        if or_replace {
+            return Err(Error::invalid_syntax(
+                "OR REPLACE and IF NOT EXISTS cannot be used together",
+                Span::new(if_token.span.start, self.previous_end()),
+            ));
+        }
+        Ok(true)
+    }
+
     /// Parse CREATE TABLE statement.
     fn parse_create_table(&mut self, or_replace: bool, temporary: bool) -> Result<StatementKind> {
-        let if_not_exists = if self.consume_keyword(Keyword::If)?.is_some() {
-            self.expect_keyword(Keyword::Not)?;
-            self.expect_keyword(Keyword::Exists)?;
-            true
-        } else {
-            false
-        };
+        let if_not_exists = self.parse_create_if_not_exists(or_replace)?;
 
         let name = self.parse_object_name()?;
 
@@ -345,7 +370,8 @@ impl<'a> Parser<'a> {
             None
         };
 
-        // Check for AS SELECT
+        // Check for AS SELECT, which may also follow the column list
+        // and options
         let as_query = if self.consume_keyword(Keyword::As)?.is_some() {
             Some(Box::new(self.parse_query()?))
         } else {
@@ -400,6 +426,13 @@ impl<'a> Parser<'a> {
         // Parse OPTIONS
         let options = self.parse_options_clause()?;
 
+        let as_query = match as_query {
+            None if self.consume_keyword(Keyword::As)?.is_some() => {
+                Some(Box::new(self.parse_query()?))
+            }
+            as_query => as_query,
+        };
+
         Ok(StatementKind::CreateTable(CreateTableStatement {
             or_replace,
             temporary,
@@ -654,13 +687,7 @@ impl<'a> Parser<'a> {
 
     /// Parse CREATE VIEW statement.
     fn parse_create_view(&mut self, or_replace: bool, materialized: bool) -> Result<StatementKind> {
-        let if_not_exists = if self.consume_keyword(Keyword::If)?.is_some() {
-            self.expect_keyword(Keyword::Not)?;
-            self.expect_keyword(Keyword::Exists)?;
-            true
-        } else {
-            false
-        };
+        let if_not_exists = self.parse_create_if_not_exists(or_replace)?;
 
         let name = self.parse_object_name()?;
 
@@ -725,6 +752,19 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    /// Parse an optional `LANGUAGE name` clause.
+    fn parse_language_clause(&mut self) -> Result<Option<String>> {
+        if self.consume_keyword(Keyword::Language)?.is_none() {
+            return Ok(None);
+        }
+        let token = self.advance()?;
+        match token.kind {
+            TokenKind::Identifier(name) => Ok(Some(name)),
+            TokenKind::Keyword(_) => Ok(Some(token.text)),
+            _ => Err(Error::expected_identifier(token.span)),
+        }
+    }
+
     /// Parse CREATE FUNCTION statement.
     fn parse_create_function(
         &mut self,
@@ -757,20 +797,7 @@ impl<'a> Parser<'a> {
             None
         };
 
-        // Optional LANGUAGE clause
-        let language = if self.consume_keyword(Keyword::Language)?.is_some() {
-            let token = self.advance()?;
-            match token.kind {
-                TokenKind::Identifier(name) => Some(name),
-                TokenKind::Keyword(_) => Some(token.text.to_string()),
-                _ => {
-                    return Err(Error::expected_identifier(token.span));
-                }
-            }
-        } else {
-            None
-        };
-
+        let mut language = self.parse_language_clause()?;
         let options = self.parse_options_clause()?;
 
         // Parse body
@@ -796,6 +823,10 @@ impl<'a> Parser<'a> {
             // No body - external function
             FunctionBody::External(String::new())
         };
+        // PostgreSQL writes LANGUAGE after the body.
+        if language.is_none() {
+            language = self.parse_language_clause()?;
+        }
 
         Ok(StatementKind::CreateFunction(CreateFunctionStatement {
             or_replace,
@@ -1313,7 +1344,7 @@ impl<'a> Parser<'a> {
             None
         };
 
-        let statement = Box::new(self.parse_statement()?);
+        let statement = Box::new(self.parse_nested_statement()?);
 
         Ok(StatementKind::Explain(ExplainStatement {
             analyze,
@@ -1429,7 +1460,7 @@ impl<'a> Parser<'a> {
             if self.consume(&TokenKind::Semicolon)?.is_some() {
                 continue;
             }
-            let stmt = self.parse_statement()?;
+            let stmt = self.parse_nested_statement()?;
             statements.push(stmt);
             self.consume(&TokenKind::Semicolon)?;
         }
@@ -1491,6 +1522,20 @@ mod tests {
         assert!(matches!(stmt.kind, StatementKind::CreateView(_)));
     }
 
+    #[test]
+    fn test_create_function_dollar_quoted_body() {
+        let sql = "CREATE FUNCTION inc(x INT64) RETURNS INT64 AS $body$\n  SELECT x + 1; -- 'it's'\n$body$ LANGUAGE sql";
+        let stmt = parse_stmt(sql);
+        let StatementKind::CreateFunction(function) = &stmt.kind else {
+            panic!("Expected CREATE FUNCTION statement");
+        };
+        assert_eq!(
+            function.body,
+            FunctionBody::External("\n  SELECT x + 1; -- 'it's'\n".to_string())
+        );
+        assert_eq!(function.language.as_deref(), Some("sql"));
+    }
+
     #[test]
     fn test_drop_table() {
         let stmt = parse_stmt("DROP TABLE IF EXISTS users CASCADE");
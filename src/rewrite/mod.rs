@@ -0,0 +1,373 @@
+//! AST-to-AST rewrites.
+//!
+//! [`fold_constants`] evaluates constant subexpressions and applies
+//! simplifications that hold under SQL's three-valued logic, so the result
+//! means the same as the input for every row, including rows with NULLs.
+//! [`normalize`] replaces literals with parameters, so statements of the
+//! same shape become equal, and [`fingerprint`] hashes that shape.
+
+use crate::analyzer::constant::{fold_binary, fold_unary};
+use crate::ast::visit::{walk_expr_mut, VisitorMut};
+use crate::ast::*;
+use crate::error::Span;
+use crate::types::Value;
+
+mod normalize;
+
+pub use normalize::{fingerprint, normalize};
+
+/// Fold constant subexpressions and simplify boolean logic in a statement.
+///
+/// - Operators over literals are evaluated: `2 + 3 * 4` becomes `14`,
+///   `'a' || 'b'` becomes `'ab'`, and `1 = 1` becomes `TRUE`. AND and OR
+///   follow three-valued logic, so `NULL AND FALSE` is `FALSE` while
+///   `NULL AND TRUE` is `NULL`. Integer overflow and division by zero are
+///   left for runtime.
+/// - `x AND TRUE` and `x OR FALSE` become `x`, and `NOT NOT x` becomes `x`.
+/// - NOT is pushed into comparisons and negatable predicates:
+///   `NOT (a = b)` becomes `a != b` and `NOT (x IN (1, 2))` becomes
+///   `x NOT IN (1, 2)`.
+/// - CASE branches with constant conditions are removed or taken.
+///
+/// Function calls are never evaluated, so side-effecting functions such as
+/// `RAND()` are kept. A rewritten node keeps the span of the node it
+/// replaces.
+///
+/// ```
+/// use vibesql::parser::Parser;
+/// use vibesql::rewrite::fold_constants;
+///
+/// let stmt = Parser::new("SELECT 2 + 3 * 4 FROM t WHERE 1 = 1 AND NOT (a = b)")
+///     .parse_statement()
+///     .unwrap();
+/// assert_eq!(
+///     fold_constants(stmt).to_string(),
+///     "SELECT 14 FROM t WHERE a != b"
+/// );
+/// ```
+pub fn fold_constants(mut stmt: Statement) -> Statement {
+    struct Folder;
+
+    impl VisitorMut for Folder {
+        fn visit_expr(&mut self, expr: &mut Expr) {
+            // Detach the left operands of a chain such as `a + b + c` and
+            // fold it from the inside out, rather than recursing once per
+            // operator.
+            let mut links = Vec::new();
+            let mut node = std::mem::replace(expr, Expr::new(ExprKind::Null, Span::default()));
+            while let ExprKind::BinaryOp { left, .. } = &mut node.kind {
+                let inner = take(left);
+                links.push(node);
+                node = inner;
+            }
+            walk_expr_mut(self, &mut node);
+            simplify(&mut node);
+            while let Some(mut link) = links.pop() {
+                if let ExprKind::BinaryOp { left, right, .. } = &mut link.kind {
+                    **left = node;
+                    self.visit_expr(right);
+                }
+                simplify(&mut link);
+                node = link;
+            }
+            *expr = node;
+        }
+    }
+
+    Folder.visit_statement(&mut stmt);
+    stmt
+}
+
+/// Simplify an expression whose children are already simplified.
+fn simplify(expr: &mut Expr) {
+    let replacement = match &mut expr.kind {
+        ExprKind::Parenthesized(inner) if Value::from_literal(&inner.kind).is_some() => {
+            Some(take(inner))
+        }
+        ExprKind::UnaryOp { op, expr: operand } => {
+            match Value::from_literal(&operand.kind).and_then(|v| fold_unary(*op, &v)) {
+                Some(value) => literal(value),
+                None if *op == UnaryOp::Not => negate(operand),
+                None => None,
+            }
+        }
+        ExprKind::BinaryOp { op, left, right } => {
+            let folded = match (
+                Value::from_literal(&left.kind),
+                Value::from_literal(&right.kind),
+            ) {
+                (Some(l), Some(r)) => fold_binary(*op, &l, &r).and_then(literal),
+                _ => None,
+            };
+            match (folded, *op) {
+                (Some(folded), _) => Some(folded),
+                (None, BinaryOp::And) if is_boolean(right, true) => Some(take(left)),
+                (None, BinaryOp::And) if is_boolean(left, true) => Some(take(right)),
+                (None, BinaryOp::Or) if is_boolean(right, false) => Some(take(left)),
+                (None, BinaryOp::Or) if is_boolean(left, false) => Some(take(right)),
+                _ => None,
+            }
+        }
+        ExprKind::Case {
+            operand,
+            conditions,
+            else_result,
+        } => simplify_case(operand, conditions, else_result),
+        _ => None,
+    };
+    if let Some(mut replacement) = replacement {
+        replacement.span = expr.span;
+        replacement.id = expr.id;
+        *expr = replacement;
+    }
+}
+
+/// The negation of `operand`, if it can be written without NOT.
+fn negate(operand: &mut Box<Expr>) -> Option<Expr> {
+    if let ExprKind::Parenthesized(inner) = &mut operand.kind {
+        return negate(inner);
+    }
+    match &mut operand.kind {
+        ExprKind::UnaryOp {
+            op: UnaryOp::Not,
+            expr,
+        } => return Some(take(expr)),
+        ExprKind::BinaryOp { op, .. } => *op = negated_comparison(*op)?,
+        ExprKind::Between { negated, .. }
+        | ExprKind::In { negated, .. }
+        | ExprKind::Like { negated, .. }
+        | ExprKind::SimilarTo { negated, .. }
+        | ExprKind::IsExpr { negated, .. }
+        | ExprKind::IsDistinct { negated, .. }
+        | ExprKind::InSubquery { negated, .. }
+        | ExprKind::Exists { negated, .. } => *negated = !*negated,
+        _ => return None,
+    }
+    Some(take(operand))
+}
+
+/// The comparison that is true exactly when `op` is false.
+fn negated_comparison(op: BinaryOp) -> Option<BinaryOp> {
+    Some(match op {
+        BinaryOp::Eq => BinaryOp::NotEq,
+        BinaryOp::NotEq => BinaryOp::Eq,
+        BinaryOp::Lt => BinaryOp::GtEq,
+        BinaryOp::LtEq => BinaryOp::Gt,
+        BinaryOp::Gt => BinaryOp::LtEq,
+        BinaryOp::GtEq => BinaryOp::Lt,
+        _ => return None,
+    })
+}
+
+/// Drop CASE branches that can never be taken, and collapse the CASE when
+/// its result is known.
+///
+/// A searched CASE branch is dropped when its condition is FALSE or NULL;
+/// a TRUE condition makes its result the ELSE of the remaining branches. A
+/// simple CASE with a literal operand compares it with literal WHEN values
+/// the same way.
+fn simplify_case(
+    operand: &mut Option<Box<Expr>>,
+    conditions: &mut Vec<(Box<Expr>, Box<Expr>)>,
+    else_result: &mut Option<Box<Expr>>,
+) -> Option<Expr> {
+    let operand_value = match operand {
+        Some(operand) => Some(Value::from_literal(&operand.kind)?),
+        None => None,
+    };
+    let branch_taken = |when: &Expr| -> Option<bool> {
+        let value = Value::from_literal(&when.kind)?;
+        let matched = match &operand_value {
+            Some(operand) => fold_binary(BinaryOp::Eq, operand, &value)?,
+            None => value,
+        };
+        match matched {
+            Value::Boolean(b) => Some(b),
+            Value::Null => Some(false),
+            _ => None,
+        }
+    };
+
+    let mut kept = Vec::with_capacity(conditions.len());
+    for (when, then) in std::mem::take(conditions) {
+        match branch_taken(&when) {
+            Some(false) => {}
+            Some(true) => {
+                *else_result = Some(then);
+                break;
+            }
+            None => kept.push((when, then)),
+        }
+    }
+    if kept.is_empty() {
+        return Some(match else_result.take() {
+            Some(result) => *result,
+            None => Expr::new(ExprKind::Null, Span::default()),
+        });
+    }
+    *conditions = kept;
+    None
+}
+
+/// A literal expression for a folded value, if the value has one.
+fn literal(value: Value) -> Option<Expr> {
+    let kind = match value {
+        Value::Null => ExprKind::Null,
+        Value::Boolean(b) => ExprKind::Boolean(b),
+        Value::Int64(n) => ExprKind::Integer(n),
+        Value::Float64(f) => ExprKind::Float(f),
+        Value::String(s) => ExprKind::String(s),
+        Value::Bytes(b) => ExprKind::Bytes(b),
+        _ => return None,
+    };
+    Some(Expr::new(kind, Span::default()))
+}
+
+/// Whether an expression is the boolean literal `value`.
+fn is_boolean(expr: &Expr, value: bool) -> bool {
+    matches!(expr.kind, ExprKind::Boolean(b) if b == value)
+}
+
+/// Move an expression out of its box, leaving a NULL in its place.
+fn take(expr: &mut Box<Expr>) -> Expr {
+    std::mem::replace(expr, Expr::new(ExprKind::Null, Span::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn fold(sql: &str) -> Statement {
+        fold_constants(Parser::new(sql).parse_statement().unwrap())
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        let cases = [
+            ("SELECT 2 + 3 * 4", "SELECT 14"),
+            (
+                "SELECT (2 + 3) * 4, -(1.5), 7 / 2, 7 % 2",
+                "SELECT 20, -1.5, 3, 1",
+            ),
+            ("SELECT 'a' || 'b' || 'c'", "SELECT 'abc'"),
+            ("SELECT * FROM t WHERE 1 = 1", "SELECT * FROM t WHERE TRUE"),
+            (
+                "SELECT 1 < 2, 'a' <> 'a', 1 + NULL",
+                "SELECT TRUE, FALSE, NULL",
+            ),
+            // Three-valued logic
+            (
+                "SELECT NULL AND FALSE, NULL AND TRUE, NULL OR TRUE, NULL OR FALSE, NOT NULL",
+                "SELECT FALSE, NULL, TRUE, NULL, NULL",
+            ),
+            (
+                "SELECT * FROM t WHERE x > 1 AND TRUE",
+                "SELECT * FROM t WHERE x > 1",
+            ),
+            (
+                "SELECT * FROM t WHERE TRUE AND x",
+                "SELECT * FROM t WHERE x",
+            ),
+            (
+                "SELECT * FROM t WHERE x OR 1 = 2",
+                "SELECT * FROM t WHERE x",
+            ),
+            (
+                "SELECT * FROM t WHERE x AND NULL",
+                "SELECT * FROM t WHERE x AND NULL",
+            ),
+            (
+                "SELECT * FROM t WHERE x OR TRUE",
+                "SELECT * FROM t WHERE x OR TRUE",
+            ),
+            // Negation
+            ("SELECT NOT NOT x", "SELECT x"),
+            ("SELECT NOT (NOT x)", "SELECT x"),
+            ("SELECT NOT (a = b), NOT (a < b)", "SELECT a != b, a >= b"),
+            (
+                "SELECT NOT (x IN (1, 2)), NOT (x IS NULL), NOT (x BETWEEN 1 AND 2)",
+                "SELECT x NOT IN (1, 2), x IS NOT NULL, x NOT BETWEEN 1 AND 2",
+            ),
+            ("SELECT NOT (a AND b)", "SELECT NOT (a AND b)"),
+            // CASE
+            ("SELECT CASE WHEN 1 = 1 THEN 'a' ELSE 'b' END", "SELECT 'a'"),
+            ("SELECT CASE WHEN FALSE THEN 'a' END", "SELECT NULL"),
+            (
+                "SELECT CASE WHEN NULL THEN 1 WHEN x THEN 2 WHEN TRUE THEN 3 WHEN y THEN 4 END",
+                "SELECT CASE WHEN x THEN 2 ELSE 3 END",
+            ),
+            (
+                "SELECT CASE 2 WHEN 1 THEN 'a' WHEN 1 + 1 THEN 'b' ELSE 'c' END",
+                "SELECT 'b'",
+            ),
+            (
+                "SELECT CASE NULL WHEN NULL THEN 'a' ELSE 'c' END",
+                "SELECT 'c'",
+            ),
+            (
+                "SELECT CASE x WHEN 1 THEN 'a' END",
+                "SELECT CASE x WHEN 1 THEN 'a' END",
+            ),
+            // Left for runtime
+            (
+                "SELECT 9223372036854775807 + 1, 1 / 0",
+                "SELECT 9223372036854775807 + 1, 1 / 0",
+            ),
+            // Functions are not evaluated
+            ("SELECT RAND() + (1 + 2)", "SELECT RAND() + 3"),
+            (
+                "SELECT * FROM t WHERE RAND() < 0.5 AND TRUE",
+                "SELECT * FROM t WHERE RAND() < 0.5",
+            ),
+            // Subqueries and other statements
+            (
+                "SELECT (SELECT 1 + 1) FROM t WHERE x IN (SELECT y FROM u WHERE 2 > 1)",
+                "SELECT (SELECT 2) FROM t WHERE x IN (SELECT y FROM u WHERE TRUE)",
+            ),
+            (
+                "UPDATE t SET a = 1 + 1 WHERE NOT (b <> 2)",
+                "UPDATE t SET a = 2 WHERE b = 2",
+            ),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(fold(sql).to_string(), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_fold_long_chain() {
+        let sql = format!("SELECT {}", vec!["1"; 10_000].join(" + "));
+        assert_eq!(fold(&sql).to_string(), "SELECT 10000");
+    }
+
+    #[test]
+    fn test_fold_constants_spans() {
+        let sql = "SELECT x + (2 * 3), CASE WHEN TRUE THEN y END FROM t";
+        let stmt = fold(sql);
+        let StatementKind::Query(query) = &stmt.kind else {
+            panic!("expected a query");
+        };
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected a SELECT");
+        };
+        let exprs: Vec<&Expr> = select
+            .projection
+            .iter()
+            .map(|item| match item {
+                SelectItem::Expr { expr, .. } => &**expr,
+                other => panic!("expected an expression, got {:?}", other),
+            })
+            .collect();
+        let ExprKind::BinaryOp { right, .. } = &exprs[0].kind else {
+            panic!("expected x + 6, got {:?}", exprs[0].kind);
+        };
+        assert_eq!(right.kind, ExprKind::Integer(6));
+        assert_eq!(&sql[right.span.start..right.span.end], "(2 * 3)");
+        assert!(matches!(exprs[1].kind, ExprKind::Identifier(_)));
+        assert_eq!(
+            &sql[exprs[1].span.start..exprs[1].span.end],
+            "CASE WHEN TRUE THEN y END"
+        );
+    }
+}
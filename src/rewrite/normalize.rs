@@ -0,0 +1,401 @@
+//! Query normalization and fingerprints.
+
+use crate::ast::visit::{
+    walk_binary_chain_mut, walk_cte_mut, walk_function_call_mut, walk_order_by_expr_mut,
+    walk_select_item_mut, walk_select_mut, walk_statement_mut, walk_table_ref_mut,
+    walk_window_spec_mut, VisitorMut,
+};
+use crate::ast::*;
+
+/// Rewrite a statement into the shape it shares with every statement that
+/// differs from it only in its literals and the case of its identifiers.
+///
+/// - Every literal becomes a `?` parameter: `WHERE id = 5` becomes
+///   `WHERE id = ?`. Existing `?` parameters are renumbered with them, in
+///   order of appearance. Integers that are ordinals, as in `ORDER BY 1`
+///   and `GROUP BY 1`, are kept, since they name a column.
+/// - An IN list of literals becomes a single parameter, so
+///   `x IN (1, 2, 3)` and `x IN (4)` are both `x IN (?)`.
+/// - Unquoted identifiers are lowercased; quoted identifiers are kept.
+///
+/// Nothing is reordered, so the result means the same as the input once
+/// its parameters are bound. Keywords and comments are not part of the
+/// tree, so neither their case nor their presence changes the result.
+///
+/// ```
+/// use vibesql::parser::Parser;
+/// use vibesql::rewrite::normalize;
+///
+/// let stmt = Parser::new("SELECT Name FROM Users WHERE id IN (1, 2) ORDER BY 1 LIMIT 10")
+///     .parse_statement()
+///     .unwrap();
+/// assert_eq!(
+///     normalize(stmt).to_string(),
+///     "SELECT name FROM users WHERE id IN (?) ORDER BY 1 LIMIT ?"
+/// );
+/// ```
+pub fn normalize(mut stmt: Statement) -> Statement {
+    Normalizer::default().visit_statement(&mut stmt);
+    stmt
+}
+
+/// A stable hash of a statement's [normalized](normalize) form, identifying
+/// its shape: `SELECT * FROM users WHERE id = 5` and `... WHERE id = 99`
+/// have the same fingerprint, while statements selecting different
+/// columns do not.
+///
+/// The fingerprint is the 64-bit FNV-1a hash of the UTF-8 text the
+/// normalized statement displays as. It only changes between minor
+/// releases, so fingerprints recorded with one patch release can be
+/// compared with those of the next.
+///
+/// ```
+/// use vibesql::parser::Parser;
+/// use vibesql::fingerprint;
+///
+/// let parse = |sql| Parser::new(sql).parse_statement().unwrap();
+/// let five = parse("SELECT * FROM users WHERE id = 5");
+/// let ninety_nine = parse("select * from USERS where id = 99");
+/// assert_eq!(fingerprint(&five), fingerprint(&ninety_nine));
+/// assert_ne!(fingerprint(&five), fingerprint(&parse("SELECT name FROM users WHERE id = 5")));
+/// ```
+pub fn fingerprint(stmt: &Statement) -> u64 {
+    fnv1a(normalize(stmt.clone()).to_string().as_bytes())
+}
+
+/// The 64-bit FNV-1a hash of `bytes`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[derive(Default)]
+struct Normalizer {
+    /// The number of the last `?` parameter.
+    parameters: u32,
+    /// The GROUP BY ordinals of the SELECTs being walked, which are kept.
+    ordinals: Vec<*const Expr>,
+}
+
+impl Normalizer {
+    fn parameter(&mut self, expr: &mut Expr) {
+        self.parameters += 1;
+        expr.kind = ExprKind::Parameter(Parameter::Positional(self.parameters));
+    }
+}
+
+impl VisitorMut for Normalizer {
+    fn visit_statement(&mut self, stmt: &mut Statement) {
+        match &mut stmt.kind {
+            StatementKind::Insert(insert) => {
+                lowercase_name(&mut insert.table);
+                insert.columns.iter_mut().for_each(lowercase);
+            }
+            StatementKind::Update(update) => {
+                for assignment in &mut update.assignments {
+                    match &mut assignment.target {
+                        AssignmentTarget::Column(column) => lowercase(column),
+                        AssignmentTarget::Path(path) => path.iter_mut().for_each(lowercase),
+                    }
+                }
+            }
+            StatementKind::Delete(delete) => {
+                lowercase_name(&mut delete.table);
+                lowercase_alias(&mut delete.alias);
+            }
+            _ => {}
+        }
+        walk_statement_mut(self, stmt);
+    }
+
+    fn visit_cte(&mut self, cte: &mut Cte) {
+        lowercase(&mut cte.name);
+        cte.columns.iter_mut().for_each(lowercase);
+        walk_cte_mut(self, cte);
+    }
+
+    fn visit_select(&mut self, select: &mut Select) {
+        for def in &mut select.window {
+            lowercase(&mut def.name);
+        }
+        let outer = self.ordinals.len();
+        if let Some(group_by) = &select.group_by {
+            for item in &group_by.items {
+                if let GroupByItem::Expr(expr) = item {
+                    if matches!(expr.kind, ExprKind::Integer(_)) {
+                        self.ordinals.push(&**expr);
+                    }
+                }
+            }
+        }
+        walk_select_mut(self, select);
+        self.ordinals.truncate(outer);
+    }
+
+    fn visit_select_item(&mut self, item: &mut SelectItem) {
+        match item {
+            SelectItem::Expr {
+                alias: Some(alias), ..
+            } => lowercase(alias),
+            SelectItem::QualifiedWildcard { qualifier } => lowercase_name(qualifier),
+            SelectItem::WildcardExcept { qualifier, except } => {
+                if let Some(qualifier) = qualifier {
+                    lowercase_name(qualifier);
+                }
+                except.iter_mut().for_each(lowercase);
+            }
+            _ => {}
+        }
+        walk_select_item_mut(self, item);
+    }
+
+    fn visit_table_ref(&mut self, table: &mut TableRef) {
+        match &mut table.kind {
+            TableRefKind::Table { name, alias, .. } => {
+                lowercase_name(name);
+                lowercase_alias(alias);
+            }
+            TableRefKind::Subquery { alias, .. } | TableRefKind::Pivot { alias, .. } => {
+                lowercase_alias(alias)
+            }
+            TableRefKind::Unnest {
+                alias,
+                offset_alias,
+                ..
+            } => {
+                lowercase_alias(alias);
+                if let Some(offset_alias) = offset_alias {
+                    lowercase(offset_alias);
+                }
+            }
+            TableRefKind::TableFunction { name, alias, .. } => {
+                lowercase_name(name);
+                lowercase_alias(alias);
+            }
+            TableRefKind::Join {
+                condition: Some(JoinCondition::Using(columns)),
+                ..
+            } => columns.iter_mut().for_each(lowercase),
+            _ => {}
+        }
+        walk_table_ref_mut(self, table);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if self
+            .ordinals
+            .iter()
+            .any(|&ordinal| std::ptr::eq(ordinal, expr))
+        {
+            return;
+        }
+        match &mut expr.kind {
+            ExprKind::Null
+            | ExprKind::Boolean(_)
+            | ExprKind::Integer(_)
+            | ExprKind::Float(_)
+            | ExprKind::String(_)
+            | ExprKind::Bytes(_)
+            | ExprKind::TypedLiteral { .. }
+            | ExprKind::Parameter(Parameter::Positional(_)) => return self.parameter(expr),
+            ExprKind::In {
+                expr: operand,
+                list: InList::Values(values),
+                ..
+            } if !values.is_empty() && values.iter().all(|value| is_literal(value)) => {
+                self.visit_expr(operand);
+                values.truncate(1);
+                return self.parameter(&mut values[0]);
+            }
+            ExprKind::Identifier(ident) | ExprKind::FieldAccess { field: ident, .. } => {
+                lowercase(ident)
+            }
+            ExprKind::CompoundIdentifier(parts) => parts.iter_mut().for_each(lowercase),
+            ExprKind::Struct { fields } => {
+                for field in fields {
+                    if let Some(name) = &mut field.name {
+                        lowercase(name);
+                    }
+                }
+            }
+            ExprKind::WindowFunction(call) => {
+                if let WindowSpecOrRef::Ref(name) = &mut call.window {
+                    lowercase(name);
+                }
+            }
+            _ => {}
+        }
+        walk_binary_chain_mut(self, expr, |_, _| {});
+    }
+
+    fn visit_function_call(&mut self, call: &mut FunctionCall) {
+        lowercase_name(&mut call.name);
+        for arg in &mut call.args {
+            if let FunctionArg::Named { name, .. } = arg {
+                lowercase(name);
+            }
+        }
+        walk_function_call_mut(self, call);
+    }
+
+    fn visit_window_spec(&mut self, spec: &mut WindowSpec) {
+        if let Some(base) = &mut spec.base {
+            lowercase(base);
+        }
+        walk_window_spec_mut(self, spec);
+    }
+
+    fn visit_order_by_expr(&mut self, item: &mut OrderByExpr) {
+        // An ordinal, as in `ORDER BY 1`
+        if matches!(item.expr.kind, ExprKind::Integer(_)) {
+            return;
+        }
+        walk_order_by_expr_mut(self, item);
+    }
+}
+
+/// Whether an expression is a literal, which [`normalize`] replaces.
+fn is_literal(expr: &Expr) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::Null
+            | ExprKind::Boolean(_)
+            | ExprKind::Integer(_)
+            | ExprKind::Float(_)
+            | ExprKind::String(_)
+            | ExprKind::Bytes(_)
+            | ExprKind::TypedLiteral { .. }
+    )
+}
+
+fn lowercase(ident: &mut Ident) {
+    if !ident.quoted {
+        ident.value = ident.value.to_lowercase();
+    }
+}
+
+fn lowercase_name(name: &mut ObjectName) {
+    name.parts.iter_mut().for_each(lowercase);
+}
+
+fn lowercase_alias(alias: &mut Option<Alias>) {
+    if let Some(alias) = alias {
+        lowercase(&mut alias.name);
+        alias.columns.iter_mut().for_each(lowercase);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn parse(sql: &str) -> Statement {
+        Parser::new(sql).parse_statement().unwrap()
+    }
+
+    #[test]
+    fn test_normalize() {
+        let cases = [
+            (
+                "SELECT * FROM users WHERE id = 5",
+                "SELECT * FROM users WHERE id = ?",
+            ),
+            (
+                "select U.Name AS N, 'x', -1.5, NULL, TRUE from Users u",
+                "SELECT u.name AS n, ?, -?, ?, ? FROM users AS u",
+            ),
+            (
+                "SELECT `MixedCase`, DATE '2024-01-31', b'ab' FROM t",
+                "SELECT `MixedCase`, ?, ? FROM t",
+            ),
+            (
+                "SELECT * FROM t WHERE a IN (1, 2, 3) AND b NOT IN ('x') AND c IN (d, 1)",
+                "SELECT * FROM t WHERE a IN (?) AND b NOT IN (?) AND c IN (d, ?)",
+            ),
+            (
+                "SELECT a, COUNT(*) FROM t GROUP BY 1, a + 1 ORDER BY 2 DESC, a * 2 LIMIT 5",
+                "SELECT a, count(*) FROM t GROUP BY 1, a + ? ORDER BY 2 DESC, a * ? LIMIT ?",
+            ),
+            (
+                "INSERT INTO Logs (Id, Msg) VALUES (1, 'a'), (?, 'b')",
+                "INSERT INTO logs (id, msg) VALUES (?, ?), (?, ?)",
+            ),
+            (
+                "UPDATE T SET Total = Total + 1 WHERE Id = 7",
+                "UPDATE t SET total = total + ? WHERE id = ?",
+            ),
+            (
+                "WITH Recent AS (SELECT * FROM T WHERE x > 3) SELECT * FROM Recent JOIN U USING (Id)",
+                "WITH recent AS (SELECT * FROM t WHERE x > ?) SELECT * FROM recent JOIN u USING (id)",
+            ),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(normalize(parse(sql)).to_string(), expected, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_normalize_numbers_parameters() {
+        let stmt = normalize(parse(
+            "SELECT ? FROM t WHERE a = 1 AND b IN (2, 3) AND c = $1",
+        ));
+        let mut parameters = Vec::new();
+        crate::ast::visit::walk_statement(&mut ParameterCollector(&mut parameters), &stmt);
+        assert_eq!(
+            parameters,
+            [
+                Parameter::Positional(1),
+                Parameter::Positional(2),
+                Parameter::Positional(3),
+                Parameter::Numbered(1),
+            ]
+        );
+    }
+
+    struct ParameterCollector<'a>(&'a mut Vec<Parameter>);
+
+    impl<'ast> crate::ast::visit::Visitor<'ast> for ParameterCollector<'_> {
+        fn visit_expr(&mut self, expr: &'ast Expr) {
+            if let ExprKind::Parameter(parameter) = &expr.kind {
+                self.0.push(parameter.clone());
+            }
+            crate::ast::visit::walk_expr(self, expr);
+        }
+    }
+
+    #[test]
+    fn test_long_chain() {
+        let chain = |n| format!("SELECT {}", vec![n; 10_000].join(" + "));
+        let ones = parse(&chain("1"));
+        assert_eq!(normalize(ones.clone()), normalize(parse(&chain("2"))));
+        assert_eq!(fingerprint(&ones), fingerprint(&parse(&chain("2"))));
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let fingerprint_of = |sql| fingerprint(&parse(sql));
+        let base = fingerprint_of("SELECT * FROM users WHERE id = 5");
+        assert_eq!(base, fingerprint_of("SELECT * FROM users WHERE id = 99"));
+        assert_eq!(
+            base,
+            fingerprint_of("select *\n-- by id\nfrom USERS where ID = /* any */ 1")
+        );
+        assert_ne!(base, fingerprint_of("SELECT name FROM users WHERE id = 5"));
+        assert_ne!(
+            base,
+            fingerprint_of("SELECT * FROM users WHERE id = 5 ORDER BY 1")
+        );
+        assert_ne!(
+            fingerprint_of("SELECT a FROM t ORDER BY 1"),
+            fingerprint_of("SELECT a FROM t ORDER BY 2")
+        );
+        // The algorithm is part of the crate's compatibility promise
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(base, fnv1a(b"SELECT * FROM users WHERE id = ?"));
+    }
+}
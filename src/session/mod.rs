@@ -0,0 +1,360 @@
+//! Session variables.
+//!
+//! A [`Session`] holds the values that `SET` statements assign and that
+//! `SHOW VARIABLES` lists. It knows a fixed set of variables, each with a
+//! type and a default; assigning any other variable is an error unless the
+//! session is [permissive](Session::permissive).
+//!
+//! ```
+//! use vibesql::ast::StatementKind;
+//! use vibesql::parser::Parser;
+//! use vibesql::session::Session;
+//! use vibesql::types::Value;
+//!
+//! let mut session = Session::new();
+//! let stmt = Parser::new("SET strict_mode = TRUE").parse_statement().unwrap();
+//! if let StatementKind::Set(set) = &stmt.kind {
+//!     session.apply_set(set).unwrap();
+//! }
+//! assert_eq!(session.get("STRICT_MODE"), Some(&Value::Boolean(true)));
+//! ```
+
+use crate::analyzer::constant::{fold_binary, fold_unary};
+use crate::ast::{Expr, ExprKind, SetStatement, SetValue, ShowFilter};
+use crate::error::{Error, ErrorKind, Result};
+use crate::types::{SqlType, Value};
+
+/// A variable a session knows, with its type and default value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDefinition {
+    pub name: String,
+    pub data_type: SqlType,
+    pub default: Value,
+}
+
+impl VariableDefinition {
+    pub fn new(name: impl Into<String>, data_type: SqlType, default: impl Into<Value>) -> Self {
+        Self {
+            name: name.into(),
+            data_type,
+            default: default.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Variable {
+    definition: VariableDefinition,
+    value: Value,
+}
+
+/// The variables of a session and their current values.
+#[derive(Debug, Clone)]
+pub struct Session {
+    variables: Vec<Variable>,
+    permissive: bool,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Session {
+    /// A session with the known variables at their defaults:
+    ///
+    /// | Variable | Type | Default |
+    /// |----------|------|---------|
+    /// | `strict_mode` | BOOLEAN | FALSE |
+    /// | `time_zone` | VARCHAR | `'UTC'` |
+    /// | `max_rows` | BIGINT | NULL |
+    pub fn new() -> Self {
+        let mut session = Self::empty();
+        session.define(VariableDefinition::new("strict_mode", SqlType::Bool, false));
+        session.define(VariableDefinition::new(
+            "time_zone",
+            SqlType::Varchar(None),
+            "UTC",
+        ));
+        session.define(VariableDefinition::new(
+            "max_rows",
+            SqlType::Int64,
+            Value::Null,
+        ));
+        session
+    }
+
+    /// A session with no variables defined.
+    pub fn empty() -> Self {
+        Self {
+            variables: Vec::new(),
+            permissive: false,
+        }
+    }
+
+    /// Allow `SET` to create variables the session does not know. A new
+    /// variable takes the type of the first value assigned to it, and
+    /// defaults to NULL.
+    pub fn permissive(mut self, permissive: bool) -> Self {
+        self.permissive = permissive;
+        self
+    }
+
+    /// Whether `SET` can create variables.
+    pub fn is_permissive(&self) -> bool {
+        self.permissive
+    }
+
+    /// Define a variable, or redefine one with the same name, at its default.
+    pub fn define(&mut self, definition: VariableDefinition) {
+        let value = definition.default.clone();
+        let variable = Variable { definition, value };
+        match self.position(&variable.definition.name) {
+            Some(i) => self.variables[i] = variable,
+            None => self.variables.push(variable),
+        }
+    }
+
+    /// The definition of a variable, matched case-insensitively.
+    pub fn definition(&self, name: &str) -> Option<&VariableDefinition> {
+        self.position(name).map(|i| &self.variables[i].definition)
+    }
+
+    /// The current value of a variable, matched case-insensitively.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.position(name).map(|i| &self.variables[i].value)
+    }
+
+    /// The variables and their current values, in definition order.
+    pub fn variables(&self) -> impl Iterator<Item = (&VariableDefinition, &Value)> {
+        self.variables.iter().map(|v| (&v.definition, &v.value))
+    }
+
+    /// Assign a variable. `SET name = DEFAULT` restores its default.
+    ///
+    /// The value must be a constant, such as `-1` or `'a' || 'b'`, of the
+    /// variable's type; an integer is accepted for a floating-point
+    /// variable, and NULL for any variable.
+    pub fn apply_set(&mut self, stmt: &SetStatement) -> Result<()> {
+        let name = &stmt.variable;
+        let value = match &stmt.value {
+            SetValue::Default => None,
+            SetValue::Expr(expr) => match constant_value(expr) {
+                Some(value) => Some((value, expr.span)),
+                None => {
+                    return Err(Error::with_span(
+                        ErrorKind::UnsupportedFeature(format!(
+                            "non-constant value for variable '{}'",
+                            name.value
+                        )),
+                        expr.span,
+                    ))
+                }
+            },
+        };
+
+        let Some(i) = self.position(&name.value) else {
+            if !self.permissive {
+                return Err(Error::with_span(
+                    ErrorKind::UndefinedVariable(name.value.clone()),
+                    name.span,
+                ));
+            }
+            let value = value.map_or(Value::Null, |(value, _)| value);
+            let data_type = value_type(&value);
+            self.variables.push(Variable {
+                definition: VariableDefinition::new(name.value.clone(), data_type, Value::Null),
+                value,
+            });
+            return Ok(());
+        };
+
+        let variable = &mut self.variables[i];
+        variable.value = match value {
+            None => variable.definition.default.clone(),
+            Some((value, span)) => {
+                let found = value.type_name();
+                coerce(value, &variable.definition.data_type).ok_or_else(|| {
+                    Error::with_span(
+                        ErrorKind::TypeMismatch {
+                            expected: variable.definition.data_type.to_string(),
+                            found: found.to_string(),
+                        },
+                        span,
+                    )
+                })?
+            }
+        };
+        Ok(())
+    }
+
+    /// The variables `SHOW VARIABLES` lists under `filter`, in definition
+    /// order. A `LIKE` pattern matches names case-insensitively; a `WHERE`
+    /// filter is not supported.
+    pub fn show_variables(
+        &self,
+        filter: Option<&ShowFilter>,
+    ) -> Result<Vec<(&VariableDefinition, &Value)>> {
+        match filter {
+            None => Ok(self.variables().collect()),
+            Some(ShowFilter::Like(pattern)) => Ok(self
+                .variables()
+                .filter(|(definition, _)| like(pattern, &definition.name))
+                .collect()),
+            Some(ShowFilter::Where(expr)) => Err(Error::with_span(
+                ErrorKind::UnsupportedFeature("SHOW VARIABLES WHERE".to_string()),
+                expr.span,
+            )),
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.variables
+            .iter()
+            .position(|v| v.definition.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// The value of an expression of literals and operators.
+fn constant_value(expr: &Expr) -> Option<Value> {
+    match &expr.kind {
+        ExprKind::Parenthesized(inner) => constant_value(inner),
+        ExprKind::UnaryOp { op, expr } => fold_unary(*op, &constant_value(expr)?),
+        ExprKind::BinaryOp { op, left, right } => {
+            fold_binary(*op, &constant_value(left)?, &constant_value(right)?)
+        }
+        kind => Value::from_literal(kind),
+    }
+}
+
+/// `value` as a value of `data_type`, if it is one.
+fn coerce(value: Value, data_type: &SqlType) -> Option<Value> {
+    match (value, data_type) {
+        (Value::Null, _) => Some(Value::Null),
+        (Value::Int64(n), SqlType::Float32 | SqlType::Float64) => Some(Value::Float64(n as f64)),
+        (value, data_type) if value_type(&value) == *data_type => Some(value),
+        (value @ Value::Int64(_), SqlType::Int32 | SqlType::Uint32 | SqlType::Uint64) => {
+            Some(value)
+        }
+        (value @ Value::Float64(_), SqlType::Float32) => Some(value),
+        (value @ Value::String(_), SqlType::Varchar(_)) => Some(value),
+        (value @ Value::Bytes(_), SqlType::Varbinary(_)) => Some(value),
+        _ => None,
+    }
+}
+
+/// The type of a constant, as a variable created by assigning it has.
+fn value_type(value: &Value) -> SqlType {
+    match value {
+        Value::Null | Value::Array(_) | Value::Struct(_) => SqlType::Unknown,
+        value => value.data_type(),
+    }
+}
+
+/// Whether `name` matches a LIKE pattern, ignoring ASCII case.
+fn like(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('%', rest)) => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+            Some(('\\', [escaped, rest @ ..])) => match name.split_first() {
+                Some((c, name)) if c.eq_ignore_ascii_case(escaped) => matches(rest, name),
+                _ => false,
+            },
+            Some((p, rest)) => match name.split_first() {
+                Some((c, name)) if *p == '_' || c.eq_ignore_ascii_case(p) => matches(rest, name),
+                _ => false,
+            },
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::StatementKind;
+    use crate::parser::Parser;
+
+    fn set(session: &mut Session, sql: &str) -> Result<()> {
+        match Parser::new(sql).parse_statement().unwrap().kind {
+            StatementKind::Set(set) => session.apply_set(&set),
+            other => panic!("not a SET: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_set_known_variables() {
+        let mut session = Session::new();
+        assert_eq!(session.get("strict_mode"), Some(&Value::Boolean(false)));
+
+        set(&mut session, "SET strict_mode = NOT FALSE").unwrap();
+        assert_eq!(session.get("Strict_Mode"), Some(&Value::Boolean(true)));
+        set(&mut session, "SET strict_mode = DEFAULT").unwrap();
+        assert_eq!(session.get("strict_mode"), Some(&Value::Boolean(false)));
+
+        set(&mut session, "SET max_rows = 10 * 100").unwrap();
+        assert_eq!(session.get("max_rows"), Some(&Value::Int64(1000)));
+        set(&mut session, "SET max_rows = NULL").unwrap();
+        assert_eq!(session.get("max_rows"), Some(&Value::Null));
+
+        let err = set(&mut session, "SET time_zone = 1").unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ErrorKind::TypeMismatch {
+                expected: "VARCHAR".to_string(),
+                found: "BIGINT".to_string(),
+            }
+        );
+        assert_eq!(session.get("time_zone"), Some(&Value::String("UTC".into())));
+
+        let err = set(&mut session, "SET max_rows = x").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnsupportedFeature(_)));
+    }
+
+    #[test]
+    fn test_unknown_variables() {
+        let mut session = Session::new();
+        let err = set(&mut session, "SET nope = 1").unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ErrorKind::UndefinedVariable("nope".to_string())
+        );
+        assert_eq!(err.span(), Some(crate::error::Span::new(4, 8)));
+
+        let mut session = Session::new().permissive(true);
+        set(&mut session, "SET nope = 'a'").unwrap();
+        assert_eq!(
+            session.definition("nope").unwrap().data_type,
+            SqlType::Varchar(None)
+        );
+        assert!(set(&mut session, "SET nope = 1").is_err());
+        set(&mut session, "SET nope = DEFAULT").unwrap();
+        assert_eq!(session.get("nope"), Some(&Value::Null));
+    }
+
+    #[test]
+    fn test_show_variables() {
+        let session = Session::new();
+        let names = |filter: Option<&ShowFilter>| -> Vec<String> {
+            session
+                .show_variables(filter)
+                .unwrap()
+                .into_iter()
+                .map(|(d, _)| d.name.clone())
+                .collect()
+        };
+        assert_eq!(names(None), ["strict_mode", "time_zone", "max_rows"]);
+        assert_eq!(
+            names(Some(&ShowFilter::Like("%\\_MODE".to_string()))),
+            ["strict_mode"]
+        );
+        assert_eq!(
+            names(Some(&ShowFilter::Like("t_me%".to_string()))),
+            ["time_zone"]
+        );
+    }
+}
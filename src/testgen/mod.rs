@@ -0,0 +1,572 @@
+//! Random schema and query generation for property-based testing.
+//!
+//! [`TestGen`] produces a random [`MemoryCatalog`] and random queries over it
+//! that are valid by construction. Each [`GeneratedQuery`] carries the SQL
+//! text, the parsed statement, and the [`AnalyzedQuery`] the analyzer is
+//! expected to produce, so downstream engines can check their own output
+//! schemas against it. Output is fully determined by the seed and the
+//! [`GenConfig`].
+//!
+//! This module is compiled with the `testing` feature.
+//!
+//! ```
+//! use vibesql::testgen::{GenConfig, TestGen};
+//! use vibesql::Analyzer;
+//!
+//! let mut gen = TestGen::with_config(42, GenConfig::new(4).with_subqueries(false));
+//! let catalog = gen.catalog();
+//! let query = gen.query(&catalog).unwrap();
+//!
+//! let mut analyzer = Analyzer::with_catalog(catalog);
+//! let analyzed = analyzer.analyze(&query.statement).unwrap();
+//! assert!(!query.sql.is_empty());
+//! # let _ = analyzed;
+//! ```
+//!
+//! # Shaping the distribution
+//!
+//! - `size` bounds the number of tables, columns per table, projected
+//!   expressions, and WHERE predicates.
+//! - `joins` allows inner joins on columns of the same type.
+//! - `aggregates` allows GROUP BY, HAVING, and aggregate calls in the
+//!   SELECT list.
+//! - `subqueries` allows `IN (SELECT ...)` and `EXISTS` predicates.
+//! - `order_by` and `limit` allow the trailing ORDER BY and LIMIT clauses.
+//!   ORDER BY refers to output columns by position and uses the default
+//!   [`NullOrdering`].
+//!
+//! Generated tables are named `t0`, `t1`, ... and always start with a
+//! non-null `id BIGINT` column. Every projected expression is aliased
+//! `col0`, `col1`, ... so output names do not depend on derivation rules.
+
+use crate::analyzer::{AnalyzedQuery, NullOrdering, OutputColumn, SortKey, SortTarget};
+use crate::ast::{SortOrder, Statement};
+use crate::catalog::{Catalog, ColumnSchema, MemoryCatalog, TableSchema, TableSchemaBuilder};
+use crate::error::{Error, ErrorKind, Result};
+use crate::parser::Parser;
+use crate::types::SqlType;
+
+/// Column types used by generated tables.
+const COLUMN_TYPES: [SqlType; 5] = [
+    SqlType::Bool,
+    SqlType::Int64,
+    SqlType::Float64,
+    SqlType::Varchar(None),
+    SqlType::Date,
+];
+
+/// Knobs controlling what the generator emits.
+#[derive(Debug, Clone)]
+pub struct GenConfig {
+    /// Size budget for tables, columns, projections, and predicates.
+    pub size: usize,
+    /// Generate inner joins.
+    pub joins: bool,
+    /// Generate GROUP BY, HAVING, and aggregate calls.
+    pub aggregates: bool,
+    /// Generate `IN (SELECT ...)` and `EXISTS` predicates.
+    pub subqueries: bool,
+    /// Generate ORDER BY clauses.
+    pub order_by: bool,
+    /// Generate LIMIT clauses.
+    pub limit: bool,
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+impl GenConfig {
+    /// Create a configuration with every feature enabled.
+    pub fn new(size: usize) -> Self {
+        Self {
+            size: size.max(1),
+            joins: true,
+            aggregates: true,
+            subqueries: true,
+            order_by: true,
+            limit: true,
+        }
+    }
+
+    /// Enable or disable joins.
+    pub fn with_joins(mut self, joins: bool) -> Self {
+        self.joins = joins;
+        self
+    }
+
+    /// Enable or disable aggregation.
+    pub fn with_aggregates(mut self, aggregates: bool) -> Self {
+        self.aggregates = aggregates;
+        self
+    }
+
+    /// Enable or disable subqueries.
+    pub fn with_subqueries(mut self, subqueries: bool) -> Self {
+        self.subqueries = subqueries;
+        self
+    }
+
+    /// Enable or disable ORDER BY.
+    pub fn with_order_by(mut self, order_by: bool) -> Self {
+        self.order_by = order_by;
+        self
+    }
+
+    /// Enable or disable LIMIT.
+    pub fn with_limit(mut self, limit: bool) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// A generated query with its expected analysis.
+#[derive(Debug, Clone)]
+pub struct GeneratedQuery {
+    /// The SQL text.
+    pub sql: String,
+    /// The parsed statement.
+    pub statement: Statement,
+    /// The analysis result the analyzer should produce.
+    pub expected: AnalyzedQuery,
+}
+
+/// A deterministic generator of catalogs and queries.
+#[derive(Debug, Clone)]
+pub struct TestGen {
+    rng: Rng,
+    config: GenConfig,
+}
+
+/// A table in the FROM clause with its alias.
+struct Source {
+    alias: String,
+    table: TableSchema,
+}
+
+impl TestGen {
+    /// Create a generator with the default configuration.
+    pub fn new(seed: u64) -> Self {
+        Self::with_config(seed, GenConfig::default())
+    }
+
+    /// Create a generator with a custom configuration.
+    pub fn with_config(seed: u64, config: GenConfig) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            config,
+        }
+    }
+
+    /// The generator configuration.
+    pub fn config(&self) -> &GenConfig {
+        &self.config
+    }
+
+    /// Generate a catalog of random tables, with builtin functions registered.
+    pub fn catalog(&mut self) -> MemoryCatalog {
+        let mut catalog = MemoryCatalog::new();
+        catalog.register_builtins();
+
+        let table_count = 1 + self.rng.below(self.config.size.min(8));
+        for t in 0..table_count {
+            let mut builder = TableSchemaBuilder::new(format!("t{}", t))
+                .column(ColumnSchema::new("id", SqlType::Int64).not_null());
+            let column_count = 1 + self.rng.below(self.config.size.min(8));
+            for c in 0..column_count {
+                let data_type = self.rng.pick(&COLUMN_TYPES).clone();
+                let mut column = ColumnSchema::new(format!("c{}", c), data_type);
+                if self.rng.chance(30) {
+                    column = column.not_null();
+                }
+                builder = builder.column(column);
+            }
+            catalog.add_table(builder.build());
+        }
+        catalog
+    }
+
+    /// Generate a query over the tables of `catalog`.
+    ///
+    /// Fails if the catalog has no tables in its default schema.
+    pub fn query<C: Catalog>(&mut self, catalog: &C) -> Result<GeneratedQuery> {
+        let mut names = catalog.list_tables(None)?;
+        names.sort();
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            if let Some(table) = catalog.resolve_table(&[name])? {
+                tables.push(TableSchema::clone(&table));
+            }
+        }
+        if tables.is_empty() {
+            return Err(Error::new(ErrorKind::Internal(
+                "catalog has no tables to query".to_string(),
+            )));
+        }
+
+        let mut sql = String::from("SELECT ");
+        let sources = self.pick_sources(&tables);
+        let columns: Vec<(String, ColumnSchema)> = sources
+            .iter()
+            .flat_map(|s| {
+                s.table
+                    .columns
+                    .iter()
+                    .map(move |c| (format!("{}.{}", s.alias, c.name), c.clone()))
+            })
+            .collect();
+
+        let grouped = self.config.aggregates && self.rng.chance(40);
+        let mut group_by = Vec::new();
+        if grouped {
+            for _ in 0..1 + self.rng.below(2) {
+                let column = self.rng.pick(&columns).clone();
+                if !group_by.iter().any(|(name, _)| *name == column.0) {
+                    group_by.push(column);
+                }
+            }
+        }
+
+        // SELECT list
+        let mut items = Vec::new();
+        let mut output = Vec::new();
+        if grouped {
+            for (name, column) in &group_by {
+                items.push(name.clone());
+                output.push((column.data_type.clone(), column.nullable));
+            }
+            for _ in 0..1 + self.rng.below(self.config.size.min(3)) {
+                let (expr, data_type, nullable) = self.aggregate(&columns);
+                items.push(expr);
+                output.push((data_type, nullable));
+            }
+        } else {
+            for _ in 0..1 + self.rng.below(self.config.size.min(6)) {
+                let (name, column) = self.rng.pick(&columns).clone();
+                if column.data_type == SqlType::Int64 && self.rng.chance(25) {
+                    items.push(format!("{} + {}", name, self.rng.below(100)));
+                } else {
+                    items.push(name);
+                }
+                output.push((column.data_type, column.nullable));
+            }
+        }
+        let select_list: Vec<String> = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| format!("{} AS col{}", item, i))
+            .collect();
+        sql.push_str(&select_list.join(", "));
+
+        // FROM
+        sql.push_str(" FROM ");
+        sql.push_str(&sources[0].table.name);
+        sql.push_str(" AS ");
+        sql.push_str(&sources[0].alias);
+        for pair in sources.windows(2) {
+            let (left, right) = (&pair[0], &pair[1]);
+            let on = self.join_condition(left, right);
+            sql.push_str(&format!(
+                " JOIN {} AS {} ON {}",
+                right.table.name, right.alias, on
+            ));
+        }
+
+        // WHERE
+        if self.rng.chance(60) {
+            let count = 1 + self.rng.below(self.config.size.min(3));
+            let mut predicates = Vec::new();
+            for _ in 0..count {
+                predicates.push(self.predicate(&columns, &tables));
+            }
+            sql.push_str(" WHERE ");
+            sql.push_str(&predicates.join(if self.rng.chance(70) { " AND " } else { " OR " }));
+        }
+
+        // GROUP BY / HAVING
+        if grouped {
+            let names: Vec<&str> = group_by.iter().map(|(n, _)| n.as_str()).collect();
+            sql.push_str(" GROUP BY ");
+            sql.push_str(&names.join(", "));
+            if self.rng.chance(30) {
+                sql.push_str(&format!(" HAVING COUNT(*) > {}", self.rng.below(10)));
+            }
+        }
+
+        // ORDER BY, by output position
+        let mut sort_keys = Vec::new();
+        if self.config.order_by && self.rng.chance(40) {
+            let index = self.rng.below(items.len());
+            let order = if self.rng.chance(50) {
+                SortOrder::Asc
+            } else {
+                SortOrder::Desc
+            };
+            let direction = if order == SortOrder::Asc {
+                "ASC"
+            } else {
+                "DESC"
+            };
+            sql.push_str(&format!(" ORDER BY {} {}", index + 1, direction));
+            sort_keys.push(SortKey {
+                target: SortTarget::Output(index),
+                data_type: output[index].0.clone(),
+                order,
+                nulls: NullOrdering::default().resolve(order),
+                collation: None,
+            });
+        }
+
+        // LIMIT
+        if self.config.limit && self.rng.chance(30) {
+            sql.push_str(&format!(" LIMIT {}", 1 + self.rng.below(100)));
+        }
+
+        let statement = Parser::new(&sql).parse()?.remove(0);
+        let expected = AnalyzedQuery {
+            columns: output
+                .into_iter()
+                .enumerate()
+                .map(|(i, (data_type, nullable))| OutputColumn {
+                    name: format!("col{}", i),
+                    data_type,
+                    nullable,
+                    source: None,
+                    constant_value: None,
+                })
+                .collect(),
+            has_aggregation: grouped || items.iter().any(|i| is_aggregate_call(i)),
+            has_window_functions: false,
+            windows: Vec::new(),
+            sort_keys,
+            implicit_cross_joins: Vec::new(),
+        };
+
+        Ok(GeneratedQuery {
+            sql,
+            statement,
+            expected,
+        })
+    }
+
+    /// Pick the tables joined in the FROM clause.
+    fn pick_sources(&mut self, tables: &[TableSchema]) -> Vec<Source> {
+        let mut count = 1;
+        if self.config.joins {
+            while count < tables.len().min(3) && self.rng.chance(40) {
+                count += 1;
+            }
+        }
+        (0..count)
+            .map(|i| Source {
+                alias: format!("a{}", i),
+                table: self.rng.pick(tables).clone(),
+            })
+            .collect()
+    }
+
+    /// An equality between same-typed columns of two joined tables.
+    fn join_condition(&mut self, left: &Source, right: &Source) -> String {
+        let mut pairs = Vec::new();
+        for l in &left.table.columns {
+            for r in &right.table.columns {
+                if l.data_type == r.data_type {
+                    pairs.push((l.name.clone(), r.name.clone()));
+                }
+            }
+        }
+        // `id` columns always match, so `pairs` is never empty.
+        let (l, r) = self.rng.pick(&pairs).clone();
+        format!("{}.{} = {}.{}", left.alias, l, right.alias, r)
+    }
+
+    /// An aggregate call with its expected type and nullability.
+    fn aggregate(&mut self, columns: &[(String, ColumnSchema)]) -> (String, SqlType, bool) {
+        let (name, column) = self.rng.pick(columns).clone();
+        match self.rng.below(4) {
+            0 => ("COUNT(*)".to_string(), SqlType::Int64, false),
+            1 => (format!("COUNT({})", name), SqlType::Int64, false),
+            2 if column.data_type.is_numeric() => {
+                let func = if self.rng.chance(50) { "SUM" } else { "AVG" };
+                (format!("{}({})", func, name), SqlType::Float64, true)
+            }
+            _ => {
+                // MIN and MAX return their argument's type.
+                let func = if self.rng.chance(50) { "MIN" } else { "MAX" };
+                (format!("{}({})", func, name), column.data_type, true)
+            }
+        }
+    }
+
+    /// A boolean predicate over the FROM columns.
+    fn predicate(&mut self, columns: &[(String, ColumnSchema)], tables: &[TableSchema]) -> String {
+        let (name, column) = self.rng.pick(columns).clone();
+        if self.config.subqueries && self.rng.chance(20) {
+            let table = self.rng.pick(tables).clone();
+            return if column.data_type == SqlType::Int64 && self.rng.chance(50) {
+                format!("{} IN (SELECT id FROM {})", name, table.name)
+            } else {
+                format!("EXISTS (SELECT 1 FROM {})", table.name)
+            };
+        }
+        if self.rng.chance(15) {
+            let not = if self.rng.chance(50) { " NOT" } else { "" };
+            return format!("{} IS{} NULL", name, not);
+        }
+        let op = if column.data_type == SqlType::Bool {
+            *self.rng.pick(&["=", "<>"])
+        } else {
+            *self.rng.pick(&["=", "<>", "<", "<=", ">", ">="])
+        };
+        format!("{} {} {}", name, op, self.literal(&column.data_type))
+    }
+
+    /// A literal of the given type.
+    fn literal(&mut self, data_type: &SqlType) -> String {
+        match data_type {
+            SqlType::Bool => {
+                if self.rng.chance(50) {
+                    "TRUE".to_string()
+                } else {
+                    "FALSE".to_string()
+                }
+            }
+            SqlType::Float64 => format!("{}.{}", self.rng.below(1000), self.rng.below(10)),
+            SqlType::Varchar(_) => format!("'v{}'", self.rng.below(1000)),
+            SqlType::Date => format!(
+                "DATE '2024-{:02}-{:02}'",
+                1 + self.rng.below(12),
+                1 + self.rng.below(28)
+            ),
+            _ => self.rng.below(1000).to_string(),
+        }
+    }
+}
+
+/// Whether a generated SELECT item is an aggregate call.
+fn is_aggregate_call(item: &str) -> bool {
+    ["COUNT(", "SUM(", "AVG(", "MIN(", "MAX("]
+        .iter()
+        .any(|prefix| item.starts_with(prefix))
+}
+
+/// SplitMix64: small, fast, and stable across platforms.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..n` (`0` when `n` is zero).
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next_u64() % n as u64) as usize
+        }
+    }
+
+    /// True with the given percent probability.
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    pub(crate) fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::Analyzer;
+    use crate::ast::StatementKind;
+
+    fn output(query: &AnalyzedQuery) -> Vec<(String, SqlType, bool)> {
+        query
+            .columns
+            .iter()
+            .map(|c| (c.name.clone(), c.data_type.clone(), c.nullable))
+            .collect()
+    }
+
+    #[test]
+    fn test_generated_queries_analyze() {
+        for seed in 0..300 {
+            let mut gen = TestGen::new(seed);
+            let catalog = gen.catalog();
+            let mut analyzer = Analyzer::with_catalog(catalog.clone());
+            for _ in 0..5 {
+                let query = gen.query(&catalog).unwrap();
+                let StatementKind::Query(q) = &query.statement.kind else {
+                    panic!("expected a query: {}", query.sql);
+                };
+                let analyzed = analyzer
+                    .analyze_query_result(q)
+                    .unwrap_or_else(|e| panic!("seed {}: {}\n{}", seed, e, query.sql));
+                assert_eq!(
+                    output(&analyzed),
+                    output(&query.expected),
+                    "seed {}: {}",
+                    seed,
+                    query.sql
+                );
+                assert_eq!(
+                    analyzed.sort_keys, query.expected.sort_keys,
+                    "seed {}: {}",
+                    seed, query.sql
+                );
+                assert_eq!(
+                    analyzed.has_aggregation, query.expected.has_aggregation,
+                    "seed {}: {}",
+                    seed, query.sql
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let run = |seed| {
+            let mut gen = TestGen::new(seed);
+            let catalog = gen.catalog();
+            (0..10)
+                .map(|_| gen.query(&catalog).unwrap().sql)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn test_disabled_features_are_not_generated() {
+        let config = GenConfig::new(6)
+            .with_joins(false)
+            .with_aggregates(false)
+            .with_subqueries(false)
+            .with_order_by(false)
+            .with_limit(false);
+        for seed in 0..100 {
+            let mut gen = TestGen::with_config(seed, config.clone());
+            let catalog = gen.catalog();
+            let sql = gen.query(&catalog).unwrap().sql;
+            for keyword in ["JOIN", "GROUP BY", "SELECT 1", "IN (", "ORDER BY", "LIMIT"] {
+                assert!(!sql.contains(keyword), "{}", sql);
+            }
+        }
+    }
+}
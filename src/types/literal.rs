@@ -0,0 +1,914 @@
+//! The contents of typed literals such as `DATE '2024-01-31'`, parsed
+//! and validated.
+//!
+//! Each parser fails with a message saying what is malformed, such as
+//! `"month 13 is out of range"`.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// The number of microseconds in a day.
+pub(crate) const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// A date in the proleptic Gregorian calendar, in years 1 to 9999.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// The date, if it exists.
+    pub fn from_ymd(year: i32, month: u8, day: u8) -> Option<Date> {
+        let exists = (1..=9999).contains(&year)
+            && (1..=12).contains(&month)
+            && (1..=days_in_month(year, month)).contains(&day);
+        exists.then_some(Date { year, month, day })
+    }
+
+    /// Parse `YYYY-[M]M-[D]D`.
+    pub fn parse(text: &str) -> Result<Date, String> {
+        let mut cursor = Cursor::new(text);
+        let date = cursor.date()?;
+        cursor.end("date")?;
+        Ok(date)
+    }
+
+    /// The number of days from 1970-01-01 to the date.
+    pub fn days_since_epoch(&self) -> i32 {
+        // Howard Hinnant's days_from_civil, with years starting in March.
+        let year = self.year - i32::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = i32::from(self.month);
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i32::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// The date `days` after 1970-01-01, if it is in years 1 to 9999.
+    pub fn from_days_since_epoch(days: i32) -> Option<Date> {
+        let days = days.checked_add(719_468)?;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i32::from(month <= 2);
+        Date::from_ymd(year, month as u8, day as u8)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A time of day, to the microsecond.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub microsecond: u32,
+}
+
+impl Time {
+    /// Parse `[H]H:[M]M:[S]S[.F]`, with up to six fractional digits.
+    pub fn parse(text: &str) -> Result<Time, String> {
+        let mut cursor = Cursor::new(text);
+        let time = cursor.time()?;
+        cursor.end("time")?;
+        Ok(time)
+    }
+
+    /// The number of microseconds from midnight to the time.
+    pub fn micros_since_midnight(&self) -> i64 {
+        let seconds =
+            i64::from(self.hour) * 3600 + i64::from(self.minute) * 60 + i64::from(self.second);
+        seconds * 1_000_000 + i64::from(self.microsecond)
+    }
+
+    /// The time `micros` microseconds after midnight, if that is before
+    /// the next midnight.
+    pub fn from_micros_since_midnight(micros: i64) -> Option<Time> {
+        if !(0..MICROS_PER_DAY).contains(&micros) {
+            return None;
+        }
+        let seconds = micros / 1_000_000;
+        Some(Time {
+            hour: (seconds / 3600) as u8,
+            minute: (seconds / 60 % 60) as u8,
+            second: (seconds % 60) as u8,
+            microsecond: (micros % 1_000_000) as u32,
+        })
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.microsecond != 0 {
+            write!(f, ".{:06}", self.microsecond)?;
+        }
+        Ok(())
+    }
+}
+
+/// A date and a time of day, without a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Datetime {
+    pub date: Date,
+    pub time: Time,
+}
+
+impl Datetime {
+    /// Parse a date, optionally followed by `T` or a space and a time.
+    /// Without a time, the datetime is at midnight.
+    pub fn parse(text: &str) -> Result<Datetime, String> {
+        let mut cursor = Cursor::new(text);
+        let datetime = cursor.datetime()?;
+        cursor.end("datetime")?;
+        Ok(datetime)
+    }
+
+    /// The number of microseconds from 1970-01-01 00:00:00 to the
+    /// datetime.
+    pub fn micros_since_epoch(&self) -> i64 {
+        i64::from(self.date.days_since_epoch()) * MICROS_PER_DAY + self.time.micros_since_midnight()
+    }
+
+    /// The datetime `micros` microseconds after 1970-01-01 00:00:00, if it
+    /// is in years 1 to 9999.
+    pub fn from_micros_since_epoch(micros: i64) -> Option<Datetime> {
+        let days = i32::try_from(micros.div_euclid(MICROS_PER_DAY)).ok()?;
+        Some(Datetime {
+            date: Date::from_days_since_epoch(days)?,
+            time: Time::from_micros_since_midnight(micros.rem_euclid(MICROS_PER_DAY))?,
+        })
+    }
+}
+
+impl fmt::Display for Datetime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.date, self.time)
+    }
+}
+
+/// The time zone of a timestamp.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TimeZone {
+    /// An offset from UTC in minutes, positive east of Greenwich. `Z` is
+    /// offset 0.
+    Offset(i32),
+    /// A named zone, such as `UTC` or `America/Los_Angeles`.
+    Named(String),
+}
+
+/// A datetime in a time zone.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Timestamp {
+    pub datetime: Datetime,
+    /// The zone the timestamp names, if any. A timestamp without one is
+    /// in UTC.
+    pub zone: Option<TimeZone>,
+}
+
+impl Timestamp {
+    /// Parse a datetime optionally followed by a zone: `Z`, an offset
+    /// `{+|-}H[H][:MM]`, or a space and a zone name.
+    pub fn parse(text: &str) -> Result<Timestamp, String> {
+        let mut cursor = Cursor::new(text);
+        let datetime = cursor.datetime()?;
+        let zone = cursor.zone()?;
+        cursor.end("timestamp")?;
+        Ok(Timestamp { datetime, zone })
+    }
+
+    /// The number of microseconds from 1970-01-01 00:00:00 UTC to the
+    /// timestamp, or `None` for a named zone other than `UTC`, which this
+    /// crate has no time zone database to resolve.
+    pub fn utc_micros(&self) -> Option<i64> {
+        let offset_minutes = match &self.zone {
+            None => 0,
+            Some(TimeZone::Offset(minutes)) => *minutes,
+            Some(TimeZone::Named(name)) if name.eq_ignore_ascii_case("UTC") => 0,
+            Some(TimeZone::Named(_)) => return None,
+        };
+        Some(self.datetime.micros_since_epoch() - i64::from(offset_minutes) * 60_000_000)
+    }
+}
+
+/// An exact decimal number, as written in a NUMERIC literal.
+///
+/// The number is kept as its significant digits and a scale, without
+/// leading zeros or trailing fractional zeros, so equal numbers compare
+/// equal however they were written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Numeric {
+    negative: bool,
+    digits: String,
+    scale: u32,
+}
+
+impl Numeric {
+    /// Parse `[+|-]digits[.digits]`, where either side of the decimal
+    /// point may be empty but not both.
+    pub fn parse(text: &str) -> Result<Numeric, String> {
+        let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+        let negative = text.starts_with('-');
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if integer.is_empty() && fraction.is_empty() {
+            return Err("expected digits".to_string());
+        }
+        if let Some(c) = integer
+            .chars()
+            .chain(fraction.chars())
+            .find(|c| !c.is_ascii_digit())
+        {
+            return Err(format!("unexpected '{}' in a number", c));
+        }
+        let fraction = fraction.trim_end_matches('0');
+        let digits = format!("{}{}", integer, fraction);
+        let digits = digits.trim_start_matches('0');
+        if digits.is_empty() {
+            return Ok(Numeric::zero());
+        }
+        Ok(Numeric {
+            negative,
+            digits: digits.to_string(),
+            scale: fraction.len() as u32,
+        })
+    }
+
+    fn zero() -> Numeric {
+        Numeric {
+            negative: false,
+            digits: "0".to_string(),
+            scale: 0,
+        }
+    }
+
+    /// Whether the number is less than zero.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The significant digits, without the decimal point.
+    pub fn digits(&self) -> &str {
+        &self.digits
+    }
+
+    /// The number of digits after the decimal point.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// The number of digits before the decimal point.
+    pub fn integer_digits(&self) -> u32 {
+        (self.digits.len() as u32).saturating_sub(self.scale)
+    }
+
+    /// The number `mantissa` × 10<sup>-scale</sup>.
+    pub fn from_i128(mantissa: i128, scale: u32) -> Numeric {
+        let digits = mantissa.unsigned_abs().to_string();
+        let trailing_zeros = (digits.len() - digits.trim_end_matches('0').len()) as u32;
+        let dropped = trailing_zeros.min(scale);
+        if mantissa == 0 {
+            return Numeric::zero();
+        }
+        Numeric {
+            negative: mantissa < 0,
+            digits: digits[..digits.len() - dropped as usize].to_string(),
+            scale: scale - dropped,
+        }
+    }
+
+    /// The digits as a signed integer, so the number is the result
+    /// × 10<sup>-scale</sup>; `None` beyond 38 digits.
+    pub(crate) fn mantissa(&self) -> Option<i128> {
+        let magnitude: i128 = self.digits.parse().ok()?;
+        Some(if self.negative { -magnitude } else { magnitude })
+    }
+
+    /// The number rounded half away from zero to `scale` fractional
+    /// digits; `None` if rounding carries it beyond 38 digits.
+    pub fn round(&self, scale: u32) -> Option<Numeric> {
+        if self.scale <= scale {
+            return Some(self.clone());
+        }
+        let dropped = (self.scale - scale) as usize;
+        let kept = self.digits.len().saturating_sub(dropped);
+        let round_up = self.digits.len() >= dropped && self.digits.as_bytes()[kept] >= b'5';
+        let magnitude: i128 = match &self.digits[..kept] {
+            "" => 0,
+            digits => digits.parse().ok()?,
+        };
+        let magnitude = magnitude.checked_add(i128::from(round_up))?;
+        let mantissa = if self.negative { -magnitude } else { magnitude };
+        Some(Numeric::from_i128(mantissa, scale))
+    }
+}
+
+impl PartialOrd for Numeric {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Numeric {
+    /// Numeric order, exact at any number of digits.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let magnitude = || {
+            // Integer parts compare by length first, as neither has
+            // leading zeros; fractions compare digit by digit.
+            let (a_integer, a_fraction) = self.split();
+            let (b_integer, b_fraction) = other.split();
+            a_integer
+                .len()
+                .cmp(&b_integer.len())
+                .then_with(|| a_integer.cmp(&b_integer))
+                .then_with(|| a_fraction.cmp(&b_fraction))
+        };
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => magnitude(),
+            (true, true) => magnitude().reverse(),
+        }
+    }
+}
+
+impl Numeric {
+    /// The digits before and after the decimal point, with a zero integer
+    /// part empty.
+    fn split(&self) -> (String, String) {
+        if self.digits == "0" {
+            return (String::new(), String::new());
+        }
+        let padded = format!("{:0>width$}", self.digits, width = self.scale as usize);
+        let (integer, fraction) = padded.split_at(padded.len() - self.scale as usize);
+        (integer.to_string(), fraction.to_string())
+    }
+}
+
+impl fmt::Display for Numeric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}", self.digits);
+        }
+        let padded = format!("{:0>width$}", self.digits, width = scale + 1);
+        let (integer, fraction) = padded.split_at(padded.len() - scale);
+        write!(f, "{}.{}", integer, fraction)
+    }
+}
+
+/// How deeply [`check_json`] lets arrays and objects nest.
+pub const MAX_JSON_DEPTH: usize = 500;
+
+/// Check that `text` is one JSON value, optionally surrounded by
+/// whitespace.
+pub fn check_json(text: &str) -> Result<(), String> {
+    let mut json = JsonChecker {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    json.value(0)?;
+    json.whitespace();
+    match json.peek() {
+        None => Ok(()),
+        Some(_) => Err(json.unexpected("after the JSON value")),
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// A position in the text of a date/time literal.
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char, after: &str) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' after the {}", c, after))
+        }
+    }
+
+    /// Between `min` and `max` digits naming `what`.
+    fn number(&mut self, min: usize, max: usize, what: &str) -> Result<u32, String> {
+        let count = self.rest().bytes().take_while(u8::is_ascii_digit).count();
+        if count < min || count > max {
+            let digits = if min == max {
+                format!("{}", min)
+            } else {
+                format!("{} to {}", min, max)
+            };
+            return Err(format!("expected {} digits for the {}", digits, what));
+        }
+        let digits = &self.rest()[..count];
+        self.pos += count;
+        // At most 9 digits always fit.
+        Ok(digits.parse().unwrap_or(u32::MAX))
+    }
+
+    fn end(&self, what: &str) -> Result<(), String> {
+        match self.rest() {
+            "" => Ok(()),
+            rest => Err(format!("unexpected '{}' after the {}", rest, what)),
+        }
+    }
+
+    fn date(&mut self) -> Result<Date, String> {
+        let year = self.number(4, 4, "year")? as i32;
+        self.expect('-', "year")?;
+        let month = self.number(1, 2, "month")?;
+        self.expect('-', "month")?;
+        let day = self.number(1, 2, "day")?;
+        if year == 0 {
+            return Err("year 0 is out of range".to_string());
+        }
+        if !(1..=12).contains(&month) {
+            return Err(format!("month {} is out of range", month));
+        }
+        Date::from_ymd(year, month as u8, day as u8)
+            .ok_or_else(|| format!("day {} is out of range for {:04}-{:02}", day, year, month))
+    }
+
+    fn time(&mut self) -> Result<Time, String> {
+        let hour = self.number(1, 2, "hour")?;
+        self.expect(':', "hour")?;
+        let minute = self.number(1, 2, "minute")?;
+        self.expect(':', "minute")?;
+        let second = self.number(1, 2, "second")?;
+        let microsecond = if self.eat('.') {
+            let start = self.pos;
+            let fraction = self.number(1, 6, "fraction of a second")?;
+            let width = (self.pos - start) as u32;
+            fraction * 10u32.pow(6 - width)
+        } else {
+            0
+        };
+        for (value, max, what) in [
+            (hour, 23, "hour"),
+            (minute, 59, "minute"),
+            (second, 59, "second"),
+        ] {
+            if value > max {
+                return Err(format!("{} {} is out of range", what, value));
+            }
+        }
+        Ok(Time {
+            hour: hour as u8,
+            minute: minute as u8,
+            second: second as u8,
+            microsecond,
+        })
+    }
+
+    fn datetime(&mut self) -> Result<Datetime, String> {
+        let date = self.date()?;
+        // A space starts a time only when a digit follows; otherwise it
+        // may start a timestamp's zone name.
+        let starts_time = match self.rest().as_bytes() {
+            [b'T' | b't', ..] => true,
+            [b' ', digit, ..] => digit.is_ascii_digit(),
+            _ => false,
+        };
+        let time = if starts_time {
+            self.pos += 1;
+            self.time()?
+        } else {
+            Time {
+                hour: 0,
+                minute: 0,
+                second: 0,
+                microsecond: 0,
+            }
+        };
+        Ok(Datetime { date, time })
+    }
+
+    fn zone(&mut self) -> Result<Option<TimeZone>, String> {
+        if self.eat('Z') || self.eat('z') {
+            return Ok(Some(TimeZone::Offset(0)));
+        }
+        let spaced = self.eat(' ');
+        let sign = if self.eat('+') {
+            1
+        } else if self.eat('-') {
+            -1
+        } else if spaced {
+            let name = self.rest();
+            let valid = name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+            if name.is_empty() || !valid {
+                return Err(format!("invalid time zone '{}'", name));
+            }
+            self.pos = self.text.len();
+            return Ok(Some(TimeZone::Named(name.to_string())));
+        } else {
+            return Ok(None);
+        };
+        let hours = self.number(1, 2, "time zone hour")?;
+        let minutes = if self.eat(':') {
+            self.number(2, 2, "time zone minute")?
+        } else {
+            0
+        };
+        if hours > 14 || minutes > 59 || (hours == 14 && minutes > 0) {
+            return Err("time zone offset is out of range".to_string());
+        }
+        Ok(Some(TimeZone::Offset(sign * (hours * 60 + minutes) as i32)))
+    }
+}
+
+/// A recursive-descent check of JSON text.
+struct JsonChecker<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl JsonChecker<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn unexpected(&self, context: &str) -> String {
+        match self.peek() {
+            None => format!("unexpected end of JSON {}", context),
+            Some(_) => {
+                // Report the whole character at a UTF-8 boundary.
+                let rest = String::from_utf8_lossy(&self.bytes[self.pos..]);
+                let c = rest.chars().next().unwrap_or('?');
+                format!("unexpected '{}' at offset {} {}", c, self.pos, context)
+            }
+        }
+    }
+
+    fn expect(&mut self, byte: u8, context: &str) -> Result<(), String> {
+        self.whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected(context))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<(), String> {
+        self.whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(depth + 1),
+            Some(b'[') => self.array(depth + 1),
+            Some(b'"') => self.string(),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b't') => self.word("true"),
+            Some(b'f') => self.word("false"),
+            Some(b'n') => self.word("null"),
+            _ => Err(self.unexpected("where a value was expected")),
+        }
+    }
+
+    fn nest(&self, depth: usize) -> Result<(), String> {
+        if depth > MAX_JSON_DEPTH {
+            Err(format!("JSON nests deeper than {} levels", MAX_JSON_DEPTH))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<(), String> {
+        self.nest(depth)?;
+        self.pos += 1;
+        self.whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            self.whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.unexpected("where an object key was expected"));
+            }
+            self.string()?;
+            self.expect(b':', "after an object key")?;
+            self.value(depth)?;
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.unexpected("in an object")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<(), String> {
+        self.nest(depth)?;
+        self.pos += 1;
+        self.whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            self.value(depth)?;
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.unexpected("in an array")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<(), String> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                None => return Err(format!("unterminated string at offset {}", start)),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't') => {
+                            self.pos += 1
+                        }
+                        Some(b'u') => {
+                            self.pos += 1;
+                            for _ in 0..4 {
+                                if !self.peek().is_some_and(|b| b.is_ascii_hexdigit()) {
+                                    return Err(self.unexpected("in a \\u escape"));
+                                }
+                                self.pos += 1;
+                            }
+                        }
+                        _ => return Err(self.unexpected("after '\\' in a string")),
+                    }
+                }
+                Some(byte) if byte < 0x20 => {
+                    return Err(format!(
+                        "unescaped control character at offset {} in a string",
+                        self.pos
+                    ))
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn number(&mut self) -> Result<(), String> {
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => {
+                self.digits();
+            }
+            _ => return Err(self.unexpected("in a number")),
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if self.digits() == 0 {
+                return Err(self.unexpected("after a decimal point"));
+            }
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if self.digits() == 0 {
+                return Err(self.unexpected("in an exponent"));
+            }
+        }
+        Ok(())
+    }
+
+    fn word(&mut self, word: &str) -> Result<(), String> {
+        if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(())
+        } else {
+            Err(self.unexpected("where a value was expected"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dates() {
+        let date = Date::parse("2024-2-29").unwrap();
+        assert_eq!((date.year, date.month, date.day), (2024, 2, 29));
+        assert_eq!(date.to_string(), "2024-02-29");
+        for (days, text) in [
+            (0, "1970-01-01"),
+            (-1, "1969-12-31"),
+            (19_782, "2024-02-29"),
+            (-719_162, "0001-01-01"),
+            (2_932_896, "9999-12-31"),
+        ] {
+            let date = Date::parse(text).unwrap();
+            assert_eq!(date.days_since_epoch(), days, "{}", text);
+            assert_eq!(Date::from_days_since_epoch(days), Some(date));
+        }
+        assert_eq!(Date::from_days_since_epoch(2_932_897), None);
+
+        for (text, reason) in [
+            ("2024-13-45", "month 13 is out of range"),
+            ("2023-02-29", "day 29 is out of range for 2023-02"),
+            ("1900-02-29", "day 29 is out of range for 1900-02"),
+            ("24-01-01", "expected 4 digits for the year"),
+            ("2024/01/01", "expected '-' after the year"),
+            ("2024-01-01x", "unexpected 'x' after the date"),
+        ] {
+            assert_eq!(Date::parse(text), Err(reason.to_string()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_times_and_datetimes() {
+        let time = Time::parse("23:59:59.5").unwrap();
+        assert_eq!(time.microsecond, 500_000);
+        assert_eq!(time.micros_since_midnight(), 86_399_500_000);
+        assert_eq!(time.to_string(), "23:59:59.500000");
+        assert_eq!(
+            Time::parse("24:00:00"),
+            Err("hour 24 is out of range".to_string())
+        );
+        assert_eq!(
+            Time::parse("12:00:00.1234567"),
+            Err("expected 1 to 6 digits for the fraction of a second".to_string())
+        );
+
+        let datetime = Datetime::parse("1970-01-02T00:00:01").unwrap();
+        assert_eq!(datetime.micros_since_epoch(), 86_401_000_000);
+        assert_eq!(
+            Datetime::parse("2024-01-01").unwrap().time,
+            Time::parse("0:0:0").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let utc = Timestamp::parse("2024-01-01 00:00:00Z").unwrap();
+        let offset = Timestamp::parse("2024-01-01 01:30:00+01:30").unwrap();
+        assert_eq!(offset.zone, Some(TimeZone::Offset(90)));
+        assert_eq!(utc.utc_micros(), offset.utc_micros());
+        assert_eq!(
+            Timestamp::parse("2024-01-01 -8").unwrap().zone,
+            Some(TimeZone::Offset(-480))
+        );
+
+        let named = Timestamp::parse("2024-01-01 12:00:00 America/Los_Angeles").unwrap();
+        assert_eq!(
+            named.zone,
+            Some(TimeZone::Named("America/Los_Angeles".to_string()))
+        );
+        assert_eq!(named.utc_micros(), None);
+        assert_eq!(
+            Timestamp::parse("2024-01-01 UTC").unwrap().utc_micros(),
+            Timestamp::parse("2024-01-01").unwrap().utc_micros()
+        );
+
+        for (text, reason) in [
+            ("not a timestamp", "expected 4 digits for the year"),
+            ("2024-01-01 00:00:00+15", "time zone offset is out of range"),
+            ("2024-01-01 00:00:00 ", "invalid time zone ''"),
+        ] {
+            assert_eq!(Timestamp::parse(text), Err(reason.to_string()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_numerics() {
+        let numeric = Numeric::parse("-0012.3400").unwrap();
+        assert!(numeric.is_negative());
+        assert_eq!(
+            (numeric.digits(), numeric.scale(), numeric.integer_digits()),
+            ("1234", 2, 2)
+        );
+        assert_eq!(numeric.to_string(), "-12.34");
+        assert_eq!(Numeric::parse(".05").unwrap().to_string(), "0.05");
+        assert_eq!(Numeric::parse("-0.0"), Numeric::parse("0"));
+        assert_eq!(Numeric::parse("1."), Numeric::parse("1"));
+
+        assert_eq!(Numeric::parse("."), Err("expected digits".to_string()));
+        assert_eq!(
+            Numeric::parse("1.2.3"),
+            Err("unexpected '.' in a number".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_json() {
+        for text in [
+            "{}",
+            " [1, -2.5e+3, \"a\\u00e9\\n\", true, false, null, {\"k\": []}] ",
+            "\"é\"",
+            "0",
+        ] {
+            assert_eq!(check_json(text), Ok(()), "{}", text);
+        }
+        for (text, reason) in [
+            (
+                "{invalid",
+                "unexpected 'i' at offset 1 where an object key was expected",
+            ),
+            (
+                "[1,]",
+                "unexpected ']' at offset 3 where a value was expected",
+            ),
+            ("[1 2]", "unexpected '2' at offset 3 in an array"),
+            ("\"abc", "unterminated string at offset 0"),
+            ("01", "unexpected '1' at offset 1 after the JSON value"),
+            (
+                "{\"a\" 1}",
+                "unexpected '1' at offset 5 after an object key",
+            ),
+            ("", "unexpected end of JSON where a value was expected"),
+        ] {
+            assert_eq!(check_json(text), Err(reason.to_string()), "{}", text);
+        }
+
+        let deep = "[".repeat(MAX_JSON_DEPTH + 1);
+        assert_eq!(
+            check_json(&deep),
+            Err(format!("JSON nests deeper than {} levels", MAX_JSON_DEPTH))
+        );
+    }
+}
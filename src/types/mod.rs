@@ -3,8 +3,10 @@
 //! This module provides runtime type information for SQL expressions
 //! and values, used during semantic analysis.
 
+pub mod literal;
 pub mod sql_type;
 pub mod value;
 
+pub use literal::*;
 pub use sql_type::*;
 pub use value::*;
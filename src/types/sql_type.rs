@@ -12,8 +12,9 @@ use std::fmt;
 /// - `Int64` displays as "BIGINT"
 /// - `Float32` displays as "REAL"
 /// - `Float64` displays as "DOUBLE PRECISION"
-/// - `Varchar` displays as "VARCHAR"
-/// - `Varbinary` displays as "VARBINARY"
+/// - `Varchar(None)` displays as "VARCHAR", and `Varchar(Some(10))` as
+///   "VARCHAR(10)"
+/// - `Varbinary(None)` displays as "VARBINARY"
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SqlType {
     /// Boolean type (BOOLEAN)
@@ -43,11 +44,13 @@ pub enum SqlType {
         scale: Option<u8>,
     },
 
-    /// Variable-length character string (VARCHAR)
-    Varchar,
+    /// Variable-length character string (VARCHAR), with its maximum length
+    /// in characters if it has one
+    Varchar(Option<u32>),
 
-    /// Variable-length binary data (VARBINARY)
-    Varbinary,
+    /// Variable-length binary data (VARBINARY), with its maximum length in
+    /// bytes if it has one
+    Varbinary(Option<u32>),
 
     /// Date (year, month, day)
     Date,
@@ -86,6 +89,59 @@ pub enum SqlType {
     Any,
 }
 
+/// How a value converts from one type to another, from
+/// [`SqlType::can_cast_to`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CastKind {
+    /// Converted automatically, as in comparisons and assignments.
+    Implicit,
+    /// Converted only by an explicit `CAST`.
+    Explicit,
+    /// Not convertible.
+    Invalid,
+}
+
+impl CastKind {
+    /// The more restrictive of two kinds.
+    pub fn weakest(self, other: CastKind) -> CastKind {
+        match (self, other) {
+            (CastKind::Invalid, _) | (_, CastKind::Invalid) => CastKind::Invalid,
+            (CastKind::Explicit, _) | (_, CastKind::Explicit) => CastKind::Explicit,
+            _ => CastKind::Implicit,
+        }
+    }
+
+    /// Demote `Implicit` to `Explicit`, for conversions that are not
+    /// coercions even though all their parts are.
+    fn at_most_explicit(self) -> CastKind {
+        self.weakest(CastKind::Explicit)
+    }
+}
+
+/// The largest precision of a `NUMERIC` type.
+pub const MAX_NUMERIC_PRECISION: u8 = 38;
+
+/// An arithmetic operator, for [`SqlType::numeric_arithmetic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArithmeticOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+/// The result of arithmetic on `NUMERIC` operands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumericArithmetic {
+    /// The result type.
+    pub result: SqlType,
+    /// The precision the result needed when it was more than
+    /// [`MAX_NUMERIC_PRECISION`]. The result keeps its integer digits and
+    /// gives up scale, down to 6 digits, to fit.
+    pub capped_precision: Option<u32>,
+}
+
 /// A field in a struct type.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StructField {
@@ -133,7 +189,15 @@ impl SqlType {
 
     /// Check if this type is a string type.
     pub fn is_string(&self) -> bool {
-        matches!(self, SqlType::Varchar)
+        matches!(self, SqlType::Varchar(_))
+    }
+
+    /// The maximum length of a `VARCHAR` or `VARBINARY` type, if it has one.
+    pub fn max_length(&self) -> Option<u32> {
+        match self {
+            SqlType::Varchar(len) | SqlType::Varbinary(len) => *len,
+            _ => None,
+        }
     }
 
     /// Check if this type is a date/time type.
@@ -144,6 +208,14 @@ impl SqlType {
         )
     }
 
+    /// Check if values of this type can be sorted.
+    ///
+    /// Arrays, structs, and JSON have no total order. Unknown and Any are
+    /// accepted since they may resolve to an orderable type.
+    pub fn is_orderable(&self) -> bool {
+        !matches!(self, SqlType::Array(_) | SqlType::Struct(_) | SqlType::Json)
+    }
+
     /// Check if this type is comparable with another type.
     pub fn is_comparable_with(&self, other: &SqlType) -> bool {
         match (self, other) {
@@ -153,6 +225,10 @@ impl SqlType {
             // Numeric types are comparable with each other
             (a, b) if a.is_numeric() && b.is_numeric() => true,
 
+            // Strings and bytes of any length
+            (SqlType::Varchar(_), SqlType::Varchar(_))
+            | (SqlType::Varbinary(_), SqlType::Varbinary(_)) => true,
+
             // Date/time types are comparable with each other (with caveats)
             (a, b) if a.is_datetime() && b.is_datetime() => true,
 
@@ -186,6 +262,11 @@ impl SqlType {
             // Float32 can coerce to Float64
             (SqlType::Float32, SqlType::Float64) => true,
 
+            // Strings and bytes coerce regardless of length; the length
+            // limit applies to stored values
+            (SqlType::Varchar(_), SqlType::Varchar(_))
+            | (SqlType::Varbinary(_), SqlType::Varbinary(_)) => true,
+
             // Integers can coerce to Numeric
             (SqlType::Int32, SqlType::Numeric { .. }) => true,
             (SqlType::Int64, SqlType::Numeric { .. }) => true,
@@ -196,6 +277,15 @@ impl SqlType {
             (SqlType::Float32, SqlType::Numeric { .. }) => true,
             (SqlType::Float64, SqlType::Numeric { .. }) => true,
 
+            // Any Numeric can coerce to unconstrained Numeric
+            (
+                SqlType::Numeric { .. },
+                SqlType::Numeric {
+                    precision: None,
+                    scale: None,
+                },
+            ) => true,
+
             // Date can coerce to Datetime/Timestamp
             (SqlType::Date, SqlType::Datetime) => true,
             (SqlType::Date, SqlType::Timestamp) => true,
@@ -216,6 +306,92 @@ impl SqlType {
         }
     }
 
+    /// Classify a conversion from this type to `target`.
+    ///
+    /// Implicit conversions are the coercions of [`can_coerce_to`](Self::can_coerce_to).
+    /// Arrays and ranges convert element-wise and structs field by field, in
+    /// position order; the result is the weakest kind among the parts.
+    pub fn can_cast_to(&self, target: &SqlType) -> CastKind {
+        use SqlType::*;
+
+        if self.can_coerce_to(target) || matches!(self, Any) || matches!(target, Unknown) {
+            return CastKind::Implicit;
+        }
+        let explicit = match (self, target) {
+            // Numbers convert among themselves, to and from strings, and
+            // integers to and from booleans
+            (a, b) if a.is_numeric() && b.is_numeric() => true,
+            (a, Varchar(_)) | (Varchar(_), a) if a.is_numeric() => true,
+            (Bool, b) | (b, Bool) if b.is_integer() => true,
+            (Bool, Varchar(_)) | (Varchar(_), Bool) => true,
+
+            // Strings and bytes
+            (Varchar(_), Varbinary(_)) | (Varbinary(_), Varchar(_)) => true,
+
+            // Date/time values convert to and from strings and narrow to
+            // their date or time part
+            (a, Varchar(_)) | (Varchar(_), a) if a.is_datetime() => true,
+            (Datetime | Timestamp, Date | Time) => true,
+            (Timestamp, Datetime) => true,
+
+            // Other string forms
+            (Interval | Uuid | Json, Varchar(_)) | (Varchar(_), Interval | Uuid | Json) => true,
+            (Uuid, Varbinary(_)) | (Varbinary(_), Uuid) => true,
+            (Json, Bool) => true,
+            (Json, b) if b.is_numeric() => true,
+
+            // Containers convert element-wise
+            (Array(a), Array(b)) | (Range(a), Range(b)) => {
+                return a.can_cast_to(b).at_most_explicit();
+            }
+            (Struct(a), Struct(b)) => {
+                if a.len() != b.len() {
+                    return CastKind::Invalid;
+                }
+                return a
+                    .iter()
+                    .zip(b)
+                    .map(|(a, b)| a.data_type.can_cast_to(&b.data_type))
+                    .fold(CastKind::Implicit, CastKind::weakest)
+                    .at_most_explicit();
+            }
+
+            _ => false,
+        };
+        if explicit {
+            CastKind::Explicit
+        } else {
+            CastKind::Invalid
+        }
+    }
+
+    /// Check whether converting a value of this type to `target` can fail
+    /// at runtime, making `SAFE_CAST` return NULL.
+    ///
+    /// Invalid conversions and implicit coercions never fail at runtime.
+    pub fn cast_may_fail(&self, target: &SqlType) -> bool {
+        use SqlType::*;
+
+        if self.can_cast_to(target) != CastKind::Explicit {
+            return false;
+        }
+        match (self, target) {
+            (Array(a), Array(b)) | (Range(a), Range(b)) => a.cast_may_fail(b),
+            (Struct(a), Struct(b)) => a
+                .iter()
+                .zip(b)
+                .any(|(a, b)| a.data_type.cast_may_fail(&b.data_type)),
+            // Bytes may not be valid UTF-8
+            (Varbinary(_), Varchar(_)) => true,
+            // Everything else has a string form
+            (_, Varchar(_)) => false,
+            (Bool, _) | (_, Bool) if !matches!(self, Varchar(_) | Json) => false,
+            (Varchar(_), Varbinary(_)) | (Uuid, Varbinary(_)) => false,
+            (Datetime | Timestamp, Date | Time) | (Timestamp, Datetime) => false,
+            _ => true,
+        }
+    }
+
     /// Get the common supertype of two types.
     pub fn common_supertype(&self, other: &SqlType) -> Option<SqlType> {
         match (self, other) {
@@ -273,6 +449,20 @@ impl SqlType {
                 Some(SqlType::Float64)
             }
 
+            // Strings and bytes widen to the longer length
+            (SqlType::Varchar(a), SqlType::Varchar(b)) => {
+                Some(SqlType::Varchar(a.zip(*b).map(|(a, b)| a.max(b))))
+            }
+            (SqlType::Varbinary(a), SqlType::Varbinary(b)) => {
+                Some(SqlType::Varbinary(a.zip(*b).map(|(a, b)| a.max(b))))
+            }
+
+            // Numerics of different precision widen to unconstrained
+            (SqlType::Numeric { .. }, SqlType::Numeric { .. }) => Some(SqlType::Numeric {
+                precision: None,
+                scale: None,
+            }),
+
             // Numeric supertypes (any integer or float with Numeric)
             (t, SqlType::Numeric { .. }) | (SqlType::Numeric { .. }, t)
                 if t.is_integer() || t.is_floating_point() =>
@@ -309,6 +499,98 @@ impl SqlType {
         }
     }
 
+    /// The result type of an arithmetic operator on `NUMERIC` operands.
+    ///
+    /// Returns `None` unless one operand is `NUMERIC` and the other is
+    /// `NUMERIC` or an integer, which counts as `NUMERIC(10, 0)`,
+    /// `NUMERIC(19, 0)`, or `NUMERIC(20, 0)` by its width. With
+    /// `NUMERIC(p1, s1)` and `NUMERIC(p2, s2)` operands the result is:
+    ///
+    /// | Operator | Precision | Scale |
+    /// |----------|-----------|-------|
+    /// | `+`, `-` | `max(p1 - s1, p2 - s2) + s + 1` | `max(s1, s2)` |
+    /// | `*` | `p1 + p2 + 1` | `s1 + s2` |
+    /// | `/` | `p1 - s1 + s2 + s` | `max(6, s1 + p2 + 1)` |
+    /// | `%` | `min(p1 - s1, p2 - s2) + s` | `max(s1, s2)` |
+    ///
+    /// An unconstrained `NUMERIC` operand gives an unconstrained result.
+    ///
+    /// ```
+    /// use vibesql::types::{ArithmeticOp, SqlType};
+    ///
+    /// let numeric = |p, s| SqlType::Numeric { precision: Some(p), scale: Some(s) };
+    /// let sum = numeric(10, 2).numeric_arithmetic(ArithmeticOp::Add, &numeric(8, 4));
+    /// assert_eq!(sum.unwrap().result, numeric(13, 4));
+    /// ```
+    pub fn numeric_arithmetic(
+        &self,
+        op: ArithmeticOp,
+        other: &SqlType,
+    ) -> Option<NumericArithmetic> {
+        if !matches!(self, SqlType::Numeric { .. }) && !matches!(other, SqlType::Numeric { .. }) {
+            return None;
+        }
+        let unconstrained = NumericArithmetic {
+            result: SqlType::Numeric {
+                precision: None,
+                scale: None,
+            },
+            capped_precision: None,
+        };
+        let (Some((p1, s1)), Some((p2, s2))) = (self.decimal_digits()?, other.decimal_digits()?)
+        else {
+            return Some(unconstrained);
+        };
+
+        let (precision, scale) = match op {
+            ArithmeticOp::Add | ArithmeticOp::Subtract => {
+                let scale = s1.max(s2);
+                ((p1 - s1).max(p2 - s2) + scale + 1, scale)
+            }
+            ArithmeticOp::Multiply => (p1 + p2 + 1, s1 + s2),
+            ArithmeticOp::Divide => {
+                let scale = (s1 + p2 + 1).max(6);
+                (p1 - s1 + s2 + scale, scale)
+            }
+            ArithmeticOp::Modulo => {
+                let scale = s1.max(s2);
+                ((p1 - s1).min(p2 - s2) + scale, scale)
+            }
+        };
+        let max = u32::from(MAX_NUMERIC_PRECISION);
+        let (result_precision, result_scale, capped_precision) = if precision > max {
+            let integer_digits = precision - scale;
+            let capped_scale = scale.min(6).max(max.saturating_sub(integer_digits));
+            (max, capped_scale.min(scale), Some(precision))
+        } else {
+            (precision.max(1), scale, None)
+        };
+        Some(NumericArithmetic {
+            result: SqlType::Numeric {
+                precision: Some(result_precision as u8),
+                scale: Some(result_scale as u8),
+            },
+            capped_precision,
+        })
+    }
+
+    /// The precision and scale this type holds as a decimal: `Some(None)`
+    /// for an unconstrained `NUMERIC`, and `None` if it is not `NUMERIC` or
+    /// an integer.
+    fn decimal_digits(&self) -> Option<Option<(u32, u32)>> {
+        match self {
+            SqlType::Numeric {
+                precision: Some(p),
+                scale,
+            } => Some(Some((u32::from(*p), u32::from(scale.unwrap_or(0).min(*p))))),
+            SqlType::Numeric { .. } => Some(None),
+            SqlType::Int32 | SqlType::Uint32 => Some(Some((10, 0))),
+            SqlType::Int64 => Some(Some((19, 0))),
+            SqlType::Uint64 => Some(Some((20, 0))),
+            _ => None,
+        }
+    }
+
     /// Get the element type if this is an array.
     pub fn element_type(&self) -> Option<&SqlType> {
         match self {
@@ -317,6 +599,26 @@ impl SqlType {
         }
     }
 
+    /// A struct type with named fields.
+    ///
+    /// ```
+    /// use vibesql::types::SqlType;
+    ///
+    /// let address = SqlType::struct_of([("city", SqlType::Varchar(None)), ("zip", SqlType::Int32)]);
+    /// assert_eq!(address.to_string(), "STRUCT<city VARCHAR, zip INTEGER>");
+    /// ```
+    pub fn struct_of<N: Into<String>>(fields: impl IntoIterator<Item = (N, SqlType)>) -> SqlType {
+        SqlType::Struct(
+            fields
+                .into_iter()
+                .map(|(name, data_type)| StructField {
+                    name: Some(name.into()),
+                    data_type,
+                })
+                .collect(),
+        )
+    }
+
     /// Get struct fields if this is a struct.
     pub fn struct_fields(&self) -> Option<&[StructField]> {
         match self {
@@ -347,8 +649,20 @@ impl fmt::Display for SqlType {
                 }
                 Ok(())
             }
-            SqlType::Varchar => write!(f, "VARCHAR"),
-            SqlType::Varbinary => write!(f, "VARBINARY"),
+            SqlType::Varchar(len) => {
+                write!(f, "VARCHAR")?;
+                if let Some(len) = len {
+                    write!(f, "({})", len)?;
+                }
+                Ok(())
+            }
+            SqlType::Varbinary(len) => {
+                write!(f, "VARBINARY")?;
+                if let Some(len) = len {
+                    write!(f, "({})", len)?;
+                }
+                Ok(())
+            }
             SqlType::Date => write!(f, "DATE"),
             SqlType::Time => write!(f, "TIME"),
             SqlType::Datetime => write!(f, "DATETIME"),
@@ -394,7 +708,7 @@ mod tests {
             scale: None
         }
         .is_numeric());
-        assert!(!SqlType::Varchar.is_numeric());
+        assert!(!SqlType::Varchar(None).is_numeric());
     }
 
     #[test]
@@ -423,10 +737,140 @@ mod tests {
         }));
 
         // No coercion
-        assert!(!SqlType::Varchar.can_coerce_to(&SqlType::Int64));
+        assert!(!SqlType::Varchar(None).can_coerce_to(&SqlType::Int64));
         assert!(!SqlType::Int64.can_coerce_to(&SqlType::Int32)); // No narrowing
     }
 
+    #[test]
+    fn test_can_cast_to() {
+        use SqlType::*;
+
+        let numeric = Numeric {
+            precision: Some(10),
+            scale: Some(2),
+        };
+        assert_eq!(Int32.can_cast_to(&Int64), CastKind::Implicit);
+        assert_eq!(Int64.can_cast_to(&Int32), CastKind::Explicit);
+        assert_eq!(Float64.can_cast_to(&numeric), CastKind::Implicit);
+        assert_eq!(Varchar(None).can_cast_to(&Int64), CastKind::Explicit);
+        assert_eq!(Int64.can_cast_to(&Varchar(None)), CastKind::Explicit);
+        assert_eq!(Bool.can_cast_to(&Int64), CastKind::Explicit);
+        assert_eq!(Bool.can_cast_to(&Float64), CastKind::Invalid);
+        assert_eq!(Varchar(None).can_cast_to(&Timestamp), CastKind::Explicit);
+        assert_eq!(Timestamp.can_cast_to(&Date), CastKind::Explicit);
+        assert_eq!(Date.can_cast_to(&Time), CastKind::Invalid);
+        assert_eq!(
+            Varbinary(None).can_cast_to(&Varchar(None)),
+            CastKind::Explicit
+        );
+        assert_eq!(Json.can_cast_to(&Int64), CastKind::Explicit);
+        assert_eq!(Json.can_cast_to(&Date), CastKind::Invalid);
+
+        let ints = Array(Box::new(Int64));
+        assert_eq!(
+            Array(Box::new(Int32)).can_cast_to(&ints),
+            CastKind::Implicit
+        );
+        assert_eq!(
+            Array(Box::new(Varchar(None))).can_cast_to(&ints),
+            CastKind::Explicit
+        );
+        assert_eq!(ints.can_cast_to(&Bool), CastKind::Invalid);
+        assert_eq!(
+            Array(Box::new(Date)).can_cast_to(&Array(Box::new(Time))),
+            CastKind::Invalid
+        );
+
+        let field = |data_type| StructField {
+            name: None,
+            data_type,
+        };
+        let pair = Struct(vec![field(Int64), field(Varchar(None))]);
+        assert_eq!(
+            Struct(vec![field(Varchar(None)), field(Int64)]).can_cast_to(&pair),
+            CastKind::Explicit
+        );
+        assert_eq!(
+            Struct(vec![field(Int64)]).can_cast_to(&pair),
+            CastKind::Invalid
+        );
+        assert_eq!(
+            Struct(vec![field(Date), field(Varchar(None))]).can_cast_to(&pair),
+            CastKind::Invalid
+        );
+    }
+
+    #[test]
+    fn test_cast_may_fail() {
+        use SqlType::*;
+
+        assert!(Varchar(None).cast_may_fail(&Int64));
+        assert!(Int64.cast_may_fail(&Int32));
+        assert!(!Int64.cast_may_fail(&Varchar(None)));
+        assert!(!Int32.cast_may_fail(&Int64));
+        assert!(!Int64.cast_may_fail(&Bool));
+        assert!(Varchar(None).cast_may_fail(&Bool));
+        assert!(!Timestamp.cast_may_fail(&Date));
+        assert!(Varbinary(None).cast_may_fail(&Varchar(None)));
+        assert!(!Date.cast_may_fail(&Time));
+        assert!(Array(Box::new(Varchar(None))).cast_may_fail(&Array(Box::new(Int64))));
+    }
+
+    #[test]
+    fn test_numeric_arithmetic() {
+        use ArithmeticOp::*;
+        let numeric = |p, s| SqlType::Numeric {
+            precision: Some(p),
+            scale: Some(s),
+        };
+        let result = |l: &SqlType, op, r: &SqlType| l.numeric_arithmetic(op, r).unwrap();
+
+        let (a, b) = (numeric(10, 2), numeric(8, 4));
+        for (op, expected) in [
+            (Add, numeric(13, 4)),
+            (Subtract, numeric(13, 4)),
+            (Multiply, numeric(19, 6)),
+            (Divide, numeric(23, 11)),
+            (Modulo, numeric(8, 4)),
+        ] {
+            let arithmetic = result(&a, op, &b);
+            assert_eq!(arithmetic.result, expected, "{:?}", op);
+            assert_eq!(arithmetic.capped_precision, None);
+        }
+
+        // Integers count as NUMERIC with their width, in either position.
+        assert_eq!(result(&SqlType::Int32, Add, &a).result, numeric(13, 2));
+        assert_eq!(result(&a, Multiply, &SqlType::Int64).result, numeric(30, 2));
+        assert_eq!(
+            result(
+                &a,
+                Add,
+                &SqlType::Numeric {
+                    precision: None,
+                    scale: None
+                }
+            )
+            .result,
+            SqlType::Numeric {
+                precision: None,
+                scale: None
+            }
+        );
+        assert_eq!(
+            SqlType::Int64.numeric_arithmetic(Add, &SqlType::Int64),
+            None
+        );
+        assert_eq!(a.numeric_arithmetic(Add, &SqlType::Float64), None);
+
+        // Results over 38 digits keep their integer digits and lose scale.
+        let capped = result(&numeric(30, 10), Multiply, &numeric(20, 5));
+        assert_eq!(capped.result, numeric(38, 6));
+        assert_eq!(capped.capped_precision, Some(51));
+        let capped = result(&numeric(38, 10), Add, &numeric(38, 10));
+        assert_eq!(capped.result, numeric(38, 9));
+        assert_eq!(capped.capped_precision, Some(39));
+    }
+
     #[test]
     fn test_common_supertype() {
         // Integer promotions
@@ -459,14 +903,51 @@ mod tests {
             Some(SqlType::Float32)
         );
 
+        // Numerics of different precision
+        assert_eq!(
+            SqlType::Numeric {
+                precision: Some(10),
+                scale: Some(2),
+            }
+            .common_supertype(&SqlType::Numeric {
+                precision: Some(5),
+                scale: None,
+            }),
+            Some(SqlType::Numeric {
+                precision: None,
+                scale: None,
+            })
+        );
+        assert_eq!(
+            SqlType::Numeric {
+                precision: Some(10),
+                scale: Some(2),
+            }
+            .common_supertype(&SqlType::Varchar(None)),
+            None
+        );
+
         // Date/time
         assert_eq!(
             SqlType::Date.common_supertype(&SqlType::Timestamp),
             Some(SqlType::Timestamp)
         );
 
+        // Strings keep the longer length, or none
+        assert_eq!(
+            SqlType::Varchar(Some(10)).common_supertype(&SqlType::Varchar(Some(20))),
+            Some(SqlType::Varchar(Some(20)))
+        );
+        assert_eq!(
+            SqlType::Varchar(Some(10)).common_supertype(&SqlType::Varchar(None)),
+            Some(SqlType::Varchar(None))
+        );
+
         // No common supertype
-        assert_eq!(SqlType::Varchar.common_supertype(&SqlType::Int64), None);
+        assert_eq!(
+            SqlType::Varchar(None).common_supertype(&SqlType::Int64),
+            None
+        );
     }
 
     #[test]
@@ -482,7 +963,7 @@ mod tests {
             scale: None
         }
         .is_integer());
-        assert!(!SqlType::Varchar.is_integer());
+        assert!(!SqlType::Varchar(None).is_integer());
     }
 
     #[test]
@@ -511,15 +992,18 @@ mod tests {
             scale: None
         }
         .is_floating_point());
-        assert!(!SqlType::Varchar.is_floating_point());
+        assert!(!SqlType::Varchar(None).is_floating_point());
     }
 
     #[test]
     fn test_is_string() {
-        assert!(SqlType::Varchar.is_string());
+        assert!(SqlType::Varchar(None).is_string());
         assert!(!SqlType::Int64.is_string());
-        assert!(!SqlType::Varbinary.is_string());
+        assert!(!SqlType::Varbinary(None).is_string());
         assert!(!SqlType::Json.is_string());
+        assert!(SqlType::Varchar(Some(10)).is_string());
+        assert_eq!(SqlType::Varchar(Some(10)).max_length(), Some(10));
+        assert_eq!(SqlType::Varbinary(None).max_length(), None);
     }
 
     #[test]
@@ -529,7 +1013,7 @@ mod tests {
         assert!(SqlType::Datetime.is_datetime());
         assert!(SqlType::Timestamp.is_datetime());
         assert!(!SqlType::Int64.is_datetime());
-        assert!(!SqlType::Varchar.is_datetime());
+        assert!(!SqlType::Varchar(None).is_datetime());
         assert!(!SqlType::Interval.is_datetime());
     }
 
@@ -537,7 +1021,8 @@ mod tests {
     fn test_is_comparable_with() {
         // Same types
         assert!(SqlType::Int64.is_comparable_with(&SqlType::Int64));
-        assert!(SqlType::Varchar.is_comparable_with(&SqlType::Varchar));
+        assert!(SqlType::Varchar(None).is_comparable_with(&SqlType::Varchar(None)));
+        assert!(SqlType::Varchar(Some(10)).is_comparable_with(&SqlType::Varchar(Some(20))));
 
         // Numeric types are comparable
         assert!(SqlType::Int64.is_comparable_with(&SqlType::Float64));
@@ -553,10 +1038,10 @@ mod tests {
         // Unknown/Any are comparable with anything
         assert!(SqlType::Unknown.is_comparable_with(&SqlType::Int64));
         assert!(SqlType::Int64.is_comparable_with(&SqlType::Unknown));
-        assert!(SqlType::Any.is_comparable_with(&SqlType::Varchar));
+        assert!(SqlType::Any.is_comparable_with(&SqlType::Varchar(None)));
 
         // Incompatible types
-        assert!(!SqlType::Varchar.is_comparable_with(&SqlType::Int64));
+        assert!(!SqlType::Varchar(None).is_comparable_with(&SqlType::Int64));
         assert!(!SqlType::Bool.is_comparable_with(&SqlType::Float64));
     }
 
@@ -565,14 +1050,14 @@ mod tests {
         let array_int = SqlType::Array(Box::new(SqlType::Int64));
         assert_eq!(array_int.element_type(), Some(&SqlType::Int64));
 
-        let nested = SqlType::Array(Box::new(SqlType::Array(Box::new(SqlType::Varchar))));
+        let nested = SqlType::Array(Box::new(SqlType::Array(Box::new(SqlType::Varchar(None)))));
         assert_eq!(
             nested.element_type(),
-            Some(&SqlType::Array(Box::new(SqlType::Varchar)))
+            Some(&SqlType::Array(Box::new(SqlType::Varchar(None))))
         );
 
         assert_eq!(SqlType::Int64.element_type(), None);
-        assert_eq!(SqlType::Varchar.element_type(), None);
+        assert_eq!(SqlType::Varchar(None).element_type(), None);
     }
 
     #[test]
@@ -584,7 +1069,7 @@ mod tests {
             },
             StructField {
                 name: Some("b".to_string()),
-                data_type: SqlType::Varchar,
+                data_type: SqlType::Varchar(None),
             },
         ]);
         let fields = struct_type.struct_fields().unwrap();
@@ -634,8 +1119,10 @@ mod tests {
             ),
             "NUMERIC(10, 2)"
         );
-        assert_eq!(format!("{}", SqlType::Varchar), "VARCHAR");
-        assert_eq!(format!("{}", SqlType::Varbinary), "VARBINARY");
+        assert_eq!(format!("{}", SqlType::Varchar(None)), "VARCHAR");
+        assert_eq!(format!("{}", SqlType::Varbinary(None)), "VARBINARY");
+        assert_eq!(format!("{}", SqlType::Varchar(Some(10))), "VARCHAR(10)");
+        assert_eq!(format!("{}", SqlType::Varbinary(Some(16))), "VARBINARY(16)");
         assert_eq!(
             format!("{}", SqlType::Array(Box::new(SqlType::Int64))),
             "ARRAY<BIGINT>"
@@ -1,5 +1,13 @@
 //! SQL value types.
 
+use super::literal::{self, check_json, Date, Datetime, Numeric, Time, MICROS_PER_DAY};
+use super::{ArithmeticOp, CastKind, SqlType, StructField};
+use crate::ast::{write_quoted, ExprKind, IntervalUnit, TypedLiteralType, UnaryOp};
+use crate::error::{Error, ErrorKind, Result};
+use crate::lexer::Keyword;
+use std::cmp::Ordering;
+use std::fmt;
+
 /// A SQL value.
 #[derive(Debug, Clone, PartialEq, Default)]
 pub enum Value {
@@ -8,6 +16,7 @@ pub enum Value {
     Boolean(bool),
     Int64(i64),
     Float64(f64),
+    Numeric(Numeric),
     String(String),
     Bytes(Vec<u8>),
     Date(i32),      // Days since Unix epoch
@@ -41,6 +50,7 @@ impl Value {
             Value::Boolean(_) => "BOOLEAN",
             Value::Int64(_) => "BIGINT",
             Value::Float64(_) => "DOUBLE PRECISION",
+            Value::Numeric(_) => "NUMERIC",
             Value::String(_) => "VARCHAR",
             Value::Bytes(_) => "VARBINARY",
             Value::Date(_) => "DATE",
@@ -55,12 +65,845 @@ impl Value {
     }
 }
 
+/// The most digits before and after the decimal point of a NUMERIC and a
+/// BIGNUMERIC literal.
+const NUMERIC_DIGITS: (u32, u32) = (29, 9);
+const BIGNUMERIC_DIGITS: (u32, u32) = (38, 38);
+
+/// Comparison and arithmetic with SQL semantics, for evaluating values at
+/// runtime the way the analyzer folds constants.
+///
+/// These are methods rather than `PartialOrd` and `std::ops` impls: SQL
+/// compares `1` and `1.0` as equal while the derived `PartialEq` does not,
+/// and arithmetic can fail.
+impl Value {
+    /// Compare two values, or `None` if either is NULL or they are not
+    /// comparable.
+    ///
+    /// Numbers compare by value across `Int64`, `Float64` and `Numeric`;
+    /// NaN is not comparable. Strings compare by their UTF-8 bytes.
+    /// Dates, datetimes and timestamps compare with each other as points
+    /// in UTC. Arrays and structs compare element by element.
+    pub fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        use Value::*;
+
+        match (self, other) {
+            (Null, _) | (_, Null) => None,
+            (Boolean(a), Boolean(b)) => Some(a.cmp(b)),
+            (Int64(a), Int64(b)) => Some(a.cmp(b)),
+            (Int64(a), Float64(b)) => compare_int_float(*a, *b),
+            (Float64(a), Int64(b)) => compare_int_float(*b, *a).map(Ordering::reverse),
+            (Float64(a), Float64(b)) => a.partial_cmp(b),
+            (Numeric(a), Numeric(b)) => Some(a.cmp(b)),
+            (Numeric(a), Int64(b)) => Some(a.cmp(&literal::Numeric::from_i128((*b).into(), 0))),
+            (Int64(a), Numeric(b)) => Some(literal::Numeric::from_i128((*a).into(), 0).cmp(b)),
+            (Numeric(a), Float64(b)) => numeric_to_f64(a).partial_cmp(b),
+            (Float64(a), Numeric(b)) => a.partial_cmp(&numeric_to_f64(b)),
+            (String(a), String(b)) => Some(a.cmp(b)),
+            (Bytes(a), Bytes(b)) => Some(a.cmp(b)),
+            (Time(a), Time(b)) => Some(a.cmp(b)),
+            (Interval(a), Interval(b)) => Some(a.normalized_micros().cmp(&b.normalized_micros())),
+            (Array(a), Array(b)) => {
+                for (a, b) in a.iter().zip(b) {
+                    match a.partial_cmp(b)? {
+                        Ordering::Equal => {}
+                        ordering => return Some(ordering),
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
+            }
+            (Struct(a), Struct(b)) if a.len() == b.len() => {
+                for ((_, a), (_, b)) in a.iter().zip(b) {
+                    match a.partial_cmp(b)? {
+                        Ordering::Equal => {}
+                        ordering => return Some(ordering),
+                    }
+                }
+                Some(Ordering::Equal)
+            }
+            _ => Some(self.instant_micros()?.cmp(&other.instant_micros()?)),
+        }
+    }
+
+    /// `self + other`.
+    ///
+    /// Integers add exactly, and an `Int64` or `Numeric` result that does
+    /// not fit is a [`NumericOverflow`](ErrorKind::NumericOverflow) error;
+    /// a `Float64` operand makes the result a float. Intervals add field
+    /// by field. NULL operands give NULL.
+    pub fn add(&self, other: &Value) -> Result<Value> {
+        self.arithmetic(ArithmeticOp::Add, other)
+    }
+
+    /// `self - other`, as for [`add`](Self::add).
+    pub fn sub(&self, other: &Value) -> Result<Value> {
+        self.arithmetic(ArithmeticOp::Subtract, other)
+    }
+
+    /// `self * other`, as for [`add`](Self::add).
+    pub fn mul(&self, other: &Value) -> Result<Value> {
+        self.arithmetic(ArithmeticOp::Multiply, other)
+    }
+
+    /// `self / other`, as for [`add`](Self::add).
+    ///
+    /// `Int64` division truncates toward zero, as the analyzer types it
+    /// `BIGINT`; `Numeric` division rounds to at least 9 fractional digits.
+    /// A zero divisor is a [`DivisionByZero`](ErrorKind::DivisionByZero)
+    /// error.
+    pub fn div(&self, other: &Value) -> Result<Value> {
+        self.arithmetic(ArithmeticOp::Divide, other)
+    }
+
+    /// `self % other`, with the sign of `self`, as for [`div`](Self::div).
+    pub fn rem(&self, other: &Value) -> Result<Value> {
+        self.arithmetic(ArithmeticOp::Modulo, other)
+    }
+
+    fn arithmetic(&self, op: ArithmeticOp, other: &Value) -> Result<Value> {
+        use Value::*;
+
+        let overflow = || {
+            Error::new(ErrorKind::NumericOverflow(format!(
+                "{} {} {}",
+                self,
+                op_symbol(op),
+                other
+            )))
+        };
+        let divides = matches!(op, ArithmeticOp::Divide | ArithmeticOp::Modulo);
+        match (self, other) {
+            (Null, _) | (_, Null) => Ok(Null),
+            (Int64(_) | Float64(_) | Numeric(_), _)
+                if divides && other.partial_cmp(&Int64(0)) == Some(Ordering::Equal) =>
+            {
+                Err(Error::new(ErrorKind::DivisionByZero))
+            }
+            (Int64(a), Int64(b)) => {
+                let result = match op {
+                    ArithmeticOp::Add => a.checked_add(*b),
+                    ArithmeticOp::Subtract => a.checked_sub(*b),
+                    ArithmeticOp::Multiply => a.checked_mul(*b),
+                    ArithmeticOp::Divide => a.checked_div(*b),
+                    ArithmeticOp::Modulo => a.checked_rem(*b),
+                };
+                result.map(Int64).ok_or_else(overflow)
+            }
+            (Int64(_) | Numeric(_), Int64(_) | Numeric(_)) => {
+                let (a, b) = (self.to_numeric(), other.to_numeric());
+                numeric_arithmetic(op, &a, &b)
+                    .map(Numeric)
+                    .ok_or_else(overflow)
+            }
+            (Int64(_) | Float64(_) | Numeric(_), Int64(_) | Float64(_) | Numeric(_)) => {
+                let (a, b) = (self.to_f64(), other.to_f64());
+                let result = match op {
+                    ArithmeticOp::Add => a + b,
+                    ArithmeticOp::Subtract => a - b,
+                    ArithmeticOp::Multiply => a * b,
+                    ArithmeticOp::Divide => a / b,
+                    ArithmeticOp::Modulo => a % b,
+                };
+                if result.is_finite() || !(a.is_finite() && b.is_finite()) {
+                    Ok(Float64(result))
+                } else {
+                    Err(overflow())
+                }
+            }
+            (Interval(a), Interval(b))
+                if matches!(op, ArithmeticOp::Add | ArithmeticOp::Subtract) =>
+            {
+                let fields = match op {
+                    ArithmeticOp::Add => (
+                        a.months.checked_add(b.months),
+                        a.days.checked_add(b.days),
+                        a.micros.checked_add(b.micros),
+                    ),
+                    _ => (
+                        a.months.checked_sub(b.months),
+                        a.days.checked_sub(b.days),
+                        a.micros.checked_sub(b.micros),
+                    ),
+                };
+                match fields {
+                    (Some(months), Some(days), Some(micros)) => Ok(Interval(self::Interval {
+                        months,
+                        days,
+                        micros,
+                    })),
+                    _ => Err(overflow()),
+                }
+            }
+            (Int64(_) | Float64(_) | Numeric(_), _) => Err(not_a_number(other)),
+            _ => Err(not_a_number(self)),
+        }
+    }
+
+    /// Convert the value to `target`, following [`SqlType::can_cast_to`]:
+    /// conversions it rejects are errors, and explicit ones fail when the
+    /// value has no counterpart in `target`, such as the string `'abc'` as
+    /// a `BIGINT` or 2<sup>31</sup> as an `INTEGER`.
+    ///
+    /// Numbers round half away from zero when they lose digits. Timestamps
+    /// convert to dates and datetimes in UTC. Strings convert to date/time
+    /// types, `NUMERIC` and `JSON` by the rules of their typed literals.
+    /// NULL converts to NULL of any type.
+    pub fn cast_to(&self, target: &SqlType) -> Result<Value> {
+        use SqlType as T;
+        use Value as V;
+
+        if self.is_null() || matches!(target, T::Unknown | T::Any) {
+            return Ok(self.clone());
+        }
+        let source = self.data_type();
+        let fail = |reason: String| {
+            Err(Error::new(ErrorKind::InvalidCast(format!(
+                "{} to {}: {}",
+                self, target, reason
+            ))))
+        };
+        if source.can_cast_to(target) == CastKind::Invalid {
+            return fail(format!("{} does not convert to {}", source, target));
+        }
+        match (self, target) {
+            (V::Array(values), T::Array(element)) => values
+                .iter()
+                .map(|v| v.cast_to(element))
+                .collect::<Result<_>>()
+                .map(V::Array),
+            (V::Struct(values), T::Struct(fields)) => values
+                .iter()
+                .zip(fields)
+                .map(|((_, v), field)| {
+                    let name = field.name.clone().unwrap_or_default();
+                    Ok((name, v.cast_to(&field.data_type)?))
+                })
+                .collect::<Result<_>>()
+                .map(V::Struct),
+
+            (V::Boolean(_), T::Bool) => Ok(self.clone()),
+            (V::Int64(n), T::Bool) => Ok(V::Boolean(*n != 0)),
+            (V::String(s) | V::Json(s), T::Bool) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" => Ok(V::Boolean(true)),
+                "false" => Ok(V::Boolean(false)),
+                _ => fail("not a boolean".to_string()),
+            },
+
+            (_, T::Int32 | T::Int64 | T::Uint32 | T::Uint64) => {
+                let n = match self {
+                    V::Boolean(b) => i64::from(*b),
+                    V::Int64(n) => *n,
+                    V::Float64(f) => match float_to_i64(*f) {
+                        Some(n) => n,
+                        None => return fail("out of range".to_string()),
+                    },
+                    V::Numeric(n) => match n.round(0).and_then(|n| n.mantissa()) {
+                        Some(n) => match i64::try_from(n) {
+                            Ok(n) => n,
+                            Err(_) => return fail("out of range".to_string()),
+                        },
+                        None => return fail("out of range".to_string()),
+                    },
+                    V::String(s) | V::Json(s) => match s.trim().parse() {
+                        Ok(n) => n,
+                        Err(_) => return fail("not an integer".to_string()),
+                    },
+                    _ => unreachable!("can_cast_to allows no other sources"),
+                };
+                let in_range = match target {
+                    T::Int32 => i32::try_from(n).is_ok(),
+                    T::Uint32 => u32::try_from(n).is_ok(),
+                    T::Uint64 => n >= 0,
+                    _ => true,
+                };
+                if in_range {
+                    Ok(V::Int64(n))
+                } else {
+                    fail("out of range".to_string())
+                }
+            }
+
+            (_, T::Float32 | T::Float64) => {
+                let f = match self {
+                    V::String(s) | V::Json(s) => match s.trim().parse::<f64>() {
+                        Ok(f) => f,
+                        Err(_) => return fail("not a number".to_string()),
+                    },
+                    _ => self.to_f64(),
+                };
+                let narrowed = match target {
+                    T::Float32 => f as f32 as f64,
+                    _ => f,
+                };
+                if narrowed.is_infinite() && f.is_finite() {
+                    fail("out of range".to_string())
+                } else {
+                    Ok(V::Float64(narrowed))
+                }
+            }
+
+            (_, T::Numeric { precision, scale }) => {
+                let numeric = match self {
+                    V::Float64(f) if !f.is_finite() => return fail("not finite".to_string()),
+                    V::Float64(f) => literal::Numeric::parse(&f.to_string()),
+                    V::String(s) | V::Json(s) => literal::Numeric::parse(s.trim()),
+                    _ => Ok(self.to_numeric()),
+                };
+                let numeric = match numeric {
+                    Ok(numeric) => numeric,
+                    Err(reason) => return fail(reason),
+                };
+                let numeric = match scale.map(|scale| numeric.round(scale.into())) {
+                    None => numeric,
+                    Some(Some(rounded)) => rounded,
+                    Some(None) => return fail("out of range".to_string()),
+                };
+                let integer_digits = precision.map(|p| p.saturating_sub(scale.unwrap_or(0)));
+                match integer_digits {
+                    Some(digits) if numeric.integer_digits() > u32::from(digits) => {
+                        fail("out of range".to_string())
+                    }
+                    _ => Ok(V::Numeric(numeric)),
+                }
+            }
+
+            (_, T::Varchar(limit)) => {
+                let text = match self {
+                    V::Boolean(b) => b.to_string(),
+                    V::Int64(n) => n.to_string(),
+                    V::Float64(f) => f.to_string(),
+                    V::Numeric(n) => n.to_string(),
+                    V::String(s) | V::Json(s) => s.clone(),
+                    V::Bytes(b) => match std::string::String::from_utf8(b.clone()) {
+                        Ok(s) => s,
+                        Err(_) => return fail("invalid UTF-8".to_string()),
+                    },
+                    V::Date(days) => match literal::Date::from_days_since_epoch(*days) {
+                        Some(date) => date.to_string(),
+                        None => return fail("out of range".to_string()),
+                    },
+                    V::Time(micros) => match literal::Time::from_micros_since_midnight(*micros) {
+                        Some(time) => time.to_string(),
+                        None => return fail("out of range".to_string()),
+                    },
+                    V::Datetime(micros) | V::Timestamp(micros) => {
+                        match literal::Datetime::from_micros_since_epoch(*micros) {
+                            Some(datetime) if matches!(self, V::Timestamp(_)) => {
+                                format!("{}+00", datetime)
+                            }
+                            Some(datetime) => datetime.to_string(),
+                            None => return fail("out of range".to_string()),
+                        }
+                    }
+                    V::Interval(interval) => interval.to_string(),
+                    _ => unreachable!("can_cast_to allows no other sources"),
+                };
+                match limit {
+                    Some(limit) if text.chars().count() > *limit as usize => {
+                        fail(format!("longer than {} characters", limit))
+                    }
+                    _ => Ok(V::String(text)),
+                }
+            }
+
+            (V::String(_) | V::Bytes(_), T::Varbinary(limit)) => {
+                let bytes = match self {
+                    V::String(s) => s.as_bytes().to_vec(),
+                    V::Bytes(b) => b.clone(),
+                    _ => unreachable!(),
+                };
+                match limit {
+                    Some(limit) if bytes.len() > *limit as usize => {
+                        fail(format!("longer than {} bytes", limit))
+                    }
+                    _ => Ok(V::Bytes(bytes)),
+                }
+            }
+
+            (V::String(s), T::Date) => literal::Date::parse(s).map(V::from).or_else(fail),
+            (V::String(s), T::Time) => literal::Time::parse(s).map(V::from).or_else(fail),
+            (V::String(s), T::Datetime) => literal::Datetime::parse(s).map(V::from).or_else(fail),
+            (V::String(s), T::Timestamp) => match literal::Timestamp::parse(s) {
+                Ok(timestamp) => match timestamp.utc_micros() {
+                    Some(micros) => Ok(V::Timestamp(micros)),
+                    None => fail("time zone names other than UTC are not supported".to_string()),
+                },
+                Err(reason) => fail(reason),
+            },
+            (V::String(s), T::Json) => match check_json(s) {
+                Ok(()) => Ok(V::Json(s.clone())),
+                Err(reason) => fail(reason),
+            },
+
+            (V::Date(days), T::Datetime | T::Timestamp) => {
+                let micros = i64::from(*days) * MICROS_PER_DAY;
+                Ok(match target {
+                    T::Datetime => V::Datetime(micros),
+                    _ => V::Timestamp(micros),
+                })
+            }
+            (V::Datetime(micros) | V::Timestamp(micros), T::Date) => {
+                match i32::try_from(micros.div_euclid(MICROS_PER_DAY)) {
+                    Ok(days) => Ok(V::Date(days)),
+                    Err(_) => fail("out of range".to_string()),
+                }
+            }
+            (V::Datetime(micros) | V::Timestamp(micros), T::Time) => {
+                Ok(V::Time(micros.rem_euclid(MICROS_PER_DAY)))
+            }
+            (V::Datetime(micros) | V::Timestamp(micros), T::Datetime) => Ok(V::Datetime(*micros)),
+            (V::Datetime(micros) | V::Timestamp(micros), T::Timestamp) => Ok(V::Timestamp(*micros)),
+            (V::Date(_), T::Date) | (V::Time(_), T::Time) | (V::Json(_), T::Json) => {
+                Ok(self.clone())
+            }
+            (V::Interval(_), T::Interval) => Ok(self.clone()),
+
+            _ => fail(format!("{} values are not supported", target)),
+        }
+    }
+
+    /// The value of a literal expression, including typed literals such
+    /// as `DATE '2024-01-31'` and single-unit intervals such as
+    /// `INTERVAL 3 DAY`; `None` for other expressions and malformed
+    /// literals.
+    pub fn from_literal(kind: &ExprKind) -> Option<Value> {
+        match kind {
+            ExprKind::Null => Some(Value::Null),
+            ExprKind::Boolean(b) => Some(Value::Boolean(*b)),
+            ExprKind::Integer(n) => Some(Value::Int64(*n)),
+            ExprKind::Float(f) => Some(Value::Float64(*f)),
+            ExprKind::String(s) => Some(Value::String(s.clone())),
+            ExprKind::Bytes(b) => Some(Value::Bytes(b.clone())),
+            ExprKind::TypedLiteral { data_type, value } => {
+                Value::parse_typed_literal(*data_type, value).ok()?
+            }
+            ExprKind::Interval { value, unit } => {
+                let count = match &value.kind {
+                    ExprKind::Integer(n) => *n,
+                    ExprKind::UnaryOp {
+                        op: UnaryOp::Minus,
+                        expr,
+                    } => match expr.kind {
+                        ExprKind::Integer(n) => n.checked_neg()?,
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                Interval::of(count, *unit).map(Value::Interval)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse the string of a typed literal, such as the `'2024-01-31'` of
+    /// `DATE '2024-01-31'`, failing with what is malformed. `NUMERIC`
+    /// takes up to 29 integer and 9 fractional digits, and `BIGNUMERIC` up
+    /// to 38 of each. A `RANGE` literal, and a timestamp in a named zone
+    /// other than UTC, has no value here and is `Ok(None)`.
+    pub fn parse_typed_literal(
+        data_type: TypedLiteralType,
+        text: &str,
+    ) -> std::result::Result<Option<Value>, String> {
+        let numeric = |(integer_digits, scale): (u32, u32)| {
+            let numeric = literal::Numeric::parse(text)?;
+            if numeric.integer_digits() > integer_digits {
+                return Err(format!(
+                    "more than {} digits before the decimal point",
+                    integer_digits
+                ));
+            }
+            if numeric.scale() > scale {
+                return Err(format!(
+                    "more than {} digits after the decimal point",
+                    scale
+                ));
+            }
+            Ok(Some(Value::Numeric(numeric)))
+        };
+        match data_type {
+            TypedLiteralType::Date => literal::Date::parse(text).map(|d| Some(d.into())),
+            TypedLiteralType::Time => literal::Time::parse(text).map(|t| Some(t.into())),
+            TypedLiteralType::Datetime => literal::Datetime::parse(text).map(|d| Some(d.into())),
+            TypedLiteralType::Timestamp => {
+                literal::Timestamp::parse(text).map(|t| t.utc_micros().map(Value::Timestamp))
+            }
+            TypedLiteralType::Json => {
+                check_json(text).map(|()| Some(Value::Json(text.to_string())))
+            }
+            TypedLiteralType::Numeric => numeric(NUMERIC_DIGITS),
+            TypedLiteralType::Bignumeric => numeric(BIGNUMERIC_DIGITS),
+            TypedLiteralType::Range => Ok(None),
+        }
+    }
+
+    /// The type of the value: `Unknown` for NULL, and an array takes the
+    /// type of its first non-NULL element.
+    pub fn data_type(&self) -> SqlType {
+        match self {
+            Value::Null => SqlType::Unknown,
+            Value::Boolean(_) => SqlType::Bool,
+            Value::Int64(_) => SqlType::Int64,
+            Value::Float64(_) => SqlType::Float64,
+            Value::Numeric(_) => SqlType::Numeric {
+                precision: None,
+                scale: None,
+            },
+            Value::String(_) => SqlType::Varchar(None),
+            Value::Bytes(_) => SqlType::Varbinary(None),
+            Value::Date(_) => SqlType::Date,
+            Value::Time(_) => SqlType::Time,
+            Value::Datetime(_) => SqlType::Datetime,
+            Value::Timestamp(_) => SqlType::Timestamp,
+            Value::Interval(_) => SqlType::Interval,
+            Value::Array(values) => SqlType::Array(Box::new(
+                values
+                    .iter()
+                    .find(|v| !v.is_null())
+                    .map_or(SqlType::Unknown, Value::data_type),
+            )),
+            Value::Struct(fields) => SqlType::Struct(
+                fields
+                    .iter()
+                    .map(|(name, value)| StructField {
+                        name: (!name.is_empty()).then(|| name.clone()),
+                        data_type: value.data_type(),
+                    })
+                    .collect(),
+            ),
+            Value::Json(_) => SqlType::Json,
+        }
+    }
+
+    /// A number as an exact decimal; `Float64` is not exact and has no
+    /// case here.
+    fn to_numeric(&self) -> literal::Numeric {
+        match self {
+            Value::Int64(n) => literal::Numeric::from_i128((*n).into(), 0),
+            Value::Numeric(n) => n.clone(),
+            _ => unreachable!("only integers and numerics convert exactly"),
+        }
+    }
+
+    /// A number as a float.
+    fn to_f64(&self) -> f64 {
+        match self {
+            Value::Boolean(b) => f64::from(u8::from(*b)),
+            Value::Int64(n) => *n as f64,
+            Value::Float64(f) => *f,
+            Value::Numeric(n) => numeric_to_f64(n),
+            _ => f64::NAN,
+        }
+    }
+
+    /// A date, datetime or timestamp as microseconds since the Unix epoch.
+    fn instant_micros(&self) -> Option<i64> {
+        match self {
+            Value::Date(days) => Some(i64::from(*days) * MICROS_PER_DAY),
+            Value::Datetime(micros) | Value::Timestamp(micros) => Some(*micros),
+            _ => None,
+        }
+    }
+}
+
+impl Interval {
+    /// `count` of `unit`, or `None` if it overflows or is a fraction of a
+    /// microsecond.
+    fn of(count: i64, unit: IntervalUnit) -> Option<Interval> {
+        let months = |per: i64| i32::try_from(count.checked_mul(per)?).ok();
+        let days = |per: i64| i32::try_from(count.checked_mul(per)?).ok();
+        let micros = |per: i64| count.checked_mul(per);
+        let (months, days, micros) = match unit {
+            IntervalUnit::Year => (months(12)?, 0, 0),
+            IntervalUnit::Quarter => (months(3)?, 0, 0),
+            IntervalUnit::Month => (months(1)?, 0, 0),
+            IntervalUnit::Week => (0, days(7)?, 0),
+            IntervalUnit::Day => (0, days(1)?, 0),
+            IntervalUnit::Hour => (0, 0, micros(3_600_000_000)?),
+            IntervalUnit::Minute => (0, 0, micros(60_000_000)?),
+            IntervalUnit::Second => (0, 0, micros(1_000_000)?),
+            IntervalUnit::Millisecond => (0, 0, micros(1_000)?),
+            IntervalUnit::Microsecond => (0, 0, count),
+            IntervalUnit::Nanosecond if count % 1_000 == 0 => (0, 0, count / 1_000),
+            IntervalUnit::Nanosecond => return None,
+        };
+        Some(Interval {
+            months,
+            days,
+            micros,
+        })
+    }
+
+    /// The counts and units of the `INTERVAL` literals that sum to this
+    /// interval: years or months, days, and seconds or microseconds, with
+    /// `0 DAY` for an empty interval.
+    pub(crate) fn literal_parts(&self) -> Vec<(i64, IntervalUnit)> {
+        let mut parts = Vec::new();
+        match self.months {
+            0 => {}
+            months if months % 12 == 0 => parts.push((i64::from(months / 12), IntervalUnit::Year)),
+            months => parts.push((i64::from(months), IntervalUnit::Month)),
+        }
+        if self.days != 0 {
+            parts.push((i64::from(self.days), IntervalUnit::Day));
+        }
+        match self.micros {
+            0 => {}
+            micros if micros % 1_000_000 == 0 => {
+                parts.push((micros / 1_000_000, IntervalUnit::Second))
+            }
+            micros => parts.push((micros, IntervalUnit::Microsecond)),
+        }
+        if parts.is_empty() {
+            parts.push((0, IntervalUnit::Day));
+        }
+        parts
+    }
+
+    /// The length in microseconds, with months of 30 days and days of 24
+    /// hours, by which intervals compare.
+    pub(crate) fn normalized_micros(&self) -> i128 {
+        let days = i128::from(self.months) * 30 + i128::from(self.days);
+        days * i128::from(MICROS_PER_DAY) + i128::from(self.micros)
+    }
+}
+
+/// The canonical string form `Y-M D H:M:S[.F]`, such as `1-2 3 4:05:06`.
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = |n: i64| if n < 0 { "-" } else { "" };
+        let months = self.months.unsigned_abs();
+        let micros = self.micros.unsigned_abs();
+        let seconds = micros / 1_000_000;
+        write!(
+            f,
+            "{}{}-{} {} {}{}:{:02}:{:02}",
+            sign(self.months.into()),
+            months / 12,
+            months % 12,
+            self.days,
+            sign(self.micros),
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )?;
+        if !micros.is_multiple_of(1_000_000) {
+            write!(f, ".{:06}", micros % 1_000_000)?;
+        }
+        Ok(())
+    }
+}
+
+/// The value as a SQL expression that evaluates to it: a literal for most
+/// values, such as `'it\'s'`, `DATE '2024-01-31'` or `[1, 2]`.
+///
+/// Values no literal writes use the expression that builds them, such as
+/// `CAST('NaN' AS FLOAT64)`, and dates and times outside years 1 to 9999
+/// use `DATE_FROM_UNIX_DATE` and `TIMESTAMP_MICROS`. A time outside one
+/// day, which no expression builds, is written as `TIME` of a timestamp,
+/// which evaluates to the time of day it wraps to.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("NULL"),
+            Value::Boolean(true) => f.write_str("TRUE"),
+            Value::Boolean(false) => f.write_str("FALSE"),
+            // The lexer reads the digits of a negative literal as a
+            // positive number, which i64::MIN is too large for
+            Value::Int64(i64::MIN) => write!(f, "({} - 1)", i64::MIN + 1),
+            Value::Int64(n) => write!(f, "{}", n),
+            Value::Float64(n) if n.is_finite() => write!(f, "{:?}", n),
+            Value::Float64(n) => write!(f, "CAST('{}' AS FLOAT64)", n),
+            Value::Numeric(n) => write!(f, "NUMERIC '{}'", n),
+            Value::String(s) => write_quoted(f, s, '\''),
+            Value::Bytes(bytes) => {
+                f.write_str("b'")?;
+                for &b in bytes {
+                    match b {
+                        b'\\' => f.write_str("\\\\")?,
+                        b'\'' => f.write_str("\\'")?,
+                        0x20..=0x7e => write!(f, "{}", b as char)?,
+                        _ => write!(f, "\\x{:02x}", b)?,
+                    }
+                }
+                f.write_str("'")
+            }
+            Value::Date(days) => match literal::Date::from_days_since_epoch(*days) {
+                Some(date) => write!(f, "DATE '{}'", date),
+                None => write!(f, "DATE_FROM_UNIX_DATE({})", days),
+            },
+            Value::Time(micros) => match literal::Time::from_micros_since_midnight(*micros) {
+                Some(time) => write!(f, "TIME '{}'", time),
+                None => write!(f, "TIME(TIMESTAMP_MICROS({}))", Value::Int64(*micros)),
+            },
+            Value::Datetime(micros) => match literal::Datetime::from_micros_since_epoch(*micros) {
+                Some(datetime) => write!(f, "DATETIME '{}'", datetime),
+                None => write!(f, "DATETIME(TIMESTAMP_MICROS({}))", Value::Int64(*micros)),
+            },
+            Value::Timestamp(micros) => match literal::Datetime::from_micros_since_epoch(*micros) {
+                Some(datetime) => write!(f, "TIMESTAMP '{}+00'", datetime),
+                None => write!(f, "TIMESTAMP_MICROS({})", Value::Int64(*micros)),
+            },
+            Value::Interval(interval) => {
+                for (i, (count, unit)) in interval.literal_parts().into_iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" + ")?;
+                    }
+                    write!(f, "INTERVAL {} {}", Value::Int64(count), unit)?;
+                }
+                Ok(())
+            }
+            Value::Array(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+            Value::Struct(fields) => {
+                f.write_str("STRUCT(")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                    if !name.is_empty() {
+                        f.write_str(" AS ")?;
+                        if is_plain_identifier(name) {
+                            f.write_str(name)?;
+                        } else {
+                            write_quoted(f, name, '`')?;
+                        }
+                    }
+                }
+                f.write_str(")")
+            }
+            Value::Json(text) => {
+                f.write_str("JSON ")?;
+                write_quoted(f, text, '\'')
+            }
+        }
+    }
+}
+
+/// Compare an integer with a float exactly, which converting the integer
+/// to a float would not be beyond 2<sup>53</sup>.
+fn compare_int_float(int: i64, float: f64) -> Option<Ordering> {
+    const TWO_POW_63: f64 = 9_223_372_036_854_775_808.0;
+    if float.is_nan() {
+        None
+    } else if float >= TWO_POW_63 {
+        Some(Ordering::Less)
+    } else if float < -TWO_POW_63 {
+        Some(Ordering::Greater)
+    } else {
+        let truncated = float.trunc();
+        Some(
+            int.cmp(&(truncated as i64))
+                .then_with(|| 0.0_f64.partial_cmp(&(float - truncated)).unwrap()),
+        )
+    }
+}
+
+/// A float rounded half away from zero to an integer, if it is in range.
+fn float_to_i64(float: f64) -> Option<i64> {
+    let rounded = float.round();
+    let in_range = (-9_223_372_036_854_775_808.0..9_223_372_036_854_775_808.0).contains(&rounded);
+    in_range.then_some(rounded as i64)
+}
+
+fn numeric_to_f64(numeric: &literal::Numeric) -> f64 {
+    numeric.to_string().parse().unwrap_or(f64::NAN)
+}
+
+/// Exact arithmetic on decimals of up to 38 digits, or `None` on overflow
+/// or a zero divisor.
+fn numeric_arithmetic(
+    op: ArithmeticOp,
+    a: &literal::Numeric,
+    b: &literal::Numeric,
+) -> Option<literal::Numeric> {
+    let (a_scale, b_scale) = (a.scale(), b.scale());
+    let (a, b) = (a.mantissa()?, b.mantissa()?);
+    let scale = a_scale.max(b_scale);
+    let aligned = |m: i128, s: u32| m.checked_mul(10i128.checked_pow(scale - s)?);
+    let result = match op {
+        ArithmeticOp::Add => (
+            aligned(a, a_scale)?.checked_add(aligned(b, b_scale)?)?,
+            scale,
+        ),
+        ArithmeticOp::Subtract => (
+            aligned(a, a_scale)?.checked_sub(aligned(b, b_scale)?)?,
+            scale,
+        ),
+        ArithmeticOp::Multiply => (a.checked_mul(b)?, a_scale + b_scale),
+        ArithmeticOp::Modulo => (
+            aligned(a, a_scale)?.checked_rem(aligned(b, b_scale)?)?,
+            scale,
+        ),
+        ArithmeticOp::Divide => {
+            // a / b at `scale` digits is a × 10^(b_scale + scale) / (b × 10^a_scale)
+            let scale = scale.clamp(9, 38);
+            let numerator = a.checked_mul(10i128.checked_pow(b_scale + scale)?)?;
+            let denominator = b.checked_mul(10i128.checked_pow(a_scale)?)?;
+            let quotient = numerator.checked_div(denominator)?;
+            let remainder = numerator % denominator;
+            if 2 * remainder.unsigned_abs() >= denominator.unsigned_abs() {
+                let away = if (numerator < 0) == (denominator < 0) {
+                    1
+                } else {
+                    -1
+                };
+                (quotient + away, scale)
+            } else {
+                (quotient, scale)
+            }
+        }
+    };
+    literal::Numeric::from_i128(result.0, result.1).round(38)
+}
+
+fn op_symbol(op: ArithmeticOp) -> &'static str {
+    match op {
+        ArithmeticOp::Add => "+",
+        ArithmeticOp::Subtract => "-",
+        ArithmeticOp::Multiply => "*",
+        ArithmeticOp::Divide => "/",
+        ArithmeticOp::Modulo => "%",
+    }
+}
+
+fn not_a_number(value: &Value) -> Error {
+    Error::new(ErrorKind::TypeMismatch {
+        expected: "a number".to_string(),
+        found: value.type_name().to_string(),
+    })
+}
+
+/// Whether `name` can be written without quotes.
+pub(crate) fn is_plain_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !matches!(Keyword::parse(name), Some(k) if k.is_reserved())
+}
+
 impl From<bool> for Value {
     fn from(v: bool) -> Self {
         Value::Boolean(v)
     }
 }
 
+impl From<i32> for Value {
+    fn from(v: i32) -> Self {
+        Value::Int64(v.into())
+    }
+}
+
 impl From<i64> for Value {
     fn from(v: i64) -> Self {
         Value::Int64(v)
@@ -91,9 +934,34 @@ impl From<Vec<u8>> for Value {
     }
 }
 
+impl From<Numeric> for Value {
+    fn from(v: Numeric) -> Self {
+        Value::Numeric(v)
+    }
+}
+
+impl From<Date> for Value {
+    fn from(v: Date) -> Self {
+        Value::Date(v.days_since_epoch())
+    }
+}
+
+impl From<Time> for Value {
+    fn from(v: Time) -> Self {
+        Value::Time(v.micros_since_midnight())
+    }
+}
+
+impl From<Datetime> for Value {
+    fn from(v: Datetime) -> Self {
+        Value::Datetime(v.micros_since_epoch())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Parser;
 
     #[test]
     fn test_value_from_bool() {
@@ -109,6 +977,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)] // 3.14 is a value, not PI
     fn test_value_from_f64() {
         assert_eq!(Value::from(3.14f64), Value::Float64(3.14));
         assert_eq!(Value::from(-0.5f64), Value::Float64(-0.5));
@@ -135,6 +1004,18 @@ mod tests {
         assert_eq!(Value::from(Vec::<u8>::new()), Value::Bytes(vec![]));
     }
 
+    #[test]
+    fn test_value_from_literals() {
+        let date = Date::parse("1970-01-11").unwrap();
+        assert_eq!(Value::from(date), Value::Date(10));
+        let time = Time::parse("00:00:01.000002").unwrap();
+        assert_eq!(Value::from(time), Value::Time(1_000_002));
+        let datetime = Datetime::parse("1970-01-01 00:01:00").unwrap();
+        assert_eq!(Value::from(datetime), Value::Datetime(60_000_000));
+        let numeric = Numeric::parse("1.50").unwrap();
+        assert_eq!(Value::from(numeric).type_name(), "NUMERIC");
+    }
+
     #[test]
     fn test_value_is_null() {
         assert!(Value::Null.is_null());
@@ -144,6 +1025,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_value_type_name() {
         assert_eq!(Value::Null.type_name(), "NULL");
         assert_eq!(Value::Boolean(true).type_name(), "BOOLEAN");
@@ -169,6 +1051,291 @@ mod tests {
         assert_eq!(Value::Json("{}".to_string()).type_name(), "JSON");
     }
 
+    #[test]
+    fn test_value_partial_cmp() {
+        let numeric = |s: &str| Value::Numeric(Numeric::parse(s).unwrap());
+        let cases = [
+            (Value::Int64(1), Value::Float64(1.0), Some(Ordering::Equal)),
+            (Value::Int64(1), numeric("1.5"), Some(Ordering::Less)),
+            (numeric("-0.5"), Value::Float64(-0.25), Some(Ordering::Less)),
+            (numeric("10"), numeric("9.99"), Some(Ordering::Greater)),
+            (numeric("0.05"), numeric("0.5"), Some(Ordering::Less)),
+            // Exact beyond the 53 bits of a float
+            (
+                Value::Int64(i64::MAX),
+                Value::Float64(9_223_372_036_854_775_807.0),
+                Some(Ordering::Less),
+            ),
+            (Value::Int64(-2), Value::Float64(-1.5), Some(Ordering::Less)),
+            (Value::Float64(f64::NAN), Value::Int64(0), None),
+            (Value::from("B"), Value::from("a"), Some(Ordering::Less)),
+            (Value::from("é"), Value::from("z"), Some(Ordering::Greater)),
+            (Value::Null, Value::Null, None),
+            (Value::Int64(1), Value::from("1"), None),
+            (
+                Value::Date(1),
+                Value::Datetime(86_400_000_000),
+                Some(Ordering::Equal),
+            ),
+            (Value::Timestamp(1), Value::Date(0), Some(Ordering::Greater)),
+            (
+                Value::Array(vec![Value::Int64(1), Value::Int64(2)]),
+                Value::Array(vec![Value::Int64(1)]),
+                Some(Ordering::Greater),
+            ),
+            (
+                Value::Array(vec![Value::Null]),
+                Value::Array(vec![Value::Int64(1)]),
+                None,
+            ),
+        ];
+        for (left, right, expected) in cases {
+            assert_eq!(left.partial_cmp(&right), expected, "{} vs {}", left, right);
+        }
+    }
+
+    #[test]
+    fn test_value_arithmetic() {
+        let numeric = |s: &str| Value::Numeric(Numeric::parse(s).unwrap());
+        assert_eq!(
+            Value::Int64(2).add(&Value::Int64(3)).unwrap(),
+            Value::Int64(5)
+        );
+        assert_eq!(
+            Value::Int64(7).div(&Value::Int64(-2)).unwrap(),
+            Value::Int64(-3)
+        );
+        assert_eq!(
+            Value::Int64(-7).rem(&Value::Int64(2)).unwrap(),
+            Value::Int64(-1)
+        );
+        assert_eq!(
+            Value::Int64(1).add(&Value::Float64(0.5)).unwrap(),
+            Value::Float64(1.5)
+        );
+        assert_eq!(
+            numeric("1.25").add(&Value::Int64(1)).unwrap(),
+            numeric("2.25")
+        );
+        assert_eq!(
+            numeric("0.1").mul(&numeric("0.2")).unwrap(),
+            numeric("0.02")
+        );
+        assert_eq!(
+            numeric("2").div(&numeric("3")).unwrap(),
+            numeric("0.666666667")
+        );
+        assert_eq!(
+            numeric("-2").div(&numeric("3")).unwrap(),
+            numeric("-0.666666667")
+        );
+        assert_eq!(
+            numeric("5.5").rem(&Value::Int64(2)).unwrap(),
+            numeric("1.5")
+        );
+        assert_eq!(Value::Null.sub(&Value::Int64(1)).unwrap(), Value::Null);
+
+        let err = Value::Int64(i64::MAX).add(&Value::Int64(1)).unwrap_err();
+        assert_eq!(err.to_string(), "numeric overflow: 9223372036854775807 + 1");
+        let err = Value::Int64(i64::MIN).div(&Value::Int64(-1)).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::NumericOverflow(_)));
+        let err = Value::Float64(1e308).mul(&Value::Int64(10)).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::NumericOverflow(_)));
+        for divisor in [Value::Int64(0), Value::Float64(-0.0), numeric("0")] {
+            let err = Value::Int64(1).div(&divisor).unwrap_err();
+            assert!(matches!(err.kind, ErrorKind::DivisionByZero), "{}", divisor);
+        }
+        let err = Value::Boolean(true).add(&Value::Int64(1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "type mismatch: expected a number, found BOOLEAN"
+        );
+    }
+
+    #[test]
+    fn test_value_cast_to() {
+        let numeric_type = |precision, scale| SqlType::Numeric {
+            precision: Some(precision),
+            scale: Some(scale),
+        };
+        let ok = [
+            (Value::from(" 42 "), SqlType::Int64, Value::Int64(42)),
+            (Value::Float64(2.5), SqlType::Int64, Value::Int64(3)),
+            (Value::Float64(-2.5), SqlType::Int32, Value::Int64(-3)),
+            (Value::Int64(7), SqlType::Varchar(None), Value::from("7")),
+            (Value::Boolean(true), SqlType::Int64, Value::Int64(1)),
+            (Value::from("TRUE"), SqlType::Bool, Value::Boolean(true)),
+            (
+                Value::Float64(1.005),
+                numeric_type(5, 2),
+                Value::Numeric(Numeric::parse("1.01").unwrap()),
+            ),
+            (
+                Value::from("2024-02-29"),
+                SqlType::Date,
+                Value::from(Date::parse("2024-02-29").unwrap()),
+            ),
+            (
+                Value::from("1970-01-01 01:00:00+01"),
+                SqlType::Timestamp,
+                Value::Timestamp(0),
+            ),
+            (Value::Timestamp(-1), SqlType::Date, Value::Date(-1)),
+            (
+                Value::Timestamp(86_400_000_000),
+                SqlType::Varchar(None),
+                Value::from("1970-01-02 00:00:00+00"),
+            ),
+            (
+                Value::from("[1]"),
+                SqlType::Json,
+                Value::Json("[1]".to_string()),
+            ),
+            (
+                Value::Array(vec![Value::from("1"), Value::Null]),
+                SqlType::Array(Box::new(SqlType::Int64)),
+                Value::Array(vec![Value::Int64(1), Value::Null]),
+            ),
+            (Value::Null, SqlType::Date, Value::Null),
+        ];
+        for (value, target, expected) in ok {
+            assert_eq!(
+                value.cast_to(&target).unwrap(),
+                expected,
+                "{} AS {}",
+                value,
+                target
+            );
+        }
+
+        let errors = [
+            (
+                Value::from("abc"),
+                SqlType::Int64,
+                "cannot cast 'abc' to BIGINT: not an integer",
+            ),
+            (
+                Value::Int64(1 << 31),
+                SqlType::Int32,
+                "cannot cast 2147483648 to INTEGER: out of range",
+            ),
+            (
+                Value::Int64(-1),
+                SqlType::Uint64,
+                "cannot cast -1 to UBIGINT: out of range",
+            ),
+            (
+                Value::Float64(123.4),
+                numeric_type(3, 1),
+                "cannot cast 123.4 to NUMERIC(3, 1): out of range",
+            ),
+            (
+                Value::from("2023-02-29"),
+                SqlType::Date,
+                "cannot cast '2023-02-29' to DATE: day 29 is out of range for 2023-02",
+            ),
+            (
+                Value::from("abc"),
+                SqlType::Varchar(Some(2)),
+                "cannot cast 'abc' to VARCHAR(2): longer than 2 characters",
+            ),
+            (
+                Value::Date(0),
+                SqlType::Int64,
+                "cannot cast DATE '1970-01-01' to BIGINT: DATE does not convert to BIGINT",
+            ),
+        ];
+        for (value, target, message) in errors {
+            assert_eq!(value.cast_to(&target).unwrap_err().to_string(), message);
+        }
+    }
+
+    #[test]
+    fn test_value_display() {
+        let cases = [
+            (Value::Null, "NULL"),
+            (Value::Int64(-3), "-3"),
+            (Value::Int64(i64::MIN), "(-9223372036854775807 - 1)"),
+            (Value::Float64(1.0), "1.0"),
+            (Value::Float64(f64::NEG_INFINITY), "CAST('-inf' AS FLOAT64)"),
+            (Value::from("it's\n"), "'it\\'s\\n'"),
+            (Value::from(vec![b'a', 0, b'\'']), "b'a\\x00\\''"),
+            (
+                Value::Numeric(Numeric::parse("-0.50").unwrap()),
+                "NUMERIC '-0.5'",
+            ),
+            (Value::Date(0), "DATE '1970-01-01'"),
+            (Value::Date(-800_000), "DATE_FROM_UNIX_DATE(-800000)"),
+            (Value::Time(1_500_000), "TIME '00:00:01.500000'"),
+            (Value::Time(-1), "TIME(TIMESTAMP_MICROS(-1))"),
+            (Value::Datetime(0), "DATETIME '1970-01-01 00:00:00'"),
+            (
+                Value::Datetime(i64::MIN),
+                "DATETIME(TIMESTAMP_MICROS((-9223372036854775807 - 1)))",
+            ),
+            (Value::Timestamp(0), "TIMESTAMP '1970-01-01 00:00:00+00'"),
+            (
+                Value::Interval(Interval {
+                    months: 24,
+                    days: -1,
+                    micros: 1_500,
+                }),
+                "INTERVAL 2 YEAR + INTERVAL -1 DAY + INTERVAL 1500 MICROSECOND",
+            ),
+            (
+                Value::Interval(Interval {
+                    months: 0,
+                    days: 0,
+                    micros: i64::MIN,
+                }),
+                "INTERVAL (-9223372036854775807 - 1) MICROSECOND",
+            ),
+            (
+                Value::Array(vec![Value::Int64(1), Value::Null]),
+                "[1, NULL]",
+            ),
+            (
+                Value::Struct(vec![
+                    ("a".to_string(), Value::Int64(1)),
+                    (String::new(), Value::Boolean(true)),
+                    ("select".to_string(), Value::from("x")),
+                ]),
+                "STRUCT(1 AS a, TRUE, 'x' AS `select`)",
+            ),
+            (Value::Json("{\"a\": 1}".to_string()), "JSON '{\"a\": 1}'"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(value.to_string(), expected);
+        }
+
+        let interval = Interval {
+            months: -14,
+            days: 3,
+            micros: -3_661_000_001,
+        };
+        assert_eq!(interval.to_string(), "-1-2 3 -1:01:01.000001");
+    }
+
+    #[test]
+    fn test_value_from_literal() {
+        let parse = |sql: &str| Parser::new(sql).parse_expression().unwrap().kind.clone();
+        assert_eq!(Value::from_literal(&parse("'a'")), Some(Value::from("a")));
+        assert_eq!(
+            Value::from_literal(&parse("DATE '1970-01-02'")),
+            Some(Value::Date(1))
+        );
+        assert_eq!(
+            Value::from_literal(&parse("INTERVAL -2 WEEK")),
+            Some(Value::Interval(Interval {
+                months: 0,
+                days: -14,
+                micros: 0
+            }))
+        );
+        assert_eq!(Value::from_literal(&parse("DATE '1970-13-01'")), None);
+        assert_eq!(Value::from_literal(&parse("1 + 1")), None);
+    }
+
     #[test]
     fn test_value_default() {
         assert_eq!(Value::default(), Value::Null);
@@ -0,0 +1,461 @@
+//! Binary encoding of statements, for caching parsed queries.
+//!
+//! [`Statement::encode`] writes a compact encoding of a statement and
+//! everything in it, spans and node IDs included, and
+//! [`Statement::decode`] reads it back into an equal statement. Floats
+//! keep their exact bits.
+//!
+//! ```
+//! use vibesql::{Parser, Statement};
+//!
+//! let stmt = Parser::new("SELECT a FROM t WHERE b > 1.5").parse_statement().unwrap();
+//! let mut bytes = Vec::new();
+//! stmt.encode(&mut bytes);
+//! assert_eq!(Statement::decode(&bytes).unwrap(), stmt);
+//! ```
+//!
+//! # Format
+//!
+//! An encoding starts with the bytes `VSQL` and a [`FORMAT_VERSION`] byte.
+//! The version changes whenever the AST does, and decoding an encoding of
+//! another version is an error, so a cache shared by several versions of
+//! this crate never yields a wrong tree.
+//!
+//! After the header, fields follow in declaration order with no padding.
+//! Unsigned integers are LEB128 varints and signed integers are zigzag
+//! varints; a `u8` is a single byte and an `f64` its eight bits, little
+//! endian. A string or `Vec` is its length followed by its contents, an
+//! `Option` a 0 or 1 byte before any value, and an enum the index of its
+//! variant before the variant's fields.
+//!
+//! Decoding checks every length against the input left, so truncated or
+//! corrupted input is an [`ErrorKind::InvalidEncoding`] error rather than a
+//! panic or a huge allocation.
+
+mod ast;
+
+use crate::ast::{NodeId, Statement};
+use crate::error::{Error, ErrorKind, Result, Span};
+
+/// Version of the encoding; see the [module docs](self).
+pub const FORMAT_VERSION: u8 = 7;
+
+/// The bytes every encoding starts with, before the version.
+const MAGIC: &[u8; 4] = b"VSQL";
+
+impl Statement {
+    /// Append the binary encoding of this statement to `out`.
+    ///
+    /// See the [`wire`](crate::wire) module for the format.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT_VERSION);
+        self.write(out);
+    }
+
+    /// Decode a statement written by [`encode`](Self::encode).
+    ///
+    /// An encoding from a different [`FORMAT_VERSION`], or input that is
+    /// not exactly one encoding, is an [`ErrorKind::InvalidEncoding`] error.
+    pub fn decode(bytes: &[u8]) -> Result<Statement> {
+        let mut input = Reader { bytes, pos: 0 };
+        if input.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("not an encoded statement"));
+        }
+        let version = input.byte()?;
+        if version != FORMAT_VERSION {
+            return Err(invalid(format!(
+                "format version {}, expected {}",
+                version, FORMAT_VERSION
+            )));
+        }
+        let stmt = Statement::read(&mut input)?;
+        if input.pos != bytes.len() {
+            return Err(invalid("trailing bytes after the statement"));
+        }
+        Ok(stmt)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidEncoding(message.into()))
+}
+
+/// A value with a binary encoding.
+trait Wire: Sized {
+    /// Append the encoding of `self` to `out`.
+    fn write(&self, out: &mut Vec<u8>);
+
+    /// Read a value written by [`write`](Self::write).
+    fn read(input: &mut Reader<'_>) -> Result<Self>;
+}
+
+/// Encoded input being decoded.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.remaining() {
+            return Err(invalid("unexpected end of input"));
+        }
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint too long"))
+    }
+
+    /// A length or count, which cannot exceed the input left since every
+    /// element takes at least one byte.
+    fn len(&mut self) -> Result<usize> {
+        let len = self.varint()?;
+        usize::try_from(len)
+            .ok()
+            .filter(|&len| len <= self.remaining())
+            .ok_or_else(|| invalid("length past the end of input"))
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Implement [`Wire`] for structs, as their fields in order.
+macro_rules! wire_struct {
+    ($($ty:ty { $($field:ident),* $(,)? })*) => {$(
+        impl Wire for $ty {
+            fn write(&self, out: &mut Vec<u8>) {
+                let Self { $($field),* } = self;
+                $($field.write(out);)*
+            }
+
+            fn read(input: &mut Reader<'_>) -> Result<Self> {
+                Ok(Self { $($field: Wire::read(input)?),* })
+            }
+        }
+    )*};
+}
+
+/// Implement [`Wire`] for enums, as the variant index and then its fields.
+macro_rules! wire_enum {
+    ($($ty:ty {
+        $($variant:ident $(( $($value:ident),+ ))? $({ $($field:ident),* })?),* $(,)?
+    })*) => {$(
+        impl Wire for $ty {
+            fn write(&self, out: &mut Vec<u8>) {
+                enum Tag { $($variant),* }
+                match self {
+                    $(Self::$variant $(( $($value),+ ))? $({ $($field),* })? => {
+                        write_varint(out, Tag::$variant as u64);
+                        $($($value.write(out);)+)?
+                        $($($field.write(out);)*)?
+                    })*
+                }
+            }
+
+            fn read(input: &mut Reader<'_>) -> Result<Self> {
+                enum Tag { $($variant),* }
+                let tag = input.varint()?;
+                $(if tag == Tag::$variant as u64 {
+                    return Ok(Self::$variant
+                        $(( $(wire_enum!(@read input, $value)),+ ))?
+                        $({ $($field: Wire::read(input)?),* })?);
+                })*
+                Err(invalid(format!(
+                    "unknown {} variant {}",
+                    stringify!($ty),
+                    tag
+                )))
+            }
+        }
+    )*};
+    // Reads one tuple field; the name only drives the repetition.
+    (@read $input:ident, $value:ident) => {
+        Wire::read($input)?
+    };
+}
+
+use wire_enum;
+use wire_struct;
+
+impl Wire for u8 {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        input.byte()
+    }
+}
+
+impl Wire for bool {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        match input.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid("invalid boolean")),
+        }
+    }
+}
+
+macro_rules! wire_unsigned {
+    ($($ty:ty),*) => {$(
+        impl Wire for $ty {
+            fn write(&self, out: &mut Vec<u8>) {
+                write_varint(out, *self as u64);
+            }
+
+            fn read(input: &mut Reader<'_>) -> Result<Self> {
+                <$ty>::try_from(input.varint()?)
+                    .map_err(|_| invalid(concat!(stringify!($ty), " out of range")))
+            }
+        }
+    )*};
+}
+
+wire_unsigned!(u32, u64, usize);
+
+impl Wire for i64 {
+    fn write(&self, out: &mut Vec<u8>) {
+        write_varint(out, ((self << 1) ^ (self >> 63)) as u64);
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        let zigzag = input.varint()?;
+        Ok((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64))
+    }
+}
+
+impl Wire for f64 {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_bits().to_le_bytes());
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        let bytes = input.take(8)?.try_into().expect("eight bytes");
+        Ok(f64::from_bits(u64::from_le_bytes(bytes)))
+    }
+}
+
+impl Wire for String {
+    fn write(&self, out: &mut Vec<u8>) {
+        write_varint(out, self.len() as u64);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        let len = input.len()?;
+        let bytes = input.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("invalid UTF-8 in a string"))
+    }
+}
+
+impl<T: Wire> Wire for Box<T> {
+    fn write(&self, out: &mut Vec<u8>) {
+        (**self).write(out);
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        T::read(input).map(Box::new)
+    }
+}
+
+impl<T: Wire> Wire for Option<T> {
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(value) => {
+                out.push(1);
+                value.write(out);
+            }
+        }
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        match input.byte()? {
+            0 => Ok(None),
+            1 => T::read(input).map(Some),
+            _ => Err(invalid("invalid option tag")),
+        }
+    }
+}
+
+impl<T: Wire> Wire for Vec<T> {
+    fn write(&self, out: &mut Vec<u8>) {
+        write_varint(out, self.len() as u64);
+        for value in self {
+            value.write(out);
+        }
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        let len = input.len()?;
+        let mut values = Vec::with_capacity(len);
+        for _ in 0..len {
+            values.push(T::read(input)?);
+        }
+        Ok(values)
+    }
+}
+
+impl<A: Wire, B: Wire> Wire for (A, B) {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.0.write(out);
+        self.1.write(out);
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        Ok((A::read(input)?, B::read(input)?))
+    }
+}
+
+impl Wire for Span {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.start.write(out);
+        self.end.write(out);
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        Ok(Span::new(usize::read(input)?, usize::read(input)?))
+    }
+}
+
+impl Wire for NodeId {
+    fn write(&self, out: &mut Vec<u8>) {
+        self.0.write(out);
+    }
+
+    fn read(input: &mut Reader<'_>) -> Result<Self> {
+        u32::read(input).map(NodeId)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{Expr, ExprKind, QueryBody, SelectItem, StatementKind, CORPUS};
+    use crate::Parser;
+
+    fn encode(stmt: &Statement) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        stmt.encode(&mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn test_corpus_round_trip() {
+        for sql in CORPUS {
+            let statements = Parser::new(sql).parse().unwrap();
+            for stmt in statements {
+                let bytes = encode(&stmt);
+                assert_eq!(Statement::decode(&bytes).unwrap(), stmt, "{}", sql);
+
+                // Cut-off and corrupted encodings are errors, not panics.
+                for len in 0..bytes.len() {
+                    assert!(Statement::decode(&bytes[..len]).is_err(), "{}", sql);
+                }
+                for i in MAGIC.len() + 1..bytes.len() {
+                    let mut corrupted = bytes.clone();
+                    corrupted[i] ^= 0x55;
+                    let _ = Statement::decode(&corrupted);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_literals_round_trip() {
+        let mut stmt = Parser::new("SELECT b'\\x00\\xff', 1.5, -9223372036854775807")
+            .parse_statement()
+            .unwrap();
+        // Floats keep their bits, NaN payload and sign of zero included.
+        let StatementKind::Query(query) = &mut stmt.kind else {
+            panic!("expected a query");
+        };
+        let QueryBody::Select(select) = &mut query.body else {
+            panic!("expected a SELECT");
+        };
+        let nan = f64::from_bits(0x7ff8_0000_dead_beef);
+        for value in [nan, -0.0] {
+            select.projection.push(SelectItem::Expr {
+                expr: Expr::boxed(ExprKind::Float(value), Span::new(0, 0)),
+                alias: None,
+            });
+        }
+
+        let decoded = Statement::decode(&encode(&stmt)).unwrap();
+        assert_eq!(decoded.to_string(), stmt.to_string());
+        let StatementKind::Query(query) = &decoded.kind else {
+            panic!("expected a query");
+        };
+        let QueryBody::Select(select) = &query.body else {
+            panic!("expected a SELECT");
+        };
+        let floats: Vec<u64> = select
+            .projection
+            .iter()
+            .filter_map(|item| match item {
+                SelectItem::Expr { expr, .. } => match expr.kind {
+                    ExprKind::Float(value) => Some(value.to_bits()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            floats,
+            [1.5f64.to_bits(), nan.to_bits(), (-0.0f64).to_bits()]
+        );
+    }
+
+    #[test]
+    fn test_long_chain_round_trip() {
+        let sql = format!("SELECT {}", vec!["c"; 10_000].join(" - "));
+        let stmt = Parser::new(&sql).parse_statement().unwrap();
+        assert_eq!(Statement::decode(&encode(&stmt)).unwrap(), stmt);
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let stmt = Parser::new("SELECT 1").parse_statement().unwrap();
+        let mut bytes = encode(&stmt);
+        bytes[MAGIC.len()] = FORMAT_VERSION + 1;
+        let err = Statement::decode(&bytes).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::InvalidEncoding(_)));
+        assert!(err.to_string().contains("format version"), "{}", err);
+
+        assert!(Statement::decode(b"SELECT 1").is_err());
+        let mut bytes = encode(&stmt);
+        bytes.push(0);
+        assert!(Statement::decode(&bytes).is_err());
+    }
+}
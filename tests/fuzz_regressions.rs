@@ -0,0 +1,100 @@
+//! Inputs that once made the parser panic.
+//!
+//! Each is a reduced crasher from fuzzing with `fuzz/`. They must now fail
+//! with an error whose span can be used to slice and render the source.
+
+use vibesql::error::{Error, ErrorKind};
+use vibesql::Parser;
+
+/// Parse `sql`, expecting an error, and return it with the text it spans.
+fn parse_error(sql: &str) -> (Error, &str) {
+    let err = Parser::new(sql).parse().unwrap_err();
+    let span = err.span().expect("error has a span");
+    let _ = err.display_with_source(sql);
+    (err, &sql[span.start..span.end])
+}
+
+#[test]
+fn test_escape_of_multibyte_character() {
+    // The span of an invalid escape used to start inside the character.
+    for sql in ["SELECT 'ab\\日本'", "SELECT b'\\😀'", "SELECT `a\\ß`"] {
+        let (err, text) = parse_error(sql);
+        assert!(
+            matches!(err.kind(), ErrorKind::InvalidEscapeSequence(_)),
+            "{}: {:?}",
+            sql,
+            err
+        );
+        assert!(text.starts_with('\\'), "{}: {:?}", sql, text);
+    }
+
+    let (_, text) = parse_error("SELECT '\\x4日'");
+    assert_eq!(text, "\\x4");
+    let (_, text) = parse_error("SELECT '\\u00é9'");
+    assert_eq!(text, "\\u00");
+}
+
+#[test]
+fn test_non_ascii_in_bytes_literal() {
+    // Characters used to be truncated to their low byte.
+    for (sql, expected) in [
+        ("SELECT b'é'", "é"),
+        ("SELECT B\"\"\"ok\n日本\"\"\"", "日"),
+        ("SELECT rb'\\😀'", "😀"),
+    ] {
+        let (err, text) = parse_error(sql);
+        assert_eq!(*err.kind(), ErrorKind::InvalidBytesLiteral, "{}", sql);
+        assert_eq!(text, expected);
+    }
+    let stmts = Parser::new("SELECT b'\\xc3\\xa9', b'''a'b'''")
+        .parse()
+        .unwrap();
+    assert_eq!(stmts[0].to_string(), "SELECT b'\\xc3\\xa9', b'a\\'b'");
+}
+
+#[test]
+fn test_octal_escape_out_of_byte_range() {
+    // `\777` overflowed a `u8` while being decoded.
+    let (err, text) = parse_error("SELECT b'\\777'");
+    assert!(matches!(err.kind(), ErrorKind::InvalidEscapeSequence(_)));
+    assert_eq!(text, "\\777");
+    let stmts = Parser::new("SELECT b'\\377'").parse().unwrap();
+    assert_eq!(stmts[0].to_string(), "SELECT b'\\xff'");
+}
+
+#[test]
+fn test_deeply_nested_types() {
+    // Type parameters recursed without the nesting limit and overflowed
+    // the stack.
+    let n = 100_000;
+    for (open, close) in [("ARRAY<", ">"), ("STRUCT<a ", ">"), ("RANGE<", ">")] {
+        let sql = format!(
+            "SELECT CAST(1 AS {}INT64{})",
+            open.repeat(n),
+            close.repeat(n)
+        );
+        let (err, _) = parse_error(&sql);
+        assert_eq!(
+            *err.kind(),
+            ErrorKind::InvalidSyntax("expression nesting too deep".into()),
+            "{}",
+            open
+        );
+    }
+    let sql = format!(
+        "SELECT CAST(1 AS {}INT64{})",
+        "ARRAY<".repeat(20),
+        ">".repeat(20)
+    );
+    assert!(Parser::new(&sql).parse().is_ok());
+}
+
+#[test]
+fn test_long_operator_chain_displays() {
+    // Printing recursed once per operator of a chain, which the parser
+    // builds in a loop, and overflowed the stack.
+    let terms: Vec<String> = (0..10_000).map(|i| format!("c{}", i)).collect();
+    let sql = format!("SELECT {}", terms.join(" + "));
+    let stmts = Parser::new(&sql).parse().unwrap();
+    assert_eq!(stmts[0].to_string(), sql);
+}